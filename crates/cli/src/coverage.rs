@@ -0,0 +1,216 @@
+//! `roc test --coverage` counts how often each `when` branch in the main module's package runs
+//! while the tests do, and writes the counts to an [lcov](https://github.com/linux-test-project/lcov)
+//! tracefile that editors and coverage services can display.
+//!
+//! Only the tests that run in the test runner's own process are counted. Tests with effects run
+//! in a child process, so the branches they take don't show up.
+
+use roc_collections::{MutMap, VecMap};
+use roc_load::Expectations;
+use roc_module::symbol::ModuleId;
+use roc_mono::coverage::BranchCounters;
+use roc_region::all::{LineInfo, Region};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The number of counters the tests count in, across all modules.
+pub fn counter_count(expectations: &VecMap<ModuleId, Expectations>) -> usize {
+    expectations
+        .values()
+        .filter_map(|expectations| expectations.branch_counters.as_ref())
+        .map(|counters| counters.end() as usize)
+        .max()
+        .unwrap_or(0)
+}
+
+pub struct CoverageSummary {
+    pub branches: usize,
+    pub branches_hit: usize,
+}
+
+/// Writes a record for every module with branches, in the order of their paths.
+pub fn write_lcov<W: Write>(
+    writer: &mut W,
+    expectations: &VecMap<ModuleId, Expectations>,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    counters: &[u64],
+) -> io::Result<CoverageSummary> {
+    let mut summary = CoverageSummary {
+        branches: 0,
+        branches_hit: 0,
+    };
+
+    let mut modules: Vec<_> = expectations
+        .iter()
+        .filter_map(|(module_id, expectations)| {
+            let branch_counters = expectations.branch_counters.as_ref()?;
+            let (path, src) = sources.get(module_id)?;
+
+            Some((path, src, branch_counters))
+        })
+        .filter(|(_, _, branch_counters)| !branch_counters.branches.is_empty())
+        .collect();
+
+    modules.sort_by_key(|(path, _, _)| *path);
+
+    for (path, src, branch_counters) in modules {
+        let module_summary = write_record(writer, path, src, branch_counters, counters)?;
+
+        summary.branches += module_summary.branches;
+        summary.branches_hit += module_summary.branches_hit;
+    }
+
+    Ok(summary)
+}
+
+/// Writes the lcov record of one module.
+fn write_record<W: Write>(
+    writer: &mut W,
+    path: &Path,
+    src: &str,
+    branch_counters: &BranchCounters,
+    counters: &[u64],
+) -> io::Result<CoverageSummary> {
+    let line_info = LineInfo::new(src);
+    let line_of = |region: Region| line_info.convert_pos(region.start()).line + 1;
+
+    // lcov calls a `when` a block. Number them in source order.
+    let mut blocks: BTreeMap<Region, Vec<u64>> = BTreeMap::new();
+    let mut lines: BTreeMap<u32, u64> = BTreeMap::new();
+
+    for (branch, hits) in branch_counters.hits(counters) {
+        blocks.entry(branch.when_region).or_default().push(hits);
+        *lines.entry(line_of(branch.region)).or_default() += hits;
+    }
+
+    writeln!(writer, "TN:")?;
+    writeln!(writer, "SF:{}", path.display())?;
+
+    let mut branches = 0;
+    let mut branches_hit = 0;
+
+    for (block, (when_region, hits)) in blocks.iter().enumerate() {
+        // A `when` that never ran has no branches that could have been taken.
+        let when_ran = hits.iter().any(|hits| *hits > 0);
+
+        for (branch, hits) in hits.iter().enumerate() {
+            let taken = if when_ran {
+                hits.to_string()
+            } else {
+                "-".to_string()
+            };
+
+            writeln!(
+                writer,
+                "BRDA:{},{block},{branch},{taken}",
+                line_of(*when_region)
+            )?;
+
+            branches += 1;
+            branches_hit += (*hits > 0) as usize;
+        }
+    }
+
+    writeln!(writer, "BRF:{branches}")?;
+    writeln!(writer, "BRH:{branches_hit}")?;
+
+    for (line, hits) in lines.iter() {
+        writeln!(writer, "DA:{line},{hits}")?;
+    }
+
+    writeln!(writer, "LF:{}", lines.len())?;
+    writeln!(
+        writer,
+        "LH:{}",
+        lines.values().filter(|hits| **hits > 0).count()
+    )?;
+    writeln!(writer, "end_of_record")?;
+
+    Ok(CoverageSummary {
+        branches,
+        branches_hit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use roc_mono::coverage::CoveredBranch;
+    use roc_region::all::Position;
+
+    #[test]
+    fn lcov_record() {
+        let src = indoc!(
+            r#"
+            when x is
+                A -> 1
+                B -> 2
+            when y is
+                C -> 3
+            "#
+        );
+        let region_of = |snippet: &str| {
+            let start = src.find(snippet).unwrap() as u32;
+
+            Region::new(
+                Position::new(start),
+                Position::new(start + snippet.len() as u32),
+            )
+        };
+        let first_when = region_of("when x is\n    A -> 1\n    B -> 2");
+        let second_when = region_of("when y is\n    C -> 3");
+        let branch_counters = BranchCounters {
+            first_counter: 0,
+            branches: vec![
+                CoveredBranch {
+                    when_region: first_when,
+                    region: region_of("1"),
+                },
+                CoveredBranch {
+                    when_region: first_when,
+                    region: region_of("2"),
+                },
+                CoveredBranch {
+                    when_region: second_when,
+                    region: region_of("3"),
+                },
+            ],
+        };
+
+        let mut lcov = Vec::new();
+        let summary = write_record(
+            &mut lcov,
+            Path::new("Main.roc"),
+            src,
+            &branch_counters,
+            &[2, 0, 0],
+        )
+        .unwrap();
+
+        // The second `when` never ran, so its branch is `-` rather than not taken.
+        assert_eq!(
+            String::from_utf8(lcov).unwrap(),
+            indoc!(
+                r#"
+                TN:
+                SF:Main.roc
+                BRDA:1,0,0,2
+                BRDA:1,0,1,0
+                BRDA:4,1,0,-
+                BRF:3
+                BRH:1
+                DA:2,2
+                DA:3,0
+                DA:5,0
+                LF:3
+                LH:1
+                end_of_record
+                "#
+            )
+        );
+        assert_eq!((summary.branches, summary.branches_hit), (3, 1));
+    }
+}
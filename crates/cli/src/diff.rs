@@ -0,0 +1,363 @@
+//! A small line-based diff, used to show what `roc format --diff` would change.
+
+/// How many unchanged lines are shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Change {
+    Same,
+    Removed,
+    Added,
+}
+
+/// One line of the diff, with the (0-based) positions it's at in the old and new text.
+/// A removed line has the position of the next new line, and vice versa.
+#[derive(Clone, Copy, Debug)]
+struct DiffLine<'a> {
+    change: Change,
+    old_index: usize,
+    new_index: usize,
+    text: &'a str,
+}
+
+/// Renders the changes from `old` to `new` as a unified diff, with `name` as the label of
+/// both sides. Returns an empty string if there are no changes.
+pub fn unified_diff(name: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let lines = diff_lines(&old_lines, &new_lines);
+
+    let mut out = String::new();
+
+    for (start, end) in hunks(&lines) {
+        if out.is_empty() {
+            out.push_str(&format!("--- {name}\n+++ {name}\n"));
+        }
+
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|l| l.change != Change::Added).count();
+        let new_len = hunk.iter().filter(|l| l.change != Change::Removed).count();
+
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(hunk[0].old_index, old_len),
+            hunk_range(hunk[0].new_index, new_len)
+        ));
+
+        for line in hunk {
+            out.push(match line.change {
+                Change::Same => ' ',
+                Change::Removed => '-',
+                Change::Added => '+',
+            });
+            out.push_str(line.text);
+
+            if !line.text.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    out
+}
+
+/// The `start,len` part of a hunk header. An empty range starts at the line before it.
+fn hunk_range(index: usize, len: usize) -> String {
+    match len {
+        0 => format!("{index},0"),
+        1 => format!("{}", index + 1),
+        _ => format!("{},{len}", index + 1),
+    }
+}
+
+/// The ranges of `lines` to show as hunks: every change, with some context around it.
+/// Changes whose context would overlap go in the same hunk.
+fn hunks(lines: &[DiffLine]) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.change == Change::Same {
+            continue;
+        }
+
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let end = std::cmp::min(index + 1 + CONTEXT_LINES, lines.len());
+
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    hunks
+}
+
+/// The shortest edit script from `old` to `new`, using Myers' algorithm.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let max = (n + m) as usize;
+
+    // The furthest x reached along each diagonal k = x - y, offset by `max`.
+    let mut furthest = vec![0isize; 2 * max + 2];
+    // After each round d, the entries of `furthest` for diagonals -d..=d.
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max as isize {
+        for k in (-d..=d).step_by(2) {
+            let get = |k: isize| furthest[(k + max as isize) as usize];
+
+            let mut x = if d == 0 {
+                0
+            } else if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+                get(k + 1)
+            } else {
+                get(k - 1) + 1
+            };
+            let mut y = x - k;
+
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            furthest[(k + max as isize) as usize] = x;
+
+            if x >= n && y >= m {
+                trace.push(window(&furthest, max, d));
+                break 'search;
+            }
+        }
+
+        trace.push(window(&furthest, max, d));
+    }
+
+    // Walk back from the end, through the diagonal each round came from.
+    let mut lines = Vec::new();
+    let (mut x, mut y) = (n, m);
+
+    for d in (0..trace.len() as isize).rev() {
+        let (prev_x, prev_y) = if d == 0 {
+            (0, 0)
+        } else {
+            let prev = &trace[d as usize - 1];
+            let get = |k: isize| prev[(k + d - 1) as usize];
+            let k = x - y;
+
+            let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            let prev_x = get(prev_k);
+
+            (prev_x, prev_x - prev_k)
+        };
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            lines.push(DiffLine {
+                change: Change::Same,
+                old_index: x as usize,
+                new_index: y as usize,
+                text: old[x as usize],
+            });
+        }
+
+        if d > 0 {
+            if x == prev_x {
+                y -= 1;
+                lines.push(DiffLine {
+                    change: Change::Added,
+                    old_index: x as usize,
+                    new_index: y as usize,
+                    text: new[y as usize],
+                });
+            } else {
+                x -= 1;
+                lines.push(DiffLine {
+                    change: Change::Removed,
+                    old_index: x as usize,
+                    new_index: y as usize,
+                    text: old[x as usize],
+                });
+            }
+        }
+    }
+
+    lines.reverse();
+    lines
+}
+
+fn window(furthest: &[isize], max: usize, d: isize) -> Vec<isize> {
+    let start = (max as isize - d) as usize;
+    let end = (max as isize + d) as usize;
+
+    furthest[start..=end].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn no_changes() {
+        assert_eq!(unified_diff("Main.roc", "", ""), "");
+        assert_eq!(unified_diff("Main.roc", "a\nb\n", "a\nb\n"), "");
+    }
+
+    #[test]
+    fn insertion_into_empty_file() {
+        assert_eq!(
+            unified_diff("Main.roc", "", "a\nb\n"),
+            indoc!(
+                r#"
+                --- Main.roc
+                +++ Main.roc
+                @@ -0,0 +1,2 @@
+                +a
+                +b
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn deletion_of_whole_file() {
+        assert_eq!(
+            unified_diff("Main.roc", "a\nb\n", ""),
+            indoc!(
+                r#"
+                --- Main.roc
+                +++ Main.roc
+                @@ -1,2 +0,0 @@
+                -a
+                -b
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn pure_insertion_and_deletion() {
+        assert_eq!(
+            unified_diff("Main.roc", "a\nb\nc\n", "a\nb\nx\nc\n"),
+            indoc!(
+                r#"
+                --- Main.roc
+                +++ Main.roc
+                @@ -1,3 +1,4 @@
+                 a
+                 b
+                +x
+                 c
+                "#
+            )
+        );
+        assert_eq!(
+            unified_diff("Main.roc", "a\nb\nc\n", "a\nc\n"),
+            indoc!(
+                r#"
+                --- Main.roc
+                +++ Main.roc
+                @@ -1,3 +1,2 @@
+                 a
+                -b
+                 c
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn adjacent_changes_share_a_hunk() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+        let new = "1\nX\n3\n4\n5\n6\n7\nY\n9\n";
+
+        assert_eq!(
+            unified_diff("Main.roc", old, new),
+            indoc!(
+                r#"
+                --- Main.roc
+                +++ Main.roc
+                @@ -1,9 +1,9 @@
+                 1
+                -2
+                +X
+                 3
+                 4
+                 5
+                 6
+                 7
+                -8
+                +Y
+                 9
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn distant_changes_get_separate_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "X\n2\n3\n4\n5\n6\n7\n8\n9\nY\n";
+
+        assert_eq!(
+            unified_diff("Main.roc", old, new),
+            indoc!(
+                r#"
+                --- Main.roc
+                +++ Main.roc
+                @@ -1,4 +1,4 @@
+                -1
+                +X
+                 2
+                 3
+                 4
+                @@ -7,4 +7,4 @@
+                 7
+                 8
+                 9
+                -10
+                +Y
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn missing_final_newline() {
+        assert_eq!(
+            unified_diff("Main.roc", "a\nb", "a\nb\n"),
+            indoc!(
+                r#"
+                --- Main.roc
+                +++ Main.roc
+                @@ -1,2 +1,2 @@
+                 a
+                -b
+                \ No newline at end of file
+                +b
+                "#
+            )
+        );
+        assert_eq!(
+            unified_diff("Main.roc", "a\nb", "a\nc"),
+            indoc!(
+                r#"
+                --- Main.roc
+                +++ Main.roc
+                @@ -1,2 +1,2 @@
+                 a
+                -b
+                \ No newline at end of file
+                +c
+                \ No newline at end of file
+                "#
+            )
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! `roc explain` prints the extended explanation for a report code, e.g. `E2001`.
+
+use roc_reporting::error::r#type::suggest;
+use roc_reporting::error::{explain as find_explanation, explanations};
+use roc_reporting::report::{pretty_header, Explanation};
+
+pub fn explain(code: Option<&str>) -> i32 {
+    let code = match code {
+        Some(code) => code,
+        None => {
+            println!("These codes have extended explanations:\n");
+
+            for explanation in explanations() {
+                println!("    {}  {}", explanation.code, explanation.title);
+            }
+
+            println!("\nRun `roc explain <CODE>` to read one of them.");
+
+            return 0;
+        }
+    };
+
+    match find_explanation(code) {
+        Some(explanation) => {
+            print_explanation(explanation);
+
+            0
+        }
+        None => {
+            let options: Vec<&str> = explanations().map(|explanation| explanation.code).collect();
+
+            eprintln!("There is no extended explanation for {code}.");
+
+            if let Some(closest) = suggest::sort(code, options).first() {
+                eprintln!("\nDid you mean {closest}? Run `roc explain` to see every code that has an explanation.");
+            }
+
+            1
+        }
+    }
+}
+
+fn print_explanation(explanation: &Explanation) {
+    println!("{}\n", pretty_header(explanation.title));
+    println!("Code: {}\n", explanation.code);
+    println!("{}\n", explanation.explanation);
+    println!("For example:\n");
+
+    for line in explanation.example.lines() {
+        println!("    {line}");
+    }
+
+    println!("\n{}", explanation.fix);
+}
@@ -4,21 +4,28 @@ use std::path::{Path, PathBuf};
 
 use bumpalo::Bump;
 use roc_error_macros::{internal_error, user_error};
-use roc_fmt::def::fmt_defs;
+use roc_fmt::def::fmt_top_level_defs;
 use roc_fmt::module::fmt_module;
 use roc_fmt::spaces::RemoveSpaces;
 use roc_fmt::{Ast, Buf};
 use roc_parse::{
+    ast::{Collection, Header, Module, Spaced},
+    header::{ImportsEntry, KeywordItem},
     module::{self, module_defs},
     parser::{Parser, SyntaxError},
     state::State,
 };
+use roc_region::all::Loc;
+
+use crate::diff::unified_diff;
 
 #[derive(Copy, Clone, Debug)]
 pub enum FormatMode {
     WriteToFile,
     WriteToStdout,
     CheckOnly,
+    /// Print a diff of what would change, without writing anything
+    Diff,
 }
 
 fn flatten_directories(files: std::vec::Vec<PathBuf>) -> std::vec::Vec<PathBuf> {
@@ -65,31 +72,86 @@ fn is_roc_file(path: &Path) -> bool {
     matches!(path.extension().and_then(OsStr::to_str), Some("roc"))
 }
 
-pub fn format_files(files: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(), String> {
+/// Write `contents` to `path` without ever leaving a partially-written file behind.
+///
+/// We write to a temporary file in the same directory first and then rename it over
+/// the original, which is atomic on the platforms we support.
+fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(dir)?;
+    temp_file.write_all(contents.as_bytes())?;
+
+    // Preserve the original file's permissions, since the temp file is created with 0600.
+    if let Ok(metadata) = std::fs::metadata(path) {
+        temp_file
+            .as_file()
+            .set_permissions(metadata.permissions())?;
+    }
+
+    temp_file.persist(path).map_err(|err| err.error)?;
+
+    Ok(())
+}
+
+pub fn format_files(
+    files: std::vec::Vec<PathBuf>,
+    mode: FormatMode,
+    max_width: Option<usize>,
+    sort_headers: bool,
+) -> Result<(), String> {
     let arena = Bump::new();
+    let mut unformatted_files = Vec::new();
 
     for file in flatten_directories(files) {
         let src = std::fs::read_to_string(&file).unwrap();
 
-        match format_src(&arena, &src) {
+        match format_src(&arena, &src, max_width, sort_headers) {
             Ok(buf) => {
                 match mode {
                     FormatMode::CheckOnly => {
-                        // If we notice that this file needs to be formatted, return early
+                        // Keep going so we can report every file that needs formatting at once.
                         if buf.as_str() != src {
-                            return Err("One or more files need to be reformatted.".to_string());
+                            unformatted_files.push(file);
                         }
                     }
                     FormatMode::WriteToFile => {
                         // If all the checks above passed, actually write out the new file.
-                        std::fs::write(&file, buf.as_str()).unwrap();
+                        // Skip files that are already formatted, so we don't touch their mtime.
+                        if buf.as_str() != src {
+                            write_atomically(&file, buf.as_str()).unwrap_or_else(|err| {
+                                user_error!(
+                                    "I could not write the formatted file {}: {}",
+                                    file.display(),
+                                    err
+                                )
+                            });
+                        }
                     }
                     FormatMode::WriteToStdout => {
                         std::io::stdout().lock().write_all(buf.as_bytes()).unwrap()
                     }
+                    FormatMode::Diff => {
+                        if buf.as_str() != src {
+                            let diff = unified_diff(&file.display().to_string(), &src, &buf);
+                            std::io::stdout().lock().write_all(diff.as_bytes()).unwrap();
+
+                            unformatted_files.push(file);
+                        }
+                    }
                 }
             }
             Err(err) => match err {
+                FormatProblem::InvalidSource { parse_err } => {
+                    user_error!(
+                        "Unexpected parse failure when parsing {} for formatting:\n\n{}\n\n",
+                        file.display(),
+                        parse_err
+                    );
+                }
                 FormatProblem::ParsingFailed {
                     formatted_src,
                     parse_err,
@@ -156,11 +218,25 @@ pub fn format_files(files: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(
         }
     }
 
-    Ok(())
+    if unformatted_files.is_empty() {
+        Ok(())
+    } else {
+        let mut message = String::from("These files need to be reformatted:\n");
+
+        for file in unformatted_files {
+            message.push_str(&format!("\n    {}", file.display()));
+        }
+
+        message.push_str("\n\nYou can run `roc format` on them to fix this.");
+
+        Err(message)
+    }
 }
 
 #[derive(Debug)]
 pub enum FormatProblem {
+    /// The source didn't parse, so it couldn't be formatted
+    InvalidSource { parse_err: String },
     ParsingFailed {
         formatted_src: String,
         parse_err: String,
@@ -176,18 +252,46 @@ pub enum FormatProblem {
     },
 }
 
-pub fn format_src(arena: &Bump, src: &str) -> Result<String, FormatProblem> {
-    let ast = arena.alloc(parse_all(arena, src).unwrap_or_else(|e| {
-        user_error!("Unexpected parse failure when parsing this formatting:\n\n{:?}\n\nParse error was:\n\n{:?}\n\n", src, e)
-    }));
+/// Formats the given source, and checks that the result reparses to the same code and that
+/// formatting it again changes nothing.
+/// See [Buf::set_max_width] and [Buf::set_sort_headers] for how `max_width` and `sort_headers`
+/// are used.
+pub fn format_src(
+    arena: &Bump,
+    src: &str,
+    max_width: Option<usize>,
+    sort_headers: bool,
+) -> Result<String, FormatProblem> {
+    let ast = match parse_all(arena, src) {
+        Ok(ast) => arena.alloc(ast),
+        Err(e) => {
+            return Err(FormatProblem::InvalidSource {
+                parse_err: format!("{:?}", e),
+            });
+        }
+    };
     let mut buf = Buf::new_in(arena);
+    buf.set_max_width(max_width);
+    buf.set_sort_headers(sort_headers);
     fmt_all(&mut buf, ast);
 
-    let reparsed_ast = match arena.alloc(parse_all(arena, buf.as_str())) {
+    verify_formatting(arena, ast, buf.as_str(), max_width, sort_headers)?;
+
+    Ok(buf.as_str().to_string())
+}
+
+fn verify_formatting<'a>(
+    arena: &'a Bump,
+    ast: &Ast<'a>,
+    formatted_src: &'a str,
+    max_width: Option<usize>,
+    sort_headers: bool,
+) -> Result<(), FormatProblem> {
+    let reparsed_ast = match arena.alloc(parse_all(arena, formatted_src)) {
         Ok(ast) => ast,
         Err(e) => {
             return Err(FormatProblem::ParsingFailed {
-                formatted_src: buf.as_str().to_string(),
+                formatted_src: formatted_src.to_string(),
                 parse_err: format!("{:?}", e),
             });
         }
@@ -201,9 +305,20 @@ pub fn format_src(arena: &Bump, src: &str) -> Result<String, FormatProblem> {
     // the PartialEq implementation is returning `false` even when the Debug-formatted impl is exactly the same.
     // I don't have the patience to debug this right now, so let's leave it for another day...
     // TODO: fix PartialEq impl on ast types
-    if format!("{ast_normalized:?}") != format!("{reparsed_ast_normalized:?}") {
+    let changed = if sort_headers {
+        // Sorting the header changes the order of its lists, so compare them in a canonical order
+        let module_before = sort_header_lists(arena, &ast_normalized.module);
+        let module_after = sort_header_lists(arena, &reparsed_ast_normalized.module);
+
+        format!("{module_before:?}") != format!("{module_after:?}")
+            || format!("{:?}", ast_normalized.defs) != format!("{:?}", reparsed_ast_normalized.defs)
+    } else {
+        format!("{ast_normalized:?}") != format!("{reparsed_ast_normalized:?}")
+    };
+
+    if changed {
         return Err(FormatProblem::ReformattingChangedAst {
-            formatted_src: buf.as_str().to_string(),
+            formatted_src: formatted_src.to_string(),
             ast_before: format!("{ast_normalized:#?}\n"),
             ast_after: format!("{reparsed_ast_normalized:#?}\n"),
         });
@@ -211,17 +326,112 @@ pub fn format_src(arena: &Bump, src: &str) -> Result<String, FormatProblem> {
 
     // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
     let mut reformatted_buf = Buf::new_in(arena);
+    reformatted_buf.set_max_width(max_width);
+    reformatted_buf.set_sort_headers(sort_headers);
 
     fmt_all(&mut reformatted_buf, reparsed_ast);
 
-    if buf.as_str() != reformatted_buf.as_str() {
+    if formatted_src != reformatted_buf.as_str() {
         return Err(FormatProblem::ReformattingUnstable {
-            formatted_src: buf.as_str().to_string(),
+            formatted_src: formatted_src.to_string(),
             reformatted_src: reformatted_buf.as_str().to_string(),
         });
     }
 
-    Ok(buf.as_str().to_string())
+    Ok(())
+}
+
+/// Sorts the header lists that `--sort-headers` sorts, and removes duplicates from them, so that
+/// a header can be compared with its sorted reformatting. The header must have had its spaces
+/// removed, because items are compared by their debug representation.
+fn sort_header_lists<'a>(arena: &'a Bump, module: &Module<'a>) -> Module<'a> {
+    let header = match &module.header {
+        Header::Interface(header) => Header::Interface(roc_parse::header::InterfaceHeader {
+            exposes: sort_keyword_list(arena, &header.exposes),
+            imports: sort_imports(arena, &header.imports),
+            ..header.clone()
+        }),
+        // `generates … with` lists aren't sorted
+        Header::Hosted(header) => Header::Hosted(roc_parse::header::HostedHeader {
+            exposes: sort_keyword_list(arena, &header.exposes),
+            imports: sort_imports(arena, &header.imports),
+            ..header.clone()
+        }),
+        Header::App(header) => Header::App(roc_parse::header::AppHeader {
+            imports: header
+                .imports
+                .as_ref()
+                .map(|imports| sort_imports(arena, imports)),
+            ..header.clone()
+        }),
+        Header::Package(header) => Header::Package(roc_parse::header::PackageHeader {
+            exposes: sort_keyword_list(arena, &header.exposes),
+            ..header.clone()
+        }),
+        Header::Platform(header) => Header::Platform(roc_parse::header::PlatformHeader {
+            exposes: sort_keyword_list(arena, &header.exposes),
+            imports: sort_imports(arena, &header.imports),
+            ..header.clone()
+        }),
+    };
+
+    Module {
+        comments: module.comments,
+        header,
+    }
+}
+
+fn sort_keyword_list<'a, K: Clone, T: std::fmt::Debug + Copy>(
+    arena: &'a Bump,
+    list: &KeywordItem<'a, K, Collection<'a, Loc<Spaced<'a, T>>>>,
+) -> KeywordItem<'a, K, Collection<'a, Loc<Spaced<'a, T>>>> {
+    KeywordItem {
+        keyword: list.keyword.clone(),
+        item: sort_list(arena, list.item),
+    }
+}
+
+fn sort_imports<'a, K: Clone>(
+    arena: &'a Bump,
+    imports: &KeywordItem<'a, K, Collection<'a, Loc<Spaced<'a, ImportsEntry<'a>>>>>,
+) -> KeywordItem<'a, K, Collection<'a, Loc<Spaced<'a, ImportsEntry<'a>>>>> {
+    // The values imported from each module are sorted too
+    let entries = imports.item.map_items(arena, |loc_entry| {
+        let entry = match *loc_entry.value.item() {
+            ImportsEntry::Module(name, exposed) => {
+                ImportsEntry::Module(name, sort_list(arena, exposed))
+            }
+            ImportsEntry::Package(shorthand, name, exposed) => {
+                ImportsEntry::Package(shorthand, name, sort_list(arena, exposed))
+            }
+            entry @ ImportsEntry::IngestedFile(..) => entry,
+        };
+
+        Loc::at(loc_entry.region, Spaced::Item(entry))
+    });
+
+    KeywordItem {
+        keyword: imports.keyword.clone(),
+        item: sort_list(arena, entries),
+    }
+}
+
+fn sort_list<'a, T: std::fmt::Debug + Copy>(
+    arena: &'a Bump,
+    list: Collection<'a, Loc<Spaced<'a, T>>>,
+) -> Collection<'a, Loc<Spaced<'a, T>>> {
+    let mut items: std::vec::Vec<_> = list
+        .items
+        .iter()
+        .map(|loc_item| (format!("{:?}", loc_item.value), *loc_item))
+        .collect();
+
+    items.sort_by(|(a, _), (b, _)| a.cmp(b));
+    items.dedup_by(|(a, _), (b, _)| a == b);
+
+    let items = arena.alloc_slice_fill_iter(items.into_iter().map(|(_, loc_item)| loc_item));
+
+    list.replace_items(items)
 }
 
 fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'a>> {
@@ -236,7 +446,7 @@ fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'
 fn fmt_all<'a>(buf: &mut Buf<'a>, ast: &'a Ast) {
     fmt_module(buf, &ast.module);
 
-    fmt_defs(buf, &ast.defs, 0);
+    fmt_top_level_defs(buf, &ast.defs);
 
     buf.fmt_end_of_file();
 }
@@ -0,0 +1,149 @@
+//! `roc info` prints details about the environment roc is running in, for bug reports.
+
+use crate::VERSION;
+use roc_linker::LinkType;
+use roc_packaging::cache::{self, CachedPackage};
+use roc_target::Target;
+use std::env;
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
+use target_lexicon::{Architecture, Environment, OperatingSystem, Triple};
+
+pub fn info() -> i32 {
+    println!("roc {}", VERSION.trim_end());
+
+    println!("\nHost\n");
+    println!("    {:<20}{}", "target triple", Triple::host());
+
+    println!("\nBackends\n");
+    println!("    {:<20}all targets", "LLVM");
+    println!(
+        "    {:<20}{}",
+        "dev (--dev)",
+        dev_backend_support(&Triple::host().architecture)
+    );
+    println!("    {:<20}wasm32", "dev wasm (--dev)");
+
+    println!("\nTargets\n");
+    for target in Target::iter().filter(|target| *target != Target::System) {
+        let name: &'static str = target.into();
+        let triple = target.to_triple();
+        let compiled_in = if target_compiled_in(target) {
+            "enabled"
+        } else {
+            "not compiled in"
+        };
+
+        println!(
+            "    {name:<20}{compiled_in:<20}linkers: {}",
+            linkers(&triple).join(", ")
+        );
+    }
+
+    let cache_dir = cache::roc_cache_dir();
+
+    println!("\nPackage cache\n");
+    println!("    {:<20}{}", "location", cache_dir.display());
+
+    match cache::cached_packages(&cache_dir) {
+        Ok(packages) => {
+            let total: u64 = packages.iter().map(|package| package.size_bytes).sum();
+
+            println!(
+                "    {:<20}{} in {} packages",
+                "size",
+                format_size(total),
+                packages.len()
+            );
+
+            if !packages.is_empty() {
+                println!();
+            }
+
+            for CachedPackage { path, size_bytes } in packages {
+                println!("    {:>10}  {}", format_size(size_bytes), path.display());
+            }
+        }
+        Err(err) => {
+            println!("    {:<20}unable to read the cache: {err}", "size");
+        }
+    }
+
+    println!();
+
+    0
+}
+
+/// These are the features in this crate's Cargo.toml which enable code gen for each target.
+fn target_compiled_in(target: Target) -> bool {
+    match target.to_triple().architecture {
+        Architecture::X86_64 => cfg!(feature = "target-x86_64"),
+        Architecture::X86_32(_) => cfg!(feature = "target-x86"),
+        Architecture::Aarch64(_) => cfg!(feature = "target-aarch64"),
+        Architecture::Arm(_) => cfg!(feature = "target-arm"),
+        Architecture::Wasm32 => cfg!(feature = "target-wasm32"),
+        _ => false,
+    }
+}
+
+fn dev_backend_support(architecture: &Architecture) -> &'static str {
+    match architecture {
+        Architecture::X86_64 => "x86_64 (this machine)",
+        Architecture::Aarch64(_) => "aarch64 (this machine)",
+        _ => "not supported on this machine",
+    }
+}
+
+/// Which linkers could be used to build an executable for the given target,
+/// including whether the external tool the legacy linker needs is installed.
+fn linkers(triple: &Triple) -> Vec<String> {
+    let mut linkers = Vec::new();
+
+    if roc_linker::supported(LinkType::Executable, triple) {
+        linkers.push("surgical".to_string());
+    }
+
+    let tool = match (triple.architecture, triple.operating_system) {
+        _ if triple.environment == Environment::Musl => "zig",
+        (Architecture::Wasm32, _)
+        | (Architecture::X86_32(_), _)
+        | (_, OperatingSystem::Windows) => "zig",
+        _ => "ld",
+    };
+
+    match find_on_path(tool) {
+        Some(_) => linkers.push(format!("legacy (using {tool})")),
+        None => linkers.push(format!("legacy (needs {tool}, which was not found)")),
+    }
+
+    linkers
+}
+
+fn find_on_path(executable: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+
+    env::split_paths(&paths).find_map(|dir| {
+        let candidates = [
+            dir.join(executable),
+            dir.join(Path::new(executable).with_extension("exe")),
+        ];
+
+        candidates.into_iter().find(|candidate| candidate.is_file())
+    })
+}
+
+fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = 1024 * KB;
+    const GB: u64 = 1024 * MB;
+
+    if bytes >= GB {
+        format!("{:.1} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{bytes} B")
+    }
+}
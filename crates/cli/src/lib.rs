@@ -8,37 +8,58 @@ use clap::{
     builder::PossibleValuesParser, parser::ValueSource, value_parser, Arg, ArgAction, ArgMatches,
     Command,
 };
-use roc_build::link::{LinkType, LinkingStrategy};
+use roc_build::link::{LinkOptions, LinkType, LinkingStrategy};
 use roc_build::program::{
-    handle_error_module, handle_loading_problem, standard_load_config, BuildFileError,
-    BuildOrdering, BuiltFile, CodeGenBackend, CodeGenOptions, DEFAULT_ROC_FILENAME,
+    check_file, handle_error_module, handle_loading_problem, standard_load_config, BuildFileError,
+    BuildOrdering, BuiltFile, CodeGenArtifact, CodeGenBackend, CodeGenOptions,
+    DEFAULT_ROC_FILENAME,
 };
+use roc_collections::all::into_sorted_entries_by_key;
 use roc_collections::MutMap;
 use roc_error_macros::{internal_error, user_error};
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
-use roc_load::{ExpectMetadata, Threading};
+use roc_load::{ExpectMetadata, Threading, TypeCheckedProgram};
 use roc_module::symbol::ModuleId;
 use roc_mono::ir::OptLevel;
 use roc_packaging::cache::RocCacheDir;
 use roc_packaging::tarball::Compression;
-use roc_reporting::report::ANSI_STYLE_CODES;
+use roc_reporting::cli::ProblemOutput;
+use roc_reporting::cli::{
+    choose_palette, set_max_errors, set_snippet_config, set_terminal_palette, terminal_palette,
+    ColorChoice, Theme,
+};
+use roc_reporting::report::DEFAULT_SNIPPET_CONFIG;
 use roc_target::Target;
 use std::env;
-use std::ffi::{CString, OsStr, OsString};
+#[cfg(target_family = "unix")]
+use std::ffi::CString;
+use std::ffi::{OsStr, OsString};
 use std::io;
 use std::mem::ManuallyDrop;
+#[cfg(target_family = "unix")]
 use std::os::raw::{c_char, c_int};
 use std::path::{Path, PathBuf};
 use std::process;
+use std::str::FromStr;
 use std::time::{Duration, Instant};
-use strum::IntoEnumIterator;
+use strum::{EnumIter, EnumString, IntoEnumIterator, IntoStaticStr};
 use target_lexicon::{Architecture, Triple};
 #[cfg(not(target_os = "linux"))]
 use tempfile::TempDir;
 
+#[cfg(not(windows))]
+mod coverage;
+mod diff;
+mod explain;
 mod format;
-pub use format::{format_files, format_src, FormatMode};
+mod info;
+mod vendor;
+pub use diff::unified_diff;
+pub use explain::explain;
+pub use format::{format_files, format_src, FormatMode, FormatProblem};
+pub use info::info;
+pub use vendor::{vendor, PackagesDir};
 
 pub const CMD_BUILD: &str = "build";
 pub const CMD_RUN: &str = "run";
@@ -52,35 +73,70 @@ pub const CMD_TEST: &str = "test";
 pub const CMD_GLUE: &str = "glue";
 pub const CMD_GEN_STUB_LIB: &str = "gen-stub-lib";
 pub const CMD_PREPROCESS_HOST: &str = "preprocess-host";
+pub const CMD_BUNDLE: &str = "bundle";
+pub const CMD_INFO: &str = "info";
+pub const CMD_EXPLAIN: &str = "explain";
+pub const CMD_VENDOR: &str = "vendor";
 
 pub const FLAG_EMIT_LLVM_IR: &str = "emit-llvm-ir";
+pub const FLAG_EMIT: &str = "emit";
 pub const FLAG_PROFILING: &str = "profiling";
 pub const FLAG_BUNDLE: &str = "bundle";
+pub const FLAG_COMPRESSION: &str = "compression";
 pub const FLAG_DEV: &str = "dev";
 pub const FLAG_OPTIMIZE: &str = "optimize";
+pub const FLAG_PROFILE: &str = "profile";
 pub const FLAG_MAX_THREADS: &str = "max-threads";
 pub const FLAG_OPT_SIZE: &str = "opt-size";
 pub const FLAG_LIB: &str = "lib";
 pub const FLAG_NO_LINK: &str = "no-link";
 pub const FLAG_TARGET: &str = "target";
 pub const FLAG_TIME: &str = "time";
+pub const FLAG_DENY_WARNINGS: &str = "deny-warnings";
+pub const FLAG_COLOR: &str = "color";
+pub const FLAG_THEME: &str = "theme";
+pub const FLAG_CONTEXT_LINES: &str = "context-lines";
+pub const FLAG_MAX_SNIPPET_LINES: &str = "max-snippet-lines";
+pub const FLAG_MAX_SNIPPET_WIDTH: &str = "max-snippet-width";
+pub const FLAG_MAX_ERRORS: &str = "max-errors";
 pub const FLAG_VERBOSE: &str = "verbose";
 pub const FLAG_LINKER: &str = "linker";
+pub const FLAG_LINK_ARG: &str = "link-arg";
+pub const FLAG_STRIP: &str = "strip";
+pub const FLAG_REPRODUCIBLE: &str = "reproducible";
+pub const FLAG_EMIT_BLOAT_REPORT: &str = "emit-bloat-report";
 pub const FLAG_PREBUILT: &str = "prebuilt-platform";
 pub const FLAG_CHECK: &str = "check";
+pub const FLAG_DIFF: &str = "diff";
 pub const FLAG_STDIN: &str = "stdin";
 pub const FLAG_STDOUT: &str = "stdout";
+pub const FLAG_STDIN_FILENAME: &str = "stdin-filename";
+pub const FLAG_SORT_HEADERS: &str = "sort-headers";
+pub const FLAG_MAX_WIDTH: &str = "max-width";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
 pub const FLAG_OUTPUT: &str = "output";
+pub const FLAG_OUT_DIR: &str = "out-dir";
+pub const FLAG_FIX: &str = "fix";
 pub const FLAG_FUZZ: &str = "fuzz";
+pub const FLAG_OFFLINE: &str = "offline";
+pub const FLAG_COVERAGE: &str = "coverage";
+pub const FLAG_PROPERTIES: &str = "properties";
+pub const FLAG_INTERP: &str = "interp";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const ROC_DIR: &str = "ROC_DIR";
 pub const GLUE_DIR: &str = "GLUE_DIR";
 pub const GLUE_SPEC: &str = "GLUE_SPEC";
 pub const DIRECTORY_OR_FILES: &str = "DIRECTORY_OR_FILES";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
+pub const REPORT_CODE: &str = "REPORT_CODE";
 
-const VERSION: &str = include_str!("../../../version.txt");
+pub const EMIT_EXECUTABLE: &str = "executable";
+pub const EMIT_OBJECT: &str = "object";
+pub const EMIT_LLVM_IR: &str = "llvm-ir";
+pub const EMIT_ASM: &str = "asm";
+pub const EMIT_WASM: &str = "wasm";
+
+pub(crate) const VERSION: &str = include_str!("../../../version.txt");
 const DEFAULT_GENERATED_DOCS_DIR: &str = "generated-docs";
 
 pub fn build_app() -> Command {
@@ -90,6 +146,12 @@ pub fn build_app() -> Command {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_profile = Arg::new(FLAG_PROFILE)
+        .long(FLAG_PROFILE)
+        .help("Use a preset of build settings\n(`dev` uses the dev backend and keeps debug info, `release` optimizes for speed, and `size` optimizes for binary size and strips symbols. Flags like --optimize, --profiling, or --strip override the preset.)")
+        .value_parser(PossibleValuesParser::new(BuildProfile::iter().map(Into::<&'static str>::into)))
+        .required(false);
+
     let flag_max_threads = Arg::new(FLAG_MAX_THREADS)
         .long(FLAG_MAX_THREADS)
         .help("Limit the number of threads (and hence cores) used during compilation")
@@ -126,12 +188,32 @@ pub fn build_app() -> Command {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_deny_warnings = Arg::new(FLAG_DENY_WARNINGS)
+        .long(FLAG_DENY_WARNINGS)
+        .help("Treat warnings as errors\n(Useful in CI, to keep warnings from piling up.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
     let flag_linker = Arg::new(FLAG_LINKER)
         .long(FLAG_LINKER)
         .help("Set which linker to use\n(The surgical linker is enabled by default only when building for wasm32 or x86_64 Linux, because those are the only targets it currently supports. Otherwise the legacy linker is used by default.)")
         .value_parser(["surgical", "legacy"])
         .required(false);
 
+    let flag_link_arg = Arg::new(FLAG_LINK_ARG)
+        .long(FLAG_LINK_ARG)
+        .help("Pass an extra argument to the linker, e.g. `--link-arg=-L/opt/lib`\n(Can be given multiple times. Only the legacy linker accepts extra arguments.)")
+        .action(ArgAction::Append)
+        .allow_hyphen_values(true)
+        .required(false);
+
+    let flag_strip = Arg::new(FLAG_STRIP)
+        .long(FLAG_STRIP)
+        .help("Leave debug info and symbols out of the final generated program, even in development builds")
+        .action(ArgAction::SetTrue)
+        .conflicts_with(FLAG_PROFILING)
+        .required(false);
+
     let flag_prebuilt = Arg::new(FLAG_PREBUILT)
         .long(FLAG_PREBUILT)
         .help("Assume the platform has been prebuilt and skip rebuilding the platform\n(This is enabled implicitly when using `roc build` with a --target other than `--target <current machine>`, unless the target is wasm.)")
@@ -150,6 +232,12 @@ pub fn build_app() -> Command {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_offline = Arg::new(FLAG_OFFLINE)
+        .long(FLAG_OFFLINE)
+        .help("Don't download any packages\n(Packages that are not in the cache yet are reported as missing. Projects with vendored packages are always built this way.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
     let roc_file_to_run = Arg::new(ROC_FILE)
         .help("The .roc file of an app to run")
         .value_parser(value_parser!(PathBuf))
@@ -165,6 +253,48 @@ pub fn build_app() -> Command {
     let build_target_values_parser =
         PossibleValuesParser::new(Target::iter().map(Into::<&'static str>::into));
 
+    let flag_color = Arg::new(FLAG_COLOR)
+        .long(FLAG_COLOR)
+        .help("When to use colors in the output\n(`auto` uses colors when printing to a terminal, unless the NO_COLOR environment variable is set.)")
+        .value_parser(["auto", "always", "never"])
+        .global(true)
+        .required(false);
+
+    let flag_theme = Arg::new(FLAG_THEME)
+        .long(FLAG_THEME)
+        .help("Which colors to use in the output\n(Defaults to the ROC_THEME environment variable, or `dark` if that is not set.)")
+        .value_parser(["dark", "light", "high-contrast"])
+        .global(true)
+        .required(false);
+
+    let flag_context_lines = Arg::new(FLAG_CONTEXT_LINES)
+        .long(FLAG_CONTEXT_LINES)
+        .help("How many lines of code to show before and after the code that a problem is about\n(Defaults to 0.)")
+        .value_parser(value_parser!(u32))
+        .global(true)
+        .required(false);
+
+    let flag_max_snippet_lines = Arg::new(FLAG_MAX_SNIPPET_LINES)
+        .long(FLAG_MAX_SNIPPET_LINES)
+        .help("When the code around a problem is longer than this many lines, only show the lines with the problem\n(Defaults to 60.)")
+        .value_parser(value_parser!(u32))
+        .global(true)
+        .required(false);
+
+    let flag_max_errors = Arg::new(FLAG_MAX_ERRORS)
+        .long(FLAG_MAX_ERRORS)
+        .help("Stop printing errors after this many\n(The summary at the end still counts all of them.)")
+        .value_parser(value_parser!(usize))
+        .global(true)
+        .required(false);
+
+    let flag_max_snippet_width = Arg::new(FLAG_MAX_SNIPPET_WIDTH)
+        .long(FLAG_MAX_SNIPPET_WIDTH)
+        .help("Cut off lines of code in problem reports after this many characters, e.g. for generated code\n(Defaults to 500.)")
+        .value_parser(value_parser!(usize))
+        .global(true)
+        .required(false);
+
     Command::new("roc")
         .version(concatcp!(VERSION, "\n"))
         .about("Run the given .roc file, if there are no compilation errors.\nYou can use one of the SUBCOMMANDS below to do something else!")
@@ -173,27 +303,63 @@ pub fn build_app() -> Command {
             .about("Build a binary from the given .roc file, but don't run it")
             .arg(Arg::new(FLAG_OUTPUT)
                 .long(FLAG_OUTPUT)
-                .help("The full path to the output binary, including filename. To specify directory only, specify a path that ends in a directory separator (e.g. a slash).")
+                .visible_alias("out")
+                .help("The full path to the output binary (or library, or .wasm file), including filename. To specify directory only, specify a path that ends in a directory separator (e.g. a slash), or an existing directory.")
                 .value_parser(value_parser!(OsString))
                 .required(false)
             )
+            .arg(Arg::new(FLAG_OUT_DIR)
+                .long(FLAG_OUT_DIR)
+                .help("The directory to put the output in, using a filename based on the .roc file's name\n(The directory is created if it does not exist yet.)")
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with(FLAG_OUTPUT)
+                .required(false)
+            )
             .arg(flag_optimize.clone())
+            .arg(flag_profile.clone())
             .arg(flag_max_threads.clone())
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
             .arg(flag_profiling.clone())
             .arg(flag_time.clone())
+            .arg(flag_deny_warnings.clone())
             .arg(flag_linker.clone())
+            .arg(flag_link_arg.clone())
+            .arg(flag_strip.clone())
             .arg(flag_prebuilt.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_offline.clone())
             .arg(flag_wasm_stack_size_kb)
+            .arg(
+                Arg::new(FLAG_REPRODUCIBLE)
+                    .long(FLAG_REPRODUCIBLE)
+                    .help("Build twice, and fail unless both builds are byte-for-byte identical\n(Modules are numbered in the order threads load them, so this loads them on a single thread. Only builds made with this flag are reproducible; a build without it can differ from one with it, and from other builds without it. This also keeps timestamps and temporary paths out of the output.)")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with(FLAG_MAX_THREADS)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_EMIT_BLOAT_REPORT)
+                    .long(FLAG_EMIT_BLOAT_REPORT)
+                    .help("Write a breakdown of the binary's size next to it\n(This attributes the size to Roc functions, builtin specializations, generated effect wrappers, and host code, using the binary's symbols.)")
+                    .action(ArgAction::SetTrue)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_EMIT)
+                    .long(FLAG_EMIT)
+                    .help("Choose what kind of output to produce\n(`object`, `llvm-ir`, and `asm` write only that to the output path, without linking. `wasm` builds for the wasm32 target.)")
+                    .value_parser([EMIT_EXECUTABLE, EMIT_OBJECT, EMIT_LLVM_IR, EMIT_ASM, EMIT_WASM])
+                    .required(false),
+            )
             .arg(
                 Arg::new(FLAG_TARGET)
                     .long(FLAG_TARGET)
-                    .help("Choose a different target")
+                    .help("Choose a different target\n(Can be given more than once to build for several targets. Each target's output then goes in a directory named after the target.)")
                     .default_value(Into::<&'static str>::into(Target::default()))
                     .value_parser(build_target_values_parser.clone())
+                    .action(ArgAction::Append)
                     .required(false),
             )
             .arg(
@@ -229,15 +395,18 @@ pub fn build_app() -> Command {
         .subcommand(Command::new(CMD_TEST)
             .about("Run all top-level `expect`s in a main module and any modules it imports")
             .arg(flag_optimize.clone())
+            .arg(flag_profile.clone())
             .arg(flag_max_threads.clone())
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
             .arg(flag_profiling.clone())
             .arg(flag_time.clone())
+            .arg(flag_deny_warnings.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_offline.clone())
             .arg(
                 Arg::new(FLAG_VERBOSE)
                     .long(FLAG_VERBOSE)
@@ -245,6 +414,23 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false)
             )
+            .arg(
+                Arg::new(FLAG_COVERAGE)
+                    .long(FLAG_COVERAGE)
+                    .help("Count how often each `when` branch runs, and write the counts to an lcov file\n(lcov.info unless a path is given, as in --coverage=out/lcov.info)")
+                    .value_parser(value_parser!(PathBuf))
+                    .num_args(0..=1)
+                    .require_equals(true)
+                    .default_missing_value("lcov.info")
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_PROPERTIES)
+                    .long(FLAG_PROPERTIES)
+                    .help("Also check the expects inside functions, by calling the functions with generated arguments")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file for the main module")
@@ -260,30 +446,47 @@ pub fn build_app() -> Command {
         .subcommand(Command::new(CMD_RUN)
             .about("Run a .roc file even if it has build errors")
             .arg(flag_optimize.clone())
+            .arg(flag_profile.clone())
             .arg(flag_max_threads.clone())
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
             .arg(flag_profiling.clone())
             .arg(flag_time.clone())
+            .arg(flag_deny_warnings.clone())
             .arg(flag_linker.clone())
+            .arg(flag_link_arg.clone())
+            .arg(flag_strip.clone())
             .arg(flag_prebuilt.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_offline.clone())
+            .arg(
+                Arg::new(FLAG_INTERP)
+                    .long(FLAG_INTERP)
+                    .help("Run the program with the interpreter instead of compiling it\n(The platform's host is not used, so this only works for platforms whose hosted functions are all standard I/O ones like putLine, getLine, and args.)")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone().last(true))
         )
         .subcommand(Command::new(CMD_DEV)
             .about("`check` a .roc file, and then run it if there were no errors")
             .arg(flag_optimize.clone())
+            .arg(flag_profile.clone())
             .arg(flag_max_threads.clone())
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
             .arg(flag_profiling.clone())
             .arg(flag_time.clone())
+            .arg(flag_deny_warnings.clone())
             .arg(flag_linker.clone())
+            .arg(flag_link_arg.clone())
+            .arg(flag_strip.clone())
             .arg(flag_prebuilt.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_offline.clone())
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone().last(true))
         )
@@ -302,13 +505,29 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false),
             )
+            .arg(
+                Arg::new(FLAG_DIFF)
+                    .long(FLAG_DIFF)
+                    .help("Print a diff of the changes formatting would make, without changing any files\n(If formatting is needed, return a non-zero exit code.)")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with_all([FLAG_CHECK, FLAG_STDOUT])
+                    .required(false),
+            )
             .arg(
                 Arg::new(FLAG_STDIN)
                     .long(FLAG_STDIN)
-                    .help("Read file to format from stdin")
+                    .help("Read file to format from stdin\n(The formatted file is printed to stdout, unless --check or --diff is also specified.)")
                     .action(ArgAction::SetTrue)
                     .required(false),
             )
+            .arg(
+                Arg::new(FLAG_STDIN_FILENAME)
+                    .long(FLAG_STDIN_FILENAME)
+                    .help("The name of the file being read from stdin, to use in error messages")
+                    .value_parser(value_parser!(PathBuf))
+                    .requires(FLAG_STDIN)
+                    .required(false),
+            )
             .arg(
                 Arg::new(FLAG_STDOUT)
                     .long(FLAG_STDOUT)
@@ -316,14 +535,56 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false),
             )
+            .arg(
+                Arg::new(FLAG_MAX_WIDTH)
+                    .long(FLAG_MAX_WIDTH)
+                    .help("Split expressions over multiple lines, and reflow doc comments, when they would\ngo past this many characters\n(By default, only the line breaks in the original code decide this.)")
+                    .value_parser(value_parser!(usize))
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_SORT_HEADERS)
+                    .long(FLAG_SORT_HEADERS)
+                    .help("Sort the exposes and imports lists in module headers alphabetically, and\nremove duplicates from them")
+                    .action(ArgAction::SetTrue)
+                    .required(false),
+            )
             .after_help("If DIRECTORY_OR_FILES is omitted, the .roc files in the current working\ndirectory are formatted.")
         )
+        .subcommand(Command::new(CMD_INFO)
+            .about("Print information about this roc installation, for use in bug reports")
+        )
+        .subcommand(Command::new(CMD_EXPLAIN)
+            .about("Print an extended explanation of an error or warning, with an example")
+            .arg(
+                Arg::new(REPORT_CODE)
+                    .help("The code of the error or warning, e.g. E2001\n(If omitted, every code that has an explanation is listed.)")
+                    .required(false)
+            )
+        )
         .subcommand(Command::new(CMD_VERSION)
             .about(concatcp!("Print the Roc compiler’s version, which is currently ", VERSION)))
         .subcommand(Command::new(CMD_CHECK)
             .about("Check the code for problems, but don’t build or run it")
             .arg(flag_time.clone())
+            .arg(flag_deny_warnings.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_offline.clone())
+            .arg(
+                Arg::new(FLAG_OUTPUT)
+                    .long(FLAG_OUTPUT)
+                    .help("How to print the problems that were found\n(`json` prints a single JSON document, for use in CI and editor plugins.)")
+                    .value_parser(["text", "json"])
+                    .default_value("text")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_FIX)
+                    .long(FLAG_FIX)
+                    .help("Apply the fixes that are certain to be right (such as removing unused imports), then report the remaining problems\n(Only files in the same directory as the checked file, or below it, are changed.)")
+                    .action(ArgAction::SetTrue)
+                    .required(false),
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file of an app to check")
@@ -349,12 +610,49 @@ pub fn build_app() -> Command {
                     .default_value(DEFAULT_ROC_FILENAME),
                 )
         )
+        .subcommand(Command::new(CMD_BUNDLE)
+            .about("Check a package or platform, then bundle it into an archive that others can add as a HTTPS dependency")
+            .arg(flag_max_threads.clone())
+            .arg(flag_offline.clone())
+            .arg(
+                Arg::new(FLAG_COMPRESSION)
+                    .long(FLAG_COMPRESSION)
+                    .help("What kind of archive to create\n(The archive's filename will be the hash of its contents, which is what URL dependencies expect.)")
+                    .value_parser([".tar", ".tar.gz", ".tar.br"])
+                    .default_value(".tar.br")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The package's or platform's main .roc file")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME),
+            )
+        )
+        .subcommand(Command::new(CMD_VENDOR)
+            .about("Copy the packages in the project's roc.json manifest into a vendor directory beside it, and load them from there from now on")
+            .arg(
+                Arg::new(FLAG_OFFLINE)
+                    .long(FLAG_OFFLINE)
+                    .help("Copy the packages from the cache, without downloading the ones that are missing")
+                    .action(ArgAction::SetTrue)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file of the project's app")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME),
+            )
+        )
         .subcommand(Command::new(CMD_GLUE)
             .about("Generate glue code between a platform's Roc API and its host language")
             .arg(&flag_dev)
             .arg(
                 Arg::new(GLUE_SPEC)
-                    .help("The specification for how to translate Roc types into output files.")
+                    .help("The specification for how to translate Roc types into output files.\n(Specs for Rust, C, and Zig are in the crates/glue/src/ directory of the Roc repo.)")
                     .value_parser(value_parser!(PathBuf))
                     .required(true)
             )
@@ -407,19 +705,93 @@ pub fn build_app() -> Command {
             )
         )
         .arg(flag_optimize)
+        .arg(flag_profile)
         .arg(flag_max_threads)
         .arg(flag_opt_size)
         .arg(flag_dev)
         .arg(flag_emit_llvm_ir)
         .arg(flag_profiling)
         .arg(flag_time)
+        .arg(flag_deny_warnings)
         .arg(flag_linker)
+        .arg(flag_link_arg)
+        .arg(flag_strip)
         .arg(flag_prebuilt)
         .arg(flag_fuzz)
+        .arg(flag_offline)
+        .arg(flag_color)
+        .arg(flag_theme)
+        .arg(flag_context_lines)
+        .arg(flag_max_snippet_lines)
+        .arg(flag_max_snippet_width)
+        .arg(flag_max_errors)
         .arg(roc_file_to_run)
         .arg(args_for_app.trailing_var_arg(true))
 }
 
+/// Pick the palette for everything printed to the terminal, based on `--color`, `--theme`,
+/// and the environment. This should happen before anything is printed.
+pub fn set_palette_from_flags(matches: &ArgMatches) {
+    let matches = innermost_matches(matches);
+
+    let color = match matches.get_one::<String>(FLAG_COLOR).map(String::as_str) {
+        Some("always") => ColorChoice::Always,
+        Some("never") => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    };
+
+    let theme = match matches.get_one::<String>(FLAG_THEME) {
+        Some(name) => Theme::from_name(name).unwrap_or_default(),
+        None => Theme::from_env().unwrap_or_default(),
+    };
+
+    set_terminal_palette(choose_palette(color, theme));
+}
+
+/// Pick how much code the reports printed to the terminal show, based on `--context-lines`,
+/// `--max-snippet-lines`, and `--max-snippet-width`. This should happen before anything is printed.
+pub fn set_snippet_config_from_flags(matches: &ArgMatches) {
+    let matches = innermost_matches(matches);
+
+    let mut config = DEFAULT_SNIPPET_CONFIG;
+
+    if let Some(context_lines) = matches.get_one::<u32>(FLAG_CONTEXT_LINES) {
+        config.context_lines = *context_lines;
+    }
+
+    match matches.get_one::<u32>(FLAG_MAX_SNIPPET_LINES) {
+        None => {}
+        Some(0) => user_error!("--{FLAG_MAX_SNIPPET_LINES} must be at least 1"),
+        Some(max_lines) => config.max_lines = *max_lines,
+    }
+
+    match matches.get_one::<usize>(FLAG_MAX_SNIPPET_WIDTH) {
+        None => {}
+        Some(0) => user_error!("--{FLAG_MAX_SNIPPET_WIDTH} must be at least 1"),
+        Some(max_line_width) => config.max_line_width = *max_line_width,
+    }
+
+    set_snippet_config(config);
+}
+
+/// Apply `--max-errors`. This should happen before anything is printed.
+pub fn set_max_errors_from_flags(matches: &ArgMatches) {
+    match innermost_matches(matches).get_one::<usize>(FLAG_MAX_ERRORS) {
+        None => {}
+        Some(0) => user_error!("--{FLAG_MAX_ERRORS} must be at least 1"),
+        Some(max_errors) => set_max_errors(*max_errors),
+    }
+}
+
+/// Global flags end up in the innermost subcommand's matches.
+fn innermost_matches(mut matches: &ArgMatches) -> &ArgMatches {
+    while let Some((_, sub_matches)) = matches.subcommand() {
+        matches = sub_matches;
+    }
+
+    matches
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum BuildConfig {
     BuildOnly,
@@ -427,6 +799,90 @@ pub enum BuildConfig {
     BuildAndRunIfNoErrors,
 }
 
+/// A named set of defaults for the build flags, so people don't have to work out
+/// which combination of flags they want. Any flags that are given explicitly win.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, IntoStaticStr, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum BuildProfile {
+    /// Build as fast as possible, with the dev backend
+    Dev,
+    /// Optimize for runtime performance
+    Release,
+    /// Optimize for binary size, and strip symbols
+    Size,
+}
+
+impl BuildProfile {
+    fn from_flags(matches: &ArgMatches) -> Option<Self> {
+        matches
+            .get_one::<String>(FLAG_PROFILE)
+            .map(|profile| BuildProfile::from_str(profile).unwrap())
+    }
+
+    fn opt_level(self) -> OptLevel {
+        match self {
+            BuildProfile::Dev => OptLevel::Development,
+            BuildProfile::Release => OptLevel::Optimize,
+            BuildProfile::Size => OptLevel::Size,
+        }
+    }
+
+    fn uses_dev_backend(self) -> bool {
+        matches!(self, BuildProfile::Dev)
+    }
+
+    fn emit_debug_info(self) -> bool {
+        matches!(self, BuildProfile::Dev)
+    }
+
+    fn strip(self) -> bool {
+        matches!(self, BuildProfile::Size)
+    }
+}
+
+/// The settings that the build flags and `--profile` come to
+#[derive(Debug, Clone, Copy)]
+struct BuildSettings {
+    opt_level: OptLevel,
+    use_dev_backend: bool,
+    strip: bool,
+    emit_debug_info: bool,
+}
+
+impl BuildSettings {
+    fn from_flags(matches: &ArgMatches, config: &BuildConfig) -> Self {
+        let opt_level = if let BuildConfig::BuildAndRunIfNoErrors = config {
+            OptLevel::Development
+        } else {
+            opt_level_from_flags(matches)
+        };
+
+        let profile = BuildProfile::from_flags(matches);
+
+        // Note: This allows using `--profile=dev` with `--optimize`.
+        // This means frontend optimizations and dev backend.
+        let use_dev_backend =
+            matches.get_flag(FLAG_DEV) || profile.map_or(false, BuildProfile::uses_dev_backend);
+
+        let profiling = matches.get_flag(FLAG_PROFILING);
+        let strip = matches.get_flag(FLAG_STRIP)
+            || (!profiling && profile.map_or(false, BuildProfile::strip));
+        let emit_debug_info = !strip
+            && (profiling
+                || match profile {
+                    Some(profile) => profile.emit_debug_info(),
+                    None => matches!(opt_level, OptLevel::Development | OptLevel::Normal),
+                });
+
+        BuildSettings {
+            opt_level,
+            use_dev_backend,
+            strip,
+            emit_debug_info,
+        }
+    }
+}
+
 fn opt_level_from_flags(matches: &ArgMatches) -> OptLevel {
     match (
         matches.get_flag(FLAG_OPTIMIZE),
@@ -436,7 +892,10 @@ fn opt_level_from_flags(matches: &ArgMatches) -> OptLevel {
         (true, false, false) => OptLevel::Optimize,
         (false, true, false) => OptLevel::Size,
         (false, false, true) => OptLevel::Development,
-        (false, false, false) => OptLevel::Normal,
+        (false, false, false) => match BuildProfile::from_flags(matches) {
+            Some(profile) => profile.opt_level(),
+            None => OptLevel::Normal,
+        },
         _ => user_error!("build can be only one of `--dev`, `--optimize`, or `--opt-size`"),
     }
 }
@@ -457,7 +916,6 @@ struct ModuleTestResults {
 pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     use roc_build::program::report_problems_monomorphized;
     use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError};
-    use roc_packaging::cache;
     use roc_target::TargetInfo;
 
     let start_time = Instant::now();
@@ -472,6 +930,7 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     };
 
     let path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+    let coverage_path = matches.get_one::<PathBuf>(FLAG_COVERAGE);
 
     // Spawn the root task
     if !path.exists() {
@@ -506,14 +965,18 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         function_kind,
         // TODO: expose this from CLI?
         render: roc_reporting::report::RenderTarget::ColorTerminal,
-        palette: roc_reporting::report::DEFAULT_PALETTE,
+        palette: *terminal_palette(),
         threading,
-        exec_mode: ExecutionMode::Test,
+        exec_mode: ExecutionMode::Test {
+            coverage: coverage_path.is_some(),
+            properties: matches.get_flag(FLAG_PROPERTIES),
+        },
     };
+    let packages_dir = PackagesDir::from_matches(matches);
     let load_result = roc_load::load_and_monomorphize(
         arena,
         path.to_path_buf(),
-        RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+        packages_dir.roc_cache_dir(),
         load_config,
     );
 
@@ -526,7 +989,20 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
             return handle_error_module(module, start_time.elapsed(), path.as_os_str(), false);
         }
     };
-    let problems = report_problems_monomorphized(&mut loaded);
+    let problems = report_problems_monomorphized(&mut loaded)
+        .deny_warnings(matches.get_flag(FLAG_DENY_WARNINGS));
+
+    if problems.failed() {
+        problems.print_error_warning_count(start_time.elapsed());
+
+        if problems.errors > 0 {
+            println!(".\n\nNot running tests, because there are errors.\n");
+        } else {
+            println!(".\n\nNot running tests, because warnings are denied.\n");
+        }
+
+        return Ok(problems.exit_code());
+    }
 
     let mut expectations = std::mem::take(&mut loaded.expectations);
 
@@ -543,6 +1019,12 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         )
         .unwrap();
 
+    // One counter for each `when` branch, if we're measuring coverage.
+    let mut branch_counters = vec![0; coverage::counter_count(&expectations)];
+    if coverage_path.is_some() {
+        roc_repl_expect::run::set_coverage_buffer(&dyn_lib, &mut branch_counters);
+    }
+
     // Print warnings before running tests.
     {
         debug_assert_eq!(
@@ -551,7 +1033,7 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         );
         if problems.warnings > 0 {
             problems.print_error_warning_count(start_time.elapsed());
-            println!(".\n\nRunning tests…\n\n\x1B[36m{}\x1B[39m", "─".repeat(80));
+            println!(".\n\nRunning tests…\n\n{}", horizontal_rule());
         }
     }
 
@@ -569,7 +1051,12 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
 
     let compilation_duration = start_time.elapsed();
 
-    for (module_id, expects) in expects_by_module.into_iter() {
+    // Run the modules in the order of their paths, so the output is the same from run to run.
+    let expects_by_module = into_sorted_entries_by_key(expects_by_module, |module_id, _| {
+        sources.get(module_id).map(|(path, _)| path.clone())
+    });
+
+    for (module_id, expects) in expects_by_module {
         let test_start_time = Instant::now();
 
         let (failed_count, passed_count) = roc_repl_expect::run::run_toplevel_expects(
@@ -599,6 +1086,24 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
 
     let total_duration = start_time.elapsed();
 
+    if let Some(coverage_path) = coverage_path {
+        let mut file = io::BufWriter::new(std::fs::File::create(coverage_path)?);
+        let summary = coverage::write_lcov(&mut file, &expectations, &sources, &branch_counters)?;
+        io::Write::flush(&mut file)?;
+
+        let percentage = match summary.branches {
+            0 => 100.0,
+            branches => 100.0 * summary.branches_hit as f64 / branches as f64,
+        };
+
+        println!(
+            "{} of {} `when` branches ran ({percentage:.1}%). Wrote coverage to {}\n",
+            summary.branches_hit,
+            summary.branches,
+            coverage_path.display()
+        );
+    }
+
     if total_failed_count == 0 && total_passed_count == 0 {
         // TODO print this in a more nicely formatted way!
         println!("No expectations were found.");
@@ -644,14 +1149,22 @@ fn print_test_results(
     println!("\n{module_name}:\n    {test_summary_str}",);
 }
 
+/// The rule printed between the compiler's output and the program's output.
+fn horizontal_rule() -> String {
+    let palette = terminal_palette();
+
+    format!("{}{}{}", palette.header, "─".repeat(80), palette.reset)
+}
+
 fn test_summary(failed_count: usize, passed_count: usize, tests_duration: Duration) -> String {
+    let palette = terminal_palette();
     let failed_color = if failed_count == 0 {
-        ANSI_STYLE_CODES.green
+        palette.success
     } else {
-        ANSI_STYLE_CODES.red
+        palette.error
     };
-    let passed_color = ANSI_STYLE_CODES.green;
-    let reset = ANSI_STYLE_CODES.reset;
+    let passed_color = palette.success;
+    let reset = palette.reset;
 
     format!(
         "{failed_color}{failed_count}{reset} failed and {passed_color}{passed_count}{reset} passed in {} ms.",
@@ -678,154 +1191,309 @@ pub fn build(
     roc_cache_dir: RocCacheDir<'_>,
     link_type: LinkType,
 ) -> io::Result<i32> {
-    use roc_build::program::build_file;
-    use BuildConfig::*;
-
     let path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
-    {
-        // Spawn the root task
-        if !path.exists() {
-            let current_dir = env::current_dir().unwrap();
-            let expected_file_path = current_dir.join(path);
-
-            let current_dir_string = current_dir.display();
-            let expected_file_path_string = expected_file_path.display();
-
-            // TODO these should use roc_reporting to display nicer error messages.
-            match matches.value_source(ROC_FILE) {
-                Some(ValueSource::DefaultValue) => {
-                    eprintln!(
-                        "\nThe current directory ({current_dir_string}) does not contain a {DEFAULT_ROC_FILENAME} file to use as a default.\n\nYou can run `roc help` for more information on how to provide a .roc file.\n"
-                    )
-                }
-                _ => {
-                    let mut error_lines = Vec::new();
-                    error_lines.push(format!(
-                        "This file was not found: {expected_file_path_string}"
-                    ));
-                    // Add some additional hints if run as `roc [FILENAME]`.
-                    if matches.subcommand().is_none() {
-                        match path.to_str() {
-                            Some(possible_typo) if !possible_typo.ends_with(".roc") => {
-                                if let Some((nearest_command, _)) =
-                                    nearest_match(possible_typo, subcommands)
-                                {
-                                    error_lines.push(format!(
-                                        "Did you mean to use the {nearest_command} subcommand?"
-                                    ));
-                                }
-                            }
-                            _ => (),
-                        }
-                    }
-                    error_lines.push("You can run `roc help` to see the list of available subcommands and for more information on how to provide a .roc file.".to_string());
 
-                    eprintln!("\n{}\n", error_lines.join("\n\n"));
-                }
-            }
-
-            process::exit(1);
-        }
+    exit_if_roc_file_missing(matches, subcommands, path);
 
-        if config == BuildConfig::BuildOnly && matches.contains_id(FLAG_BUNDLE) {
-            let start_time = Instant::now();
+    if config == BuildConfig::BuildOnly && matches.contains_id(FLAG_BUNDLE) {
+        let compression =
+            Compression::try_from(matches.get_one::<String>(FLAG_BUNDLE).unwrap().as_str())
+                .unwrap();
 
-            let compression =
-                Compression::try_from(matches.get_one::<String>(FLAG_BUNDLE).unwrap().as_str())
-                    .unwrap();
+        // Rather than building an executable or library, we're building
+        // a tarball so this code can be distributed via a HTTPS
+        return write_bundle(path, compression, FLAG_BUNDLE);
+    }
 
-            // Print a note of advice. This is mainly here because brotli takes so long but produces
-            // such smaller output files; the idea is to encourage people to wait for brotli,
-            // so that downloads go faster. The compression only happens once, but the network
-            // transfer and decompression will happen many more times!
-            match compression {
-                Compression::Brotli => {
-                    println!("Compressing with Brotli at maximum quality level…\n\n(Note: Brotli compression can take awhile! Using --{FLAG_BUNDLE} .tar.gz takes less time, but usually produces a significantly larger output file. Brotli is generally worth the up-front wait if this is a file people will be downloading!)\n");
-                }
-                Compression::Gzip => {
-                    println!("Compressing with gzip at minimum quality…\n\n(Note: Gzip usually runs faster than Brotli but typically produces significantly larger output files. Consider using --{FLAG_BUNDLE} .tar.br if this is a file people will be downloading!)\n");
-                }
-                Compression::Uncompressed => {
-                    println!("Building .tar archive without compression…\n\n(Note: Compression takes more time to run but typically produces much smaller output files. Consider using --{FLAG_BUNDLE} .tar.br if this is a file people will be downloading!)\n");
-                }
-            }
+    // the process will end after this function,
+    // so we don't want to spend time freeing these values
+    let arena = ManuallyDrop::new(Bump::new());
 
-            // Rather than building an executable or library, we're building
-            // a tarball so this code can be distributed via a HTTPS
-            let filename = roc_packaging::tarball::build(path, compression)?;
-            let total_time_ms = start_time.elapsed().as_millis();
-            let total_time = if total_time_ms > 1000 {
-                format!("{}s {}ms", total_time_ms / 1000, total_time_ms % 1000)
-            } else {
-                format!("{total_time_ms} ms")
-            };
-            let created_path = path.with_file_name(&filename);
+    build_in(
+        &arena,
+        matches,
+        config,
+        triple,
+        out_path,
+        roc_cache_dir,
+        link_type,
+        None,
+    )
+}
 
-            println!(
-                "\nBundled \x1B[33m{}\x1B[39m and its dependent files into the following archive in {total_time}:\n\n\t\x1B[33m{}\x1B[39m\n\nTo distribute this archive as a package, upload this to some URL and then add it as a dependency with:\n\n\t\x1B[32m\"https://your-url-goes-here/{filename}\"\x1B[39m\n",
-                path.to_string_lossy(),
-                created_path.to_string_lossy()
-            );
+/// Build for each of the given targets, putting each target's output in a directory named
+/// after the target, e.g. `linux-x64/main` and `wasm32/main.wasm`.
+///
+/// The app is only loaded and type checked once; only specialization, code gen, and linking
+/// are redone for each target.
+pub fn build_for_targets(
+    matches: &ArgMatches,
+    subcommands: &[String],
+    targets: &[Target],
+    out_path: Option<&Path>,
+    roc_cache_dir: RocCacheDir<'_>,
+    link_type: LinkType,
+) -> io::Result<i32> {
+    let path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
 
-            return Ok(0);
-        }
-    }
+    exit_if_roc_file_missing(matches, subcommands, path);
+
+    let out_dir = match out_path {
+        // A directory which doesn't exist yet gets created below, along with the target dirs.
+        Some(out) if out.is_file() => user_error!(
+            "When building for more than one target, --{FLAG_OUTPUT} must be a directory, because each target gets its own output file."
+        ),
+        Some(out) => out.to_path_buf(),
+        None => match path.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => PathBuf::new(),
+        },
+    };
 
     // the process will end after this function,
     // so we don't want to spend time freeing these values
     let arena = ManuallyDrop::new(Bump::new());
 
-    let opt_level = if let BuildConfig::BuildAndRunIfNoErrors = config {
-        OptLevel::Development
-    } else {
-        opt_level_from_flags(matches)
-    };
+    // Type checking doesn't depend on the target, so any of them will do here.
+    let load_config = standard_load_config(
+        &targets[0].to_triple(),
+        BuildOrdering::AlwaysBuild,
+        threading_from_flags(matches),
+    );
 
-    // Note: This allows using `--dev` with `--optimize`.
-    // This means frontend optimizations and dev backend.
-    let code_gen_backend = if matches.get_flag(FLAG_DEV) {
-        if matches!(triple.architecture, Architecture::Wasm32) {
-            CodeGenBackend::Wasm
-        } else {
-            CodeGenBackend::Assembly(AssemblyBackendMode::Binary)
-        }
-    } else {
-        let backend_mode = match opt_level {
-            OptLevel::Development => LlvmBackendMode::BinaryDev,
-            OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => LlvmBackendMode::Binary,
+    let program =
+        match roc_load::load_for_targets(&arena, path.to_owned(), roc_cache_dir, load_config) {
+            Ok(program) => program,
+            Err(problem) => return handle_loading_problem(problem),
         };
 
-        CodeGenBackend::Llvm(backend_mode)
-    };
+    for target in targets {
+        let target_name: &'static str = (*target).into();
+        let target_dir = out_dir.join(target_name);
 
-    let emit_llvm_ir = matches.get_flag(FLAG_EMIT_LLVM_IR);
-    if emit_llvm_ir && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
-        user_error!("Cannot emit llvm ir while using a dev backend.");
+        if let Err(err) = std::fs::create_dir_all(&target_dir) {
+            user_error!(
+                "Unable to create the output directory {}: {err}",
+                target_dir.display()
+            );
+        }
+
+        println!("\nBuilding for {target_name}…\n");
+
+        let exit_code = build_in(
+            &arena,
+            matches,
+            BuildConfig::BuildOnly,
+            target.to_triple(),
+            Some(&target_dir),
+            roc_cache_dir,
+            link_type,
+            Some(&program),
+        )?;
+
+        if exit_code != 0 {
+            return Ok(exit_code);
+        }
     }
 
-    let emit_debug_info = matches.get_flag(FLAG_PROFILING)
-        || matches!(opt_level, OptLevel::Development | OptLevel::Normal);
-    let emit_timings = matches.get_flag(FLAG_TIME);
+    Ok(0)
+}
 
-    let threading = match matches.get_one::<usize>(FLAG_MAX_THREADS) {
-        None => Threading::AllAvailable,
-        Some(0) => user_error!("cannot build with at most 0 threads"),
-        Some(1) => Threading::Single,
-        Some(n) => Threading::AtMost(*n),
-    };
+fn exit_if_roc_file_missing(matches: &ArgMatches, subcommands: &[String], path: &Path) {
+    if !path.exists() {
+        let current_dir = env::current_dir().unwrap();
+        let expected_file_path = current_dir.join(path);
 
-    let wasm_dev_backend = matches!(code_gen_backend, CodeGenBackend::Wasm);
+        let current_dir_string = current_dir.display();
+        let expected_file_path_string = expected_file_path.display();
 
-    let linking_strategy = if wasm_dev_backend {
-        LinkingStrategy::Additive
-    } else if !roc_linker::supported(link_type, &triple)
-        || matches.get_one::<String>(FLAG_LINKER).map(|s| s.as_str()) == Some("legacy")
-    {
-        LinkingStrategy::Legacy
+        // TODO these should use roc_reporting to display nicer error messages.
+        match matches.value_source(ROC_FILE) {
+            Some(ValueSource::DefaultValue) => {
+                eprintln!(
+                    "\nThe current directory ({current_dir_string}) does not contain a {DEFAULT_ROC_FILENAME} file to use as a default.\n\nYou can run `roc help` for more information on how to provide a .roc file.\n"
+                )
+            }
+            _ => {
+                let mut error_lines = Vec::new();
+                error_lines.push(format!(
+                    "This file was not found: {expected_file_path_string}"
+                ));
+                // Add some additional hints if run as `roc [FILENAME]`.
+                if matches.subcommand().is_none() {
+                    match path.to_str() {
+                        Some(possible_typo) if !possible_typo.ends_with(".roc") => {
+                            if let Some((nearest_command, _)) =
+                                nearest_match(possible_typo, subcommands)
+                            {
+                                error_lines.push(format!(
+                                    "Did you mean to use the {nearest_command} subcommand?"
+                                ));
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                error_lines.push("You can run `roc help` to see the list of available subcommands and for more information on how to provide a .roc file.".to_string());
+
+                eprintln!("\n{}\n", error_lines.join("\n\n"));
+            }
+        }
+
+        process::exit(1);
+    }
+}
+
+/// Threading to build with, according to the `--max-threads` and `--reproducible` flags.
+fn threading_from_flags(matches: &ArgMatches) -> Threading {
+    // Only `roc build` has the --reproducible flag
+    let reproducible = matches
+        .try_get_one::<bool>(FLAG_REPRODUCIBLE)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+
+    match matches.get_one::<usize>(FLAG_MAX_THREADS) {
+        // Modules and layouts are numbered in the order the threads get to them, and those
+        // numbers end up in symbol names. Only a single thread gets to them in the same order
+        // every time, so builds on more threads may differ; see the flag's help.
+        // (--reproducible conflicts with --max-threads.)
+        _ if reproducible => Threading::Single,
+        None => Threading::AllAvailable,
+        Some(0) => user_error!("cannot build with at most 0 threads"),
+        Some(1) => Threading::Single,
+        Some(n) => Threading::AtMost(*n),
+    }
+}
+
+/// Builds the app, either loading it from scratch or specializing an already type checked
+/// `front_end` for this target.
+#[allow(clippy::too_many_arguments)]
+fn build_in<'a>(
+    arena: &'a Bump,
+    matches: &ArgMatches,
+    config: BuildConfig,
+    triple: Triple,
+    out_path: Option<&Path>,
+    roc_cache_dir: RocCacheDir<'_>,
+    link_type: LinkType,
+    front_end: Option<&TypeCheckedProgram<'a>>,
+) -> io::Result<i32> {
+    use roc_build::program::{build_file, build_file_for_target};
+    use BuildConfig::*;
+
+    let path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+
+    let BuildSettings {
+        opt_level,
+        use_dev_backend,
+        strip,
+        emit_debug_info,
+    } = BuildSettings::from_flags(matches, &config);
+
+    let code_gen_backend = if use_dev_backend {
+        if matches!(triple.architecture, Architecture::Wasm32) {
+            CodeGenBackend::Wasm
+        } else {
+            CodeGenBackend::Assembly(AssemblyBackendMode::Binary)
+        }
     } else {
-        LinkingStrategy::Surgical
+        let backend_mode = match opt_level {
+            OptLevel::Development => LlvmBackendMode::BinaryDev,
+            OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => LlvmBackendMode::Binary,
+        };
+
+        CodeGenBackend::Llvm(backend_mode)
+    };
+
+    // Only `roc build` has the --emit flag
+    let emit = matches
+        .try_get_one::<String>(FLAG_EMIT)
+        .ok()
+        .flatten()
+        .map(String::as_str);
+
+    let emit_artifact = match emit {
+        Some(EMIT_LLVM_IR) => Some(CodeGenArtifact::LlvmIr),
+        Some(EMIT_ASM) => Some(CodeGenArtifact::Assembly),
+        _ => None,
+    };
+
+    let emit_llvm_ir = matches.get_flag(FLAG_EMIT_LLVM_IR);
+    if (emit_llvm_ir || emit_artifact == Some(CodeGenArtifact::LlvmIr))
+        && !matches!(code_gen_backend, CodeGenBackend::Llvm(_))
+    {
+        user_error!("Cannot emit llvm ir while using a dev backend.");
+    }
+
+    if emit_artifact == Some(CodeGenArtifact::Assembly)
+        && !matches!(code_gen_backend, CodeGenBackend::Llvm(_))
+    {
+        user_error!("Cannot emit assembly while using a dev backend.");
+    }
+
+    let emit_timings = matches.get_flag(FLAG_TIME);
+
+    // Only `roc build` has the --emit-bloat-report flag
+    let emit_bloat_report = matches
+        .try_get_one::<bool>(FLAG_EMIT_BLOAT_REPORT)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+
+    // Only `roc build` has the --reproducible flag
+    let reproducible = matches
+        .try_get_one::<bool>(FLAG_REPRODUCIBLE)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+
+    let threading = threading_from_flags(matches);
+
+    let wasm_dev_backend = matches!(code_gen_backend, CodeGenBackend::Wasm);
+
+    let link_options = LinkOptions {
+        extra_args: matches
+            .get_many::<String>(FLAG_LINK_ARG)
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        strip,
+        reproducible,
+        emit_bloat_report,
+        surgical_requested: matches.get_one::<String>(FLAG_LINKER).map(|s| s.as_str())
+            == Some("surgical"),
     };
 
+    let linking_strategy = match matches.get_one::<String>(FLAG_LINKER).map(|s| s.as_str()) {
+        _ if wasm_dev_backend => LinkingStrategy::Additive,
+        Some("legacy") => LinkingStrategy::Legacy,
+        Some("surgical") => {
+            if !roc_linker::supported(link_type, &triple) {
+                user_error!("The surgical linker does not support {triple} yet. Please use --{FLAG_LINKER}=legacy instead.");
+            }
+
+            LinkingStrategy::Surgical
+        }
+        // Extra linker arguments and stripping only work with the legacy linker,
+        // so asking for them picks it by default.
+        _ if !link_options.extra_args.is_empty() || strip => LinkingStrategy::Legacy,
+        _ if roc_linker::supported(link_type, &triple) => LinkingStrategy::Surgical,
+        _ => LinkingStrategy::Legacy,
+    };
+
+    if linking_strategy == LinkingStrategy::Surgical && !link_options.extra_args.is_empty() {
+        user_error!("The surgical linker does not accept --{FLAG_LINK_ARG}. Please use --{FLAG_LINKER}=legacy to pass extra arguments to the linker.");
+    }
+
+    // The surgical linker copies the prebuilt host as-is, symbols and all.
+    if linking_strategy == LinkingStrategy::Surgical && strip {
+        user_error!("The surgical linker cannot strip the host, so it does not support --{FLAG_STRIP}. Please use --{FLAG_LINKER}=legacy to strip the output.");
+    }
+
     let prebuilt = {
         let cross_compile = triple != Triple::host();
         let targeting_wasm = matches!(triple.architecture, Architecture::Wasm32);
@@ -859,25 +1527,45 @@ pub fn build(
         opt_level,
         emit_debug_info,
         emit_llvm_ir,
+        emit_artifact,
         fuzz,
     };
 
-    let load_config = standard_load_config(&triple, build_ordering, threading);
+    let build_into = |out_path: Option<&Path>| match front_end {
+        Some(program) => build_file_for_target(
+            arena,
+            &triple,
+            program,
+            path.to_owned(),
+            code_gen_options,
+            emit_timings,
+            link_type,
+            linking_strategy,
+            &link_options,
+            prebuilt,
+            wasm_dev_stack_bytes,
+            roc_cache_dir,
+            threading,
+            out_path,
+        ),
+        None => build_file(
+            arena,
+            &triple,
+            path.to_owned(),
+            code_gen_options,
+            emit_timings,
+            link_type,
+            linking_strategy,
+            &link_options,
+            prebuilt,
+            wasm_dev_stack_bytes,
+            roc_cache_dir,
+            standard_load_config(&triple, build_ordering, threading),
+            out_path,
+        ),
+    };
 
-    let res_binary_path = build_file(
-        &arena,
-        &triple,
-        path.to_owned(),
-        code_gen_options,
-        emit_timings,
-        link_type,
-        linking_strategy,
-        prebuilt,
-        wasm_dev_stack_bytes,
-        roc_cache_dir,
-        load_config,
-        out_path,
-    );
+    let res_binary_path = build_into(out_path);
 
     match res_binary_path {
         Ok(BuiltFile {
@@ -886,6 +1574,8 @@ pub fn build(
             total_time,
             expect_metadata,
         }) => {
+            let problems = problems.deny_warnings(matches.get_flag(FLAG_DENY_WARNINGS));
+
             match config {
                 BuildOnly => {
                     // If possible, report the generated executable name relative to the current dir.
@@ -902,6 +1592,12 @@ pub fn build(
                     problems.print_error_warning_count(total_time);
                     println!(" while successfully building:\n\n    {generated_filename}");
 
+                    if reproducible
+                        && !verify_reproducible(&binary_path, |out| build_into(Some(out)))?
+                    {
+                        return Ok(1);
+                    }
+
                     // Return a nonzero exit code if there were problems
                     Ok(problems.exit_code())
                 }
@@ -909,19 +1605,22 @@ pub fn build(
                     if problems.fatally_errored {
                         problems.print_error_warning_count(total_time);
                         println!(
-                            ".\n\nCannot run program due to fatal error…\n\n\x1B[36m{}\x1B[39m",
-                            "─".repeat(80)
+                            ".\n\nCannot run program due to fatal error…\n\n{}",
+                            horizontal_rule()
                         );
 
                         // Return a nonzero exit code due to fatal problem
                         return Ok(problems.exit_code());
                     }
+                    if problems.warnings_denied && problems.warnings > 0 {
+                        problems.print_error_warning_count(total_time);
+                        println!(".\n\nNot running the program, because warnings are denied.\n");
+
+                        return Ok(problems.exit_code());
+                    }
                     if problems.errors > 0 || problems.warnings > 0 {
                         problems.print_error_warning_count(total_time);
-                        println!(
-                            ".\n\nRunning program anyway…\n\n\x1B[36m{}\x1B[39m",
-                            "─".repeat(80)
-                        );
+                        println!(".\n\nRunning program anyway…\n\n{}", horizontal_rule());
                     }
 
                     let args = matches
@@ -933,14 +1632,14 @@ pub fn build(
                     // ManuallyDrop will leak the bytes because we don't drop manually
                     let bytes = &ManuallyDrop::new(std::fs::read(&binary_path).unwrap());
 
-                    roc_run(&arena, opt_level, triple, args, bytes, expect_metadata)
+                    roc_run(arena, opt_level, triple, args, bytes, expect_metadata)
                 }
                 BuildAndRunIfNoErrors => {
                     if problems.fatally_errored {
                         problems.print_error_warning_count(total_time);
                         println!(
-                            ".\n\nCannot run program due to fatal error…\n\n\x1B[36m{}\x1B[39m",
-                            "─".repeat(80)
+                            ".\n\nCannot run program due to fatal error…\n\n{}",
+                            horizontal_rule()
                         );
 
                         // Return a nonzero exit code due to fatal problem
@@ -951,12 +1650,16 @@ pub fn build(
                         "if there are non-fatal errors, they should have been returned as an error variant"
                     );
 
+                    if problems.failed() {
+                        problems.print_error_warning_count(total_time);
+                        println!(".\n\nNot running the program, because warnings are denied.\n");
+
+                        return Ok(problems.exit_code());
+                    }
+
                     if problems.warnings > 0 {
                         problems.print_error_warning_count(total_time);
-                        println!(
-                            ".\n\nRunning program…\n\n\x1B[36m{}\x1B[39m",
-                            "─".repeat(80)
-                        );
+                        println!(".\n\nRunning program…\n\n{}", horizontal_rule());
                     }
 
                     let args = matches
@@ -968,7 +1671,7 @@ pub fn build(
                     // ManuallyDrop will leak the bytes because we don't drop manually
                     let bytes = &ManuallyDrop::new(std::fs::read(&binary_path).unwrap());
 
-                    roc_run(&arena, opt_level, triple, args, bytes, expect_metadata)
+                    roc_run(arena, opt_level, triple, args, bytes, expect_metadata)
                 }
             }
         }
@@ -979,6 +1682,274 @@ pub fn build(
     }
 }
 
+/// Builds the app a second time, into a temporary directory, and checks that this gives the
+/// same bytes as the first build.
+fn verify_reproducible<'a>(
+    binary_path: &Path,
+    rebuild: impl FnOnce(&Path) -> Result<BuiltFile<'a>, BuildFileError<'a>>,
+) -> io::Result<bool> {
+    let tempdir = tempfile::tempdir()?;
+
+    // Use the same filename, because it can end up in the output (e.g. in a code signature)
+    let rebuilt_path = tempdir
+        .path()
+        .join(binary_path.file_name().unwrap_or_default());
+
+    let rebuilt_path = match rebuild(&rebuilt_path) {
+        Ok(BuiltFile { binary_path, .. }) => binary_path,
+        Err(_) => user_error!(
+            "Building a second time, to check that the build is reproducible, failed even though the first build succeeded."
+        ),
+    };
+
+    let first = std::fs::read(binary_path)?;
+    let second = std::fs::read(rebuilt_path)?;
+
+    match first_difference(&first, &second) {
+        None => {
+            println!("\nA second build gave the exact same bytes, so this build is reproducible.");
+
+            Ok(true)
+        }
+        Some(offset) => {
+            eprintln!(
+                "\nA second build gave different bytes, starting at byte {offset}, so this build is not reproducible.\n(The first build is {} bytes, and the second one is {} bytes.)",
+                first.len(),
+                second.len()
+            );
+
+            Ok(false)
+        }
+    }
+}
+
+/// The offset of the first byte where the two differ, if they do
+fn first_difference(a: &[u8], b: &[u8]) -> Option<usize> {
+    a.iter()
+        .zip(b)
+        .position(|(x, y)| x != y)
+        .or_else(|| (a.len() != b.len()).then_some(a.len().min(b.len())))
+}
+
+/// Check a package or platform, and if it has no errors, write it to a
+/// content-addressed archive that can be used as a URL dependency.
+pub fn bundle(matches: &ArgMatches, roc_cache_dir: RocCacheDir<'_>) -> io::Result<i32> {
+    let path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+
+    if !path.is_file() {
+        eprintln!(
+            "\nThis file was not found: {}\n\nYou can run `roc help {CMD_BUNDLE}` for more information on how to provide a .roc file.\n",
+            path.to_string_lossy()
+        );
+
+        return Ok(1);
+    }
+
+    let compression = Compression::try_from(
+        matches
+            .get_one::<String>(FLAG_COMPRESSION)
+            .unwrap()
+            .as_str(),
+    )
+    .unwrap();
+    let threading = threading_from_flags(matches);
+
+    let absolute_imports = roc_packaging::tarball::absolute_path_imports(path)?;
+
+    if !absolute_imports.is_empty() {
+        let palette = terminal_palette();
+
+        eprintln!("\nThese modules refer to files by absolute paths, which won't exist on the machines of people who download this package:\n");
+
+        for import in absolute_imports {
+            eprintln!(
+                "\t{}: {}\"{}\"{}",
+                import.module_path.to_string_lossy(),
+                palette.warning,
+                import.imported_path,
+                palette.reset,
+            );
+        }
+
+        eprintln!("\nPlease change them to paths relative to the module that refers to them, and then try bundling again.\n");
+
+        return Ok(1);
+    }
+
+    let arena = Bump::new();
+
+    match check_file(
+        &arena,
+        path.to_owned(),
+        false,
+        ProblemOutput::Text,
+        roc_cache_dir,
+        threading,
+        false,
+    ) {
+        Ok((problems, total_time)) => {
+            if problems.errors > 0 {
+                problems.print_error_warning_count(total_time);
+                println!(
+                    "\nNo archive was created, because a package with errors can't be bundled.\n"
+                );
+
+                return Ok(problems.exit_code());
+            }
+        }
+        Err(problem) => return handle_loading_problem(problem),
+    }
+
+    write_bundle(path, compression, FLAG_COMPRESSION)
+}
+
+fn write_bundle(path: &Path, compression: Compression, compression_flag: &str) -> io::Result<i32> {
+    let start_time = Instant::now();
+
+    // Print a note of advice. This is mainly here because brotli takes so long but produces
+    // such smaller output files; the idea is to encourage people to wait for brotli,
+    // so that downloads go faster. The compression only happens once, but the network
+    // transfer and decompression will happen many more times!
+    match compression {
+        Compression::Brotli => {
+            println!("Compressing with Brotli at maximum quality level…\n\n(Note: Brotli compression can take awhile! Using --{compression_flag} .tar.gz takes less time, but usually produces a significantly larger output file. Brotli is generally worth the up-front wait if this is a file people will be downloading!)\n");
+        }
+        Compression::Gzip => {
+            println!("Compressing with gzip at minimum quality…\n\n(Note: Gzip usually runs faster than Brotli but typically produces significantly larger output files. Consider using --{compression_flag} .tar.br if this is a file people will be downloading!)\n");
+        }
+        Compression::Uncompressed => {
+            println!("Building .tar archive without compression…\n\n(Note: Compression takes more time to run but typically produces much smaller output files. Consider using --{compression_flag} .tar.br if this is a file people will be downloading!)\n");
+        }
+    }
+
+    let filename = roc_packaging::tarball::build(path, compression)?;
+    let total_time_ms = start_time.elapsed().as_millis();
+    let total_time = if total_time_ms > 1000 {
+        format!("{}s {}ms", total_time_ms / 1000, total_time_ms % 1000)
+    } else {
+        format!("{total_time_ms} ms")
+    };
+    let created_path = path.with_file_name(&filename);
+
+    let palette = terminal_palette();
+    let (yellow, green, reset) = (palette.warning, palette.success, palette.reset);
+
+    println!(
+        "\nBundled {yellow}{}{reset} and its dependent files into the following archive in {total_time}:\n\n\t{yellow}{}{reset}\n\nTo distribute this archive as a package, upload this to some URL and then add it as a dependency with:\n\n\t{green}\"https://your-url-goes-here/{filename}\"{reset}\n",
+        path.to_string_lossy(),
+        created_path.to_string_lossy()
+    );
+
+    Ok(0)
+}
+
+/// Runs an app with the interpreter from `roc_interp`, without code generation or linking.
+pub fn interpret(matches: &ArgMatches) -> io::Result<i32> {
+    use roc_build::program::report_problems_monomorphized;
+    use roc_interp::{RuntimeError, StdHost, Value};
+    use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError};
+    use roc_target::TargetInfo;
+
+    let start_time = Instant::now();
+    let arena = Bump::new();
+
+    let threading = threading_from_flags(matches);
+
+    let path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+
+    if !path.exists() {
+        let expected_file_path = env::current_dir().unwrap().join(path);
+
+        eprintln!(
+            "\nThis file was not found: {}\n\nYou can run `roc help` for more information on how to provide a .roc file.\n",
+            expected_file_path.display()
+        );
+
+        process::exit(1);
+    }
+
+    let load_config = LoadConfig {
+        target_info: TargetInfo::from(&Triple::host()),
+        function_kind: FunctionKind::LambdaSet,
+        render: roc_reporting::report::RenderTarget::ColorTerminal,
+        palette: *terminal_palette(),
+        threading,
+        exec_mode: ExecutionMode::Executable,
+    };
+    let packages_dir = PackagesDir::from_matches(matches);
+    let load_result = roc_load::load_and_monomorphize(
+        &arena,
+        path.to_path_buf(),
+        packages_dir.roc_cache_dir(),
+        load_config,
+    );
+
+    let mut loaded = match load_result {
+        Ok(loaded) => loaded,
+        Err(LoadMonomorphizedError::LoadingProblem(problem)) => {
+            return handle_loading_problem(problem);
+        }
+        Err(LoadMonomorphizedError::ErrorModule(module)) => {
+            return handle_error_module(module, start_time.elapsed(), path.as_os_str(), false);
+        }
+    };
+
+    // Like `roc run`, report problems but run the program anyway, unless warnings are denied.
+    let problems = report_problems_monomorphized(&mut loaded)
+        .deny_warnings(matches.get_flag(FLAG_DENY_WARNINGS));
+
+    if problems.warnings_denied && problems.warnings > 0 {
+        problems.print_error_warning_count(start_time.elapsed());
+        println!(".\n\nNot running the program, because warnings are denied.\n");
+
+        return Ok(problems.exit_code());
+    }
+
+    if problems.errors > 0 || problems.warnings > 0 {
+        problems.print_error_warning_count(start_time.elapsed());
+        println!(".\n\nRunning program anyway…\n\n{}", horizontal_rule());
+    }
+
+    // Like the arguments of a compiled app, these start with the program's path
+    let args = std::iter::once(path.as_os_str())
+        .chain(
+            matches
+                .get_many::<OsString>(ARGS_FOR_APP)
+                .unwrap_or_default()
+                .map(|s| s.as_os_str()),
+        )
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+
+    match roc_interp::run_main(&loaded, StdHost::new(args)) {
+        Ok(Value::Int(exit_code, _)) => Ok(exit_code as i32),
+        Ok(Value::Str(string)) => {
+            println!("{string}");
+
+            Ok(0)
+        }
+        Ok(_) => Ok(0),
+        Err(error @ RuntimeError::Crashed { .. }) => {
+            eprintln!("{error}");
+
+            Ok(1)
+        }
+        Err(error @ RuntimeError::UnsupportedPlatform { .. }) => {
+            eprintln!(
+                "{error}\n\nThe only hosted functions available with --{FLAG_INTERP} are {}. Run the program without --{FLAG_INTERP} to use the platform's own host.",
+                StdHost::FUNCTIONS.join(", ")
+            );
+
+            Ok(1)
+        }
+        Err(error) => {
+            eprintln!("The interpreter could not run this program: {error}");
+
+            Ok(1)
+        }
+    }
+}
+
 fn roc_run<'a, I: IntoIterator<Item = &'a OsStr>>(
     arena: &Bump,
     opt_level: OptLevel,
@@ -997,28 +1968,26 @@ fn roc_run<'a, I: IntoIterator<Item = &'a OsStr>>(
                 .unwrap_or(path);
 
             #[cfg(target_family = "unix")]
-            {
+            let exit_code = {
                 use std::os::unix::ffi::OsStrExt;
 
                 run_wasm(
                     generated_filename,
                     args.into_iter().map(|os_str| os_str.as_bytes()),
-                );
-            }
+                )
+            };
 
             #[cfg(not(target_family = "unix"))]
-            {
-                run_wasm(
-                    generated_filename,
-                    args.into_iter().map(|os_str| {
-                        os_str.to_str().expect(
-                            "Roc does not currently support passing non-UTF8 arguments to Wasm.",
-                        )
-                    }),
-                );
-            }
+            let exit_code = run_wasm(
+                generated_filename,
+                args.into_iter().map(|os_str| {
+                    os_str.to_str().expect(
+                        "Roc does not currently support passing non-UTF8 arguments to Wasm.",
+                    )
+                }),
+            );
 
-            Ok(0)
+            Ok(exit_code)
         }
         _ => roc_run_native(arena, opt_level, args, binary_bytes, expect_metadata),
     }
@@ -1030,11 +1999,7 @@ fn os_str_as_utf8_bytes(os_str: &OsStr) -> &[u8] {
     os_str.as_bytes()
 }
 
-#[cfg(not(target_family = "unix"))]
-fn os_str_as_utf8_bytes(os_str: &OsStr) -> &[u8] {
-    os_str.to_str().unwrap().as_bytes()
-}
-
+#[cfg(target_family = "unix")]
 fn make_argv_envp<'a, I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
     arena: &'a Bump,
     executable: &ExecutableFile,
@@ -1118,6 +2083,7 @@ fn roc_run_native<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
     Ok(1)
 }
 
+#[cfg(target_family = "unix")]
 unsafe fn roc_run_native_fast(
     executable: ExecutableFile,
     argv: &[*const c_char],
@@ -1157,6 +2123,7 @@ impl ExecutableFile {
         }
     }
 
+    #[cfg(target_family = "unix")]
     unsafe fn execve(&self, argv: &[*const c_char], envp: &[*const c_char]) -> c_int {
         match self {
             #[cfg(target_os = "linux")]
@@ -1169,13 +2136,6 @@ impl ExecutableFile {
                 let path_cstring = CString::new(path.as_os_str().as_bytes()).unwrap();
                 libc::execve(path_cstring.as_ptr().cast(), argv.as_ptr(), envp.as_ptr())
             }
-
-            #[cfg(target_family = "windows")]
-            ExecutableFile::OnDisk(_, path) => {
-                let path_cstring = CString::new(path.to_str().unwrap()).unwrap();
-
-                libc::execve(path_cstring.as_ptr().cast(), argv.as_ptr(), envp.as_ptr())
-            }
         }
     }
 }
@@ -1238,7 +2198,16 @@ fn roc_dev_native(
                         let options = 0;
                         unsafe { libc::waitpid(pid, &mut status, options) };
 
-                        break status;
+                        // `status` packs the exit code together with other information,
+                        // so it has to be unpacked before we can exit with the same code.
+                        if libc::WIFEXITED(status) {
+                            break libc::WEXITSTATUS(status);
+                        } else if libc::WIFSIGNALED(status) {
+                            // The same convention shells use for programs killed by a signal
+                            break 128 + libc::WTERMSIG(status);
+                        } else {
+                            break 1;
+                        }
                     }
                     ChildProcessMsg::Expect => {
                         roc_repl_expect::run::render_expects_in_memory(
@@ -1338,48 +2307,33 @@ fn roc_run_executable_file_path(binary_bytes: &[u8]) -> std::io::Result<Executab
 /// Run on the native OS (not on wasm)
 #[cfg(not(target_family = "unix"))]
 fn roc_run_native<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
-    arena: &Bump, // This should be passed an owned value, not a reference, so we can usefully mem::forget it!
+    _arena: &Bump,
     opt_level: OptLevel,
     args: I,
     binary_bytes: &[u8],
     _expect_metadata: ExpectMetadata,
 ) -> io::Result<i32> {
-    use bumpalo::collections::CollectIn;
-
-    unsafe {
-        let executable = roc_run_executable_file_path(binary_bytes)?;
+    let executable = roc_run_executable_file_path(binary_bytes)?;
 
-        // TODO forward the arguments
-        let (argv_cstrings, envp_cstrings) = make_argv_envp(&arena, &executable, args);
-
-        let argv: bumpalo::collections::Vec<*const c_char> = argv_cstrings
-            .iter()
-            .map(|s| s.as_ptr())
-            .chain([std::ptr::null()])
-            .collect_in(arena);
-
-        let envp: bumpalo::collections::Vec<*const c_char> = envp_cstrings
-            .iter()
-            .map(|s| s.as_ptr())
-            .chain([std::ptr::null()])
-            .collect_in(arena);
-
-        match opt_level {
-            OptLevel::Development => {
-                // roc_run_native_debug(executable, &argv, &envp, expectations, interns)
-                internal_error!("running `expect`s does not currently work on windows")
-            }
-            OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => {
-                roc_run_native_fast(executable, &argv, &envp);
-            }
+    match opt_level {
+        OptLevel::Development => {
+            internal_error!("running `expect`s does not currently work on windows")
+        }
+        OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => {
+            // There is no way to replace the current process on Windows (its `execve` starts
+            // a new process and exits the current one right away), so wait for the program
+            // to finish instead, and then exit with the same code it did.
+            let status = std::process::Command::new(executable.as_path())
+                .args(args)
+                .status()?;
+
+            Ok(status.code().unwrap_or(1))
         }
     }
-
-    Ok(1)
 }
 
 #[cfg(feature = "run-wasm32")]
-fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(wasm_path: &std::path::Path, args: I) {
+fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(wasm_path: &std::path::Path, args: I) -> i32 {
     use bumpalo::collections::Vec;
     use roc_wasm_interp::{DefaultImportDispatcher, Instance};
 
@@ -1396,15 +2350,133 @@ fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(wasm_path: &std::path::Path,
 
     let mut instance = Instance::from_bytes(&arena, &bytes, import_dispatcher, false).unwrap();
 
-    instance
-        .call_export("_start", [])
-        .unwrap()
-        .unwrap()
-        .expect_i32()
-        .unwrap();
+    // If the program calls `proc_exit`, the interpreter exits the process with that code
+    // directly. Otherwise, use whatever `_start` returned, if anything.
+    match instance.call_export("_start", []) {
+        Ok(Some(value)) => value.expect_i32().unwrap_or(0),
+        Ok(None) => 0,
+        Err(message) => {
+            eprintln!("{message}");
+
+            1
+        }
+    }
 }
 
 #[cfg(not(feature = "run-wasm32"))]
-fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(_wasm_path: &std::path::Path, _args: I) {
+fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(_wasm_path: &std::path::Path, _args: I) -> i32 {
     println!("Running wasm files is not supported on this target.");
+
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_settings(flags: &[&str]) -> BuildSettings {
+        let matches = build_app()
+            .try_get_matches_from(
+                ["roc", CMD_BUILD]
+                    .iter()
+                    .chain(flags)
+                    .chain(["app.roc"].iter()),
+            )
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        BuildSettings::from_flags(matches, &BuildConfig::BuildOnly)
+    }
+
+    #[test]
+    fn no_profile() {
+        let settings = build_settings(&[]);
+
+        assert!(matches!(settings.opt_level, OptLevel::Normal));
+        assert!(!settings.use_dev_backend);
+        assert!(!settings.strip);
+        assert!(settings.emit_debug_info);
+    }
+
+    #[test]
+    fn dev_profile() {
+        let settings = build_settings(&["--profile=dev"]);
+
+        assert!(matches!(settings.opt_level, OptLevel::Development));
+        assert!(settings.use_dev_backend);
+        assert!(!settings.strip);
+        assert!(settings.emit_debug_info);
+    }
+
+    #[test]
+    fn release_profile() {
+        let settings = build_settings(&["--profile=release"]);
+
+        assert!(matches!(settings.opt_level, OptLevel::Optimize));
+        assert!(!settings.use_dev_backend);
+        assert!(!settings.strip);
+        assert!(!settings.emit_debug_info);
+    }
+
+    #[test]
+    fn size_profile() {
+        let settings = build_settings(&["--profile=size"]);
+
+        assert!(matches!(settings.opt_level, OptLevel::Size));
+        assert!(!settings.use_dev_backend);
+        assert!(settings.strip);
+        assert!(!settings.emit_debug_info);
+    }
+
+    #[test]
+    fn opt_level_flags_override_the_profile() {
+        let settings = build_settings(&["--profile=size", "--optimize"]);
+
+        assert!(matches!(settings.opt_level, OptLevel::Optimize));
+        // Everything else still comes from the profile
+        assert!(settings.strip);
+
+        // This keeps the dev backend, with the frontend optimizations
+        let settings = build_settings(&["--profile=dev", "--optimize"]);
+
+        assert!(matches!(settings.opt_level, OptLevel::Optimize));
+        assert!(settings.use_dev_backend);
+    }
+
+    #[test]
+    fn profiling_overrides_stripping_of_the_profile() {
+        let settings = build_settings(&["--profile=size", "--profiling"]);
+
+        assert!(matches!(settings.opt_level, OptLevel::Size));
+        assert!(!settings.strip);
+        assert!(settings.emit_debug_info);
+    }
+
+    #[test]
+    fn strip_overrides_debug_info_of_the_profile() {
+        let settings = build_settings(&["--profile=dev", "--strip"]);
+
+        assert!(settings.use_dev_backend);
+        assert!(settings.strip);
+        assert!(!settings.emit_debug_info);
+    }
+
+    #[test]
+    fn dev_flag_overrides_the_backend_of_the_profile() {
+        let settings = build_settings(&["--profile=release", "--dev"]);
+
+        assert!(matches!(settings.opt_level, OptLevel::Development));
+        assert!(settings.use_dev_backend);
+    }
+
+    #[test]
+    fn dev_and_run_ignores_the_opt_level_of_the_profile() {
+        let matches = build_app()
+            .try_get_matches_from(["roc", CMD_DEV, "--profile=release", "app.roc"])
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+        let settings = BuildSettings::from_flags(matches, &BuildConfig::BuildAndRunIfNoErrors);
+
+        assert!(matches!(settings.opt_level, OptLevel::Development));
+    }
 }
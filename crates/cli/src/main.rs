@@ -1,13 +1,18 @@
 //! The `roc` binary that brings together all functionality in the Roc toolset.
 use bumpalo::Bump;
 use roc_build::link::LinkType;
-use roc_build::program::{check_file, CodeGenBackend};
+use roc_build::program::{check_file, handle_loading_problem, CodeGenBackend};
 use roc_cli::{
-    build_app, format_files, format_src, test, BuildConfig, FormatMode, CMD_BUILD, CMD_CHECK,
-    CMD_DEV, CMD_DOCS, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_PREPROCESS_HOST, CMD_REPL,
-    CMD_RUN, CMD_TEST, CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_LIB,
-    FLAG_NO_LINK, FLAG_OUTPUT, FLAG_STDIN, FLAG_STDOUT, FLAG_TARGET, FLAG_TIME, GLUE_DIR,
-    GLUE_SPEC, ROC_FILE,
+    build_app, bundle, explain, format_files, format_src, info, interpret,
+    set_max_errors_from_flags, set_palette_from_flags, set_snippet_config_from_flags, test,
+    unified_diff, vendor, BuildConfig, FormatMode, FormatProblem, PackagesDir, CMD_BUILD,
+    CMD_BUNDLE, CMD_CHECK, CMD_DEV, CMD_DOCS, CMD_EXPLAIN, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE,
+    CMD_INFO, CMD_PREPROCESS_HOST, CMD_REPL, CMD_RUN, CMD_TEST, CMD_VENDOR, CMD_VERSION,
+    DIRECTORY_OR_FILES, EMIT_ASM, EMIT_LLVM_IR, EMIT_OBJECT, EMIT_WASM, FLAG_CHECK,
+    FLAG_DENY_WARNINGS, FLAG_DEV, FLAG_DIFF, FLAG_EMIT, FLAG_FIX, FLAG_INTERP, FLAG_LIB,
+    FLAG_MAX_WIDTH, FLAG_NO_LINK, FLAG_OUTPUT, FLAG_OUT_DIR, FLAG_SORT_HEADERS, FLAG_STDIN,
+    FLAG_STDIN_FILENAME, FLAG_STDOUT, FLAG_TARGET, FLAG_TIME, GLUE_DIR, GLUE_SPEC, REPORT_CODE,
+    ROC_FILE,
 };
 use roc_docs::generate_docs_html;
 use roc_error_macros::user_error;
@@ -15,6 +20,7 @@ use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
 use roc_load::{FunctionKind, LoadingProblem, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
+use roc_reporting::cli::{report_loading_problem_json, ProblemOutput};
 use roc_target::{get_target_triple_str, Target};
 use std::fs::{self, FileType};
 use std::io::{self, Read, Write};
@@ -30,7 +36,7 @@ static ALLOC: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 use std::ffi::{OsStr, OsString};
 
-use roc_cli::build;
+use roc_cli::{build, build_for_targets};
 
 fn main() -> io::Result<()> {
     let _tracing_guards = roc_tracing::setup_tracing!();
@@ -42,6 +48,10 @@ fn main() -> io::Result<()> {
         .collect();
     let matches = app.get_matches();
 
+    set_palette_from_flags(&matches);
+    set_snippet_config_from_flags(&matches);
+    set_max_errors_from_flags(&matches);
+
     let exit_code = match matches.subcommand() {
         None => {
             if matches.contains_id(ROC_FILE) {
@@ -51,7 +61,7 @@ fn main() -> io::Result<()> {
                     BuildConfig::BuildAndRunIfNoErrors,
                     Triple::host(),
                     None,
-                    RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+                    PackagesDir::from_matches(&matches).roc_cache_dir(),
                     LinkType::Executable,
                 )
             } else {
@@ -59,14 +69,16 @@ fn main() -> io::Result<()> {
             }
         }
         Some((CMD_RUN, matches)) => {
-            if matches.contains_id(ROC_FILE) {
+            if matches.get_flag(FLAG_INTERP) {
+                interpret(matches)
+            } else if matches.contains_id(ROC_FILE) {
                 build(
                     matches,
                     &subcommands,
                     BuildConfig::BuildAndRun,
                     Triple::host(),
                     None,
-                    RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+                    PackagesDir::from_matches(matches).roc_cache_dir(),
                     LinkType::Executable,
                 )
             } else {
@@ -92,7 +104,7 @@ fn main() -> io::Result<()> {
                     BuildConfig::BuildAndRunIfNoErrors,
                     Triple::host(),
                     None,
-                    RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+                    PackagesDir::from_matches(matches).roc_cache_dir(),
                     LinkType::Executable,
                 )
             } else {
@@ -166,34 +178,83 @@ fn main() -> io::Result<()> {
             Ok(0)
         }
         Some((CMD_BUILD, matches)) => {
-            let target = matches
-                .get_one::<String>(FLAG_TARGET)
-                .and_then(|s| Target::from_str(s).ok())
-                .unwrap_or_default();
-            let link_type = match (matches.get_flag(FLAG_LIB), matches.get_flag(FLAG_NO_LINK)) {
+            let emit = matches.get_one::<String>(FLAG_EMIT).map(String::as_str);
+            let mut targets: Vec<Target> = Vec::new();
+
+            // Build each target once, in the order they were first given.
+            for target in matches
+                .get_many::<String>(FLAG_TARGET)
+                .unwrap_or_default()
+                .map(|s| Target::from_str(s).unwrap())
+            {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+
+            if emit == Some(EMIT_WASM) {
+                match targets.as_slice() {
+                    [Target::System | Target::Wasm32] => targets = vec![Target::Wasm32],
+                    _ => user_error!("`--emit wasm` can only be used with `--target wasm32`"),
+                }
+            }
+            // Objects, LLVM IR, and assembly are written as they are, without linking.
+            let no_link = matches.get_flag(FLAG_NO_LINK)
+                || matches!(emit, Some(EMIT_OBJECT | EMIT_LLVM_IR | EMIT_ASM));
+            let link_type = match (matches.get_flag(FLAG_LIB), no_link) {
                 (true, false) => LinkType::Dylib,
-                (true, true) => user_error!("build can only be one of `--lib` or `--no-link`"),
+                (true, true) => user_error!(
+                    "build can only be one of `--lib` or `--no-link` (or `--emit object`, `llvm-ir`, or `asm`)"
+                ),
                 (false, true) => LinkType::None,
                 (false, false) => LinkType::Executable,
             };
-            let out_path = matches
-                .get_one::<OsString>(FLAG_OUTPUT)
-                .map(OsString::as_ref);
-
-            Ok(build(
-                matches,
-                &subcommands,
-                BuildConfig::BuildOnly,
-                target.to_triple(),
-                out_path,
-                RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
-                link_type,
-            )?)
+            let out_path = match matches.get_one::<PathBuf>(FLAG_OUT_DIR) {
+                Some(out_dir) => {
+                    // A path to a directory which exists is always treated as a directory,
+                    // so making sure it exists is all we need to do here.
+                    if let Err(err) = fs::create_dir_all(out_dir) {
+                        user_error!(
+                            "Unable to create the output directory {}: {err}",
+                            out_dir.display()
+                        );
+                    }
+
+                    Some(out_dir.as_path())
+                }
+                None => matches
+                    .get_one::<OsString>(FLAG_OUTPUT)
+                    .map(OsString::as_ref),
+            };
+
+            match targets.as_slice() {
+                [target] => Ok(build(
+                    matches,
+                    &subcommands,
+                    BuildConfig::BuildOnly,
+                    target.to_triple(),
+                    out_path,
+                    PackagesDir::from_matches(matches).roc_cache_dir(),
+                    link_type,
+                )?),
+                _ => Ok(build_for_targets(
+                    matches,
+                    &subcommands,
+                    &targets,
+                    out_path,
+                    PackagesDir::from_matches(matches).roc_cache_dir(),
+                    link_type,
+                )?),
+            }
         }
         Some((CMD_CHECK, matches)) => {
             let arena = Bump::new();
 
             let emit_timings = matches.get_flag(FLAG_TIME);
+            let problem_output = match matches.get_one::<String>(FLAG_OUTPUT).map(String::as_str) {
+                Some("json") => ProblemOutput::Json,
+                _ => ProblemOutput::Text,
+            };
             let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
             let threading = match matches.get_one::<usize>(roc_cli::FLAG_MAX_THREADS) {
                 None => Threading::AllAvailable,
@@ -206,24 +267,39 @@ fn main() -> io::Result<()> {
                 &arena,
                 roc_file_path.to_owned(),
                 emit_timings,
-                RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+                problem_output,
+                PackagesDir::from_matches(matches).roc_cache_dir(),
                 threading,
+                matches.get_flag(FLAG_FIX),
             ) {
                 Ok((problems, total_time)) => {
-                    problems.print_error_warning_count(total_time);
+                    let problems = problems.deny_warnings(matches.get_flag(FLAG_DENY_WARNINGS));
+
+                    if problem_output == ProblemOutput::Text {
+                        problems.print_error_warning_count(total_time);
+                    }
+
                     Ok(problems.exit_code())
                 }
+                Err(problem) => match problem_output {
+                    ProblemOutput::Text => handle_loading_problem(problem),
+                    ProblemOutput::Json => {
+                        let message = match problem {
+                            LoadingProblem::FormattedReport(report) => report,
+                            other => format!("{other:?}"),
+                        };
 
-                Err(LoadingProblem::FormattedReport(report)) => {
-                    print!("{report}");
+                        report_loading_problem_json(&message);
 
-                    Ok(1)
-                }
-                Err(other) => {
-                    panic!("build_file failed with error:\n{other:?}");
-                }
+                        Ok(1)
+                    }
+                },
             }
         }
+        Some((CMD_BUNDLE, matches)) => {
+            bundle(matches, PackagesDir::from_matches(matches).roc_cache_dir())
+        }
+        Some((CMD_VENDOR, matches)) => Ok(vendor(matches)),
         Some((CMD_REPL, _)) => Ok(roc_repl_cli::main()),
         Some((CMD_DOCS, matches)) => {
             let root_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
@@ -236,20 +312,21 @@ fn main() -> io::Result<()> {
         Some((CMD_FORMAT, matches)) => {
             let from_stdin = matches.get_flag(FLAG_STDIN);
             let to_stdout = matches.get_flag(FLAG_STDOUT);
+            let max_width = matches.get_one::<usize>(FLAG_MAX_WIDTH).copied();
+            let sort_headers = matches.get_flag(FLAG_SORT_HEADERS);
             let format_mode = if to_stdout {
                 FormatMode::WriteToStdout
+            } else if matches.get_flag(FLAG_DIFF) {
+                FormatMode::Diff
             } else {
                 match matches.get_flag(FLAG_CHECK) {
                     true => FormatMode::CheckOnly,
+                    // There's no file to write to, so the formatted input goes to stdout
+                    false if from_stdin => FormatMode::WriteToStdout,
                     false => FormatMode::WriteToFile,
                 }
             };
 
-            if from_stdin && matches!(format_mode, FormatMode::WriteToFile) {
-                eprintln!("When using the --stdin flag, either the --check or the --stdout flag must also be specified. (Otherwise, it's unclear what filename to write to!)");
-                std::process::exit(1);
-            }
-
             let roc_files = {
                 let mut roc_files = Vec::new();
 
@@ -288,20 +365,24 @@ fn main() -> io::Result<()> {
             let format_exit_code = if from_stdin {
                 let mut buf = Vec::new();
                 let arena = Bump::new();
+                let input_name = match matches.get_one::<PathBuf>(FLAG_STDIN_FILENAME) {
+                    Some(path) => path.display().to_string(),
+                    None => "<stdin>".to_string(),
+                };
 
                 io::stdin().read_to_end(&mut buf)?;
 
                 let src = std::str::from_utf8(&buf).unwrap_or_else(|err| {
-                    eprintln!("Stdin contained invalid UTF-8 bytes: {err:?}");
+                    eprintln!("{input_name} contained invalid UTF-8 bytes: {err:?}");
                     std::process::exit(1);
                 });
 
-                match format_src(&arena, src) {
+                match format_src(&arena, src, max_width, sort_headers) {
                     Ok(formatted_src) => {
                         match format_mode {
                             FormatMode::CheckOnly => {
-                                if src == formatted_src {
-                                    eprintln!("One or more files need to be reformatted.");
+                                if src != formatted_src {
+                                    eprintln!("{input_name} needs to be reformatted.");
                                     1
                                 } else {
                                     0
@@ -315,20 +396,34 @@ fn main() -> io::Result<()> {
 
                                 0
                             }
+                            FormatMode::Diff => {
+                                let diff = unified_diff(&input_name, src, &formatted_src);
+                                std::io::stdout().lock().write_all(diff.as_bytes()).unwrap();
+
+                                if diff.is_empty() {
+                                    0
+                                } else {
+                                    1
+                                }
+                            }
                             FormatMode::WriteToFile => {
-                                // We would have errored out already if you specified --stdin
-                                // without either --stdout or --check specified as well.
+                                // With --stdin, we always write to stdout unless --check
+                                // was specified.
                                 unreachable!()
                             }
                         }
                     }
+                    Err(FormatProblem::InvalidSource { parse_err }) => {
+                        eprintln!("Unexpected parse failure when parsing {input_name} for formatting:\n\n{parse_err}");
+                        1
+                    }
                     Err(problem) => {
-                        eprintln!("`roc format` failed: {problem:?}");
+                        eprintln!("`roc format` failed on {input_name}: {problem:?}");
                         1
                     }
                 }
             } else {
-                match format_files(roc_files, format_mode) {
+                match format_files(roc_files, format_mode, max_width, sort_headers) {
                     Ok(()) => 0,
                     Err(message) => {
                         eprintln!("{message}");
@@ -339,6 +434,10 @@ fn main() -> io::Result<()> {
 
             Ok(format_exit_code)
         }
+        Some((CMD_INFO, _)) => Ok(info()),
+        Some((CMD_EXPLAIN, matches)) => Ok(explain(
+            matches.get_one::<String>(REPORT_CODE).map(String::as_str),
+        )),
         Some((CMD_VERSION, _)) => {
             print!(
                 "{}",
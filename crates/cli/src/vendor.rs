@@ -0,0 +1,154 @@
+//! `roc vendor` copies the bundles of all the packages a project depends on into the project, so
+//! that it can be built without downloading anything. `--offline` builds don't download anything
+//! either, but rely on the packages being in the cache already.
+
+use crate::{FLAG_OFFLINE, ROC_FILE};
+use clap::ArgMatches;
+use roc_packaging::cache::{self, RocCacheDir};
+use roc_packaging::manifest::{self, MANIFEST_FILE_NAME};
+use roc_reporting::report::to_manifest_problem_report_string;
+use std::path::{Path, PathBuf};
+
+/// Where the packages for building a .roc file are loaded from: the vendor dir of its project,
+/// if `roc vendor` was run in it, and otherwise the cache dir.
+pub struct PackagesDir {
+    path: PathBuf,
+    offline: bool,
+}
+
+impl PackagesDir {
+    pub fn new(roc_file: &Path, offline: bool) -> Self {
+        match manifest::find_vendor_dir(&project_dir(roc_file)) {
+            // Vendored packages should be all the project needs, so nothing gets downloaded.
+            Some(path) => Self {
+                path,
+                offline: true,
+            },
+            None => Self {
+                path: cache::roc_cache_dir(),
+                offline,
+            },
+        }
+    }
+
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let roc_file = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+
+        Self::new(roc_file, matches.get_flag(FLAG_OFFLINE))
+    }
+
+    pub fn roc_cache_dir(&self) -> RocCacheDir<'_> {
+        if self.offline {
+            RocCacheDir::Offline(&self.path)
+        } else {
+            RocCacheDir::Persistent(&self.path)
+        }
+    }
+}
+
+pub fn vendor(matches: &ArgMatches) -> i32 {
+    let roc_file = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+    let cache_dir = cache::roc_cache_dir();
+    let roc_cache_dir = if matches.get_flag(FLAG_OFFLINE) {
+        RocCacheDir::Offline(&cache_dir)
+    } else {
+        RocCacheDir::Persistent(&cache_dir)
+    };
+
+    match manifest::vendor(&project_dir(roc_file), roc_cache_dir) {
+        Ok(Some((vendor_dir, vendored))) => {
+            for name in vendored {
+                println!("Vendored {name}");
+            }
+
+            println!(
+                "\nThe project's packages are in {}, and will be loaded from there from now on.",
+                vendor_dir.display()
+            );
+
+            0
+        }
+        Ok(None) => {
+            eprintln!(
+                "I could not find a {MANIFEST_FILE_NAME} in the directory of {}, or in any directory above it, so I don't know which packages the project uses.",
+                roc_file.display()
+            );
+
+            1
+        }
+        Err(problem) => {
+            eprint!(
+                "{}",
+                to_manifest_problem_report_string(problem, roc_file.to_path_buf())
+            );
+
+            1
+        }
+    }
+}
+
+/// The dir to look for the project's manifest from. This is absolute, so that the directories
+/// above the current one are searched too.
+fn project_dir(roc_file: &Path) -> PathBuf {
+    let dir = match roc_file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn packages_path(packages_dir: &PackagesDir) -> (PathBuf, bool) {
+        match packages_dir.roc_cache_dir() {
+            RocCacheDir::Offline(path) => (path.to_path_buf(), true),
+            RocCacheDir::Persistent(path) => (path.to_path_buf(), false),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn vendored_packages_are_loaded_offline() {
+        let project = tempfile::tempdir().unwrap();
+        let project_path = project.path().canonicalize().unwrap();
+        std::fs::write(project_path.join(MANIFEST_FILE_NAME), "{}").unwrap();
+        std::fs::create_dir(project_path.join(manifest::VENDOR_DIR_NAME)).unwrap();
+        std::fs::create_dir(project_path.join("src")).unwrap();
+
+        let packages_dir = PackagesDir::new(&project_path.join("src").join("main.roc"), false);
+
+        assert_eq!(
+            packages_path(&packages_dir),
+            (project_path.join(manifest::VENDOR_DIR_NAME), true)
+        );
+    }
+
+    #[test]
+    fn unvendored_packages_are_loaded_from_the_cache() {
+        let project = tempfile::tempdir().unwrap();
+        let project_path = project.path().canonicalize().unwrap();
+        std::fs::write(project_path.join(MANIFEST_FILE_NAME), "{}").unwrap();
+        let roc_file = project_path.join("main.roc");
+
+        assert_eq!(
+            packages_path(&PackagesDir::new(&roc_file, false)),
+            (cache::roc_cache_dir(), false)
+        );
+        assert_eq!(
+            packages_path(&PackagesDir::new(&roc_file, true)),
+            (cache::roc_cache_dir(), true)
+        );
+    }
+
+    #[test]
+    fn bare_file_name_searches_from_the_current_dir() {
+        assert_eq!(
+            project_dir(Path::new("main.roc")),
+            Path::new(".").canonicalize().unwrap()
+        );
+    }
+}
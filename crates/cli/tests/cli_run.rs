@@ -17,7 +17,9 @@ mod cli_run {
     use const_format::concatcp;
     use indoc::indoc;
     use regex::Regex;
-    use roc_cli::{CMD_BUILD, CMD_CHECK, CMD_DEV, CMD_FORMAT, CMD_RUN, CMD_TEST};
+    use roc_cli::{
+        CMD_BUILD, CMD_BUNDLE, CMD_CHECK, CMD_DEV, CMD_EXPLAIN, CMD_FORMAT, CMD_RUN, CMD_TEST,
+    };
     use roc_reporting::report::strip_colors;
     use roc_test_utils::assert_multiline_str_eq;
     use serial_test::serial;
@@ -57,6 +59,7 @@ mod cli_run {
     const PREBUILT_PLATFORM: &str = concatcp!("--", roc_cli::FLAG_PREBUILT);
     #[allow(dead_code)]
     const TARGET_FLAG: &str = concatcp!("--", roc_cli::FLAG_TARGET);
+    const TIME_FLAG: &str = concatcp!("--", roc_cli::FLAG_TIME);
 
     #[derive(Debug)]
     enum CliMode {
@@ -529,6 +532,42 @@ mod cli_run {
         )
     }
 
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn platform_switching_c_musl() {
+        let file = file_path_from_root("examples/platform-switching", "rocLovesC.roc");
+        let out_dir = tempfile::tempdir().unwrap();
+        let exe = out_dir.path().join("rocLovesC");
+        let output_flag = format!("--{}={}", roc_cli::FLAG_OUTPUT, exe.display());
+
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                concatcp!(TARGET_FLAG, "=linux-x64-musl"),
+                output_flag.as_str(),
+                file.to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+        assert!(out.status.success(), "{}", out.stderr);
+
+        // A static executable has no PT_INTERP program header naming a dynamic linker.
+        let elf = std::fs::read(&exe).unwrap();
+        let u16_at = |at: usize| u16::from_le_bytes([elf[at], elf[at + 1]]) as usize;
+        let program_headers = u64::from_le_bytes(elf[0x20..0x28].try_into().unwrap()) as usize;
+        let (header_size, header_count) = (u16_at(0x36), u16_at(0x38));
+        let has_interpreter = (0..header_count).any(|index| {
+            let at = program_headers + index * header_size;
+
+            u32::from_le_bytes(elf[at..at + 4].try_into().unwrap()) == 3
+        });
+        assert!(!has_interpreter, "{} is dynamically linked", exe.display());
+
+        let run = run_cmd(exe.to_str().unwrap(), [], &[], []);
+        assert_eq!(run.stdout, "Roc <3 C!\n");
+    }
+
     #[test]
     fn platform_switching_wasm() {
         test_roc_app_slim(
@@ -1401,11 +1440,12 @@ mod cli_run {
             &[],
             indoc!(
                 r#"
-                ── TYPE MISMATCH in tests/known_bad/TypeError.roc ──────────────────────────────
+                ── TYPE MISMATCH [E2001] in tests/known_bad/TypeError.roc ──────────────────────
 
                 Something is off with the body of the main definition:
 
                 6│  main : Str -> Task {} []
+                           -----------------
                 7│  main = /_ ->
                 8│      "this is a string, not a Task {} [] function like the platform expects."
                         ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
@@ -1430,6 +1470,495 @@ mod cli_run {
         );
     }
 
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn build_into_out_dir() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let out_dir = tempdir.path().join("artifacts").join("bin");
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                concatcp!("--", roc_cli::FLAG_OUT_DIR),
+                out_dir.to_str().unwrap(),
+                fixture_file("multi-dep-str", "Main.roc").to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(out.status.success(), "{}", out.stderr);
+        assert!(out_dir.join("Main").is_file(), "{}", out.stdout);
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn info_reports_the_host_backends_targets_and_cache() {
+        // A cache with a single package in it, which is the multi-dep-str app
+        let cache_home = tempfile::tempdir().unwrap();
+        let packages_dir = cache_home.path().join("roc").join("packages");
+        let package_dir = packages_dir.join("example.com").join("pkgs").join("hash");
+        let app = fixture_file("multi-dep-str", "Main.roc");
+
+        std::fs::create_dir_all(&package_dir).unwrap();
+        std::fs::copy(&app, package_dir.join("main.roc")).unwrap();
+
+        let app_size = std::fs::metadata(&app).unwrap().len();
+        let cache_home = cache_home.path().to_str().unwrap();
+        let out = run_roc([roc_cli::CMD_INFO], &[], &[("XDG_CACHE_HOME", cache_home)]);
+
+        assert!(out.status.success(), "{}", out.stderr);
+
+        let field = |name: &str| {
+            out.stdout
+                .lines()
+                .find_map(|line| line.trim().strip_prefix(name))
+                .unwrap_or_else(|| panic!("no {name} in\n{}", out.stdout))
+                .trim()
+                .to_string()
+        };
+
+        assert!(out.stdout.starts_with("roc "), "{}", out.stdout);
+        assert_eq!(
+            field("target triple"),
+            target_lexicon::Triple::host().to_string()
+        );
+        assert_eq!(field("LLVM"), "all targets");
+        assert_eq!(field("dev wasm (--dev)"), "wasm32");
+        assert!(field("wasm32").contains("linkers: "), "{}", out.stdout);
+        assert_eq!(field("location"), packages_dir.display().to_string());
+        assert_eq!(field("size"), format!("{app_size} B in 1 packages"));
+        assert_eq!(
+            field(&format!("{app_size} B")),
+            Path::new("example.com")
+                .join("pkgs")
+                .join("hash")
+                .display()
+                .to_string()
+        );
+    }
+
+    /// Runs roc with these arguments on an app of the exit-code fixture, whose host exits with
+    /// whatever the app's `main` is (or gets killed by that signal, when it's negative).
+    fn run_exit_code_app(args: &[&str], app: &str) -> Out {
+        let file = fixture_file("exit-code", app);
+
+        run_roc(
+            args.iter().copied().chain([file.to_str().unwrap()]),
+            &[],
+            &[],
+        )
+    }
+
+    #[test]
+    #[serial(exit_code)]
+    #[cfg_attr(windows, ignore)]
+    fn exit_code_of_the_program() {
+        for args in [&[][..], &[CMD_RUN], &[CMD_RUN, OPTIMIZE_FLAG], &[CMD_DEV]] {
+            let out = run_exit_code_app(args, "Exit.roc");
+
+            assert_eq!(out.status.code(), Some(42), "{args:?}\n{}", out.stderr);
+        }
+    }
+
+    #[test]
+    #[serial(exit_code)]
+    #[cfg_attr(windows, ignore)]
+    fn exit_code_of_a_crash() {
+        let out = run_exit_code_app(&[CMD_RUN], "Crash.roc");
+
+        assert_eq!(out.status.code(), Some(1), "{}", out.stderr);
+        assert!(
+            out.stderr.contains("this app crashes on purpose"),
+            "{}",
+            out.stderr
+        );
+    }
+
+    #[test]
+    #[serial(exit_code)]
+    #[cfg(unix)]
+    fn exit_code_of_a_program_killed_by_a_signal() {
+        use std::os::unix::process::ExitStatusExt;
+
+        // Without --optimize, roc waits for the program, and exits like a shell would.
+        let out = run_exit_code_app(&[CMD_RUN], "Killed.roc");
+        assert_eq!(out.status.code(), Some(128 + 9), "{}", out.stderr);
+
+        // With --optimize, roc becomes the program, so it gets killed itself.
+        let out = run_exit_code_app(&[CMD_RUN, OPTIMIZE_FLAG], "Killed.roc");
+        assert_eq!(out.status.signal(), Some(9), "{}", out.stderr);
+    }
+
+    #[test]
+    #[serial(exit_code)]
+    #[cfg_attr(windows, ignore)]
+    fn exit_code_of_errors() {
+        for args in [&[CMD_BUILD][..], &[CMD_DEV]] {
+            let out = run_exit_code_app(args, "TypeError.roc");
+
+            assert_eq!(out.status.code(), Some(1), "{args:?}\n{}", out.stdout);
+            assert!(out.stdout.contains("TYPE MISMATCH"), "{}", out.stdout);
+        }
+    }
+
+    #[test]
+    #[serial(exit_code)]
+    #[cfg_attr(windows, ignore)]
+    fn exit_code_of_warnings() {
+        let out = run_exit_code_app(&[CMD_BUILD], "Warning.roc");
+
+        assert_eq!(out.status.code(), Some(2), "{}", out.stdout);
+        assert!(out.stdout.contains("UNUSED DEFINITION"), "{}", out.stdout);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn surgical_linker_rejects_strip() {
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                concatcp!(LINKER_FLAG, "=surgical"),
+                concatcp!("--", roc_cli::FLAG_STRIP),
+                fixture_file("multi-dep-str", "Main.roc").to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(!out.status.success(), "{}", out.stdout);
+        assert!(
+            out.stderr
+                .contains("The surgical linker cannot strip the host"),
+            "{}",
+            out.stderr
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn surgical_linker_rejects_a_platform_without_a_surgical_host() {
+        // A prebuilt copy of the multi-dep-str platform, which only has a host for the legacy
+        // linker. The build stops before that host is used, so it can be empty.
+        let tempdir = tempfile::tempdir().unwrap();
+        let fixture = fixtures_dir("multi-dep-str");
+
+        std::fs::create_dir(tempdir.path().join("platform")).unwrap();
+
+        for file in ["Main.roc", "Dep1.roc", "Dep2.roc", "platform/main.roc"] {
+            std::fs::copy(fixture.join(file), tempdir.path().join(file)).unwrap();
+        }
+
+        std::fs::write(tempdir.path().join("platform").join("linux-x64.a"), []).unwrap();
+
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                concatcp!(LINKER_FLAG, "=surgical"),
+                PREBUILT_PLATFORM,
+                tempdir.path().join("Main.roc").to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(!out.status.success(), "{}", out.stdout);
+        assert!(
+            out.stderr
+                .contains("this prebuilt platform does not have a preprocessed host"),
+            "{}",
+            out.stderr
+        );
+    }
+
+    /// Builds an app into an empty directory with `--emit`, and returns the names and contents
+    /// of the files that the build wrote there.
+    fn build_emitting(emit: &str, app: &Path) -> (Out, Vec<(String, Vec<u8>)>) {
+        let tempdir = tempfile::tempdir().unwrap();
+        let emit_flag = format!("--{}={emit}", roc_cli::FLAG_EMIT);
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                emit_flag.as_str(),
+                concatcp!("--", roc_cli::FLAG_OUT_DIR),
+                tempdir.path().to_str().unwrap(),
+                app.to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        let mut files: Vec<_> = std::fs::read_dir(tempdir.path())
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+
+                (name, std::fs::read(&path).unwrap())
+            })
+            .collect();
+        files.sort();
+
+        (out, files)
+    }
+
+    fn file_names(files: &[(String, Vec<u8>)]) -> Vec<&str> {
+        files.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn emit_executable() {
+        let (out, files) = build_emitting(
+            roc_cli::EMIT_EXECUTABLE,
+            &fixture_file("multi-dep-str", "Main.roc"),
+        );
+
+        assert!(out.status.success(), "{}{}", out.stdout, out.stderr);
+        assert_eq!(file_names(&files), ["Main"]);
+    }
+
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn emit_object() {
+        let (out, files) = build_emitting(
+            roc_cli::EMIT_OBJECT,
+            &fixture_file("multi-dep-str", "Main.roc"),
+        );
+
+        assert!(out.status.success(), "{}{}", out.stdout, out.stderr);
+        assert_eq!(file_names(&files), ["Main.o"]);
+    }
+
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn emit_llvm_ir() {
+        let (out, files) = build_emitting(
+            roc_cli::EMIT_LLVM_IR,
+            &fixture_file("multi-dep-str", "Main.roc"),
+        );
+
+        assert!(out.status.success(), "{}{}", out.stdout, out.stderr);
+        // Only the IR is written; nothing gets linked.
+        assert_eq!(file_names(&files), ["Main.ll"]);
+
+        let ir = String::from_utf8_lossy(&files[0].1);
+        assert!(ir.contains("define "), "{ir}");
+    }
+
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn emit_asm() {
+        let (out, files) = build_emitting(
+            roc_cli::EMIT_ASM,
+            &fixture_file("multi-dep-str", "Main.roc"),
+        );
+
+        assert!(out.status.success(), "{}{}", out.stdout, out.stderr);
+        // The assembly goes to the --out-dir rather than next to the .roc file, and nothing
+        // gets linked.
+        assert_eq!(file_names(&files), ["Main.s"]);
+
+        let asm = String::from_utf8_lossy(&files[0].1);
+        assert!(asm.contains(".text"), "{asm}");
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn emit_wasm() {
+        let (out, files) = build_emitting(
+            roc_cli::EMIT_WASM,
+            &file_path_from_root("examples/platform-switching", "rocLovesWebAssembly.roc"),
+        );
+
+        assert!(out.status.success(), "{}{}", out.stdout, out.stderr);
+        assert_eq!(file_names(&files), ["rocLovesWebAssembly.wasm"]);
+        assert!(files[0].1.starts_with(b"\0asm"));
+    }
+
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn build_for_multiple_targets() {
+        let tempdir = tempfile::tempdir().unwrap();
+        // This directory doesn't exist yet, so the build has to create it.
+        let out_dir = tempdir.path().join("artifacts");
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                concatcp!("--", roc_cli::FLAG_NO_LINK),
+                concatcp!(TARGET_FLAG, "=system"),
+                concatcp!(TARGET_FLAG, "=wasm32"),
+                concatcp!(TARGET_FLAG, "=system"),
+                concatcp!("--", roc_cli::FLAG_OUTPUT),
+                out_dir.to_str().unwrap(),
+                fixture_file("multi-dep-str", "Main.roc").to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(out.status.success(), "{}{}", out.stdout, out.stderr);
+
+        // Repeated targets are only built once, even when they aren't next to each other.
+        assert_eq!(out.stdout.matches("Building for system").count(), 1);
+        assert_eq!(out.stdout.matches("Building for wasm32").count(), 1);
+
+        for target in ["system", "wasm32"] {
+            let built = std::fs::read_dir(out_dir.join(target))
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .any(|path| path.file_stem().and_then(|stem| stem.to_str()) == Some("Main"));
+
+            assert!(built, "nothing was built for {target}\n{}", out.stdout);
+        }
+    }
+
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn reproducible_build() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                concatcp!("--", roc_cli::FLAG_REPRODUCIBLE),
+                concatcp!("--", roc_cli::FLAG_OUT_DIR),
+                tempdir.path().to_str().unwrap(),
+                fixture_file("multi-dep-str", "Main.roc").to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(out.status.success(), "{}{}", out.stdout, out.stderr);
+        assert!(
+            out.stdout.contains("so this build is reproducible"),
+            "{}",
+            out.stdout
+        );
+    }
+
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn emit_bloat_report() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                concatcp!("--", roc_cli::FLAG_EMIT_BLOAT_REPORT),
+                concatcp!("--", roc_cli::FLAG_OUT_DIR),
+                tempdir.path().to_str().unwrap(),
+                fixture_file("multi-dep-str", "Main.roc").to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(out.status.success(), "{}{}", out.stdout, out.stderr);
+
+        let report_path = std::fs::read_dir(tempdir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.to_string_lossy().ends_with(".bloat.txt"))
+            .expect("no bloat report was written");
+        let report = std::fs::read_to_string(report_path).unwrap();
+
+        assert!(report.contains("Roc functions"), "{report}");
+        assert!(report.contains("Largest functions:"), "{report}");
+        assert!(report.contains("#UserApp_"), "{report}");
+    }
+
+    #[test]
+    fn check_time_reports_phases() {
+        let out = run_roc(
+            [
+                CMD_CHECK,
+                TIME_FLAG,
+                fixture_file("multi-dep-str", "Main.roc").to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(out.status.success(), "{}", out.stderr);
+        assert!(
+            out.stdout.contains("Here's how long each phase took"),
+            "{}",
+            out.stdout
+        );
+
+        for phase in [
+            "Parse",
+            "Canonicalize",
+            "Constrain",
+            "Solve",
+            "Total (wall time)",
+        ] {
+            assert!(out.stdout.contains(phase), "{}", out.stdout);
+        }
+
+        // `roc check` never gets as far as code generation.
+        assert!(!out.stdout.contains("Code generation"), "{}", out.stdout);
+    }
+
+    #[test]
+    fn known_type_error_json_output() {
+        let out = run_roc(
+            [
+                CMD_CHECK,
+                known_bad_file("TypeError.roc").to_str().unwrap(),
+                "--output=json",
+            ],
+            &[],
+            &[],
+        );
+
+        assert_eq!(out.status.code(), Some(1));
+
+        let stdout = out.stdout.trim();
+
+        assert!(stdout.starts_with("{\"diagnostics\":["), "{stdout}");
+        assert!(stdout.contains("\"severity\":\"error\""), "{stdout}");
+        assert!(stdout.contains("\"title\":\"TYPE MISMATCH\""), "{stdout}");
+        assert!(stdout.contains("\"errors\":1"), "{stdout}");
+        // No ANSI colors in machine-readable output
+        assert!(!stdout.contains('\u{1b}'), "{stdout}");
+    }
+
+    #[test]
+    fn check_time_keeps_json_output_parseable() {
+        let out = run_roc(
+            [
+                CMD_CHECK,
+                TIME_FLAG,
+                known_bad_file("TypeError.roc").to_str().unwrap(),
+                "--output=json",
+            ],
+            &[],
+            &[],
+        );
+
+        let json: serde_json::Value = serde_json::from_str(out.stdout.trim())
+            .unwrap_or_else(|err| panic!("stdout is not JSON ({err}):\n{}", out.stdout));
+
+        assert_eq!(
+            json["diagnostics"].as_array().map(Vec::len),
+            Some(1),
+            "{json}"
+        );
+        assert!(
+            out.stderr.contains("Here's how long each phase took"),
+            "{}",
+            out.stderr
+        );
+    }
+
     #[test]
     fn known_type_error_with_long_path() {
         check_compile_error(
@@ -1437,7 +1966,7 @@ mod cli_run {
             &[],
             indoc!(
                 r#"
-                ── UNUSED IMPORT in ...nown_bad/UnusedImportButWithALongFileNameForTesting.roc ─
+                ── UNUSED IMPORT [E1002] in .../UnusedImportButWithALongFileNameForTesting.roc ─
 
                 Nothing from Symbol is used in this module.
 
@@ -1453,6 +1982,33 @@ mod cli_run {
         );
     }
 
+    #[test]
+    fn snippet_context_and_width() {
+        check_compile_error(
+            &known_bad_file("UnusedImportButWithALongFileNameForTesting.roc"),
+            &["--context-lines=2", "--max-snippet-width=20"],
+            indoc!(
+                r#"
+                ── UNUSED IMPORT [E1002] in .../UnusedImportButWithALongFileNameForTesting.roc ─
+
+                Nothing from Symbol is used in this module.
+
+                1│  interface UnusedImpo…
+                2│      exposes [plainTe…
+                3│      imports [Symbol.…
+                 │               ^^^^^^^
+                4│
+                5│  plainText = \str -> …
+
+                Since Symbol isn't used, you don't need to import it.
+
+                ────────────────────────────────────────────────────────────────────────────────
+
+                0 errors and 1 warning found in <ignored for test> ms."#
+            ),
+        );
+    }
+
     #[test]
     fn exposed_not_defined() {
         check_compile_error(
@@ -1460,7 +2016,7 @@ mod cli_run {
             &[],
             indoc!(
                 r#"
-                ── MISSING DEFINITION in tests/known_bad/ExposedNotDefined.roc ─────────────────
+                ── MISSING DEFINITION [E1004] in tests/known_bad/ExposedNotDefined.roc ─────────
 
                 bar is listed as exposed, but it isn't defined in this module.
 
@@ -1481,7 +2037,7 @@ mod cli_run {
             &[],
             indoc!(
                 r#"
-                ── UNUSED IMPORT in tests/known_bad/UnusedImport.roc ───────────────────────────
+                ── UNUSED IMPORT [E1002] in tests/known_bad/UnusedImport.roc ───────────────────
 
                 Nothing from Symbol is used in this module.
 
@@ -1497,6 +2053,232 @@ mod cli_run {
         );
     }
 
+    #[test]
+    fn unused_import_with_deny_warnings() {
+        let file = known_bad_file("UnusedImport.roc");
+
+        let out = run_roc([CMD_CHECK, file.to_str().unwrap()], &[], &[]);
+        assert_eq!(out.status.code(), Some(2));
+
+        let out = run_roc(
+            [CMD_CHECK, file.to_str().unwrap(), "--deny-warnings"],
+            &[],
+            &[],
+        );
+        assert_eq!(out.status.code(), Some(1));
+
+        let stdout = strip_colors(out.stdout.trim());
+        assert!(
+            stdout.contains("0 errors and 1 warning (denied by --deny-warnings) found in"),
+            "{stdout}"
+        );
+    }
+
+    #[test]
+    fn shadowing_cascade_is_left_out() {
+        let file = known_bad_file("ShadowingCascade.roc");
+        let out = run_roc([CMD_CHECK, file.to_str().unwrap()], &[], &[]);
+
+        let stdout = strip_colors(out.stdout.trim());
+        assert!(stdout.contains("DUPLICATE NAME"), "{stdout}");
+        // Only `farewell`'s mistake is reported; its `name` is a different variable than the
+        // shadowed one in `greeting`.
+        assert_eq!(stdout.matches("TYPE MISMATCH").count(), 1, "{stdout}");
+        assert!(stdout.contains("farewell"), "{stdout}");
+        assert!(
+            stdout.contains(
+                "I left out 1 more problem, because it is probably caused by the problems above."
+            ),
+            "{stdout}"
+        );
+    }
+
+    #[test]
+    fn shadowing_cascade_is_left_out_of_json_output() {
+        let file = known_bad_file("ShadowingCascade.roc");
+        let out = run_roc(
+            [CMD_CHECK, file.to_str().unwrap(), "--output=json"],
+            &[],
+            &[],
+        );
+
+        let stdout = out.stdout.trim();
+        assert_eq!(
+            stdout.matches("\"title\":\"TYPE MISMATCH\"").count(),
+            1,
+            "{stdout}"
+        );
+        assert!(stdout.contains("\"left_out\":1"), "{stdout}");
+    }
+
+    #[test]
+    fn check_fix_removes_unused_imports() {
+        let tempdir = tempfile::tempdir().unwrap();
+
+        for file in ["UnusedImport.roc", "Symbol.roc"] {
+            std::fs::copy(known_bad_file(file), tempdir.path().join(file)).unwrap();
+        }
+
+        let file = tempdir.path().join("UnusedImport.roc");
+        let out = run_roc(
+            [
+                CMD_CHECK,
+                file.to_str().unwrap(),
+                concatcp!("--", roc_cli::FLAG_FIX),
+            ],
+            &[],
+            &[],
+        );
+
+        let stdout = strip_colors(out.stdout.trim());
+        assert!(stdout.contains("Fixed "), "{stdout}");
+        assert!(
+            stdout.contains("0 errors and 0 warnings found in"),
+            "{stdout}"
+        );
+
+        let fixed = std::fs::read_to_string(&file).unwrap();
+        assert!(!fixed.contains("Ident"), "{fixed}");
+    }
+
+    #[test]
+    fn reports_are_ordered_by_position() {
+        let file = known_bad_file("MultipleWarnings.roc");
+        let out = run_roc([CMD_CHECK, file.to_str().unwrap()], &[], &[]);
+
+        let stdout = strip_colors(out.stdout.trim());
+        let position = |needle: &str| {
+            stdout
+                .find(needle)
+                .unwrap_or_else(|| panic!("{needle} is missing from:\n{stdout}"))
+        };
+
+        assert!(
+            position("UNUSED IMPORT") < position("unusedFirst"),
+            "{stdout}"
+        );
+        assert!(
+            position("unusedFirst") < position("unusedSecond"),
+            "{stdout}"
+        );
+    }
+
+    #[test]
+    fn problems_are_summarized_by_file() {
+        let file = known_bad_file("ErrorsInTwoFiles.roc");
+        let out = run_roc([CMD_CHECK, file.to_str().unwrap()], &[], &[]);
+
+        let stdout = strip_colors(out.stdout.trim());
+
+        assert!(stdout.contains("Problems by file:"), "{stdout}");
+        assert!(
+            stdout.contains("tests/known_bad/BrokenDependency.roc: 1 error and 0 warnings"),
+            "{stdout}"
+        );
+        assert!(
+            stdout.contains("tests/known_bad/ErrorsInTwoFiles.roc: 1 error and 0 warnings"),
+            "{stdout}"
+        );
+    }
+
+    #[test]
+    fn max_errors_stops_early() {
+        let file = known_bad_file("ErrorsInTwoFiles.roc");
+        let out = run_roc(
+            [CMD_CHECK, file.to_str().unwrap(), "--max-errors=1"],
+            &[],
+            &[],
+        );
+
+        let stdout = strip_colors(out.stdout.trim());
+
+        assert_eq!(stdout.matches("── TYPE MISMATCH").count(), 1, "{stdout}");
+        assert!(
+            stdout.contains("There is 1 more error, which I left out because of --max-errors."),
+            "{stdout}"
+        );
+        // The summary still counts the errors that were left out.
+        assert!(stdout.contains("Problems by file:"), "{stdout}");
+    }
+
+    #[test]
+    fn no_colors_with_color_never() {
+        let file = known_bad_file("UnusedImport.roc");
+        let out = run_roc(
+            [CMD_CHECK, file.to_str().unwrap(), "--color=never"],
+            &[],
+            &[],
+        );
+
+        assert!(out.stdout.contains("UNUSED IMPORT"), "{}", out.stdout);
+        assert!(!out.stdout.contains('\u{1b}'), "{}", out.stdout);
+    }
+
+    #[test]
+    fn explain_report_code() {
+        let out = run_roc([CMD_EXPLAIN, "e2001"], &[], &[]);
+
+        assert!(out.status.success(), "{}", out.stderr);
+        assert!(out.stdout.contains("TYPE MISMATCH"), "{}", out.stdout);
+        assert!(out.stdout.contains("Code: E2001"), "{}", out.stdout);
+
+        let out = run_roc([CMD_EXPLAIN, "E20011"], &[], &[]);
+
+        assert_eq!(out.status.code(), Some(1));
+        assert!(out.stderr.contains("Did you mean E2001?"), "{}", out.stderr);
+    }
+
+    #[test]
+    fn run_with_deny_warnings() {
+        let file = known_bad_file("UnusedDef.roc");
+
+        let out = run_roc(
+            [CMD_RUN, "--deny-warnings", file.to_str().unwrap()],
+            &[],
+            &[],
+        );
+        assert_eq!(out.status.code(), Some(1));
+
+        let stdout = strip_colors(out.stdout.trim());
+        assert!(
+            stdout.contains("Not running the program, because warnings are denied."),
+            "{stdout}"
+        );
+        assert!(!stdout.contains("should not be printed"), "{stdout}");
+    }
+
+    #[test]
+    fn test_with_deny_warnings() {
+        let file = known_bad_file("UnusedDef.roc");
+
+        let out = run_roc(
+            [CMD_TEST, "--deny-warnings", file.to_str().unwrap()],
+            &[],
+            &[],
+        );
+        assert_eq!(out.status.code(), Some(1));
+
+        let stdout = strip_colors(out.stdout.trim());
+        assert!(
+            stdout.contains("Not running tests, because warnings are denied."),
+            "{stdout}"
+        );
+    }
+
+    #[test]
+    fn test_with_errors() {
+        let file = known_bad_file("TypeError.roc");
+
+        let out = run_roc([CMD_TEST, file.to_str().unwrap()], &[], &[]);
+        assert_eq!(out.status.code(), Some(1));
+
+        let stdout = strip_colors(out.stdout.trim());
+        assert!(
+            stdout.contains("Not running tests, because there are errors."),
+            "{stdout}"
+        );
+    }
+
     #[test]
     fn unknown_generates_with() {
         check_compile_error(
@@ -1539,6 +2321,119 @@ mod cli_run {
         // This doesn't fail, since only "Formatted.roc" and non-roc files are present in this folder
         check_format_check_as_expected(&fixtures_dir("format/formatted_directory"), true);
     }
+
+    #[test]
+    fn format_check_lists_unformatted_files() {
+        let out = run_roc(
+            [
+                CMD_FORMAT,
+                fixtures_dir("format").to_str().unwrap(),
+                CHECK_FLAG,
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(!out.status.success());
+        assert!(out.stderr.contains("NotFormatted.roc"));
+    }
+
+    #[test]
+    fn format_check_with_max_width() {
+        let file = fixture_file("format", "Formatted.roc");
+        let check_with_max_width = |max_width: &str| {
+            run_roc(
+                [
+                    CMD_FORMAT,
+                    file.to_str().unwrap(),
+                    CHECK_FLAG,
+                    "--max-width",
+                    max_width,
+                ],
+                &[],
+                &[],
+            )
+        };
+
+        assert!(check_with_max_width("80").status.success());
+        assert!(!check_with_max_width("20").status.success());
+    }
+
+    #[test]
+    fn format_stdin_to_stdout() {
+        let formatted = std::fs::read_to_string(fixture_file("format", "Formatted.roc")).unwrap();
+        let out = run_roc([CMD_FORMAT, "--stdin"], &[formatted.as_str()], &[]);
+
+        assert!(out.status.success(), "{}", out.stderr);
+        assert_eq!(out.stdout, formatted);
+    }
+
+    #[test]
+    fn format_check_stdin_with_filename() {
+        let not_formatted =
+            std::fs::read_to_string(fixture_file("format", "NotFormatted.roc")).unwrap();
+        let out = run_roc(
+            [
+                CMD_FORMAT,
+                "--stdin",
+                "--stdin-filename",
+                "NotFormatted.roc",
+                CHECK_FLAG,
+            ],
+            &[not_formatted.as_str()],
+            &[],
+        );
+
+        assert!(!out.status.success());
+        assert!(out
+            .stderr
+            .contains("NotFormatted.roc needs to be reformatted."));
+    }
+
+    #[test]
+    fn format_diff_without_writing() {
+        let file = fixture_file("format", "NotFormatted.roc");
+        let before = std::fs::read_to_string(&file).unwrap();
+        let out = run_roc([CMD_FORMAT, file.to_str().unwrap(), "--diff"], &[], &[]);
+
+        assert!(!out.status.success());
+        assert!(out
+            .stdout
+            .contains("-  provides [main] to pf\n+    provides [main] to pf\n"));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), before);
+    }
+
+    #[test]
+    fn format_diff_formatted_file() {
+        let file = fixture_file("format", "Formatted.roc");
+        let out = run_roc([CMD_FORMAT, file.to_str().unwrap(), "--diff"], &[], &[]);
+
+        assert!(out.status.success(), "{}", out.stderr);
+        assert_eq!(out.stdout, "");
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn bundle_rejects_absolute_path_imports() {
+        let out = run_roc(
+            [
+                CMD_BUNDLE,
+                fixture_file("absolute-path-package", "main.roc")
+                    .to_str()
+                    .unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert_eq!(out.status.code(), Some(1));
+        assert!(out.stderr.contains("Greeting.roc"), "{}", out.stderr);
+        assert!(
+            out.stderr.contains("/etc/roc/greeting.txt"),
+            "{}",
+            out.stderr
+        );
+    }
 }
 
 #[cfg(feature = "wasm32-cli-run")]
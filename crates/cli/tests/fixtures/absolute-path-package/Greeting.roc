@@ -0,0 +1,5 @@
+interface Greeting
+    exposes [greeting]
+    imports [
+        "/etc/roc/greeting.txt" as greeting : Str,
+    ]
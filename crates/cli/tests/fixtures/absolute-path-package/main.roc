@@ -0,0 +1,3 @@
+package "absolute"
+    exposes [Greeting]
+    packages {}
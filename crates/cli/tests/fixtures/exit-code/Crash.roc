@@ -0,0 +1,7 @@
+app "crash"
+    packages { pf: "platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+main : I32
+main = crash "this app crashes on purpose"
@@ -0,0 +1,7 @@
+app "exit"
+    packages { pf: "platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+main : I32
+main = 42
@@ -0,0 +1,8 @@
+app "killed"
+    packages { pf: "platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+# Stands for SIGKILL; see the platform's host
+main : I32
+main = -9
@@ -0,0 +1,7 @@
+app "typeerror"
+    packages { pf: "platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+main : I32
+main = "not an exit code"
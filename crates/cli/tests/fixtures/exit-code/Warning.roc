@@ -0,0 +1,10 @@
+app "warning"
+    packages { pf: "platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+main : I32
+main =
+    unused = 1
+
+    42
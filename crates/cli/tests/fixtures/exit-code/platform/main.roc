@@ -0,0 +1,9 @@
+platform "exit-code"
+    requires {}{ main : I32 }
+    exposes []
+    packages {}
+    imports []
+    provides [mainForHost]
+
+mainForHost : I32
+mainForHost = main
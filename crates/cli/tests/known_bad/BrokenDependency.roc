@@ -0,0 +1,7 @@
+interface BrokenDependency
+    exposes [name]
+    imports []
+
+# NOTE: this module has a type error, and is imported by ErrorsInTwoFiles.roc
+name : Str
+name = 42
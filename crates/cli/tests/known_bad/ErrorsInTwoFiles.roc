@@ -0,0 +1,9 @@
+interface ErrorsInTwoFiles
+    exposes [greeting, count]
+    imports [BrokenDependency]
+
+greeting : Str
+greeting = BrokenDependency.name
+
+count : Str
+count = 2
@@ -0,0 +1,9 @@
+interface MultipleWarnings
+    exposes [greeting]
+    imports [Symbol.{ Ident }]
+
+greeting =
+    unusedFirst = 1
+    unusedSecond = 2
+
+    "Hello!"
@@ -0,0 +1,18 @@
+interface ShadowingCascade
+    exposes [greeting, farewell]
+    imports []
+
+greeting =
+    name = "Sam"
+
+    count =
+        name = 42
+
+        name + 1
+
+    "Hello, \(name)! \(Num.toStr count)"
+
+farewell =
+    name = "Bye"
+
+    name + 1
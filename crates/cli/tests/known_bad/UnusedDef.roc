@@ -0,0 +1,9 @@
+app "unused-def"
+    packages { pf: "../../../../examples/platform-switching/c-platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+main =
+    unused = 1
+
+    "This should not be printed when warnings are denied."
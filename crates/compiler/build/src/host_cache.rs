@@ -0,0 +1,453 @@
+//! Avoids rebuilding a platform's host when nothing it is built from has changed.
+//!
+//! The artifacts of every host build are stored in the roc cache directory, keyed by a hash of
+//! the platform's files and of everything else that goes into the build: the target, the
+//! optimization level, the symbols the app provides, and the builtins that get linked in.
+//! Switching back and forth between e.g. `--optimize` and development builds then doesn't
+//! rebuild the host either.
+//!
+//! Besides the platform's directory, the directories of a Rust host's Cargo path dependencies
+//! (like `roc_std = { path = "..." }`) are hashed as well. A host that depends on other files
+//! outside of the platform's directory can be stale; set [ROC_NO_HOST_CACHE] to always rebuild
+//! it. Platforms with a build hook choose which files are hashed, or opt out of caching
+//! altogether; see [crate::host_hook].
+use crate::host_hook::HostHook;
+use crate::link::{legacy_host_file, LinkingStrategy};
+use roc_mono::ir::OptLevel;
+use serde::Deserialize;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use target_lexicon::{Architecture, Triple};
+
+/// When this environment variable is set, hosts are always rebuilt.
+pub const ROC_NO_HOST_CACHE: &str = "ROC_NO_HOST_CACHE";
+
+/// Directories that the host's own build tools write their outputs to, besides hidden ones.
+const IGNORED_DIRS: &[&str] = &["target", "zig-cache", "zig-out"];
+
+/// Build outputs, which would otherwise change the hash on every build.
+const IGNORED_EXTENSIONS: &[&str] = &[
+    "o", "a", "obj", "lib", "exe", "pdb", "bc", "so", "dylib", "dll", "wasm", "rh", "rm",
+];
+const IGNORED_FILE_NAMES: &[&str] = &["dynhost"];
+
+pub struct HostCacheKey(blake3::Hash);
+
+impl HostCacheKey {
+    pub fn new(
+        opt_level: OptLevel,
+        linking_strategy: LinkingStrategy,
+        target: &Triple,
+        platform_main_roc: &Path,
+        stub_dll_symbols: &[String],
+    ) -> io::Result<Self> {
+        let mut hasher = blake3::Hasher::new();
+
+        // Hosts built by another version of roc may have been built differently.
+        hash_str(&mut hasher, env!("CARGO_PKG_VERSION"));
+        hasher.update(roc_bitcode::host_bytes());
+
+        hash_str(&mut hasher, &format!("{opt_level:?}"));
+        hash_str(&mut hasher, &format!("{linking_strategy:?}"));
+        hash_str(&mut hasher, &target.to_string());
+
+        hasher.update(&(stub_dll_symbols.len() as u64).to_le_bytes());
+        for symbol in stub_dll_symbols {
+            hash_str(&mut hasher, symbol);
+        }
+
+        let platform_dir = platform_main_roc.parent().unwrap_or_else(|| Path::new("."));
+
+        // A platform with a build hook says which files its host is built from.
+        let input_paths = match HostHook::find(platform_main_roc) {
+            Some(hook) => hook.input_paths(platform_dir),
+            None if platform_dir.join("Cargo.toml").exists() => {
+                let mut paths = vec![platform_dir.to_path_buf()];
+                paths.extend(cargo_path_dependencies(platform_dir)?);
+
+                paths
+            }
+            None => vec![platform_dir.to_path_buf()],
+        };
+
+        for input_path in input_paths {
+            let walker = walkdir::WalkDir::new(input_path)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|entry| {
+                    entry.depth() == 0 || !is_ignored(entry.path(), entry.file_type().is_dir())
+                });
+
+            for entry in walker {
+                let entry = entry?;
+
+                if entry.file_type().is_file() {
+                    let relative_path = entry
+                        .path()
+                        .strip_prefix(platform_dir)
+                        .unwrap_or(entry.path());
+
+                    hash_str(&mut hasher, &relative_path.to_string_lossy());
+                    hash_bytes(&mut hasher, &std::fs::read(entry.path())?);
+                }
+            }
+        }
+
+        Ok(Self(hasher.finalize()))
+    }
+
+    /// Copies the cached artifacts to the given paths. Returns false if they are not cached,
+    /// in which case the host needs to be rebuilt.
+    pub fn restore(&self, artifacts: &[PathBuf]) -> bool {
+        self.restore_from(&hosts_dir(), artifacts)
+    }
+
+    /// Stores the artifacts of a host build, so that the next build with the same inputs can
+    /// restore them.
+    pub fn store(&self, artifacts: &[PathBuf]) -> io::Result<()> {
+        self.store_in(&hosts_dir(), artifacts)
+    }
+
+    fn restore_from(&self, hosts_dir: &Path, artifacts: &[PathBuf]) -> bool {
+        let dir = hosts_dir.join(self.0.to_hex().as_str());
+
+        // Copy every artifact into a temporary file beside its destination first, and only move
+        // them into place once all of them were copied. That way a missing artifact or an
+        // interrupted copy never leaves a truncated host, or a host without its metadata, behind.
+        let mut copies = Vec::with_capacity(artifacts.len());
+
+        for artifact in artifacts {
+            let Some(file_name) = artifact.file_name() else {
+                return false;
+            };
+
+            let artifact_dir = match artifact.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+
+            let copied = tempfile::NamedTempFile::new_in(artifact_dir).and_then(|temp| {
+                std::fs::copy(dir.join(file_name), temp.path())?;
+
+                Ok(temp)
+            });
+
+            match copied {
+                Ok(temp) => copies.push((temp, artifact)),
+                Err(_) => return false,
+            }
+        }
+
+        copies
+            .into_iter()
+            .all(|(temp, artifact)| temp.persist(artifact).is_ok())
+    }
+
+    fn store_in(&self, hosts_dir: &Path, artifacts: &[PathBuf]) -> io::Result<()> {
+        let dir = hosts_dir.join(self.0.to_hex().as_str());
+
+        // Write to a temporary directory first, so that a concurrent build never restores a
+        // partially written entry.
+        let temp_dir = dir.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::create_dir_all(&temp_dir)?;
+
+        for artifact in artifacts {
+            if let Some(file_name) = artifact.file_name() {
+                std::fs::copy(artifact, temp_dir.join(file_name))?;
+            }
+        }
+
+        if std::fs::rename(&temp_dir, &dir).is_err() {
+            // Another build stored the same entry in the meantime.
+            std::fs::remove_dir_all(&temp_dir)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// e.g. ~/.cache/roc/hosts, beside the packages directory
+fn hosts_dir() -> PathBuf {
+    roc_packaging::cache::roc_cache_root().join("hosts")
+}
+
+/// The files a host build produces, which is what gets cached. Returns [None] for builds that
+/// aren't cached.
+pub fn host_artifacts(
+    linking_strategy: LinkingStrategy,
+    target: &Triple,
+    platform_main_roc: &Path,
+    preprocessed_host_path: &Path,
+) -> Option<Vec<PathBuf>> {
+    if std::env::var_os(ROC_NO_HOST_CACHE).is_some() {
+        return None;
+    }
+
+    if HostHook::find(platform_main_roc).map_or(false, |hook| !hook.cache) {
+        return None;
+    }
+
+    match linking_strategy {
+        LinkingStrategy::Surgical => Some(vec![
+            preprocessed_host_path.to_path_buf(),
+            platform_main_roc.with_file_name(roc_linker::metadata_file_name(target)),
+        ]),
+        LinkingStrategy::Legacy if !matches!(target.architecture, Architecture::Wasm32) => {
+            Some(vec![legacy_host_file(target, platform_main_roc)?])
+        }
+        LinkingStrategy::Legacy | LinkingStrategy::Additive => None,
+    }
+}
+
+/// The output of `cargo metadata`, as far as the host cache needs it.
+#[derive(Deserialize)]
+struct CargoMetadata {
+    packages: Vec<CargoPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    dependencies: Vec<CargoDependency>,
+}
+
+#[derive(Deserialize)]
+struct CargoDependency {
+    path: Option<PathBuf>,
+}
+
+/// The directories of the path dependencies of the Cargo package in `platform_dir`, and of their
+/// path dependencies in turn, except for those within `platform_dir` itself.
+///
+/// Fails if `cargo metadata` does, in which case the host isn't cached.
+fn cargo_path_dependencies(platform_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dependencies: Vec<PathBuf> = Vec::new();
+    let mut manifest_dirs = vec![platform_dir.to_path_buf()];
+
+    while let Some(manifest_dir) = manifest_dirs.pop() {
+        let output = Command::new("cargo")
+            .args([
+                "metadata",
+                "--format-version",
+                "1",
+                "--no-deps",
+                "--offline",
+            ])
+            .arg("--manifest-path")
+            .arg(manifest_dir.join("Cargo.toml"))
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            ));
+        }
+
+        let metadata: CargoMetadata = serde_json::from_slice(&output.stdout)?;
+
+        let paths = metadata
+            .packages
+            .into_iter()
+            .flat_map(|package| package.dependencies)
+            .filter_map(|dependency| dependency.path);
+
+        for path in paths {
+            if !path.starts_with(platform_dir) && !dependencies.contains(&path) {
+                dependencies.push(path.clone());
+                manifest_dirs.push(path);
+            }
+        }
+    }
+
+    dependencies.sort();
+
+    Ok(dependencies)
+}
+
+fn is_ignored(path: &Path, is_dir: bool) -> bool {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    if is_dir {
+        IGNORED_DIRS.contains(&file_name.as_ref()) || file_name.starts_with('.')
+    } else {
+        let extension = path.extension().unwrap_or_default().to_string_lossy();
+
+        IGNORED_EXTENSIONS.contains(&extension.as_ref())
+            || IGNORED_FILE_NAMES.contains(&file_name.as_ref())
+    }
+}
+
+fn hash_str(hasher: &mut blake3::Hasher, string: &str) {
+    hash_bytes(hasher, string.as_bytes())
+}
+
+/// Hashes the length before the bytes, so that consecutive inputs can't run into each other.
+fn hash_bytes(hasher: &mut blake3::Hasher, bytes: &[u8]) {
+    hasher.update(&(bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Platform {
+        dir: tempfile::TempDir,
+        hosts_dir: tempfile::TempDir,
+    }
+
+    impl Platform {
+        fn new() -> Self {
+            let platform = Self {
+                dir: tempfile::tempdir().unwrap(),
+                hosts_dir: tempfile::tempdir().unwrap(),
+            };
+
+            platform.write("main.roc", "platform \"test\"");
+            platform.write("host.c", "int main() { return 0; }");
+
+            platform
+        }
+
+        fn path(&self, file_name: &str) -> PathBuf {
+            self.dir.path().join(file_name)
+        }
+
+        fn write(&self, file_name: &str, contents: &str) {
+            std::fs::write(self.path(file_name), contents).unwrap();
+        }
+
+        fn key(&self, opt_level: OptLevel) -> HostCacheKey {
+            HostCacheKey::new(
+                opt_level,
+                LinkingStrategy::Legacy,
+                &Triple::host(),
+                &self.path("main.roc"),
+                &[],
+            )
+            .unwrap()
+        }
+
+        fn restore(&self, key: &HostCacheKey, artifacts: &[PathBuf]) -> bool {
+            key.restore_from(self.hosts_dir.path(), artifacts)
+        }
+
+        fn store(&self, key: &HostCacheKey, artifacts: &[PathBuf]) {
+            key.store_in(self.hosts_dir.path(), artifacts).unwrap()
+        }
+    }
+
+    #[test]
+    fn hit() {
+        let platform = Platform::new();
+        let artifacts = [platform.path("host.o")];
+
+        platform.write("host.o", "built host");
+        platform.store(&platform.key(OptLevel::Normal), &artifacts);
+        std::fs::remove_file(&artifacts[0]).unwrap();
+
+        assert!(platform.restore(&platform.key(OptLevel::Normal), &artifacts));
+        assert_eq!(
+            std::fs::read_to_string(&artifacts[0]).unwrap(),
+            "built host"
+        );
+    }
+
+    #[test]
+    fn miss() {
+        let platform = Platform::new();
+        let artifacts = [platform.path("host.o")];
+
+        platform.write("host.o", "stale host");
+
+        assert!(!platform.restore(&platform.key(OptLevel::Normal), &artifacts));
+        assert_eq!(
+            std::fs::read_to_string(&artifacts[0]).unwrap(),
+            "stale host"
+        );
+    }
+
+    #[test]
+    fn missing_artifact_restores_nothing() {
+        let platform = Platform::new();
+        let host = platform.path("host.o");
+        let metadata = platform.path("metadata");
+        let key = platform.key(OptLevel::Normal);
+
+        platform.write("host.o", "built host");
+        platform.store(&key, std::slice::from_ref(&host));
+        platform.write("host.o", "stale host");
+
+        assert!(!platform.restore(&key, &[host.clone(), metadata.clone()]));
+        assert_eq!(std::fs::read_to_string(&host).unwrap(), "stale host");
+        assert!(!metadata.exists());
+    }
+
+    #[test]
+    fn invalidation() {
+        let platform = Platform::new();
+        let key = |platform: &Platform| platform.key(OptLevel::Normal).0;
+        let original = key(&platform);
+
+        // Build outputs and hidden directories are not what the host is built from.
+        platform.write("host.o", "built host");
+        std::fs::create_dir(platform.path(".git")).unwrap();
+        platform.write(".git/HEAD", "ref: refs/heads/main");
+        assert_eq!(key(&platform), original);
+
+        assert_ne!(platform.key(OptLevel::Optimize).0, original);
+
+        platform.write("host.c", "int main() { return 1; }");
+        assert_ne!(key(&platform), original);
+    }
+
+    #[test]
+    fn cargo_path_dependencies_are_hashed() {
+        let platform = Platform::new();
+        let dependency = tempfile::tempdir().unwrap();
+        let write_dependency = |file_name: &str, contents: &str| {
+            std::fs::write(dependency.path().join(file_name), contents).unwrap()
+        };
+
+        std::fs::create_dir(dependency.path().join("src")).unwrap();
+        write_dependency(
+            "Cargo.toml",
+            "[package]\nname = \"dependency\"\nversion = \"0.0.1\"\n",
+        );
+        write_dependency("src/lib.rs", "pub fn answer() -> i64 { 42 }");
+
+        std::fs::create_dir(platform.path("src")).unwrap();
+        platform.write("src/lib.rs", "pub use dependency::answer;");
+        platform.write(
+            "Cargo.toml",
+            &format!(
+                "[package]\nname = \"host\"\nversion = \"0.0.1\"\n\n[dependencies]\ndependency = {{ path = {:?} }}\n\n[workspace]\n",
+                dependency.path()
+            ),
+        );
+
+        let original = platform.key(OptLevel::Normal).0;
+
+        write_dependency("src/lib.rs", "pub fn answer() -> i64 { 43 }");
+        assert_ne!(platform.key(OptLevel::Normal).0, original);
+    }
+
+    #[test]
+    fn hook_inputs_are_hashed() {
+        let platform = Platform::new();
+
+        platform.write(
+            crate::host_hook::HOST_HOOK_FILE_NAME,
+            r#"{ "command": ["make"], "inputs": ["host.c"] }"#,
+        );
+
+        let original = platform.key(OptLevel::Normal).0;
+
+        platform.write("README.md", "not an input");
+        assert_eq!(platform.key(OptLevel::Normal).0, original);
+
+        platform.write("host.c", "int main() { return 1; }");
+        assert_ne!(platform.key(OptLevel::Normal).0, original);
+    }
+}
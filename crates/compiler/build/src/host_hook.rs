@@ -0,0 +1,303 @@
+//! Lets a platform build its host with a command of its own choosing, instead of relying on the
+//! conventions `rebuild_host` knows about (a `host.zig`, `host.c`, `Cargo.toml` etc. beside the
+//! platform's main.roc).
+//!
+//! A platform opts in with a `build-host.json` beside its main.roc:
+//!
+//! ```json
+//! {
+//!     "command": ["zig", "build", "host"],
+//!     "inputs": ["build.zig", "host"],
+//!     "cache": true
+//! }
+//! ```
+//!
+//! - `command` is the program and its arguments. It runs in the platform's directory.
+//! - `inputs` are the files and directories, relative to the platform's directory, that the host
+//!   is built from. Optional, defaulting to the whole platform directory.
+//! - `cache` is whether the host may be restored from the host cache instead of running the
+//!   command. Optional, defaulting to true.
+//!
+//! The command learns what to build from these environment variables:
+//!
+//! - [ROC_HOST_TARGET]: the target triple, e.g. `x86_64-unknown-linux-gnu`.
+//! - [ROC_HOST_OPT_LEVEL]: one of `development`, `normal`, `size` or `optimize`.
+//! - [ROC_HOST_OUTPUT]: the absolute path to write the host to.
+//! - [ROC_HOST_KIND]: `object` if the host is linked with the app by a regular linker, in which
+//!   case the output is an object file or a static library (a wasm object or LLVM bitcode for
+//!   wasm32). `executable` if it is linked by the surgical linker, in which case the output is an
+//!   executable dynamically linked against the stub library.
+//! - [ROC_HOST_STUB_LIB]: the stub library that defines the symbols the app provides. Only set
+//!   for `executable` hosts.
+//! - [ROC_HOST_BUILTINS]: an object file with the builtins the host can call, like `roc_alloc`
+//!   wrappers. Linking it in is optional.
+//!
+//! The command must exit with a non-zero status if the build fails. Its output is only shown in
+//! that case.
+//!
+//! Caching contract: when `cache` is true, the host is only rebuilt when one of the inputs or the
+//! environment variables above changes (see [crate::host_cache]). A command whose output depends
+//! on anything else, like files outside of the platform's directory, should set `cache` to false.
+use roc_error_macros::user_error;
+use roc_mono::ir::OptLevel;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use target_lexicon::Triple;
+
+pub const HOST_HOOK_FILE_NAME: &str = "build-host.json";
+
+pub const ROC_HOST_TARGET: &str = "ROC_HOST_TARGET";
+pub const ROC_HOST_OPT_LEVEL: &str = "ROC_HOST_OPT_LEVEL";
+pub const ROC_HOST_OUTPUT: &str = "ROC_HOST_OUTPUT";
+pub const ROC_HOST_KIND: &str = "ROC_HOST_KIND";
+pub const ROC_HOST_STUB_LIB: &str = "ROC_HOST_STUB_LIB";
+pub const ROC_HOST_BUILTINS: &str = "ROC_HOST_BUILTINS";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HostHook {
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub inputs: Option<Vec<PathBuf>>,
+    #[serde(default = "cache_by_default")]
+    pub cache: bool,
+}
+
+fn cache_by_default() -> bool {
+    true
+}
+
+impl HostHook {
+    /// Reads the platform's `build-host.json`, if it has one.
+    pub fn find(platform_main_roc: &Path) -> Option<Self> {
+        let path = platform_main_roc.with_file_name(HOST_HOOK_FILE_NAME);
+
+        if !path.is_file() {
+            return None;
+        }
+
+        Some(Self::read(&path).unwrap_or_else(|err| user_error!("{err}")))
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let hook: Self = std::fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|err| err.to_string()))
+            .map_err(|err| format!("Unable to read {}: {err}", path.display()))?;
+
+        if hook.command.is_empty() {
+            return Err(format!(
+                "The \"command\" in {} is empty, so I don't know how to build the host.",
+                path.display()
+            ));
+        }
+
+        Ok(hook)
+    }
+
+    /// The paths whose contents the host is built from, which are what the host cache hashes.
+    pub fn input_paths(&self, platform_dir: &Path) -> Vec<PathBuf> {
+        let mut paths = vec![platform_dir.join(HOST_HOOK_FILE_NAME)];
+
+        match &self.inputs {
+            Some(inputs) => paths.extend(inputs.iter().map(|input| platform_dir.join(input))),
+            None => paths.push(platform_dir.to_path_buf()),
+        }
+
+        paths
+    }
+
+    /// Builds the host into `host_dest`. `shared_lib_path` is the stub library to link against
+    /// when building an executable for the surgical linker.
+    pub fn command(
+        &self,
+        opt_level: OptLevel,
+        target: &Triple,
+        platform_main_roc: &Path,
+        host_dest: &Path,
+        shared_lib_path: Option<&Path>,
+        builtins_host_path: &Path,
+    ) -> Command {
+        let platform_dir = platform_main_roc.parent().unwrap_or_else(|| Path::new("."));
+
+        let mut command = Command::new(&self.command[0]);
+
+        command
+            .args(&self.command[1..])
+            .current_dir(platform_dir)
+            .env(ROC_HOST_TARGET, target.to_string())
+            .env(ROC_HOST_OPT_LEVEL, opt_level_str(opt_level))
+            .env(ROC_HOST_OUTPUT, absolute(host_dest))
+            .env(ROC_HOST_BUILTINS, builtins_host_path)
+            .env_remove(ROC_HOST_STUB_LIB);
+
+        match shared_lib_path {
+            Some(stub_lib) => {
+                command
+                    .env(ROC_HOST_KIND, "executable")
+                    .env(ROC_HOST_STUB_LIB, absolute(stub_lib));
+            }
+            None => {
+                command.env(ROC_HOST_KIND, "object");
+            }
+        }
+
+        command
+    }
+}
+
+/// The command runs in the platform's directory, so relative paths would be resolved against that
+/// instead of against ours.
+fn absolute(path: &Path) -> PathBuf {
+    std::env::current_dir()
+        .map(|dir| dir.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn opt_level_str(opt_level: OptLevel) -> &'static str {
+    match opt_level {
+        OptLevel::Development => "development",
+        OptLevel::Normal => "normal",
+        OptLevel::Size => "size",
+        OptLevel::Optimize => "optimize",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn platform_with_hook(json: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(HOST_HOOK_FILE_NAME), json).unwrap();
+
+        let main_roc = dir.path().join("main.roc");
+        (dir, main_roc)
+    }
+
+    #[test]
+    fn no_hook_without_json() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert_eq!(HostHook::find(&dir.path().join("main.roc")), None);
+    }
+
+    #[test]
+    fn hook_beside_main_roc() {
+        let (dir, main_roc) = platform_with_hook(r#"{ "command": ["zig", "build", "host"] }"#);
+
+        let hook = HostHook::find(&main_roc).unwrap();
+
+        assert_eq!(hook.command, ["zig", "build", "host"]);
+        assert!(hook.cache);
+        assert_eq!(
+            hook.input_paths(dir.path()),
+            [
+                dir.path().join(HOST_HOOK_FILE_NAME),
+                dir.path().to_path_buf()
+            ]
+        );
+    }
+
+    #[test]
+    fn hook_inputs_are_relative_to_the_platform() {
+        let (dir, main_roc) = platform_with_hook(
+            r#"{ "command": ["make"], "inputs": ["Makefile", "host"], "cache": false }"#,
+        );
+
+        let hook = HostHook::find(&main_roc).unwrap();
+
+        assert!(!hook.cache);
+        assert_eq!(
+            hook.input_paths(dir.path()),
+            [
+                dir.path().join(HOST_HOOK_FILE_NAME),
+                dir.path().join("Makefile"),
+                dir.path().join("host"),
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_json() {
+        let (dir, _) = platform_with_hook(r#"{ "command": ["make"], "#);
+        let path = dir.path().join(HOST_HOOK_FILE_NAME);
+
+        let err = HostHook::read(&path).unwrap_err();
+
+        assert!(err.starts_with(&format!("Unable to read {}: ", path.display())));
+    }
+
+    #[test]
+    fn unknown_field() {
+        let (dir, _) = platform_with_hook(r#"{ "command": ["make"], "cached": false }"#);
+
+        let err = HostHook::read(&dir.path().join(HOST_HOOK_FILE_NAME)).unwrap_err();
+
+        assert!(err.contains("unknown field `cached`"), "{err}");
+    }
+
+    #[test]
+    fn empty_command() {
+        let (dir, _) = platform_with_hook(r#"{ "command": [] }"#);
+
+        let err = HostHook::read(&dir.path().join(HOST_HOOK_FILE_NAME)).unwrap_err();
+
+        assert!(err.contains("is empty"), "{err}");
+    }
+
+    #[test]
+    fn command_environment() {
+        let (dir, main_roc) = platform_with_hook(r#"{ "command": ["make", "host"] }"#);
+        let hook = HostHook::find(&main_roc).unwrap();
+        let target = Triple::host();
+
+        let env = |command: &Command, name: &str| {
+            command
+                .get_envs()
+                .find(|(key, _)| *key == OsStr::new(name))
+                .and_then(|(_, value)| value)
+                .map(|value| value.to_string_lossy().into_owned())
+        };
+
+        let object = hook.command(
+            OptLevel::Optimize,
+            &target,
+            &main_roc,
+            &dir.path().join("host.o"),
+            None,
+            Path::new("builtins.o"),
+        );
+
+        assert_eq!(object.get_program(), "make");
+        assert_eq!(object.get_args().collect::<Vec<_>>(), ["host"]);
+        assert_eq!(object.get_current_dir(), Some(dir.path()));
+        assert_eq!(env(&object, ROC_HOST_TARGET), Some(target.to_string()));
+        assert_eq!(
+            env(&object, ROC_HOST_OPT_LEVEL).as_deref(),
+            Some("optimize")
+        );
+        assert_eq!(env(&object, ROC_HOST_KIND).as_deref(), Some("object"));
+        assert_eq!(env(&object, ROC_HOST_STUB_LIB), None);
+
+        let executable = hook.command(
+            OptLevel::Development,
+            &target,
+            &main_roc,
+            &dir.path().join("dynhost"),
+            Some(&dir.path().join("libapp.so")),
+            Path::new("builtins.o"),
+        );
+
+        assert_eq!(
+            env(&executable, ROC_HOST_KIND).as_deref(),
+            Some("executable")
+        );
+        assert_eq!(
+            env(&executable, ROC_HOST_STUB_LIB),
+            Some(dir.path().join("libapp.so").to_string_lossy().into_owned())
+        );
+    }
+}
@@ -2,6 +2,8 @@
 #![warn(clippy::dbg_macro)]
 // See github.com/roc-lang/roc/issues/800 for discussion of the large_enum_variant check.
 #![allow(clippy::large_enum_variant)]
+pub mod host_cache;
+pub mod host_hook;
 pub mod link;
 pub mod program;
 pub mod target;
@@ -1,8 +1,9 @@
-use crate::target::{arch_str, target_zig_str};
+use crate::host_hook::{HostHook, HOST_HOOK_FILE_NAME};
+use crate::target::{arch_str, target_triple_str, target_zig_str};
 use libloading::{Error, Library};
 use roc_command_utils::{cargo, clang, rustup, zig};
 use roc_debug_flags;
-use roc_error_macros::internal_error;
+use roc_error_macros::{internal_error, user_error};
 use roc_mono::ir::OptLevel;
 use std::collections::HashMap;
 use std::ffi::OsString;
@@ -26,30 +27,64 @@ pub enum LinkingStrategy {
     Additive,
 }
 
+/// Options for the legacy linker, which the user can set when the defaults don't work for them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkOptions {
+    /// Passed to the linker after the input files, e.g. `-L/opt/lib` or `-lfoo`
+    pub extra_args: Vec<String>,
+    /// Strip symbols and debug info from the output
+    pub strip: bool,
+    /// Keep timestamps and the paths of temporary files out of the output, so that linking the
+    /// same inputs always gives the same bytes
+    pub reproducible: bool,
+    /// Write a report of which functions take up the space in the output next to it. This works
+    /// with every linker, not just the legacy one.
+    pub emit_bloat_report: bool,
+    /// Whether the surgical linker was asked for by name. If so, a prebuilt platform which only
+    /// has a host for the legacy linker is an error, rather than a reason to use the legacy one.
+    pub surgical_requested: bool,
+}
+
 /// input_paths can include the host as well as the app. e.g. &["host.o", "roc_app.o"]
 pub fn link(
     target: &Triple,
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+) -> io::Result<(Child, PathBuf)> {
+    link_with_options(
+        target,
+        output_path,
+        input_paths,
+        link_type,
+        &LinkOptions::default(),
+    )
+}
+
+pub fn link_with_options(
+    target: &Triple,
+    output_path: PathBuf,
+    input_paths: &[&str],
+    link_type: LinkType,
+    options: &LinkOptions,
 ) -> io::Result<(Child, PathBuf)> {
     match target {
         Triple {
             architecture: Architecture::Wasm32,
             ..
-        } => link_wasm32(target, output_path, input_paths, link_type),
+        } => link_wasm32(target, output_path, input_paths, link_type, options),
         Triple {
             operating_system: OperatingSystem::Linux,
             ..
-        } => link_linux(target, output_path, input_paths, link_type),
+        } => link_linux(target, output_path, input_paths, link_type, options),
         Triple {
             operating_system: OperatingSystem::Darwin,
             ..
-        } => link_macos(target, output_path, input_paths, link_type),
+        } => link_macos(target, output_path, input_paths, link_type, options),
         Triple {
             operating_system: OperatingSystem::Windows,
             ..
-        } => link_windows(target, output_path, input_paths, link_type),
+        } => link_windows(target, output_path, input_paths, link_type, options),
         _ => internal_error!("TODO gracefully handle unsupported target: {:?}", target),
     }
 }
@@ -326,7 +361,15 @@ pub fn build_c_host_native(
     shared_lib_path: Option<&Path>,
     builtins_host_path: &Path,
 ) -> Command {
-    let mut clang_cmd = clang();
+    let mut clang_cmd = if is_musl(target) {
+        // The system's C headers are for glibc, so let zig compile against its own musl headers
+        let mut zig_cc = zig();
+        zig_cc.args(["cc", "-target", target_zig_str(target)]);
+
+        zig_cc
+    } else {
+        clang()
+    };
     clang_cmd
         .env_clear()
         .env("PATH", env_path)
@@ -478,7 +521,19 @@ pub fn rebuild_host(
     let builtins_host_tempfile =
         roc_bitcode::host_tempfile().expect("failed to write host builtins object to tempfile");
 
-    if zig_host_src.exists() {
+    if let Some(hook) = HostHook::find(platform_main_roc) {
+        // The platform's own build command takes precedence over the conventions below
+        let hook_cmd = hook.command(
+            opt_level,
+            target,
+            platform_main_roc,
+            &host_dest,
+            shared_lib_path,
+            builtins_host_tempfile.path(),
+        );
+
+        run_build_command(hook_cmd, HOST_HOOK_FILE_NAME, 0);
+    } else if zig_host_src.exists() {
         // Compile host.zig
         let zig_cmd = match target.architecture {
             Architecture::Wasm32 => {
@@ -546,6 +601,10 @@ pub fn rebuild_host(
         };
 
         cargo_cmd.arg("build").current_dir(cargo_dir);
+        if is_musl(target) {
+            // find_used_target_sub_folder picks up the output in target/<triple>
+            cargo_cmd.args(["--target", target_triple_str(target)]);
+        }
         // Rust doesn't expose size without editing the cargo.toml. Instead just use release.
         if matches!(opt_level, OptLevel::Optimize | OptLevel::Size) {
             cargo_cmd.arg("--release");
@@ -789,11 +848,19 @@ fn get_target_str(target: &Triple) -> &str {
         && target.environment == target_lexicon::Environment::Gnu
     {
         "x86_64-windows-gnu"
+    } else if is_musl(target) {
+        target_zig_str(target)
     } else {
         "native"
     }
 }
 
+/// Builds for musl targets are linked into a fully static executable, with zig providing musl.
+fn is_musl(target: &Triple) -> bool {
+    target.operating_system == OperatingSystem::Linux
+        && target.environment == target_lexicon::Environment::Musl
+}
+
 fn nix_paths() -> Vec<String> {
     let mut paths = vec![];
 
@@ -866,6 +933,7 @@ fn link_linux(
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    options: &LinkOptions,
 ) -> io::Result<(Child, PathBuf)> {
     let architecture = format!("{}-linux-gnu", target.architecture);
 
@@ -890,6 +958,34 @@ fn link_linux(
                     "-lc",
                     &format!("-femit-bin={}", output_path.to_str().unwrap()),
                 ])
+                .args(options.strip.then_some("-fstrip"))
+                .args(&options.extra_args)
+                .spawn()?,
+            output_path,
+        ));
+    }
+
+    if is_musl(target) {
+        if !matches!(link_type, LinkType::Executable) {
+            user_error!(
+                "I can only build executables for {}, which are linked statically against musl. Remove the `--lib` flag, or build for another `--target`.",
+                target_triple_str(target)
+            );
+        }
+
+        // zig links musl's libc statically, so the executable doesn't need a dynamic linker
+        return Ok((
+            zig()
+                .args(["build-exe", "-static"])
+                .args(input_paths)
+                .args([
+                    "-target",
+                    target_zig_str(target),
+                    "-lc",
+                    &format!("-femit-bin={}", output_path.to_str().unwrap()),
+                ])
+                .args(options.strip.then_some("-fstrip"))
+                .args(&options.extra_args)
                 .spawn()?,
             output_path,
         ));
@@ -1058,6 +1154,8 @@ fn link_linux(
         .args(["-dynamic-linker", ld_linux_path_str])
         .args(input_paths)
         .args(extra_link_flags())
+        .args(&options.extra_args)
+        .args(options.strip.then_some("--strip-all"))
         // ld.lld requires this argument, and does not accept --arch
         // .args(&["-L/usr/lib/x86_64-linux-gnu"])
         .args([
@@ -1087,6 +1185,7 @@ fn link_macos(
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    options: &LinkOptions,
 ) -> io::Result<(Child, PathBuf)> {
     let (link_type_args, output_path) = match link_type {
         LinkType::Executable => (vec!["-execute"], output_path),
@@ -1131,7 +1230,28 @@ fn link_macos(
             &get_macos_version(),
         ])
         .args(input_paths)
-        .args(extra_link_flags());
+        .args(extra_link_flags())
+        .args(&options.extra_args);
+
+    if options.strip {
+        // -S strips debug info, and -x strips local symbols
+        ld_command.args(["-S", "-x"]);
+    } else if options.reproducible {
+        // The debug map points at the (temporary) object files, with their modification times.
+        // Those files are gone once we're done linking, so the debug map is of no use anyway.
+        ld_command.arg("-S");
+    }
+
+    if options.reproducible {
+        // Don't put timestamps in the debug map or in archive members
+        ld_command.env("ZERO_AR_DATE", "1");
+
+        if let LinkType::Dylib = link_type {
+            // Otherwise the install name is the path the dylib was built at
+            let file_name = output_path.file_name().unwrap().to_str().unwrap();
+            ld_command.args(["-install_name", &format!("@rpath/{file_name}")]);
+        }
+    }
 
     let sdk_path = "/Library/Developer/CommandLineTools/SDKs/MacOSX.sdk/usr/lib";
     if Path::new(sdk_path).exists() {
@@ -1218,6 +1338,7 @@ fn link_wasm32(
     output_path: PathBuf,
     input_paths: &[&str],
     _link_type: LinkType,
+    options: &LinkOptions,
 ) -> io::Result<(Child, PathBuf)> {
     let child = zig()
         // .env_clear()
@@ -1243,6 +1364,8 @@ fn link_wasm32(
             // useful for debugging
             // "-femit-llvm-ir=/home/folkertdev/roc/roc/crates/cli/tests/benchmarks/platform/host.ll",
         ])
+        // wasm output is always stripped, so there's nothing to do for options.strip
+        .args(&options.extra_args)
         .spawn()?;
 
     Ok((child, output_path))
@@ -1253,6 +1376,7 @@ fn link_windows(
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    options: &LinkOptions,
 ) -> io::Result<(Child, PathBuf)> {
     match link_type {
         LinkType::Dylib => {
@@ -1272,6 +1396,8 @@ fn link_windows(
                     "Debug",
                     "-dynamic",
                 ])
+                .args(options.strip.then_some("-fstrip"))
+                .args(&options.extra_args)
                 .spawn()?;
 
             Ok((child, output_path))
@@ -1288,6 +1414,8 @@ fn link_windows(
                     "-lc",
                     &format!("-femit-bin={}", output_path.to_str().unwrap()),
                 ])
+                .args(options.strip.then_some("-fstrip"))
+                .args(&options.extra_args)
                 .spawn()?;
 
             Ok((child, output_path))
@@ -1,21 +1,26 @@
+use crate::host_cache::{host_artifacts, HostCacheKey};
 use crate::link::{
-    legacy_host_file, link, preprocess_host_wasm32, rebuild_host, LinkType, LinkingStrategy,
+    legacy_host_file, link_with_options, preprocess_host_wasm32, rebuild_host, LinkOptions,
+    LinkType, LinkingStrategy,
 };
 use bumpalo::Bump;
 use inkwell::memory_buffer::MemoryBuffer;
-use roc_error_macros::internal_error;
+use roc_error_macros::{internal_error, user_error};
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode};
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_load::{
     EntryPoint, ExecutionMode, ExpectMetadata, FunctionKind, LoadConfig, LoadMonomorphizedError,
-    LoadedModule, LoadingProblem, MonomorphizedModule, Threading,
+    LoadedModule, LoadingProblem, ModuleTiming, MonomorphizedModule, Threading, TypeCheckedProgram,
 };
 use roc_mono::ir::{OptLevel, SingleEntryPoint};
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::{
-    cli::{report_problems, Problems},
-    report::{RenderTarget, DEFAULT_PALETTE},
+    cli::{
+        apply_machine_applicable_fixes, report_problems, report_problems_json, terminal_palette,
+        FixedModule, ProblemOutput, Problems,
+    },
+    report::RenderTarget,
 };
 use roc_target::{OperatingSystem, TargetInfo};
 use std::ffi::OsStr;
@@ -43,6 +48,7 @@ pub fn report_problems_monomorphized(loaded: &mut MonomorphizedModule) -> Proble
     report_problems(
         &loaded.sources,
         &loaded.interns,
+        &loaded.imported_symbols,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
     )
@@ -52,6 +58,7 @@ pub fn report_problems_typechecked(loaded: &mut LoadedModule) -> Problems {
     report_problems(
         &loaded.sources,
         &loaded.interns,
+        &loaded.imported_symbols,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
     )
@@ -86,9 +93,27 @@ pub struct CodeGenOptions {
     pub opt_level: OptLevel,
     pub emit_debug_info: bool,
     pub emit_llvm_ir: bool,
+    /// Generate only this, and write it to the output path instead of an object file or executable
+    pub emit_artifact: Option<CodeGenArtifact>,
     pub fuzz: bool,
 }
 
+/// A human-readable form of the generated code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeGenArtifact {
+    LlvmIr,
+    Assembly,
+}
+
+impl CodeGenArtifact {
+    fn extension(self) -> &'static str {
+        match self {
+            CodeGenArtifact::LlvmIr => "ll",
+            CodeGenArtifact::Assembly => "s",
+        }
+    }
+}
+
 type GenFromMono<'a> = (CodeObject, CodeGenTiming, ExpectMetadata<'a>);
 
 #[allow(clippy::too_many_arguments)]
@@ -104,6 +129,7 @@ pub fn gen_from_mono_module<'a>(
     let path = roc_file_path;
     let debug = code_gen_options.emit_debug_info;
     let emit_llvm_ir = code_gen_options.emit_llvm_ir;
+    let emit_artifact = code_gen_options.emit_artifact;
     let fuzz = code_gen_options.fuzz;
     let opt = code_gen_options.opt_level;
 
@@ -133,6 +159,7 @@ pub fn gen_from_mono_module<'a>(
             backend_mode,
             debug,
             emit_llvm_ir,
+            emit_artifact,
             fuzz,
         ),
     }
@@ -151,6 +178,7 @@ fn gen_from_mono_module_llvm<'a>(
     backend_mode: LlvmBackendMode,
     emit_debug_info: bool,
     emit_llvm_ir: bool,
+    emit_artifact: Option<CodeGenArtifact>,
     fuzz: bool,
 ) -> GenFromMono<'a> {
     use crate::target::{self, convert_opt_level};
@@ -281,7 +309,26 @@ fn gen_from_mono_module_llvm<'a>(
     // env.module.print_to_stderr();
 
     let gen_sanitizers = cfg!(feature = "sanitizers") && std::env::var("ROC_SANITIZERS").is_ok();
-    let memory_buffer = if fuzz || gen_sanitizers {
+    let memory_buffer = if let Some(artifact) = emit_artifact {
+        // Only the artifact is wanted, so there's no object file to build.
+        match artifact {
+            CodeGenArtifact::LlvmIr => MemoryBuffer::create_from_memory_range_copy(
+                env.module.print_to_string().to_bytes(),
+                "app.ll",
+            ),
+            CodeGenArtifact::Assembly => {
+                match target::target_machine(target, convert_opt_level(opt_level), RelocMode::PIC) {
+                    Some(target_machine) => target_machine
+                        .write_to_memory_buffer(env.module, FileType::Assembly)
+                        .expect("Writing .s file failed"),
+                    None => internal_error!(
+                        "TODO gracefully handle not being able to emit assembly for {:?}",
+                        target.architecture
+                    ),
+                }
+            }
+        }
+    } else if fuzz || gen_sanitizers {
         let dir = tempfile::tempdir().unwrap();
         let dir = dir.into_path();
 
@@ -613,6 +660,62 @@ fn report_timing(buf: &mut String, label: &str, duration: Duration) {
     .unwrap()
 }
 
+/// How long each phase of compilation took, summed over all modules.
+///
+/// Modules are compiled in parallel, so the per-module phases can add up
+/// to more than the wall time that the whole compilation took.
+#[derive(Debug, Default)]
+struct PhaseTimings {
+    parse: Duration,
+    canonicalize: Duration,
+    constrain: Duration,
+    solve: Duration,
+    specialize: Duration,
+    code_gen: Option<Duration>,
+    link: Option<Duration>,
+}
+
+impl PhaseTimings {
+    fn from_modules<'a>(module_timings: impl IntoIterator<Item = &'a ModuleTiming>) -> Self {
+        let mut phases = PhaseTimings::default();
+
+        for timing in module_timings {
+            phases.parse += timing.read_roc_file + timing.parse_header + timing.parse_body;
+            phases.canonicalize += timing.canonicalize;
+            phases.constrain += timing.constrain;
+            phases.solve += timing.solve;
+            phases.specialize += timing.find_specializations;
+            phases.specialize += timing.make_specializations.iter().sum::<Duration>();
+        }
+
+        phases
+    }
+
+    fn report(&self, total: Duration) -> String {
+        let buf = &mut String::with_capacity(256);
+
+        report_timing(buf, "Parse", self.parse);
+        report_timing(buf, "Canonicalize", self.canonicalize);
+        report_timing(buf, "Constrain", self.constrain);
+        report_timing(buf, "Solve", self.solve);
+
+        // Checking stops after solving, so it never specializes, generates code, or links.
+        if let Some(code_gen) = self.code_gen {
+            report_timing(buf, "Specialize", self.specialize);
+            report_timing(buf, "Code generation", code_gen);
+        }
+
+        if let Some(link) = self.link {
+            report_timing(buf, "Link", link);
+        }
+
+        buf.push('\n');
+        report_timing(buf, "Total (wall time)", total);
+
+        format!("Here's how long each phase took, summed over all modules:\n\n{buf}")
+    }
+}
+
 pub struct BuiltFile<'a> {
     pub binary_path: PathBuf,
     pub problems: Problems,
@@ -620,6 +723,7 @@ pub struct BuiltFile<'a> {
     pub expect_metadata: ExpectMetadata<'a>,
 }
 
+#[derive(Debug, Clone, Copy)]
 pub enum BuildOrdering {
     /// Run up through typechecking first; continue building iff that is successful.
     BuildIfChecks,
@@ -720,7 +824,7 @@ pub fn standard_load_config(
         target_info,
         function_kind,
         render: RenderTarget::ColorTerminal,
-        palette: DEFAULT_PALETTE,
+        palette: *terminal_palette(),
         threading,
         exec_mode,
     }
@@ -735,6 +839,7 @@ pub fn build_file<'a>(
     emit_timings: bool,
     link_type: LinkType,
     linking_strategy: LinkingStrategy,
+    link_options: &LinkOptions,
     prebuilt_requested: bool,
     wasm_dev_stack_bytes: Option<u32>,
     roc_cache_dir: RocCacheDir<'_>,
@@ -756,6 +861,55 @@ pub fn build_file<'a>(
         emit_timings,
         link_type,
         linking_strategy,
+        link_options,
+        prebuilt_requested,
+        wasm_dev_stack_bytes,
+        loaded,
+        compilation_start,
+        out_path,
+    )
+}
+
+/// Like [build_file], but specializes a program which was loaded (and type checked) once for
+/// several targets with [roc_load::load_for_targets], rather than loading it again.
+#[allow(clippy::too_many_arguments)]
+pub fn build_file_for_target<'a>(
+    arena: &'a Bump,
+    target: &Triple,
+    program: &TypeCheckedProgram<'a>,
+    app_module_path: PathBuf,
+    code_gen_options: CodeGenOptions,
+    emit_timings: bool,
+    link_type: LinkType,
+    linking_strategy: LinkingStrategy,
+    link_options: &LinkOptions,
+    prebuilt_requested: bool,
+    wasm_dev_stack_bytes: Option<u32>,
+    roc_cache_dir: RocCacheDir<'_>,
+    threading: Threading,
+    out_path: Option<&Path>,
+) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
+    let compilation_start = Instant::now();
+
+    // Step 1: specialize the app for this target and generate the .o file
+    let loaded = roc_load::specialize_for_target(
+        arena,
+        program,
+        TargetInfo::from(target),
+        threading,
+        roc_cache_dir,
+    )
+    .map_err(BuildFileError::LoadingProblem)?;
+
+    build_loaded_file(
+        arena,
+        target,
+        app_module_path,
+        code_gen_options,
+        emit_timings,
+        link_type,
+        linking_strategy,
+        link_options,
         prebuilt_requested,
         wasm_dev_stack_bytes,
         loaded,
@@ -773,12 +927,18 @@ fn build_loaded_file<'a>(
     emit_timings: bool,
     link_type: LinkType,
     mut linking_strategy: LinkingStrategy,
+    link_options: &LinkOptions,
     prebuilt_requested: bool,
     wasm_dev_stack_bytes: Option<u32>,
     loaded: roc_load::MonomorphizedModule<'a>,
     compilation_start: Instant,
     out_path: Option<&Path>,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
+    debug_assert!(
+        code_gen_options.emit_artifact.is_none() || link_type == LinkType::None,
+        "an emitted artifact is written as it is, so there's nothing to link"
+    );
+
     let operating_system = roc_target::OperatingSystem::from(target.operating_system);
 
     let platform_main_roc = match &loaded.entry_point {
@@ -796,6 +956,13 @@ fn build_loaded_file<'a>(
             .with_file_name(roc_linker::preprocessed_host_filename(target).unwrap());
         let legacy_host_path = legacy_host_file(target, &platform_main_roc).unwrap();
         if !preprocessed_host_path.exists() && legacy_host_path.exists() {
+            if link_options.surgical_requested {
+                user_error!(
+                    "I was asked to use the surgical linker, but this prebuilt platform does not have a preprocessed host for it:\n\n    {}\n\nIt does have a host for the legacy linker, so --linker=legacy should work.",
+                    preprocessed_host_path.display()
+                );
+            }
+
             linking_strategy = LinkingStrategy::Legacy;
         }
     }
@@ -836,14 +1003,16 @@ fn build_loaded_file<'a>(
                 }
             };
 
-            // If you specified a path that ends in in a directory separator, then
-            // use that directory, but use the app module's filename for the filename.
-            if ends_with_sep {
+            // If you specified a path that ends in in a directory separator (or a directory
+            // that already exists), then use that directory, but use the app module's
+            // filename for the filename.
+            if ends_with_sep || path.is_dir() {
                 let filename = app_module_path.file_name().unwrap_or_default();
 
                 with_output_extension(
                     &path.join(filename),
                     operating_system,
+                    code_gen_options.emit_artifact,
                     linking_strategy,
                     link_type,
                 )
@@ -854,11 +1023,24 @@ fn build_loaded_file<'a>(
         None => with_output_extension(
             &app_module_path,
             operating_system,
+            code_gen_options.emit_artifact,
             linking_strategy,
             link_type,
         ),
     };
 
+    // Build systems often ask for the output to go into a directory they haven't created yet.
+    if let Some(parent) = output_exe_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|err| {
+                user_error!(
+                    "Unable to create the output directory {}: {err}",
+                    parent.display()
+                )
+            });
+        }
+    }
+
     // We don't need to spawn a rebuild thread when using a prebuilt host.
     let rebuild_thread = if matches!(link_type, LinkType::Dylib | LinkType::None) {
         None
@@ -925,6 +1107,8 @@ fn build_loaded_file<'a>(
         }
     }
 
+    let mut phase_timings = PhaseTimings::from_modules(loaded.timings.values());
+
     // This only needs to be mutable for report_problems. This can't be done
     // inside a nested scope without causing a borrow error!
     let mut loaded = loaded;
@@ -978,6 +1162,8 @@ fn build_loaded_file<'a>(
     buf.push('\n');
     report_timing(buf, "Total", code_gen_timing.total);
 
+    phase_timings.code_gen = Some(code_gen_timing.total);
+
     let compilation_end = compilation_start.elapsed();
     let size = roc_app_bytes.len();
 
@@ -1014,7 +1200,7 @@ fn build_loaded_file<'a>(
             );
         }
         (LinkingStrategy::Additive, _) | (LinkingStrategy::Legacy, LinkType::None) => {
-            // Just copy the object file to the output folder.
+            // Just copy the object file (or the emitted artifact) to the output folder.
             std::fs::write(&output_exe_path, &*roc_app_bytes).unwrap();
         }
         (LinkingStrategy::Legacy, _) => {
@@ -1049,8 +1235,14 @@ fn build_loaded_file<'a>(
                 inputs.push(builtins_host_tempfile.path().to_str().unwrap());
             }
 
-            let (mut child, _) = link(target, output_exe_path.clone(), &inputs, link_type)
-                .map_err(|_| todo!("gracefully handle `ld` failing to spawn."))?;
+            let (mut child, _) = link_with_options(
+                target,
+                output_exe_path.clone(),
+                &inputs,
+                link_type,
+                link_options,
+            )
+            .map_err(|_| todo!("gracefully handle `ld` failing to spawn."))?;
 
             let exit_status = child
                 .wait()
@@ -1071,12 +1263,39 @@ fn build_loaded_file<'a>(
 
     let linking_time = link_start.elapsed();
 
-    if emit_timings {
-        println!("Finished linking in {} ms\n", linking_time.as_millis());
+    if link_options.emit_bloat_report {
+        let report_path = roc_linker::bloat::report_path(&output_exe_path);
+        let binary = std::fs::read(&output_exe_path).unwrap();
+
+        // The surgical linker doesn't copy the app's symbols into the output, so they have to
+        // come from the app itself.
+        let app_object = (linking_strategy == LinkingStrategy::Surgical).then_some(&*roc_app_bytes);
+
+        match roc_linker::bloat::bloat_report(&binary, app_object) {
+            Ok(report) => {
+                eprintln!("Emitting a bloat report to {}", report_path.display());
+
+                std::fs::write(report_path, report.to_string()).unwrap();
+            }
+            Err(message) => {
+                eprintln!(
+                    "Could not emit a bloat report, because I could not read the symbols of {}: {message}",
+                    output_exe_path.display()
+                );
+            }
+        }
     }
 
     let total_time = compilation_start.elapsed();
 
+    if emit_timings {
+        println!("Finished linking in {} ms\n", linking_time.as_millis());
+
+        phase_timings.link = Some(linking_time);
+
+        println!("{}", phase_timings.report(total_time));
+    }
+
     Ok(BuiltFile {
         binary_path: output_exe_path,
         problems,
@@ -1129,13 +1348,42 @@ fn spawn_rebuild_thread(
 ) -> std::thread::JoinHandle<u128> {
     let thread_local_target = target.clone();
     std::thread::spawn(move || {
+        let rebuild_host_start = Instant::now();
+
+        let cache_key = host_artifacts(
+            linking_strategy,
+            &thread_local_target,
+            &platform_main_roc,
+            &preprocessed_host_path,
+        )
+        .and_then(|artifacts| {
+            let key = HostCacheKey::new(
+                opt_level,
+                linking_strategy,
+                &thread_local_target,
+                &platform_main_roc,
+                &dll_stub_symbols,
+            )
+            .ok()?;
+
+            Some((key, artifacts))
+        });
+
+        if let Some((key, artifacts)) = &cache_key {
+            if key.restore(artifacts) {
+                if linking_strategy == LinkingStrategy::Surgical {
+                    std::fs::copy(&preprocessed_host_path, output_exe_path.as_path()).unwrap();
+                }
+
+                return rebuild_host_start.elapsed().as_millis();
+            }
+        }
+
         // Printing to stderr because we want stdout to contain only the output of the roc program.
         // We are aware of the trade-offs.
         // `cargo run` follows the same approach
         eprintln!("🔨 Rebuilding platform...");
 
-        let rebuild_host_start = Instant::now();
-
         match linking_strategy {
             LinkingStrategy::Additive => {
                 let host_dest = rebuild_host(
@@ -1170,6 +1418,11 @@ fn spawn_rebuild_thread(
             }
         }
 
+        if let Some((key, artifacts)) = cache_key {
+            // Failing to cache the host only means that the next build rebuilds it as well.
+            let _ = key.store(&artifacts);
+        }
+
         rebuild_host_start.elapsed().as_millis()
     })
 }
@@ -1215,78 +1468,179 @@ fn build_and_preprocess_host_lowlevel(
     )
 }
 
+/// Applying a fix can reveal another one (e.g. removing the only value imported from a module
+/// can leave the whole import unused), so `--fix` re-checks and fixes again, up to this many times.
+const MAX_FIX_ROUNDS: usize = 8;
+
+/// Load the given file only as far as type checking, and report any problems.
+///
+/// This never specializes or generates code, which makes it the fastest way to find out
+/// whether a program has problems (e.g. for editors or pre-commit hooks).
+///
+/// With `fix`, machine-applicable fixes are written back to the files in the checked file's
+/// directory first, and only the problems that remain are reported.
 #[allow(clippy::too_many_arguments)]
 pub fn check_file<'a>(
     arena: &'a Bump,
     roc_file_path: PathBuf,
     emit_timings: bool,
+    problem_output: ProblemOutput,
     roc_cache_dir: RocCacheDir<'_>,
     threading: Threading,
+    fix: bool,
 ) -> Result<(Problems, Duration), LoadingProblem<'a>> {
     let compilation_start = Instant::now();
 
     // only used for generating errors. We don't do code generation, so hardcoding should be fine
-    // we need monomorphization for when exhaustiveness checking
     let target_info = TargetInfo::default_x86_64();
 
-    // Step 1: compile the app and generate the .o file
-
-    let load_config = LoadConfig {
+    let load_config = || LoadConfig {
         target_info,
         // TODO: we may not want this for just checking.
         function_kind: FunctionKind::LambdaSet,
-        // TODO: expose this from CLI?
-        render: RenderTarget::ColorTerminal,
-        palette: DEFAULT_PALETTE,
+        render: match problem_output {
+            ProblemOutput::Text => RenderTarget::ColorTerminal,
+            ProblemOutput::Json => RenderTarget::Generic,
+        },
+        palette: *terminal_palette(),
         threading,
+        // Stops the loader after solving types, so no specialization or code gen happens.
         exec_mode: ExecutionMode::Check,
     };
     let mut loaded =
-        roc_load::load_and_typecheck(arena, roc_file_path, roc_cache_dir, load_config)?;
+        roc_load::load_and_typecheck(arena, roc_file_path.clone(), roc_cache_dir, load_config())?;
 
-    let buf = &mut String::with_capacity(1024);
+    if fix {
+        let mut fixes_applied = 0;
 
-    let mut it = loaded.timings.iter().peekable();
-    while let Some((module_id, module_timing)) = it.next() {
-        let module_name = loaded.interns.module_name(*module_id);
+        for _ in 0..MAX_FIX_ROUNDS {
+            let applied = apply_fixes(&loaded, &roc_file_path)?;
 
-        buf.push_str("    ");
+            if applied == 0 {
+                break;
+            }
 
-        if module_name.is_empty() {
-            // the App module
-            buf.push_str("Application Module");
-        } else {
-            buf.push_str(module_name);
+            fixes_applied += applied;
+            loaded = roc_load::load_and_typecheck(
+                arena,
+                roc_file_path.clone(),
+                roc_cache_dir,
+                load_config(),
+            )?;
         }
 
-        buf.push('\n');
+        if problem_output == ProblemOutput::Text {
+            match fixes_applied {
+                0 => println!("There were no problems that could be fixed automatically.\n"),
+                1 => println!("Fixed 1 problem.\n"),
+                n => println!("Fixed {n} problems.\n"),
+            }
+        }
+    }
 
-        report_timing(buf, "Read .roc file from disk", module_timing.read_roc_file);
-        report_timing(buf, "Parse header", module_timing.parse_header);
-        report_timing(buf, "Parse body", module_timing.parse_body);
-        report_timing(buf, "Canonicalize", module_timing.canonicalize);
-        report_timing(buf, "Constrain", module_timing.constrain);
-        report_timing(buf, "Solve", module_timing.solve);
-        report_timing(buf, "Other", module_timing.other());
-        buf.push('\n');
-        report_timing(buf, "Total", module_timing.total());
+    if emit_timings {
+        let buf = &mut String::with_capacity(1024);
+
+        let mut it = loaded.timings.iter().peekable();
+        while let Some((module_id, module_timing)) = it.next() {
+            let module_name = loaded.interns.module_name(*module_id);
+
+            buf.push_str("    ");
+
+            if module_name.is_empty() {
+                // the App module
+                buf.push_str("Application Module");
+            } else {
+                buf.push_str(module_name);
+            }
 
-        if it.peek().is_some() {
             buf.push('\n');
+
+            report_timing(buf, "Read .roc file from disk", module_timing.read_roc_file);
+            report_timing(buf, "Parse header", module_timing.parse_header);
+            report_timing(buf, "Parse body", module_timing.parse_body);
+            report_timing(buf, "Canonicalize", module_timing.canonicalize);
+            report_timing(buf, "Constrain", module_timing.constrain);
+            report_timing(buf, "Solve", module_timing.solve);
+            report_timing(buf, "Other", module_timing.other());
+            buf.push('\n');
+            report_timing(buf, "Total", module_timing.total());
+
+            if it.peek().is_some() {
+                buf.push('\n');
+            }
+        }
+
+        let phase_timings = PhaseTimings::from_modules(loaded.timings.values());
+        let timings = format!(
+            "\n\nCompilation finished!\n\nHere's how long each module took to compile:\n\n{buf}\n\
+             Finished checking in {} ms\n\n\
+             {}",
+            compilation_start.elapsed().as_millis(),
+            phase_timings.report(compilation_start.elapsed())
+        );
+
+        // JSON output has to be the only thing on stdout, so that it can be parsed.
+        match problem_output {
+            ProblemOutput::Text => println!("{timings}"),
+            ProblemOutput::Json => eprintln!("{timings}"),
         }
     }
 
-    let compilation_end = compilation_start.elapsed();
+    let problems = match problem_output {
+        ProblemOutput::Text => report_problems_typechecked(&mut loaded),
+        ProblemOutput::Json => report_problems_json(
+            &loaded.sources,
+            &loaded.interns,
+            &loaded.imported_symbols,
+            &mut loaded.can_problems,
+            &mut loaded.type_problems,
+        ),
+    };
 
-    if emit_timings {
-        println!(
-            "\n\nCompilation finished!\n\nHere's how long each module took to compile:\n\n{buf}"
-        );
+    // Include the time spent rendering problems, so this is the wall time the user waited.
+    Ok((problems, compilation_start.elapsed()))
+}
+
+/// Write machine-applicable fixes to the modules in the same directory as the checked file
+/// (or below it), returning how many fixes were applied. Packages are never modified.
+fn apply_fixes<'a>(
+    loaded: &LoadedModule,
+    roc_file_path: &Path,
+) -> Result<usize, LoadingProblem<'a>> {
+    let file_problem = |filename: &Path| {
+        move |err: std::io::Error| LoadingProblem::FileProblem {
+            filename: filename.to_path_buf(),
+            error: err.kind(),
+        }
+    };
 
-        println!("Finished checking in {} ms\n", compilation_end.as_millis(),);
+    let roc_file_path = roc_file_path
+        .canonicalize()
+        .map_err(file_problem(roc_file_path))?;
+    let project_dir = roc_file_path.parent().unwrap_or(&roc_file_path);
+
+    let fixed_modules = apply_machine_applicable_fixes(
+        &loaded.sources,
+        &loaded.interns,
+        &loaded.can_problems,
+        &loaded.type_problems,
+    );
+
+    let mut applied = 0;
+
+    for FixedModule { path, src, fixes } in fixed_modules {
+        let in_project = path
+            .canonicalize()
+            .map_or(false, |path| path.starts_with(project_dir));
+
+        if in_project {
+            std::fs::write(&path, src).map_err(file_problem(&path))?;
+            applied += fixes;
+        }
     }
 
-    Ok((report_problems_typechecked(&mut loaded), compilation_end))
+    Ok(applied)
 }
 
 pub fn build_str_test<'a>(
@@ -1302,6 +1656,7 @@ pub fn build_str_test<'a>(
         opt_level: OptLevel::Normal,
         emit_debug_info: false,
         emit_llvm_ir: false,
+        emit_artifact: None,
         fuzz: false,
     };
 
@@ -1337,6 +1692,7 @@ pub fn build_str_test<'a>(
         emit_timings,
         link_type,
         linking_strategy,
+        &LinkOptions::default(),
         assume_prebuild,
         wasm_dev_stack_bytes,
         loaded,
@@ -1348,9 +1704,14 @@ pub fn build_str_test<'a>(
 fn with_output_extension(
     path: &Path,
     os: OperatingSystem,
+    emit_artifact: Option<CodeGenArtifact>,
     linking_strategy: LinkingStrategy,
     link_type: LinkType,
 ) -> PathBuf {
+    if let Some(artifact) = emit_artifact {
+        return path.with_extension(artifact.extension());
+    }
+
     match (linking_strategy, link_type) {
         (LinkingStrategy::Additive, _) | (LinkingStrategy::Legacy, LinkType::None) => {
             // Additive linking and no linking both output the object file type.
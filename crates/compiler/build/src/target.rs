@@ -11,6 +11,12 @@ pub fn target_triple_str(target: &Triple) -> &'static str {
     //
     // https://stackoverflow.com/questions/15036909/clang-how-to-list-supported-target-architectures
     match target {
+        Triple {
+            architecture: Architecture::X86_64,
+            operating_system: OperatingSystem::Linux,
+            environment: Environment::Musl,
+            ..
+        } => "x86_64-unknown-linux-musl",
         Triple {
             architecture: Architecture::X86_64,
             operating_system: OperatingSystem::Linux,
@@ -19,6 +19,38 @@ pub fn setSharedBuffer(ptr: [*]u8, length: usize) callconv(.C) usize {
     return 0;
 }
 
+// The counters of `roc test --coverage`, one for each `when` branch.
+var COVERAGE_COUNTERS: [*]u64 = undefined;
+var COVERAGE_COUNTER_COUNT: usize = 0;
+
+pub fn setCoverageBuffer(ptr: [*]u64, length: usize) callconv(.C) usize {
+    COVERAGE_COUNTERS = ptr;
+    COVERAGE_COUNTER_COUNT = length;
+
+    // the rust side expects that a pointer is returned
+    return 0;
+}
+
+pub fn coverageHit(counter: u64) callconv(.C) void {
+    if (counter < COVERAGE_COUNTER_COUNT) {
+        COVERAGE_COUNTERS[@as(usize, @intCast(counter))] += 1;
+    }
+}
+
+// The arguments `roc test` generated for the property it is checking.
+var PROPERTY_ARGS: [*]u8 = undefined;
+
+pub fn setPropertyArgs(ptr: [*]u8) callconv(.C) usize {
+    PROPERTY_ARGS = ptr;
+
+    // the rust side expects that a pointer is returned
+    return 0;
+}
+
+pub fn propertyArgs() callconv(.C) [*]u8 {
+    return PROPERTY_ARGS;
+}
+
 pub fn expectFailedStartSharedBuffer() callconv(.C) [*]u8 {
     return SHARED_BUFFER.ptr;
 }
@@ -63,3 +63,21 @@ pub fn host_tempfile() -> std::io::Result<NamedTempFile> {
         unreachable!()
     }
 }
+
+/// The builtins object file that [host_tempfile] writes out.
+pub fn host_bytes() -> &'static [u8] {
+    #[cfg(unix)]
+    {
+        HOST_UNIX
+    }
+
+    #[cfg(windows)]
+    {
+        HOST_WINDOWS
+    }
+
+    #[cfg(not(any(windows, unix)))]
+    {
+        unreachable!()
+    }
+}
@@ -231,6 +231,13 @@ comptime {
     exportUtilsFn(utils.decrefCheckNullC, "decref_check_null");
     exportUtilsFn(utils.allocateWithRefcountC, "allocate_with_refcount");
     exportUtilsFn(utils.dictPseudoSeed, "dict_pseudo_seed");
+    exportUtilsFn(utils.allocC, "alloc");
+    exportUtilsFn(utils.deallocC, "dealloc");
+    exportUtilsFn(utils.dbgC, "dbg");
+
+    // lets the host install its own allocator and hooks, see `utils.HostAbi`.
+    // The version is part of the name, so that a host built against other hooks fails to link.
+    @export(utils.setHostAbi, .{ .name = std.fmt.comptimePrint("roc_set_host_abi_v{d}", .{utils.HOST_ABI_VERSION}), .linkage = .Weak });
 
     @export(panic_utils.panic, .{ .name = "roc_builtins.utils." ++ "panic", .linkage = .Weak });
     @export(dbg_utils.dbg_impl, .{ .name = "roc_builtins.utils." ++ "dbg_impl", .linkage = .Weak });
@@ -244,6 +251,14 @@ comptime {
         @export(expect.setSharedBuffer, .{ .name = "set_shared_buffer", .linkage = .Weak });
 
         exportUtilsFn(expect.readSharedBufferEnv, "read_env_shared_buffer");
+
+        // counts how often `when` branches run, for `roc test --coverage`
+        @export(expect.setCoverageBuffer, .{ .name = "set_coverage_buffer", .linkage = .Weak });
+        @export(expect.coverageHit, .{ .name = "roc_coverage_hit", .linkage = .Weak });
+
+        // the arguments of properties, for `roc test --properties`
+        @export(expect.setPropertyArgs, .{ .name = "set_property_args", .linkage = .Weak });
+        @export(expect.propertyArgs, .{ .name = "roc_property_args", .linkage = .Weak });
     }
 
     if (builtin.target.cpu.arch == .aarch64) {
@@ -1,15 +1,20 @@
 const std = @import("std");
 const RocStr = @import("str.zig").RocStr;
+const utils = @import("utils.zig");
 
 // Signals to the host that the program has panicked
 extern fn roc_panic(msg: *const RocStr, tag_id: u32) callconv(.C) noreturn;
 
 pub fn panic_help(msg: []const u8, tag_id: u32) noreturn {
     var str = RocStr.init(msg.ptr, msg.len);
-    roc_panic(&str, tag_id);
+    panic(&str, tag_id);
 }
 
 // must export this explicitly because right now it is not used from zig code
-pub fn panic(msg: *const RocStr, alignment: u32) callconv(.C) noreturn {
-    return roc_panic(msg, alignment);
+pub fn panic(msg: *const RocStr, tag_id: u32) callconv(.C) noreturn {
+    if (utils.host_abi) |abi| {
+        abi.panic(abi.context, msg, tag_id);
+    }
+
+    return roc_panic(msg, tag_id);
 }
@@ -27,6 +27,40 @@ pub fn test_dbg(loc: *anyopaque, src: *anyopaque, message: *anyopaque) callconv(
     roc_dbg(loc, message, src);
 }
 
+/// The version of `HostAbi` these builtins were written against.
+/// Bump this whenever the layout of `HostAbi` changes, together with `ROC_HOST_ABI_VERSION` in roc_std
+/// and the `SET_HOST_ABI` symbol name in bitcode.rs, which ends in this version.
+pub const HOST_ABI_VERSION: u32 = 1;
+
+/// Hooks a host can install for a run with `roc_set_host_abi_v1`, in place of the
+/// `roc_alloc`, `roc_realloc`, `roc_dealloc`, `roc_panic`, and `roc_dbg` symbols it links in.
+/// Every hook gets `context` as its first argument, e.g. the arena to allocate in.
+pub const HostAbi = extern struct {
+    version: u32,
+    context: ?*anyopaque,
+    alloc: *const fn (?*anyopaque, usize, u32) callconv(.C) ?*anyopaque,
+    realloc: *const fn (?*anyopaque, *anyopaque, usize, usize, u32) callconv(.C) ?*anyopaque,
+    dealloc: *const fn (?*anyopaque, *anyopaque, u32) callconv(.C) void,
+    panic: *const fn (?*anyopaque, *const anyopaque, u32) callconv(.C) noreturn,
+    dbg: *const fn (?*anyopaque, *anyopaque, *anyopaque, *anyopaque) callconv(.C) void,
+};
+
+pub var host_abi: ?*const HostAbi = null;
+
+/// Installs `abi`, or goes back to the linked-in symbols if it is null.
+/// Returns the `HOST_ABI_VERSION` these builtins were compiled with; hooks of another version are not installed.
+pub fn setHostAbi(abi: ?*const HostAbi) callconv(.C) u32 {
+    if (abi) |hooks| {
+        if (hooks.version != HOST_ABI_VERSION) {
+            return HOST_ABI_VERSION;
+        }
+    }
+
+    host_abi = abi;
+
+    return HOST_ABI_VERSION;
+}
+
 extern fn kill(pid: c_int, sig: c_int) c_int;
 extern fn shm_open(name: *const i8, oflag: c_int, mode: c_uint) c_int;
 extern fn mmap(addr: ?*anyopaque, length: c_uint, prot: c_int, flags: c_int, fd: c_int, offset: c_uint) *anyopaque;
@@ -126,20 +160,45 @@ fn testing_roc_panic(c_ptr: *anyopaque, tag_id: u32) callconv(.C) void {
 }
 
 pub fn alloc(size: usize, alignment: u32) ?[*]u8 {
-    return @as(?[*]u8, @ptrCast(roc_alloc(size, alignment)));
+    const ptr = if (host_abi) |abi| abi.alloc(abi.context, size, alignment) else roc_alloc(size, alignment);
+
+    return @as(?[*]u8, @ptrCast(ptr));
 }
 
 pub fn realloc(c_ptr: [*]u8, new_size: usize, old_size: usize, alignment: u32) [*]u8 {
     if (DEBUG_INCDEC and builtin.target.cpu.arch != .wasm32) {
         std.debug.print("- realloc {*}\n", .{c_ptr});
     }
-    return @as([*]u8, @ptrCast(roc_realloc(c_ptr, new_size, old_size, alignment)));
+    const ptr = if (host_abi) |abi| abi.realloc(abi.context, c_ptr, new_size, old_size, alignment) else roc_realloc(c_ptr, new_size, old_size, alignment);
+
+    return @as([*]u8, @ptrCast(ptr));
 }
 
 pub fn dealloc(c_ptr: [*]u8, alignment: u32) void {
+    if (host_abi) |abi| {
+        return abi.dealloc(abi.context, c_ptr, alignment);
+    }
+
     return roc_dealloc(c_ptr, alignment);
 }
 
+// The versions of alloc, dealloc, and dbg that the generated code calls
+pub fn allocC(size: usize, alignment: u32) callconv(.C) ?*anyopaque {
+    return alloc(size, alignment);
+}
+
+pub fn deallocC(c_ptr: *anyopaque, alignment: u32) callconv(.C) void {
+    dealloc(@as([*]u8, @ptrCast(c_ptr)), alignment);
+}
+
+pub fn dbgC(loc: *anyopaque, message: *anyopaque, src: *anyopaque) callconv(.C) void {
+    if (host_abi) |abi| {
+        return abi.dbg(abi.context, loc, message, src);
+    }
+
+    roc_dbg(loc, message, src);
+}
+
 // indirection because otherwise zig creates an alias to the panic function which our LLVM code
 // does not know how to deal with
 pub fn test_panic(c_ptr: *anyopaque, crash_tag: u32) callconv(.C) void {
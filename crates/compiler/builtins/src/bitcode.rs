@@ -432,6 +432,15 @@ pub const UTILS_FREE_DATA_PTR: &str = "roc_builtins.utils.free_data_ptr";
 pub const UTILS_IS_UNIQUE: &str = "roc_builtins.utils.is_unique";
 pub const UTILS_DECREF_CHECK_NULL: &str = "roc_builtins.utils.decref_check_null";
 pub const UTILS_DICT_PSEUDO_SEED: &str = "roc_builtins.utils.dict_pseudo_seed";
+pub const UTILS_ALLOC: &str = "roc_builtins.utils.alloc";
+pub const UTILS_DEALLOC: &str = "roc_builtins.utils.dealloc";
+pub const UTILS_PANIC: &str = "roc_builtins.utils.panic";
+pub const UTILS_DBG: &str = "roc_builtins.utils.dbg";
+
+/// Lets a host install its own allocator and hooks. The name ends in `utils.HOST_ABI_VERSION`,
+/// so a host built against another version of the hooks fails to link instead of misbehaving.
+/// Keep it in sync with that constant and with `ROC_HOST_ABI_VERSION` in roc_std.
+pub const SET_HOST_ABI: &str = "roc_set_host_abi_v1";
 
 pub const UTILS_EXPECT_FAILED_START_SHARED_BUFFER: &str =
     "roc_builtins.utils.expect_failed_start_shared_buffer";
@@ -3,7 +3,18 @@ use roc_module::symbol::ModuleId;
 
 #[inline(always)]
 pub fn module_source(module_id: ModuleId) -> &'static str {
-    match module_id {
+    match try_module_source(module_id) {
+        Some(source) => source,
+        None => internal_error!(
+            "ModuleId {:?} is not part of the standard library",
+            module_id
+        ),
+    }
+}
+
+/// The source of a standard library module, or None if the module isn't one of them.
+pub fn try_module_source(module_id: ModuleId) -> Option<&'static str> {
+    let source = match module_id {
         ModuleId::RESULT => RESULT,
         ModuleId::NUM => NUM,
         ModuleId::STR => STR,
@@ -17,11 +28,10 @@ pub fn module_source(module_id: ModuleId) -> &'static str {
         ModuleId::HASH => HASH,
         ModuleId::INSPECT => INSPECT,
         ModuleId::JSON => JSON,
-        _ => internal_error!(
-            "ModuleId {:?} is not part of the standard library",
-            module_id
-        ),
-    }
+        _ => return None,
+    };
+
+    Some(source)
 }
 
 const RESULT: &str = include_str!("../roc/Result.roc");
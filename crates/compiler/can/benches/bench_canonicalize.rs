@@ -0,0 +1,81 @@
+use bumpalo::Bump;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use roc_can::desugar::desugar_expr;
+use roc_can::env::Env;
+use roc_can::expr::canonicalize_expr;
+use roc_can::scope::Scope;
+use roc_module::symbol::{IdentIds, ModuleIds};
+use roc_region::all::Region;
+use roc_types::subs::VarStore;
+
+/// An expression with many defs which destructure records and tuples, so that every def binds
+/// several symbols.
+fn many_defs_source(count: usize) -> String {
+    let mut src = String::new();
+
+    for i in 0..count {
+        src.push_str(&format!(
+            "{{ a{i}, b{i} }} = {{ a: {i}, b: \"{i}\" }}\n(c{i}, d{i}) = (a{i}, [b{i}])\n"
+        ));
+    }
+
+    let sum = (0..count).map(|i| format!("c{i}")).collect::<Vec<_>>();
+    src.push_str(&sum.join(" + "));
+
+    src
+}
+
+/// An expression with a chain of functions which each call the previous one. None of them
+/// capture anything, which canonicalization has to find out for every one of them.
+fn many_closures_source(count: usize) -> String {
+    let mut src = String::from("f0 = \\x -> x\n");
+
+    for i in 1..count {
+        src.push_str(&format!("f{i} = \\x -> f{} x\n", i - 1));
+    }
+
+    src.push_str(&format!("f{} 1", count - 1));
+
+    src
+}
+
+fn canonicalize_source(src: &str) {
+    let arena = Bump::new();
+    let home = ModuleIds::default().get_or_insert(&"Bench".into());
+    let module_ids = ModuleIds::default();
+    let dep_idents = IdentIds::exposed_builtins(0);
+
+    let loc_expr = roc_parse::test_helpers::parse_loc_with(&arena, src).unwrap();
+    let loc_expr = desugar_expr(&arena, &loc_expr, src, &mut None, "Bench.roc");
+
+    let mut var_store = VarStore::default();
+    let mut scope = Scope::new(home, IdentIds::default(), Default::default());
+    let mut env = Env::new(&arena, home, &dep_idents, &module_ids);
+
+    let (loc_expr, _output) = canonicalize_expr(
+        &mut env,
+        &mut var_store,
+        &mut scope,
+        Region::zero(),
+        &loc_expr.value,
+    );
+
+    black_box(loc_expr);
+}
+
+pub fn canonicalize_benchmark(c: &mut Criterion) {
+    c.bench_function("canonicalize 500 destructuring defs", |b| {
+        let src = many_defs_source(500);
+
+        b.iter(|| canonicalize_source(&src))
+    });
+
+    c.bench_function("canonicalize 500 non-capturing closures", |b| {
+        let src = many_closures_source(500);
+
+        b.iter(|| canonicalize_source(&src))
+    });
+}
+
+criterion_group!(benches, canonicalize_benchmark);
+criterion_main!(benches);
@@ -477,6 +477,57 @@ impl IAbilitiesStore<Resolved> {
         self.resolved_specializations.get(&id).copied()
     }
 
+    /// Replaces every symbol in the store, e.g. to translate a store that was persisted by an
+    /// earlier run of the compiler into the `ModuleId`s of this run.
+    pub fn remap_symbols(&mut self, remap: impl Fn(Symbol) -> Symbol) {
+        let remap_impl_key = |impl_key: ImplKey| ImplKey {
+            opaque: remap(impl_key.opaque),
+            ability_member: remap(impl_key.ability_member),
+        };
+
+        self.members_of_ability = std::mem::take(&mut self.members_of_ability)
+            .into_iter()
+            .map(|(ability, members)| (remap(ability), members.into_iter().map(&remap).collect()))
+            .collect();
+
+        self.specialization_to_root = std::mem::take(&mut self.specialization_to_root)
+            .into_iter()
+            .map(|(symbol, impl_key)| (remap(symbol), remap_impl_key(impl_key)))
+            .collect();
+
+        self.ability_members = std::mem::take(&mut self.ability_members)
+            .into_iter()
+            .map(|(member, mut data)| {
+                data.parent_ability = remap(data.parent_ability);
+                (remap(member), data)
+            })
+            .collect();
+
+        self.declared_implementations = std::mem::take(&mut self.declared_implementations)
+            .into_iter()
+            .map(|(impl_key, member_impl)| {
+                let member_impl = match member_impl {
+                    MemberImpl::Impl(symbol) => MemberImpl::Impl(remap(symbol)),
+                    MemberImpl::Error => MemberImpl::Error,
+                };
+
+                (remap_impl_key(impl_key), member_impl)
+            })
+            .collect();
+
+        self.specializations = std::mem::take(&mut self.specializations)
+            .into_iter()
+            .map(|(symbol, mut info)| {
+                info.symbol = remap(info.symbol);
+                (remap(symbol), info)
+            })
+            .collect();
+
+        for specialization in self.resolved_specializations.values_mut() {
+            *specialization = remap(*specialization);
+        }
+    }
+
     pub fn serialize(&self, writer: &mut impl std::io::Write) -> std::io::Result<usize> {
         serialize::serialize(self, writer)
     }
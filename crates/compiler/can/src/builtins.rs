@@ -1,9 +1,9 @@
 use crate::def::Def;
-use crate::expr::{AnnotatedMark, ClosureData, Expr::*};
+use crate::expr::{AnnotatedMark, Captures, ClosureData, Expr::*};
 use crate::expr::{Expr, Recursive};
 
 use crate::pattern::Pattern;
-use roc_collections::all::SendMap;
+use roc_collections::VecMap;
 use roc_module::ident::TagName;
 use roc_module::low_level::LowLevel;
 use roc_module::symbol::Symbol;
@@ -412,7 +412,7 @@ fn defn(
             value: expr,
         },
         expr_var: var_store.fresh(),
-        pattern_vars: SendMap::default(),
+        pattern_vars: VecMap::default(),
         annotation: None,
     }
 }
@@ -443,7 +443,7 @@ fn defn_help(
         closure_type: var_store.fresh(),
         return_type: ret_var,
         name: fn_name,
-        captured_symbols: Vec::new(),
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: closure_args,
         loc_body: Box::new(no_region(body)),
@@ -540,7 +540,7 @@ fn to_num_checked(symbol: Symbol, var_store: &mut VarStore, lowlevel: LowLevel)
             ret_var: record_var,
         }),
         expr_var: record_var,
-        pattern_vars: SendMap::default(),
+        pattern_vars: VecMap::default(),
         annotation: None,
     };
 
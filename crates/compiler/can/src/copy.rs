@@ -830,7 +830,7 @@ fn deep_copy_pattern_help<C: CopyEnv>(
             ident: *ident,
             specializes: *specializes,
         },
-        Shadowed(region, ident, symbol) => Shadowed(*region, ident.clone(), *symbol),
+        Shadowed(original, ident, symbol) => Shadowed(*original, ident.clone(), *symbol),
         OpaqueNotInScope(ident) => OpaqueNotInScope(ident.clone()),
         UnsupportedPattern(region) => UnsupportedPattern(*region),
         MalformedPattern(problem, region) => MalformedPattern(*problem, *region),
@@ -12,6 +12,7 @@ use crate::derive;
 use crate::env::Env;
 use crate::expr::get_lookup_symbols;
 use crate::expr::AnnotatedMark;
+use crate::expr::Captures;
 use crate::expr::ClosureData;
 use crate::expr::Declarations;
 use crate::expr::Expr::{self, *};
@@ -24,7 +25,7 @@ use crate::scope::{PendingAbilitiesInScope, Scope};
 use roc_collections::ReferenceMatrix;
 use roc_collections::VecMap;
 use roc_collections::VecSet;
-use roc_collections::{ImSet, MutMap, SendMap};
+use roc_collections::{ImSet, MutMap};
 use roc_error_macros::internal_error;
 use roc_module::ident::Ident;
 use roc_module::ident::Lowercase;
@@ -58,7 +59,7 @@ pub struct Def {
     pub loc_pattern: Loc<Pattern>,
     pub loc_expr: Loc<Expr>,
     pub expr_var: Variable,
-    pub pattern_vars: SendMap<Symbol, Variable>,
+    pub pattern_vars: VecMap<Symbol, Variable>,
     pub annotation: Option<Annotation>,
 }
 
@@ -1765,7 +1766,7 @@ pub(crate) fn sort_can_defs_new(
                                 def.loc_expr,
                                 def.expr_var,
                                 def.annotation,
-                                def.pattern_vars.into_iter().collect(),
+                                def.pattern_vars,
                             );
                         }
                     }
@@ -2019,7 +2020,7 @@ fn make_cycle_entry(symbol: Symbol, def: &Def) -> CycleEntry {
 }
 
 fn pattern_to_vars_by_symbol(
-    vars_by_symbol: &mut SendMap<Symbol, Variable>,
+    vars_by_symbol: &mut VecMap<Symbol, Variable>,
     pattern: &Pattern,
     expr_var: Variable,
 ) {
@@ -2097,7 +2098,7 @@ fn single_can_def(
     loc_can_expr: Loc<Expr>,
     expr_var: Variable,
     opt_loc_annotation: Option<Loc<crate::annotation::Annotation>>,
-    pattern_vars: SendMap<Symbol, Variable>,
+    pattern_vars: VecMap<Symbol, Variable>,
 ) -> Def {
     let def_annotation = opt_loc_annotation.map(|loc_annotation| Annotation {
         signature: loc_annotation.value.typ,
@@ -2159,7 +2160,7 @@ fn canonicalize_pending_value_def<'a>(
         AnnotationOnly(_, loc_can_pattern, loc_ann) => {
             // Make types for the body expr, even if we won't end up having a body.
             let expr_var = var_store.fresh();
-            let mut vars_by_symbol = SendMap::default();
+            let mut vars_by_symbol = VecMap::default();
 
             // annotation sans body cannot introduce new rigids that are visible in other annotations
             // but the rigids can show up in type error messages, so still register them
@@ -2188,10 +2189,11 @@ fn canonicalize_pending_value_def<'a>(
                 Pattern::Identifier(symbol) => RuntimeError::NoImplementationNamed {
                     def_symbol: *symbol,
                 },
-                Pattern::Shadowed(region, loc_ident, _new_symbol) => RuntimeError::Shadowing {
-                    original_region: *region,
+                Pattern::Shadowed(original, loc_ident, _new_symbol) => RuntimeError::Shadowing {
+                    original_region: original.region,
                     shadow: loc_ident.clone(),
                     kind: ShadowKind::Variable,
+                    original_symbol: original.value,
                 },
                 _ => RuntimeError::NoImplementation,
             };
@@ -2238,7 +2240,7 @@ fn canonicalize_pending_value_def<'a>(
                         closure_type: var_store.fresh(),
                         return_type: var_store.fresh(),
                         name: symbol,
-                        captured_symbols: Vec::new(),
+                        captured_symbols: Captures::new(),
                         recursive: Recursive::NotRecursive,
                         arguments: underscores,
                         loc_body: Box::new(body_expr),
@@ -2433,7 +2435,7 @@ fn canonicalize_pending_body<'a>(
     };
 
     let expr_var = var_store.fresh();
-    let mut vars_by_symbol = SendMap::default();
+    let mut vars_by_symbol = VecMap::default();
 
     pattern_to_vars_by_symbol(&mut vars_by_symbol, &loc_can_pattern.value, expr_var);
 
@@ -1,15 +1,20 @@
 use crate::annotation::IntroducedVariables;
 use crate::def::Def;
-use crate::expr::{AnnotatedMark, ClosureData, Declarations, Expr, Recursive, WhenBranchPattern};
+use crate::expr::{
+    AnnotatedMark, Captures, ClosureData, Declarations, Expr, Field, Recursive, WhenBranchPattern,
+};
 use crate::pattern::Pattern;
 use crate::scope::Scope;
-use roc_collections::{SendMap, VecMap, VecSet};
+use roc_collections::{VecMap, VecSet};
+use roc_error_macros::internal_error;
 use roc_module::called_via::CalledVia;
 use roc_module::ident::TagName;
 use roc_module::symbol::Symbol;
 use roc_region::all::{Loc, Region};
 use roc_types::subs::{ExhaustiveMark, RedundantMark, VarStore, Variable};
-use roc_types::types::{AliasKind, LambdaSet, OptAbleType, OptAbleVar, Type, TypeExtension};
+use roc_types::types::{
+    AliasCommon, AliasKind, LambdaSet, OptAbleType, OptAbleVar, Type, TypeExtension,
+};
 
 #[derive(Debug, Default, Clone, Copy)]
 pub(crate) struct HostedGeneratedFunctions {
@@ -18,8 +23,93 @@ pub(crate) struct HostedGeneratedFunctions {
     pub(crate) always: bool,
     pub(crate) loop_: bool,
     pub(crate) forever: bool,
+    pub(crate) attempt: bool,
+    pub(crate) map_ok: bool,
+    pub(crate) map_err: bool,
+    pub(crate) if_then: bool,
+    pub(crate) unless: bool,
+    pub(crate) bracket: bool,
+    pub(crate) once: bool,
+    pub(crate) batch: bool,
+    /// Not a function, but has `batch` hand its effects to the host's `roc_fx_batch`, instead of
+    /// running them one after the other itself. See [build_effect_batch].
+    pub(crate) host_batch: bool,
+    /// Not a function, but asks for an effect that passes a continuation to the host, see
+    /// [build_async_effect_actual]. The other functions can't be generated for such an effect.
+    pub(crate) asynchronous: bool,
+    /// `map_n[i]` asks for `map2` when `i` is 0, up to `map8`
+    pub(crate) map_n: [bool; MAX_MAP_ARITY - 1],
 }
 
+impl HostedGeneratedFunctions {
+    /// Whether the function called `name` is generated, or `None` if it can't be generated
+    pub(crate) fn requested_mut(&mut self, name: &str) -> Option<&mut bool> {
+        let requested = match name {
+            "after" => &mut self.after,
+            "map" => &mut self.map,
+            "always" => &mut self.always,
+            "loop" => &mut self.loop_,
+            "forever" => &mut self.forever,
+            "attempt" => &mut self.attempt,
+            "mapOk" => &mut self.map_ok,
+            "mapErr" => &mut self.map_err,
+            "ifThen" => &mut self.if_then,
+            "unless" => &mut self.unless,
+            "bracket" => &mut self.bracket,
+            "once" => &mut self.once,
+            "batch" => &mut self.batch,
+            other => &mut self.map_n[map_arity(other)? - 2],
+        };
+
+        Some(requested)
+    }
+}
+
+/// The description that docs give the generated function called `name`, like `map` or `map3`
+pub fn generated_function_docs(name: &str) -> Option<String> {
+    let docs = match name {
+        "after" => "Runs an effect, then passes its result to a function that gives the effect to run next.",
+        "map" => "Runs an effect, and transforms its result with a function.",
+        "always" => "An effect that doesn't do anything, and always gives back the value it was made with.",
+        "loop" => "Runs the effect that a step function gives for the current state, for as long as that effect gives back `Step` with a new state. Stops at `Done`, and gives back its value.",
+        "forever" => "Runs an effect over and over again, so it never finishes.",
+        "attempt" => "Runs an effect that can fail, then passes its `Result` to a function that gives the effect to run next.",
+        "mapOk" => "Runs an effect that can fail, and transforms its result with a function if it succeeded.",
+        "mapErr" => "Runs an effect that can fail, and transforms its error with a function if it failed.",
+        "ifThen" => "Runs an effect only if the condition is true.",
+        "unless" => "Runs an effect only if the condition is false.",
+        "bracket" => "Acquires a resource, passes it to a function that gives the effect to use it with, and releases it with another effect when that is done.",
+        "once" => "Runs an effect the first time that it is needed, and gives back that same result every time after.",
+        "batch" => "Runs a list of effects, and gives back their results in the same order. Depending on the platform, the effects run one after the other, or the host may run them concurrently.",
+        other => {
+            let arity = map_arity(other)?;
+
+            return Some(format!(
+                "Runs {arity} effects one after the other, and combines their results with a function."
+            ));
+        }
+    };
+
+    Some(docs.to_string())
+}
+
+/// `Some(3)` for `map3`, for the arities that `mapN` can be generated with
+fn map_arity(name: &str) -> Option<usize> {
+    let arity = name.strip_prefix("map")?;
+
+    if arity.starts_with('0') {
+        return None;
+    }
+
+    match arity.parse() {
+        Ok(arity @ 2..=MAX_MAP_ARITY) => Some(arity),
+        _ => None,
+    }
+}
+
+/// The most effects that a generated `mapN` function combines
+pub(crate) const MAX_MAP_ARITY: usize = 8;
+
 /// the Effects alias & associated functions
 ///
 /// A platform can define an Effect type in its header. It can have an arbitrary name
@@ -63,6 +153,15 @@ pub(crate) fn build_effect_builtins(
         declarations.push_def(def);
     }
 
+    // Effect.map2 : Effect a, Effect b, (a, b -> out) -> Effect out
+    for (index, requested) in generated_functions.map_n.into_iter().enumerate() {
+        if requested {
+            let (symbol, def) = build_effect_map_n(scope, effect_symbol, var_store, index + 2);
+            exposed_symbols.insert(symbol);
+            declarations.push_def(def);
+        }
+    }
+
     // Effect.always : a -> Effect a
     if generated_functions.always {
         let def = helper!(build_effect_always);
@@ -81,17 +180,76 @@ pub(crate) fn build_effect_builtins(
         declarations.push_def(def);
     }
 
-    // Useful when working on functions in this module. By default symbols that we named do now
-    // show up with their name. We have to register them like below to make the names show up in
-    // debug prints
-    if false {
-        scope.register_debug_idents();
+    // Effect.attempt : Effect (Result ok err), (Result ok err -> Effect b) -> Effect b
+    if generated_functions.attempt {
+        let def = helper!(build_effect_attempt);
+        declarations.push_def(def);
+    }
+
+    // Effect.mapOk : Effect (Result a err), (a -> b) -> Effect (Result b err)
+    if generated_functions.map_ok {
+        let def = helper!(build_effect_map_ok);
+        declarations.push_def(def);
+    }
+
+    // Effect.mapErr : Effect (Result ok a), (a -> b) -> Effect (Result ok b)
+    if generated_functions.map_err {
+        let def = helper!(build_effect_map_err);
+        declarations.push_def(def);
+    }
+
+    // Effect.bracket : Effect a, (a -> Effect {}), (a -> Effect b) -> Effect b
+    if generated_functions.bracket {
+        let def = helper!(build_effect_bracket);
+        declarations.push_def(def);
+    }
+
+    // Effect.once : Effect a -> Effect a
+    if generated_functions.once {
+        let def = helper!(build_effect_once);
+        declarations.push_def(def);
+    }
+
+    // Effect.batch : List (Effect a) -> Effect (List a)
+    if generated_functions.batch {
+        let (symbol, def) = build_effect_batch(
+            scope,
+            effect_symbol,
+            var_store,
+            generated_functions.host_batch,
+        );
+        exposed_symbols.insert(symbol);
+        declarations.push_def(def);
+    }
+
+    // Effect.ifThen : Bool, Effect {} -> Effect {}
+    if generated_functions.if_then {
+        let (symbol, def) = build_effect_conditional(scope, effect_symbol, var_store, "ifThen");
+        exposed_symbols.insert(symbol);
+        declarations.push_def(def);
+    }
+
+    // Effect.unless : Bool, Effect {} -> Effect {}
+    if generated_functions.unless {
+        let (symbol, def) = build_effect_conditional(scope, effect_symbol, var_store, "unless");
+        exposed_symbols.insert(symbol);
+        declarations.push_def(def);
     }
+
+    // So that the generated symbols show up with their names in debug prints
+    scope.register_debug_idents();
 }
 
+/// A symbol for the generated code. When the hosted module already has something by that name,
+/// this is a fresh symbol that can't be looked up, which is fine because the generated code only
+/// refers to its symbols directly. The names of the generated functions themselves are checked
+/// beforehand, see [HostedGeneratedFunctions::requested_mut].
 macro_rules! new_symbol {
     ($scope:expr, $name:expr) => {{
-        $scope.introduce($name.into(), Region::zero()).unwrap()
+        match $scope.introduce($name.into(), Region::zero()) {
+            Ok(symbol) => symbol,
+            Err((_, _, unreachable_symbol)) => unreachable_symbol,
+        }
     }};
 }
 
@@ -102,19 +260,11 @@ fn build_effect_always(
 ) -> (Symbol, Def) {
     // Effect.always = \value -> @Effect \{} -> value
 
-    let value_symbol = {
-        scope
-            .introduce("effect_always_value".into(), Region::zero())
-            .unwrap()
-    };
+    let value_symbol = new_symbol!(scope, "effect_always_value");
 
-    let inner_closure_symbol = {
-        scope
-            .introduce("effect_always_inner".into(), Region::zero())
-            .unwrap()
-    };
+    let inner_closure_symbol = new_symbol!(scope, "effect_always_inner");
 
-    let always_symbol = { scope.introduce("always".into(), Region::zero()).unwrap() };
+    let always_symbol = new_symbol!(scope, "always");
 
     // \{} -> value
     let const_closure = {
@@ -132,7 +282,7 @@ fn build_effect_always(
             closure_type: var_store.fresh(),
             return_type: var_store.fresh(),
             name: inner_closure_symbol,
-            captured_symbols: vec![(value_symbol, value_var)],
+            captured_symbols: Captures::from([(value_symbol, value_var)]),
             recursive: Recursive::NotRecursive,
             arguments,
             loc_body: Box::new(Loc::at_zero(body)),
@@ -165,7 +315,7 @@ fn build_effect_always(
             closure_type: var_store.fresh(),
             return_type: var_store.fresh(),
             name: always_symbol,
-            captured_symbols: Vec::new(),
+            captured_symbols: Captures::new(),
             recursive: Recursive::NotRecursive,
             arguments,
             loc_body: Box::new(Loc::at_zero(body)),
@@ -183,7 +333,6 @@ fn build_effect_always(
 
         let effect_a = build_effect_opaque(
             effect_symbol,
-            var_a,
             Type::Variable(var_a),
             var_store,
             &mut introduced_variables,
@@ -207,7 +356,7 @@ fn build_effect_always(
     };
 
     let pattern = Pattern::Identifier(always_symbol);
-    let mut pattern_vars = SendMap::default();
+    let mut pattern_vars = VecMap::default();
     pattern_vars.insert(always_symbol, function_var);
     let def = Def {
         loc_pattern: Loc::at_zero(pattern),
@@ -227,21 +376,13 @@ fn build_effect_map(
 ) -> (Symbol, Def) {
     // Effect.map = \@Effect thunk, mapper -> @Effect \{} -> mapper (thunk {})
 
-    let thunk_symbol = {
-        scope
-            .introduce("effect_map_thunk".into(), Region::zero())
-            .unwrap()
-    };
+    let thunk_symbol = new_symbol!(scope, "effect_map_thunk");
     let thunk_var = var_store.fresh();
 
-    let mapper_symbol = {
-        scope
-            .introduce("effect_map_mapper".into(), Region::zero())
-            .unwrap()
-    };
+    let mapper_symbol = new_symbol!(scope, "effect_map_mapper");
     let mapper_var = var_store.fresh();
 
-    let map_symbol = { scope.introduce("map".into(), Region::zero()).unwrap() };
+    let map_symbol = new_symbol!(scope, "map");
 
     // `thunk {}`
     let force_thunk_call = {
@@ -265,84 +406,1283 @@ fn build_effect_map(
             var_store.fresh(),
         );
 
-        let arguments = vec![(var_store.fresh(), Loc::at_zero(force_thunk_call))];
-        Expr::Call(Box::new(boxed), arguments, CalledVia::Space)
-    };
+        let arguments = vec![(var_store.fresh(), Loc::at_zero(force_thunk_call))];
+        Expr::Call(Box::new(boxed), arguments, CalledVia::Space)
+    };
+
+    let inner_closure_symbol = new_symbol!(scope, "effect_map_inner");
+
+    // \{} -> mapper (thunk {})
+    let inner_closure = {
+        let arguments = vec![(
+            var_store.fresh(),
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(empty_record_pattern(var_store)),
+        )];
+
+        Expr::Closure(ClosureData {
+            function_type: var_store.fresh(),
+            closure_type: var_store.fresh(),
+            return_type: var_store.fresh(),
+            name: inner_closure_symbol,
+            captured_symbols: Captures::from([
+                (thunk_symbol, var_store.fresh()),
+                (mapper_symbol, var_store.fresh()),
+            ]),
+            recursive: Recursive::NotRecursive,
+            arguments,
+            loc_body: Box::new(Loc::at_zero(mapper_call)),
+        })
+    };
+
+    // \@Effect thunk, mapper
+    let (specialized_def_type, type_arguments, lambda_set_variables) =
+        build_fresh_opaque_variables(var_store);
+    let arguments = vec![
+        (
+            var_store.fresh(),
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(Pattern::UnwrappedOpaque {
+                opaque: effect_symbol,
+                whole_var: var_store.fresh(),
+                argument: Box::new((
+                    var_store.fresh(),
+                    Loc::at_zero(Pattern::Identifier(thunk_symbol)),
+                )),
+                specialized_def_type,
+                type_arguments,
+                lambda_set_variables,
+            }),
+        ),
+        (
+            var_store.fresh(),
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(Pattern::Identifier(mapper_symbol)),
+        ),
+    ];
+
+    // `@Effect \{} -> (mapper (thunk {}))`
+    let (specialized_def_type, type_arguments, lambda_set_variables) =
+        build_fresh_opaque_variables(var_store);
+    let body = Expr::OpaqueRef {
+        opaque_var: var_store.fresh(),
+        name: effect_symbol,
+        argument: Box::new((var_store.fresh(), Loc::at_zero(inner_closure))),
+        specialized_def_type,
+        type_arguments,
+        lambda_set_variables,
+    };
+
+    let function_var = var_store.fresh();
+    let map_closure = Expr::Closure(ClosureData {
+        function_type: function_var,
+        closure_type: var_store.fresh(),
+        return_type: var_store.fresh(),
+        name: map_symbol,
+        captured_symbols: Captures::new(),
+        recursive: Recursive::NotRecursive,
+        arguments,
+        loc_body: Box::new(Loc::at_zero(body)),
+    });
+
+    let mut introduced_variables = IntroducedVariables::default();
+
+    let signature = {
+        // Effect.map : Effect a, (a -> b) -> Effect b
+        let var_a = var_store.fresh();
+        let var_b = var_store.fresh();
+
+        introduced_variables.insert_named("a".into(), Loc::at_zero(var_a));
+        introduced_variables.insert_named("b".into(), Loc::at_zero(var_b));
+
+        let effect_a = build_effect_opaque(
+            effect_symbol,
+            Type::Variable(var_a),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let effect_b = build_effect_opaque(
+            effect_symbol,
+            Type::Variable(var_b),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        let a_to_b = {
+            Type::Function(
+                vec![Type::Variable(var_a)],
+                Box::new(Type::Variable(closure_var)),
+                Box::new(Type::Variable(var_b)),
+            )
+        };
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        Type::Function(
+            vec![effect_a, a_to_b],
+            Box::new(Type::Variable(closure_var)),
+            Box::new(effect_b),
+        )
+    };
+
+    let def_annotation = crate::def::Annotation {
+        signature,
+        introduced_variables,
+        aliases: VecMap::default(),
+        region: Region::zero(),
+    };
+
+    let pattern = Pattern::Identifier(map_symbol);
+    let mut pattern_vars = VecMap::default();
+    pattern_vars.insert(map_symbol, function_var);
+    let def = Def {
+        loc_pattern: Loc::at_zero(pattern),
+        loc_expr: Loc::at_zero(map_closure),
+        expr_var: function_var,
+        pattern_vars,
+        annotation: Some(def_annotation),
+    };
+
+    (map_symbol, def)
+}
+
+fn build_effect_map_n(
+    scope: &mut Scope,
+    effect_symbol: Symbol,
+    var_store: &mut VarStore,
+    arity: usize,
+) -> (Symbol, Def) {
+    // Effect.map2 = \@Effect thunk1, @Effect thunk2, mapper ->
+    //     @Effect \{} -> mapper (thunk1 {}) (thunk2 {})
+
+    let name = format!("map{arity}");
+    let thunk_symbols: Vec<Symbol> = (1..=arity)
+        .map(|i| new_symbol!(scope, format!("effect_{name}_thunk{i}")))
+        .collect();
+    let mapper_symbol = new_symbol!(scope, format!("effect_{name}_mapper"));
+    let map_symbol = new_symbol!(scope, name.as_str());
+    let inner_closure_symbol = new_symbol!(scope, format!("effect_{name}_inner"));
+
+    // `mapper (thunk1 {}) (thunk2 {})`
+    let mapper_call = {
+        let mapper_var = var_store.fresh();
+        let boxed = (
+            mapper_var,
+            Loc::at_zero(Expr::Var(mapper_symbol, mapper_var)),
+            var_store.fresh(),
+            var_store.fresh(),
+        );
+
+        let arguments = thunk_symbols
+            .iter()
+            .map(|thunk_symbol| {
+                let thunk_var = var_store.fresh();
+                let call = force_thunk(Expr::Var(*thunk_symbol, thunk_var), thunk_var, var_store);
+
+                (var_store.fresh(), Loc::at_zero(call))
+            })
+            .collect();
+        Expr::Call(Box::new(boxed), arguments, CalledVia::Space)
+    };
+
+    let mut captured_symbols = thunk_symbols.clone();
+    captured_symbols.push(mapper_symbol);
+
+    // `@Effect \{} -> mapper (thunk1 {}) (thunk2 {})`
+    let body = wrap_in_effect_thunk(
+        mapper_call,
+        effect_symbol,
+        inner_closure_symbol,
+        captured_symbols,
+        var_store,
+    );
+
+    // \@Effect thunk1, @Effect thunk2, mapper
+    let mut arguments: Vec<_> = thunk_symbols
+        .iter()
+        .map(|thunk_symbol| {
+            let (specialized_def_type, type_arguments, lambda_set_variables) =
+                build_fresh_opaque_variables(var_store);
+
+            (
+                var_store.fresh(),
+                AnnotatedMark::new(var_store),
+                Loc::at_zero(Pattern::UnwrappedOpaque {
+                    opaque: effect_symbol,
+                    whole_var: var_store.fresh(),
+                    argument: Box::new((
+                        var_store.fresh(),
+                        Loc::at_zero(Pattern::Identifier(*thunk_symbol)),
+                    )),
+                    specialized_def_type,
+                    type_arguments,
+                    lambda_set_variables,
+                }),
+            )
+        })
+        .collect();
+    arguments.push((
+        var_store.fresh(),
+        AnnotatedMark::new(var_store),
+        Loc::at_zero(Pattern::Identifier(mapper_symbol)),
+    ));
+
+    let function_var = var_store.fresh();
+    let map_closure = Expr::Closure(ClosureData {
+        function_type: function_var,
+        closure_type: var_store.fresh(),
+        return_type: var_store.fresh(),
+        name: map_symbol,
+        captured_symbols: Captures::new(),
+        recursive: Recursive::NotRecursive,
+        arguments,
+        loc_body: Box::new(Loc::at_zero(body)),
+    });
+
+    let mut introduced_variables = IntroducedVariables::default();
+
+    let signature = {
+        // Effect.map2 : Effect a, Effect b, (a, b -> out) -> Effect out
+        let input_vars: Vec<Variable> = (0..arity).map(|_| var_store.fresh()).collect();
+        let var_out = var_store.fresh();
+
+        for (letter, var) in ('a'..).zip(&input_vars) {
+            introduced_variables.insert_named(letter.to_string().into(), Loc::at_zero(*var));
+        }
+        introduced_variables.insert_named("out".into(), Loc::at_zero(var_out));
+
+        let mut effect_arguments: Vec<Type> = input_vars
+            .iter()
+            .map(|var| {
+                build_effect_opaque(
+                    effect_symbol,
+                    Type::Variable(*var),
+                    var_store,
+                    &mut introduced_variables,
+                )
+            })
+            .collect();
+
+        let effect_out = build_effect_opaque(
+            effect_symbol,
+            Type::Variable(var_out),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        let mapper = Type::Function(
+            input_vars.into_iter().map(Type::Variable).collect(),
+            Box::new(Type::Variable(closure_var)),
+            Box::new(Type::Variable(var_out)),
+        );
+        effect_arguments.push(mapper);
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        Type::Function(
+            effect_arguments,
+            Box::new(Type::Variable(closure_var)),
+            Box::new(effect_out),
+        )
+    };
+
+    let def_annotation = crate::def::Annotation {
+        signature,
+        introduced_variables,
+        aliases: VecMap::default(),
+        region: Region::zero(),
+    };
+
+    let pattern = Pattern::Identifier(map_symbol);
+    let mut pattern_vars = VecMap::default();
+    pattern_vars.insert(map_symbol, function_var);
+    let def = Def {
+        loc_pattern: Loc::at_zero(pattern),
+        loc_expr: Loc::at_zero(map_closure),
+        expr_var: function_var,
+        pattern_vars,
+        annotation: Some(def_annotation),
+    };
+
+    (map_symbol, def)
+}
+
+fn build_effect_bracket(
+    scope: &mut Scope,
+    effect_symbol: Symbol,
+    var_store: &mut VarStore,
+) -> (Symbol, Def) {
+    // Effect.bracket = \@Effect acquire, release, use ->
+    //     @Effect \{} ->
+    //         resource = acquire {}
+    //         @Effect useThunk = use resource
+    //         result = useThunk {}
+    //         @Effect releaseThunk = release resource
+    //         {} = releaseThunk {}
+    //
+    //         result
+    //
+    // The effect returned by `use` can't skip the rest of the thunk, so the resource is released
+    // whether its result is an `Ok` or an `Err`.
+
+    let acquire_symbol = new_symbol!(scope, "effect_bracket_acquire");
+    let release_symbol = new_symbol!(scope, "effect_bracket_release");
+    let use_symbol = new_symbol!(scope, "effect_bracket_use");
+    let resource_symbol = new_symbol!(scope, "effect_bracket_resource");
+    let use_thunk_symbol = new_symbol!(scope, "effect_bracket_useThunk");
+    let result_symbol = new_symbol!(scope, "effect_bracket_result");
+    let release_thunk_symbol = new_symbol!(scope, "effect_bracket_releaseThunk");
+    let bracket_symbol = new_symbol!(scope, "bracket");
+    let inner_closure_symbol = new_symbol!(scope, "effect_bracket_inner");
+
+    // `f resource`
+    let mut call_with_resource = |function_symbol: Symbol| {
+        let function_var = var_store.fresh();
+        let boxed = (
+            function_var,
+            Loc::at_zero(Expr::Var(function_symbol, function_var)),
+            var_store.fresh(),
+            var_store.fresh(),
+        );
+
+        let resource_var = var_store.fresh();
+        let arguments = vec![(
+            resource_var,
+            Loc::at_zero(Expr::Var(resource_symbol, resource_var)),
+        )];
+        Expr::Call(Box::new(boxed), arguments, CalledVia::Space)
+    };
+
+    let use_call = call_with_resource(use_symbol);
+    let release_call = call_with_resource(release_symbol);
+
+    // `{} = (@Effect releaseThunk = release resource; releaseThunk {})` followed by `result`
+    let release = {
+        let def = Def {
+            loc_pattern: Loc::at_zero(empty_record_pattern(var_store)),
+            loc_expr: Loc::at_zero(force_effect(
+                release_call,
+                effect_symbol,
+                release_thunk_symbol,
+                var_store,
+            )),
+            expr_var: var_store.fresh(),
+            pattern_vars: VecMap::default(),
+            annotation: None,
+        };
+
+        let result = Expr::Var(result_symbol, var_store.fresh());
+        Expr::LetNonRec(Box::new(def), Box::new(Loc::at_zero(result)))
+    };
+
+    // `result = (@Effect useThunk = use resource; useThunk {})`
+    let use_resource = {
+        let result_var = var_store.fresh();
+        let mut pattern_vars = VecMap::default();
+        pattern_vars.insert(result_symbol, result_var);
+
+        let def = Def {
+            loc_pattern: Loc::at_zero(Pattern::Identifier(result_symbol)),
+            loc_expr: Loc::at_zero(force_effect(
+                use_call,
+                effect_symbol,
+                use_thunk_symbol,
+                var_store,
+            )),
+            expr_var: result_var,
+            pattern_vars,
+            annotation: None,
+        };
+
+        Expr::LetNonRec(Box::new(def), Box::new(Loc::at_zero(release)))
+    };
+
+    // `resource = acquire {}`
+    let acquire_resource = {
+        let resource_var = var_store.fresh();
+        let mut pattern_vars = VecMap::default();
+        pattern_vars.insert(resource_symbol, resource_var);
+
+        let acquire_var = var_store.fresh();
+        let def = Def {
+            loc_pattern: Loc::at_zero(Pattern::Identifier(resource_symbol)),
+            loc_expr: Loc::at_zero(force_thunk(
+                Expr::Var(acquire_symbol, acquire_var),
+                acquire_var,
+                var_store,
+            )),
+            expr_var: resource_var,
+            pattern_vars,
+            annotation: None,
+        };
+
+        Expr::LetNonRec(Box::new(def), Box::new(Loc::at_zero(use_resource)))
+    };
+
+    let body = wrap_in_effect_thunk(
+        acquire_resource,
+        effect_symbol,
+        inner_closure_symbol,
+        vec![acquire_symbol, release_symbol, use_symbol],
+        var_store,
+    );
+
+    let (specialized_def_type, type_arguments, lambda_set_variables) =
+        build_fresh_opaque_variables(var_store);
+
+    let arguments = vec![
+        (
+            var_store.fresh(),
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(Pattern::UnwrappedOpaque {
+                opaque: effect_symbol,
+                whole_var: var_store.fresh(),
+                argument: Box::new((
+                    var_store.fresh(),
+                    Loc::at_zero(Pattern::Identifier(acquire_symbol)),
+                )),
+                specialized_def_type,
+                type_arguments,
+                lambda_set_variables,
+            }),
+        ),
+        (
+            var_store.fresh(),
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(Pattern::Identifier(release_symbol)),
+        ),
+        (
+            var_store.fresh(),
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(Pattern::Identifier(use_symbol)),
+        ),
+    ];
+
+    let function_var = var_store.fresh();
+    let bracket_closure = Expr::Closure(ClosureData {
+        function_type: function_var,
+        closure_type: var_store.fresh(),
+        return_type: var_store.fresh(),
+        name: bracket_symbol,
+        captured_symbols: Captures::new(),
+        recursive: Recursive::NotRecursive,
+        arguments,
+        loc_body: Box::new(Loc::at_zero(body)),
+    });
+
+    let mut introduced_variables = IntroducedVariables::default();
+
+    let signature = {
+        // Effect.bracket : Effect a, (a -> Effect {}), (a -> Effect b) -> Effect b
+        let var_a = var_store.fresh();
+        let var_b = var_store.fresh();
+        introduced_variables.insert_named("a".into(), Loc::at_zero(var_a));
+        introduced_variables.insert_named("b".into(), Loc::at_zero(var_b));
+
+        let effect_a = build_effect_opaque(
+            effect_symbol,
+            Type::Variable(var_a),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let effect_empty = build_effect_opaque(
+            effect_symbol,
+            Type::EmptyRec,
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let effect_b1 = build_effect_opaque(
+            effect_symbol,
+            Type::Variable(var_b),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let effect_b2 = build_effect_opaque(
+            effect_symbol,
+            Type::Variable(var_b),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        let release = Type::Function(
+            vec![Type::Variable(var_a)],
+            Box::new(Type::Variable(closure_var)),
+            Box::new(effect_empty),
+        );
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        let use_ = Type::Function(
+            vec![Type::Variable(var_a)],
+            Box::new(Type::Variable(closure_var)),
+            Box::new(effect_b1),
+        );
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        Type::Function(
+            vec![effect_a, release, use_],
+            Box::new(Type::Variable(closure_var)),
+            Box::new(effect_b2),
+        )
+    };
+
+    let def_annotation = crate::def::Annotation {
+        signature,
+        introduced_variables,
+        aliases: VecMap::default(),
+        region: Region::zero(),
+    };
+
+    let pattern = Pattern::Identifier(bracket_symbol);
+    let mut pattern_vars = VecMap::default();
+    pattern_vars.insert(bracket_symbol, function_var);
+    let def = Def {
+        loc_pattern: Loc::at_zero(pattern),
+        loc_expr: Loc::at_zero(bracket_closure),
+        expr_var: function_var,
+        pattern_vars,
+        annotation: Some(def_annotation),
+    };
+
+    (bracket_symbol, def)
+}
+
+fn build_effect_map_ok(
+    scope: &mut Scope,
+    effect_symbol: Symbol,
+    var_store: &mut VarStore,
+) -> (Symbol, Def) {
+    build_effect_map_result(scope, effect_symbol, var_store, "mapOk", Symbol::RESULT_MAP)
+}
+
+fn build_effect_map_err(
+    scope: &mut Scope,
+    effect_symbol: Symbol,
+    var_store: &mut VarStore,
+) -> (Symbol, Def) {
+    build_effect_map_result(
+        scope,
+        effect_symbol,
+        var_store,
+        "mapErr",
+        Symbol::RESULT_MAP_ERR,
+    )
+}
+
+/// Builds `mapOk` with `Result.map`, or `mapErr` with `Result.mapErr`
+fn build_effect_map_result(
+    scope: &mut Scope,
+    effect_symbol: Symbol,
+    var_store: &mut VarStore,
+    name: &str,
+    result_map: Symbol,
+) -> (Symbol, Def) {
+    // Effect.mapOk = \@Effect thunk, mapper -> @Effect \{} -> Result.map (thunk {}) mapper
+
+    let thunk_symbol = new_symbol!(scope, format!("effect_{name}_thunk"));
+    let mapper_symbol = new_symbol!(scope, format!("effect_{name}_mapper"));
+    let map_symbol = new_symbol!(scope, name);
+    let inner_closure_symbol = new_symbol!(scope, format!("effect_{name}_inner"));
+
+    // `thunk {}`
+    let thunk_var = var_store.fresh();
+    let force_thunk_call = force_thunk(Expr::Var(thunk_symbol, thunk_var), thunk_var, var_store);
+
+    // `Result.map (thunk {}) mapper`
+    let result_map_call = {
+        let result_map_var = var_store.fresh();
+        let boxed = (
+            result_map_var,
+            Loc::at_zero(Expr::Var(result_map, result_map_var)),
+            var_store.fresh(),
+            var_store.fresh(),
+        );
+
+        let mapper_var = var_store.fresh();
+        let arguments = vec![
+            (var_store.fresh(), Loc::at_zero(force_thunk_call)),
+            (
+                mapper_var,
+                Loc::at_zero(Expr::Var(mapper_symbol, mapper_var)),
+            ),
+        ];
+        Expr::Call(Box::new(boxed), arguments, CalledVia::Space)
+    };
+
+    // `@Effect \{} -> Result.map (thunk {}) mapper`
+    let body = wrap_in_effect_thunk(
+        result_map_call,
+        effect_symbol,
+        inner_closure_symbol,
+        vec![thunk_symbol, mapper_symbol],
+        var_store,
+    );
+
+    // \@Effect thunk, mapper
+    let (specialized_def_type, type_arguments, lambda_set_variables) =
+        build_fresh_opaque_variables(var_store);
+    let arguments = vec![
+        (
+            var_store.fresh(),
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(Pattern::UnwrappedOpaque {
+                opaque: effect_symbol,
+                whole_var: var_store.fresh(),
+                argument: Box::new((
+                    var_store.fresh(),
+                    Loc::at_zero(Pattern::Identifier(thunk_symbol)),
+                )),
+                specialized_def_type,
+                type_arguments,
+                lambda_set_variables,
+            }),
+        ),
+        (
+            var_store.fresh(),
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(Pattern::Identifier(mapper_symbol)),
+        ),
+    ];
+
+    let function_var = var_store.fresh();
+    let map_closure = Expr::Closure(ClosureData {
+        function_type: function_var,
+        closure_type: var_store.fresh(),
+        return_type: var_store.fresh(),
+        name: map_symbol,
+        captured_symbols: Captures::new(),
+        recursive: Recursive::NotRecursive,
+        arguments,
+        loc_body: Box::new(Loc::at_zero(body)),
+    });
+
+    let mut introduced_variables = IntroducedVariables::default();
+
+    let signature = {
+        // Effect.mapOk : Effect (Result a err), (a -> b) -> Effect (Result b err)
+        // Effect.mapErr : Effect (Result ok a), (a -> b) -> Effect (Result ok b)
+        let var_a = var_store.fresh();
+        let var_b = var_store.fresh();
+        let var_other = var_store.fresh();
+
+        let other_name = if result_map == Symbol::RESULT_MAP {
+            "err"
+        } else {
+            "ok"
+        };
+
+        introduced_variables.insert_named("a".into(), Loc::at_zero(var_a));
+        introduced_variables.insert_named("b".into(), Loc::at_zero(var_b));
+        introduced_variables.insert_named(other_name.into(), Loc::at_zero(var_other));
+
+        let with_other = |mapped: Variable| {
+            if result_map == Symbol::RESULT_MAP {
+                result_type(Type::Variable(mapped), Type::Variable(var_other))
+            } else {
+                result_type(Type::Variable(var_other), Type::Variable(mapped))
+            }
+        };
+
+        let effect_a = build_effect_opaque(
+            effect_symbol,
+            with_other(var_a),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let effect_b = build_effect_opaque(
+            effect_symbol,
+            with_other(var_b),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        let a_to_b = Type::Function(
+            vec![Type::Variable(var_a)],
+            Box::new(Type::Variable(closure_var)),
+            Box::new(Type::Variable(var_b)),
+        );
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        Type::Function(
+            vec![effect_a, a_to_b],
+            Box::new(Type::Variable(closure_var)),
+            Box::new(effect_b),
+        )
+    };
+
+    let def_annotation = crate::def::Annotation {
+        signature,
+        introduced_variables,
+        aliases: VecMap::default(),
+        region: Region::zero(),
+    };
+
+    let pattern = Pattern::Identifier(map_symbol);
+    let mut pattern_vars = VecMap::default();
+    pattern_vars.insert(map_symbol, function_var);
+    let def = Def {
+        loc_pattern: Loc::at_zero(pattern),
+        loc_expr: Loc::at_zero(map_closure),
+        expr_var: function_var,
+        pattern_vars,
+        annotation: Some(def_annotation),
+    };
+
+    (map_symbol, def)
+}
+
+/// `[Ok ok, Err err]`, which is what `Result ok err` stands for
+fn result_type(ok: Type, err: Type) -> Type {
+    Type::TagUnion(
+        vec![
+            (TagName("Ok".into()), vec![ok]),
+            (TagName("Err".into()), vec![err]),
+        ],
+        TypeExtension::Closed,
+    )
+}
+
+fn force_thunk(expr: Expr, thunk_var: Variable, var_store: &mut VarStore) -> Expr {
+    let boxed = (
+        thunk_var,
+        Loc::at_zero(expr),
+        var_store.fresh(),
+        var_store.fresh(),
+    );
+
+    let arguments = vec![(var_store.fresh(), Loc::at_zero(Expr::EmptyRecord))];
+    Expr::Call(Box::new(boxed), arguments, CalledVia::Space)
+}
+
+fn build_effect_after(
+    scope: &mut Scope,
+    effect_opaque_symbol: Symbol,
+    var_store: &mut VarStore,
+) -> (Symbol, Def) {
+    // Effect.after : Effect a, (a -> Effect b) -> Effect b
+    let mut introduced_variables = IntroducedVariables::default();
+
+    let var_a = var_store.fresh();
+    introduced_variables.insert_named("a".into(), Loc::at_zero(var_a));
+
+    build_effect_chain(
+        scope,
+        effect_opaque_symbol,
+        var_store,
+        "after",
+        Type::Variable(var_a),
+        introduced_variables,
+    )
+}
+
+fn build_effect_attempt(
+    scope: &mut Scope,
+    effect_opaque_symbol: Symbol,
+    var_store: &mut VarStore,
+) -> (Symbol, Def) {
+    // Effect.attempt : Effect (Result ok err), (Result ok err -> Effect b) -> Effect b
+    let mut introduced_variables = IntroducedVariables::default();
+
+    let var_ok = var_store.fresh();
+    let var_err = var_store.fresh();
+    introduced_variables.insert_named("ok".into(), Loc::at_zero(var_ok));
+    introduced_variables.insert_named("err".into(), Loc::at_zero(var_err));
+
+    build_effect_chain(
+        scope,
+        effect_opaque_symbol,
+        var_store,
+        "attempt",
+        result_type(Type::Variable(var_ok), Type::Variable(var_err)),
+        introduced_variables,
+    )
+}
+
+/// Builds `once`, whose effect runs the given effect the first time only, and after that
+/// produces the same value again. The value is kept in a cell that the host provides with
+///
+/// - `roc_fx_onceCell`, which takes no arguments and returns a new, empty cell as a `U64`
+/// - `roc_fx_onceIsSet : U64 -> Bool`
+/// - `roc_fx_onceGet : U64 -> Box a`, which increments the refcount of the box it returns
+/// - `roc_fx_onceSet : U64, Box a -> {}`, which increments the refcount of the box it keeps
+///
+/// The cell is made when `once` is applied, so the cache is shared by all uses of the effect
+/// that `once` returns.
+fn build_effect_once(
+    scope: &mut Scope,
+    effect_symbol: Symbol,
+    var_store: &mut VarStore,
+) -> (Symbol, Def) {
+    // Effect.once = \@Effect thunk ->
+    //     cell : U64
+    //     cell = roc_fx_onceCell
+    //
+    //     @Effect \{} ->
+    //         if roc_fx_onceIsSet cell then
+    //             Box.unbox (roc_fx_onceGet cell)
+    //         else
+    //             value = thunk {}
+    //             {} = roc_fx_onceSet cell (Box.box value)
+    //
+    //             value
+
+    let thunk_symbol = new_symbol!(scope, "effect_once_thunk");
+    let cell_symbol = new_symbol!(scope, "effect_once_cell");
+    let value_symbol = new_symbol!(scope, "effect_once_value");
+    let once_symbol = new_symbol!(scope, "once");
+    let inner_closure_symbol = new_symbol!(scope, "effect_once_inner");
+
+    let new_cell = foreign_call("roc_fx_onceCell", vec![], var_store);
+    let is_set = foreign_call(
+        "roc_fx_onceIsSet",
+        vec![Expr::Var(cell_symbol, var_store.fresh())],
+        var_store,
+    );
+    let get = foreign_call(
+        "roc_fx_onceGet",
+        vec![Expr::Var(cell_symbol, var_store.fresh())],
+        var_store,
+    );
+
+    // `Box.box value`
+    let boxed_value = call_symbol(
+        Symbol::BOX_BOX_FUNCTION,
+        Expr::Var(value_symbol, var_store.fresh()),
+        var_store,
+    );
+
+    let set = foreign_call(
+        "roc_fx_onceSet",
+        vec![Expr::Var(cell_symbol, var_store.fresh()), boxed_value],
+        var_store,
+    );
+
+    // `{} = roc_fx_onceSet cell (Box.box value)` followed by `value`
+    let set_then_value = {
+        let def = Def {
+            loc_pattern: Loc::at_zero(empty_record_pattern(var_store)),
+            loc_expr: Loc::at_zero(set),
+            expr_var: var_store.fresh(),
+            pattern_vars: VecMap::default(),
+            annotation: None,
+        };
+
+        let value = Expr::Var(value_symbol, var_store.fresh());
+        Expr::LetNonRec(Box::new(def), Box::new(Loc::at_zero(value)))
+    };
+
+    // `value = thunk {}`
+    let run_thunk = {
+        let value_var = var_store.fresh();
+        let mut pattern_vars = VecMap::default();
+        pattern_vars.insert(value_symbol, value_var);
+
+        let thunk_var = var_store.fresh();
+        let def = Def {
+            loc_pattern: Loc::at_zero(Pattern::Identifier(value_symbol)),
+            loc_expr: Loc::at_zero(force_thunk(
+                Expr::Var(thunk_symbol, thunk_var),
+                thunk_var,
+                var_store,
+            )),
+            expr_var: value_var,
+            pattern_vars,
+            annotation: None,
+        };
+
+        Expr::LetNonRec(Box::new(def), Box::new(Loc::at_zero(set_then_value)))
+    };
+
+    let cached_or_run = Expr::If {
+        cond_var: var_store.fresh(),
+        branch_var: var_store.fresh(),
+        branches: vec![(
+            Loc::at_zero(is_set),
+            Loc::at_zero(call_symbol(Symbol::BOX_UNBOX, get, var_store)),
+        )],
+        final_else: Box::new(Loc::at_zero(run_thunk)),
+    };
+
+    let effect = wrap_in_effect_thunk(
+        cached_or_run,
+        effect_symbol,
+        inner_closure_symbol,
+        vec![thunk_symbol, cell_symbol],
+        var_store,
+    );
+
+    // `cell : U64`, `cell = roc_fx_onceCell`
+    let body = {
+        let cell_var = var_store.fresh();
+        let mut pattern_vars = VecMap::default();
+        pattern_vars.insert(cell_symbol, cell_var);
+
+        let def = Def {
+            loc_pattern: Loc::at_zero(Pattern::Identifier(cell_symbol)),
+            loc_expr: Loc::at_zero(new_cell),
+            expr_var: cell_var,
+            pattern_vars,
+            annotation: Some(crate::def::Annotation {
+                signature: Type::Variable(Variable::U64),
+                introduced_variables: IntroducedVariables::default(),
+                aliases: VecMap::default(),
+                region: Region::zero(),
+            }),
+        };
+
+        Expr::LetNonRec(Box::new(def), Box::new(Loc::at_zero(effect)))
+    };
+
+    let (specialized_def_type, type_arguments, lambda_set_variables) =
+        build_fresh_opaque_variables(var_store);
+
+    let arguments = vec![(
+        var_store.fresh(),
+        AnnotatedMark::new(var_store),
+        Loc::at_zero(Pattern::UnwrappedOpaque {
+            opaque: effect_symbol,
+            whole_var: var_store.fresh(),
+            argument: Box::new((
+                var_store.fresh(),
+                Loc::at_zero(Pattern::Identifier(thunk_symbol)),
+            )),
+            specialized_def_type,
+            type_arguments,
+            lambda_set_variables,
+        }),
+    )];
+
+    let function_var = var_store.fresh();
+    let once_closure = Expr::Closure(ClosureData {
+        function_type: function_var,
+        closure_type: var_store.fresh(),
+        return_type: var_store.fresh(),
+        name: once_symbol,
+        captured_symbols: Captures::new(),
+        recursive: Recursive::NotRecursive,
+        arguments,
+        loc_body: Box::new(Loc::at_zero(body)),
+    });
+
+    let mut introduced_variables = IntroducedVariables::default();
+
+    let signature = {
+        // Effect.once : Effect a -> Effect a
+        let var_a = var_store.fresh();
+        introduced_variables.insert_named("a".into(), Loc::at_zero(var_a));
+
+        let effect_a1 = build_effect_opaque(
+            effect_symbol,
+            Type::Variable(var_a),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let effect_a2 = build_effect_opaque(
+            effect_symbol,
+            Type::Variable(var_a),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        Type::Function(
+            vec![effect_a1],
+            Box::new(Type::Variable(closure_var)),
+            Box::new(effect_a2),
+        )
+    };
+
+    let def_annotation = crate::def::Annotation {
+        signature,
+        introduced_variables,
+        aliases: VecMap::default(),
+        region: Region::zero(),
+    };
+
+    let pattern = Pattern::Identifier(once_symbol);
+    let mut pattern_vars = VecMap::default();
+    pattern_vars.insert(once_symbol, function_var);
+    let def = Def {
+        loc_pattern: Loc::at_zero(pattern),
+        loc_expr: Loc::at_zero(once_closure),
+        expr_var: function_var,
+        pattern_vars,
+        annotation: Some(def_annotation),
+    };
+
+    (once_symbol, def)
+}
+
+/// Builds `batch`, which runs each of its effects after the one before it, the same as chaining
+/// them with `after`, and gives back a list of their results in the same order.
+///
+/// When the hosted module also asks for `hostBatch`, `batch` hands all of its effects to the host
+/// at once instead. The host gets a list of the effects' thunks, which it runs with the
+/// `roc__fx_batch_0_caller` that is exposed for them, so a host with an async runtime can run
+/// them concurrently. It gives back a list of their results in the same order.
+fn build_effect_batch(
+    scope: &mut Scope,
+    effect_symbol: Symbol,
+    var_store: &mut VarStore,
+    host_batch: bool,
+) -> (Symbol, Def) {
+    // with `hostBatch`:
+    //
+    // Effect.batch = \effects -> @Effect \{} -> roc_fx_batch effects
+    //
+    // otherwise:
+    //
+    // Effect.batch = \effects ->
+    //     @Effect \{} ->
+    //         List.map effects \effect ->
+    //             @Effect thunk = effect
+    //             thunk {}
+
+    let effects_symbol = new_symbol!(scope, "effect_batch_effects");
+    let batch_symbol = new_symbol!(scope, "batch");
+    let inner_closure_symbol = new_symbol!(scope, "effect_batch_inner");
+
+    let run_all = if host_batch {
+        foreign_call(
+            "roc_fx_batch",
+            vec![Expr::Var(effects_symbol, var_store.fresh())],
+            var_store,
+        )
+    } else {
+        let element_symbol = new_symbol!(scope, "effect_batch_effect");
+        let thunk_symbol = new_symbol!(scope, "effect_batch_thunk");
+        let run_one_symbol = new_symbol!(scope, "effect_batch_run");
+
+        // \effect -> (@Effect thunk = effect; thunk {})
+        let run_one = Expr::Closure(ClosureData {
+            function_type: var_store.fresh(),
+            closure_type: var_store.fresh(),
+            return_type: var_store.fresh(),
+            name: run_one_symbol,
+            captured_symbols: Captures::new(),
+            recursive: Recursive::NotRecursive,
+            arguments: vec![(
+                var_store.fresh(),
+                AnnotatedMark::new(var_store),
+                Loc::at_zero(Pattern::Identifier(element_symbol)),
+            )],
+            loc_body: Box::new(Loc::at_zero(force_effect(
+                Expr::Var(element_symbol, var_store.fresh()),
+                effect_symbol,
+                thunk_symbol,
+                var_store,
+            ))),
+        });
+
+        // `List.map effects run_one`, which runs the effects in order
+        let map_var = var_store.fresh();
+        let boxed = (
+            map_var,
+            Loc::at_zero(Expr::Var(Symbol::LIST_MAP, map_var)),
+            var_store.fresh(),
+            var_store.fresh(),
+        );
+
+        let arguments = vec![
+            (
+                var_store.fresh(),
+                Loc::at_zero(Expr::Var(effects_symbol, var_store.fresh())),
+            ),
+            (var_store.fresh(), Loc::at_zero(run_one)),
+        ];
+        Expr::Call(Box::new(boxed), arguments, CalledVia::Space)
+    };
+
+    let body = wrap_in_effect_thunk(
+        run_all,
+        effect_symbol,
+        inner_closure_symbol,
+        vec![effects_symbol],
+        var_store,
+    );
+
+    let arguments = vec![(
+        var_store.fresh(),
+        AnnotatedMark::new(var_store),
+        Loc::at_zero(Pattern::Identifier(effects_symbol)),
+    )];
+
+    let function_var = var_store.fresh();
+    let batch_closure = Expr::Closure(ClosureData {
+        function_type: function_var,
+        closure_type: var_store.fresh(),
+        return_type: var_store.fresh(),
+        name: batch_symbol,
+        captured_symbols: Captures::new(),
+        recursive: Recursive::NotRecursive,
+        arguments,
+        loc_body: Box::new(Loc::at_zero(body)),
+    });
+
+    let mut introduced_variables = IntroducedVariables::default();
+
+    let signature = {
+        // Effect.batch : List (Effect a) -> Effect (List a)
+        let var_a = var_store.fresh();
+        introduced_variables.insert_named("a".into(), Loc::at_zero(var_a));
+
+        let effect_a = build_effect_opaque(
+            effect_symbol,
+            Type::Variable(var_a),
+            var_store,
+            &mut introduced_variables,
+        );
+
+        let list_a = Type::Apply(
+            Symbol::LIST_LIST,
+            vec![Loc::at_zero(Type::Variable(var_a))],
+            Region::zero(),
+        );
+
+        let effect_list_a =
+            build_effect_opaque(effect_symbol, list_a, var_store, &mut introduced_variables);
+
+        let list_effect_a = Type::Apply(
+            Symbol::LIST_LIST,
+            vec![Loc::at_zero(effect_a)],
+            Region::zero(),
+        );
+
+        let closure_var = var_store.fresh();
+        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
+        Type::Function(
+            vec![list_effect_a],
+            Box::new(Type::Variable(closure_var)),
+            Box::new(effect_list_a),
+        )
+    };
+
+    let def_annotation = crate::def::Annotation {
+        signature,
+        introduced_variables,
+        aliases: VecMap::default(),
+        region: Region::zero(),
+    };
+
+    let pattern = Pattern::Identifier(batch_symbol);
+    let mut pattern_vars = VecMap::default();
+    pattern_vars.insert(batch_symbol, function_var);
+    let def = Def {
+        loc_pattern: Loc::at_zero(pattern),
+        loc_expr: Loc::at_zero(batch_closure),
+        expr_var: function_var,
+        pattern_vars,
+        annotation: Some(def_annotation),
+    };
+
+    (batch_symbol, def)
+}
+
+fn foreign_call(name: &str, args: Vec<Expr>, var_store: &mut VarStore) -> Expr {
+    Expr::ForeignCall {
+        foreign_symbol: name.into(),
+        args: args
+            .into_iter()
+            .map(|arg| (var_store.fresh(), arg))
+            .collect(),
+        ret_var: var_store.fresh(),
+    }
+}
+
+/// `function argument`, for a function from another module like `Box.box`
+fn call_symbol(function: Symbol, argument: Expr, var_store: &mut VarStore) -> Expr {
+    let function_var = var_store.fresh();
+    let boxed = (
+        function_var,
+        Loc::at_zero(Expr::Var(function, function_var)),
+        var_store.fresh(),
+        var_store.fresh(),
+    );
+
+    let arguments = vec![(var_store.fresh(), Loc::at_zero(argument))];
+    Expr::Call(Box::new(boxed), arguments, CalledVia::Space)
+}
+
+/// Builds `ifThen`, which runs the effect when the condition holds, or `unless`, which runs it
+/// when the condition does not hold. The effect is returned as is, and otherwise an effect that
+/// captures nothing, so neither branch allocates a new thunk.
+fn build_effect_conditional(
+    scope: &mut Scope,
+    effect_opaque_symbol: Symbol,
+    var_store: &mut VarStore,
+    name: &str,
+) -> (Symbol, Def) {
+    // Effect.ifThen = \condition, effect -> if condition then effect else @Effect \{} -> {}
+    // Effect.unless = \condition, effect -> if condition then @Effect \{} -> {} else effect
+
+    let condition_symbol = new_symbol!(scope, format!("effect_{name}_condition"));
+    let effect_symbol = new_symbol!(scope, format!("effect_{name}_effect"));
+    let noop_symbol = new_symbol!(scope, format!("effect_{name}_noop"));
+    let conditional_symbol = new_symbol!(scope, name);
+
+    // `@Effect \{} -> {}`
+    let noop = wrap_in_effect_thunk(
+        Expr::EmptyRecord,
+        effect_opaque_symbol,
+        noop_symbol,
+        vec![],
+        var_store,
+    );
+
+    let effect = Expr::Var(effect_symbol, var_store.fresh());
 
-    let inner_closure_symbol = {
-        scope
-            .introduce("effect_map_inner".into(), Region::zero())
-            .unwrap()
+    let (then_branch, else_branch) = match name {
+        "unless" => (noop, effect),
+        _ => (effect, noop),
     };
 
-    // \{} -> mapper (thunk {})
-    let inner_closure = {
-        let arguments = vec![(
-            var_store.fresh(),
-            AnnotatedMark::new(var_store),
-            Loc::at_zero(empty_record_pattern(var_store)),
-        )];
-
-        Expr::Closure(ClosureData {
-            function_type: var_store.fresh(),
-            closure_type: var_store.fresh(),
-            return_type: var_store.fresh(),
-            name: inner_closure_symbol,
-            captured_symbols: vec![
-                (thunk_symbol, var_store.fresh()),
-                (mapper_symbol, var_store.fresh()),
-            ],
-            recursive: Recursive::NotRecursive,
-            arguments,
-            loc_body: Box::new(Loc::at_zero(mapper_call)),
-        })
+    let body = Expr::If {
+        cond_var: var_store.fresh(),
+        branch_var: var_store.fresh(),
+        branches: vec![(
+            Loc::at_zero(Expr::Var(condition_symbol, var_store.fresh())),
+            Loc::at_zero(then_branch),
+        )],
+        final_else: Box::new(Loc::at_zero(else_branch)),
     };
 
-    // \@Effect thunk, mapper
-    let (specialized_def_type, type_arguments, lambda_set_variables) =
-        build_fresh_opaque_variables(var_store);
     let arguments = vec![
         (
             var_store.fresh(),
             AnnotatedMark::new(var_store),
-            Loc::at_zero(Pattern::UnwrappedOpaque {
-                opaque: effect_symbol,
-                whole_var: var_store.fresh(),
-                argument: Box::new((
-                    var_store.fresh(),
-                    Loc::at_zero(Pattern::Identifier(thunk_symbol)),
-                )),
-                specialized_def_type,
-                type_arguments,
-                lambda_set_variables,
-            }),
+            Loc::at_zero(Pattern::Identifier(condition_symbol)),
         ),
         (
             var_store.fresh(),
             AnnotatedMark::new(var_store),
-            Loc::at_zero(Pattern::Identifier(mapper_symbol)),
+            Loc::at_zero(Pattern::Identifier(effect_symbol)),
         ),
     ];
 
-    // `@Effect \{} -> (mapper (thunk {}))`
-    let (specialized_def_type, type_arguments, lambda_set_variables) =
-        build_fresh_opaque_variables(var_store);
-    let body = Expr::OpaqueRef {
-        opaque_var: var_store.fresh(),
-        name: effect_symbol,
-        argument: Box::new((var_store.fresh(), Loc::at_zero(inner_closure))),
-        specialized_def_type,
-        type_arguments,
-        lambda_set_variables,
-    };
-
     let function_var = var_store.fresh();
-    let map_closure = Expr::Closure(ClosureData {
+    let conditional_closure = Expr::Closure(ClosureData {
         function_type: function_var,
         closure_type: var_store.fresh(),
         return_type: var_store.fresh(),
-        name: map_symbol,
-        captured_symbols: Vec::new(),
+        name: conditional_symbol,
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments,
         loc_body: Box::new(Loc::at_zero(body)),
@@ -351,45 +1691,27 @@ fn build_effect_map(
     let mut introduced_variables = IntroducedVariables::default();
 
     let signature = {
-        // Effect.map : Effect a, (a -> b) -> Effect b
-        let var_a = var_store.fresh();
-        let var_b = var_store.fresh();
-
-        introduced_variables.insert_named("a".into(), Loc::at_zero(var_a));
-        introduced_variables.insert_named("b".into(), Loc::at_zero(var_b));
-
-        let effect_a = build_effect_opaque(
-            effect_symbol,
-            var_a,
-            Type::Variable(var_a),
+        let effect_type = build_effect_opaque(
+            effect_opaque_symbol,
+            Type::EmptyRec,
             var_store,
             &mut introduced_variables,
         );
 
-        let effect_b = build_effect_opaque(
-            effect_symbol,
-            var_b,
-            Type::Variable(var_b),
+        let returned_effect_type = build_effect_opaque(
+            effect_opaque_symbol,
+            Type::EmptyRec,
             var_store,
             &mut introduced_variables,
         );
 
         let closure_var = var_store.fresh();
         introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
-        let a_to_b = {
-            Type::Function(
-                vec![Type::Variable(var_a)],
-                Box::new(Type::Variable(closure_var)),
-                Box::new(Type::Variable(var_b)),
-            )
-        };
 
-        let closure_var = var_store.fresh();
-        introduced_variables.insert_wildcard(Loc::at_zero(closure_var));
         Type::Function(
-            vec![effect_a, a_to_b],
+            vec![Type::Variable(Variable::BOOL), effect_type],
             Box::new(Type::Variable(closure_var)),
-            Box::new(effect_b),
+            Box::new(returned_effect_type),
         )
     };
 
@@ -400,48 +1722,41 @@ fn build_effect_map(
         region: Region::zero(),
     };
 
-    let pattern = Pattern::Identifier(map_symbol);
-    let mut pattern_vars = SendMap::default();
-    pattern_vars.insert(map_symbol, function_var);
+    let pattern = Pattern::Identifier(conditional_symbol);
+    let mut pattern_vars = VecMap::default();
+    pattern_vars.insert(conditional_symbol, function_var);
     let def = Def {
         loc_pattern: Loc::at_zero(pattern),
-        loc_expr: Loc::at_zero(map_closure),
+        loc_expr: Loc::at_zero(conditional_closure),
         expr_var: function_var,
         pattern_vars,
         annotation: Some(def_annotation),
     };
 
-    (map_symbol, def)
-}
-
-fn force_thunk(expr: Expr, thunk_var: Variable, var_store: &mut VarStore) -> Expr {
-    let boxed = (
-        thunk_var,
-        Loc::at_zero(expr),
-        var_store.fresh(),
-        var_store.fresh(),
-    );
-
-    let arguments = vec![(var_store.fresh(), Loc::at_zero(Expr::EmptyRecord))];
-    Expr::Call(Box::new(boxed), arguments, CalledVia::Space)
+    (conditional_symbol, def)
 }
 
-fn build_effect_after(
+/// Builds `after`, or a function that is implemented like it but whose first effect produces an
+/// `a_type`, whose variables are already in `introduced_variables`.
+fn build_effect_chain(
     scope: &mut Scope,
     effect_opaque_symbol: Symbol,
     var_store: &mut VarStore,
+    name: &str,
+    a_type: Type,
+    mut introduced_variables: IntroducedVariables,
 ) -> (Symbol, Def) {
     //    Effect.after = \@Effect effect, toEffect ->
     //        @Effect \{} ->
     //            when toEffect (effect {}) is
     //            @Effect thunk -> thunk {}
 
-    let thunk_symbol = new_symbol!(scope, "effect_after_thunk");
+    let thunk_symbol = new_symbol!(scope, format!("effect_{name}_thunk"));
 
-    let effect_symbol = new_symbol!(scope, "effect_after_effect");
-    let to_effect_symbol = new_symbol!(scope, "effect_after_toEffect");
-    let after_symbol = new_symbol!(scope, "after");
-    let outer_closure_symbol = new_symbol!(scope, "effect_after_inner");
+    let effect_symbol = new_symbol!(scope, format!("effect_{name}_effect"));
+    let to_effect_symbol = new_symbol!(scope, format!("effect_{name}_toEffect"));
+    let after_symbol = new_symbol!(scope, name);
+    let outer_closure_symbol = new_symbol!(scope, format!("effect_{name}_inner"));
 
     // `effect {}`
     let force_effect_var = var_store.fresh();
@@ -555,32 +1870,26 @@ fn build_effect_after(
         closure_type: var_store.fresh(),
         return_type: var_store.fresh(),
         name: after_symbol,
-        captured_symbols: Vec::new(),
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments,
         loc_body: Box::new(Loc::at_zero(outer_effect)),
     });
 
-    let mut introduced_variables = IntroducedVariables::default();
-
     let signature = {
-        let var_a = var_store.fresh();
         let var_b = var_store.fresh();
 
-        introduced_variables.insert_named("a".into(), Loc::at_zero(var_a));
         introduced_variables.insert_named("b".into(), Loc::at_zero(var_b));
 
         let effect_a = build_effect_opaque(
             effect_opaque_symbol,
-            var_a,
-            Type::Variable(var_a),
+            a_type.clone(),
             var_store,
             &mut introduced_variables,
         );
 
         let effect_b1 = build_effect_opaque(
             effect_opaque_symbol,
-            var_b,
             Type::Variable(var_b),
             var_store,
             &mut introduced_variables,
@@ -589,7 +1898,6 @@ fn build_effect_after(
         // we need a second b2 to give it a unique lambda set variable
         let effect_b2 = build_effect_opaque(
             effect_opaque_symbol,
-            var_b,
             Type::Variable(var_b),
             var_store,
             &mut introduced_variables,
@@ -598,7 +1906,7 @@ fn build_effect_after(
         let closure_var = var_store.fresh();
         introduced_variables.insert_lambda_set(closure_var);
         let a_to_effect_b = Type::Function(
-            vec![Type::Variable(var_a)],
+            vec![a_type],
             Box::new(Type::Variable(closure_var)),
             Box::new(effect_b1),
         );
@@ -620,7 +1928,7 @@ fn build_effect_after(
     };
 
     let pattern = Pattern::Identifier(after_symbol);
-    let mut pattern_vars = SendMap::default();
+    let mut pattern_vars = VecMap::default();
     pattern_vars.insert(after_symbol, function_var);
     let def = Def {
         loc_pattern: Loc::at_zero(pattern),
@@ -641,7 +1949,7 @@ fn wrap_in_effect_thunk(
     captured_symbols: Vec<Symbol>,
     var_store: &mut VarStore,
 ) -> Expr {
-    let captured_symbols: Vec<_> = captured_symbols
+    let captured_symbols: Captures = captured_symbols
         .into_iter()
         .map(|x| (x, var_store.fresh()))
         .collect();
@@ -659,7 +1967,7 @@ fn wrap_in_effect_thunk(
             closure_type: var_store.fresh(),
             return_type: var_store.fresh(),
             name: closure_name,
-            // captured_symbols: vec![(value_symbol, var_store.fresh())],
+            // captured_symbols: Captures::from([(value_symbol, var_store.fresh())]),
             captured_symbols,
             recursive: Recursive::NotRecursive,
             arguments,
@@ -702,7 +2010,7 @@ fn force_effect(
         lambda_set_variables,
     };
 
-    let pattern_vars = SendMap::default();
+    let pattern_vars = VecMap::default();
     // pattern_vars.insert(thunk_symbol, thunk_var);
 
     let def = Def {
@@ -786,9 +2094,9 @@ fn build_effect_forever(
     //
     // Making `foreverInner` perfectly tail-call optimizable
 
-    let forever_symbol = { scope.introduce("forever".into(), Region::zero()).unwrap() };
+    let forever_symbol = new_symbol!(scope, "forever");
 
-    let effect = { scope.introduce("effect".into(), Region::zero()).unwrap() };
+    let effect = new_symbol!(scope, "effect");
 
     let body = build_effect_forever_body(scope, effect_symbol, forever_symbol, effect, var_store);
 
@@ -804,7 +2112,7 @@ fn build_effect_forever(
         closure_type: var_store.fresh(),
         return_type: var_store.fresh(),
         name: forever_symbol,
-        captured_symbols: Vec::new(),
+        captured_symbols: Captures::new(),
         recursive: Recursive::Recursive,
         arguments,
         loc_body: Box::new(Loc::at_zero(body)),
@@ -821,7 +2129,6 @@ fn build_effect_forever(
 
         let effect_a = build_effect_opaque(
             effect_symbol,
-            var_a,
             Type::Variable(var_a),
             var_store,
             &mut introduced_variables,
@@ -829,7 +2136,6 @@ fn build_effect_forever(
 
         let effect_b = build_effect_opaque(
             effect_symbol,
-            var_b,
             Type::Variable(var_b),
             var_store,
             &mut introduced_variables,
@@ -853,7 +2159,7 @@ fn build_effect_forever(
     };
 
     let pattern = Pattern::Identifier(forever_symbol);
-    let mut pattern_vars = SendMap::default();
+    let mut pattern_vars = VecMap::default();
     pattern_vars.insert(forever_symbol, function_var);
     let def = Def {
         loc_pattern: Loc::at_zero(pattern),
@@ -873,11 +2179,7 @@ fn build_effect_forever_body(
     effect: Symbol,
     var_store: &mut VarStore,
 ) -> Expr {
-    let closure_name = {
-        scope
-            .introduce("forever_inner".into(), Region::zero())
-            .unwrap()
-    };
+    let closure_name = new_symbol!(scope, "forever_inner");
 
     let inner_body =
         build_effect_forever_inner_body(scope, effect_symbol, forever_symbol, effect, var_store);
@@ -900,9 +2202,9 @@ fn build_effect_forever_inner_body(
     var_store: &mut VarStore,
 ) -> Expr {
     let thunk1_var = var_store.fresh();
-    let thunk1_symbol = { scope.introduce("thunk1".into(), Region::zero()).unwrap() };
+    let thunk1_symbol = new_symbol!(scope, "thunk1");
 
-    let thunk2_symbol = { scope.introduce("thunk2".into(), Region::zero()).unwrap() };
+    let thunk2_symbol = new_symbol!(scope, "thunk2");
 
     // @Effect thunk1 = effect
     let thunk_from_effect = {
@@ -921,7 +2223,7 @@ fn build_effect_forever_inner_body(
             lambda_set_variables,
         };
 
-        let pattern_vars = SendMap::default();
+        let pattern_vars = VecMap::default();
 
         Def {
             loc_pattern: Loc::at_zero(pattern),
@@ -1028,7 +2330,7 @@ fn build_effect_loop(
         closure_type: var_store.fresh(),
         return_type: var_store.fresh(),
         name: loop_symbol,
-        captured_symbols: Vec::new(),
+        captured_symbols: Captures::new(),
         recursive: Recursive::Recursive,
         arguments,
         loc_body: Box::new(Loc::at_zero(body)),
@@ -1045,7 +2347,6 @@ fn build_effect_loop(
 
         let effect_b = build_effect_opaque(
             effect_symbol,
-            var_b,
             Type::Variable(var_b),
             var_store,
             &mut introduced_variables,
@@ -1113,7 +2414,7 @@ fn build_effect_loop(
     };
 
     let pattern = Pattern::Identifier(loop_symbol);
-    let mut pattern_vars = SendMap::default();
+    let mut pattern_vars = VecMap::default();
     pattern_vars.insert(loop_symbol, function_var);
     let def = Def {
         loc_pattern: Loc::at_zero(pattern),
@@ -1134,11 +2435,7 @@ fn build_effect_loop_body(
     step_symbol: Symbol,
     var_store: &mut VarStore,
 ) -> Expr {
-    let closure_name = {
-        scope
-            .introduce("loop_inner".into(), Region::zero())
-            .unwrap()
-    };
+    let closure_name = new_symbol!(scope, "loop_inner");
 
     let inner_body = build_effect_loop_inner_body(
         scope,
@@ -1212,7 +2509,7 @@ fn build_effect_loop_inner_body(
             lambda_set_variables,
         };
 
-        let pattern_vars = SendMap::default();
+        let pattern_vars = VecMap::default();
 
         // `step state`
         let rhs = {
@@ -1334,23 +2631,24 @@ fn build_effect_loop_inner_body(
     )
 }
 
+/// A hosted signature without a body gets a def calling the host's `roc_fx_<ident>`. The
+/// signature may also be a record of effects, like `api : { init : Effect Model }`. Then each
+/// field gets a def of its own, calling a function like `roc_fx_api_init`, and these defs are
+/// returned alongside.
+///
+/// Every call is wrapped in `effect_symbol`, unless it returns one of `more_effect_symbols`.
+/// Calls wrapped in an `asynchronous` `effect_symbol` pass a continuation to the host.
+#[allow(clippy::too_many_arguments)]
 pub fn build_host_exposed_def(
     scope: &mut Scope,
     symbol: Symbol,
     ident: &str,
     effect_symbol: Symbol,
+    asynchronous: bool,
+    more_effect_symbols: &[Symbol],
     var_store: &mut VarStore,
     annotation: crate::annotation::Annotation,
-) -> Def {
-    let expr_var = var_store.fresh();
-    let pattern = Pattern::Identifier(symbol);
-    let mut pattern_vars = SendMap::default();
-    pattern_vars.insert(symbol, expr_var);
-
-    let mut arguments: Vec<(Variable, AnnotatedMark, Loc<Pattern>)> = Vec::new();
-    let mut linked_symbol_arguments: Vec<(Variable, Expr)> = Vec::new();
-    let mut captured_symbols: Vec<(Symbol, Variable)> = Vec::new();
-
+) -> (Def, Vec<Def>) {
     let crate::annotation::Annotation {
         introduced_variables,
         typ,
@@ -1358,130 +2656,70 @@ pub fn build_host_exposed_def(
         ..
     } = annotation;
 
-    let def_body = {
-        match typ.shallow_structural_dealias() {
-            Type::Function(args, _, _) => {
-                for i in 0..args.len() {
-                    let name = format!("closure_arg_{ident}_{i}");
+    let effect_of = |typ: &Type| {
+        returned_effect(typ)
+            .filter(|symbol| more_effect_symbols.contains(symbol))
+            .unwrap_or(effect_symbol)
+    };
 
-                    let arg_symbol = {
-                        let ident = name.clone().into();
-                        scope.introduce(ident, Region::zero()).unwrap()
+    let mut field_defs = Vec::new();
+
+    let def_body = match typ.shallow_structural_dealias() {
+        Type::Record(fields, _) => {
+            let fields = fields
+                .iter()
+                .map(|(label, field)| {
+                    let field_ident = format!("{ident}_{label}");
+                    let field_symbol = new_symbol!(scope, format!("effect_field_{field_ident}"));
+                    let field_type = field.as_inner();
+
+                    let field_effect = effect_of(field_type);
+                    let body = build_host_exposed_body(
+                        scope,
+                        field_symbol,
+                        &field_ident,
+                        field_effect,
+                        asynchronous && field_effect == effect_symbol,
+                        var_store,
+                        field_type,
+                    );
+
+                    let field_annotation = crate::def::Annotation {
+                        signature: field_type.clone(),
+                        introduced_variables: introduced_variables.clone(),
+                        aliases: aliases.clone(),
+                        region: Region::zero(),
                     };
 
-                    let arg_var = var_store.fresh();
-
-                    arguments.push((
-                        arg_var,
-                        AnnotatedMark::new(var_store),
-                        Loc::at_zero(Pattern::Identifier(arg_symbol)),
+                    field_defs.push(host_exposed_def(
+                        field_symbol,
+                        body,
+                        field_annotation,
+                        var_store,
                     ));
 
-                    captured_symbols.push((arg_symbol, arg_var));
-                    linked_symbol_arguments.push((arg_var, Expr::Var(arg_symbol, arg_var)));
-                }
-
-                let foreign_symbol_name = format!("roc_fx_{ident}");
-                let low_level_call = Expr::ForeignCall {
-                    foreign_symbol: foreign_symbol_name.into(),
-                    args: linked_symbol_arguments,
-                    ret_var: var_store.fresh(),
-                };
-
-                let effect_closure_symbol = {
-                    let name = format!("effect_closure_{ident}");
-
-                    let ident = name.into();
-                    scope.introduce(ident, Region::zero()).unwrap()
-                };
-
-                let effect_closure = Expr::Closure(ClosureData {
-                    function_type: var_store.fresh(),
-                    closure_type: var_store.fresh(),
-                    return_type: var_store.fresh(),
-                    name: effect_closure_symbol,
-                    captured_symbols,
-                    recursive: Recursive::NotRecursive,
-                    arguments: vec![(
-                        var_store.fresh(),
-                        AnnotatedMark::new(var_store),
-                        Loc::at_zero(empty_record_pattern(var_store)),
-                    )],
-                    loc_body: Box::new(Loc::at_zero(low_level_call)),
-                });
-
-                let (specialized_def_type, type_arguments, lambda_set_variables) =
-                    build_fresh_opaque_variables(var_store);
-                let body = Expr::OpaqueRef {
-                    opaque_var: var_store.fresh(),
-                    name: effect_symbol,
-                    argument: Box::new((var_store.fresh(), Loc::at_zero(effect_closure))),
-                    specialized_def_type,
-                    type_arguments,
-                    lambda_set_variables,
-                };
-
-                Expr::Closure(ClosureData {
-                    function_type: var_store.fresh(),
-                    closure_type: var_store.fresh(),
-                    return_type: var_store.fresh(),
-                    name: symbol,
-                    captured_symbols: std::vec::Vec::new(),
-                    recursive: Recursive::NotRecursive,
-                    arguments,
-                    loc_body: Box::new(Loc::at_zero(body)),
-                })
-            }
-            _ => {
-                // not a function
-
-                let foreign_symbol_name = format!("roc_fx_{ident}");
-                let low_level_call = Expr::ForeignCall {
-                    foreign_symbol: foreign_symbol_name.into(),
-                    args: linked_symbol_arguments,
-                    ret_var: var_store.fresh(),
-                };
-
-                let effect_closure_symbol = {
-                    let name = format!("effect_closure_{ident}");
+                    let var = var_store.fresh();
+                    let field = Field {
+                        var,
+                        region: Region::zero(),
+                        loc_expr: Box::new(Loc::at_zero(Expr::Var(field_symbol, var))),
+                    };
 
-                    let ident = name.into();
-                    scope.introduce(ident, Region::zero()).unwrap()
-                };
+                    (label.clone(), field)
+                })
+                .collect();
 
-                let empty_record_pattern = Pattern::RecordDestructure {
-                    whole_var: var_store.fresh(),
-                    ext_var: var_store.fresh(),
-                    destructs: vec![],
-                };
-
-                let effect_closure = Expr::Closure(ClosureData {
-                    function_type: var_store.fresh(),
-                    closure_type: var_store.fresh(),
-                    return_type: var_store.fresh(),
-                    name: effect_closure_symbol,
-                    captured_symbols,
-                    recursive: Recursive::NotRecursive,
-                    arguments: vec![(
-                        var_store.fresh(),
-                        AnnotatedMark::new(var_store),
-                        Loc::at_zero(empty_record_pattern),
-                    )],
-                    loc_body: Box::new(Loc::at_zero(low_level_call)),
-                });
-
-                let (specialized_def_type, type_arguments, lambda_set_variables) =
-                    build_fresh_opaque_variables(var_store);
-                Expr::OpaqueRef {
-                    opaque_var: var_store.fresh(),
-                    name: effect_symbol,
-                    argument: Box::new((var_store.fresh(), Loc::at_zero(effect_closure))),
-                    specialized_def_type,
-                    type_arguments,
-                    lambda_set_variables,
-                }
+            Expr::Record {
+                record_var: var_store.fresh(),
+                fields,
             }
         }
+        _ => {
+            let effect = effect_of(&typ);
+            let asynchronous = asynchronous && effect == effect_symbol;
+
+            build_host_exposed_body(scope, symbol, ident, effect, asynchronous, var_store, &typ)
+        }
     };
 
     let def_annotation = crate::def::Annotation {
@@ -1491,15 +2729,278 @@ pub fn build_host_exposed_def(
         region: Region::zero(),
     };
 
+    let def = host_exposed_def(symbol, def_body, def_annotation, var_store);
+
+    (def, field_defs)
+}
+
+fn host_exposed_def(
+    symbol: Symbol,
+    body: Expr,
+    annotation: crate::def::Annotation,
+    var_store: &mut VarStore,
+) -> Def {
+    let expr_var = var_store.fresh();
+    let mut pattern_vars = VecMap::default();
+    pattern_vars.insert(symbol, expr_var);
+
     Def {
-        loc_pattern: Loc::at_zero(pattern),
+        loc_pattern: Loc::at_zero(Pattern::Identifier(symbol)),
+        loc_expr: Loc::at_zero(body),
+        expr_var,
+        pattern_vars,
+        annotation: Some(annotation),
+    }
+}
+
+/// Calls `roc_fx_<ident>` in an effect, and takes the arguments of the call first if `typ` is a
+/// function type. The closure of an asynchronous effect takes a continuation, which is passed
+/// on to the host as the last argument, as in `@Effect \continuation -> roc_fx_ident continuation`.
+fn build_host_exposed_body(
+    scope: &mut Scope,
+    symbol: Symbol,
+    ident: &str,
+    effect_symbol: Symbol,
+    asynchronous: bool,
+    var_store: &mut VarStore,
+    typ: &Type,
+) -> Expr {
+    let mut arguments: Vec<(Variable, AnnotatedMark, Loc<Pattern>)> = Vec::new();
+    let mut linked_symbol_arguments: Vec<(Variable, Expr)> = Vec::new();
+    let mut captured_symbols = Captures::new();
+
+    let arity = match typ.shallow_structural_dealias() {
+        Type::Function(args, _, _) => Some(args.len()),
+        _ => None,
+    };
+
+    for i in 0..arity.unwrap_or(0) {
+        let name = format!("closure_arg_{ident}_{i}");
+
+        let arg_symbol = new_symbol!(scope, name);
+
+        let arg_var = var_store.fresh();
+
+        arguments.push((
+            arg_var,
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(Pattern::Identifier(arg_symbol)),
+        ));
+
+        captured_symbols.push((arg_symbol, arg_var));
+        linked_symbol_arguments.push((arg_var, Expr::Var(arg_symbol, arg_var)));
+    }
+
+    // `{}`, or the continuation of an asynchronous effect
+    let effect_closure_argument = if asynchronous {
+        let continuation_symbol = new_symbol!(scope, format!("continuation_{ident}"));
+        let continuation_var = var_store.fresh();
+
+        linked_symbol_arguments.push((
+            continuation_var,
+            Expr::Var(continuation_symbol, continuation_var),
+        ));
+
+        Pattern::Identifier(continuation_symbol)
+    } else {
+        empty_record_pattern(var_store)
+    };
+
+    let foreign_symbol_name = format!("roc_fx_{ident}");
+    let low_level_call = Expr::ForeignCall {
+        foreign_symbol: foreign_symbol_name.into(),
+        args: linked_symbol_arguments,
+        ret_var: var_store.fresh(),
+    };
+
+    let effect_closure_symbol = new_symbol!(scope, format!("effect_closure_{ident}"));
+
+    let effect_closure = Expr::Closure(ClosureData {
+        function_type: var_store.fresh(),
+        closure_type: var_store.fresh(),
+        return_type: var_store.fresh(),
+        name: effect_closure_symbol,
+        captured_symbols,
+        recursive: Recursive::NotRecursive,
+        arguments: vec![(
+            var_store.fresh(),
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(effect_closure_argument),
+        )],
+        loc_body: Box::new(Loc::at_zero(low_level_call)),
+    });
+
+    let (specialized_def_type, type_arguments, lambda_set_variables) = if asynchronous {
+        // the continuation has a lambda set of its own
+        let opaque = scope.lookup_alias(effect_symbol).unwrap();
+        let (type_arguments, lambda_set_variables, specialized_def_type) =
+            crate::annotation::freshen_opaque_def(var_store, opaque);
+
+        (
+            Box::new(specialized_def_type),
+            type_arguments,
+            lambda_set_variables,
+        )
+    } else {
+        build_fresh_opaque_variables(var_store)
+    };
+
+    let body = Expr::OpaqueRef {
+        opaque_var: var_store.fresh(),
+        name: effect_symbol,
+        argument: Box::new((var_store.fresh(), Loc::at_zero(effect_closure))),
+        specialized_def_type,
+        type_arguments,
+        lambda_set_variables,
+    };
+
+    match arity {
+        Some(_) => Expr::Closure(ClosureData {
+            function_type: var_store.fresh(),
+            closure_type: var_store.fresh(),
+            return_type: var_store.fresh(),
+            name: symbol,
+            captured_symbols: Captures::new(),
+            recursive: Recursive::NotRecursive,
+            arguments,
+            loc_body: Box::new(Loc::at_zero(body)),
+        }),
+        None => body,
+    }
+}
+
+/// The alias a hosted function's annotation returns, e.g. `Stream` for `Str -> Stream {}`
+pub fn returned_effect(typ: &Type) -> Option<Symbol> {
+    let returned = match typ.shallow_structural_dealias() {
+        Type::Function(_, _, ret) => &**ret,
+        _ => typ,
+    };
+
+    match returned {
+        Type::Alias { symbol, .. } | Type::DelayedAlias(AliasCommon { symbol, .. }) => {
+            Some(*symbol)
+        }
+        _ => None,
+    }
+}
+
+/// The types that can be passed to and returned from a C function as they are
+const C_ABI_TYPES: &[Symbol] = &[
+    Symbol::NUM_I8,
+    Symbol::NUM_I16,
+    Symbol::NUM_I32,
+    Symbol::NUM_I64,
+    Symbol::NUM_U8,
+    Symbol::NUM_U16,
+    Symbol::NUM_U32,
+    Symbol::NUM_U64,
+    Symbol::NUM_F32,
+    Symbol::NUM_F64,
+    Symbol::BOOL_BOOL,
+];
+
+/// Whether a foreign function's signature only uses types that C has too: integers up to 64 bits,
+/// floats and `Bool`, plus `{}` as the return type of a function that returns nothing.
+pub fn is_c_abi_function(typ: &Type) -> bool {
+    fn is_c_abi_type(typ: &Type) -> bool {
+        match typ {
+            Type::Alias { symbol, .. }
+            | Type::DelayedAlias(AliasCommon { symbol, .. })
+            | Type::Apply(symbol, _, _) => C_ABI_TYPES.contains(symbol),
+            _ => false,
+        }
+    }
+
+    match typ.shallow_structural_dealias() {
+        Type::Function(args, _, ret) => {
+            args.iter().all(is_c_abi_type)
+                && (is_c_abi_type(ret) || matches!(**ret, Type::EmptyRec))
+        }
+        _ => false,
+    }
+}
+
+/// Builds the def of a function that a platform module binds to the C function `c_name`: calling
+/// it calls the C function right away, without wrapping the call in an `Effect`.
+pub fn build_foreign_def(
+    scope: &mut Scope,
+    symbol: Symbol,
+    ident: &str,
+    c_name: &str,
+    var_store: &mut VarStore,
+    annotation: crate::def::Annotation,
+) -> Def {
+    let arity = match annotation.signature.shallow_structural_dealias() {
+        Type::Function(args, _, _) => args.len(),
+        _ => internal_error!("A foreign function for {symbol:?} doesn't have a function type"),
+    };
+
+    let mut arguments = Vec::with_capacity(arity);
+    let mut foreign_arguments = Vec::with_capacity(arity);
+
+    for i in 0..arity {
+        let arg_symbol = new_symbol!(scope, format!("foreign_arg_{ident}_{i}"));
+        let arg_var = var_store.fresh();
+
+        arguments.push((
+            arg_var,
+            AnnotatedMark::new(var_store),
+            Loc::at_zero(Pattern::Identifier(arg_symbol)),
+        ));
+        foreign_arguments.push((arg_var, Expr::Var(arg_symbol, arg_var)));
+    }
+
+    let foreign_call = Expr::ForeignCall {
+        foreign_symbol: c_name.into(),
+        args: foreign_arguments,
+        ret_var: var_store.fresh(),
+    };
+
+    let def_body = Expr::Closure(ClosureData {
+        function_type: var_store.fresh(),
+        closure_type: var_store.fresh(),
+        return_type: var_store.fresh(),
+        name: symbol,
+        captured_symbols: Captures::new(),
+        recursive: Recursive::NotRecursive,
+        arguments,
+        loc_body: Box::new(Loc::at_zero(foreign_call)),
+    });
+
+    let expr_var = var_store.fresh();
+    let mut pattern_vars = VecMap::default();
+    pattern_vars.insert(symbol, expr_var);
+
+    Def {
+        loc_pattern: Loc::at_zero(Pattern::Identifier(symbol)),
         loc_expr: Loc::at_zero(def_body),
         expr_var,
         pattern_vars,
-        annotation: Some(def_annotation),
+        annotation: Some(annotation),
     }
 }
 
+/// Effect a := (a -> {}) -> {}
+///
+/// An asynchronous effect is run by passing it what to do with the result, which lets the host
+/// hold on to the continuation and resume the program later, e.g. from an event loop.
+pub fn build_async_effect_actual(a_type: Type, var_store: &mut VarStore) -> Type {
+    let continuation_closure_var = var_store.fresh();
+    let closure_var = var_store.fresh();
+
+    let continuation = Type::Function(
+        vec![a_type],
+        Box::new(Type::Variable(continuation_closure_var)),
+        Box::new(Type::EmptyRec),
+    );
+
+    Type::Function(
+        vec![continuation],
+        Box::new(Type::Variable(closure_var)),
+        Box::new(Type::EmptyRec),
+    )
+}
+
 pub fn build_effect_actual(a_type: Type, var_store: &mut VarStore) -> Type {
     let closure_var = var_store.fresh();
 
@@ -1513,7 +3014,6 @@ pub fn build_effect_actual(a_type: Type, var_store: &mut VarStore) -> Type {
 /// Effect a := {} -> a
 fn build_effect_opaque(
     effect_symbol: Symbol,
-    a_var: Variable,
     a_type: Type,
     var_store: &mut VarStore,
     introduced_variables: &mut IntroducedVariables,
@@ -1525,12 +3025,12 @@ fn build_effect_opaque(
     let actual = Type::Function(
         vec![Type::EmptyRec],
         Box::new(Type::Variable(closure_var)),
-        Box::new(a_type),
+        Box::new(a_type.clone()),
     );
 
     Type::Alias {
         symbol: effect_symbol,
-        type_arguments: vec![OptAbleType::unbound(Type::Variable(a_var))],
+        type_arguments: vec![OptAbleType::unbound(a_type)],
         lambda_set_variables: vec![roc_types::types::LambdaSet(Type::Variable(closure_var))],
         infer_ext_in_output_types: vec![],
         actual: Box::new(actual),
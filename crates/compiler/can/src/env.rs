@@ -1,9 +1,9 @@
 use crate::procedure::References;
 use crate::scope::Scope;
 use bumpalo::Bump;
-use roc_collections::{MutMap, VecSet};
+use roc_collections::MutMap;
 use roc_module::ident::{Ident, Lowercase, ModuleName};
-use roc_module::symbol::{IdentIdsByModule, ModuleId, ModuleIds, Symbol};
+use roc_module::symbol::{IdentIdsByModule, ModuleId, ModuleIds, Symbol, SymbolSet};
 use roc_problem::can::{Problem, RuntimeError};
 use roc_region::all::{Loc, Region};
 
@@ -27,12 +27,16 @@ pub struct Env<'a> {
     pub tailcallable_symbol: Option<Symbol>,
 
     /// Symbols of values/functions which were referenced by qualified lookups.
-    pub qualified_value_lookups: VecSet<Symbol>,
+    pub qualified_value_lookups: SymbolSet,
 
     /// Symbols of types which were referenced by qualified lookups.
-    pub qualified_type_lookups: VecSet<Symbol>,
+    pub qualified_type_lookups: SymbolSet,
 
-    pub top_level_symbols: VecSet<Symbol>,
+    pub top_level_symbols: SymbolSet,
+
+    /// Whether a variable may shadow another one, with a warning rather than an error.
+    /// Only the REPL permits this, so that a name can be reused for a new value.
+    pub permit_shadowing: bool,
 
     pub arena: &'a Bump,
 }
@@ -51,10 +55,11 @@ impl<'a> Env<'a> {
             module_ids,
             problems: Vec::new(),
             closures: MutMap::default(),
-            qualified_value_lookups: VecSet::default(),
-            qualified_type_lookups: VecSet::default(),
+            qualified_value_lookups: SymbolSet::default(),
+            qualified_type_lookups: SymbolSet::default(),
             tailcallable_symbol: None,
-            top_level_symbols: VecSet::default(),
+            top_level_symbols: SymbolSet::default(),
+            permit_shadowing: false,
         }
     }
 
@@ -76,6 +81,7 @@ impl<'a> Env<'a> {
             Some(module_id) => self.qualified_lookup_help(scope, module_id, ident, region),
             None => Err(RuntimeError::ModuleNotImported {
                 module_name,
+                ident: Ident::from(ident),
                 imported_modules: self
                     .module_ids
                     .available_modules()
@@ -177,6 +183,7 @@ impl<'a> Env<'a> {
                         .get_name(module_id)
                         .expect("Module ID known, but not in the module IDs somehow")
                         .clone(),
+                    ident: Ident::from(ident),
                     imported_modules: self
                         .dep_idents
                         .keys()
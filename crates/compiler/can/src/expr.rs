@@ -12,12 +12,12 @@ use crate::procedure::References;
 use crate::scope::Scope;
 use crate::traverse::{walk_expr, Visitor};
 use roc_collections::soa::Index;
-use roc_collections::{SendMap, VecMap, VecSet};
+use roc_collections::{SendMap, SmallVec, VecMap, VecSet};
 use roc_error_macros::internal_error;
 use roc_module::called_via::CalledVia;
 use roc_module::ident::{ForeignSymbol, Lowercase, TagName};
 use roc_module::low_level::LowLevel;
-use roc_module::symbol::Symbol;
+use roc_module::symbol::{Symbol, SymbolSet};
 use roc_parse::ast::{self, Defs, PrecedenceConflict, StrLiteral};
 use roc_parse::ident::Accessor;
 use roc_parse::pattern::PatternType::*;
@@ -42,7 +42,7 @@ pub struct Output {
     pub tail_call: Option<Symbol>,
     pub introduced_variables: IntroducedVariables,
     pub aliases: VecMap<Symbol, Alias>,
-    pub non_closures: VecSet<Symbol>,
+    pub non_closures: SymbolSet,
     pub pending_derives: PendingDerives,
 }
 
@@ -57,7 +57,7 @@ impl Output {
         self.introduced_variables
             .union_owned(other.introduced_variables);
         self.aliases.extend(other.aliases);
-        self.non_closures.extend(other.non_closures);
+        self.non_closures.union_mut(&other.non_closures);
 
         {
             let expected_derives_size = self.pending_derives.len() + other.pending_derives.len();
@@ -378,13 +378,17 @@ impl AnnotatedMark {
     }
 }
 
+/// The symbols a closure captures, with their variables. Most closures capture only a few
+/// symbols, so those are stored inline.
+pub type Captures = SmallVec<(Symbol, Variable), 4>;
+
 #[derive(Clone, Debug)]
 pub struct ClosureData {
     pub function_type: Variable,
     pub closure_type: Variable,
     pub return_type: Variable,
     pub name: Symbol,
-    pub captured_symbols: Vec<(Symbol, Variable)>,
+    pub captured_symbols: Captures,
     pub recursive: Recursive,
     pub arguments: Vec<(Variable, AnnotatedMark, Loc<Pattern>)>,
     pub loc_body: Box<Loc<Expr>>,
@@ -460,7 +464,7 @@ impl StructAccessorData {
             closure_type: closure_var,
             return_type: field_var,
             name,
-            captured_symbols: vec![],
+            captured_symbols: Captures::new(),
             recursive: Recursive::NotRecursive,
             arguments,
             loc_body: Box::new(loc_body),
@@ -533,7 +537,7 @@ impl OpaqueWrapFunctionData {
             closure_type: closure_var,
             return_type: opaque_var,
             name: function_name,
-            captured_symbols: vec![],
+            captured_symbols: Captures::new(),
             recursive: Recursive::NotRecursive,
             arguments,
             loc_body: Box::new(loc_body),
@@ -1533,7 +1537,7 @@ fn canonicalize_closure_body<'a>(
         &loc_body_expr.value,
     );
 
-    let mut captured_symbols: Vec<_> = new_output
+    let mut captured_symbols: Captures = new_output
         .references
         .value_lookups()
         .copied()
@@ -2343,7 +2347,7 @@ pub fn inline_calls(var_store: &mut VarStore, expr: Expr) -> Expr {
                             {
                                 // TODO get the correct vars into here.
                                 // Not sure if param_var should be involved.
-                                let pattern_vars = SendMap::default();
+                                let pattern_vars = VecMap::default();
 
                                 let def = Def {
                                     loc_pattern,
@@ -3107,7 +3111,7 @@ impl DeclarationTag {
 pub struct FunctionDef {
     pub closure_type: Variable,
     pub return_type: Variable,
-    pub captured_symbols: Vec<(Symbol, Variable)>,
+    pub captured_symbols: Captures,
     pub arguments: Vec<(Variable, AnnotatedMark, Loc<Pattern>)>,
 }
 
@@ -4,16 +4,16 @@ use crate::def::{canonicalize_defs, Def};
 use crate::effect_module::HostedGeneratedFunctions;
 use crate::env::Env;
 use crate::expr::{
-    ClosureData, DbgLookup, Declarations, ExpectLookup, Expr, Output, PendingDerives,
+    Captures, ClosureData, DbgLookup, Declarations, ExpectLookup, Expr, Output, PendingDerives,
 };
 use crate::pattern::{BindingsFromPattern, Pattern};
 use crate::scope::Scope;
 use bumpalo::Bump;
-use roc_collections::{MutMap, SendMap, VecMap, VecSet};
+use roc_collections::{MutMap, VecMap, VecSet};
 use roc_error_macros::internal_error;
 use roc_module::ident::Ident;
 use roc_module::ident::Lowercase;
-use roc_module::symbol::{IdentIds, IdentIdsByModule, ModuleId, ModuleIds, Symbol};
+use roc_module::symbol::{IdentIds, IdentIdsByModule, ModuleId, ModuleIds, Symbol, SymbolRemap};
 use roc_parse::ast::{Defs, TypeAnnotation};
 use roc_parse::header::HeaderType;
 use roc_parse::pattern::PatternType;
@@ -167,20 +167,35 @@ fn validate_generate_with<'a>(
     let mut unknown = Vec::new();
 
     for generated in generate_with {
-        match generated.value.as_str() {
-            "after" => functions.after = true,
-            "map" => functions.map = true,
-            "always" => functions.always = true,
-            "loop" => functions.loop_ = true,
-            "forever" => functions.forever = true,
-            other => {
-                // we don't know how to generate this function
-                let ident = Ident::from(other);
-                unknown.push(Loc::at(generated.region, ident));
-            }
+        let name = generated.value.as_str();
+
+        if name == "async" {
+            functions.asynchronous = true;
+        } else if name == "hostBatch" {
+            functions.host_batch = true;
+        } else if let Some(requested) = functions.requested_mut(name) {
+            *requested = true;
+        } else {
+            // we don't know how to generate this function
+            unknown.push(Loc::at(generated.region, Ident::from(name)));
         }
     }
 
+    if functions.asynchronous {
+        // the generated functions all assume a synchronous effect
+        unknown.extend(
+            generate_with
+                .iter()
+                .filter(|generated| generated.value.as_str() != "async")
+                .map(|generated| Loc::at(generated.region, Ident::from(generated.value.as_str()))),
+        );
+
+        functions = HostedGeneratedFunctions {
+            asynchronous: true,
+            ..Default::default()
+        };
+    }
+
     (functions, unknown)
 }
 
@@ -191,6 +206,8 @@ enum GeneratedInfo {
         generated_functions: HostedGeneratedFunctions,
     },
     Builtin,
+    /// A platform module can bind C functions directly; see [bind_foreign_function].
+    Platform,
     NotSpecial,
 }
 
@@ -207,6 +224,7 @@ impl GeneratedInfo {
                 generates_with,
                 name: _,
                 exposes: _,
+                more_generates: _,
             } => {
                 let name: &str = generates.into();
                 let (generated_functions, unknown_generated) =
@@ -221,8 +239,14 @@ impl GeneratedInfo {
                 {
                     let a_var = var_store.fresh();
 
-                    let actual =
-                        crate::effect_module::build_effect_actual(Type::Variable(a_var), var_store);
+                    let actual = if generated_functions.asynchronous {
+                        crate::effect_module::build_async_effect_actual(
+                            Type::Variable(a_var),
+                            var_store,
+                        )
+                    } else {
+                        crate::effect_module::build_effect_actual(Type::Variable(a_var), var_store)
+                    };
 
                     scope.add_alias(
                         effect_symbol,
@@ -247,11 +271,69 @@ impl GeneratedInfo {
                 debug_assert!(generates_with.is_empty());
                 GeneratedInfo::Builtin
             }
+            HeaderType::Platform { .. } => GeneratedInfo::Platform,
             _ => GeneratedInfo::NotSpecial,
         }
     }
 }
 
+/// A platform module binds a C function with a function signature whose body is the name of
+/// the C function, like
+///
+/// ```text
+/// sqrt : F64 -> F64
+/// sqrt = "sqrt"
+/// ```
+///
+/// Calling `sqrt` then calls the C function right away, without wrapping the call in an
+/// `Effect`. Only types with a C equivalent can cross into C; any other type is reported.
+fn bind_foreign_function(
+    env: &mut Env,
+    scope: &mut Scope,
+    declarations: &mut Declarations,
+    index: usize,
+    var_store: &mut VarStore,
+) {
+    let c_name = match &declarations.expressions[index].value {
+        Expr::Str(c_name) => c_name.to_string(),
+        _ => return,
+    };
+
+    let annotation = match &declarations.annotations[index] {
+        Some(annotation)
+            if matches!(
+                annotation.signature.shallow_structural_dealias(),
+                Type::Function(..)
+            ) =>
+        {
+            annotation.clone()
+        }
+        // A plain string, like `greeting : Str` and `greeting = "Hello"`
+        _ => return,
+    };
+
+    let symbol = declarations.symbols[index].value;
+    let ident = scope
+        .locals
+        .ident_ids
+        .get_name(symbol.ident_id())
+        .unwrap()
+        .to_string();
+
+    if !crate::effect_module::is_c_abi_function(&annotation.signature) {
+        env.problem(Problem::ForeignTypeNotCAbi {
+            symbol,
+            region: annotation.region,
+        });
+    }
+
+    let foreign_def = crate::effect_module::build_foreign_def(
+        scope, symbol, &ident, &c_name, var_store, annotation,
+    );
+
+    declarations.update_builtin_def(index, foreign_def);
+}
+
 fn has_no_implementation(expr: &Expr) -> bool {
     match expr {
         Expr::RuntimeError(RuntimeError::NoImplementationNamed { .. }) => true,
@@ -285,11 +367,13 @@ pub fn canonicalize_module_defs<'a>(
     exposed_imports: MutMap<Ident, (Symbol, Region)>,
     exposed_symbols: VecSet<Symbol>,
     symbols_from_requires: &[(Loc<Symbol>, Loc<TypeAnnotation<'a>>)],
+    permit_shadowing: bool,
     var_store: &mut VarStore,
 ) -> ModuleOutput {
     let mut can_exposed_imports = MutMap::default();
     let mut scope = Scope::new(home, exposed_ident_ids, imported_abilities_state);
     let mut env = Env::new(arena, home, dep_idents, module_ids);
+    env.permit_shadowing = permit_shadowing;
 
     for (name, alias) in aliases.into_iter() {
         scope.add_alias(
@@ -368,6 +452,15 @@ pub fn canonicalize_module_defs<'a>(
         }
     }
 
+    // Further effect types of a hosted module are defined in modules of their own, and imported
+    let more_effect_symbols: Vec<Symbol> = match header_type {
+        HeaderType::Hosted { more_generates, .. } => more_generates
+            .iter()
+            .filter_map(|(effect, _)| scope.lookup_str((*effect).into(), Region::zero()).ok())
+            .collect(),
+        _ => Vec::new(),
+    };
+
     let (defs, output, symbols_introduced) = canonicalize_defs(
         &mut env,
         Output::default(),
@@ -419,8 +512,8 @@ pub fn canonicalize_module_defs<'a>(
     referenced_values.extend(output.references.calls().copied());
 
     // Gather up all the symbols that were referenced from other modules.
-    referenced_values.extend(env.qualified_value_lookups.iter().copied());
-    referenced_types.extend(env.qualified_type_lookups.iter().copied());
+    referenced_values.extend(env.qualified_value_lookups.iter());
+    referenced_types.extend(env.qualified_type_lookups.iter());
 
     // NOTE previously we inserted builtin defs into the list of defs here
     // this is now done later, in file.rs.
@@ -487,8 +580,30 @@ pub fn canonicalize_module_defs<'a>(
     } = generated_info
     {
         let mut exposed_symbols = VecSet::default();
+        let mut generated_functions = generated_functions;
+
+        // A generated function can't take the name of something the module defines or imports
+        if let HeaderType::Hosted { generates_with, .. } = header_type {
+            for generated in generates_with.iter() {
+                let name = generated.value.as_str();
+
+                let requested = generated_functions
+                    .requested_mut(name)
+                    .filter(|requested| **requested);
+
+                if let (Some(requested), Some((_, original_region))) =
+                    (requested, scope.lookup_with_region(name))
+                {
+                    *requested = false;
+
+                    env.problem(Problem::GeneratedNameTaken {
+                        generated: Loc::at(generated.region, Ident::from(name)),
+                        original_region,
+                    });
+                }
+            }
+        }
 
-        // NOTE this currently builds all functions, not just the ones that the user requested
         crate::effect_module::build_effect_builtins(
             &mut scope,
             effect_symbol,
@@ -497,8 +612,30 @@ pub fn canonicalize_module_defs<'a>(
             &mut declarations,
             generated_functions,
         );
+
+        // These are implemented with `Result.map` and `Result.mapErr`
+        if generated_functions.map_ok {
+            referenced_values.insert(Symbol::RESULT_MAP);
+        }
+        if generated_functions.map_err {
+            referenced_values.insert(Symbol::RESULT_MAP_ERR);
+        }
+
+        // Without `hostBatch`, this is implemented with `List.map`
+        if generated_functions.batch && !generated_functions.host_batch {
+            referenced_values.insert(Symbol::LIST_MAP);
+        }
+
+        // This keeps its value in a `Box`
+        if generated_functions.once {
+            referenced_values.insert(Symbol::BOX_BOX_FUNCTION);
+            referenced_values.insert(Symbol::BOX_UNBOX);
+        }
     }
 
+    // The defs of the fields of hosted records of effects, like `api : { init : Effect Model }`
+    let mut hosted_field_defs = Vec::new();
+
     for index in 0..declarations.len() {
         use crate::expr::DeclarationTag::*;
 
@@ -506,6 +643,16 @@ pub fn canonicalize_module_defs<'a>(
 
         match tag {
             Value => {
+                if let GeneratedInfo::Platform = generated_info {
+                    bind_foreign_function(
+                        &mut env,
+                        &mut scope,
+                        &mut declarations,
+                        index,
+                        var_store,
+                    );
+                }
+
                 let symbol = &declarations.symbols[index].value;
 
                 // Remove this from exposed_symbols,
@@ -530,7 +677,10 @@ pub fn canonicalize_module_defs<'a>(
                                 }
                             }
                         }
-                        GeneratedInfo::Hosted { effect_symbol, .. } => {
+                        GeneratedInfo::Hosted {
+                            effect_symbol,
+                            generated_functions,
+                        } => {
                             let ident_id = symbol.ident_id();
                             let ident = scope
                                 .locals
@@ -548,16 +698,20 @@ pub fn canonicalize_module_defs<'a>(
                                 aliases: Default::default(),
                             };
 
-                            let hosted_def = crate::effect_module::build_host_exposed_def(
-                                &mut scope,
-                                *symbol,
-                                &ident,
-                                effect_symbol,
-                                var_store,
-                                annotation,
-                            );
+                            let (hosted_def, field_defs) =
+                                crate::effect_module::build_host_exposed_def(
+                                    &mut scope,
+                                    *symbol,
+                                    &ident,
+                                    effect_symbol,
+                                    generated_functions.asynchronous,
+                                    &more_effect_symbols,
+                                    var_store,
+                                    annotation,
+                                );
 
                             declarations.update_builtin_def(index, hosted_def);
+                            hosted_field_defs.extend(field_defs);
                         }
                         _ => (),
                     }
@@ -588,7 +742,10 @@ pub fn canonicalize_module_defs<'a>(
                                 }
                             }
                         }
-                        GeneratedInfo::Hosted { effect_symbol, .. } => {
+                        GeneratedInfo::Hosted {
+                            effect_symbol,
+                            generated_functions,
+                        } => {
                             let ident_id = symbol.ident_id();
                             let ident = scope
                                 .locals
@@ -606,16 +763,20 @@ pub fn canonicalize_module_defs<'a>(
                                 aliases: Default::default(),
                             };
 
-                            let hosted_def = crate::effect_module::build_host_exposed_def(
-                                &mut scope,
-                                *symbol,
-                                &ident,
-                                effect_symbol,
-                                var_store,
-                                annotation,
-                            );
+                            let (hosted_def, field_defs) =
+                                crate::effect_module::build_host_exposed_def(
+                                    &mut scope,
+                                    *symbol,
+                                    &ident,
+                                    effect_symbol,
+                                    generated_functions.asynchronous,
+                                    &more_effect_symbols,
+                                    var_store,
+                                    annotation,
+                                );
 
                             declarations.update_builtin_def(index, hosted_def);
+                            hosted_field_defs.extend(field_defs);
                         }
                         _ => (),
                     }
@@ -636,6 +797,10 @@ pub fn canonicalize_module_defs<'a>(
         }
     }
 
+    for def in hosted_field_defs {
+        declarations.push_def(def);
+    }
+
     let mut aliases = MutMap::default();
 
     if let GeneratedInfo::Hosted { effect_symbol, .. } = generated_info {
@@ -682,7 +847,7 @@ pub fn canonicalize_module_defs<'a>(
 
         // In case this exposed value is referenced by other modules,
         // create a decl for it whose implementation is a runtime error.
-        let mut pattern_vars = SendMap::default();
+        let mut pattern_vars = VecMap::default();
         pattern_vars.insert(symbol, var_store.fresh());
 
         let runtime_error = RuntimeError::ExposedButNotDefined(symbol);
@@ -705,8 +870,8 @@ pub fn canonicalize_module_defs<'a>(
     referenced_values.extend(output.references.calls().copied());
 
     // Gather up all the symbols that were referenced from other modules.
-    referenced_values.extend(env.qualified_value_lookups.iter().copied());
-    referenced_types.extend(env.qualified_type_lookups.iter().copied());
+    referenced_values.extend(env.qualified_value_lookups.iter());
+    referenced_types.extend(env.qualified_type_lookups.iter());
 
     let mut fix_closures_no_capture_symbols = VecSet::default();
     let mut fix_closures_closure_captures = VecMap::default();
@@ -857,7 +1022,7 @@ fn fix_values_captured_in_closure_defs(
         }
     }
 
-    let mut total_capture_set: Vec<_> = total_capture_set.into_iter().collect();
+    let mut total_capture_set: Captures = total_capture_set.into_iter().collect();
     total_capture_set.sort_by_key(|(sym, _)| *sym);
     for def in defs.iter_mut() {
         if let Expr::Closure(ClosureData {
@@ -1049,7 +1214,7 @@ fn fix_values_captured_in_closure_expr(
                 let (captured_symbol, _) = captured_symbols[i];
                 if let Some(captures) = closure_captures.get(&captured_symbol) {
                     debug_assert!(!captures.is_empty());
-                    captured_symbols.extend(captures);
+                    captured_symbols.extend(captures.iter().copied());
                     captured_symbols.swap_remove(i);
 
                     added_captures = true;
@@ -1270,6 +1435,38 @@ impl TypeState {
         Ok(written_subs + written_ab + written_solved_impls)
     }
 
+    /// Translates the symbols of a state that was persisted by another run of the compiler,
+    /// which may have numbered the modules differently, into the `ModuleId`s of this run.
+    pub fn remap_symbols(&mut self, remap: &SymbolRemap) {
+        let remap_symbol = |symbol: Symbol| remap.symbol(symbol);
+
+        self.subs.remap_symbols(remap_symbol);
+        self.abilities.remap_symbols(remap_symbol);
+
+        for (symbol, _) in self.exposed_vars_by_symbol.iter_mut() {
+            *symbol = remap_symbol(*symbol);
+        }
+
+        self.solved_implementations = std::mem::take(&mut self.solved_implementations)
+            .into_iter()
+            .map(|(impl_key, resolved_impl)| {
+                let impl_key = ImplKey {
+                    opaque: remap_symbol(impl_key.opaque),
+                    ability_member: remap_symbol(impl_key.ability_member),
+                };
+                let resolved_impl = match resolved_impl {
+                    ResolvedImpl::Impl(mut info) => {
+                        info.symbol = remap_symbol(info.symbol);
+                        ResolvedImpl::Impl(info)
+                    }
+                    ResolvedImpl::Error => ResolvedImpl::Error,
+                };
+
+                (impl_key, resolved_impl)
+            })
+            .collect();
+    }
+
     pub fn deserialize(bytes: &[u8]) -> (Self, usize) {
         let ((subs, exposed_vars_by_symbol), len_subs) = Subs::deserialize(bytes);
         let bytes = &bytes[len_subs..];
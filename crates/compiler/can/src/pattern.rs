@@ -87,7 +87,8 @@ pub enum Pattern {
     },
 
     // Runtime Exceptions
-    Shadowed(Region, Loc<Ident>, Symbol),
+    /// The original symbol with its definition's region, the shadow, and the shadow's symbol
+    Shadowed(Loc<Symbol>, Loc<Ident>, Symbol),
     OpaqueNotInScope(Loc<Ident>),
     // Example: (5 = 1 + 2) is an unsupported pattern in an assignment; Int patterns aren't allowed in assignments!
     UnsupportedPattern(Region),
@@ -289,15 +290,23 @@ pub fn canonicalize_def_header_pattern<'a>(
                     };
                     Loc::at(region, can_pattern)
                 }
-                Err((original_region, shadow, new_symbol)) => {
+                Err((original, shadow, new_symbol))
+                    if permit_shadow(env, scope, original, &shadow, new_symbol) =>
+                {
+                    output.references.insert_bound(new_symbol);
+
+                    Loc::at(region, Pattern::Identifier(new_symbol))
+                }
+                Err((original, shadow, new_symbol)) => {
                     env.problem(Problem::RuntimeError(RuntimeError::Shadowing {
-                        original_region,
+                        original_region: original.region,
                         shadow: shadow.clone(),
                         kind: ShadowKind::Variable,
+                        original_symbol: original.value,
                     }));
                     output.references.insert_bound(new_symbol);
 
-                    let can_pattern = Pattern::Shadowed(original_region, shadow, new_symbol);
+                    let can_pattern = Pattern::Shadowed(original, shadow, new_symbol);
                     Loc::at(region, can_pattern)
                 }
             }
@@ -321,6 +330,45 @@ pub fn canonicalize_def_header_pattern<'a>(
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub struct PermitShadows(pub bool);
 
+/// In a module that permits shadowing (like the REPL's), brings the shadow of `original` into
+/// scope in its place and reports a warning. Returns whether that happened.
+fn permit_shadow(
+    env: &mut Env,
+    scope: &mut Scope,
+    original: Loc<Symbol>,
+    shadow: &Loc<Ident>,
+    shadow_symbol: Symbol,
+) -> bool {
+    if !(env.permit_shadowing && scope.shadow(original.value, shadow_symbol)) {
+        return false;
+    }
+
+    env.problem(Problem::PermittedShadowing {
+        original_region: original.region,
+        shadow: shadow.clone(),
+    });
+
+    true
+}
+
+/// Like [Scope::introduce_str], but a variable that shadows another one is introduced anyway
+/// if the module permits that.
+fn introduce_variable(
+    env: &mut Env,
+    scope: &mut Scope,
+    ident: &str,
+    region: Region,
+) -> Result<Symbol, (Loc<Symbol>, Loc<Ident>, Symbol)> {
+    match scope.introduce_str(ident, region) {
+        Err((original, shadow, shadow_symbol))
+            if permit_shadow(env, scope, original, &shadow, shadow_symbol) =>
+        {
+            Ok(shadow_symbol)
+        }
+        introduced => introduced,
+    }
+}
+
 fn canonicalize_pattern_symbol(
     env: &mut Env,
     scope: &mut Scope,
@@ -329,7 +377,13 @@ fn canonicalize_pattern_symbol(
     permit_shadows: PermitShadows,
     name: &str,
 ) -> Result<Symbol, Pattern> {
-    match scope.introduce_str(name, region) {
+    let introduced = if permit_shadows.0 {
+        scope.introduce_str(name, region)
+    } else {
+        introduce_variable(env, scope, name, region)
+    };
+
+    match introduced {
         Ok(symbol) => {
             output.references.insert_bound(symbol);
 
@@ -345,14 +399,11 @@ fn canonicalize_pattern_symbol(
                     original_region: shadowed_symbol.region,
                     shadow: shadow.clone(),
                     kind: ShadowKind::Variable,
+                    original_symbol: shadowed_symbol.value,
                 }));
                 output.references.insert_bound(new_symbol);
 
-                Err(Pattern::Shadowed(
-                    shadowed_symbol.region,
-                    shadow,
-                    new_symbol,
-                ))
+                Err(Pattern::Shadowed(shadowed_symbol, shadow, new_symbol))
             }
         }
     }
@@ -629,7 +680,7 @@ pub fn canonicalize_pattern<'a>(
             for loc_pattern in patterns.iter() {
                 match loc_pattern.value {
                     Identifier(label) => {
-                        match scope.introduce(label.into(), region) {
+                        match introduce_variable(env, scope, label, region) {
                             Ok(symbol) => {
                                 output.references.insert_bound(symbol);
 
@@ -648,17 +699,15 @@ pub fn canonicalize_pattern<'a>(
                                     original_region: shadowed_symbol.region,
                                     shadow: shadow.clone(),
                                     kind: ShadowKind::Variable,
+                                    original_symbol: shadowed_symbol.value,
                                 }));
 
                                 // No matter what the other patterns
                                 // are, we're definitely shadowed and will
                                 // get a runtime exception as soon as we
                                 // encounter the first bad pattern.
-                                opt_erroneous = Some(Pattern::Shadowed(
-                                    shadowed_symbol.region,
-                                    shadow,
-                                    new_symbol,
-                                ));
+                                opt_erroneous =
+                                    Some(Pattern::Shadowed(shadowed_symbol, shadow, new_symbol));
                             }
                         };
                     }
@@ -690,7 +739,7 @@ pub fn canonicalize_pattern<'a>(
                     }
                     OptionalField(label, loc_default) => {
                         // an optional DOES introduce the label into scope!
-                        match scope.introduce(label.into(), region) {
+                        match introduce_variable(env, scope, label, region) {
                             Ok(symbol) => {
                                 let (can_default, expr_output) = canonicalize_expr(
                                     env,
@@ -720,17 +769,15 @@ pub fn canonicalize_pattern<'a>(
                                     original_region: shadowed_symbol.region,
                                     shadow: shadow.clone(),
                                     kind: ShadowKind::Variable,
+                                    original_symbol: shadowed_symbol.value,
                                 }));
 
                                 // No matter what the other patterns
                                 // are, we're definitely shadowed and will
                                 // get a runtime exception as soon as we
                                 // encounter the first bad pattern.
-                                opt_erroneous = Some(Pattern::Shadowed(
-                                    shadowed_symbol.region,
-                                    shadow,
-                                    new_symbol,
-                                ));
+                                opt_erroneous =
+                                    Some(Pattern::Shadowed(shadowed_symbol, shadow, new_symbol));
                             }
                         };
                     }
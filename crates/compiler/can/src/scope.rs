@@ -45,6 +45,10 @@ pub struct Scope {
     /// Ignored variables (variables that start with an underscore).
     /// We won't intern them because they're only used during canonicalization for error reporting.
     ignored_locals: VecMap<String, Region>,
+
+    /// Locals hidden by a permitted shadow (see [Self::shadow]); they come back into scope
+    /// when the inner scope of the shadow ends.
+    hidden_locals: Vec<IdentId>,
 }
 
 impl Scope {
@@ -70,6 +74,7 @@ impl Scope {
             shadows: VecMap::default(),
             imports: default_imports,
             ignored_locals: VecMap::default(),
+            hidden_locals: Vec::new(),
         }
     }
 
@@ -77,6 +82,14 @@ impl Scope {
         self.lookup_str(ident.as_str(), region)
     }
 
+    /// What `ident` refers to and where that was defined, if it is in scope
+    pub fn lookup_with_region(&self, ident: &str) -> Option<(Symbol, Region)> {
+        match self.scope_contains_ident(ident) {
+            ContainsIdent::InScope(symbol, region) => Some((symbol, region)),
+            ContainsIdent::NotInScope(_) | ContainsIdent::NotPresent => None,
+        }
+    }
+
     pub fn lookup_ability_member_shadow(&self, member: Symbol) -> Option<Symbol> {
         self.shadows.get(&member).map(|loc_shadow| loc_shadow.value)
     }
@@ -320,7 +333,7 @@ impl Scope {
         pending_abilities_in_scope: &PendingAbilitiesInScope,
         ident: Ident,
         region: Region,
-    ) -> Result<(Symbol, Option<Symbol>), (Region, Loc<Ident>, Symbol)> {
+    ) -> Result<(Symbol, Option<Symbol>), (Loc<Symbol>, Loc<Ident>, Symbol)> {
         let ident = &ident;
 
         match self.introduce_help(ident.as_str(), region) {
@@ -339,7 +352,7 @@ impl Scope {
                                 value: ident.clone(),
                                 region,
                             };
-                            Err((loc_original_shadow.region, shadow, shadow_symbol))
+                            Err((*loc_original_shadow, shadow, shadow_symbol))
                         }
                         None => {
                             self.shadows
@@ -355,13 +368,37 @@ impl Scope {
                         region,
                     };
 
-                    Err((original_region, shadow, shadow_symbol))
+                    Err((
+                        Loc::at(original_region, original_symbol),
+                        shadow,
+                        shadow_symbol,
+                    ))
                 }
             }
             Ok(symbol) => Ok((symbol, None)),
         }
     }
 
+    /// Make the ident of `original` refer to `shadow_symbol` (as returned by a failed
+    /// [Self::introduce]) from now on, hiding `original` until the current inner scope ends.
+    ///
+    /// Returns false if `original` is not a local of this module, e.g. an import; those can't
+    /// be shadowed.
+    pub fn shadow(&mut self, original: Symbol, shadow_symbol: Symbol) -> bool {
+        if original.module_id() != self.home {
+            return false;
+        }
+
+        self.locals.in_scope.set(original.ident_id().index(), false);
+        self.hidden_locals.push(original.ident_id());
+
+        self.locals
+            .in_scope
+            .set(shadow_symbol.ident_id().index(), true);
+
+        true
+    }
+
     pub fn get_member_shadow(&self, ability_member: Symbol) -> Option<&Loc<Symbol>> {
         self.shadows.get(&ability_member)
     }
@@ -428,6 +465,7 @@ impl Scope {
         // - home: unchanged
         let aliases_count = self.aliases.len();
         let ignored_locals_count = self.ignored_locals.len();
+        let hidden_locals_count = self.hidden_locals.len();
         let locals_snapshot = self.locals.in_scope.len();
 
         let result = f(self);
@@ -440,6 +478,13 @@ impl Scope {
             self.locals.in_scope.set(i, false);
         }
 
+        // and what was shadowed in the inner scope is visible again
+        for ident_id in self.hidden_locals.drain(hidden_locals_count..) {
+            if ident_id.index() < locals_snapshot {
+                self.locals.in_scope.set(ident_id.index(), true);
+            }
+        }
+
         result
     }
 
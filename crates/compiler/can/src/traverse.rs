@@ -1,5 +1,6 @@
 //! Traversals over the can ast.
 
+use roc_collections::MutMap;
 use roc_module::{ident::Lowercase, symbol::Symbol};
 use roc_region::all::{Loc, Position, Region};
 use roc_types::{subs::Variable, types::MemberImpl};
@@ -662,6 +663,8 @@ impl Visitor for TypeAtVisitor {
 struct TypeAtPositionVisitor {
     position: Position,
     region_typ: Option<(Region, Variable)>,
+    /// The symbol that the narrowest node is a lookup of, if it is one
+    lookup: Option<Symbol>,
 }
 
 impl Visitor for TypeAtPositionVisitor {
@@ -672,6 +675,10 @@ impl Visitor for TypeAtPositionVisitor {
     fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
         if region.contains_pos(self.position) {
             self.region_typ = Some((region, var));
+            self.lookup = match expr {
+                Expr::Var(symbol, _) => Some(*symbol),
+                _ => None,
+            };
 
             walk_expr(self, expr, var);
         }
@@ -681,6 +688,7 @@ impl Visitor for TypeAtPositionVisitor {
         if region.contains_pos(self.position) {
             if let Some(var) = opt_var {
                 self.region_typ = Some((region, var));
+                self.lookup = None;
             }
 
             walk_pattern(self, pat);
@@ -724,11 +732,66 @@ pub fn find_closest_type_at(
     let mut visitor = TypeAtPositionVisitor {
         position,
         region_typ: None,
+        lookup: None,
     };
     visitor.visit_decls(decls);
     visitor.region_typ
 }
 
+/// Like [find_closest_type_at], but a lookup gets the variable of the symbol's definition. The
+/// lookup's own variable has the type that the surrounding expression expects, so an argument
+/// `age : Age` that is passed to `Num.mul` would be a plain `U8`.
+pub fn find_closest_declared_type_at(
+    position: Position,
+    decls: &Declarations,
+) -> Option<(Region, Variable)> {
+    let mut visitor = TypeAtPositionVisitor {
+        position,
+        region_typ: None,
+        lookup: None,
+    };
+    visitor.visit_decls(decls);
+
+    let (region, var) = visitor.region_typ?;
+    let var = visitor
+        .lookup
+        .and_then(|symbol| find_definition_var(symbol, decls))
+        .unwrap_or(var);
+
+    Some((region, var))
+}
+
+struct DefinitionVarVisitor {
+    symbol: Symbol,
+    var: Option<Variable>,
+}
+
+impl Visitor for DefinitionVarVisitor {
+    fn should_visit(&mut self, _region: Region) -> bool {
+        self.var.is_none()
+    }
+
+    fn visit_pattern(&mut self, pat: &Pattern, _region: Region, opt_var: Option<Variable>) {
+        match pat {
+            Pattern::Identifier(symbol)
+            | Pattern::Shadowed(_, _, symbol)
+            | Pattern::As(_, symbol)
+                if *symbol == self.symbol =>
+            {
+                self.var = self.var.or(opt_var);
+            }
+            _ => walk_pattern(self, pat),
+        }
+    }
+}
+
+/// The variable of the pattern that defines `symbol`, if that pattern has one
+fn find_definition_var(symbol: Symbol, decls: &Declarations) -> Option<Variable> {
+    let mut visitor = DefinitionVarVisitor { symbol, var: None };
+    visitor.visit_decls(decls);
+    visitor.var
+}
+
 /// Given an ability Foo has foo : ..., returns (T, foo1) if the symbol at the given region is a
 /// symbol foo1 that specializes foo for T. Otherwise if the symbol is foo but the specialization
 /// is unknown, (Foo, foo) is returned. Otherwise [None] is returned.
@@ -892,6 +955,108 @@ pub fn symbols_introduced_from_pattern(
     }
 }
 
+/// Where a symbol is defined and used, as recorded by [index_symbol_occurrences].
+#[derive(Debug, Default, Clone)]
+pub struct SymbolOccurrences {
+    /// The regions of the patterns which define the symbol.
+    pub definitions: Vec<Region>,
+    /// The regions of the lookups of the symbol. A qualified lookup like `Str.concat` includes
+    /// the module name.
+    pub lookups: Vec<Region>,
+    /// The regions of the types in the symbol's annotations, whose names come before them in
+    /// the source but aren't part of the canonical AST.
+    pub annotations: Vec<Region>,
+    /// The regions of record fields like the `x` in `{ x }` or `{ x ? 0 }`, which name both a
+    /// field and the symbol. These are among the definitions or lookups as well.
+    pub punned: Vec<Region>,
+}
+
+/// Finds everywhere each symbol is defined or looked up in `decls`.
+pub fn index_symbol_occurrences(decls: &Declarations) -> MutMap<Symbol, SymbolOccurrences> {
+    let mut visitor = Indexer {
+        index: MutMap::default(),
+    };
+    visitor.visit_decls(decls);
+    return visitor.index;
+
+    struct Indexer {
+        index: MutMap<Symbol, SymbolOccurrences>,
+    }
+
+    impl Indexer {
+        fn occurrences(&mut self, symbol: Symbol) -> &mut SymbolOccurrences {
+            self.index.entry(symbol).or_default()
+        }
+    }
+
+    impl Visitor for Indexer {
+        fn visit_decls(&mut self, decls: &Declarations) {
+            for (loc_symbol, annotation) in decls.symbols.iter().zip(decls.annotations.iter()) {
+                if let Some(annotation) = annotation {
+                    let occurrences = self.occurrences(loc_symbol.value);
+                    occurrences.annotations.push(annotation.region);
+                }
+            }
+
+            walk_decls(self, decls);
+        }
+
+        fn visit_def(&mut self, def: &Def) {
+            if let (Pattern::Identifier(symbol), Some(annotation)) =
+                (&def.loc_pattern.value, &def.annotation)
+            {
+                let occurrences = self.occurrences(*symbol);
+                occurrences.annotations.push(annotation.region);
+            }
+
+            walk_def(self, def);
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+            if let Pattern::Identifier(symbol) | Pattern::As(_, symbol) = pattern {
+                self.occurrences(*symbol).definitions.push(region);
+            }
+
+            walk_pattern(self, pattern);
+        }
+
+        fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+            // `{ x }` and `{ x ? 0 }` define `x`, while `{ x: y }` has a pattern which defines `y`
+            if let DestructType::Required | DestructType::Optional(..) = destruct.typ {
+                let label_end = region.start().offset + destruct.label.as_str().len() as u32;
+                let label_region = Region::new(region.start(), Position::new(label_end));
+
+                let occurrences = self.occurrences(destruct.symbol);
+                occurrences.definitions.push(label_region);
+                occurrences.punned.push(label_region);
+            }
+
+            walk_record_destruct(self, destruct);
+        }
+
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            match expr {
+                Expr::Var(symbol, _) => {
+                    self.occurrences(*symbol).lookups.push(region);
+                }
+                Expr::Record { fields, .. } => {
+                    // `{ x }` is sugar for `{ x: x }`, where the lookup has the field's region
+                    for field in fields.values() {
+                        if let Expr::Var(symbol, _) = &field.loc_expr.value {
+                            if field.loc_expr.region == field.region {
+                                self.occurrences(*symbol).punned.push(field.region);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            walk_expr(self, expr, var);
+        }
+    }
+}
+
 pub enum FoundDeclaration<'a> {
     Decl(DeclarationInfo<'a>),
     Def(&'a Def),
@@ -35,6 +35,17 @@ pub struct CanExprOut {
 
 #[allow(dead_code)]
 pub fn can_expr_with(arena: &Bump, home: ModuleId, expr_str: &str) -> CanExprOut {
+    can_expr_with_shadowing(arena, home, expr_str, false)
+}
+
+/// Like [can_expr_with], but lets the caller permit shadowing, like the REPL does.
+#[allow(dead_code)]
+pub fn can_expr_with_shadowing(
+    arena: &Bump,
+    home: ModuleId,
+    expr_str: &str,
+    permit_shadowing: bool,
+) -> CanExprOut {
     let loc_expr = roc_parse::test_helpers::parse_loc_with(arena, expr_str).unwrap_or_else(|e| {
         panic!(
             "can_expr_with() got a parse error when attempting to canonicalize:\n\n{expr_str:?} {e:?}"
@@ -75,6 +86,7 @@ pub fn can_expr_with(arena: &Bump, home: ModuleId, expr_str: &str) -> CanExprOut
 
     let dep_idents = IdentIds::exposed_builtins(0);
     let mut env = Env::new(arena, home, &dep_idents, &module_ids);
+    env.permit_shadowing = permit_shadowing;
     let (loc_expr, output) = canonicalize_expr(
         &mut env,
         &mut var_store,
@@ -12,7 +12,7 @@ mod helpers;
 
 #[cfg(test)]
 mod test_can {
-    use crate::helpers::{can_expr_with, test_home, CanExprOut};
+    use crate::helpers::{can_expr_with, can_expr_with_shadowing, test_home, CanExprOut};
     use bumpalo::Bump;
     use core::panic;
     use roc_can::expr::Expr::{self, *};
@@ -407,6 +407,50 @@ mod test_can {
         )));
     }
 
+    #[test]
+    fn permitted_shadowing_of_an_argument() {
+        let src = indoc!(
+            r"
+                x = 1
+                f = \x -> x + 1
+
+                f x
+            "
+        );
+        let arena = Bump::new();
+        let CanExprOut { problems, .. } = can_expr_with_shadowing(&arena, test_home(), src, true);
+
+        // both `x`s are used, so the outer one is back in scope after the function
+        assert_eq!(problems.len(), 1, "{problems:#?}");
+        assert!(matches!(
+            &problems[0],
+            Problem::PermittedShadowing { shadow, .. } if shadow.value.as_str() == "x"
+        ));
+    }
+
+    #[test]
+    fn permitted_shadowing_of_a_def() {
+        let src = indoc!(
+            r"
+                x = 1
+                x = 2
+
+                x
+            "
+        );
+        let arena = Bump::new();
+        let CanExprOut { problems, .. } = can_expr_with_shadowing(&arena, test_home(), src, true);
+
+        // the second `x` is the one in scope, so the first one is unused
+        assert_eq!(problems.len(), 2, "{problems:#?}");
+        assert!(problems
+            .iter()
+            .any(|problem| matches!(problem, Problem::PermittedShadowing { .. })));
+        assert!(problems
+            .iter()
+            .any(|problem| matches!(problem, Problem::UnusedDef(..))));
+    }
+
     #[test]
     fn shadowed_annotation() {
         let src = indoc!(
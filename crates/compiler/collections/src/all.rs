@@ -92,6 +92,40 @@ where
     }
 }
 
+/// The entries of a MutMap, sorted by key.
+///
+/// Iterating a MutMap or MutSet directly visits the entries in hash order. That order depends
+/// on the keys and on the order they were inserted in, so it can differ between runs, e.g. when
+/// the keys are symbols of modules that were loaded concurrently. Whenever the iteration order
+/// ends up in output, iterate through this or [into_sorted_entries_by_key] instead.
+///
+/// Sorting only helps when the keys are the same from run to run. The specializations which
+/// mono makes are keyed by symbols and layouts, which are numbered in the order threads get to
+/// them, so sorting those wouldn't make their order stable; building on a single thread does.
+pub fn sorted_entries<K: Ord, V, S>(map: &std::collections::HashMap<K, V, S>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+
+    entries
+}
+
+/// Consumes a MutMap and returns its entries sorted by a key computed from each entry. This is
+/// for keys which don't have a meaningful order of their own, like a `ModuleId`, but can be
+/// ordered by something that is stable between runs, like the module's path.
+pub fn into_sorted_entries_by_key<K, V, S, T, F>(
+    map: std::collections::HashMap<K, V, S>,
+    mut sort_key: F,
+) -> Vec<(K, V)>
+where
+    T: Ord,
+    F: FnMut(&K, &V) -> T,
+{
+    let mut entries: Vec<_> = map.into_iter().collect();
+    entries.sort_by_cached_key(|(key, value)| sort_key(key, value));
+
+    entries
+}
+
 /// Like im's relative_complement, but for MutMap and with references for arguments.
 pub fn relative_complement<K, V>(map: &MutMap<K, V>, other: &MutMap<K, V>) -> MutMap<K, V>
 where
@@ -207,3 +241,37 @@ macro_rules! mut_map {
         }
     };
 }
+
+#[cfg(test)]
+mod test_sorted_entries {
+    use super::{into_sorted_entries_by_key, sorted_entries, MutMap};
+
+    #[test]
+    fn test_empty() {
+        let map: MutMap<u32, &str> = MutMap::default();
+
+        assert!(sorted_entries(&map).is_empty());
+        assert!(into_sorted_entries_by_key(map, |key, _| *key).is_empty());
+    }
+
+    #[test]
+    fn test_insertion_order_does_not_matter() {
+        let entries = [(3, "c"), (1, "a"), (4, "d"), (2, "b")];
+
+        let forwards: MutMap<_, _> = entries.iter().copied().collect();
+        let backwards: MutMap<_, _> = entries.iter().rev().copied().collect();
+
+        let expected = vec![(&1, &"a"), (&2, &"b"), (&3, &"c"), (&4, &"d")];
+        assert_eq!(sorted_entries(&forwards), expected);
+        assert_eq!(sorted_entries(&backwards), expected);
+    }
+
+    #[test]
+    fn test_sort_by_computed_key() {
+        let map: MutMap<_, _> = [(1, "zeta"), (2, "alpha"), (3, "mu")].into_iter().collect();
+
+        let sorted = into_sorted_entries_by_key(map, |_, name| *name);
+
+        assert_eq!(sorted, vec![(2, "alpha"), (3, "mu"), (1, "zeta")]);
+    }
+}
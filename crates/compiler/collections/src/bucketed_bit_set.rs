@@ -0,0 +1,194 @@
+use std::{iter::FromIterator, marker::PhantomData};
+
+type Order = bitvec::order::Lsb0;
+type BitVec = bitvec::vec::BitVec<usize, Order>;
+
+/// A value that is identified by a small index within a bucket, like a symbol's ident id within
+/// its module.
+pub trait BucketedIndex: Copy {
+    fn bucket(self) -> u32;
+    fn index_in_bucket(self) -> u32;
+    fn from_bucket_and_index(bucket: u32, index: u32) -> Self;
+}
+
+/// A set of values that are densely numbered within their bucket, with one bit vector per
+/// bucket. Membership and insertion don't need to look at the other elements, and taking the
+/// union of two sets is a bitwise or of their buckets.
+///
+/// Iterating the set goes over the buckets in the order they were first inserted into, and the
+/// elements of each bucket by index.
+#[derive(Clone, Debug)]
+pub struct BucketedBitSet<T> {
+    buckets: Vec<(u32, BitVec)>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Default for BucketedBitSet<T> {
+    fn default() -> Self {
+        Self {
+            buckets: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: BucketedIndex> BucketedBitSet<T> {
+    fn bucket(&self, bucket: u32) -> Option<&BitVec> {
+        self.buckets
+            .iter()
+            .find_map(|(b, bits)| (*b == bucket).then_some(bits))
+    }
+
+    fn bucket_mut(&mut self, bucket: u32) -> &mut BitVec {
+        let position = match self.buckets.iter().position(|(b, _)| *b == bucket) {
+            Some(position) => position,
+            None => {
+                self.buckets.push((bucket, BitVec::new()));
+                self.buckets.len() - 1
+            }
+        };
+
+        &mut self.buckets[position].1
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(|(_, bits)| bits.count_ones()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter().all(|(_, bits)| bits.not_any())
+    }
+
+    /// Returns true iff the value was not in the set yet.
+    pub fn insert(&mut self, value: T) -> bool {
+        let index = value.index_in_bucket() as usize;
+        let bits = self.bucket_mut(value.bucket());
+
+        if index >= bits.len() {
+            bits.resize(index + 1, false);
+        }
+
+        !bits.replace(index, true)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        match self.bucket(value.bucket()) {
+            Some(bits) => bits
+                .get(value.index_in_bucket() as usize)
+                .map_or(false, |bit| *bit),
+            None => false,
+        }
+    }
+
+    /// Returns true iff the value was in the set.
+    pub fn remove(&mut self, value: &T) -> bool {
+        let index = value.index_in_bucket() as usize;
+
+        match self.buckets.iter_mut().find(|(b, _)| *b == value.bucket()) {
+            Some((_, bits)) if index < bits.len() => bits.replace(index, false),
+            _ => false,
+        }
+    }
+
+    pub fn union_mut(&mut self, other: &Self) {
+        for (bucket, other_bits) in other.buckets.iter() {
+            let bits = self.bucket_mut(*bucket);
+
+            if bits.len() < other_bits.len() {
+                bits.resize(other_bits.len(), false);
+            }
+
+            bits[..other_bits.len()] |= other_bits.as_bitslice();
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.buckets.iter().flat_map(|(bucket, bits)| {
+            bits.iter_ones()
+                .map(move |index| T::from_bucket_and_index(*bucket, index as u32))
+        })
+    }
+}
+
+impl<T: BucketedIndex> Extend<T> for BucketedBitSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: BucketedIndex> FromIterator<T> for BucketedBitSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: BucketedIndex> PartialEq for BucketedBitSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        // Buckets can have trailing zeros, or be empty, so compare the elements.
+        self.len() == other.len() && self.iter().all(|value| other.contains(&value))
+    }
+}
+
+impl<T: BucketedIndex> Eq for BucketedBitSet<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::{BucketedBitSet, BucketedIndex};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Id(u32, u32);
+
+    impl BucketedIndex for Id {
+        fn bucket(self) -> u32 {
+            self.0
+        }
+
+        fn index_in_bucket(self) -> u32 {
+            self.1
+        }
+
+        fn from_bucket_and_index(bucket: u32, index: u32) -> Self {
+            Id(bucket, index)
+        }
+    }
+
+    #[test]
+    fn insert_contains_remove() {
+        let mut set = BucketedBitSet::default();
+
+        assert!(set.insert(Id(3, 100)));
+        assert!(set.insert(Id(1, 0)));
+        assert!(!set.insert(Id(3, 100)));
+
+        assert!(set.contains(&Id(3, 100)));
+        assert!(!set.contains(&Id(3, 99)));
+        assert!(!set.contains(&Id(2, 100)));
+        assert_eq!(set.len(), 2);
+
+        assert!(set.remove(&Id(3, 100)));
+        assert!(!set.remove(&Id(3, 100)));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![Id(1, 0)]);
+    }
+
+    #[test]
+    fn union() {
+        let mut left: BucketedBitSet<Id> = [Id(1, 1), Id(2, 64)].into_iter().collect();
+        let right: BucketedBitSet<Id> = [Id(2, 3), Id(2, 200), Id(5, 0)].into_iter().collect();
+
+        left.union_mut(&right);
+
+        assert_eq!(
+            left.iter().collect::<Vec<_>>(),
+            vec![Id(1, 1), Id(2, 3), Id(2, 64), Id(2, 200), Id(5, 0)]
+        );
+
+        let mut removed = left.clone();
+        removed.remove(&Id(2, 200));
+        removed.insert(Id(2, 200));
+        assert_eq!(removed, left);
+    }
+}
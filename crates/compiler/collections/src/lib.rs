@@ -4,6 +4,7 @@
 #![allow(clippy::large_enum_variant)]
 
 pub mod all;
+mod bucketed_bit_set;
 mod reference_matrix;
 mod small_string_interner;
 mod small_vec;
@@ -12,6 +13,7 @@ mod vec_map;
 mod vec_set;
 
 pub use all::{default_hasher, BumpMap, ImEntry, ImMap, ImSet, MutMap, MutSet, SendMap};
+pub use bucketed_bit_set::{BucketedBitSet, BucketedIndex};
 pub use reference_matrix::{ReferenceMatrix, Sccs, TopologicalSort};
 pub use small_string_interner::SmallStringInterner;
 pub use small_vec::SmallVec;
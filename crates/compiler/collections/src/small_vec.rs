@@ -1,4 +1,5 @@
 use std::fmt::Debug;
+use std::ops::{Deref, DerefMut};
 
 use smallvec::SmallVec as Vec;
 
@@ -17,9 +18,33 @@ impl<T, const N: usize> SmallVec<T, N> {
     pub fn pop(&mut self) -> Option<T> {
         self.0.pop()
     }
+
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        self.0.swap_remove(index)
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.0.retain(|value| f(value))
+    }
+
+    pub fn dedup_by_key<F, K>(&mut self, key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.0.dedup_by_key(key)
+    }
+
+    /// Whether the elements are stored on the heap, because there are more than `N` of them.
+    pub fn spilled(&self) -> bool {
+        self.0.spilled()
+    }
 }
 
-impl<T, const N: usize> std::ops::Deref for SmallVec<T, N> {
+impl<T, const N: usize> Deref for SmallVec<T, N> {
     type Target = [T];
 
     fn deref(&self) -> &Self::Target {
@@ -27,6 +52,12 @@ impl<T, const N: usize> std::ops::Deref for SmallVec<T, N> {
     }
 }
 
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.deref_mut()
+    }
+}
+
 impl<T, const N: usize> Debug for SmallVec<T, N>
 where
     T: Debug,
@@ -59,3 +90,15 @@ impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
         Self(Vec::from_iter(iter))
     }
 }
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl<T, const N: usize, const M: usize> From<[T; M]> for SmallVec<T, N> {
+    fn from(array: [T; M]) -> Self {
+        Self::from_iter(array)
+    }
+}
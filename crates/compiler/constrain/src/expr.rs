@@ -874,6 +874,7 @@ pub fn constrain_expr(
                 }
                 _ => {
                     let branch_var_index = constraints.push_variable(*branch_var);
+                    let first_branch = branches[0].1.region;
 
                     for (index, (loc_cond, loc_body)) in branches.iter().enumerate() {
                         let expected_bool = expect_bool(constraints, loc_cond.region);
@@ -890,6 +891,7 @@ pub fn constrain_expr(
                             Reason::IfBranch {
                                 index: HumanIndex::zero_based(index),
                                 total_branches: branches.len(),
+                                first_branch,
                             },
                             branch_var_index,
                             loc_body.region,
@@ -910,6 +912,7 @@ pub fn constrain_expr(
                         Reason::IfBranch {
                             index: HumanIndex::zero_based(branches.len()),
                             total_branches: branches.len() + 1,
+                            first_branch,
                         },
                         branch_var_index,
                         final_else.region,
@@ -975,7 +978,14 @@ pub fn constrain_expr(
                         )
                     }
 
-                    _ => ForReason(Reason::WhenBranch { index }, body_type_index, branch_region),
+                    _ => ForReason(
+                        Reason::WhenBranch {
+                            index,
+                            first_branch: branches[0].value.region,
+                        },
+                        body_type_index,
+                        branch_region,
+                    ),
                 };
 
             // Our goal is to constrain and introduce variables in all pattern when branch patterns before
@@ -1000,7 +1010,7 @@ pub fn constrain_expr(
             // as part of the pattern constraint, solving all of those at once, and then solving the body
             // constraints.
             let mut pattern_vars = Vec::with_capacity(branches.len());
-            let mut pattern_headers = SendMap::default();
+            let mut pattern_headers = VecMap::default();
             let mut pattern_cons = Vec::with_capacity(branches.len() + 2);
             let mut delayed_is_open_constraints = Vec::with_capacity(2);
             let mut body_cons = Vec::with_capacity(branches.len());
@@ -147,7 +147,7 @@ fn wrap_in_decode_custom_decode_with(
             closure_type: fn_clos_var,
             return_type: decode_with_result_var,
             name: fn_name,
-            captured_symbols: sorted_inner_decoder_captures,
+            captured_symbols: sorted_inner_decoder_captures.into_iter().collect(),
             recursive: Recursive::NotRecursive,
             arguments: vec![
                 (
@@ -1,5 +1,5 @@
 use roc_can::expr::{
-    AnnotatedMark, ClosureData, Expr, Field, Recursive, WhenBranch, WhenBranchPattern,
+    AnnotatedMark, Captures, ClosureData, Expr, Field, Recursive, WhenBranch, WhenBranchPattern,
 };
 use roc_can::pattern::Pattern;
 use roc_collections::SendMap;
@@ -556,7 +556,7 @@ fn step_field(
                 closure_type: custom_callback_lambda_set_var,
                 return_type: custom_callback_ret_var,
                 name: custom_closure_symbol,
-                captured_symbols: vec![(state_arg_symbol, state_record_var)],
+                captured_symbols: Captures::from([(state_arg_symbol, state_record_var)]),
                 recursive: Recursive::NotRecursive,
                 arguments: vec![
                     (
@@ -704,7 +704,7 @@ fn step_field(
         closure_type,
         return_type: keep_or_skip_var,
         name: step_field_closure,
-        captured_symbols: Vec::new(),
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![
             (
@@ -898,7 +898,7 @@ fn finalizer(
         closure_type,
         return_type: return_type_var,
         name: function_symbol,
-        captured_symbols: Vec::new(),
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             state_record_var,
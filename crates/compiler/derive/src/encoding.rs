@@ -3,7 +3,7 @@
 use std::iter::once;
 
 use roc_can::expr::{
-    AnnotatedMark, ClosureData, Expr, Field, Recursive, WhenBranch, WhenBranchPattern,
+    AnnotatedMark, Captures, ClosureData, Expr, Field, Recursive, WhenBranch, WhenBranchPattern,
 };
 use roc_can::pattern::Pattern;
 use roc_collections::SendMap;
@@ -190,7 +190,7 @@ fn to_encoder_list(env: &mut Env<'_>, fn_name: Symbol) -> (Expr, Variable) {
         closure_type: to_elem_encoder_lset,
         return_type: elem_encoder_var,
         name: to_elem_encoder_sym,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             elem_var,
@@ -282,7 +282,7 @@ fn to_encoder_list(env: &mut Env<'_>, fn_name: Symbol) -> (Expr, Variable) {
         closure_type: fn_clos_var,
         return_type: this_encoder_var,
         name: fn_name,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             list_var,
@@ -492,7 +492,7 @@ fn to_encoder_record(
         closure_type: fn_clos_var,
         return_type: this_encoder_var,
         name: fn_name,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             record_var,
@@ -675,7 +675,7 @@ fn to_encoder_tuple(
         closure_type: fn_clos_var,
         return_type: this_encoder_var,
         name: fn_name,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             tuple_var,
@@ -920,7 +920,7 @@ fn to_encoder_tag_union(
         closure_type: fn_clos_var,
         return_type: this_encoder_var,
         name: fn_name,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             tag_union_var,
@@ -1030,7 +1030,7 @@ fn wrap_in_encode_custom(
         closure_type: fn_clos_var,
         return_type: Variable::LIST_U8,
         name: fn_name,
-        captured_symbols: vec![(captured_symbol, captured_var)],
+        captured_symbols: Captures::from([(captured_symbol, captured_var)]),
         recursive: Recursive::NotRecursive,
         arguments: vec![
             (
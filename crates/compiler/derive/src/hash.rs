@@ -3,7 +3,10 @@
 use std::iter::once;
 
 use roc_can::{
-    expr::{AnnotatedMark, ClosureData, Expr, IntValue, Recursive, WhenBranch, WhenBranchPattern},
+    expr::{
+        AnnotatedMark, Captures, ClosureData, Expr, IntValue, Recursive, WhenBranch,
+        WhenBranchPattern,
+    },
     num::{IntBound, IntLitWidth},
     pattern::Pattern,
 };
@@ -544,7 +547,7 @@ fn build_outer_derived_closure(
         closure_type: fn_clos_var,
         return_type: body_var,
         name: fn_name,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![
             (
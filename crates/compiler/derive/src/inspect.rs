@@ -3,7 +3,7 @@
 use std::iter::once;
 
 use roc_can::expr::{
-    AnnotatedMark, ClosureData, Expr, Field, Recursive, WhenBranch, WhenBranchPattern,
+    AnnotatedMark, Captures, ClosureData, Expr, Field, Recursive, WhenBranch, WhenBranchPattern,
 };
 use roc_can::pattern::Pattern;
 use roc_collections::SendMap;
@@ -196,7 +196,7 @@ fn to_inspector_list(env: &mut Env<'_>, fn_name: Symbol) -> (Expr, Variable) {
         closure_type: to_elem_inspector_lset,
         return_type: elem_inspector_var,
         name: to_elem_inspector_sym,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             elem_var,
@@ -295,7 +295,7 @@ fn to_inspector_list(env: &mut Env<'_>, fn_name: Symbol) -> (Expr, Variable) {
         closure_type: fn_clos_var,
         return_type: this_inspector_var,
         name: fn_name,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             list_var,
@@ -506,7 +506,7 @@ fn to_inspector_record(
         closure_type: fn_clos_var,
         return_type: this_inspector_var,
         name: fn_name,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             record_var,
@@ -690,7 +690,7 @@ fn to_inspector_tuple(
         closure_type: fn_clos_var,
         return_type: this_inspector_var,
         name: fn_name,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             tuple_var,
@@ -938,7 +938,7 @@ fn to_inspector_tag_union(
         closure_type: fn_clos_var,
         return_type: this_inspector_var,
         name: fn_name,
-        captured_symbols: vec![],
+        captured_symbols: Captures::new(),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             tag_union_var,
@@ -1037,7 +1037,7 @@ fn wrap_in_inspect_custom(
         closure_type: fn_clos_var,
         return_type: fmt_var,
         name: fn_name,
-        captured_symbols: vec![(captured_symbol, captured_var)],
+        captured_symbols: Captures::from([(captured_symbol, captured_var)]),
         recursive: Recursive::NotRecursive,
         arguments: vec![(
             fmt_var,
@@ -40,7 +40,7 @@ pub fn synth_var(subs: &mut Subs, content: Content) -> Variable {
 /// Map of [`DeriveKey`]s to their derived symbols.
 ///
 /// This represents the [`Derived_synth`][Symbol::DERIVED_SYNTH] module.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct DerivedModule {
     map: MutMap<DeriveKey, (Symbol, Def, SpecializationLambdaSets)>,
     subs: Subs,
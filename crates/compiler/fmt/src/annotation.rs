@@ -193,6 +193,10 @@ impl<'a> Formattable for TypeAnnotation<'a> {
 
         let self_is_multiline = self.is_multiline();
 
+        // Type annotations are always laid out the way they were written
+        let max_width = buf.max_width();
+        buf.set_max_width(None);
+
         match self {
             Function(args, ret) => {
                 let needs_parens = parens != Parens::NotNeeded;
@@ -386,6 +390,8 @@ impl<'a> Formattable for TypeAnnotation<'a> {
                 buf.push_str(raw)
             }
         }
+
+        buf.set_max_width(max_width);
     }
 }
 
@@ -13,6 +13,13 @@ pub enum Braces {
     Curly,
 }
 
+/// Formats a list, tuple, tag union or similar collection.
+///
+/// The collection stays on one line unless it contains a comment, one of its items is multiline
+/// or was written on a line of its own, or it doesn't fit in the maximum width. Otherwise every
+/// item goes on its own line followed by a comma, the last one included, and the closing brace
+/// goes on a line of its own. That way adding or removing an item only changes that item's line.
+/// A comment written after an item stays on the same line as that item.
 pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable>(
     buf: &mut Buf<'buf>,
     indent: u16,
@@ -34,7 +41,11 @@ pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable>(
         Braces::Square => ']',
     };
 
-    if is_collection_multiline(&items) {
+    let is_multiline = is_collection_multiline(&items)
+        || (!items.is_empty()
+            && !buf.fits(|buf| fmt_collection(buf, indent, braces, items, newline)));
+
+    if is_multiline {
         let braces_indent = indent;
         let item_indent = braces_indent + INDENT;
         if newline == Newlines::Yes {
@@ -103,9 +114,11 @@ pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable>(
             buf.newline();
         }
 
+        // A blank line is only kept before the closing brace if it separates the final comments.
         if items
             .final_comments()
             .starts_with(&[CommentOrNewline::Newline, CommentOrNewline::Newline])
+            && items.final_comments().iter().any(|s| !s.is_newline())
         {
             buf.newline();
         }
@@ -1,10 +1,10 @@
 use crate::annotation::{Formattable, Newlines, Parens};
 use crate::pattern::fmt_pattern;
-use crate::spaces::{fmt_default_newline, fmt_spaces, INDENT};
+use crate::spaces::{fmt_spaces, fmt_top_level_spaces, INDENT};
 use crate::Buf;
 use roc_parse::ast::{
-    AbilityMember, Defs, Expr, ExtractSpaces, Pattern, Spaces, StrLiteral, TypeAnnotation, TypeDef,
-    TypeHeader, ValueDef,
+    AbilityMember, CommentOrNewline, Defs, Expr, ExtractSpaces, Pattern, Spaces, StrLiteral,
+    TypeAnnotation, TypeDef, TypeHeader, ValueDef,
 };
 use roc_region::all::Loc;
 
@@ -22,27 +22,7 @@ impl<'a> Formattable for Defs<'a> {
         _newlines: Newlines,
         indent: u16,
     ) {
-        let mut prev_spaces = true;
-
-        for (index, def) in self.defs().enumerate() {
-            let spaces_before = &self.spaces[self.space_before[index].indices()];
-            let spaces_after = &self.spaces[self.space_after[index].indices()];
-
-            if prev_spaces {
-                fmt_spaces(buf, spaces_before.iter(), indent);
-            } else {
-                fmt_default_newline(buf, spaces_before, indent);
-            }
-
-            match def {
-                Ok(type_def) => type_def.format(buf, indent),
-                Err(value_def) => value_def.format(buf, indent),
-            }
-
-            fmt_spaces(buf, spaces_after.iter(), indent);
-
-            prev_spaces = !spaces_after.is_empty();
-        }
+        format_defs(buf, self, indent, false);
     }
 }
 
@@ -296,7 +276,9 @@ fn should_outdent(mut rhs: &TypeAnnotation) -> bool {
                 }
                 rhs = &ann.value;
             }
-            TypeAnnotation::Record { .. } | TypeAnnotation::TagUnion { .. } => return true,
+            TypeAnnotation::Record { .. }
+            | TypeAnnotation::TagUnion { .. }
+            | TypeAnnotation::Tuple { .. } => return true,
             _ => return false,
         }
     }
@@ -317,6 +299,12 @@ fn fmt_expect<'a>(buf: &mut Buf, condition: &'a Loc<Expr<'a>>, is_multiline: boo
     buf.indent(indent);
     buf.push_str("expect");
 
+    let is_multiline = is_multiline
+        || !buf.fits(|buf| {
+            buf.spaces(1);
+            condition.format(buf, indent);
+        });
+
     let return_indent = if is_multiline {
         buf.newline();
         indent + INDENT
@@ -333,6 +321,12 @@ fn fmt_expect_fx<'a>(buf: &mut Buf, condition: &'a Loc<Expr<'a>>, is_multiline:
     buf.indent(indent);
     buf.push_str("expect-fx");
 
+    let is_multiline = is_multiline
+        || !buf.fits(|buf| {
+            buf.spaces(1);
+            condition.format(buf, indent);
+        });
+
     let return_indent = if is_multiline {
         buf.newline();
         indent + INDENT
@@ -356,18 +350,72 @@ pub fn fmt_defs(buf: &mut Buf, defs: &Defs, indent: u16) {
     defs.format(buf, indent);
 }
 
+/// Formats the defs at the top level of a module. Unlike defs inside a body, these keep the
+/// user's grouping of up to MAX_BLANK_LINES_BETWEEN_TOP_LEVEL_DEFS blank lines between them.
+pub fn fmt_top_level_defs(buf: &mut Buf, defs: &Defs) {
+    format_defs(buf, defs, 0, true);
+}
+
+fn format_defs(buf: &mut Buf, defs: &Defs, indent: u16, is_top_level: bool) {
+    let fmt_spaces_between_defs = |buf: &mut Buf, spaces: &[CommentOrNewline]| {
+        if is_top_level {
+            fmt_top_level_spaces(buf, spaces.iter(), indent);
+        } else {
+            fmt_spaces(buf, spaces.iter(), indent);
+        }
+    };
+
+    let mut prev_spaces = true;
+
+    for (index, def) in defs.defs().enumerate() {
+        let spaces_before = &defs.spaces[defs.space_before[index].indices()];
+        let spaces_after = &defs.spaces[defs.space_after[index].indices()];
+
+        if !prev_spaces && spaces_before.is_empty() {
+            buf.newline();
+        } else {
+            fmt_spaces_between_defs(buf, spaces_before);
+        }
+
+        match def {
+            Ok(type_def) => type_def.format(buf, indent),
+            Err(value_def) => value_def.format(buf, indent),
+        }
+
+        fmt_spaces_between_defs(buf, spaces_after);
+
+        prev_spaces = !spaces_after.is_empty();
+    }
+}
+
 pub fn fmt_body<'a>(buf: &mut Buf, pattern: &'a Pattern<'a>, body: &'a Expr<'a>, indent: u16) {
     pattern.format_with_options(buf, Parens::InApply, Newlines::No, indent);
     buf.indent(indent);
     buf.push_str(" =");
 
-    if body.is_multiline() {
+    let is_multiline = body.is_multiline()
+        || !buf.fits(|buf| {
+            buf.spaces(1);
+            body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+        });
+
+    if is_multiline {
         match body {
             Expr::SpaceBefore(sub_def, spaces) => {
                 let should_outdent = match sub_def {
                     Expr::Record { .. } | Expr::List { .. } => {
                         let is_only_newlines = spaces.iter().all(|s| s.is_newline());
-                        is_only_newlines && sub_def.is_multiline()
+                        is_only_newlines
+                            && (sub_def.is_multiline()
+                                || !buf.fits(|buf| {
+                                    buf.spaces(1);
+                                    sub_def.format_with_options(
+                                        buf,
+                                        Parens::NotNeeded,
+                                        Newlines::Yes,
+                                        indent,
+                                    );
+                                }))
                     }
                     _ => false,
                 };
@@ -404,6 +452,12 @@ pub fn fmt_body<'a>(buf: &mut Buf, pattern: &'a Pattern<'a>, body: &'a Expr<'a>,
                 buf.ensure_ends_with_newline();
                 body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent + INDENT);
             }
+            // Only move an `if` onto its own line when a maximum width was asked for,
+            // so that formatting without one keeps its existing output.
+            Expr::If(..) if buf.max_width().is_some() => {
+                buf.ensure_ends_with_newline();
+                body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent + INDENT);
+            }
             _ => {
                 buf.spaces(1);
                 body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
@@ -0,0 +1,160 @@
+//! Reflowing the markdown in `##` doc comments to fit a maximum width.
+use std::borrow::Cow;
+
+/// Rewraps the paragraphs and list items in a block of doc comment lines (without their `## `)
+/// so that each line is at most `width` characters, where the words allow it.
+///
+/// Anything whose line breaks matter in markdown is kept as it is: fenced code blocks,
+/// indented lines, headings, tables, block quotes, and rules.
+pub fn reflow_doc_lines<'a>(lines: &[&'a str], width: usize) -> Vec<Cow<'a, str>> {
+    let mut reflowed = Vec::new();
+    let mut paragraph: Option<Paragraph> = None;
+    let mut fence: Option<&str> = None;
+
+    for &line in lines {
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+
+            reflowed.push(Cow::Borrowed(line));
+            continue;
+        }
+
+        if let Some(paragraph) = paragraph.as_mut() {
+            if paragraph.continues_with(line) {
+                paragraph.push_words(line);
+                continue;
+            }
+        }
+
+        if let Some(paragraph) = paragraph.take() {
+            paragraph.wrap(width, &mut reflowed);
+        }
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+        }
+
+        paragraph = Paragraph::start(line);
+
+        if paragraph.is_none() {
+            reflowed.push(Cow::Borrowed(line));
+        }
+    }
+
+    if let Some(paragraph) = paragraph {
+        paragraph.wrap(width, &mut reflowed);
+    }
+
+    reflowed
+}
+
+/// The words of a paragraph, or of a list item (whose marker is the `prefix` of its first line).
+struct Paragraph<'a> {
+    prefix: &'a str,
+    words: Vec<&'a str>,
+    /// Set when the last line ended in a `\`, which markdown renders as a line break
+    ends_with_break: bool,
+}
+
+impl<'a> Paragraph<'a> {
+    fn start(line: &'a str) -> Option<Self> {
+        if is_verbatim(line) {
+            return None;
+        }
+
+        let prefix_len = list_marker_len(line).unwrap_or(0);
+        let mut paragraph = Paragraph {
+            prefix: &line[..prefix_len],
+            words: Vec::new(),
+            ends_with_break: false,
+        };
+
+        paragraph.push_words(&line[prefix_len..]);
+
+        Some(paragraph)
+    }
+
+    fn continues_with(&self, line: &str) -> bool {
+        if self.ends_with_break {
+            return false;
+        }
+
+        // The lines after a list item's first one may be indented to line up with its text.
+        let continuation_indent = self.prefix.len();
+        let unindented = match line.get(..continuation_indent) {
+            Some(indent) if indent.trim().is_empty() => &line[continuation_indent..],
+            _ => line,
+        };
+
+        !is_verbatim(unindented) && list_marker_len(unindented).is_none()
+    }
+
+    fn push_words(&mut self, line: &'a str) {
+        self.words.extend(line.split_whitespace());
+        self.ends_with_break = line.ends_with('\\');
+    }
+
+    fn wrap(self, width: usize, reflowed: &mut Vec<Cow<'a, str>>) {
+        let continuation_indent = " ".repeat(self.prefix.len());
+        let mut line = String::from(self.prefix);
+        let mut line_has_words = false;
+
+        for word in self.words {
+            let too_wide = line.chars().count() + 1 + word.chars().count() > width;
+
+            if line_has_words && too_wide && can_start_line(word) {
+                reflowed.push(Cow::Owned(line));
+                line = continuation_indent.clone();
+                line_has_words = false;
+            }
+
+            if line_has_words {
+                line.push(' ');
+            }
+
+            line.push_str(word);
+            line_has_words = true;
+        }
+
+        reflowed.push(Cow::Owned(line));
+    }
+}
+
+/// Whether a line has to be kept as it is, rather than being part of a paragraph.
+fn is_verbatim(line: &str) -> bool {
+    let trimmed = line.trim();
+
+    trimmed.is_empty()
+        || line.starts_with(char::is_whitespace)
+        || trimmed.starts_with("```")
+        || trimmed.starts_with("~~~")
+        || trimmed.starts_with(['#', '|', '>', '<'])
+        // A rule, or the underline of a heading
+        || trimmed.chars().all(|c| matches!(c, '-' | '=' | '*' | '_' | ' '))
+}
+
+/// Whether a wrapped line can start with this word, without it turning into a list item,
+/// heading, or anything else which isn't part of the paragraph.
+fn can_start_line(word: &str) -> bool {
+    !is_verbatim(word) && list_marker_len(&format!("{word} ")).is_none()
+}
+
+/// The length of the `- `, `* `, `+ `, `1. ` or `1) ` which starts a list item, if any.
+fn list_marker_len(line: &str) -> Option<usize> {
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    let marker_len = match line.as_bytes().get(digits) {
+        Some(b'-' | b'*' | b'+') if digits == 0 => 1,
+        Some(b'.' | b')') if digits > 0 => digits + 1,
+        _ => return None,
+    };
+
+    if line[marker_len..].starts_with(' ') {
+        Some(marker_len + 1)
+    } else {
+        None
+    }
+}
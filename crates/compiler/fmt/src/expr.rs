@@ -126,14 +126,22 @@ impl<'a> Formattable for Expr<'a> {
                 format_spaces(buf, spaces, newlines, indent);
             }
             ParensAround(sub_expr) => {
-                if parens == Parens::NotNeeded && !sub_expr_requests_parens(sub_expr) {
+                if parens == Parens::NotNeeded
+                    && !sub_expr_requests_parens(sub_expr)
+                    && !starts_with_comment(sub_expr)
+                {
                     sub_expr.format_with_options(buf, Parens::NotNeeded, newlines, indent);
                 } else {
                     let should_add_newlines = match sub_expr {
                         Expr::Closure(..)
                         | Expr::SpaceBefore(..)
                         | Expr::SpaceAfter(Closure(..), ..) => false,
-                        _ => sub_expr.is_multiline(),
+                        _ => {
+                            sub_expr.is_multiline()
+                                || !buf.fits(|buf| {
+                                    self.format_with_options(buf, parens, newlines, indent)
+                                })
+                        }
                     };
 
                     buf.indent(indent);
@@ -202,6 +210,29 @@ impl<'a> Formattable for Expr<'a> {
                 // assert!(loc_expr.extract_spaces().before.is_empty(), "{:#?}", self);
 
                 buf.indent(indent);
+
+                // Written on one line, but it doesn't fit in the maximum width
+                let is_too_wide = !self.is_multiline()
+                    && !buf.fits(|buf| self.format_with_options(buf, parens, newlines, indent));
+
+                // Whether everything before the last argument's opening brace fits on this line
+                let prefix_fits = buf.fits(|buf| {
+                    if apply_needs_parens {
+                        buf.push('(');
+                    }
+
+                    loc_expr.format_with_options(buf, Parens::InApply, Newlines::Yes, indent);
+
+                    for loc_arg in except_last(loc_args) {
+                        buf.spaces(1);
+                        loc_arg.format_with_options(buf, Parens::InApply, Newlines::Yes, indent);
+                    }
+
+                    buf.indent(indent);
+                    buf.spaces(1);
+                    buf.push('{');
+                });
+
                 if apply_needs_parens && !loc_args.is_empty() {
                     buf.push('(');
                 }
@@ -224,25 +255,37 @@ impl<'a> Formattable for Expr<'a> {
                 //   2,
                 // ]
                 // ```
-                let should_reflow_outdentable = loc_expr.extract_spaces().after.is_empty()
+                //
+                // If it's too wide, prefer this over putting each argument on its own line,
+                // as long as everything before the last argument fits.
+                let should_reflow_outdentable = prefix_fits
+                    && loc_expr.extract_spaces().after.is_empty()
                     && except_last(loc_args).all(|a| !a.is_multiline())
                     && loc_args
                         .last()
                         .map(|a| {
-                            a.extract_spaces().item.is_multiline()
-                                && matches!(
-                                    a.value.extract_spaces().item,
-                                    Expr::Tuple(_)
-                                        | Expr::List(_)
-                                        | Expr::Record(_)
-                                        | Expr::RecordBuilder(_)
-                                )
-                                && a.extract_spaces().before == [CommentOrNewline::Newline]
+                            matches!(
+                                a.value.extract_spaces().item,
+                                Expr::Tuple(_)
+                                    | Expr::List(_)
+                                    | Expr::Record(_)
+                                    | Expr::RecordBuilder(_)
+                            ) && (is_too_wide
+                                || (a.extract_spaces().item.is_multiline()
+                                    && a.extract_spaces().before == [CommentOrNewline::Newline]))
                         })
                         .unwrap_or_default();
 
+                // Like a collection, a closure as the last argument can start on this line
+                let closure_fits_on_line = prefix_fits
+                    && loc_args
+                        .last()
+                        .is_some_and(|a| matches!(a.value, Expr::Closure(..)));
+
                 let needs_indent = !should_reflow_outdentable
-                    && (!loc_expr.extract_spaces().after.is_empty()
+                    && (!prefix_fits
+                        || (is_too_wide && !closure_fits_on_line)
+                        || !loc_expr.extract_spaces().after.is_empty()
                         || except_last(loc_args).any(|a| a.is_multiline())
                         || loc_args
                             .last()
@@ -443,7 +486,10 @@ impl<'a> Formattable for Expr<'a> {
                 "LowLevelDbg should only exist after desugaring, not during formatting"
             ),
             If(branches, final_else) => {
-                fmt_if(buf, branches, final_else, self.is_multiline(), indent);
+                let is_multiline = self.is_multiline()
+                    || !buf.fits(|buf| fmt_if(buf, branches, final_else, false, indent));
+
+                fmt_if(buf, branches, final_else, is_multiline, indent);
             }
             When(loc_condition, branches) => fmt_when(buf, loc_condition, branches, indent),
             Tuple(items) => fmt_collection(buf, indent, Braces::Round, *items, Newlines::No),
@@ -587,7 +633,11 @@ fn starts_with_newline(expr: &Expr) -> bool {
 
     match expr {
         SpaceBefore(_, comment_or_newline) => {
-            matches!(comment_or_newline.first(), Some(CommentOrNewline::Newline))
+            // A line comment always ends its line, e.g. `( # comment`
+            matches!(
+                comment_or_newline.first(),
+                Some(CommentOrNewline::Newline | CommentOrNewline::LineComment(_))
+            )
         }
         _ => false,
     }
@@ -619,6 +669,9 @@ fn format_str_segment(seg: &StrSegment, buf: &mut Buf, indent: u16) {
         }
         DeprecatedInterpolated(loc_expr) | Interpolated(loc_expr) => {
             buf.push_str("$(");
+            // Interpolations can never have newlines, so they can't be broken up to fit
+            let max_width = buf.max_width();
+            buf.set_max_width(None);
             // e.g. (name) in "Hi, $(name)!"
             loc_expr.value.format_with_options(
                 buf,
@@ -626,6 +679,7 @@ fn format_str_segment(seg: &StrSegment, buf: &mut Buf, indent: u16) {
                 Newlines::No,      // Interpolations can never have newlines
                 indent,
             );
+            buf.set_max_width(max_width);
             buf.push(')');
         }
     }
@@ -724,7 +778,8 @@ fn fmt_binops<'a>(
 ) {
     let is_multiline = part_of_multi_line_binops
         || loc_right_side.value.is_multiline()
-        || lefts.iter().any(|(expr, _)| expr.value.is_multiline());
+        || lefts.iter().any(|(expr, _)| expr.value.is_multiline())
+        || !buf.fits(|buf| fmt_binops(buf, lefts, loc_right_side, false, indent));
 
     for (loc_left_side, loc_binop) in lefts {
         let binop = loc_binop.value;
@@ -781,14 +836,20 @@ fn fmt_when<'a>(
     branches: &[&'a WhenBranch<'a>],
     indent: u16,
 ) {
-    let is_multiline_condition = loc_condition.is_multiline();
     buf.ensure_ends_with_newline();
     buf.indent(indent);
     buf.push_str("when");
+    let is_multiline_condition = loc_condition.is_multiline()
+        || !buf.fits(|buf| {
+            buf.spaces(1);
+            loc_condition.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+            buf.spaces(1);
+            buf.push_str("is");
+        });
     if is_multiline_condition {
         let condition_indent = indent + INDENT;
 
-        match &loc_condition.value {
+        let condition = match &loc_condition.value {
             Expr::SpaceBefore(expr_below, spaces_above_expr) => {
                 fmt_comments_only(
                     buf,
@@ -796,38 +857,33 @@ fn fmt_when<'a>(
                     NewlineAt::Top,
                     condition_indent,
                 );
-                buf.newline();
-                match &expr_below {
-                    Expr::SpaceAfter(expr_above, spaces_below_expr) => {
-                        // If any of the spaces is a newline, add a newline at the top.
-                        // Otherwise leave it as just a comment.
-                        let newline_at = if spaces_below_expr
-                            .iter()
-                            .any(|spaces| matches!(spaces, CommentOrNewline::Newline))
-                        {
-                            NewlineAt::Top
-                        } else {
-                            NewlineAt::None
-                        };
+                *expr_below
+            }
+            other => other,
+        };
 
-                        expr_above.format(buf, condition_indent);
-                        fmt_comments_only(
-                            buf,
-                            spaces_below_expr.iter(),
-                            newline_at,
-                            condition_indent,
-                        );
-                        buf.newline();
-                    }
-                    _ => {
-                        expr_below.format(buf, condition_indent);
-                    }
-                }
+        buf.newline();
+
+        match condition {
+            Expr::SpaceAfter(expr_above, spaces_below_expr) => {
+                // If any of the spaces is a newline, add a newline at the top.
+                // Otherwise leave it as just a comment.
+                let newline_at = if spaces_below_expr
+                    .iter()
+                    .any(|spaces| matches!(spaces, CommentOrNewline::Newline))
+                {
+                    NewlineAt::Top
+                } else {
+                    NewlineAt::None
+                };
+
+                expr_above.format(buf, condition_indent);
+                fmt_comments_only(buf, spaces_below_expr.iter(), newline_at, condition_indent);
+                buf.newline();
             }
             _ => {
-                buf.newline();
-                loc_condition.format(buf, condition_indent);
-                buf.newline();
+                condition.format(buf, condition_indent);
+                buf.ensure_ends_with_newline();
             }
         }
         buf.indent(indent);
@@ -844,7 +900,6 @@ fn fmt_when<'a>(
     for (branch_index, branch) in branches.iter().enumerate() {
         let expr = &branch.value;
         let patterns = &branch.patterns;
-        let is_multiline_expr = expr.is_multiline();
         let is_multiline_patterns = is_when_patterns_multiline(branch);
 
         for (pattern_index, pattern) in patterns.iter().enumerate() {
@@ -918,7 +973,24 @@ fn fmt_when<'a>(
             guard_expr.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent + INDENT);
         }
 
-        buf.push_str(" ->");
+        if buf.ends_with_newline() {
+            // The guard ended in a comment, e.g. `Ok x if x > 0 # positive`
+            buf.indent(indent + INDENT);
+            buf.push_str("->");
+        } else {
+            buf.push_str(" ->");
+        }
+
+        let is_multiline_expr = expr.is_multiline()
+            || !buf.fits(|buf| {
+                buf.spaces(1);
+                expr.format_with_options(
+                    buf,
+                    Parens::NotNeeded,
+                    Newlines::Yes,
+                    indent + 2 * INDENT,
+                );
+            });
 
         match expr.value {
             Expr::SpaceBefore(nested, spaces) => {
@@ -1024,8 +1096,6 @@ fn fmt_if<'a>(
     };
 
     for (i, (loc_condition, loc_then)) in branches.iter().enumerate() {
-        let is_multiline_condition = loc_condition.is_multiline();
-
         buf.indent(indent);
 
         if i > 0 {
@@ -1035,6 +1105,14 @@ fn fmt_if<'a>(
 
         buf.push_str("if");
 
+        let is_multiline_condition = loc_condition.is_multiline()
+            || !buf.fits(|buf| {
+                buf.spaces(1);
+                loc_condition.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+                buf.spaces(1);
+                buf.push_str("then");
+            });
+
         if is_multiline_condition {
             match &loc_condition.value {
                 Expr::SpaceBefore(expr_below, spaces_before_expr) => {
@@ -1194,7 +1272,7 @@ fn fmt_closure<'a>(
     }
 
     if arguments_are_multiline {
-        buf.newline();
+        buf.ensure_ends_with_newline();
         buf.indent(indent);
     } else {
         buf.spaces(1);
@@ -1202,7 +1280,12 @@ fn fmt_closure<'a>(
 
     buf.push_str("->");
 
-    let is_multiline = loc_ret.value.is_multiline();
+    let is_too_wide = !loc_ret.value.is_multiline()
+        && !buf.fits(|buf| {
+            buf.spaces(1);
+            loc_ret.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+        });
+    let is_multiline = loc_ret.value.is_multiline() || is_too_wide;
 
     // If the body is multiline, go down a line and indent.
     let body_indent = if is_multiline {
@@ -1219,6 +1302,13 @@ fn fmt_closure<'a>(
             // the body starts with (first comment and then) a newline
             // do nothing
         }
+        Record { .. } | List { .. } => {
+            buf.spaces(1);
+        }
+        _ if is_too_wide => {
+            // the body doesn't fit after the `->`, so it goes on the next line
+            buf.newline();
+        }
         _ => {
             // add a space after the `->`
             buf.spaces(1);
@@ -1231,7 +1321,17 @@ fn fmt_closure<'a>(
                 let should_outdent = match sub_expr {
                     Record { .. } | List { .. } => {
                         let is_only_newlines = spaces.iter().all(|s| s.is_newline());
-                        is_only_newlines && sub_expr.is_multiline()
+                        is_only_newlines
+                            && (sub_expr.is_multiline()
+                                || !buf.fits(|buf| {
+                                    buf.spaces(1);
+                                    sub_expr.format_with_options(
+                                        buf,
+                                        Parens::NotNeeded,
+                                        Newlines::Yes,
+                                        indent,
+                                    );
+                                }))
                     }
                     _ => false,
                 };
@@ -1298,7 +1398,7 @@ fn fmt_backpassing<'a>(
     }
 
     if arguments_are_multiline {
-        buf.newline();
+        buf.ensure_ends_with_newline();
         buf.indent(indent);
     } else {
         buf.spaces(1);
@@ -1343,6 +1443,8 @@ fn pattern_needs_parens_when_backpassing(pat: &Pattern) -> bool {
     }
 }
 
+/// Formats a record, record update or record builder, laid out the same way as the collections
+/// in [fmt_collection].
 fn fmt_record_like<'a, Field, Format, ToSpaceBefore>(
     buf: &mut Buf,
     update: Option<&'a Loc<Expr<'a>>>,
@@ -1352,12 +1454,22 @@ fn fmt_record_like<'a, Field, Format, ToSpaceBefore>(
     to_space_before: ToSpaceBefore,
 ) where
     Field: Formattable,
-    Format: Fn(&mut Buf, &Field, u16, &str),
-    ToSpaceBefore: Fn(&'a Field) -> Option<(&'a Field, &'a [CommentOrNewline<'a>])>,
+    Format: Fn(&mut Buf, &Field, u16, &str) + Copy,
+    ToSpaceBefore: Fn(&'a Field) -> Option<(&'a Field, &'a [CommentOrNewline<'a>])> + Copy,
 {
     let loc_fields = fields.items;
     let final_comments = fields.final_comments();
     buf.indent(indent);
+    let fits = buf.fits(|buf| {
+        fmt_record_like(
+            buf,
+            update,
+            fields,
+            indent,
+            format_field_multiline,
+            to_space_before,
+        )
+    });
     if loc_fields.is_empty() && final_comments.iter().all(|c| c.is_newline()) && update.is_none() {
         buf.push_str("{}");
     } else {
@@ -1377,7 +1489,8 @@ fn fmt_record_like<'a, Field, Format, ToSpaceBefore>(
         }
 
         let is_multiline = loc_fields.iter().any(|loc_field| loc_field.is_multiline())
-            || !final_comments.is_empty();
+            || !final_comments.is_empty()
+            || !fits;
 
         if is_multiline {
             let field_indent = indent + INDENT;
@@ -1391,6 +1504,11 @@ fn fmt_record_like<'a, Field, Format, ToSpaceBefore>(
 
                 let is_first_item = index == 0;
                 if let Some((_sub_field, spaces)) = to_space_before(&field.value) {
+                    let spaces = if is_first_item {
+                        spaces
+                    } else {
+                        fmt_same_line_comment(buf, spaces, field_indent)
+                    };
                     let is_only_newlines = spaces.iter().all(|s| s.is_newline());
                     if !is_first_item
                         && !is_only_newlines
@@ -1409,6 +1527,12 @@ fn fmt_record_like<'a, Field, Format, ToSpaceBefore>(
                 format_field_multiline(buf, &field.value, field_indent, "");
             }
 
+            let final_comments = if loc_fields.is_empty() {
+                final_comments
+            } else {
+                fmt_same_line_comment(buf, final_comments, field_indent)
+            };
+
             if count_leading_newlines(final_comments.iter()) > 1 {
                 buf.newline();
             }
@@ -1441,6 +1565,22 @@ fn fmt_record_like<'a, Field, Format, ToSpaceBefore>(
     }
 }
 
+/// Formats a comment at the start of `spaces`, which was on the same line as the previous field,
+/// so it stays on that line after the field's comma. Returns the spaces which are left.
+fn fmt_same_line_comment<'a>(
+    buf: &mut Buf,
+    spaces: &'a [CommentOrNewline<'a>],
+    indent: u16,
+) -> &'a [CommentOrNewline<'a>] {
+    match spaces.split_first() {
+        Some((comment @ CommentOrNewline::LineComment(_), rest)) => {
+            fmt_comments_only(buf, std::iter::once(comment), NewlineAt::None, indent);
+            rest
+        }
+        _ => spaces,
+    }
+}
+
 fn format_assigned_field_multiline<T>(
     buf: &mut Buf,
     field: &AssignedField<T>,
@@ -1507,11 +1647,11 @@ fn format_assigned_field_multiline<T>(
             // ```
             // we'd like to transform it into:
             // ```
-            // field,
-            // # comment
+            // field, # comment
             // otherfield
             // ```
             format_assigned_field_multiline(buf, sub_field, indent, separator_prefix);
+            let spaces = fmt_same_line_comment(buf, spaces, indent);
             fmt_comments_only(buf, spaces.iter(), NewlineAt::Top, indent);
         }
         Malformed(raw) => {
@@ -1614,11 +1754,11 @@ fn format_record_builder_field_multiline(
             // ```
             // we'd like to transform it into:
             // ```
-            // field,
-            // # comment
+            // field, # comment
             // otherfield
             // ```
             format_record_builder_field_multiline(buf, sub_field, indent, separator_prefix);
+            let spaces = fmt_same_line_comment(buf, spaces, indent);
             fmt_comments_only(buf, spaces.iter(), NewlineAt::Top, indent);
         }
         Malformed(raw) => {
@@ -1670,3 +1810,12 @@ fn sub_expr_requests_parens(expr: &Expr<'_>) -> bool {
         _ => false,
     }
 }
+
+/// A comment right after an opening paren, e.g. `( # comment`, would end up
+/// in the wrong place if we removed the parens.
+fn starts_with_comment(expr: &Expr<'_>) -> bool {
+    match expr {
+        Expr::SpaceBefore(_, spaces) => spaces.iter().any(|space| space.is_comment()),
+        _ => false,
+    }
+}
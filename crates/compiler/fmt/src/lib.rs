@@ -5,14 +5,18 @@
 pub mod annotation;
 pub mod collection;
 pub mod def;
+mod docs;
 pub mod expr;
 pub mod module;
 pub mod pattern;
+pub mod range;
 pub mod spaces;
 
 use bumpalo::{collections::String, Bump};
 use roc_parse::ast::Module;
 
+pub use range::{format_range, Edit};
+
 #[derive(Debug)]
 pub struct Ast<'a> {
     pub module: Module<'a>,
@@ -25,6 +29,8 @@ pub struct Buf<'a> {
     spaces_to_flush: usize,
     newlines_to_flush: usize,
     beginning_of_line: bool,
+    max_width: Option<usize>,
+    sort_headers: bool,
 }
 
 impl<'a> Buf<'a> {
@@ -34,9 +40,72 @@ impl<'a> Buf<'a> {
             spaces_to_flush: 0,
             newlines_to_flush: 0,
             beginning_of_line: true,
+            max_width: None,
+            sort_headers: false,
         }
     }
 
+    /// Sets the maximum line width. Expressions which would go past it are split over multiple
+    /// lines, even if they were written on one, and the paragraphs in doc comments are reflowed
+    /// to fit. Type annotations keep the layout they were written with. Without a maximum width,
+    /// only the newlines in the source decide that.
+    pub fn set_max_width(&mut self, max_width: Option<usize>) {
+        self.max_width = max_width;
+    }
+
+    pub fn max_width(&self) -> Option<usize> {
+        self.max_width
+    }
+
+    /// Sets whether the `exposes` and `imports` lists in module headers are sorted alphabetically,
+    /// with duplicates removed. Comments move along with the items they're attached to.
+    pub fn set_sort_headers(&mut self, sort_headers: bool) {
+        self.sort_headers = sort_headers;
+    }
+
+    pub fn sort_headers(&self) -> bool {
+        self.sort_headers
+    }
+
+    pub(crate) fn arena(&self) -> &'a Bump {
+        self.text.bump()
+    }
+
+    /// Whether the output of `format` stays within the maximum width when it's written
+    /// on one line, starting from the current position.
+    ///
+    /// `format` is run against a scratch buffer without a maximum width, so it should format
+    /// the expression the way it would have been formatted before `max_width` was considered.
+    pub fn fits(&self, format: impl FnOnce(&mut Buf)) -> bool {
+        let max_width = match self.max_width {
+            Some(max_width) => max_width,
+            None => return true,
+        };
+
+        let mut scratch = Buf {
+            text: String::new_in(self.arena()),
+            spaces_to_flush: self.spaces_to_flush,
+            newlines_to_flush: 0,
+            beginning_of_line: self.beginning_of_line,
+            max_width: None,
+            sort_headers: self.sort_headers,
+        };
+
+        format(&mut scratch);
+
+        let column = if self.newlines_to_flush > 0 {
+            0
+        } else {
+            let line_start = self.text.rfind('\n').map_or(0, |index| index + 1);
+
+            self.text[line_start..].chars().count()
+        };
+
+        scratch.newlines_to_flush == 0
+            && !scratch.text.contains('\n')
+            && column + scratch.text.chars().count() <= max_width
+    }
+
     pub fn as_str(&'a self) -> &'a str {
         self.text.as_str()
     }
@@ -115,8 +184,16 @@ impl<'a> Buf<'a> {
     }
 
     pub fn newline(&mut self) {
+        self.newline_with_max_blank_lines(1);
+    }
+
+    /// Like `newline`, but lets up to `max_blank_lines` blank lines build up instead of one.
+    pub fn newline_with_max_blank_lines(&mut self, max_blank_lines: usize) {
         self.spaces_to_flush = 0;
-        self.newlines_to_flush = std::cmp::min(self.newlines_to_flush + 1, 2);
+        self.newlines_to_flush = std::cmp::max(
+            self.newlines_to_flush,
+            std::cmp::min(self.newlines_to_flush + 1, max_blank_lines + 1),
+        );
         self.beginning_of_line = true;
     }
 
@@ -151,6 +228,7 @@ impl<'a> Buf<'a> {
     /// Ensures the text ends in a newline with no whitespace preceding it.
     pub fn fmt_end_of_file(&mut self) {
         self.ensure_ends_with_newline();
+        self.newlines_to_flush = std::cmp::min(self.newlines_to_flush, 1);
         self.flush_spaces();
     }
 
@@ -5,7 +5,7 @@ use crate::spaces::RemoveSpaces;
 use crate::spaces::{fmt_comments_only, fmt_default_spaces, fmt_spaces, NewlineAt, INDENT};
 use crate::Buf;
 use bumpalo::Bump;
-use roc_parse::ast::{Collection, Header, Module, Spaced, Spaces};
+use roc_parse::ast::{Collection, CommentOrNewline, ExtractSpaces, Header, Module, Spaced, Spaces};
 use roc_parse::header::{
     AppHeader, ExposedName, ExposesKeyword, GeneratesKeyword, HostedHeader, ImportsEntry,
     ImportsKeyword, InterfaceHeader, Keyword, KeywordItem, ModuleName, PackageEntry, PackageHeader,
@@ -201,7 +201,13 @@ pub fn fmt_hosted_header<'a>(buf: &mut Buf, header: &'a HostedHeader<'a>) {
     fmt_imports(buf, header.imports.item, indent);
     header.generates.format(buf, indent);
     header.generates_with.keyword.format(buf, indent);
-    fmt_exposes(buf, header.generates_with.item, indent);
+    fmt_generates_with(buf, header.generates_with.item, indent);
+
+    for clause in header.more_generates {
+        clause.generates.format(buf, indent);
+        clause.generates_with.keyword.format(buf, indent);
+        fmt_generates_with(buf, clause.generates_with.item, indent);
+    }
 }
 
 pub fn fmt_app_header<'a>(buf: &mut Buf, header: &'a AppHeader<'a>) {
@@ -334,6 +340,8 @@ fn fmt_imports<'a>(
     loc_entries: Collection<'a, Loc<Spaced<'a, ImportsEntry<'a>>>>,
     indent: u16,
 ) {
+    let loc_entries = sort_header_list(buf, loc_entries);
+
     fmt_collection(buf, indent, Braces::Square, loc_entries, Newlines::No)
 }
 
@@ -364,9 +372,120 @@ fn fmt_exposes<N: Formattable + Copy + core::fmt::Debug>(
     loc_entries: Collection<'_, Loc<Spaced<'_, N>>>,
     indent: u16,
 ) {
+    let loc_entries = sort_header_list(buf, loc_entries);
+
     fmt_collection(buf, indent, Braces::Square, loc_entries, Newlines::No)
 }
 
+/// Unlike an `exposes` list, a `generates … with` list is never sorted by
+/// [Buf::set_sort_headers], so it keeps the order it was written in.
+fn fmt_generates_with<'a>(
+    buf: &mut Buf,
+    loc_entries: Collection<'a, Loc<Spaced<'a, ExposedName<'a>>>>,
+    indent: u16,
+) {
+    fmt_collection(buf, indent, Braces::Square, loc_entries, Newlines::No)
+}
+
+/// If [Buf::set_sort_headers] is on, sorts the items in an `exposes` or `imports` list by how
+/// they're formatted, and removes duplicates. A comment on the same line as an item stays with
+/// it, as do the comments on the lines above it.
+fn sort_header_list<'a, T: Formattable + Copy>(
+    buf: &Buf<'a>,
+    collection: Collection<'a, Loc<Spaced<'a, T>>>,
+) -> Collection<'a, Loc<Spaced<'a, T>>> {
+    struct Entry<'a, T> {
+        key: &'a str,
+        item: Loc<T>,
+        before: Vec<CommentOrNewline<'a>>,
+        after: Vec<CommentOrNewline<'a>>,
+    }
+
+    if !buf.sort_headers() {
+        return collection;
+    }
+
+    let arena = buf.arena();
+    let mut entries: Vec<Entry<'a, T>> = Vec::with_capacity(collection.items.len());
+
+    for loc_item in collection.items {
+        let spaces = loc_item.value.extract_spaces();
+        let mut before = spaces.before;
+
+        // A comment before the first newline is on the same line as the previous item
+        if let Some(prev) = entries.last_mut() {
+            let same_line = before.iter().take_while(|s| !s.is_newline()).count();
+
+            prev.after.extend_from_slice(&before[..same_line]);
+            before = &before[same_line..];
+        }
+
+        let mut key_buf = Buf::new_in(arena);
+        key_buf.set_sort_headers(true);
+        spaces.item.format(&mut key_buf, 0);
+
+        entries.push(Entry {
+            key: key_buf.into_bump_str(),
+            item: Loc::at(loc_item.region, spaces.item),
+            before: before.to_vec(),
+            after: spaces
+                .after
+                .iter()
+                .filter(|s| s.is_comment())
+                .copied()
+                .collect(),
+        });
+    }
+
+    let mut final_comments = collection.final_comments();
+
+    if let Some(last) = entries.last_mut() {
+        let same_line = final_comments
+            .iter()
+            .take_while(|s| !s.is_newline())
+            .count();
+
+        last.after.extend_from_slice(&final_comments[..same_line]);
+        final_comments = &final_comments[same_line..];
+    }
+
+    entries.sort_by_key(|entry| entry.key);
+
+    let mut sorted: Vec<Entry<'a, T>> = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        match sorted.last_mut() {
+            Some(prev) if prev.key == entry.key => {
+                // Keep the comments on the duplicate, each on its own line
+                for comment in entry.before.iter().filter(|s| s.is_comment()) {
+                    prev.before.push(*comment);
+                    prev.before.push(CommentOrNewline::Newline);
+                }
+
+                prev.after.extend(entry.after);
+            }
+            _ => sorted.push(entry),
+        }
+    }
+
+    let items = sorted.into_iter().map(|entry| {
+        let mut spaced = Spaced::Item(entry.item.value);
+
+        if !entry.after.is_empty() {
+            spaced = Spaced::SpaceAfter(arena.alloc(spaced), arena.alloc_slice_copy(&entry.after));
+        }
+
+        if !entry.before.is_empty() {
+            spaced =
+                Spaced::SpaceBefore(arena.alloc(spaced), arena.alloc_slice_copy(&entry.before));
+        }
+
+        Loc::at(entry.item.region, spaced)
+    });
+
+    Collection::with_items_and_comments(arena, arena.alloc_slice_fill_iter(items), final_comments)
+}
+
 pub trait FormatName {
     fn format(&self, buf: &mut Buf);
 }
@@ -480,11 +599,13 @@ fn fmt_imports_entry(buf: &mut Buf, entry: &ImportsEntry, indent: u16) {
             if !loc_exposes_entries.is_empty() {
                 buf.push('.');
 
+                let loc_exposes_entries = sort_header_list(buf, *loc_exposes_entries);
+
                 fmt_collection(
                     buf,
                     indent,
                     Braces::Curly,
-                    *loc_exposes_entries,
+                    loc_exposes_entries,
                     Newlines::No,
                 )
             }
@@ -498,7 +619,9 @@ fn fmt_imports_entry(buf: &mut Buf, entry: &ImportsEntry, indent: u16) {
             if !entries.is_empty() {
                 buf.push('.');
 
-                fmt_collection(buf, indent, Braces::Curly, *entries, Newlines::No)
+                let entries = sort_header_list(buf, *entries);
+
+                fmt_collection(buf, indent, Braces::Curly, entries, Newlines::No)
             }
         }
 
@@ -48,7 +48,7 @@ impl<'a> Formattable for Pattern<'a> {
                     pattern
                 );
 
-                spaces.iter().any(|s| s.is_comment())
+                spaces.iter().any(|s| s.is_comment()) || pattern.is_multiline()
             }
 
             Pattern::RecordDestructure(fields) => fields.iter().any(|f| f.is_multiline()),
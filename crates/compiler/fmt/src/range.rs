@@ -0,0 +1,112 @@
+//! Formatting part of a module, for editors which format a selection or format as you type.
+use std::ops::Range;
+
+use bumpalo::Bump;
+use roc_parse::ast::{Defs, TypeDef};
+use roc_parse::module::{self, module_defs};
+use roc_parse::parser::{Parser, SyntaxError};
+use roc_parse::state::State;
+use roc_region::all::Region;
+
+use crate::annotation::Formattable;
+use crate::Buf;
+
+/// A change to the source: the bytes in `range` are replaced with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// Formats the top-level defs which overlap the given byte range of `src`, leaving the rest of
+/// the module alone. An empty range selects the def it's in, if any.
+///
+/// Each def is formatted the same way it would be when formatting the whole module, and the
+/// returned edits only cover the text which actually changed, sorted by position.
+pub fn format_range<'a>(
+    arena: &'a Bump,
+    src: &'a str,
+    range: Range<usize>,
+    max_width: Option<usize>,
+) -> Result<Vec<Edit>, SyntaxError<'a>> {
+    let defs = parse_defs(arena, src)?;
+    let mut edits = Vec::new();
+
+    for (index, def) in defs.defs().enumerate() {
+        let region = match def {
+            // The region of an opaque type stops before its `implements` clause
+            Ok(TypeDef::Opaque {
+                derived: Some(derived),
+                ..
+            }) => Region::span_across(&defs.regions[index], &derived.region),
+            _ => defs.regions[index],
+        };
+        let start = region.start().offset as usize;
+        let end = region.end().offset as usize;
+
+        let overlaps = if range.is_empty() {
+            start <= range.start && range.start <= end
+        } else {
+            start < range.end && range.start < end
+        };
+
+        if !overlaps {
+            continue;
+        }
+
+        let mut buf = Buf::new_in(arena);
+        buf.set_max_width(max_width);
+
+        match def {
+            Ok(type_def) => type_def.format(&mut buf, 0),
+            Err(value_def) => value_def.format(&mut buf, 0),
+        }
+
+        edits.extend(minimal_edit(start, &src[start..end], buf.as_str()));
+    }
+
+    Ok(edits)
+}
+
+fn parse_defs<'a>(arena: &'a Bump, src: &'a str) -> Result<Defs<'a>, SyntaxError<'a>> {
+    let (_, state) = module::parse_header(arena, State::new(src.as_bytes()))
+        .map_err(|e| SyntaxError::Header(e.problem))?;
+
+    let (_, defs, _) = module_defs().parse(arena, state, 0).map_err(|(_, e)| e)?;
+
+    Ok(defs)
+}
+
+/// The edit which turns `old`, found at `offset` in the source, into `new`, leaving out the
+/// start and end they have in common.
+fn minimal_edit(offset: usize, old: &str, new: &str) -> Option<Edit> {
+    if old == new {
+        return None;
+    }
+
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    while !old.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let mut suffix = old.as_bytes()[prefix..]
+        .iter()
+        .rev()
+        .zip(new.as_bytes()[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    while !old.is_char_boundary(old.len() - suffix) {
+        suffix -= 1;
+    }
+
+    Some(Edit {
+        range: offset + prefix..offset + old.len() - suffix,
+        replacement: new[prefix..new.len() - suffix].to_string(),
+    })
+}
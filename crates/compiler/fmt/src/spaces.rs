@@ -9,19 +9,24 @@ use roc_parse::{
         TypeAnnotation, TypeDef, TypeHeader, ValueDef, WhenBranch,
     },
     header::{
-        AppHeader, ExposedName, HostedHeader, ImportsEntry, InterfaceHeader, KeywordItem,
-        ModuleName, PackageEntry, PackageHeader, PackageName, PlatformHeader, PlatformRequires,
-        ProvidesTo, To, TypedIdent,
+        AppHeader, ExposedName, GeneratesClause, HostedHeader, ImportsEntry, InterfaceHeader,
+        KeywordItem, ModuleName, PackageEntry, PackageHeader, PackageName, PlatformHeader,
+        PlatformRequires, ProvidesTo, To, TypedIdent,
     },
     ident::{BadIdent, UppercaseIdent},
 };
 use roc_region::all::{Loc, Position, Region};
+use std::borrow::Cow;
 
+use crate::docs::reflow_doc_lines;
 use crate::{Ast, Buf};
 
 /// The number of spaces to indent.
 pub const INDENT: u16 = 4;
 
+/// The most blank lines kept between top-level defs. Everywhere else, at most one is kept.
+pub const MAX_BLANK_LINES_BETWEEN_TOP_LEVEL_DEFS: usize = 2;
+
 pub fn fmt_default_spaces(buf: &mut Buf, spaces: &[CommentOrNewline], indent: u16) {
     if spaces.is_empty() {
         buf.spaces(1);
@@ -52,6 +57,20 @@ where
     fmt_spaces_max_consecutive_newlines(buf, spaces, 2, indent)
 }
 
+/// Like fmt_spaces, but keeps up to MAX_BLANK_LINES_BETWEEN_TOP_LEVEL_DEFS blank lines,
+/// so a module can be split into sections.
+pub fn fmt_top_level_spaces<'a, 'buf, I>(buf: &mut Buf<'buf>, spaces: I, indent: u16)
+where
+    I: Iterator<Item = &'a CommentOrNewline<'a>>,
+{
+    fmt_spaces_max_consecutive_newlines(
+        buf,
+        spaces,
+        MAX_BLANK_LINES_BETWEEN_TOP_LEVEL_DEFS + 1,
+        indent,
+    )
+}
+
 fn fmt_spaces_max_consecutive_newlines<'a, 'buf, I>(
     buf: &mut Buf<'buf>,
     spaces: I,
@@ -62,15 +81,18 @@ fn fmt_spaces_max_consecutive_newlines<'a, 'buf, I>(
 {
     use self::CommentOrNewline::*;
 
-    // Only ever print two newlines back to back.
+    // Only ever print max_consecutive_newlines newlines back to back.
     // (Two newlines renders as one blank line.)
     let mut consecutive_newlines = 0;
+    let mut spaces = spaces.peekable();
 
-    for space in spaces {
+    while let Some(space) = spaces.next() {
         match space {
             Newline => {
                 if consecutive_newlines < max_consecutive_newlines {
-                    buf.newline();
+                    // A blank line the buffer already ends with is kept, as Buf::newline does.
+                    let max_blank_lines = std::cmp::max(max_consecutive_newlines - 1, 1);
+                    buf.newline_with_max_blank_lines(max_blank_lines);
 
                     // Don't bother incrementing it if we're already over the limit.
                     // There's no upside, and it might eventually overflow.
@@ -85,9 +107,18 @@ fn fmt_spaces_max_consecutive_newlines<'a, 'buf, I>(
                 consecutive_newlines = 1;
             }
             DocComment(docs) => {
-                buf.indent(indent);
-                fmt_docs(buf, docs);
-                buf.newline();
+                let mut lines = vec![*docs];
+
+                while let Some(DocComment(docs)) = spaces.peek() {
+                    lines.push(docs);
+                    spaces.next();
+                }
+
+                for line in doc_lines_to_fit(buf, &lines, indent) {
+                    buf.indent(indent);
+                    fmt_docs(buf, &line);
+                    buf.newline();
+                }
 
                 consecutive_newlines = 1;
             }
@@ -95,6 +126,18 @@ fn fmt_spaces_max_consecutive_newlines<'a, 'buf, I>(
     }
 }
 
+/// Consecutive lines of doc comments, with their paragraphs reflowed to fit the buffer's
+/// maximum width if it has one.
+fn doc_lines_to_fit<'a>(buf: &Buf, lines: &[&'a str], indent: u16) -> std::vec::Vec<Cow<'a, str>> {
+    match buf.max_width() {
+        Some(max_width) => {
+            let width = max_width.saturating_sub(indent as usize + "## ".len());
+            reflow_doc_lines(lines, width)
+        }
+        None => lines.iter().map(|line| Cow::Borrowed(*line)).collect(),
+    }
+}
+
 #[derive(Eq, PartialEq, Debug)]
 pub enum NewlineAt {
     Top,
@@ -119,8 +162,9 @@ pub fn fmt_comments_only<'a, 'buf, I>(
     use NewlineAt::*;
 
     let mut comment_seen = false;
+    let mut spaces = spaces.peekable();
 
-    for space in spaces {
+    while let Some(space) = spaces.next() {
         match space {
             Newline => {}
             LineComment(comment) => {
@@ -132,12 +176,21 @@ pub fn fmt_comments_only<'a, 'buf, I>(
                 comment_seen = true;
             }
             DocComment(docs) => {
-                if comment_seen || new_line_at == Top || new_line_at == Both {
-                    buf.newline();
+                let mut lines = vec![*docs];
+
+                while let Some(DocComment(docs)) = spaces.peek() {
+                    lines.push(docs);
+                    spaces.next();
+                }
+
+                for line in doc_lines_to_fit(buf, &lines, indent) {
+                    if comment_seen || new_line_at == Top || new_line_at == Both {
+                        buf.newline();
+                    }
+                    buf.indent(indent);
+                    fmt_docs(buf, &line);
+                    comment_seen = true;
                 }
-                buf.indent(indent);
-                fmt_docs(buf, docs);
-                comment_seen = true;
             }
         }
     }
@@ -208,9 +261,8 @@ fn fmt_docs(buf: &mut Buf, docs: &str) {
 /// * Removing comments
 /// * Removing parens in Exprs
 ///
-/// Long term, we actuall want this transform to preserve comments (so we can assert they're maintained by formatting)
-/// - but there are currently several bugs where they're _not_ preserved.
-/// TODO: ensure formatting retains comments
+/// Comments are checked separately: the formatting tests assert that the formatted
+/// code has the same comments, in the same order, as the original.
 pub trait RemoveSpaces<'a> {
     fn remove_spaces(&self, arena: &'a Bump) -> Self;
 }
@@ -317,6 +369,7 @@ impl<'a> RemoveSpaces<'a> for Module<'a> {
                 imports: header.imports.remove_spaces(arena),
                 generates: header.generates.remove_spaces(arena),
                 generates_with: header.generates_with.remove_spaces(arena),
+                more_generates: header.more_generates.remove_spaces(arena),
             }),
         };
         Module {
@@ -410,6 +463,15 @@ impl<'a> RemoveSpaces<'a> for PackageEntry<'a> {
     }
 }
 
+impl<'a> RemoveSpaces<'a> for GeneratesClause<'a> {
+    fn remove_spaces(&self, arena: &'a Bump) -> Self {
+        GeneratesClause {
+            generates: self.generates.remove_spaces(arena),
+            generates_with: self.generates_with.remove_spaces(arena),
+        }
+    }
+}
+
 impl<'a> RemoveSpaces<'a> for ImportsEntry<'a> {
     fn remove_spaces(&self, arena: &'a Bump) -> Self {
         match *self {
@@ -835,7 +835,8 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         number_of_bytes: IntValue<'ctx>,
         alignment: u32,
     ) -> PointerValue<'ctx> {
-        let function = self.module.get_function("roc_alloc").unwrap();
+        // goes through the builtins, so that hooks installed with `roc_set_host_abi` are used
+        let function = self.module.get_function(bitcode::UTILS_ALLOC).unwrap();
         let alignment = self.alignment_const(alignment);
         let call = self.builder.new_build_call(
             function,
@@ -853,7 +854,7 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
     }
 
     pub fn call_dealloc(&self, ptr: PointerValue<'ctx>, alignment: u32) -> InstructionValue<'ctx> {
-        let function = self.module.get_function("roc_dealloc").unwrap();
+        let function = self.module.get_function(bitcode::UTILS_DEALLOC).unwrap();
         let alignment = self.alignment_const(alignment);
         let call =
             self.builder
@@ -894,7 +895,7 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         message: BasicValueEnum<'ctx>,
         tag: CrashTag,
     ) {
-        let function = self.module.get_function("roc_panic").unwrap();
+        let function = self.module.get_function(bitcode::UTILS_PANIC).unwrap();
         let tag_id = self.context.i32_type().const_int(tag as u32 as u64, false);
 
         let msg = self.string_to_arg(env, message);
@@ -913,7 +914,7 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         source: BasicValueEnum<'ctx>,
         message: BasicValueEnum<'ctx>,
     ) {
-        let function = self.module.get_function("roc_dbg").unwrap();
+        let function = self.module.get_function(bitcode::UTILS_DBG).unwrap();
 
         let loc = self.string_to_arg(env, location);
         let src = self.string_to_arg(env, source);
@@ -1089,6 +1090,11 @@ pub fn module_from_builtins<'ctx>(
         "__roc_force_longjmp",
         "__roc_force_setjmp",
         "set_shared_buffer",
+        "set_coverage_buffer",
+        "roc_coverage_hit",
+        "set_property_args",
+        "roc_property_args",
+        bitcode::SET_HOST_ABI,
     ];
     for func in module.get_functions() {
         let has_definition = func.count_basic_blocks() > 0;
@@ -6842,7 +6848,17 @@ fn build_foreign_symbol<'a, 'ctx>(
             for symbol in argument_symbols {
                 let (value, layout) = scope.load_symbol_and_layout(symbol);
 
-                cc_argument_types.push(to_cc_type(env, layout_interner, layout));
+                let cc_type = match layout_interner.get_repr(layout) {
+                    // The host calls a closure with its `roc__fx_*_caller`, which takes the
+                    // closure's captures by reference
+                    repr @ LayoutRepr::LambdaSet(_) => {
+                        basic_type_from_layout(env, layout_interner, repr)
+                            .ptr_type(AddressSpace::default())
+                            .into()
+                    }
+                    _ => to_cc_type(env, layout_interner, layout),
+                };
+                cc_argument_types.push(cc_type);
 
                 let basic_type = argument_type_from_layout(
                     env,
@@ -23,19 +23,42 @@ pub fn add_default_roc_externs(env: &Env<'_, '_, '_>) {
 
     match env.mode {
         super::build::LlvmBackendMode::CliTest => {
-            // expose this function
-            if let Some(fn_val) = module.get_function("set_shared_buffer") {
-                fn_val.set_linkage(Linkage::External);
+            // expose these functions
+            for name in [
+                "set_shared_buffer",
+                "set_coverage_buffer",
+                "set_property_args",
+            ] {
+                if let Some(fn_val) = module.get_function(name) {
+                    fn_val.set_linkage(Linkage::External);
+                }
             }
         }
         _ => {
-            // remove this function from the module
-            if let Some(fn_val) = module.get_function("set_shared_buffer") {
-                unsafe { fn_val.delete() };
+            // remove these functions from the module
+            for name in [
+                "set_shared_buffer",
+                "set_coverage_buffer",
+                "roc_coverage_hit",
+                "set_property_args",
+                "roc_property_args",
+            ] {
+                if let Some(fn_val) = module.get_function(name) {
+                    unsafe { fn_val.delete() };
+                }
             }
         }
     }
 
+    // hosts call this to install their own allocator and hooks, see `RocHostAbi` in roc_std
+    if let Some(fn_val) = module.get_function(bitcode::SET_HOST_ABI) {
+        if env.mode.has_host() {
+            fn_val.set_linkage(Linkage::External);
+        } else {
+            unsafe { fn_val.delete() };
+        }
+    }
+
     if !env.mode.has_host() {
         // roc_alloc
         {
@@ -1041,7 +1041,9 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         // load the pointer
         self.storage.load_symbols(&mut self.code_builder, &[msg]);
         self.code_builder.i32_const(tag as _);
-        self.call_host_fn_after_loading_args("roc_panic");
+
+        // goes through the builtins, so that hooks installed with `roc_set_host_abi` are used
+        self.call_host_fn_after_loading_args(bitcode::UTILS_PANIC);
 
         self.code_builder.unreachable_();
     }
@@ -1991,8 +1993,9 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         // Provide a constant for the alignment argument
         self.code_builder.i32_const(alignment_bytes as i32);
 
-        // Call the foreign function. (Zig and C calling conventions are the same for this signature)
-        self.call_host_fn_after_loading_args("roc_alloc");
+        // Call the Zig builtin, which calls the host's `roc_alloc` unless it installed its own
+        // allocator with `roc_set_host_abi`. (Zig and C calling conventions are the same here)
+        self.call_host_fn_after_loading_args(bitcode::UTILS_ALLOC);
 
         // Save the allocation address to a temporary local variable
         let local_id = self.storage.create_anonymous_local(ValueType::I32);
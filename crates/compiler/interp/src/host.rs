@@ -0,0 +1,280 @@
+use crate::value::Value;
+use roc_builtins::bitcode::IntWidth;
+use roc_collections::MutMap;
+use roc_mono::ir::HostedFunction;
+use roc_mono::layout::{
+    Builtin, InLayout, LayoutInterner, LayoutRepr, STLayoutInterner, UnionLayout,
+};
+use roc_region::all::Region;
+use std::io::{BufRead, Write};
+
+/// What the interpreter asks of the outside world: the platform's hosted functions, and somewhere
+/// to report `dbg`s and failed `expect`s.
+pub trait Host<'a> {
+    /// Called before the program runs, once for each function that it calls on its host, with
+    /// the signature that the platform declares for it. Returns `false` when this host can't
+    /// provide that function.
+    fn declare(
+        &mut self,
+        _function: &HostedFunction<'a>,
+        _layout_interner: &STLayoutInterner<'a>,
+    ) -> bool {
+        true
+    }
+
+    /// Calls the hosted function that the host exports as `roc_fx_{name}`, or the C function
+    /// called `name` that the platform declared directly. The host answers with a value of the
+    /// declared return type, so a function that returns a `Result` answers with an `Err` or an
+    /// `Ok`. `None` means this host doesn't provide it.
+    fn call_foreign(&mut self, name: &str, arguments: &[Value<'a>]) -> Option<Value<'a>>;
+
+    fn dbg(&mut self, location: &str, source: &str, message: &str) {
+        eprintln!("[{location}] {source} = {message}");
+    }
+
+    fn expect_failed(&mut self, region: Region) {
+        eprintln!("An expectation failed at {region:?}");
+    }
+}
+
+/// Stands in for the hosts of the example and test platforms, whose hosted functions do their
+/// I/O through stdin, stdout, and stderr. This is not a general way to run hosts: a hosted
+/// function is only provided when the platform declares it with the name and signature of one of
+/// [StdHost::FUNCTIONS], and any other makes [crate::run_main] refuse to run the program.
+#[derive(Debug, Default)]
+pub struct StdHost {
+    args: Vec<String>,
+    declared: MutMap<String, Declared>,
+}
+
+impl StdHost {
+    /// A host whose `args` function answers with these command line arguments
+    pub fn new(args: Vec<String>) -> Self {
+        Self {
+            args,
+            declared: MutMap::default(),
+        }
+    }
+
+    /// The names (without `roc_fx_`) of the only hosted functions this host provides
+    pub const FUNCTIONS: &'static [&'static str] = &[
+        "putLine",
+        "stdoutLine",
+        "putRaw",
+        "stdoutWrite",
+        "stderrLine",
+        "stderrWrite",
+        "putInt",
+        "getLine",
+        "stdinLine",
+        "getInt",
+        "args",
+    ];
+
+    fn run<'a>(&self, io: Io, arguments: &[Value<'a>]) -> Value<'a> {
+        let text = || match arguments.first() {
+            Some(Value::Str(string)) => string.to_string(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+
+        match io {
+            Io::Write { stderr, newline } => {
+                let text = text();
+
+                if stderr {
+                    eprint!("{text}");
+
+                    if newline {
+                        eprintln!();
+                    }
+                } else {
+                    print!("{text}");
+
+                    if newline {
+                        println!();
+                    }
+
+                    let _ = std::io::stdout().flush();
+                }
+
+                Value::unit()
+            }
+            Io::WriteInt => {
+                println!("{}", text());
+                Value::unit()
+            }
+            Io::ReadLine => Value::str(read_line().trim_end_matches(['\n', '\r'])),
+            Io::ReadInt => {
+                let parsed = read_line().trim().parse::<i64>();
+
+                // `{ value : I64, isError : Bool }`
+                Value::record(vec![
+                    Value::Int(parsed.clone().unwrap_or(0) as i128, IntWidth::I64),
+                    Value::Bool(parsed.is_err()),
+                ])
+            }
+            Io::Args => Value::list(self.args.iter().map(|arg| Value::str(arg)).collect()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Io {
+    Write { stderr: bool, newline: bool },
+    WriteInt,
+    ReadLine,
+    ReadInt,
+    Args,
+}
+
+impl Io {
+    /// What the hosted function of one of [StdHost::FUNCTIONS] does
+    fn from_name(name: &str) -> Option<Self> {
+        let io = match name {
+            "putLine" | "stdoutLine" => Io::STDOUT_LINE,
+            "putRaw" | "stdoutWrite" => Io::STDOUT_RAW,
+            "stderrLine" => Io::STDERR_LINE,
+            "stderrWrite" => Io::STDERR_RAW,
+            "putInt" => Io::WriteInt,
+            "getLine" | "stdinLine" => Io::ReadLine,
+            "getInt" => Io::ReadInt,
+            "args" => Io::Args,
+            _ => return None,
+        };
+
+        Some(io)
+    }
+
+    const STDOUT_LINE: Self = Io::Write {
+        stderr: false,
+        newline: true,
+    };
+    const STDOUT_RAW: Self = Io::Write {
+        stderr: false,
+        newline: false,
+    };
+    const STDERR_LINE: Self = Io::Write {
+        stderr: true,
+        newline: true,
+    };
+    const STDERR_RAW: Self = Io::Write {
+        stderr: true,
+        newline: false,
+    };
+
+    /// The layouts of the arguments and the return value
+    fn signature(self) -> (&'static [Shape], Shape) {
+        match self {
+            Io::Write { .. } => (&[Shape::Str], Shape::Unit),
+            Io::WriteInt => (&[Shape::I64], Shape::Unit),
+            Io::ReadLine => (&[], Shape::Str),
+            Io::ReadInt => (&[], Shape::IntOrError),
+            Io::Args => (&[], Shape::StrList),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Shape {
+    Unit,
+    Str,
+    I64,
+    /// `{ value : I64, isError : Bool }`
+    IntOrError,
+    StrList,
+}
+
+impl Shape {
+    fn matches<'a>(self, layout: InLayout<'a>, interner: &STLayoutInterner<'a>) -> bool {
+        let repr = interner.get_repr(layout);
+
+        match self {
+            Shape::Unit => repr == LayoutRepr::UNIT,
+            Shape::Str => repr == LayoutRepr::STR,
+            Shape::I64 => repr == LayoutRepr::I64,
+            Shape::IntOrError => match repr {
+                LayoutRepr::Struct([value, is_error]) => {
+                    interner.get_repr(*value) == LayoutRepr::I64
+                        && interner.get_repr(*is_error) == LayoutRepr::BOOL
+                }
+                _ => false,
+            },
+            Shape::StrList => match repr {
+                LayoutRepr::Builtin(Builtin::List(element)) => {
+                    interner.get_repr(element) == LayoutRepr::STR
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+/// A hosted function that [StdHost] provides
+#[derive(Clone, Copy, Debug)]
+struct Declared {
+    io: Io,
+    /// The platform declares that it returns a `Result`, which is always `Ok`
+    returns_result: bool,
+}
+
+impl<'a> Host<'a> for StdHost {
+    fn declare(
+        &mut self,
+        function: &HostedFunction<'a>,
+        layout_interner: &STLayoutInterner<'a>,
+    ) -> bool {
+        let name = crate::host_name(function.foreign_symbol.as_str());
+
+        let Some(io) = Io::from_name(name) else {
+            return false;
+        };
+
+        let ret_layout = if function.returns_result {
+            match layout_interner.get_repr(function.ret_layout) {
+                // `[Err e, Ok a]`
+                LayoutRepr::Union(UnionLayout::NonRecursive([_, [ok]])) => *ok,
+                _ => return false,
+            }
+        } else {
+            function.ret_layout
+        };
+
+        let (arguments, result) = io.signature();
+        let matches = arguments.len() == function.arg_layouts.len()
+            && arguments
+                .iter()
+                .zip(function.arg_layouts)
+                .all(|(shape, layout)| shape.matches(*layout, layout_interner))
+            && result.matches(ret_layout, layout_interner);
+
+        if matches {
+            let declared = Declared {
+                io,
+                returns_result: function.returns_result,
+            };
+
+            self.declared.insert(name.to_string(), declared);
+        }
+
+        matches
+    }
+
+    fn call_foreign(&mut self, name: &str, arguments: &[Value<'a>]) -> Option<Value<'a>> {
+        let declared = *self.declared.get(name)?;
+        let value = self.run(declared.io, arguments);
+
+        if declared.returns_result {
+            Some(Value::tag(1, vec![value]))
+        } else {
+            Some(value)
+        }
+    }
+}
+
+fn read_line() -> String {
+    let mut line = String::new();
+    let _ = std::io::stdin().lock().read_line(&mut line);
+
+    line
+}
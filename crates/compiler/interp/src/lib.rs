@@ -0,0 +1,546 @@
+//! Runs Roc programs by interpreting their mono IR, without generating machine code. This gives
+//! `roc run --interp` an execution path that starts instantly and works where LLVM isn't
+//! available.
+//!
+//! The platform's host is never built or loaded. Instead, its hosted functions are answered by a
+//! [Host] written in Rust, which stands in for the platform. [StdHost] only provides a fixed set
+//! of standard I/O functions, so platforms whose hosts do anything else can't be interpreted.
+//! Calling into a platform's compiled host (e.g. through libffi) is out of scope, and so is
+//! running the REPL or `roc test` with the interpreter; both still generate machine code.
+#![warn(clippy::dbg_macro)]
+// See github.com/roc-lang/roc/issues/800 for discussion of the large_enum_variant check.
+#![allow(clippy::large_enum_variant)]
+
+mod host;
+mod low_level;
+mod num;
+mod value;
+
+pub use host::{Host, StdHost};
+pub use value::{Pointer, Value};
+
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_collections::MutMap;
+use roc_load::{EntryPoint, MonomorphizedModule};
+use roc_module::low_level::LowLevelWrapperType;
+use roc_module::symbol::Symbol;
+use roc_mono::coverage::COVERAGE_HIT;
+use roc_mono::ir::{
+    Call, CallType, CrashTag, ErasedField, Expr, JoinPointId, ListLiteralElement, Literal, Param,
+    Proc, ProcLayout, Stmt,
+};
+use roc_mono::layout::{
+    Builtin, InLayout, LambdaName, LayoutInterner, LayoutRepr, STLayoutInterner, UnionLayout,
+};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeError {
+    /// The program crashed, either through `crash` or in a builtin
+    Crashed { message: String, tag: CrashTag },
+    /// A call to a procedure that mono did not specialize
+    MissingProc(Symbol),
+    /// A hosted function that the [Host] does not provide
+    MissingForeign(String),
+    /// The program calls these hosted functions, which the [Host] can't stand in for
+    UnsupportedPlatform { missing: Vec<String> },
+    /// The program does something the interpreter can't do yet
+    Unsupported(String),
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeError::Crashed {
+                message,
+                tag: CrashTag::Roc,
+            } => write!(f, "Roc standard library crashed with message\n\n    {message}\n"),
+            RuntimeError::Crashed {
+                message,
+                tag: CrashTag::User,
+            } => write!(f, "Application crashed with message\n\n    {message}\n"),
+            RuntimeError::MissingProc(symbol) => {
+                write!(f, "There is no specialization of {symbol:?} to call.")
+            }
+            RuntimeError::MissingForeign(name) => write!(
+                f,
+                "The platform's host provides `{name}`, but the interpreter does not know how to run it."
+            ),
+            RuntimeError::UnsupportedPlatform { missing } => {
+                let missing: Vec<_> = missing.iter().map(|name| format!("`{name}`")).collect();
+
+                write!(
+                    f,
+                    "The interpreter does not run the platform's host, and cannot stand in for its {}.",
+                    missing.join(", ")
+                )
+            }
+            RuntimeError::Unsupported(what) => {
+                write!(f, "The interpreter does not support {what} yet.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+pub(crate) fn crash(message: &str) -> RuntimeError {
+    RuntimeError::Crashed {
+        message: message.to_string(),
+        tag: CrashTag::Roc,
+    }
+}
+
+/// Runs the program's entry point the way its host would: evaluates the value the platform
+/// exposes, and if that is an effect, calls the closure inside it. Before that, the host is told
+/// about each function of its that the program calls.
+pub fn run_main<'a, H: Host<'a>>(
+    module: &MonomorphizedModule<'a>,
+    mut host: H,
+) -> Result<Value<'a>, RuntimeError> {
+    let exposed_to_host = match &module.entry_point {
+        EntryPoint::Executable {
+            exposed_to_host, ..
+        } => *exposed_to_host,
+        EntryPoint::Test => {
+            return Err(RuntimeError::Unsupported(
+                "running a module without an entry point".to_string(),
+            ))
+        }
+    };
+
+    let Some((symbol, proc_layout)) = exposed_to_host.first() else {
+        return Err(RuntimeError::Unsupported(
+            "a platform that exposes nothing to its host".to_string(),
+        ));
+    };
+
+    let missing: Vec<_> = module
+        .hosted_functions
+        .iter()
+        .filter(|function| !host.declare(function, &module.layout_interner))
+        .map(|function| function.foreign_symbol.as_str().to_string())
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(RuntimeError::UnsupportedPlatform { missing });
+    }
+
+    let mut interpreter = Interpreter::new(&module.procedures, &module.layout_interner, host);
+
+    let arguments = proc_layout
+        .arguments
+        .iter()
+        .map(|_| Value::unit())
+        .collect();
+    let value = interpreter.call_proc(*symbol, *proc_layout, arguments)?;
+
+    // The host calls effects through the `caller` that is generated for the lambda set
+    let mut callers = module
+        .host_exposed_lambda_sets
+        .iter()
+        .filter(|(exposed, _, _)| exposed.name() == *symbol);
+
+    match (callers.next(), callers.next()) {
+        (Some((_, _, hels)), None) => {
+            let mut arguments: Vec<_> = hels.proc_layout.arguments[1..]
+                .iter()
+                .map(|_| Value::unit())
+                .collect();
+            arguments.push(value);
+
+            interpreter.call_proc(hels.symbol, hels.proc_layout, arguments)
+        }
+        _ => Ok(value),
+    }
+}
+
+/// Evaluates the procedures of a monomorphized program
+pub struct Interpreter<'a, 'r, H> {
+    procedures: &'r MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    layout_interner: &'r STLayoutInterner<'a>,
+    host: H,
+}
+
+/// The local state of a procedure call
+#[derive(Default)]
+struct Frame<'a, 'r> {
+    values: MutMap<Symbol, Value<'a>>,
+    joins: MutMap<JoinPointId, (&'r [Param<'a>], &'r Stmt<'a>)>,
+}
+
+impl<'a, 'r> Frame<'a, 'r> {
+    fn get(&self, symbol: Symbol) -> Result<&Value<'a>, RuntimeError> {
+        self.values.get(&symbol).ok_or_else(|| {
+            RuntimeError::Unsupported(format!("reading {symbol:?} before it is defined"))
+        })
+    }
+
+    fn get_all(&self, symbols: &[Symbol]) -> Result<Vec<Value<'a>>, RuntimeError> {
+        symbols
+            .iter()
+            .map(|symbol| self.get(*symbol).cloned())
+            .collect()
+    }
+}
+
+impl<'a, 'r, H: Host<'a>> Interpreter<'a, 'r, H> {
+    pub fn new(
+        procedures: &'r MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+        layout_interner: &'r STLayoutInterner<'a>,
+        host: H,
+    ) -> Self {
+        Self {
+            procedures,
+            layout_interner,
+            host,
+        }
+    }
+
+    pub fn host(&mut self) -> &mut H {
+        &mut self.host
+    }
+
+    pub fn call_proc(
+        &mut self,
+        name: Symbol,
+        layout: ProcLayout<'a>,
+        arguments: Vec<Value<'a>>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        // Like the backends, run wrappers of lowlevels as the lowlevel itself
+        if let LowLevelWrapperType::CanBeReplacedBy(op) = LowLevelWrapperType::from_symbol(name) {
+            return self.eval_low_level(op, &arguments, layout.result);
+        }
+
+        let proc = self
+            .procedures
+            .get(&(name, layout))
+            .ok_or(RuntimeError::MissingProc(name))?;
+
+        self.eval_proc(proc, arguments)
+    }
+
+    fn call_lambda(
+        &mut self,
+        name: LambdaName<'a>,
+        arg_layouts: &'a [InLayout<'a>],
+        ret_layout: InLayout<'a>,
+        arguments: Vec<Value<'a>>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let layout = ProcLayout {
+            arguments: arg_layouts,
+            result: ret_layout,
+            niche: name.niche(),
+        };
+
+        self.call_proc(name.name(), layout, arguments)
+    }
+
+    fn eval_proc(
+        &mut self,
+        proc: &'r Proc<'a>,
+        arguments: Vec<Value<'a>>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let mut frame = Frame::default();
+
+        for ((_, symbol), value) in proc.args.iter().zip(arguments) {
+            frame.values.insert(*symbol, value);
+        }
+
+        let mut stmt = &proc.body;
+
+        loop {
+            match stmt {
+                Stmt::Let(symbol, expr, layout, rest) => {
+                    let value = self.eval_expr(&frame, expr, *layout)?;
+                    frame.values.insert(*symbol, value);
+
+                    stmt = rest;
+                }
+                Stmt::Switch {
+                    cond_symbol,
+                    branches,
+                    default_branch,
+                    ..
+                } => {
+                    let cond = frame.get(*cond_symbol)?;
+                    let cond = cond
+                        .switch_value()
+                        .ok_or_else(|| RuntimeError::Unsupported(format!("switching on {cond}")))?;
+
+                    stmt = branches
+                        .iter()
+                        .find(|(value, _, _)| *value == cond)
+                        .map_or(default_branch.1, |(_, _, branch)| branch);
+                }
+                Stmt::Ret(symbol) => {
+                    return frame
+                        .values
+                        .remove(symbol)
+                        .ok_or_else(|| RuntimeError::Unsupported(format!("returning {symbol:?}")));
+                }
+                // Values are reference counted by Rust
+                Stmt::Refcounting(_, rest) => stmt = rest,
+                Stmt::Expect {
+                    condition,
+                    region,
+                    remainder,
+                    ..
+                }
+                | Stmt::ExpectFx {
+                    condition,
+                    region,
+                    remainder,
+                    ..
+                } => {
+                    if *frame.get(*condition)? != Value::Bool(true) {
+                        self.host.expect_failed(*region);
+                    }
+
+                    stmt = remainder;
+                }
+                Stmt::Dbg {
+                    source_location,
+                    source,
+                    symbol,
+                    remainder,
+                    ..
+                } => {
+                    // By now, the value has been turned into a string with `Inspect`
+                    let message = match frame.get(*symbol)? {
+                        Value::Str(message) => message.to_string(),
+                        other => other.to_string(),
+                    };
+                    self.host.dbg(source_location, source, &message);
+
+                    stmt = remainder;
+                }
+                Stmt::Join {
+                    id,
+                    parameters,
+                    body,
+                    remainder,
+                } => {
+                    frame.joins.insert(*id, (parameters, body));
+
+                    stmt = remainder;
+                }
+                Stmt::Jump(id, arguments) => {
+                    let (parameters, body) = frame.joins[id];
+                    let values = frame.get_all(arguments)?;
+
+                    for (parameter, value) in parameters.iter().zip(values) {
+                        frame.values.insert(parameter.symbol, value);
+                    }
+
+                    stmt = body;
+                }
+                Stmt::Crash(symbol, tag) => {
+                    let message = match frame.get(*symbol)? {
+                        Value::Str(message) => message.to_string(),
+                        other => other.to_string(),
+                    };
+
+                    return Err(RuntimeError::Crashed { message, tag: *tag });
+                }
+            }
+        }
+    }
+
+    fn eval_expr(
+        &mut self,
+        frame: &Frame<'a, 'r>,
+        expr: &'r Expr<'a>,
+        layout: InLayout<'a>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let value = match expr {
+            Expr::Literal(literal) => self.literal(literal, layout),
+            Expr::Call(call) => self.eval_call(frame, call, layout)?,
+            Expr::Tag {
+                tag_layout,
+                tag_id,
+                arguments,
+                ..
+            } => {
+                if tag_layout.tag_is_null(*tag_id) {
+                    Value::Null
+                } else {
+                    Value::tag(*tag_id, frame.get_all(arguments)?)
+                }
+            }
+            Expr::Struct(fields) => Value::record(frame.get_all(fields)?),
+            Expr::NullPointer => Value::Null,
+            Expr::StructAtIndex {
+                index, structure, ..
+            } => match frame.get(*structure)? {
+                Value::Struct(fields) => fields[*index as usize].clone(),
+                other => return Err(unexpected("a struct", other)),
+            },
+            Expr::GetTagId {
+                structure,
+                union_layout,
+            } => {
+                let id = match frame.get(*structure)? {
+                    Value::Tag { id, .. } => *id,
+                    Value::Null => nullable_id(union_layout),
+                    other => return Err(unexpected("a tag", other)),
+                };
+
+                self.int(id as i128, layout)
+            }
+            Expr::UnionAtIndex {
+                structure, index, ..
+            } => match frame.get(*structure)? {
+                Value::Tag { fields, .. } => fields.borrow()[*index as usize].clone(),
+                other => return Err(unexpected("a tag", other)),
+            },
+            Expr::GetElementPointer {
+                structure, indices, ..
+            } => match frame.get(*structure)? {
+                Value::Tag { fields, .. } => {
+                    Value::Pointer(Pointer::Field(Rc::clone(fields), indices[1] as usize))
+                }
+                other => return Err(unexpected("a tag", other)),
+            },
+            Expr::Array { elem_layout, elems } => {
+                let elements = elems
+                    .iter()
+                    .map(|element| match element {
+                        ListLiteralElement::Literal(literal) => {
+                            Ok(self.literal(literal, *elem_layout))
+                        }
+                        ListLiteralElement::Symbol(symbol) => frame.get(*symbol).cloned(),
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Value::list(elements)
+            }
+            Expr::EmptyArray => Value::list(Vec::new()),
+            Expr::ErasedMake { value, callee } => Value::Erased {
+                value: match value {
+                    Some(value) => Some(Box::new(frame.get(*value)?.clone())),
+                    None => None,
+                },
+                callee: Box::new(frame.get(*callee)?.clone()),
+            },
+            Expr::ErasedLoad { symbol, field } => match frame.get(*symbol)? {
+                Value::Erased { value, callee } => match field {
+                    ErasedField::Value | ErasedField::ValuePtr => {
+                        value.as_deref().cloned().unwrap_or(Value::Null)
+                    }
+                    ErasedField::Callee => (**callee).clone(),
+                },
+                other => return Err(unexpected("an erased value", other)),
+            },
+            Expr::FunctionPointer { lambda_name } => Value::Function(*lambda_name),
+            Expr::Alloca { initializer, .. } => {
+                let initial = match initializer {
+                    Some(symbol) => frame.get(*symbol)?.clone(),
+                    None => Value::Null,
+                };
+
+                Value::Pointer(Pointer::Cell(Rc::new(RefCell::new(initial))))
+            }
+            // Reused allocations are made fresh, so the token is never looked at
+            Expr::Reset { symbol, .. } | Expr::ResetRef { symbol, .. } => {
+                frame.get(*symbol)?.clone()
+            }
+            Expr::RuntimeErrorFunction(message) => return Err(crash(message)),
+        };
+
+        Ok(value)
+    }
+
+    fn eval_call(
+        &mut self,
+        frame: &Frame<'a, 'r>,
+        call: &'r Call<'a>,
+        layout: InLayout<'a>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let arguments = || frame.get_all(call.arguments);
+
+        match &call.call_type {
+            CallType::ByName {
+                name,
+                ret_layout,
+                arg_layouts,
+                ..
+            } => self.call_lambda(*name, arg_layouts, *ret_layout, arguments()?),
+            CallType::ByPointer {
+                pointer,
+                ret_layout,
+                arg_layouts,
+            } => match frame.get(*pointer)? {
+                Value::Function(name) => {
+                    self.call_lambda(*name, arg_layouts, *ret_layout, arguments()?)
+                }
+                other => Err(unexpected("a function pointer", other)),
+            },
+            CallType::Foreign { foreign_symbol, .. } => {
+                let name = foreign_symbol.as_str();
+
+                // Inserted by `roc test --coverage`; nothing to record when interpreting
+                if name == COVERAGE_HIT {
+                    return Ok(Value::unit());
+                }
+
+                match self.host.call_foreign(host_name(name), &arguments()?) {
+                    Some(value) => Ok(value),
+                    None => Err(RuntimeError::MissingForeign(name.to_string())),
+                }
+            }
+            CallType::LowLevel { op, .. } => self.eval_low_level(*op, &arguments()?, layout),
+            CallType::HigherOrder(higher_order) => self.eval_higher_order(frame, higher_order),
+        }
+    }
+
+    pub(crate) fn literal(&self, literal: &Literal<'a>, layout: InLayout<'a>) -> Value<'a> {
+        match literal {
+            Literal::Int(bytes) => self.int(i128::from_ne_bytes(*bytes), layout),
+            Literal::U128(bytes) => self.int(i128::from_ne_bytes(*bytes), layout),
+            Literal::Float(x) => match self.layout_interner.get_repr(layout) {
+                LayoutRepr::Builtin(Builtin::Decimal) => Value::Dec(num::float_to_dec(*x)),
+                LayoutRepr::Builtin(Builtin::Float(width)) => {
+                    Value::Float(num::float_in_width(*x, width), width)
+                }
+                _ => Value::Float(*x, FloatWidth::F64),
+            },
+            Literal::Decimal(bytes) => Value::Dec(i128::from_ne_bytes(*bytes)),
+            Literal::Str(string) => Value::str(string),
+            Literal::Bool(b) => Value::Bool(*b),
+            Literal::Byte(b) => self.int(*b as i128, layout),
+        }
+    }
+
+    /// An integer in the given layout
+    pub(crate) fn int(&self, n: i128, layout: InLayout<'a>) -> Value<'a> {
+        match self.layout_interner.get_repr(layout) {
+            LayoutRepr::Builtin(Builtin::Int(width)) => Value::Int(num::wrap(n, width), width),
+            LayoutRepr::Builtin(Builtin::Bool) => Value::Bool(n != 0),
+            LayoutRepr::Builtin(Builtin::Float(width)) => Value::Float(n as f64, width),
+            LayoutRepr::Builtin(Builtin::Decimal) => Value::Dec(n * num::DEC_ONE),
+            _ => Value::Int(n, IntWidth::I64),
+        }
+    }
+}
+
+/// The name by which a [Host] knows a foreign function: hosted functions without their `roc_fx_`
+pub(crate) fn host_name(foreign_symbol: &str) -> &str {
+    foreign_symbol
+        .strip_prefix("roc_fx_")
+        .unwrap_or(foreign_symbol)
+}
+
+fn nullable_id(union_layout: &UnionLayout) -> u16 {
+    match union_layout {
+        UnionLayout::NullableWrapped { nullable_id, .. } => *nullable_id,
+        UnionLayout::NullableUnwrapped { nullable_id, .. } => *nullable_id as u16,
+        _ => 0,
+    }
+}
+
+pub(crate) fn unexpected(expected: &str, found: &Value) -> RuntimeError {
+    RuntimeError::Unsupported(format!("using {found} as {expected}"))
+}
@@ -0,0 +1,860 @@
+use crate::num;
+use crate::value::Value;
+use crate::{crash, unexpected, Frame, Host, Interpreter, RuntimeError};
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_module::low_level::LowLevel;
+use roc_mono::ir::{HigherOrderLowLevel, PassedFunction, ProcLayout};
+use roc_mono::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr};
+use roc_mono::low_level::HigherOrder;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+/// The arguments of a binary numeric operation, which always have the same type
+enum Operands {
+    Ints(i128, i128, IntWidth),
+    Floats(f64, f64, FloatWidth),
+    Decs(i128, i128),
+}
+
+fn operands(a: &Value, b: &Value) -> Result<Operands, RuntimeError> {
+    match (a, b) {
+        (Value::Int(a, width), Value::Int(b, _)) => Ok(Operands::Ints(*a, *b, *width)),
+        (Value::Float(a, width), Value::Float(b, _)) => Ok(Operands::Floats(*a, *b, *width)),
+        (Value::Dec(a), Value::Dec(b)) => Ok(Operands::Decs(*a, *b)),
+        (a, _) => Err(unexpected("a number", a)),
+    }
+}
+
+fn int(value: &Value) -> Result<(i128, IntWidth), RuntimeError> {
+    match value {
+        Value::Int(n, width) => Ok((*n, *width)),
+        other => Err(unexpected("an integer", other)),
+    }
+}
+
+/// Any number, as a float
+fn float(value: &Value) -> Result<f64, RuntimeError> {
+    match value {
+        Value::Float(x, _) => Ok(*x),
+        Value::Dec(n) => Ok(num::dec_to_float(*n)),
+        Value::Int(n, width) => Ok(num::int_to_float(*n, *width)),
+        other => Err(unexpected("a number", other)),
+    }
+}
+
+fn index(value: &Value) -> Result<usize, RuntimeError> {
+    let (n, width) = int(value)?;
+
+    match width {
+        IntWidth::U128 => Ok(usize::try_from(n as u128).unwrap_or(usize::MAX)),
+        _ => Ok(usize::try_from(n).unwrap_or(usize::MAX)),
+    }
+}
+
+fn string<'v>(value: &'v Value) -> Result<&'v str, RuntimeError> {
+    match value {
+        Value::Str(string) => Ok(string),
+        other => Err(unexpected("a string", other)),
+    }
+}
+
+fn list<'a, 'v>(value: &'v Value<'a>) -> Result<&'v Rc<Vec<Value<'a>>>, RuntimeError> {
+    match value {
+        Value::List(elements) => Ok(elements),
+        other => Err(unexpected("a list", other)),
+    }
+}
+
+fn boolean(value: &Value) -> Result<bool, RuntimeError> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        Value::Int(n, _) => Ok(*n != 0),
+        other => Err(unexpected("a Bool", other)),
+    }
+}
+
+/// Floats overflow to infinity instead of crashing
+fn checked<'a>(
+    (value, overflowed): (Value<'a>, bool),
+    message: &str,
+) -> Result<Value<'a>, RuntimeError> {
+    if overflowed && !matches!(value, Value::Float(..)) {
+        Err(crash(message))
+    } else {
+        Ok(value)
+    }
+}
+
+/// `Num.compare` returns `[EQ, GT, LT]`
+fn order_value<'a>(ordering: Ordering) -> Value<'a> {
+    let id = match ordering {
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+        Ordering::Less => 2,
+    };
+
+    Value::Int(id, IntWidth::U8)
+}
+
+fn ordering(value: &Value) -> Result<Ordering, RuntimeError> {
+    match int(value)?.0 {
+        0 => Ok(Ordering::Equal),
+        1 => Ok(Ordering::Greater),
+        _ => Ok(Ordering::Less),
+    }
+}
+
+/// The ids of `Str.Utf8ByteProblem`'s tags
+fn utf8_problem(bytes: &[u8], index: usize) -> u8 {
+    const CODEPOINT_TOO_LARGE: u8 = 0;
+    const ENCODES_SURROGATE_HALF: u8 = 1;
+    const EXPECTED_CONTINUATION: u8 = 2;
+    const INVALID_START_BYTE: u8 = 3;
+    const OVERLONG_ENCODING: u8 = 4;
+    const UNEXPECTED_END_OF_SEQUENCE: u8 = 5;
+
+    let start = bytes[index];
+
+    let length = match start {
+        0b1100_0000..=0b1101_1111 => 2,
+        0b1110_0000..=0b1110_1111 => 3,
+        0b1111_0000..=0b1111_0111 => 4,
+        _ => return INVALID_START_BYTE,
+    };
+
+    let Some(sequence) = bytes.get(index..index + length) else {
+        return UNEXPECTED_END_OF_SEQUENCE;
+    };
+
+    if sequence[1..]
+        .iter()
+        .any(|byte| byte & 0b1100_0000 != 0b1000_0000)
+    {
+        return EXPECTED_CONTINUATION;
+    }
+
+    let codepoint = sequence[1..]
+        .iter()
+        .fold((start & (0x7F >> length)) as u32, |codepoint, byte| {
+            (codepoint << 6) | (byte & 0b0011_1111) as u32
+        });
+
+    let smallest = [0, 0, 0x80, 0x800, 0x10000][length];
+
+    if codepoint < smallest {
+        OVERLONG_ENCODING
+    } else if (0xD800..=0xDFFF).contains(&codepoint) {
+        ENCODES_SURROGATE_HALF
+    } else {
+        CODEPOINT_TOO_LARGE
+    }
+}
+
+impl<'a, 'r, H: Host<'a>> Interpreter<'a, 'r, H> {
+    fn struct_fields(&self, layout: InLayout<'a>) -> &'a [InLayout<'a>] {
+        match self.layout_interner.get_repr(layout) {
+            LayoutRepr::Struct(fields) => fields,
+            _ => &[],
+        }
+    }
+
+    /// A float or `Dec`, depending on the layout
+    fn fraction(&self, x: f64, layout: InLayout<'a>) -> Value<'a> {
+        match self.layout_interner.get_repr(layout) {
+            LayoutRepr::Builtin(Builtin::Decimal) => Value::Dec(num::float_to_dec(x)),
+            LayoutRepr::Builtin(Builtin::Float(width)) => {
+                Value::Float(num::float_in_width(x, width), width)
+            }
+            _ => Value::Float(x, FloatWidth::F64),
+        }
+    }
+
+    /// Applies a float function to a float or `Dec`
+    fn float_op(
+        &self,
+        value: &Value<'a>,
+        f: impl Fn(f64) -> f64,
+    ) -> Result<Value<'a>, RuntimeError> {
+        match value {
+            Value::Float(x, width) => Ok(Value::Float(num::float_in_width(f(*x), *width), *width)),
+            Value::Dec(n) => Ok(Value::Dec(num::float_to_dec(f(num::dec_to_float(*n))))),
+            other => Err(unexpected("a fraction", other)),
+        }
+    }
+
+    /// Rounds a fraction to the integer layout
+    fn round_op(
+        &self,
+        value: &Value<'a>,
+        layout: InLayout<'a>,
+        f: impl Fn(f64) -> f64,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let x = f(float(value)?);
+
+        match self.layout_interner.get_repr(layout) {
+            LayoutRepr::Builtin(Builtin::Int(width)) => {
+                Ok(Value::Int(num::float_to_int(x, width), width))
+            }
+            _ => Ok(self.fraction(x, layout)),
+        }
+    }
+
+    /// The `{ a : Num a, b : Bool }` that the checked arithmetic lowlevels return
+    fn add_sub_mul(
+        &self,
+        a: &Value<'a>,
+        b: &Value<'a>,
+        int_op: fn(i128, i128, IntWidth) -> Option<i128>,
+        wrapping_op: fn(i128, i128, IntWidth) -> i128,
+        float_op: fn(f64, f64) -> f64,
+        dec_op: fn(i128, i128) -> Option<i128>,
+    ) -> Result<(Value<'a>, bool), RuntimeError> {
+        let result = match operands(a, b)? {
+            Operands::Ints(a, b, width) => match int_op(a, b, width) {
+                Some(n) => (Value::Int(n, width), false),
+                None => (Value::Int(wrapping_op(a, b, width), width), true),
+            },
+            Operands::Floats(a, b, width) => {
+                let x = num::float_in_width(float_op(a, b), width);
+
+                (Value::Float(x, width), x.is_infinite())
+            }
+            Operands::Decs(a, b) => match dec_op(a, b) {
+                Some(n) => (Value::Dec(n), false),
+                None => (Value::Dec(0), true),
+            },
+        };
+
+        Ok(result)
+    }
+
+    fn saturated(
+        &self,
+        a: &Value<'a>,
+        b: &Value<'a>,
+        int_op: fn(i128, i128, IntWidth) -> i128,
+        float_op: fn(f64, f64) -> f64,
+        dec_op: fn(i128, i128) -> Option<i128>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let value = match operands(a, b)? {
+            Operands::Ints(a, b, width) => Value::Int(int_op(a, b, width), width),
+            Operands::Floats(a, b, width) => {
+                Value::Float(num::float_in_width(float_op(a, b), width), width)
+            }
+            Operands::Decs(a, b) => Value::Dec(dec_op(a, b).unwrap_or_else(|| {
+                let positive = (a >= 0) == (b >= 0);
+
+                if positive {
+                    i128::MAX
+                } else {
+                    i128::MIN
+                }
+            })),
+        };
+
+        Ok(value)
+    }
+
+    fn int_op(
+        &self,
+        a: &Value<'a>,
+        b: &Value<'a>,
+        op: fn(i128, i128, IntWidth) -> i128,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let (a, width) = int(a)?;
+        let (b, _) = int(b)?;
+
+        Ok(Value::Int(op(a, b, width), width))
+    }
+
+    fn division(
+        &self,
+        a: &Value<'a>,
+        b: &Value<'a>,
+        op: fn(i128, i128, IntWidth) -> Option<i128>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let (a, width) = int(a)?;
+        let (b, _) = int(b)?;
+
+        match op(a, b, width) {
+            Some(n) => Ok(Value::Int(n, width)),
+            None => Err(crash("Integer division by 0!")),
+        }
+    }
+
+    fn compare(&self, a: &Value<'a>, b: &Value<'a>) -> Result<Option<Ordering>, RuntimeError> {
+        let ordering = match operands(a, b)? {
+            Operands::Ints(a, b, width) => Some(num::compare_ints(a, b, width)),
+            Operands::Floats(a, b, _) => a.partial_cmp(&b),
+            Operands::Decs(a, b) => Some(a.cmp(&b)),
+        };
+
+        Ok(ordering)
+    }
+
+    pub(crate) fn eval_low_level(
+        &mut self,
+        op: LowLevel,
+        args: &[Value<'a>],
+        layout: InLayout<'a>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        use LowLevel::*;
+
+        let value = match op {
+            StrConcat => Value::str(&format!("{}{}", string(&args[0])?, string(&args[1])?)),
+            StrJoinWith => {
+                let strings = list(&args[0])?
+                    .iter()
+                    .map(string)
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Value::str(&strings.join(string(&args[1])?))
+            }
+            StrIsEmpty => Value::Bool(string(&args[0])?.is_empty()),
+            StrStartsWith => Value::Bool(string(&args[0])?.starts_with(string(&args[1])?)),
+            StrEndsWith => Value::Bool(string(&args[0])?.ends_with(string(&args[1])?)),
+            StrSplit => {
+                let (haystack, delimiter) = (string(&args[0])?, string(&args[1])?);
+
+                let pieces = if delimiter.is_empty() {
+                    vec![Value::str(haystack)]
+                } else {
+                    haystack.split(delimiter).map(Value::str).collect()
+                };
+
+                Value::list(pieces)
+            }
+            StrCountUtf8Bytes => self.int(string(&args[0])?.len() as i128, layout),
+            StrFromInt | StrFromFloat | NumToStr => Value::str(&args[0].to_string()),
+            StrFromUtf8 => {
+                let bytes = list(&args[0])?
+                    .iter()
+                    .map(|byte| int(byte).map(|(n, _)| n as u8))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let (index, string, problem) = match std::str::from_utf8(&bytes) {
+                    Ok(string) => (0, string, None),
+                    Err(error) => {
+                        let index = error.valid_up_to();
+
+                        (index, "", Some(utf8_problem(&bytes, index)))
+                    }
+                };
+
+                Value::record(vec![
+                    Value::Int(index as i128, IntWidth::U64),
+                    Value::str(string),
+                    Value::Bool(problem.is_none()),
+                    Value::Int(problem.unwrap_or(0) as i128, IntWidth::U8),
+                ])
+            }
+            StrToUtf8 => Value::list(
+                string(&args[0])?
+                    .bytes()
+                    .map(|byte| Value::Int(byte as i128, IntWidth::U8))
+                    .collect(),
+            ),
+            StrRepeat => Value::str(&string(&args[0])?.repeat(index(&args[1])?)),
+            StrTrim => Value::str(string(&args[0])?.trim()),
+            StrTrimStart => Value::str(string(&args[0])?.trim_start()),
+            StrTrimEnd => Value::str(string(&args[0])?.trim_end()),
+            StrToNum => {
+                let text = string(&args[0])?;
+                let fields = self.struct_fields(layout);
+
+                let parsed = match self.layout_interner.get_repr(fields[0]) {
+                    LayoutRepr::Builtin(Builtin::Int(width)) => {
+                        num::parse_int(text, width).map(|n| Value::Int(n, width))
+                    }
+                    LayoutRepr::Builtin(Builtin::Float(width)) => text
+                        .parse::<f64>()
+                        .ok()
+                        .map(|x| Value::Float(num::float_in_width(x, width), width)),
+                    LayoutRepr::Builtin(Builtin::Decimal) => {
+                        num::dec_from_str(text).map(Value::Dec)
+                    }
+                    _ => return Err(RuntimeError::Unsupported("parsing a non-number".into())),
+                };
+
+                let error_code = parsed.is_none() as i128;
+                let result = parsed.unwrap_or_else(|| self.int(0, fields[0]));
+
+                Value::record(vec![result, Value::Int(error_code, IntWidth::U8)])
+            }
+            StrGetUnsafe => {
+                let byte = string(&args[0])?.as_bytes()[index(&args[1])?];
+
+                Value::Int(byte as i128, IntWidth::U8)
+            }
+            StrSubstringUnsafe => {
+                let bytes = string(&args[0])?.as_bytes();
+                let start = index(&args[1])?.min(bytes.len());
+                let end = start.saturating_add(index(&args[2])?).min(bytes.len());
+
+                Value::str(&String::from_utf8_lossy(&bytes[start..end]))
+            }
+            StrWithCapacity => Value::str(""),
+            StrReserve
+            | StrReleaseExcessCapacity
+            | ListReserve
+            | ListReleaseExcessCapacity
+            | ListClone
+            | PtrCast
+            | PtrClearTagId => args[0].clone(),
+            ListLenUsize | ListLenU64 | ListGetCapacity => {
+                self.int(list(&args[0])?.len() as i128, layout)
+            }
+            ListWithCapacity => Value::list(Vec::new()),
+            ListAppendUnsafe => {
+                let mut elements = list(&args[0])?.clone();
+                Rc::make_mut(&mut elements).push(args[1].clone());
+
+                Value::List(elements)
+            }
+            ListPrepend => {
+                let mut elements = list(&args[0])?.clone();
+                Rc::make_mut(&mut elements).insert(0, args[1].clone());
+
+                Value::List(elements)
+            }
+            ListGetUnsafe => {
+                let elements = list(&args[0])?;
+
+                match elements.get(index(&args[1])?) {
+                    Some(element) => element.clone(),
+                    None => return Err(crash("List index out of bounds")),
+                }
+            }
+            ListReplaceUnsafe => {
+                let mut elements = list(&args[0])?.clone();
+                let i = index(&args[1])?;
+
+                let old = match Rc::make_mut(&mut elements).get_mut(i) {
+                    Some(element) => std::mem::replace(element, args[2].clone()),
+                    None => args[2].clone(),
+                };
+
+                // The record is `{ list, value }`, and is sorted by alignment before name
+                let fields = self.struct_fields(layout);
+                let list_first = matches!(
+                    self.layout_interner.get_repr(fields[0]),
+                    LayoutRepr::Builtin(Builtin::List(_))
+                );
+
+                if list_first {
+                    Value::record(vec![Value::List(elements), old])
+                } else {
+                    Value::record(vec![old, Value::List(elements)])
+                }
+            }
+            ListConcat => {
+                let mut elements = list(&args[0])?.clone();
+                Rc::make_mut(&mut elements).extend(list(&args[1])?.iter().cloned());
+
+                Value::List(elements)
+            }
+            ListSublist => {
+                let elements = list(&args[0])?;
+                let start = index(&args[1])?.min(elements.len());
+                let end = start.saturating_add(index(&args[2])?).min(elements.len());
+
+                Value::list(elements[start..end].to_vec())
+            }
+            ListDropAt => {
+                let mut elements = list(&args[0])?.clone();
+                let i = index(&args[1])?;
+
+                if i < elements.len() {
+                    Rc::make_mut(&mut elements).remove(i);
+                }
+
+                Value::List(elements)
+            }
+            ListSwap => {
+                let mut elements = list(&args[0])?.clone();
+                let (i, j) = (index(&args[1])?, index(&args[2])?);
+
+                if i < elements.len() && j < elements.len() {
+                    Rc::make_mut(&mut elements).swap(i, j);
+                }
+
+                Value::List(elements)
+            }
+            ListIsUnique | RefCountIsUnique => Value::Bool(true),
+            ListMap | ListMap2 | ListMap3 | ListMap4 | ListSortWith => {
+                return Err(RuntimeError::Unsupported(format!(
+                    "{op:?} without a function"
+                )))
+            }
+            NumAdd => checked(
+                self.add_sub_mul(
+                    &args[0],
+                    &args[1],
+                    num::add_checked,
+                    num::add_wrap,
+                    |a, b| a + b,
+                    i128::checked_add,
+                )?,
+                self.overflow_message(&args[0], "addition"),
+            )?,
+            NumSub => checked(
+                self.add_sub_mul(
+                    &args[0],
+                    &args[1],
+                    num::sub_checked,
+                    num::sub_wrap,
+                    |a, b| a - b,
+                    i128::checked_sub,
+                )?,
+                self.overflow_message(&args[0], "subtraction"),
+            )?,
+            NumMul => checked(
+                self.add_sub_mul(
+                    &args[0],
+                    &args[1],
+                    num::mul_checked,
+                    num::mul_wrap,
+                    |a, b| a * b,
+                    num::dec_mul,
+                )?,
+                self.overflow_message(&args[0], "multiplication"),
+            )?,
+            NumAddWrap => self.int_op(&args[0], &args[1], num::add_wrap)?,
+            NumSubWrap => self.int_op(&args[0], &args[1], num::sub_wrap)?,
+            NumMulWrap => self.int_op(&args[0], &args[1], num::mul_wrap)?,
+            NumAddChecked | NumSubChecked | NumMulChecked => {
+                let (value, overflowed) = match op {
+                    NumAddChecked => self.add_sub_mul(
+                        &args[0],
+                        &args[1],
+                        num::add_checked,
+                        num::add_wrap,
+                        |a, b| a + b,
+                        i128::checked_add,
+                    )?,
+                    NumSubChecked => self.add_sub_mul(
+                        &args[0],
+                        &args[1],
+                        num::sub_checked,
+                        num::sub_wrap,
+                        |a, b| a - b,
+                        i128::checked_sub,
+                    )?,
+                    _ => self.add_sub_mul(
+                        &args[0],
+                        &args[1],
+                        num::mul_checked,
+                        num::mul_wrap,
+                        |a, b| a * b,
+                        num::dec_mul,
+                    )?,
+                };
+
+                Value::record(vec![value, Value::Bool(overflowed)])
+            }
+            NumAddSaturated => self.saturated(
+                &args[0],
+                &args[1],
+                num::add_saturated,
+                |a, b| a + b,
+                i128::checked_add,
+            )?,
+            NumSubSaturated => self.saturated(
+                &args[0],
+                &args[1],
+                num::sub_saturated,
+                |a, b| a - b,
+                i128::checked_sub,
+            )?,
+            NumMulSaturated => self.saturated(
+                &args[0],
+                &args[1],
+                num::mul_saturated,
+                |a, b| a * b,
+                num::dec_mul,
+            )?,
+            NumGt | NumGte | NumLt | NumLte => {
+                let ordering = self.compare(&args[0], &args[1])?;
+
+                Value::Bool(match op {
+                    NumGt => ordering == Some(Ordering::Greater),
+                    NumGte => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                    NumLt => ordering == Some(Ordering::Less),
+                    _ => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                })
+            }
+            NumCompare => {
+                let ordering = self.compare(&args[0], &args[1])?;
+
+                order_value(ordering.unwrap_or(Ordering::Equal))
+            }
+            NumDivFrac => match operands(&args[0], &args[1])? {
+                Operands::Floats(a, b, width) => {
+                    Value::Float(num::float_in_width(a / b, width), width)
+                }
+                Operands::Decs(a, b) => match num::dec_div(a, b) {
+                    Some(n) => Value::Dec(n),
+                    None if b == 0 => return Err(crash("Decimal division by 0!")),
+                    None => return Err(crash("Decimal division overflowed!")),
+                },
+                Operands::Ints(..) => return Err(unexpected("a fraction", &args[0])),
+            },
+            NumDivTruncUnchecked => self.division(&args[0], &args[1], num::div_trunc)?,
+            NumDivCeilUnchecked => self.division(&args[0], &args[1], num::div_ceil)?,
+            NumRemUnchecked => self.division(&args[0], &args[1], num::rem)?,
+            NumIsMultipleOf => {
+                let (a, width) = int(&args[0])?;
+                let (b, _) = int(&args[1])?;
+
+                Value::Bool(num::is_multiple_of(a, b, width))
+            }
+            NumAbs | NumNeg => match &args[0] {
+                Value::Int(n, width) => {
+                    let (result, name) = match op {
+                        NumAbs => (num::abs(*n, *width), "absolute"),
+                        _ => (num::neg(*n, *width), "negation"),
+                    };
+
+                    match result {
+                        Some(n) => Value::Int(n, *width),
+                        None => {
+                            return Err(crash(&format!(
+                            "Integer {name} overflowed because its argument is the minimum value"
+                        )))
+                        }
+                    }
+                }
+                Value::Float(x, width) => match op {
+                    NumAbs => Value::Float(x.abs(), *width),
+                    _ => Value::Float(-x, *width),
+                },
+                Value::Dec(n) => {
+                    let result = match op {
+                        NumAbs => n.checked_abs(),
+                        _ => n.checked_neg(),
+                    };
+
+                    match result {
+                        Some(n) => Value::Dec(n),
+                        None => return Err(crash("Decimal negation overflowed!")),
+                    }
+                }
+                other => return Err(unexpected("a number", other)),
+            },
+            NumSin => self.float_op(&args[0], f64::sin)?,
+            NumCos => self.float_op(&args[0], f64::cos)?,
+            NumTan => self.float_op(&args[0], f64::tan)?,
+            NumAtan => self.float_op(&args[0], f64::atan)?,
+            NumAcos => self.float_op(&args[0], f64::acos)?,
+            NumAsin => self.float_op(&args[0], f64::asin)?,
+            NumSqrtUnchecked => self.float_op(&args[0], f64::sqrt)?,
+            NumLogUnchecked => self.float_op(&args[0], f64::ln)?,
+            NumPow => {
+                let exponent = float(&args[1])?;
+
+                self.float_op(&args[0], |x| x.powf(exponent))?
+            }
+            NumRound => self.round_op(&args[0], layout, f64::round)?,
+            NumFloor => self.round_op(&args[0], layout, f64::floor)?,
+            NumCeiling => self.round_op(&args[0], layout, f64::ceil)?,
+            NumToFrac | NumToFloatCast => match (&args[0], self.layout_interner.get_repr(layout)) {
+                (Value::Int(n, width), LayoutRepr::Builtin(Builtin::Decimal)) => {
+                    match num::int_to_dec(*n, *width) {
+                        Some(n) => Value::Dec(n),
+                        None => return Err(crash("Decimal conversion overflowed!")),
+                    }
+                }
+                (Value::Dec(n), LayoutRepr::Builtin(Builtin::Decimal)) => Value::Dec(*n),
+                (value, _) => self.fraction(float(value)?, layout),
+            },
+            NumPowInt => self.int_op(&args[0], &args[1], num::pow_int)?,
+            NumIsNan => Value::Bool(float(&args[0])?.is_nan()),
+            NumIsInfinite => Value::Bool(float(&args[0])?.is_infinite()),
+            NumIsFinite => Value::Bool(float(&args[0])?.is_finite()),
+            NumBitwiseAnd => self.int_op(&args[0], &args[1], |a, b, _| a & b)?,
+            NumBitwiseXor => self.int_op(&args[0], &args[1], |a, b, _| a ^ b)?,
+            NumBitwiseOr => self.int_op(&args[0], &args[1], |a, b, _| a | b)?,
+            NumShiftLeftBy => self.int_op(&args[0], &args[1], num::shift_left)?,
+            NumShiftRightBy => self.int_op(&args[0], &args[1], num::shift_right)?,
+            NumShiftRightZfBy => self.int_op(&args[0], &args[1], num::shift_right_zero_fill)?,
+            NumIntCast => self.int(int(&args[0])?.0, layout),
+            NumToIntChecked => {
+                let (n, from) = int(&args[0])?;
+                let fields = self.struct_fields(layout);
+
+                let to = match self.layout_interner.get_repr(fields[0]) {
+                    LayoutRepr::Builtin(Builtin::Int(width)) => width,
+                    _ => {
+                        return Err(RuntimeError::Unsupported(
+                            "converting to a non-integer".into(),
+                        ))
+                    }
+                };
+
+                // The record is `{ a : converted, b : outOfBounds }`
+                if num::fits(n, from, to) {
+                    Value::record(vec![Value::Int(num::wrap(n, to), to), Value::Bool(false)])
+                } else {
+                    Value::record(vec![Value::Int(0, to), Value::Bool(true)])
+                }
+            }
+            NumToFloatChecked => {
+                let x = float(&args[0])?;
+                let fields = self.struct_fields(layout);
+                let converted = self.fraction(x, fields[0]);
+
+                let out_of_bounds = match converted {
+                    Value::Float(y, _) => y.is_infinite() && x.is_finite(),
+                    _ => false,
+                };
+
+                Value::record(vec![converted, Value::Bool(out_of_bounds)])
+            }
+            NumCountLeadingZeroBits => {
+                let (n, width) = int(&args[0])?;
+
+                self.int(num::count_leading_zero_bits(n, width) as i128, layout)
+            }
+            NumCountTrailingZeroBits => {
+                let (n, width) = int(&args[0])?;
+
+                self.int(num::count_trailing_zero_bits(n, width) as i128, layout)
+            }
+            NumCountOneBits => {
+                let (n, width) = int(&args[0])?;
+
+                self.int(num::count_one_bits(n, width) as i128, layout)
+            }
+            I128OfDec => match &args[0] {
+                Value::Dec(n) => Value::Int(*n, IntWidth::I128),
+                other => return Err(unexpected("a Dec", other)),
+            },
+            Eq => Value::Bool(args[0] == args[1]),
+            NotEq => Value::Bool(args[0] != args[1]),
+            And => Value::Bool(boolean(&args[0])? && boolean(&args[1])?),
+            Or => Value::Bool(boolean(&args[0])? || boolean(&args[1])?),
+            Not => Value::Bool(!boolean(&args[0])?),
+            PtrStore => match &args[0] {
+                Value::Pointer(pointer) => {
+                    pointer.store(args[1].clone());
+
+                    Value::unit()
+                }
+                other => return Err(unexpected("a pointer", other)),
+            },
+            PtrLoad => match &args[0] {
+                Value::Pointer(pointer) => pointer.load(),
+                other => return Err(unexpected("a pointer", other)),
+            },
+            RefCountIncRcPtr | RefCountDecRcPtr | RefCountIncDataPtr | RefCountDecDataPtr => {
+                Value::unit()
+            }
+            // Mono turns these into tags, but the lowlevel can still be called through its wrapper
+            BoxExpr => Value::tag(0, vec![args[0].clone()]),
+            UnboxExpr => match &args[0] {
+                Value::Tag { fields, .. } => fields.borrow()[0].clone(),
+                Value::Pointer(pointer) => pointer.load(),
+                other => return Err(unexpected("a box", other)),
+            },
+            Unreachable => return Err(crash("Reached code that should be unreachable")),
+            // Only used to seed hashers, so any value will do
+            DictPseudoSeed => Value::Int(0x5eed, IntWidth::U64),
+            Hash | SetJmp | LongJmp | SetLongJmpBuffer => {
+                return Err(RuntimeError::Unsupported(format!("the {op:?} lowlevel")))
+            }
+        };
+
+        Ok(value)
+    }
+
+    fn overflow_message(&self, value: &Value<'a>, operation: &str) -> &'static str {
+        match (value, operation) {
+            (Value::Dec(_), "addition") => "Decimal addition overflowed!",
+            (Value::Dec(_), "subtraction") => "Decimal subtraction overflowed!",
+            (Value::Dec(_), _) => "Decimal multiplication overflowed!",
+            (_, "addition") => "Integer addition overflowed!",
+            (_, "subtraction") => "Integer subtraction overflowed!",
+            (_, _) => "Integer multiplication overflowed!",
+        }
+    }
+
+    pub(crate) fn eval_higher_order(
+        &mut self,
+        frame: &Frame<'a, 'r>,
+        higher_order: &HigherOrderLowLevel<'a>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let passed = &higher_order.passed_function;
+        let captures = frame.get(passed.captured_environment).ok().cloned();
+
+        let lists = match higher_order.op {
+            HigherOrder::ListMap { xs } | HigherOrder::ListSortWith { xs } => vec![xs],
+            HigherOrder::ListMap2 { xs, ys } => vec![xs, ys],
+            HigherOrder::ListMap3 { xs, ys, zs } => vec![xs, ys, zs],
+            HigherOrder::ListMap4 { xs, ys, zs, ws } => vec![xs, ys, zs, ws],
+        };
+
+        let lists = frame.get_all(&lists)?;
+        let lists = lists.iter().map(list).collect::<Result<Vec<_>, _>>()?;
+
+        if let HigherOrder::ListSortWith { .. } = higher_order.op {
+            let mut elements = lists[0].to_vec();
+            let mut error = None;
+
+            elements.sort_by(|a, b| {
+                if error.is_some() {
+                    return Ordering::Equal;
+                }
+
+                let compared = self.call_passed(passed, vec![a.clone(), b.clone()], &captures);
+
+                match compared.and_then(|order| ordering(&order)) {
+                    Ok(ordering) => ordering,
+                    Err(e) => {
+                        error = Some(e);
+                        Ordering::Equal
+                    }
+                }
+            });
+
+            return match error {
+                Some(e) => Err(e),
+                None => Ok(Value::list(elements)),
+            };
+        }
+
+        let length = lists.iter().map(|list| list.len()).min().unwrap_or(0);
+        let mut mapped = Vec::with_capacity(length);
+
+        for i in 0..length {
+            let arguments = lists.iter().map(|list| list[i].clone()).collect();
+
+            mapped.push(self.call_passed(passed, arguments, &captures)?);
+        }
+
+        Ok(Value::list(mapped))
+    }
+
+    /// Calls a function passed to a higher-order lowlevel. If it's a closure, it takes its
+    /// captures as an extra argument.
+    fn call_passed(
+        &mut self,
+        passed: &PassedFunction<'a>,
+        mut arguments: Vec<Value<'a>>,
+        captures: &Option<Value<'a>>,
+    ) -> Result<Value<'a>, RuntimeError> {
+        let name = passed.name.name();
+        let layout = ProcLayout {
+            arguments: passed.argument_layouts,
+            result: passed.return_layout,
+            niche: passed.name.niche(),
+        };
+
+        if let Some(proc) = self.procedures.get(&(name, layout)) {
+            if proc.args.len() > arguments.len() {
+                arguments.push(captures.clone().unwrap_or_else(Value::unit));
+            }
+        }
+
+        self.call_proc(name, layout, arguments)
+    }
+}
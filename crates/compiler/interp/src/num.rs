@@ -0,0 +1,436 @@
+//! Arithmetic on the interpreter's representation of numbers: integers of every width are kept
+//! in an `i128`, and `Dec`s as their raw `i128`, which is the number times 10^18.
+// The `I128` case of `with_int_width!` casts from `i128` to itself
+#![allow(clippy::unnecessary_cast)]
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_std::RocDec;
+use std::cmp::Ordering;
+
+/// 10^18, i.e. `1.0` as a `Dec`
+pub(crate) const DEC_ONE: i128 = 1_000_000_000_000_000_000;
+
+/// Runs `$body` with `$t` as the Rust type of the given width
+macro_rules! with_int_width {
+    ($width:expr, $t:ident => $body:expr) => {
+        match $width {
+            IntWidth::U8 => {
+                type $t = u8;
+                $body
+            }
+            IntWidth::U16 => {
+                type $t = u16;
+                $body
+            }
+            IntWidth::U32 => {
+                type $t = u32;
+                $body
+            }
+            IntWidth::U64 => {
+                type $t = u64;
+                $body
+            }
+            IntWidth::U128 => {
+                type $t = u128;
+                $body
+            }
+            IntWidth::I8 => {
+                type $t = i8;
+                $body
+            }
+            IntWidth::I16 => {
+                type $t = i16;
+                $body
+            }
+            IntWidth::I32 => {
+                type $t = i32;
+                $body
+            }
+            IntWidth::I64 => {
+                type $t = i64;
+                $body
+            }
+            IntWidth::I128 => {
+                type $t = i128;
+                $body
+            }
+        }
+    };
+}
+
+/// Truncates `n` to the given width, then sign- or zero-extends it back to 128 bits
+pub(crate) fn wrap(n: i128, width: IntWidth) -> i128 {
+    with_int_width!(width, T => n as T as i128)
+}
+
+fn bits(width: IntWidth) -> u32 {
+    with_int_width!(width, T => T::BITS)
+}
+
+fn min_value(width: IntWidth) -> i128 {
+    with_int_width!(width, T => T::MIN as i128)
+}
+
+/// The low bits of `n`, sign-extended as if the width were signed
+fn as_signed(n: i128, width: IntWidth) -> i128 {
+    let unused = 128 - bits(width);
+
+    (n << unused) >> unused
+}
+
+/// The low bits of `n`, zero-extended as if the width were unsigned
+fn as_unsigned(n: i128, width: IntWidth) -> u128 {
+    let unused = 128 - bits(width);
+
+    ((n as u128) << unused) >> unused
+}
+
+pub(crate) fn int_to_string(n: i128, width: IntWidth) -> String {
+    match width {
+        IntWidth::U128 => (n as u128).to_string(),
+        _ => n.to_string(),
+    }
+}
+
+pub(crate) fn float_to_string(x: f64, width: FloatWidth) -> String {
+    match width {
+        FloatWidth::F32 => (x as f32).to_string(),
+        FloatWidth::F64 => x.to_string(),
+    }
+}
+
+pub(crate) fn dec_to_string(n: i128) -> String {
+    let sign = if n < 0 { "-" } else { "" };
+    let magnitude = n.unsigned_abs();
+    let whole = magnitude / DEC_ONE as u128;
+    let fraction = magnitude % DEC_ONE as u128;
+
+    if fraction == 0 {
+        format!("{sign}{whole}")
+    } else {
+        let digits = format!("{fraction:018}");
+
+        format!("{sign}{whole}.{}", digits.trim_end_matches('0'))
+    }
+}
+
+pub(crate) fn compare_ints(a: i128, b: i128, width: IntWidth) -> Ordering {
+    match width {
+        IntWidth::U128 => (a as u128).cmp(&(b as u128)),
+        _ => a.cmp(&b),
+    }
+}
+
+pub(crate) fn add_wrap(a: i128, b: i128, width: IntWidth) -> i128 {
+    wrap(a.wrapping_add(b), width)
+}
+
+pub(crate) fn sub_wrap(a: i128, b: i128, width: IntWidth) -> i128 {
+    wrap(a.wrapping_sub(b), width)
+}
+
+pub(crate) fn mul_wrap(a: i128, b: i128, width: IntWidth) -> i128 {
+    with_int_width!(width, T => (a as T).wrapping_mul(b as T) as i128)
+}
+
+pub(crate) fn add_checked(a: i128, b: i128, width: IntWidth) -> Option<i128> {
+    with_int_width!(width, T => (a as T).checked_add(b as T).map(|n| n as i128))
+}
+
+pub(crate) fn sub_checked(a: i128, b: i128, width: IntWidth) -> Option<i128> {
+    with_int_width!(width, T => (a as T).checked_sub(b as T).map(|n| n as i128))
+}
+
+pub(crate) fn mul_checked(a: i128, b: i128, width: IntWidth) -> Option<i128> {
+    with_int_width!(width, T => (a as T).checked_mul(b as T).map(|n| n as i128))
+}
+
+pub(crate) fn add_saturated(a: i128, b: i128, width: IntWidth) -> i128 {
+    with_int_width!(width, T => (a as T).saturating_add(b as T) as i128)
+}
+
+pub(crate) fn sub_saturated(a: i128, b: i128, width: IntWidth) -> i128 {
+    with_int_width!(width, T => (a as T).saturating_sub(b as T) as i128)
+}
+
+pub(crate) fn mul_saturated(a: i128, b: i128, width: IntWidth) -> i128 {
+    with_int_width!(width, T => (a as T).saturating_mul(b as T) as i128)
+}
+
+/// `None` when dividing by zero
+pub(crate) fn div_trunc(a: i128, b: i128, width: IntWidth) -> Option<i128> {
+    if b == 0 {
+        return None;
+    }
+
+    Some(with_int_width!(width, T => (a as T).wrapping_div(b as T) as i128))
+}
+
+/// `None` when dividing by zero
+pub(crate) fn div_ceil(a: i128, b: i128, width: IntWidth) -> Option<i128> {
+    let quotient = div_trunc(a, b, width)?;
+    let remainder = rem(a, b, width)?;
+
+    // The quotient was rounded towards zero, which is down when it's positive
+    let positive = match width {
+        IntWidth::U8 | IntWidth::U16 | IntWidth::U32 | IntWidth::U64 | IntWidth::U128 => true,
+        _ => (remainder > 0) == (b > 0),
+    };
+
+    if remainder != 0 && positive {
+        Some(add_wrap(quotient, 1, width))
+    } else {
+        Some(quotient)
+    }
+}
+
+/// `None` when dividing by zero
+pub(crate) fn rem(a: i128, b: i128, width: IntWidth) -> Option<i128> {
+    if b == 0 {
+        return None;
+    }
+
+    Some(with_int_width!(width, T => (a as T).wrapping_rem(b as T) as i128))
+}
+
+pub(crate) fn is_multiple_of(a: i128, b: i128, width: IntWidth) -> bool {
+    match rem(a, b, width) {
+        Some(remainder) => remainder == 0,
+        None => a == 0,
+    }
+}
+
+/// `None` when the argument is the minimum value of a signed width
+pub(crate) fn neg(a: i128, width: IntWidth) -> Option<i128> {
+    if width.is_signed() && a == min_value(width) {
+        None
+    } else {
+        Some(wrap(a.wrapping_neg(), width))
+    }
+}
+
+/// `None` when the argument is the minimum value of a signed width
+pub(crate) fn abs(a: i128, width: IntWidth) -> Option<i128> {
+    if width.is_signed() && a < 0 {
+        neg(a, width)
+    } else {
+        Some(a)
+    }
+}
+
+pub(crate) fn pow_int(a: i128, b: i128, width: IntWidth) -> i128 {
+    with_int_width!(width, T => (a as T).wrapping_pow(b as u32) as i128)
+}
+
+pub(crate) fn shift_left(a: i128, b: i128, width: IntWidth) -> i128 {
+    match u32::try_from(b) {
+        Ok(b) if b < bits(width) => wrap(a << b, width),
+        _ => 0,
+    }
+}
+
+/// Shifts in copies of the most significant bit, whether or not the width is signed
+pub(crate) fn shift_right(a: i128, b: i128, width: IntWidth) -> i128 {
+    let max = bits(width) - 1;
+    let b = u32::try_from(b).map_or(max, |b| b.min(max));
+
+    wrap(as_signed(a, width) >> b, width)
+}
+
+/// Shifts in zeros, whether or not the width is signed
+pub(crate) fn shift_right_zero_fill(a: i128, b: i128, width: IntWidth) -> i128 {
+    match u32::try_from(b) {
+        Ok(b) if b < bits(width) => wrap((as_unsigned(a, width) >> b) as i128, width),
+        _ => 0,
+    }
+}
+
+pub(crate) fn count_leading_zero_bits(a: i128, width: IntWidth) -> u32 {
+    with_int_width!(width, T => (a as T).leading_zeros())
+}
+
+pub(crate) fn count_trailing_zero_bits(a: i128, width: IntWidth) -> u32 {
+    with_int_width!(width, T => (a as T).trailing_zeros())
+}
+
+pub(crate) fn count_one_bits(a: i128, width: IntWidth) -> u32 {
+    with_int_width!(width, T => (a as T).count_ones())
+}
+
+/// Whether the integer `n` of width `from` can be represented in width `to`
+pub(crate) fn fits(n: i128, from: IntWidth, to: IntWidth) -> bool {
+    match from {
+        IntWidth::U128 => {
+            let n = n as u128;
+            with_int_width!(to, T => T::try_from(n).is_ok())
+        }
+        _ => with_int_width!(to, T => T::try_from(n).is_ok()),
+    }
+}
+
+pub(crate) fn parse_int(string: &str, width: IntWidth) -> Option<i128> {
+    with_int_width!(width, T => string.parse::<T>().ok().map(|n| n as i128))
+}
+
+pub(crate) fn int_to_float(n: i128, width: IntWidth) -> f64 {
+    match width {
+        IntWidth::U128 => n as u128 as f64,
+        _ => n as f64,
+    }
+}
+
+/// Rust's `as` saturates, and turns NaN into 0
+pub(crate) fn float_to_int(x: f64, width: IntWidth) -> i128 {
+    with_int_width!(width, T => x as T as i128)
+}
+
+pub(crate) fn float_in_width(x: f64, width: FloatWidth) -> f64 {
+    match width {
+        FloatWidth::F32 => x as f32 as f64,
+        FloatWidth::F64 => x,
+    }
+}
+
+pub(crate) fn int_to_dec(n: i128, width: IntWidth) -> Option<i128> {
+    match width {
+        IntWidth::U128 => i128::try_from(n as u128).ok()?.checked_mul(DEC_ONE),
+        _ => n.checked_mul(DEC_ONE),
+    }
+}
+
+pub(crate) fn dec_to_float(n: i128) -> f64 {
+    n as f64 / DEC_ONE as f64
+}
+
+pub(crate) fn float_to_dec(x: f64) -> i128 {
+    (x * DEC_ONE as f64) as i128
+}
+
+pub(crate) fn dec_from_str(string: &str) -> Option<i128> {
+    RocDec::from_str(string).map(|dec| i128::from_ne_bytes(dec.to_ne_bytes()))
+}
+
+pub(crate) fn dec_mul(a: i128, b: i128) -> Option<i128> {
+    signed_mul_div(a, b, DEC_ONE)
+}
+
+/// `None` when dividing by zero, or when the quotient overflows
+pub(crate) fn dec_div(a: i128, b: i128) -> Option<i128> {
+    if b == 0 {
+        return None;
+    }
+
+    signed_mul_div(a, DEC_ONE, b)
+}
+
+/// `a * b / c`, rounded towards zero, without overflowing in the middle
+fn signed_mul_div(a: i128, b: i128, c: i128) -> Option<i128> {
+    let negative = ((a < 0) != (b < 0)) != (c < 0);
+    let magnitude = mul_div(a.unsigned_abs(), b.unsigned_abs(), c.unsigned_abs())?;
+
+    if negative {
+        0i128.checked_sub_unsigned(magnitude)
+    } else {
+        i128::try_from(magnitude).ok()
+    }
+}
+
+fn mul_div(a: u128, b: u128, c: u128) -> Option<u128> {
+    let (high, low) = widening_mul(a, b);
+
+    // Long division of the 256-bit product, one bit at a time
+    let mut quotient_high = 0u128;
+    let mut quotient_low = 0u128;
+    let mut remainder = 0u128;
+
+    for i in (0..256).rev() {
+        let bit = if i >= 128 {
+            (high >> (i - 128)) & 1
+        } else {
+            (low >> i) & 1
+        };
+
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | bit;
+
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+
+            if i >= 128 {
+                quotient_high |= 1 << (i - 128);
+            } else {
+                quotient_low |= 1 << i;
+            }
+        }
+    }
+
+    (quotient_high == 0).then_some(quotient_low)
+}
+
+/// The 256-bit product of two `u128`s, as its high and low halves
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+
+    let (a_high, a_low) = (a >> 64, a & MASK);
+    let (b_high, b_low) = (b >> 64, b & MASK);
+
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    let high_high = a_high * b_high;
+
+    let middle = (low_low >> 64) + (high_low & MASK) + (low_high & MASK);
+
+    let low = (low_low & MASK) | (middle << 64);
+    let high = high_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+
+    (high, low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapping_and_checked() {
+        assert_eq!(add_wrap(255, 1, IntWidth::U8), 0);
+        assert_eq!(add_wrap(127, 1, IntWidth::I8), -128);
+        assert_eq!(add_checked(127, 1, IntWidth::I8), None);
+        assert_eq!(sub_checked(0, 1, IntWidth::U64), None);
+        assert_eq!(mul_checked(-1, -1, IntWidth::U128), None);
+        assert_eq!(neg(-128, IntWidth::I8), None);
+        assert_eq!(abs(-5, IntWidth::I32), Some(5));
+    }
+
+    #[test]
+    fn shifts() {
+        assert_eq!(shift_right(0b1001_0000, 1, IntWidth::U8), 0b1100_1000);
+        assert_eq!(
+            shift_right_zero_fill(0b1001_0000, 1, IntWidth::U8),
+            0b0100_1000
+        );
+        assert_eq!(shift_right_zero_fill(-16, 2, IntWidth::I8), 60);
+        assert_eq!(shift_left(1, 8, IntWidth::U8), 0);
+    }
+
+    #[test]
+    fn division_rounding() {
+        assert_eq!(div_ceil(7, 2, IntWidth::I64), Some(4));
+        assert_eq!(div_ceil(-7, 2, IntWidth::I64), Some(-3));
+        assert_eq!(div_ceil(7, 0, IntWidth::I64), None);
+        assert_eq!(rem(-7, 2, IntWidth::I64), Some(-1));
+    }
+
+    #[test]
+    fn decimals() {
+        let dec = |string| dec_from_str(string).unwrap();
+
+        assert_eq!(dec_mul(dec("1.5"), dec("-2.5")), Some(dec("-3.75")));
+        assert_eq!(
+            dec_div(dec("1"), dec("3")),
+            Some(dec("0.333333333333333333"))
+        );
+        assert_eq!(dec_div(dec("1"), 0), None);
+        assert_eq!(dec_mul(i128::MAX, dec("2")), None);
+        assert_eq!(dec_to_string(dec("-0.25")), "-0.25");
+    }
+}
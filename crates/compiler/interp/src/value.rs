@@ -0,0 +1,157 @@
+use crate::num::{dec_to_string, float_to_string, int_to_string};
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_module::ident::TagIdIntType;
+use roc_mono::layout::LambdaName;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// A runtime value. Values are immutable, except for the fields of a tag, which TRMC writes
+/// through a pointer right after allocating the tag.
+#[derive(Clone, Debug)]
+pub enum Value<'a> {
+    /// The value is stored as its bits in the given width, sign- or zero-extended to 128 bits.
+    /// So a `U128` above `i128::MAX` is negative here.
+    Int(i128, IntWidth),
+    Float(f64, FloatWidth),
+    /// The raw representation of a `Dec`, i.e. the number times 10^18
+    Dec(i128),
+    Bool(bool),
+    Str(Rc<str>),
+    List(Rc<Vec<Value<'a>>>),
+    Struct(Rc<Vec<Value<'a>>>),
+    Tag {
+        id: TagIdIntType,
+        fields: Rc<RefCell<Vec<Value<'a>>>>,
+    },
+    /// The null pointer, which stands for the nullable tag of a nullable union
+    Null,
+    Pointer(Pointer<'a>),
+    Function(LambdaName<'a>),
+    Erased {
+        value: Option<Box<Value<'a>>>,
+        callee: Box<Value<'a>>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum Pointer<'a> {
+    /// Made by an `Alloca`
+    Cell(Rc<RefCell<Value<'a>>>),
+    /// A field of a tag, made by a `GetElementPointer`
+    Field(Rc<RefCell<Vec<Value<'a>>>>, usize),
+}
+
+impl<'a> Pointer<'a> {
+    pub fn load(&self) -> Value<'a> {
+        match self {
+            Pointer::Cell(cell) => cell.borrow().clone(),
+            Pointer::Field(fields, index) => fields.borrow()[*index].clone(),
+        }
+    }
+
+    pub fn store(&self, value: Value<'a>) {
+        match self {
+            Pointer::Cell(cell) => *cell.borrow_mut() = value,
+            Pointer::Field(fields, index) => fields.borrow_mut()[*index] = value,
+        }
+    }
+}
+
+impl<'a> Value<'a> {
+    pub fn unit() -> Self {
+        Value::Struct(Rc::new(Vec::new()))
+    }
+
+    pub fn str(string: &str) -> Self {
+        Value::Str(Rc::from(string))
+    }
+
+    pub fn list(elements: Vec<Value<'a>>) -> Self {
+        Value::List(Rc::new(elements))
+    }
+
+    pub fn record(fields: Vec<Value<'a>>) -> Self {
+        Value::Struct(Rc::new(fields))
+    }
+
+    pub fn tag(id: TagIdIntType, fields: Vec<Value<'a>>) -> Self {
+        Value::Tag {
+            id,
+            fields: Rc::new(RefCell::new(fields)),
+        }
+    }
+
+    /// The value of a `Switch` condition
+    pub(crate) fn switch_value(&self) -> Option<u64> {
+        match self {
+            Value::Int(n, _) => Some(*n as u64),
+            Value::Bool(b) => Some(*b as u64),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> PartialEq for Value<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        use Value::*;
+
+        match (self, other) {
+            (Int(a, _), Int(b, _)) => a == b,
+            (Float(a, _), Float(b, _)) => a == b,
+            (Dec(a), Dec(b)) => a == b,
+            (Bool(a), Bool(b)) => a == b,
+            // Small tag ids are bools or bytes, depending on how many tags there are
+            (Bool(a), Int(b, _)) | (Int(b, _), Bool(a)) => *a as i128 == *b,
+            (Str(a), Str(b)) => a == b,
+            (List(a), List(b)) | (Struct(a), Struct(b)) => a == b,
+            (Tag { id: a, fields: x }, Tag { id: b, fields: y }) => {
+                a == b && (Rc::ptr_eq(x, y) || *x.borrow() == *y.borrow())
+            }
+            (Null, Null) => true,
+            (Function(a), Function(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Values don't know their type, so this shows tags by their id, e.g. `#1(5, #null)`.
+impl<'a> fmt::Display for Value<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_all<'a>(
+            f: &mut fmt::Formatter<'_>,
+            values: &[Value<'a>],
+            open: &str,
+            close: &str,
+        ) -> fmt::Result {
+            write!(f, "{open}")?;
+
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+
+                write!(f, "{value}")?;
+            }
+
+            write!(f, "{close}")
+        }
+
+        match self {
+            Value::Int(n, width) => write!(f, "{}", int_to_string(*n, *width)),
+            Value::Float(x, width) => write!(f, "{}", float_to_string(*x, *width)),
+            Value::Dec(n) => write!(f, "{}", dec_to_string(*n)),
+            Value::Bool(true) => write!(f, "Bool.true"),
+            Value::Bool(false) => write!(f, "Bool.false"),
+            Value::Str(string) => write!(f, "{string:?}"),
+            Value::List(elements) => write_all(f, elements, "[", "]"),
+            Value::Struct(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Value::Struct(fields) => write_all(f, fields, "{ ", " }"),
+            Value::Tag { id, fields } if fields.borrow().is_empty() => write!(f, "#{id}"),
+            Value::Tag { id, fields } => write_all(f, &fields.borrow(), &format!("#{id}("), ")"),
+            Value::Null => write!(f, "#null"),
+            Value::Pointer(_) => write!(f, "<pointer>"),
+            Value::Function(_) | Value::Erased { .. } => write!(f, "<function>"),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+hosted Effect
+    exposes [Effect, after, map, always, args, getLine, beep]
+    imports []
+    generates Effect with [after, map, always]
+
+args : Effect (List Str)
+
+getLine : Effect (Result Str [EndOfFile])
+
+beep : Effect {}
@@ -0,0 +1,9 @@
+platform "interp-tests"
+    requires {} { main : Effect Str }
+    exposes []
+    packages {}
+    imports [Effect.{ Effect }]
+    provides [mainForHost]
+
+mainForHost : Effect Str
+mainForHost = main
@@ -0,0 +1,343 @@
+use bumpalo::Bump;
+use indoc::indoc;
+use roc_interp::{run_main, Host, RuntimeError, StdHost, Value};
+use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError, Threading};
+use roc_mono::ir::{CrashTag, HostedFunction};
+use roc_mono::layout::STLayoutInterner;
+use roc_packaging::cache::RocCacheDir;
+use std::path::PathBuf;
+
+fn promote_expr_to_module(src: &str) -> String {
+    let mut buffer = String::from("app \"test\" provides [main] to \"./platform\"\n\nmain =\n");
+
+    for line in src.lines() {
+        buffer.push_str("    ");
+        buffer.push_str(line);
+        buffer.push('\n');
+    }
+
+    buffer
+}
+
+fn run_with<H>(src: &str, host: H) -> Result<String, RuntimeError>
+where
+    H: for<'a> Host<'a>,
+{
+    load_and_run(
+        &promote_expr_to_module(src),
+        PathBuf::from("fake/test/path"),
+        host,
+    )
+}
+
+/// Runs an app on the platform in `tests/platform`, whose hosted functions are answered by `host`
+fn run_app<H>(src: &str, host: H) -> Result<String, RuntimeError>
+where
+    H: for<'a> Host<'a>,
+{
+    let module_src = format!(
+        "app \"test\" packages {{ pf: \"platform/main.roc\" }} imports [pf.Effect] provides [main] to pf\n\n{src}"
+    );
+
+    load_and_run(
+        &module_src,
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests"),
+        host,
+    )
+}
+
+fn load_and_run<H>(module_src: &str, src_dir: PathBuf, host: H) -> Result<String, RuntimeError>
+where
+    H: for<'a> Host<'a>,
+{
+    let arena = Bump::new();
+
+    let load_config = LoadConfig {
+        target_info: roc_target::TargetInfo::default_x86_64(),
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+    };
+
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        &arena,
+        src_dir.join("Test.roc"),
+        module_src,
+        src_dir,
+        RocCacheDir::Disallowed,
+        load_config,
+    );
+
+    let loaded = match loaded {
+        Ok(loaded) => loaded,
+        Err(LoadMonomorphizedError::LoadingProblem(roc_load::LoadingProblem::FormattedReport(
+            report,
+        ))) => panic!("{report}"),
+        Err(e) => panic!("{e:?}"),
+    };
+
+    run_main(&loaded, host).map(|value| value.to_string())
+}
+
+fn run(src: &str) -> Result<String, RuntimeError> {
+    run_with(src, StdHost::default())
+}
+
+fn assert_evals_to(src: &str, expected: &str) {
+    assert_eq!(run(src).as_deref(), Ok(expected));
+}
+
+fn assert_crashes(src: &str, message: &str) {
+    match run(src) {
+        Err(RuntimeError::Crashed {
+            message: actual, ..
+        }) => assert_eq!(actual, message),
+        other => panic!("expected a crash, got {other:?}"),
+    }
+}
+
+#[test]
+fn arithmetic() {
+    assert_evals_to("1 + 2 * 3 - 4", "3");
+    assert_evals_to("Num.divTrunc 7 2", "3");
+    assert_evals_to("Num.toU8 255 |> Num.addWrap 1", "0");
+    assert_evals_to("0.1 + 0.2", "0.3");
+    assert_evals_to("Num.toF64 3 / 2", "1.5");
+}
+
+#[test]
+fn overflow_crashes() {
+    assert_crashes("Num.maxI64 + 1", "Integer addition overflowed!");
+    assert_crashes("Num.divTrunc 1 0", "Integer division by 0!");
+}
+
+#[test]
+fn user_crash() {
+    assert_eq!(
+        run(r#"if Bool.true then crash "oh no" else 1"#),
+        Err(RuntimeError::Crashed {
+            message: "oh no".to_string(),
+            tag: CrashTag::User,
+        })
+    );
+}
+
+#[test]
+fn recursion() {
+    assert_evals_to(
+        indoc!(
+            r#"
+            fib = \n ->
+                if n < 2 then n else fib (n - 1) + fib (n - 2)
+
+            fib 15
+            "#
+        ),
+        "610",
+    );
+}
+
+#[test]
+fn tail_recursion() {
+    assert_evals_to(
+        indoc!(
+            r#"
+            sum = \n, acc ->
+                if n == 0 then acc else sum (n - 1) (acc + n)
+
+            sum 100000 0
+            "#
+        ),
+        "5000050000",
+    );
+}
+
+#[test]
+fn strings() {
+    assert_evals_to(r#"Str.concat "Hello, " "World!""#, r#""Hello, World!""#);
+    assert_evals_to(r#"Str.joinWith ["a", "b", "c"] "-""#, r#""a-b-c""#);
+    assert_evals_to(r#"Str.split "1,2,3" ",""#, r#"["1", "2", "3"]"#);
+    assert_evals_to(
+        r#"when Str.toI64 "-42" is
+    Ok n -> n + 1
+    Err _ -> 0"#,
+        "-41",
+    );
+    assert_evals_to(r#"Num.toStr 12 |> Str.countUtf8Bytes"#, "2");
+}
+
+#[test]
+fn from_utf8() {
+    assert_evals_to(
+        r#"when Str.fromUtf8 [104, 105] is
+    Ok str -> str
+    Err _ -> "error""#,
+        r#""hi""#,
+    );
+    assert_evals_to(
+        r#"when Str.fromUtf8 [104, 255] is
+    Ok _ -> 0
+    Err (BadUtf8 InvalidStartByte index) -> index
+    Err _ -> 99"#,
+        "1",
+    );
+}
+
+#[test]
+fn lists() {
+    assert_evals_to("List.map [1, 2, 3] \\x -> x * 2", "[2, 4, 6]");
+    assert_evals_to(
+        "n = 10\nList.map2 [1, 2, 3] [4, 5] \\x, y -> x + y + n",
+        "[15, 17]",
+    );
+    assert_evals_to("List.sortWith [3, 1, 2] Num.compare", "[1, 2, 3]");
+    assert_evals_to("List.walk [1, 2, 3, 4] 0 Num.add", "10");
+    assert_evals_to("[1, 2, 3] |> List.append 4 |> List.reverse", "[4, 3, 2, 1]");
+    assert_evals_to("List.set [1, 2, 3] 1 7", "[1, 7, 3]");
+}
+
+#[test]
+fn records_and_tags() {
+    assert_evals_to(
+        indoc!(
+            r#"
+            point = { x: 3, y: 4 }
+
+            when Ok point is
+                Ok { x, y } -> x * y
+                Err {} -> 0
+            "#
+        ),
+        "12",
+    );
+}
+
+#[test]
+fn recursive_tags() {
+    assert_evals_to(
+        indoc!(
+            r#"
+            ConsList : [Nil, Cons I64 ConsList]
+
+            len : ConsList -> I64
+            len = \list ->
+                when list is
+                    Nil -> 0
+                    Cons _ rest -> 1 + len rest
+
+            len (Cons 1 (Cons 2 (Cons 3 Nil)))
+            "#
+        ),
+        "3",
+    );
+}
+
+#[derive(Default)]
+struct RecordingHost {
+    dbgs: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+}
+
+impl<'a> Host<'a> for RecordingHost {
+    fn call_foreign(&mut self, _name: &str, _arguments: &[Value<'a>]) -> Option<Value<'a>> {
+        None
+    }
+
+    fn dbg(&mut self, _location: &str, source: &str, message: &str) {
+        self.dbgs.borrow_mut().push(format!("{source} = {message}"));
+    }
+}
+
+#[test]
+fn dbg_goes_to_the_host() {
+    let host = RecordingHost::default();
+    let dbgs = host.dbgs.clone();
+
+    let result = run_with(
+        indoc!(
+            r#"
+            x = 1 + 1
+            dbg x
+
+            x * 10
+            "#
+        ),
+        host,
+    );
+
+    assert_eq!(result.as_deref(), Ok("20"));
+    assert_eq!(*dbgs.borrow(), ["x = 2"]);
+}
+
+#[derive(Default)]
+struct ScriptedHost {
+    declared: Vec<(String, bool)>,
+}
+
+impl<'a> Host<'a> for ScriptedHost {
+    fn declare(
+        &mut self,
+        function: &HostedFunction<'a>,
+        _layout_interner: &STLayoutInterner<'a>,
+    ) -> bool {
+        let name = function.foreign_symbol.as_str().to_string();
+        self.declared.push((name, function.returns_result));
+
+        true
+    }
+
+    fn call_foreign(&mut self, name: &str, _arguments: &[Value<'a>]) -> Option<Value<'a>> {
+        assert_eq!(self.declared, [("roc_fx_getLine".to_string(), true)]);
+
+        match name {
+            // `Ok "hello"`
+            "getLine" => Some(Value::tag(1, vec![Value::str("hello")])),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn hosts_answer_with_the_declared_result() {
+    let result = run_app(
+        indoc!(
+            r#"
+            main =
+                Effect.map Effect.getLine \result ->
+                    when result is
+                        Ok line -> line
+                        Err EndOfFile -> "end of file"
+            "#
+        ),
+        ScriptedHost::default(),
+    );
+
+    assert_eq!(result.as_deref(), Ok(r#""hello""#));
+}
+
+#[test]
+fn std_host_provides_the_apps_arguments() {
+    let args = vec!["a".to_string(), "b".to_string()];
+    let result = run_app(
+        r#"main = Effect.map Effect.args \args -> Str.joinWith args ",""#,
+        StdHost::new(args),
+    );
+
+    assert_eq!(result.as_deref(), Ok(r#""a,b""#));
+}
+
+#[test]
+fn std_host_rejects_functions_it_does_not_provide() {
+    let result = run_app(
+        r#"main = Effect.map Effect.beep \{} -> "beeped""#,
+        StdHost::default(),
+    );
+
+    assert_eq!(
+        result,
+        Err(RuntimeError::UnsupportedPlatform {
+            missing: vec!["roc_fx_beep".to_string()]
+        })
+    );
+}
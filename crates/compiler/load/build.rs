@@ -2,7 +2,7 @@ use std::path::{Path, PathBuf};
 
 use bumpalo::Bump;
 use roc_error_macros::internal_error;
-use roc_module::symbol::ModuleId;
+use roc_module::symbol::{ModuleId, StableModuleIds};
 
 const SKIP_SUBS_CACHE: bool = {
     match option_env!("ROC_SKIP_SUBS_CACHE") {
@@ -29,12 +29,36 @@ const MODULES: &[(ModuleId, &str)] = &[
 ];
 
 fn main() {
+    let mut stable_module_ids = StableModuleIds::default();
+
     for (module_id, filename) in MODULES {
-        write_subs_for_module(*module_id, filename);
+        write_subs_for_module(*module_id, filename, &mut stable_module_ids);
+    }
+
+    write_stable_module_ids(&stable_module_ids);
+}
+
+/// The cached types refer to modules by the `ModuleId`s they had while the builtins were loaded
+/// here. Store which modules those were, so that a run that numbers them differently can
+/// translate the cached symbols.
+fn write_stable_module_ids(stable_module_ids: &StableModuleIds) {
+    let mut output_path = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    output_path.push("StableModuleIds.dat");
+
+    if SKIP_SUBS_CACHE {
+        write_types_for_module_dummy(&output_path)
+    } else {
+        let mut file = std::fs::File::create(output_path).unwrap();
+
+        stable_module_ids.serialize(&mut file).unwrap();
     }
 }
 
-fn write_subs_for_module(module_id: ModuleId, filename: &str) {
+fn write_subs_for_module(
+    module_id: ModuleId,
+    filename: &str,
+    stable_module_ids: &mut StableModuleIds,
+) {
     // Tell Cargo that if the given file changes, to rerun this build script.
     let filepath = PathBuf::from("..")
         .join("builtins")
@@ -49,7 +73,7 @@ fn write_subs_for_module(module_id: ModuleId, filename: &str) {
     if SKIP_SUBS_CACHE {
         write_types_for_module_dummy(&output_path)
     } else {
-        write_types_for_module_real(module_id, filename, &output_path)
+        write_types_for_module_real(module_id, filename, &output_path, stable_module_ids)
     }
 }
 
@@ -58,7 +82,12 @@ fn write_types_for_module_dummy(output_path: &Path) {
     std::fs::write(output_path, []).unwrap();
 }
 
-fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path: &Path) {
+fn write_types_for_module_real(
+    module_id: ModuleId,
+    filename: &str,
+    output_path: &Path,
+    stable_module_ids: &mut StableModuleIds,
+) {
     use roc_can::module::TypeState;
     use roc_load_internal::file::{LoadingProblem, Threading};
     use roc_packaging::cache::RocCacheDir;
@@ -97,6 +126,7 @@ fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path:
     let problems = report_problems(
         &module.sources,
         &module.interns,
+        &module.imported_symbols,
         &mut module.can_problems,
         &mut module.type_problems,
     );
@@ -105,6 +135,23 @@ fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path:
         internal_error!("Problems were found! Refusing to build cached subs.");
     }
 
+    // All the cached modules go in one numbering, so every load has to agree on it.
+    let loaded_ids = module.interns.module_ids.stable_module_ids().unwrap();
+    for (loaded_module_id, stable_id) in loaded_ids.iter() {
+        let renumbered = match stable_module_ids.stable_id(loaded_module_id) {
+            Some(existing) => existing != stable_id,
+            None => stable_module_ids
+                .insert(loaded_module_id, stable_id)
+                .is_err(),
+        };
+
+        if renumbered {
+            internal_error!(
+                "Loading {filename} numbered the modules differently than loading another builtin"
+            );
+        }
+    }
+
     let subs = module.solved.into_inner();
     let exposed_vars_by_symbol: Vec<_> = module.exposed_to_host.into_iter().collect();
     let abilities = module.abilities_store;
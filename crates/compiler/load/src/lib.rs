@@ -3,7 +3,7 @@
 use bumpalo::Bump;
 use roc_can::module::{ExposedByModule, TypeState};
 use roc_collections::all::MutMap;
-use roc_module::symbol::ModuleId;
+use roc_module::symbol::{ModuleId, PackageModuleIds, StableModuleIds, SymbolRemap};
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::report::{Palette, RenderTarget};
 use roc_target::TargetInfo;
@@ -18,11 +18,12 @@ const SKIP_SUBS_CACHE: bool = {
 
 pub use roc_load_internal::docs;
 pub use roc_load_internal::file::{
-    ExecutionMode, ExpectMetadata, LoadConfig, LoadResult, LoadStart, LoadingProblem, Phase,
-    Threading,
+    specialize_for_target, ExecutionMode, ExpectMetadata, LoadConfig, LoadResult, LoadStart,
+    LoadingProblem, Phase, Threading, TypeCheckedProgram, VirtualFiles,
 };
 pub use roc_load_internal::module::{
-    CheckedModule, EntryPoint, Expectations, ExposedToHost, LoadedModule, MonomorphizedModule,
+    type_at, CheckedModule, EntryPoint, Expectations, ExposedToHost, LoadedModule, ModuleTiming,
+    MonomorphizedModule,
 };
 pub use roc_solve::FunctionKind;
 
@@ -115,6 +116,7 @@ pub fn load_and_monomorphize_from_str<'a>(
     match load(arena, load_start, exposed_types, roc_cache_dir, load_config)? {
         Monomorphized(module) => Ok(module),
         TypeChecked(module) => Err(LoadMonomorphizedError::ErrorModule(module)),
+        TypeCheckedForTargets(_) => unreachable!(""),
     }
 }
 
@@ -139,6 +141,37 @@ pub fn load_and_monomorphize<'a>(
     match load(arena, load_start, exposed_types, roc_cache_dir, load_config)? {
         Monomorphized(module) => Ok(module),
         TypeChecked(module) => Err(LoadMonomorphizedError::ErrorModule(module)),
+        TypeCheckedForTargets(_) => unreachable!(""),
+    }
+}
+
+/// Load and type check a program once, so that it can be specialized for several targets
+/// with [specialize_for_target]. Like [load_and_monomorphize], type errors don't stop this.
+pub fn load_for_targets<'a>(
+    arena: &'a Bump,
+    filename: PathBuf,
+    roc_cache_dir: RocCacheDir<'_>,
+    load_config: LoadConfig,
+) -> Result<TypeCheckedProgram<'a>, LoadingProblem<'a>> {
+    use LoadResult::*;
+
+    let load_start = LoadStart::from_path(
+        arena,
+        filename,
+        load_config.render,
+        roc_cache_dir,
+        load_config.palette,
+    )?;
+
+    let exposed_types = ExposedByModule::default();
+    let load_config = LoadConfig {
+        exec_mode: ExecutionMode::ExecutableForTargets,
+        ..load_config
+    };
+
+    match load(arena, load_start, exposed_types, roc_cache_dir, load_config)? {
+        Monomorphized(_) | TypeChecked(_) => unreachable!(""),
+        TypeCheckedForTargets(program) => Ok(program),
     }
 }
 
@@ -161,7 +194,7 @@ pub fn load_and_typecheck<'a>(
     let exposed_types = ExposedByModule::default();
 
     match load(arena, load_start, exposed_types, roc_cache_dir, load_config)? {
-        Monomorphized(_) => unreachable!(""),
+        Monomorphized(_) | TypeCheckedForTargets(_) => unreachable!(""),
         TypeChecked(module) => Ok(module),
     }
 }
@@ -195,7 +228,7 @@ pub fn load_and_typecheck_str<'a>(
         roc_cache_dir,
         ExecutionMode::Check,
     )? {
-        Monomorphized(_) => unreachable!(""),
+        Monomorphized(_) | TypeCheckedForTargets(_) => unreachable!(""),
         TypeChecked(module) => Ok(module),
     }
 }
@@ -229,6 +262,26 @@ fn deserialize_help(bytes: &[u8]) -> TypeState {
     state
 }
 
+/// Reads cached types, translating their symbols into the `ModuleId`s of this run.
+fn deserialize_cached(bytes: &[u8], remap: &SymbolRemap) -> TypeState {
+    let mut state = deserialize_help(bytes);
+
+    if !remap.is_identity() {
+        state.remap_symbols(remap);
+    }
+
+    state
+}
+
+/// How to translate the symbols in the cached types, which use the `ModuleId`s the builtins had
+/// when build.rs loaded them, into the `current` ones. Returns [None] if the cache can't be used,
+/// because its ids are unreadable or it refers to a module this run doesn't know.
+fn cached_symbol_remap(cached_ids: &[u8], current: &StableModuleIds) -> Option<SymbolRemap> {
+    let (cached, _) = StableModuleIds::deserialize(cached_ids).ok()?;
+
+    SymbolRemap::new(&cached, current).ok()
+}
+
 fn read_cached_types() -> MutMap<ModuleId, TypeState> {
     let mod_bool = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Bool.dat"));
     let mod_dict = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Dict.dat"));
@@ -242,30 +295,123 @@ fn read_cached_types() -> MutMap<ModuleId, TypeState> {
     let mod_decode = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Decode.dat"));
     let mod_hash = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Hash.dat"));
     let mod_inspect = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Inspect.dat"));
+    let cached_ids = include_bytes!(concat!(env!("OUT_DIR"), "/StableModuleIds.dat"));
 
     let mut output = MutMap::default();
 
     // Wasm seems to re-order definitions between build time and runtime, but only in release mode.
     // That is very strange, but we can solve it separately
-    if !cfg!(target_family = "wasm") && !SKIP_SUBS_CACHE {
-        output.insert(ModuleId::BOOL, deserialize_help(mod_bool));
+    if cfg!(target_family = "wasm") || SKIP_SUBS_CACHE {
+        return output;
+    }
 
-        output.insert(ModuleId::RESULT, deserialize_help(mod_result));
-        output.insert(ModuleId::NUM, deserialize_help(mod_num));
+    // Every load starts out with the builtins numbered like this
+    let current = match PackageModuleIds::default().stable_module_ids() {
+        Ok(current) => current,
+        Err(_) => return output,
+    };
 
-        output.insert(ModuleId::LIST, deserialize_help(mod_list));
-        output.insert(ModuleId::STR, deserialize_help(mod_str));
-        output.insert(ModuleId::BOX, deserialize_help(mod_box));
+    let remap = match cached_symbol_remap(cached_ids, &current) {
+        Some(remap) => remap,
+        None => return output,
+    };
 
-        output.insert(ModuleId::DICT, deserialize_help(mod_dict));
-        output.insert(ModuleId::SET, deserialize_help(mod_set));
+    let mut insert = |cached_module_id: ModuleId, bytes: &[u8]| {
+        output.insert(
+            remap.module_id(cached_module_id),
+            deserialize_cached(bytes, &remap),
+        );
+    };
 
-        output.insert(ModuleId::ENCODE, deserialize_help(mod_encode));
-        output.insert(ModuleId::DECODE, deserialize_help(mod_decode));
+    insert(ModuleId::BOOL, mod_bool);
 
-        output.insert(ModuleId::HASH, deserialize_help(mod_hash));
-        output.insert(ModuleId::INSPECT, deserialize_help(mod_inspect));
-    }
+    insert(ModuleId::RESULT, mod_result);
+    insert(ModuleId::NUM, mod_num);
+
+    insert(ModuleId::LIST, mod_list);
+    insert(ModuleId::STR, mod_str);
+    insert(ModuleId::BOX, mod_box);
+
+    insert(ModuleId::DICT, mod_dict);
+    insert(ModuleId::SET, mod_set);
+
+    insert(ModuleId::ENCODE, mod_encode);
+    insert(ModuleId::DECODE, mod_decode);
+
+    insert(ModuleId::HASH, mod_hash);
+    insert(ModuleId::INSPECT, mod_inspect);
 
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_module::symbol::Symbol;
+
+    /// The cached ids, with the `ModuleId`s of `left` and `right` swapped, as if another run had
+    /// loaded the two in the opposite order.
+    fn swapped(cached: &StableModuleIds, left: ModuleId, right: ModuleId) -> StableModuleIds {
+        let mut swapped = StableModuleIds::default();
+
+        for (module_id, stable_id) in cached.iter() {
+            let module_id = if module_id == left {
+                right
+            } else if module_id == right {
+                left
+            } else {
+                module_id
+            };
+
+            swapped.insert(module_id, stable_id).unwrap();
+        }
+
+        swapped
+    }
+
+    #[test]
+    fn cache_is_remapped_into_a_different_numbering() {
+        if cfg!(target_family = "wasm") || SKIP_SUBS_CACHE {
+            return;
+        }
+
+        let mod_bool = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Bool.dat"));
+        let cached_ids = include_bytes!(concat!(env!("OUT_DIR"), "/StableModuleIds.dat"));
+        let (cached, _) = StableModuleIds::deserialize(cached_ids).unwrap();
+
+        let same = cached_symbol_remap(cached_ids, &cached).unwrap();
+        assert!(same.is_identity());
+
+        let current = swapped(&cached, ModuleId::BOOL, ModuleId::STR);
+        let remap = cached_symbol_remap(cached_ids, &current).unwrap();
+        assert!(!remap.is_identity());
+        assert_eq!(remap.module_id(ModuleId::BOOL), ModuleId::STR);
+
+        let original = deserialize_cached(mod_bool, &same);
+        let remapped = deserialize_cached(mod_bool, &remap);
+
+        assert!(!remapped.exposed_vars_by_symbol.is_empty());
+        for ((symbol, var), (remapped_symbol, remapped_var)) in original
+            .exposed_vars_by_symbol
+            .iter()
+            .zip(remapped.exposed_vars_by_symbol.iter())
+        {
+            assert_eq!(symbol.module_id(), ModuleId::BOOL);
+            assert_eq!(remapped_symbol.module_id(), ModuleId::STR);
+            assert_eq!(symbol.ident_id(), remapped_symbol.ident_id());
+            assert_eq!(var, remapped_var);
+        }
+
+        let remapped_eq = Symbol::new(ModuleId::STR, Symbol::BOOL_EQ.ident_id());
+        assert!(original.abilities.is_ability(Symbol::BOOL_EQ));
+        assert!(remapped.abilities.is_ability(remapped_eq));
+        assert!(!remapped.abilities.is_ability(Symbol::BOOL_EQ));
+    }
+
+    #[test]
+    fn cache_referring_to_an_unknown_module_is_not_used() {
+        let cached_ids = include_bytes!(concat!(env!("OUT_DIR"), "/StableModuleIds.dat"));
+
+        assert!(cached_symbol_remap(cached_ids, &StableModuleIds::default()).is_none());
+    }
+}
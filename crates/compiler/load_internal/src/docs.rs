@@ -7,6 +7,10 @@ use roc_module::symbol::{IdentIds, ModuleId, ModuleIds, Symbol};
 use roc_parse::ast::AssignedField;
 use roc_parse::ast::{self, ExtractSpaces, TypeHeader};
 use roc_parse::ast::{CommentOrNewline, TypeDef, ValueDef};
+use roc_parse::header::ExposedName;
+use roc_parse::parser::Parser;
+use roc_parse::state::State;
+use roc_region::all::Loc;
 
 // Documentation generation requirements
 
@@ -131,8 +135,9 @@ pub fn generate_module_docs(
     exposed_module_ids: &[ModuleId],
     exposed_symbols: VecSet<Symbol>,
     header_comments: &[CommentOrNewline<'_>],
+    generates_with: &[Loc<ExposedName<'_>>],
 ) -> ModuleDocumentation {
-    let entries = generate_entry_docs(
+    let mut entries = generate_entry_docs(
         home,
         &scope.locals.ident_ids,
         module_ids,
@@ -141,6 +146,34 @@ pub fn generate_module_docs(
         header_comments,
     );
 
+    // The functions that a hosted module generates have no defs to get their docs from
+    for generated in generates_with {
+        let name = generated.value.as_str();
+
+        if let (Some(ident_id), Some(docs)) = (
+            scope.locals.ident_ids.get_id(name),
+            roc_can::effect_module::generated_function_docs(name),
+        ) {
+            let symbol = Symbol::new(home, ident_id);
+
+            // A def of the module itself takes the place of the generated function
+            let documented = entries.iter().any(
+                |entry| matches!(entry, DocEntry::DocDef(doc_def) if doc_def.symbol == symbol),
+            );
+
+            if !documented {
+                let doc_def = DocDef {
+                    name: name.to_string(),
+                    symbol,
+                    type_annotation: TypeAnnotation::NoTypeAnn,
+                    type_vars: Vec::new(),
+                    docs: Some(docs),
+                };
+                entries.push(DocEntry::DocDef(doc_def));
+            }
+        }
+    }
+
     ModuleDocumentation {
         name: module_name.as_str().to_string(),
         scope,
@@ -521,6 +554,21 @@ fn contains_unexposed_type(
     }
 }
 
+/// Parses a type written out in Roc syntax, like the printed type of a def which has no
+/// annotation, into the form that annotations are documented in.
+pub fn parse_type_annotation(src: &str) -> Option<TypeAnnotation> {
+    let arena = bumpalo::Bump::new();
+    let state = State::new(src.trim().as_bytes());
+
+    let (_, loc_ann, state) = roc_parse::type_annotation::located(false)
+        .parse(&arena, state, 0)
+        .ok()?;
+
+    state
+        .has_reached_end()
+        .then(|| type_to_docs(false, loc_ann.value))
+}
+
 fn type_to_docs(in_func_type_ann: bool, type_annotation: ast::TypeAnnotation) -> TypeAnnotation {
     match type_annotation {
         ast::TypeAnnotation::TagUnion { tags, ext } => {
@@ -4,7 +4,7 @@ use crate::docs::ModuleDocumentation;
 use crate::module::{
     CheckedModule, ConstrainedModule, EntryPoint, Expectations, ExposedToHost,
     FoundSpecializationsModule, LateSpecializationsModule, LoadedModule, ModuleHeader,
-    ModuleTiming, MonomorphizedModule, ParsedModule, ToplevelExpects, TypeCheckedModule,
+    ModuleTiming, MonomorphizedModule, ParsedModule, Property, ToplevelExpects, TypeCheckedModule,
 };
 use crate::module_cache::ModuleCache;
 use bumpalo::{collections::CollectIn, Bump};
@@ -37,9 +37,10 @@ use roc_module::symbol::{
     IdentIds, IdentIdsByModule, Interns, ModuleId, ModuleIds, PQModuleName, PackageModuleIds,
     PackageQualified, Symbol,
 };
+use roc_mono::coverage::BranchCounters;
 use roc_mono::ir::{
-    CapturedSymbols, ExternalSpecializations, GlueLayouts, HostExposedLambdaSets, PartialProc,
-    Proc, ProcLayout, Procs, ProcsBase, UpdateModeIds, UsageTrackingMap,
+    CapturedSymbols, ExternalSpecializations, GlueLayouts, HostExposedLambdaSets, HostedFunctions,
+    PartialProc, Proc, ProcLayout, Procs, ProcsBase, UpdateModeIds, UsageTrackingMap,
 };
 use roc_mono::layout::{
     GlobalLayoutInterner, LambdaName, Layout, LayoutCache, LayoutProblem, Niche, STLayoutInterner,
@@ -51,16 +52,16 @@ use roc_parse::ast::{
     self, CommentOrNewline, Expr, ExtractSpaces, Pattern, Spaced, StrLiteral, ValueDef,
 };
 use roc_parse::header::{
-    ExposedName, HeaderType, ImportsEntry, PackageEntry, PackageHeader, PlatformHeader, To,
-    TypedIdent,
+    ExposedName, HeaderType, ImportsEntry, PackageEntry, PackageHeader, PackageName,
+    PlatformHeader, To, TypedIdent,
 };
 use roc_parse::module::module_defs;
 use roc_parse::parser::{FileError, Parser, SourceError, SyntaxError};
 use roc_problem::Severity;
 use roc_region::all::{LineInfo, Loc, Region};
-#[cfg(not(target_family = "wasm"))]
-use roc_reporting::report::to_https_problem_report_string;
 use roc_reporting::report::{to_file_problem_report_string, Palette, RenderTarget};
+#[cfg(not(target_family = "wasm"))]
+use roc_reporting::report::{to_https_problem_report_string, to_manifest_problem_report_string};
 use roc_solve::module::{extract_module_owned_implementations, SolveConfig, Solved, SolvedModule};
 use roc_solve::FunctionKind;
 use roc_solve_problem::TypeError;
@@ -80,6 +81,7 @@ use std::{env, fs};
 use {
     roc_packaging::cache::{self},
     roc_packaging::https::{PackageMetadata, Problem},
+    roc_packaging::manifest,
 };
 
 pub use crate::work::Phase;
@@ -116,11 +118,23 @@ pub struct LoadConfig {
 pub enum ExecutionMode {
     Check,
     Executable,
+    /// Like [`ExecutionMode::Executable`], but the root module may shadow variables, with a
+    /// warning. This lets a REPL session reuse a name for a new value.
+    Repl,
     /// Like [`ExecutionMode::Executable`], but stops in the presence of type errors.
     ExecutableIfCheck,
+    /// Like [`ExecutionMode::Executable`], but stops once every module is type checked, so that
+    /// the same [`TypeCheckedProgram`] can be specialized for several targets with
+    /// [`specialize_for_target`], without parsing and solving everything again for each one.
+    ExecutableForTargets,
     /// Test is like [`ExecutionMode::ExecutableIfCheck`], but rather than producing a proper
     /// executable, run tests.
-    Test,
+    Test {
+        /// Count how often each `when` branch in the root package runs.
+        coverage: bool,
+        /// Check the expects inside functions of the root package against generated arguments.
+        properties: bool,
+    },
 }
 
 impl ExecutionMode {
@@ -128,13 +142,22 @@ impl ExecutionMode {
         use ExecutionMode::*;
 
         match self {
-            Executable => Phase::MakeSpecializations,
-            Check | ExecutableIfCheck | Test => Phase::SolveTypes,
+            Executable | Repl => Phase::MakeSpecializations,
+            Check | ExecutableIfCheck | ExecutableForTargets | Test { .. } => Phase::SolveTypes,
         }
     }
 
     fn build_if_checks(&self) -> bool {
-        matches!(self, Self::ExecutableIfCheck | Self::Test)
+        matches!(
+            self,
+            Self::ExecutableIfCheck | Self::ExecutableForTargets | Self::Test { .. }
+        )
+    }
+
+    /// Whether type errors stop the load once everything is type checked, rather than
+    /// specializing anyway (with runtime errors where the problems are).
+    fn stops_on_errors(&self) -> bool {
+        matches!(self, Self::ExecutableIfCheck | Self::Test { .. })
     }
 }
 
@@ -302,6 +325,8 @@ fn start_phase<'a>(
                     aliases,
                     abilities_store,
                     skip_constraint_gen,
+                    permit_shadowing: matches!(state.exec_mode, ExecutionMode::Repl)
+                        && module_id == state.root_id,
                     exposed_module_ids: state.exposed_modules,
                 }
             }
@@ -398,7 +423,15 @@ fn start_phase<'a>(
                 let derived_module = SharedDerivedModule::clone(&state.derived_module);
 
                 let build_expects =
-                    matches!(state.exec_mode, ExecutionMode::Test) && expectations.is_some();
+                    matches!(state.exec_mode, ExecutionMode::Test { .. }) && expectations.is_some();
+                let build_properties = build_expects
+                    && matches!(
+                        state.exec_mode,
+                        ExecutionMode::Test {
+                            properties: true,
+                            ..
+                        }
+                    );
 
                 BuildTask::BuildPendingSpecializations {
                     layout_cache,
@@ -415,6 +448,7 @@ fn start_phase<'a>(
                     derived_module,
                     expectations,
                     build_expects,
+                    build_properties,
                 }
             }
             Phase::MakeSpecializations => {
@@ -606,6 +640,7 @@ enum Msg<'a> {
         procs_base: ProcsBase<'a>,
         procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
         host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
+        hosted_functions: HostedFunctions<'a>,
         update_mode_ids: UpdateModeIds,
         module_timing: ModuleTiming,
         subs: Subs,
@@ -629,6 +664,12 @@ enum Msg<'a> {
         error: io::ErrorKind,
     },
 
+    /// Every module is type checked, and the state is ready to be specialized for each target.
+    /// Only sent in [`ExecutionMode::ExecutableForTargets`].
+    FinishedAllTypeCheckingForTargets,
+    /// Start specializing a copy of a [`TypeCheckedProgram`]'s state.
+    SpecializeForTarget,
+
     FailedToLoad(LoadingProblem<'a>),
     IncorrectModuleName(FileError<'a, IncorrectModuleName<'a>>),
 }
@@ -640,7 +681,7 @@ struct CanAndCon {
     module_docs: Option<ModuleDocumentation>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum PlatformPath<'a> {
     NotSpecified,
     Valid(To<'a>),
@@ -649,7 +690,7 @@ enum PlatformPath<'a> {
     RootIsPlatformModule,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct PlatformData<'a> {
     module_id: ModuleId,
     provides: &'a [(Loc<ExposedName<'a>>, Loc<TypedIdent<'a>>)],
@@ -686,6 +727,10 @@ struct State<'a> {
     pub root_subs: Option<Subs>,
     pub root_path: PathBuf,
     pub cache_dir: PathBuf,
+    /// Resolves the names of packages in the project's manifest, in the headers of packages the
+    /// app depends on. The app's own header has these names replaced when it's parsed.
+    #[cfg(not(target_family = "wasm"))]
+    pub lockfile: Option<manifest::Lockfile>,
     /// If the root is an app module, the shorthand specified in its header's `to` field
     pub opt_platform_shorthand: Option<&'a str>,
     pub platform_data: Option<PlatformData<'a>>,
@@ -702,6 +747,7 @@ struct State<'a> {
     pub dependencies: Dependencies<'a>,
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
+    pub hosted_functions: HostedFunctions<'a>,
     pub toplevel_expects: MutMap<ModuleId, ToplevelExpects>,
     pub exposed_to_host: ExposedToHost,
 
@@ -732,6 +778,8 @@ struct State<'a> {
     pub render: RenderTarget,
     pub palette: Palette,
     pub exec_mode: ExecutionMode,
+    /// The number of `when` branches counted by `roc test --coverage` so far.
+    branch_counter_count: u64,
 
     /// All abilities across all modules.
     pub world_abilities: WorldAbilities,
@@ -765,17 +813,34 @@ impl<'a> State<'a> {
         palette: Palette,
         number_of_workers: usize,
         exec_mode: ExecutionMode,
+        roc_cache_dir: RocCacheDir<'_>,
     ) -> Self {
         let arc_shorthands = Arc::new(Mutex::new(MutMap::default()));
-        let cache_dir = roc_packaging::cache::roc_cache_dir();
+        let cache_dir = match roc_cache_dir.path() {
+            Some(path) => path.to_path_buf(),
+            None => roc_packaging::cache::roc_cache_dir(),
+        };
         let dependencies = Dependencies::new(exec_mode.goal_phase());
 
+        // Parsing the root module's header has brought the lockfile up to date already, and
+        // reported any problems with it.
+        #[cfg(not(target_family = "wasm"))]
+        let lockfile = root_path
+            .parent()
+            .and_then(manifest::find_manifest)
+            .and_then(|path| {
+                manifest::Lockfile::read(&path.with_file_name(manifest::LOCKFILE_NAME)).ok()
+            })
+            .flatten();
+
         Self {
             root_id,
             root_path,
             root_subs: None,
             opt_platform_shorthand,
             cache_dir,
+            #[cfg(not(target_family = "wasm"))]
+            lockfile,
             target_info,
             function_kind,
             platform_data: None,
@@ -784,6 +849,7 @@ impl<'a> State<'a> {
             dependencies,
             procedures: MutMap::default(),
             host_exposed_lambda_sets: std::vec::Vec::new(),
+            hosted_functions: std::vec::Vec::new(),
             toplevel_expects: MutMap::default(),
             exposed_to_host: ExposedToHost::default(),
             exposed_modules: &[],
@@ -801,11 +867,97 @@ impl<'a> State<'a> {
             render,
             palette,
             exec_mode,
+            branch_counter_count: 0,
             make_specializations_pass: MakeSpecializationsPass::Pass(1),
             world_abilities: Default::default(),
             layout_interner: GlobalLayoutInterner::with_capacity(128, target_info),
         }
     }
+
+    /// A copy of a state which has type checked every module (and specialized nothing yet),
+    /// with fresh layouts for the given target.
+    fn fork_for_target(&self, target_info: TargetInfo) -> Self {
+        let layout_interner = GlobalLayoutInterner::with_capacity(128, target_info);
+
+        let typechecked = self
+            .module_cache
+            .typechecked
+            .iter()
+            .map(|(module_id, module)| {
+                let module = TypeCheckedModule {
+                    module_id: module.module_id,
+                    layout_cache: LayoutCache::new(layout_interner.fork(), target_info),
+                    module_timing: module.module_timing.clone(),
+                    solved_subs: module.solved_subs.clone(),
+                    decls: module.decls.clone(),
+                    ident_ids: module.ident_ids.clone(),
+                    abilities_store: module.abilities_store.clone(),
+                    expectations: module.expectations.clone(),
+
+                    #[cfg(debug_assertions)]
+                    checkmate: None,
+                };
+
+                (*module_id, module)
+            })
+            .collect();
+
+        // Everything before solving is done with, and nothing after it has started yet.
+        let module_cache = ModuleCache {
+            module_names: self.module_cache.module_names.clone(),
+            typechecked,
+            imports: self.module_cache.imports.clone(),
+            exposes: self.module_cache.exposes.clone(),
+            exposed_imports: self.module_cache.exposed_imports.clone(),
+            top_level_thunks: self.module_cache.top_level_thunks.clone(),
+            can_problems: self.module_cache.can_problems.clone(),
+            type_problems: self.module_cache.type_problems.clone(),
+            sources: self.module_cache.sources.clone(),
+            ..ModuleCache::default()
+        };
+
+        Self {
+            root_id: self.root_id,
+            root_subs: self.root_subs.clone(),
+            root_path: self.root_path.clone(),
+            cache_dir: self.cache_dir.clone(),
+            #[cfg(not(target_family = "wasm"))]
+            lockfile: self.lockfile.clone(),
+            opt_platform_shorthand: self.opt_platform_shorthand,
+            platform_data: self.platform_data.clone(),
+            exposed_types: self.exposed_types.clone(),
+            platform_path: self.platform_path.clone(),
+            target_info,
+            function_kind: self.function_kind,
+            exposed_modules: self.exposed_modules,
+            module_cache,
+            dependencies: self.dependencies.clone(),
+            procedures: MutMap::default(),
+            host_exposed_lambda_sets: std::vec::Vec::new(),
+            hosted_functions: std::vec::Vec::new(),
+            toplevel_expects: MutMap::default(),
+            exposed_to_host: self.exposed_to_host.clone(),
+            constrained_ident_ids: self.constrained_ident_ids.clone(),
+            arc_modules: Arc::new(Mutex::new(self.arc_modules.lock().clone())),
+            arc_shorthands: Arc::new(Mutex::new(self.arc_shorthands.lock().clone())),
+            derived_module: Arc::new(std::sync::Mutex::new(
+                self.derived_module.lock().unwrap().clone(),
+            )),
+            ident_ids_by_module: Arc::new(Mutex::new(self.ident_ids_by_module.lock().clone())),
+            declarations_by_id: self.declarations_by_id.clone(),
+            exposed_symbols_by_module: self.exposed_symbols_by_module.clone(),
+            timings: self.timings.clone(),
+            layout_caches: std::vec::Vec::new(),
+            render: self.render,
+            palette: self.palette,
+            exec_mode: self.exec_mode,
+            branch_counter_count: self.branch_counter_count,
+            world_abilities: Default::default(),
+            make_specializations_pass: self.make_specializations_pass,
+            cached_types: Arc::clone(&self.cached_types),
+            layout_interner,
+        }
+    }
 }
 
 fn report_timing(
@@ -875,6 +1027,7 @@ enum BuildTask<'a> {
         abilities_store: PendingAbilitiesStore,
         exposed_module_ids: &'a [ModuleId],
         skip_constraint_gen: bool,
+        permit_shadowing: bool,
     },
     Solve {
         module: Module,
@@ -909,6 +1062,7 @@ enum BuildTask<'a> {
         derived_module: SharedDerivedModule,
         expectations: Option<Expectations>,
         build_expects: bool,
+        build_properties: bool,
     },
     MakeSpecializations {
         module_id: ModuleId,
@@ -1065,7 +1219,7 @@ pub fn load_and_typecheck_str<'a>(
         roc_cache_dir,
         load_config,
     )? {
-        Monomorphized(_) => unreachable!(),
+        Monomorphized(_) | TypeCheckedForTargets(_) => unreachable!(),
         TypeChecked(module) => Ok(module),
     }
 }
@@ -1076,6 +1230,10 @@ pub enum PrintTarget {
     Generic,
 }
 
+/// The sources of modules which differ from the files on disk, such as an editor's unsaved
+/// buffers, keyed by path. Modules at these paths are read from here instead of from disk.
+pub type VirtualFiles = MutMap<PathBuf, Box<str>>;
+
 pub struct LoadStart<'a> {
     arc_modules: Arc<Mutex<PackageModuleIds<'a>>>,
     ident_ids_by_module: SharedIdentIdsByModule,
@@ -1084,6 +1242,7 @@ pub struct LoadStart<'a> {
     root_msg: Msg<'a>,
     opt_platform_shorthand: Option<&'a str>,
     src_dir: PathBuf,
+    virtual_files: VirtualFiles,
 }
 
 impl<'a> LoadStart<'a> {
@@ -1106,6 +1265,7 @@ impl<'a> LoadStart<'a> {
             let res_loaded = load_filename(
                 arena,
                 filename.clone(),
+                &VirtualFiles::default(),
                 true,
                 None,
                 None,
@@ -1143,6 +1303,7 @@ impl<'a> LoadStart<'a> {
             root_path: filename,
             root_msg: header_output.msg,
             opt_platform_shorthand: header_output.opt_platform_shorthand,
+            virtual_files: VirtualFiles::default(),
         })
     }
 
@@ -1186,8 +1347,18 @@ impl<'a> LoadStart<'a> {
             root_path: filename,
             root_msg,
             opt_platform_shorthand: opt_platform_id,
+            virtual_files: VirtualFiles::default(),
         })
     }
+
+    /// Read the modules this loads from `virtual_files` where they have a source there,
+    /// rather than from disk.
+    pub fn with_virtual_files(self, virtual_files: VirtualFiles) -> Self {
+        LoadStart {
+            virtual_files,
+            ..self
+        }
+    }
 }
 
 fn adjust_header_paths<'a>(
@@ -1221,6 +1392,15 @@ fn adjust_header_paths<'a>(
 pub enum LoadResult<'a> {
     TypeChecked(LoadedModule),
     Monomorphized(MonomorphizedModule<'a>),
+    TypeCheckedForTargets(TypeCheckedProgram<'a>),
+}
+
+/// A program whose modules are all type checked, but not yet specialized for any target; see
+/// [`ExecutionMode::ExecutableForTargets`]. Specialize it with [`specialize_for_target`].
+#[derive(Debug)]
+pub struct TypeCheckedProgram<'a> {
+    state: State<'a>,
+    src_dir: PathBuf,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -1230,6 +1410,32 @@ pub enum Threading {
     AtMost(usize),
 }
 
+enum Threads {
+    Single,
+    Many(usize),
+}
+
+impl Threads {
+    fn new(threading: Threading) -> Self {
+        if cfg!(target_family = "wasm") {
+            // When compiling to wasm, we cannot spawn extra threads
+            // so we have a single-threaded implementation
+            Threads::Single
+        } else {
+            match std::thread::available_parallelism().map(|v| v.get()) {
+                Err(_) => Threads::Single,
+                Ok(0) => unreachable!("NonZeroUsize"),
+                Ok(1) => Threads::Single,
+                Ok(reported) => match threading {
+                    Threading::Single => Threads::Single,
+                    Threading::AllAvailable => Threads::Many(reported),
+                    Threading::AtMost(at_most) => Threads::Many(Ord::min(reported, at_most)),
+                },
+            }
+        }
+    }
+}
+
 /// The loading process works like this, starting from the given filename (e.g. "main.roc"):
 ///
 /// 1. Open the file.
@@ -1288,31 +1494,7 @@ pub fn load<'a>(
     roc_cache_dir: RocCacheDir<'_>,
     load_config: LoadConfig,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
-    enum Threads {
-        Single,
-        Many(usize),
-    }
-
-    let threads = {
-        if cfg!(target_family = "wasm") {
-            // When compiling to wasm, we cannot spawn extra threads
-            // so we have a single-threaded implementation
-            Threads::Single
-        } else {
-            match std::thread::available_parallelism().map(|v| v.get()) {
-                Err(_) => Threads::Single,
-                Ok(0) => unreachable!("NonZeroUsize"),
-                Ok(1) => Threads::Single,
-                Ok(reported) => match load_config.threading {
-                    Threading::Single => Threads::Single,
-                    Threading::AllAvailable => Threads::Many(reported),
-                    Threading::AtMost(at_most) => Threads::Many(Ord::min(reported, at_most)),
-                },
-            }
-        }
-    };
-
-    match threads {
+    match Threads::new(load_config.threading) {
         Threads::Single => load_single_threaded(
             arena,
             load_start,
@@ -1341,6 +1523,54 @@ pub fn load<'a>(
     }
 }
 
+/// Specialize a program loaded in [`ExecutionMode::ExecutableForTargets`] for the given
+/// target. The program can be specialized for any number of targets, one after the other.
+pub fn specialize_for_target<'a>(
+    arena: &'a Bump,
+    program: &TypeCheckedProgram<'a>,
+    target_info: TargetInfo,
+    threading: Threading,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> Result<MonomorphizedModule<'a>, LoadingProblem<'a>> {
+    let state = program.state.fork_for_target(target_info);
+
+    let (msg_tx, msg_rx) = bounded(1024);
+
+    msg_tx
+        .send(Msg::SpecializeForTarget)
+        .map_err(|_| LoadingProblem::ChannelProblem(ChannelProblem::FailedToSendRootMsg))?;
+
+    // Every module has been read already, so there's nothing left to read from these.
+    let virtual_files = VirtualFiles::default();
+
+    let load_result = match Threads::new(threading) {
+        Threads::Single => run_single_threaded(
+            arena,
+            state,
+            msg_tx,
+            msg_rx,
+            &program.src_dir,
+            &virtual_files,
+            roc_cache_dir,
+        ),
+        Threads::Many(threads) => run_multi_threaded(
+            arena,
+            state,
+            msg_tx,
+            msg_rx,
+            &program.src_dir,
+            &virtual_files,
+            number_of_workers(threads),
+            roc_cache_dir,
+        ),
+    }?;
+
+    match load_result {
+        LoadResult::Monomorphized(module) => Ok(module),
+        LoadResult::TypeChecked(_) | LoadResult::TypeCheckedForTargets(_) => unreachable!(),
+    }
+}
+
 /// Load using only a single thread; used when compiling to webassembly
 pub fn load_single_threaded<'a>(
     arena: &'a Bump,
@@ -1362,7 +1592,7 @@ pub fn load_single_threaded<'a>(
         root_msg,
         src_dir,
         opt_platform_shorthand,
-        ..
+        virtual_files,
     } = load_start;
 
     let (msg_tx, msg_rx) = bounded(1024);
@@ -1372,7 +1602,7 @@ pub fn load_single_threaded<'a>(
         .map_err(|_| LoadingProblem::ChannelProblem(ChannelProblem::FailedToSendRootMsg))?;
 
     let number_of_workers = 1;
-    let mut state = State::new(
+    let state = State::new(
         root_id,
         root_path,
         opt_platform_shorthand,
@@ -1386,8 +1616,32 @@ pub fn load_single_threaded<'a>(
         palette,
         number_of_workers,
         exec_mode,
+        roc_cache_dir,
     );
 
+    run_single_threaded(
+        arena,
+        state,
+        msg_tx,
+        msg_rx,
+        &src_dir,
+        &virtual_files,
+        roc_cache_dir,
+    )
+}
+
+/// Step the state and a single worker in turn, until the load is done.
+fn run_single_threaded<'a>(
+    arena: &'a Bump,
+    mut state: State<'a>,
+    msg_tx: MsgSender<'a>,
+    msg_rx: crossbeam::channel::Receiver<Msg<'a>>,
+    src_dir: &Path,
+    virtual_files: &VirtualFiles,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
+    let target_info = state.target_info;
+
     // We'll add tasks to this, and then worker threads will take tasks from it.
     let injector = Injector::new();
 
@@ -1404,7 +1658,7 @@ pub fn load_single_threaded<'a>(
         match state_thread_step(
             arena,
             state,
-            &src_dir,
+            src_dir,
             worker_listeners,
             &injector,
             &msg_tx,
@@ -1425,7 +1679,8 @@ pub fn load_single_threaded<'a>(
             stealers,
             &worker_msg_rx,
             &msg_tx,
-            &src_dir,
+            src_dir,
+            virtual_files,
             roc_cache_dir,
             target_info,
         );
@@ -1490,6 +1745,19 @@ fn state_thread_step<'a>(
 
                     Ok(ControlFlow::Break(LoadResult::TypeChecked(typechecked)))
                 }
+                Msg::FinishedAllTypeCheckingForTargets => {
+                    // We're done! There should be no more messages pending.
+                    debug_assert!(msg_rx.is_empty());
+
+                    let program = TypeCheckedProgram {
+                        state,
+                        src_dir: src_dir.to_path_buf(),
+                    };
+
+                    Ok(ControlFlow::Break(LoadResult::TypeCheckedForTargets(
+                        program,
+                    )))
+                }
                 Msg::FinishedAllSpecialization {
                     subs,
                     layout_interner,
@@ -1538,6 +1806,7 @@ fn state_thread_step<'a>(
                         filename,
                         bytes,
                         state.render,
+                        state.palette,
                     );
                     Err(LoadingProblem::FormattedReport(buf))
                 }
@@ -1593,6 +1862,7 @@ fn state_thread_step<'a>(
                                 cycle,
                                 filename,
                                 render,
+                                palette,
                             );
                             return Err(LoadingProblem::FormattedReport(buf));
                         }
@@ -1610,6 +1880,7 @@ fn state_thread_step<'a>(
                                 filename,
                                 bytes,
                                 render,
+                                palette,
                             );
                             return Err(LoadingProblem::FormattedReport(buf));
                         }
@@ -1643,7 +1914,14 @@ pub fn report_loading_problem(
         LoadingProblem::ImportCycle(filename, cycle) => {
             let root_exposed_ident_ids = IdentIds::exposed_builtins(0);
 
-            to_import_cycle_report(module_ids, root_exposed_ident_ids, cycle, filename, render)
+            to_import_cycle_report(
+                module_ids,
+                root_exposed_ident_ids,
+                cycle,
+                filename,
+                render,
+                palette,
+            )
         }
         LoadingProblem::IncorrectModuleName(FileError {
             problem: SourceError { problem, bytes },
@@ -1658,6 +1936,7 @@ pub fn report_loading_problem(
                 filename,
                 bytes,
                 render,
+                palette,
             )
         }
         LoadingProblem::FormattedReport(report) => report,
@@ -1689,7 +1968,7 @@ fn load_multi_threaded<'a>(
         root_msg,
         src_dir,
         opt_platform_shorthand,
-        ..
+        virtual_files,
     } = load_start;
 
     let (msg_tx, msg_rx) = bounded(1024);
@@ -1697,6 +1976,38 @@ fn load_multi_threaded<'a>(
         .send(root_msg)
         .map_err(|_| LoadingProblem::ChannelProblem(ChannelProblem::FailedToSendRootMsg))?;
 
+    let num_workers = number_of_workers(available_threads);
+
+    let state = State::new(
+        root_id,
+        root_path,
+        opt_platform_shorthand,
+        target_info,
+        function_kind,
+        exposed_types,
+        arc_modules,
+        ident_ids_by_module,
+        cached_types,
+        render,
+        palette,
+        num_workers,
+        exec_mode,
+        roc_cache_dir,
+    );
+
+    run_multi_threaded(
+        arena,
+        state,
+        msg_tx,
+        msg_rx,
+        &src_dir,
+        &virtual_files,
+        num_workers,
+        roc_cache_dir,
+    )
+}
+
+fn number_of_workers(available_threads: usize) -> usize {
     // Reserve one CPU for the main thread, and let all the others be eligible
     // to spawn workers.
     let available_workers = available_threads - 1;
@@ -1714,21 +2025,23 @@ fn load_multi_threaded<'a>(
         "`load_multi_threaded` needs at least one worker"
     );
 
-    let mut state = State::new(
-        root_id,
-        root_path,
-        opt_platform_shorthand,
-        target_info,
-        function_kind,
-        exposed_types,
-        arc_modules,
-        ident_ids_by_module,
-        cached_types,
-        render,
-        palette,
-        num_workers,
-        exec_mode,
-    );
+    num_workers
+}
+
+/// Run the given number of worker threads, and step the state on this one, until the load
+/// is done.
+#[allow(clippy::too_many_arguments)]
+fn run_multi_threaded<'a>(
+    arena: &'a Bump,
+    mut state: State<'a>,
+    msg_tx: MsgSender<'a>,
+    msg_rx: crossbeam::channel::Receiver<Msg<'a>>,
+    src_dir: &Path,
+    virtual_files: &VirtualFiles,
+    num_workers: usize,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
+    let target_info = state.target_info;
 
     // an arena for every worker, stored in an arena-allocated bumpalo vec to make the lifetimes work
     let arenas = std::iter::repeat_with(Bump::new).take(num_workers);
@@ -1775,9 +2088,8 @@ fn load_multi_threaded<'a>(
 
                 // We only want to move a *reference* to the main task queue's
                 // injector in the thread, not the injector itself
-                // (since other threads need to reference it too). Same with src_dir.
+                // (since other threads need to reference it too).
                 let injector = &injector;
-                let src_dir = &src_dir;
 
                 // Record this thread's handle so the main thread can join it later.
                 let res_join_handle = thread_scope
@@ -1793,6 +2105,7 @@ fn load_multi_threaded<'a>(
                             worker_msg_rx,
                             msg_tx,
                             src_dir,
+                            virtual_files,
                             roc_cache_dir,
                             target_info,
                         )
@@ -1833,7 +2146,7 @@ fn load_multi_threaded<'a>(
                 match state_thread_step(
                     arena,
                     state,
-                    &src_dir,
+                    src_dir,
                     worker_listeners,
                     &injector,
                     &msg_tx,
@@ -1922,6 +2235,7 @@ fn worker_task_step<'a>(
     worker_msg_rx: &crossbeam::channel::Receiver<WorkerMsg>,
     msg_tx: &MsgSender<'a>,
     src_dir: &Path,
+    virtual_files: &VirtualFiles,
     roc_cache_dir: RocCacheDir<'_>,
     target_info: TargetInfo,
 ) -> Result<ControlFlow<(), ()>, LoadingProblem<'a>> {
@@ -1950,6 +2264,7 @@ fn worker_task_step<'a>(
                             task,
                             worker_arena,
                             src_dir,
+                            virtual_files,
                             msg_tx.clone(),
                             roc_cache_dir,
                             target_info,
@@ -1996,6 +2311,7 @@ fn worker_task<'a>(
     worker_msg_rx: crossbeam::channel::Receiver<WorkerMsg>,
     msg_tx: MsgSender<'a>,
     src_dir: &Path,
+    virtual_files: &VirtualFiles,
     roc_cache_dir: RocCacheDir<'_>,
     target_info: TargetInfo,
 ) -> Result<(), LoadingProblem<'a>> {
@@ -2049,6 +2365,7 @@ fn worker_task<'a>(
                         task,
                         worker_arena,
                         src_dir,
+                        virtual_files,
                         msg_tx.clone(),
                         roc_cache_dir,
                         target_info,
@@ -2249,6 +2566,16 @@ fn update<'a>(
 
             let mut work = MutSet::default();
 
+            // Register which package each shorthand refers to, so that modules from the package
+            // get stable ids
+            {
+                let mut module_ids = (*state.arc_modules).lock();
+
+                for (shorthand, package_name) in header.packages.iter() {
+                    module_ids.register_package(shorthand, package_name.as_str());
+                }
+            }
+
             // Register the package's path under its shorthand
             // (e.g. for { pf: "blah" }, register that "pf" should resolve to "blah")
             {
@@ -2256,6 +2583,14 @@ fn update<'a>(
 
                 for (shorthand, package_name) in header.packages.iter() {
                     let package_str = package_name.as_str();
+
+                    #[cfg(not(target_family = "wasm"))]
+                    let package_str = state
+                        .lockfile
+                        .as_ref()
+                        .and_then(|lockfile| lockfile.url(package_str))
+                        .unwrap_or(package_str);
+
                     let shorthand_path = if package_str.starts_with("https://") {
                         #[cfg(not(target_family = "wasm"))]
                         {
@@ -2592,22 +2927,35 @@ fn update<'a>(
                 .exposes
                 .insert(module_id, solved_module.exposed_vars_by_symbol.clone());
 
-            let should_include_expects = (!loc_expects.is_empty() || !loc_dbgs.is_empty()) && {
-                let modules = state.arc_modules.lock();
-                modules
-                    .package_eq(module_id, state.root_id)
-                    .expect("root or this module is not yet known - that's a bug!")
-            };
+            let count_branches =
+                matches!(state.exec_mode, ExecutionMode::Test { coverage: true, .. });
+
+            let should_include_expects =
+                (count_branches || !loc_expects.is_empty() || !loc_dbgs.is_empty()) && {
+                    let modules = state.arc_modules.lock();
+                    modules
+                        .package_eq(module_id, state.root_id)
+                        .expect("root or this module is not yet known - that's a bug!")
+                };
 
             let opt_expectations = if should_include_expects {
                 let (path, _) = state.module_cache.sources.get(&module_id).unwrap();
 
+                let branch_counters = count_branches.then(|| {
+                    let counters = BranchCounters::new(&decls, state.branch_counter_count);
+                    state.branch_counter_count = counters.end();
+
+                    counters
+                });
+
                 Some(Expectations {
                     expectations: loc_expects,
                     dbgs: loc_dbgs,
                     subs: solved_subs.clone().into_inner(),
                     path: path.to_owned(),
                     ident_ids: ident_ids.clone(),
+                    branch_counters,
+                    properties: VecMap::default(),
                 })
             } else {
                 None
@@ -2624,7 +2972,7 @@ fn update<'a>(
 
             let add_to_host_exposed = is_host_exposed &&
                 // During testing, we don't need to expose anything to the host.
-                !matches!(state.exec_mode, ExecutionMode::Test);
+                !matches!(state.exec_mode, ExecutionMode::Test { .. });
 
             if add_to_host_exposed {
                 state.exposed_to_host.top_level_values.extend(
@@ -2649,7 +2997,8 @@ fn update<'a>(
             let finish_type_checking = is_host_exposed &&
                 (state.goal_phase() == Phase::SolveTypes)
                 // If we're running in check-and-then-build mode, only exit now there are errors.
-                && (!state.exec_mode.build_if_checks() || state.module_cache.has_errors());
+                && (!state.exec_mode.build_if_checks()
+                    || (state.exec_mode.stops_on_errors() && state.module_cache.has_errors()));
 
             if finish_type_checking {
                 debug_assert!(work.is_empty());
@@ -2760,10 +3109,23 @@ fn update<'a>(
                         "work left over after host exposed is checked"
                     );
 
-                    // Load the find + make specializations portion of the dependency graph.
-                    state
-                        .dependencies
-                        .load_find_and_make_specializations_after_check()
+                    if let ExecutionMode::ExecutableForTargets = state.exec_mode {
+                        // Each target specializes its own copy of the state from here on.
+                        msg_tx
+                            .send(Msg::FinishedAllTypeCheckingForTargets)
+                            .map_err(|_| {
+                                LoadingProblem::ChannelProblem(
+                                    ChannelProblem::FailedToSendFinishedTypeCheckingMsg,
+                                )
+                            })?;
+
+                        MutSet::default()
+                    } else {
+                        // Load the find + make specializations portion of the dependency graph.
+                        state
+                            .dependencies
+                            .load_find_and_make_specializations_after_check()
+                    }
                 } else {
                     work
                 };
@@ -2787,7 +3149,10 @@ fn update<'a>(
 
             let subs = solved_subs.into_inner();
 
-            if !toplevel_expects.pure.is_empty() || !toplevel_expects.fx.is_empty() {
+            if !toplevel_expects.pure.is_empty()
+                || !toplevel_expects.fx.is_empty()
+                || !toplevel_expects.properties.is_empty()
+            {
                 state.toplevel_expects.insert(module_id, toplevel_expects);
             }
 
@@ -2828,6 +3193,7 @@ fn update<'a>(
             procs_base,
             procedures,
             host_exposed_lambda_sets,
+            hosted_functions,
             external_specializations_requested,
             module_timing,
             layout_cache,
@@ -2848,6 +3214,17 @@ fn update<'a>(
             state
                 .host_exposed_lambda_sets
                 .extend(host_exposed_lambda_sets);
+
+            for function in hosted_functions {
+                let known = state
+                    .hosted_functions
+                    .iter()
+                    .any(|known| known.foreign_symbol == function.foreign_symbol);
+
+                if !known {
+                    state.hosted_functions.push(function);
+                }
+            }
             state.module_cache.late_specializations.insert(
                 module_id,
                 LateSpecializationsModule {
@@ -2975,10 +3352,25 @@ fn update<'a>(
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_TRMC);
 
+                    let host_exposed_procs = state
+                        .exposed_to_host
+                        .top_level_values
+                        .keys()
+                        .chain(state.exposed_to_host.getters.iter())
+                        .copied()
+                        .chain(
+                            // The host calls the generated callers, which own their arguments
+                            state.host_exposed_lambda_sets.iter().flat_map(
+                                |(lambda_name, _, hels)| [lambda_name.name(), hels.symbol],
+                            ),
+                        )
+                        .collect::<std::vec::Vec<_>>();
+
                     inc_dec::insert_inc_dec_operations(
                         arena,
                         &layout_interner,
                         &mut state.procedures,
+                        &host_exposed_procs,
                     );
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_REFCOUNT);
@@ -3122,9 +3514,23 @@ fn update<'a>(
             // TODO report the error and continue instead of erroring out
             Err(problem)
         }
+        Msg::SpecializeForTarget => {
+            // Load the find + make specializations portion of the dependency graph, just like
+            // ExecutableIfCheck does once the host exposed module is checked.
+            let work = state
+                .dependencies
+                .load_find_and_make_specializations_after_check();
+
+            start_tasks(arena, &mut state, work, injector, worker_listeners)?;
+
+            Ok(state)
+        }
         Msg::FinishedAllTypeChecking { .. } => {
             unreachable!();
         }
+        Msg::FinishedAllTypeCheckingForTargets => {
+            unreachable!();
+        }
         Msg::FinishedAllSpecialization { .. } => {
             unreachable!();
         }
@@ -3201,8 +3607,11 @@ fn finish_specialization<'a>(
     let entry_point = {
         let interns: &mut Interns = &mut interns;
         match state.exec_mode {
-            ExecutionMode::Test => Ok(EntryPoint::Test),
-            ExecutionMode::Executable | ExecutionMode::ExecutableIfCheck => {
+            ExecutionMode::Test { .. } => Ok(EntryPoint::Test),
+            ExecutionMode::Executable
+            | ExecutionMode::Repl
+            | ExecutionMode::ExecutableIfCheck
+            | ExecutionMode::ExecutableForTargets => {
                 use PlatformPath::*;
 
                 let platform_path = match &state.platform_path {
@@ -3214,7 +3623,8 @@ fn finish_specialization<'a>(
                     }
                     Valid(To::NewPackage(p_or_p)) => PathBuf::from(p_or_p.as_str()),
                     other => {
-                        let buf = report_cannot_run(state.root_id, state.root_path, other);
+                        let buf =
+                            report_cannot_run(state.root_id, state.root_path, other, state.palette);
                         return Err(LoadingProblem::FormattedReport(buf));
                     }
                 };
@@ -3264,10 +3674,16 @@ fn finish_specialization<'a>(
         }
     }?;
 
+    let imported_symbols = imported_symbols(
+        &state.module_cache.imports,
+        &state.exposed_symbols_by_module,
+    );
+
     let State {
         toplevel_expects,
         procedures,
         host_exposed_lambda_sets,
+        hosted_functions,
         module_cache,
         platform_data,
         ..
@@ -3296,6 +3712,7 @@ fn finish_specialization<'a>(
     Ok(MonomorphizedModule {
         can_problems,
         type_problems,
+        imported_symbols,
         expectations: module_expectations,
         exposed_to_host,
         module_id,
@@ -3304,6 +3721,7 @@ fn finish_specialization<'a>(
         layout_interner,
         procedures,
         host_exposed_lambda_sets,
+        hosted_functions,
         entry_point,
         sources,
         timings: state.timings,
@@ -3331,6 +3749,28 @@ fn proc_layout_for<'a>(
     }
 }
 
+/// For each module, the symbols exposed by the modules it imports. Reports use these to
+/// suggest e.g. `List.map` for `Lst.map`, since they are not in the module's scope.
+fn imported_symbols(
+    imports: &MutMap<ModuleId, MutSet<ModuleId>>,
+    exposed_symbols_by_module: &MutMap<ModuleId, VecSet<Symbol>>,
+) -> MutMap<ModuleId, Vec<Symbol>> {
+    imports
+        .iter()
+        .map(|(module_id, imported_modules)| {
+            let mut symbols: Vec<Symbol> = imported_modules
+                .iter()
+                .filter_map(|imported| exposed_symbols_by_module.get(imported))
+                .flat_map(|exposed| exposed.iter().copied())
+                .collect();
+
+            symbols.sort();
+
+            (*module_id, symbols)
+        })
+        .collect()
+}
+
 #[allow(clippy::too_many_arguments)]
 fn finish(
     mut state: State,
@@ -3377,6 +3817,11 @@ fn finish(
 
     let declarations_by_id = state.declarations_by_id;
 
+    let imported_symbols = imported_symbols(
+        &state.module_cache.imports,
+        &state.exposed_symbols_by_module,
+    );
+
     roc_checkmate::dump_checkmate!(checkmate);
 
     LoadedModule {
@@ -3385,6 +3830,7 @@ fn finish(
         solved,
         can_problems: state.module_cache.can_problems,
         type_problems: state.module_cache.type_problems,
+        imported_symbols,
         declarations_by_id,
         typechecked: state.module_cache.checked,
         dep_idents,
@@ -3620,6 +4066,7 @@ fn load_builtin_module<'a>(
 fn load_module<'a>(
     arena: &'a Bump,
     src_dir: &Path,
+    virtual_files: &VirtualFiles,
     module_name: PQModuleName<'a>,
     module_ids: Arc<Mutex<PackageModuleIds<'a>>>,
     arc_shorthands: Arc<Mutex<MutMap<&'a str, ShorthandPath>>>,
@@ -3679,6 +4126,7 @@ fn load_module<'a>(
     load_filename(
         arena,
         filename,
+        virtual_files,
         false,
         opt_shorthand,
         Some(module_name),
@@ -3689,7 +4137,7 @@ fn load_module<'a>(
     )
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum ShorthandPath {
     /// e.g. "/home/rtfeldman/.cache/roc/0.1.0/oUkxSOI9zFGtSoIaMB40QPdrXphr1p1780eiui2iO9Mz"
     FromHttpsUrl {
@@ -3958,17 +4406,43 @@ fn parse_header<'a>(
             },
             parse_state,
         )) => {
+            let mut imports = bumpalo::collections::Vec::from_iter_in(
+                unspace(arena, header.imports.item.items).iter().copied(),
+                arena,
+            );
+            let mut more_generates =
+                bumpalo::collections::Vec::with_capacity_in(header.more_generates.len(), arena);
+
+            for clause in header.more_generates {
+                let effect = clause.generates.item;
+                let effect_name: &str = effect.into();
+
+                // Each further effect type lives in a synthetic module of its own; see
+                // `synthesize_hosted_effect_module`. Import its type so annotations can use it.
+                let module_name =
+                    arena.alloc(format!("{}.{}", header.name.value.as_str(), effect_name));
+                let exposed =
+                    arena.alloc([Loc::at_zero(Spaced::Item(ExposedName::new(effect_name)))]);
+
+                imports.push(Loc::at_zero(ImportsEntry::Module(
+                    roc_parse::header::ModuleName::new(module_name),
+                    ast::Collection::with_items(exposed),
+                )));
+                more_generates.push((effect, unspace(arena, clause.generates_with.item.items)));
+            }
+
             let info = HeaderInfo {
                 filename,
                 is_root_module,
                 opt_shorthand,
                 packages: &[],
-                imports: unspace(arena, header.imports.item.items),
+                imports: imports.into_bump_slice(),
                 header_type: HeaderType::Hosted {
                     name: header.name.value,
                     exposes: unspace(arena, header.exposes.item.items),
                     generates: header.generates.item,
                     generates_with: unspace(arena, header.generates_with.item.items),
+                    more_generates: more_generates.into_bump_slice(),
                 },
                 module_comments: comments,
             };
@@ -4003,6 +4477,13 @@ fn parse_header<'a>(
             } else {
                 &[]
             };
+
+            #[cfg(not(target_family = "wasm"))]
+            let packages = if is_root_module {
+                resolve_manifest_packages(arena, &app_file_dir, packages, roc_cache_dir, &filename)?
+            } else {
+                packages
+            };
             let imports = if let Some(imports) = header.imports {
                 unspace(arena, imports.item.items)
             } else {
@@ -4235,10 +4716,44 @@ fn load_packages<'a>(
     }
 }
 
+/// Lets the app's header refer to the packages in the project's manifest by name, by replacing
+/// those names with the URLs they are locked to.
+#[cfg(not(target_family = "wasm"))]
+fn resolve_manifest_packages<'a>(
+    arena: &'a Bump,
+    app_file_dir: &Path,
+    packages: &'a [Loc<PackageEntry<'a>>],
+    roc_cache_dir: RocCacheDir<'_>,
+    filename: &Path,
+) -> Result<&'a [Loc<PackageEntry<'a>>], LoadingProblem<'a>> {
+    let lockfile = match manifest::lock_project(app_file_dir, roc_cache_dir) {
+        Ok(Some(lockfile)) => lockfile,
+        Ok(None) => return Ok(packages),
+        Err(problem) => {
+            let buf = to_manifest_problem_report_string(problem, filename.to_path_buf());
+
+            return Err(LoadingProblem::FormattedReport(buf));
+        }
+    };
+
+    let resolved = packages.iter().map(|loc_entry| {
+        let mut entry = loc_entry.value;
+
+        if let Some(url) = lockfile.url(entry.package_name.value.as_str()) {
+            entry.package_name.value = PackageName::from(&*arena.alloc_str(url));
+        }
+
+        Loc::at(loc_entry.region, entry)
+    });
+
+    Ok(bumpalo::collections::Vec::from_iter_in(resolved, arena).into_bump_slice())
+}
+
 /// Load a module by its filename
 fn load_filename<'a>(
     arena: &'a Bump,
     filename: PathBuf,
+    virtual_files: &VirtualFiles,
     is_root_module: bool,
     opt_shorthand: Option<&'a str>,
     opt_expected_module_name: Option<PackageQualified<'a, ModuleName>>,
@@ -4248,9 +4763,26 @@ fn load_filename<'a>(
     module_start_time: Instant,
 ) -> Result<HeaderOutput<'a>, LoadingProblem<'a>> {
     let file_io_start = Instant::now();
-    let file = fs::read(&filename);
+    let file = match virtual_files.get(&filename) {
+        Some(source) => Ok(source.as_bytes().to_vec()),
+        None => fs::read(&filename),
+    };
     let file_io_duration = file_io_start.elapsed();
 
+    let file = match file {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            synthesize_hosted_effect_module(arena, &filename, virtual_files, &[]).ok_or(err)
+        }
+        // A file without a header may be the companion of a generated effect module
+        Ok(bytes) if !has_header(arena, &bytes) => {
+            Ok(
+                synthesize_hosted_effect_module(arena, &filename, virtual_files, &bytes)
+                    .unwrap_or(bytes),
+            )
+        }
+        other => other,
+    };
+
     match file {
         Ok(bytes) => parse_header(
             arena,
@@ -4272,6 +4804,97 @@ fn load_filename<'a>(
     }
 }
 
+/// A hosted module `Effect` that also says `generates Stream with [after, map]` gets a module
+/// `Effect.Stream` defining that effect type and its functions, as though there were a file
+/// `Effect/Stream.roc` containing
+///
+/// ```roc
+/// hosted Effect.Stream exposes [Stream, after, map] imports [] generates Stream with [after, map]
+/// ```
+///
+/// A file `Effect/Stream.roc` without a header is a companion of that module instead: its defs
+/// follow the generated header and are exposed too, so that combinators can be written in Roc
+/// using the private `@Stream` constructor.
+fn synthesize_hosted_effect_module(
+    arena: &Bump,
+    filename: &Path,
+    virtual_files: &VirtualFiles,
+    companion: &[u8],
+) -> Option<Vec<u8>> {
+    let effect = filename.file_stem()?.to_str()?;
+    let hosted_filename = filename.parent()?.with_extension(ROC_FILE_EXTENSION);
+
+    let hosted_src = match virtual_files.get(&hosted_filename) {
+        Some(source) => source.as_bytes().to_vec(),
+        None => fs::read(&hosted_filename).ok()?,
+    };
+
+    let parse_state = roc_parse::state::State::new(arena.alloc(hosted_src));
+    let header = match roc_parse::module::parse_header(arena, parse_state) {
+        Ok((
+            ast::Module {
+                header: ast::Header::Hosted(header),
+                ..
+            },
+            _,
+        )) => header,
+        _ => return None,
+    };
+
+    let clause = header
+        .more_generates
+        .iter()
+        .find(|clause| <&str>::from(clause.generates.item) == effect)?;
+
+    let functions: Vec<&str> = clause
+        .generates_with
+        .item
+        .iter()
+        .map(|name| name.value.item().as_str())
+        .collect();
+
+    let companion_defs = module_defs()
+        .parse(arena, roc_parse::state::State::new(companion), 0)
+        .map(|(_, defs, _)| defs)
+        .unwrap_or_default();
+
+    let exposes: Vec<&str> = std::iter::once(effect)
+        .chain(functions.iter().copied())
+        .chain(companion_defs.value_defs.iter().filter_map(defined_name))
+        .collect();
+
+    let src = format!(
+        "hosted {}.{effect} exposes [{}] imports [] generates {effect} with [{}]\n",
+        header.name.value.as_str(),
+        exposes.join(", "),
+        functions.join(", "),
+    );
+
+    let mut src = src.into_bytes();
+    src.extend_from_slice(companion);
+
+    Some(src)
+}
+
+fn has_header(arena: &Bump, src: &[u8]) -> bool {
+    roc_parse::module::parse_header(arena, roc_parse::state::State::new(src)).is_ok()
+}
+
+/// The name that a top-level def introduces, if it's a plain identifier
+fn defined_name<'a>(def: &ValueDef<'a>) -> Option<&'a str> {
+    let pattern = match def {
+        ValueDef::Annotation(pattern, _) => pattern,
+        ValueDef::Body(pattern, _) => *pattern,
+        ValueDef::AnnotatedBody { body_pattern, .. } => *body_pattern,
+        _ => return None,
+    };
+
+    match pattern.value.extract_spaces().item {
+        Pattern::Identifier(name) => Some(name),
+        _ => None,
+    }
+}
+
 /// Load a module from a str
 /// the `filename` is never read, but used for the module name
 fn load_from_str<'a>(
@@ -5322,7 +5945,21 @@ fn build_platform_header<'a>(
     )
 }
 
+/// How `dbg` refers to a module's file in the compiled program: relative to the app's source
+/// directory, or to the package cache for the packages it downloaded. That way the program does
+/// not depend on where the project and the cache happen to be on the machine that built it.
+fn dbg_module_path(module_path: &Path, src_dir: &Path, roc_cache_dir: RocCacheDir<'_>) -> String {
+    let relative = module_path
+        .strip_prefix(src_dir)
+        .ok()
+        .or_else(|| module_path.strip_prefix(roc_cache_dir.path()?).ok())
+        .unwrap_or(module_path);
+
+    relative.to_string_lossy().into_owned()
+}
+
 #[allow(clippy::unnecessary_wraps)]
+#[allow(clippy::too_many_arguments)]
 fn canonicalize_and_constrain<'a>(
     arena: &'a Bump,
     module_ids: &ModuleIds,
@@ -5331,14 +5968,15 @@ fn canonicalize_and_constrain<'a>(
     aliases: MutMap<Symbol, Alias>,
     imported_abilities_state: PendingAbilitiesStore,
     parsed: ParsedModule<'a>,
+    dbg_module_path: &str,
     skip_constraint_gen: bool,
+    permit_shadowing: bool,
     exposed_module_ids: &[ModuleId],
 ) -> CanAndCon {
     let canonicalize_start = Instant::now();
 
     let ParsedModule {
         module_id,
-        module_path,
         src,
         header_type,
         exposed_ident_ids,
@@ -5362,7 +6000,7 @@ fn canonicalize_and_constrain<'a>(
         parsed_defs,
         &header_type,
         module_id,
-        &*arena.alloc(module_path.to_string_lossy()),
+        dbg_module_path,
         src,
         module_ids,
         exposed_ident_ids,
@@ -5372,6 +6010,7 @@ fn canonicalize_and_constrain<'a>(
         exposed_imports,
         exposed_symbols,
         &symbols_from_requires,
+        permit_shadowing,
         &mut var_store,
     );
     let mut types = Types::new();
@@ -5394,6 +6033,10 @@ fn canonicalize_and_constrain<'a>(
     // Generate documentation information
     // TODO: store timing information?
     let module_docs = {
+        let generates_with = match header_type {
+            HeaderType::Hosted { generates_with, .. } => generates_with,
+            _ => &[],
+        };
         let module_name = header_type.get_name();
         module_name.map(|module_name| {
             let mut scope = module_output.scope.clone();
@@ -5407,6 +6050,7 @@ fn canonicalize_and_constrain<'a>(
                 exposed_module_ids,
                 module_output.exposed_symbols.clone(),
                 parsed.header_comments,
+                generates_with,
             )
         })
     };
@@ -5685,10 +6329,18 @@ fn make_specializations<'a>(
     let make_specializations_start = Instant::now();
     let mut update_mode_ids = UpdateModeIds::new();
     // do the thing
+    let (expectation_subs, branch_counters, properties) = match expectations.as_mut() {
+        Some(e) => (
+            Some(&mut e.subs),
+            e.branch_counters.as_ref(),
+            Some(&e.properties),
+        ),
+        None => (None, None, None),
+    };
     let mut mono_env = roc_mono::ir::Env {
         arena,
         subs: &mut subs,
-        expectation_subs: expectations.as_mut().map(|e| &mut e.subs),
+        expectation_subs,
         home,
         ident_ids: &mut ident_ids,
         target_info,
@@ -5699,6 +6351,7 @@ fn make_specializations<'a>(
         exposed_by_module,
         derived_module: &derived_module,
         struct_indexing: UsageTrackingMap::default(),
+        branch_counters,
     };
 
     let mut procs = Procs::new_in(arena);
@@ -5727,9 +6380,40 @@ fn make_specializations<'a>(
     );
 
     let external_specializations_requested = procs.externals_we_need.clone();
-    let (procedures, host_exposed_lambda_sets, restored_procs_base) =
+    let (mut procedures, host_exposed_lambda_sets, hosted_functions, restored_procs_base) =
         procs.get_specialized_procs_without_rc();
 
+    // Properties are specialized like host-exposed functions, in the first pass.
+    for (thunk, property) in properties
+        .into_iter()
+        .flat_map(|properties| properties.iter())
+    {
+        let key = (*thunk, roc_mono::property::PROPERTY_THUNK_LAYOUT);
+
+        if procedures.contains_key(&key) {
+            continue;
+        }
+
+        let Ok(raw_layout) = layout_cache.raw_from_var(arena, property.var, mono_env.subs) else {
+            continue;
+        };
+
+        let function_name = LambdaName::no_niche(property.function);
+        let function_layout = ProcLayout::from_raw_named(arena, function_name, raw_layout);
+
+        if procedures.contains_key(&(property.function, function_layout)) {
+            let proc = roc_mono::property::generate_property_thunk(
+                &mut mono_env,
+                &mut layout_cache,
+                *thunk,
+                property.function,
+                function_layout,
+            );
+
+            procedures.insert(key, proc);
+        }
+    }
+
     // Turn `Bytes.Decode.IdentId(238)` into `Bytes.Decode.238`, we rely on this in mono tests
     mono_env.home.register_debug_idents(mono_env.ident_ids);
 
@@ -5745,6 +6429,7 @@ fn make_specializations<'a>(
         procs_base: restored_procs_base,
         procedures,
         host_exposed_lambda_sets,
+        hosted_functions,
         update_mode_ids,
         subs,
         expectations,
@@ -5769,6 +6454,7 @@ fn build_pending_specializations<'a>(
     derived_module: SharedDerivedModule,
     mut expectations: Option<Expectations>,
     build_expects: bool,
+    build_properties: bool,
 ) -> Msg<'a> {
     let find_specializations_start = Instant::now();
 
@@ -5785,10 +6471,18 @@ fn build_pending_specializations<'a>(
 
     let mut update_mode_ids = UpdateModeIds::new();
     let mut subs = solved_subs.into_inner();
+    let (expectation_subs, branch_counters, mut properties) = match expectations.as_mut() {
+        Some(e) => (
+            Some(&mut e.subs),
+            e.branch_counters.as_ref(),
+            Some(&mut e.properties),
+        ),
+        None => (None, None, None),
+    };
     let mut mono_env = roc_mono::ir::Env {
         arena,
         subs: &mut subs,
-        expectation_subs: expectations.as_mut().map(|e| &mut e.subs),
+        expectation_subs,
         home,
         ident_ids: &mut ident_ids,
         target_info,
@@ -5802,6 +6496,7 @@ fn build_pending_specializations<'a>(
         exposed_by_module,
         derived_module: &derived_module,
         struct_indexing: UsageTrackingMap::default(),
+        branch_counters,
     };
 
     let layout_cache_snapshot = layout_cache.snapshot();
@@ -5944,6 +6639,50 @@ fn build_pending_specializations<'a>(
                     );
                 }
 
+                // A function with an expect in it is a property, which the test runner calls with
+                // arguments it generates. For that, it must be specialized like a host-exposed one.
+                let is_property = build_properties
+                    && !function_def.arguments.is_empty()
+                    && roc_mono::property::has_expect(&body.value, function_def.return_type)
+                    && layout_cache
+                        .raw_from_var(mono_env.arena, expr_var, mono_env.subs)
+                        .is_ok();
+
+                if let Some(properties) = properties.as_mut().filter(|_| is_property) {
+                    if !is_host_exposed {
+                        procs_base.host_specializations.insert_host_exposed(
+                            mono_env.subs,
+                            LambdaName::no_niche(symbol),
+                            None,
+                            expr_var,
+                        );
+                    }
+
+                    let arguments = function_def
+                        .arguments
+                        .iter()
+                        .map(|(var, _, pattern)| match pattern.value {
+                            roc_can::pattern::Pattern::Identifier(symbol) => (*var, Some(symbol)),
+                            _ => (*var, None),
+                        })
+                        .collect();
+
+                    let name_region = declarations.symbols[index].region;
+                    let expr_region = declarations.expressions[index].region;
+                    let region = Region::span_across(&name_region, &expr_region);
+
+                    let thunk = mono_env.unique_symbol();
+                    toplevel_expects.properties.insert(thunk, region);
+                    properties.insert(
+                        thunk,
+                        Property {
+                            function: symbol,
+                            var: expr_var,
+                            arguments,
+                        },
+                    );
+                }
+
                 let is_recursive = matches!(tag, Recursive(_) | TailRecursive(_));
 
                 register_toplevel_function_into_procs_base(
@@ -6283,6 +7022,7 @@ fn load_derived_partial_procs<'a>(
             exposed_by_module,
             derived_module,
             struct_indexing: UsageTrackingMap::default(),
+            branch_counters: None,
         };
 
         let partial_proc = match derived_expr {
@@ -6344,6 +7084,7 @@ fn run_task<'a>(
     task: BuildTask<'a>,
     arena: &'a Bump,
     src_dir: &Path,
+    virtual_files: &VirtualFiles,
     msg_tx: MsgSender<'a>,
     roc_cache_dir: RocCacheDir<'_>,
     target_info: TargetInfo,
@@ -6359,6 +7100,7 @@ fn run_task<'a>(
         } => load_module(
             arena,
             src_dir,
+            virtual_files,
             module_name,
             module_ids,
             shorthands,
@@ -6375,8 +7117,11 @@ fn run_task<'a>(
             aliases,
             abilities_store,
             skip_constraint_gen,
+            permit_shadowing,
             exposed_module_ids,
         } => {
+            let dbg_module_path = dbg_module_path(&parsed.module_path, src_dir, roc_cache_dir);
+
             let can_and_con = canonicalize_and_constrain(
                 arena,
                 &module_ids,
@@ -6385,7 +7130,9 @@ fn run_task<'a>(
                 aliases,
                 abilities_store,
                 parsed,
+                &dbg_module_path,
                 skip_constraint_gen,
+                permit_shadowing,
                 exposed_module_ids,
             );
 
@@ -6442,6 +7189,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            build_properties,
         } => Ok(build_pending_specializations(
             arena,
             solved_subs,
@@ -6458,6 +7206,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            build_properties,
         )),
         MakeSpecializations {
             module_id,
@@ -6501,8 +7250,9 @@ fn to_import_cycle_report(
     import_cycle: Vec<ModuleId>,
     filename: PathBuf,
     render: RenderTarget,
+    palette: Palette,
 ) -> String {
-    use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_reporting::report::{Report, RocDocAllocator};
     use ven_pretty::DocAllocator;
 
     // import_cycle looks like CycleModule, Import1, ..., ImportN, CycleModule
@@ -6546,10 +7296,11 @@ fn to_import_cycle_report(
         doc,
         title: "IMPORT CYCLE".to_string(),
         severity: Severity::RuntimeError,
+        fixes: Vec::new(),
+        code: None,
     };
 
     let mut buf = String::new();
-    let palette = DEFAULT_PALETTE;
     report.render(render, &mut buf, &alloc, &palette);
     buf
 }
@@ -6561,8 +7312,9 @@ fn to_incorrect_module_name_report<'a>(
     filename: PathBuf,
     src: &'a [u8],
     render: RenderTarget,
+    palette: Palette,
 ) -> String {
-    use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_reporting::report::{Report, RocDocAllocator};
     use ven_pretty::DocAllocator;
 
     let IncorrectModuleName {
@@ -6595,10 +7347,11 @@ fn to_incorrect_module_name_report<'a>(
         doc,
         title: "INCORRECT MODULE NAME".to_string(),
         severity: Severity::RuntimeError,
+        fixes: Vec::new(),
+        code: None,
     };
 
     let mut buf = String::new();
-    let palette = DEFAULT_PALETTE;
     report.render(render, &mut buf, &alloc, &palette);
     buf
 }
@@ -6651,8 +7404,9 @@ fn report_cannot_run(
     module_id: ModuleId,
     filename: PathBuf,
     platform_path: &PlatformPath,
+    palette: Palette,
 ) -> String {
-    use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_reporting::report::{Report, RocDocAllocator};
     use ven_pretty::DocAllocator;
     use PlatformPath::*;
 
@@ -6678,6 +7432,8 @@ fn report_cannot_run(
                     doc,
                     title: "NO PLATFORM".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             RootIsInterface => {
@@ -6693,6 +7449,8 @@ fn report_cannot_run(
                     doc,
                     title: "NO PLATFORM".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             RootIsHosted => {
@@ -6708,6 +7466,8 @@ fn report_cannot_run(
                     doc,
                     title: "NO PLATFORM".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             RootIsPlatformModule => {
@@ -6723,12 +7483,13 @@ fn report_cannot_run(
                     doc,
                     title: "NO PLATFORM".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
         }
     };
 
-    let palette = DEFAULT_PALETTE;
     let mut buf = String::new();
     report.render_color_terminal(&mut buf, &alloc, &palette);
 
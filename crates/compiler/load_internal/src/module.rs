@@ -11,11 +11,14 @@ use roc_module::ident::Ident;
 use roc_module::symbol::{
     IdentIds, IdentIdsByModule, Interns, ModuleId, PQModuleName, PackageQualified, Symbol,
 };
-use roc_mono::ir::{GlueLayouts, HostExposedLambdaSets, LambdaSetId, Proc, ProcLayout, ProcsBase};
+use roc_mono::coverage::BranchCounters;
+use roc_mono::ir::{
+    GlueLayouts, HostExposedLambdaSets, HostedFunctions, LambdaSetId, Proc, ProcLayout, ProcsBase,
+};
 use roc_mono::layout::{LayoutCache, STLayoutInterner};
 use roc_parse::ast::{CommentOrNewline, Defs, TypeAnnotation, ValueDef};
 use roc_parse::header::{HeaderType, PackageName};
-use roc_region::all::{Loc, Region};
+use roc_region::all::{Loc, Position, Region};
 use roc_solve::module::Solved;
 use roc_solve_problem::TypeError;
 use roc_types::subs::{ExposedTypesStorageSubs, Subs, VarStore, Variable};
@@ -34,6 +37,8 @@ pub struct LoadedModule {
     pub solved: Solved<Subs>,
     pub can_problems: MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     pub type_problems: MutMap<ModuleId, Vec<TypeError>>,
+    /// For each module, the symbols exposed by the modules it imports
+    pub imported_symbols: MutMap<ModuleId, Vec<Symbol>>,
     pub declarations_by_id: MutMap<ModuleId, Declarations>,
     pub exposed_to_host: MutMap<Symbol, Variable>,
     pub dep_idents: IdentIdsByModule,
@@ -81,6 +86,49 @@ impl LoadedModule {
             .map(|symbol| symbol.as_str(&self.interns))
             .collect()
     }
+
+    /// The solved type of the narrowest expression containing `position` in the given module,
+    /// along with that expression's region. Aliases are printed by name, not unfolded, and a
+    /// lookup has the type its symbol was defined with, so it keeps the aliases from there.
+    pub fn type_at(&mut self, module_id: ModuleId, position: Position) -> Option<(Region, String)> {
+        let (decls, subs) = if module_id == self.module_id {
+            (
+                self.declarations_by_id.get(&module_id)?,
+                self.solved.inner_mut(),
+            )
+        } else {
+            let checked = self.typechecked.get_mut(&module_id)?;
+
+            (&checked.decls, checked.solved_subs.inner_mut())
+        };
+
+        type_at(decls, subs, module_id, &self.interns, position)
+    }
+}
+
+/// [LoadedModule::type_at] for a module whose declarations and solved subs are kept elsewhere,
+/// like in the language server.
+pub fn type_at(
+    decls: &Declarations,
+    subs: &mut Subs,
+    module_id: ModuleId,
+    interns: &Interns,
+    position: Position,
+) -> Option<(Region, String)> {
+    let (region, var) = roc_can::traverse::find_closest_declared_type_at(position, decls)?;
+
+    // Naming the type's variables changes subs, so undo that afterwards.
+    let snapshot = subs.snapshot();
+    let type_str = roc_types::pretty_print::name_and_print_var(
+        var,
+        subs,
+        module_id,
+        interns,
+        roc_types::pretty_print::DebugPrint::NOTHING,
+    );
+    subs.rollback_to(snapshot);
+
+    Some((region, type_str))
 }
 
 #[derive(Debug)]
@@ -166,6 +214,8 @@ pub(crate) struct LateSpecializationsModule<'a> {
 pub struct ToplevelExpects {
     pub pure: VecMap<Symbol, Region>,
     pub fx: VecMap<Symbol, Region>,
+    /// The thunks of the properties, see [roc_mono::property].
+    pub properties: VecMap<Symbol, Region>,
 }
 
 #[derive(Debug)]
@@ -176,8 +226,12 @@ pub struct MonomorphizedModule<'a> {
     pub layout_interner: STLayoutInterner<'a>,
     pub can_problems: MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     pub type_problems: MutMap<ModuleId, Vec<TypeError>>,
+    /// For each module, the symbols exposed by the modules it imports
+    pub imported_symbols: MutMap<ModuleId, Vec<Symbol>>,
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
+    /// The functions of the host that the program calls
+    pub hosted_functions: HostedFunctions<'a>,
     pub toplevel_expects: MutMap<ModuleId, ToplevelExpects>,
     pub entry_point: EntryPoint<'a>,
     pub exposed_to_host: ExposedToHost,
@@ -213,13 +267,27 @@ pub enum EntryPoint<'a> {
     Test,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Expectations {
     pub subs: roc_types::subs::Subs,
     pub path: PathBuf,
     pub expectations: VecMap<Region, Vec<ExpectLookup>>,
     pub dbgs: VecMap<Symbol, DbgLookup>,
     pub ident_ids: IdentIds,
+    /// The module's `when` branches, when running `roc test --coverage`.
+    pub branch_counters: Option<BranchCounters>,
+    /// The properties checked by `roc test --properties`, by the symbol of their thunk.
+    pub properties: VecMap<Symbol, Property>,
+}
+
+/// A function whose expects are checked against generated arguments.
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub function: Symbol,
+    /// The type of the function.
+    pub var: Variable,
+    /// The type of each argument, and its name unless the argument is destructured.
+    pub arguments: Vec<(Variable, Option<Symbol>)>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -233,7 +301,7 @@ pub struct ExposedToHost {
     pub getters: Vec<Symbol>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ModuleTiming {
     pub read_roc_file: Duration,
     pub parse_header: Duration,
@@ -28,7 +28,7 @@ const PHASES: [Phase; 6] = [
     Phase::MakeSpecializations,
 ];
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum Status {
     NotStarted,
     Pending,
@@ -41,7 +41,7 @@ enum Job<'a> {
     ResolveShorthand(&'a str),
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 struct MakeSpecializationInfo {
     /// Modules to make specializations for after they are made for this module
     succ: MutSet<ModuleId>,
@@ -49,7 +49,7 @@ struct MakeSpecializationInfo {
     has_pred: bool,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct MakeSpecializationsDependents(MutMap<ModuleId, MakeSpecializationInfo>);
 
 impl MakeSpecializationsDependents {
@@ -96,7 +96,7 @@ impl Default for MakeSpecializationsDependents {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Dependencies<'a> {
     waiting_for: MutMap<Job<'a>, MutSet<Job<'a>>>,
     notifies: MutMap<Job<'a>, MutSet<Job<'a>>>,
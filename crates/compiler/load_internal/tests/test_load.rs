@@ -19,14 +19,14 @@ use bumpalo::Bump;
 use roc_can::module::ExposedByModule;
 use roc_load_internal::docs::DocDef;
 use roc_load_internal::file::{
-    ExecutionMode, LoadConfig, LoadResult, LoadStart, LoadingProblem, Threading,
+    ExecutionMode, LoadConfig, LoadResult, LoadStart, LoadingProblem, Threading, VirtualFiles,
 };
-use roc_load_internal::module::LoadedModule;
+use roc_load_internal::module::{LoadedModule, MonomorphizedModule};
 use roc_module::ident::ModuleName;
 use roc_module::symbol::{Interns, ModuleId};
 use roc_packaging::cache::RocCacheDir;
 use roc_problem::can::Problem;
-use roc_region::all::LineInfo;
+use roc_region::all::{LineInfo, Position};
 use roc_reporting::report::RocDocAllocator;
 use roc_reporting::report::{can_problem, DEFAULT_PALETTE};
 use roc_reporting::report::{strip_colors, RenderTarget};
@@ -70,7 +70,7 @@ fn load_and_typecheck(
         RocCacheDir::Disallowed,
         load_config,
     )? {
-        Monomorphized(_) => unreachable!(""),
+        Monomorphized(_) | TypeCheckedForTargets(_) => unreachable!(""),
         TypeChecked(module) => Ok(module),
     }
 }
@@ -192,6 +192,95 @@ fn multiple_modules_help<'a>(
     Ok(result)
 }
 
+/// Writes `files` to a temporary directory named after `subdir` and loads the last one.
+/// `virtual_files` replace the contents of the files with the same name.
+fn load_files<'a>(
+    arena: &'a Bump,
+    subdir: &str,
+    files: &[(&str, &str)],
+    virtual_files: &[(&str, &str)],
+    exec_mode: ExecutionMode,
+) -> LoadResult<'a> {
+    use std::fs;
+
+    let dir = roc_test_utils::TmpDir::new(&format!("tmp/{subdir}"));
+
+    for (name, source) in files {
+        let file_path = dir.path().join(name);
+
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(file_path, source).unwrap();
+    }
+
+    let virtual_files: VirtualFiles = virtual_files
+        .iter()
+        .map(|(name, source)| (dir.path().join(name), (*source).into()))
+        .collect();
+
+    let (root, _) = files.last().unwrap();
+    let load_start = LoadStart::from_path(
+        arena,
+        dir.path().join(root),
+        RenderTarget::Generic,
+        RocCacheDir::Disallowed,
+        DEFAULT_PALETTE,
+    )
+    .unwrap()
+    .with_virtual_files(virtual_files);
+    let load_config = LoadConfig {
+        target_info: TARGET_INFO,
+        function_kind: FunctionKind::LambdaSet,
+        render: RenderTarget::Generic,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::Single,
+        exec_mode,
+    };
+
+    let loaded = roc_load_internal::file::load(
+        arena,
+        load_start,
+        Default::default(),
+        Default::default(),
+        RocCacheDir::Disallowed,
+        load_config,
+    );
+
+    match loaded {
+        Ok(load_result) => load_result,
+        Err(problem) => panic!("{problem:?}"),
+    }
+}
+
+fn load_monomorphized<'a>(
+    arena: &'a Bump,
+    subdir: &str,
+    files: &[(&str, &str)],
+) -> MonomorphizedModule<'a> {
+    match load_files(arena, subdir, files, &[], ExecutionMode::Executable) {
+        LoadResult::Monomorphized(module) => module,
+        LoadResult::TypeChecked(_) | LoadResult::TypeCheckedForTargets(_) => unreachable!(),
+    }
+}
+
+/// The callers generated for closures passed to the host, named after the symbol that exposes
+/// them and numbered like in the host's symbol names, with the number of arguments they take.
+fn host_callers(module: &MonomorphizedModule) -> Vec<(String, usize)> {
+    let mut callers: Vec<_> = module
+        .host_exposed_lambda_sets
+        .iter()
+        .map(|(exposed, _, hels)| {
+            let name = exposed.name().as_str(&module.interns);
+            (
+                format!("{name}_{}", hels.id.0),
+                hels.proc_layout.arguments.len(),
+            )
+        })
+        .collect();
+    callers.sort();
+
+    callers
+}
+
 fn load_fixture(
     dir_name: &str,
     module_name: &str,
@@ -357,6 +446,106 @@ fn import_transitive_alias() {
     assert!(multiple_modules("import_transitive_alias", modules).is_ok());
 }
 
+#[test]
+fn type_at_keeps_aliases() {
+    let source = indoc!(
+        r"
+            interface Main exposes [twice] imports []
+
+            Age : U8
+
+            twice : Age -> Age
+            twice = \age -> age * 2
+        "
+    );
+    let modules = vec![("Main.roc", source)];
+
+    let mut loaded_module = multiple_modules("type_at_keeps_aliases", modules).unwrap();
+    let home = loaded_module.module_id;
+
+    let offset = source.find("age * 2").unwrap();
+    let (region, type_str) = loaded_module
+        .type_at(home, Position::new(offset as u32))
+        .unwrap();
+
+    assert_eq!(&source[region.start().offset as usize..][..3], "age");
+    assert_eq!(type_str, "Age");
+}
+
+#[test]
+fn dbg_locations_are_relative_to_the_app() {
+    let source = indoc!(
+        r#"
+            interface Main exposes [main] imports []
+
+            main =
+                dbg 42
+
+                "done"
+        "#
+    );
+    let modules = vec![("Main.roc", source)];
+
+    let mut loaded_module = multiple_modules("dbg_locations_are_relative", modules).unwrap();
+    let home = loaded_module.module_id;
+    let declarations = loaded_module.declarations_by_id.remove(&home).unwrap();
+
+    match &declarations.expressions[0].value {
+        roc_can::expr::Expr::Dbg {
+            source_location, ..
+        } => assert_eq!(&**source_location, "Main.roc:4"),
+        other => panic!("expected a dbg, got {other:?}"),
+    }
+}
+
+#[test]
+fn virtual_files_replace_files_on_disk() {
+    let main_source = indoc!(
+        r"
+            interface Main exposes [main] imports [Dep]
+
+            main = Dep.value
+        "
+    );
+    let saved_dep_source = indoc!(
+        r"
+            interface Dep exposes [value] imports []
+
+            value = 1
+        "
+    );
+    let unsaved_dep_source = indoc!(
+        r#"
+            interface Dep exposes [value] imports []
+
+            value = "unsaved"
+        "#
+    );
+
+    let arena = Bump::new();
+    let loaded = load_files(
+        &arena,
+        "virtual_files_replace_files_on_disk",
+        &[("Dep.roc", saved_dep_source), ("Main.roc", main_source)],
+        &[("Dep.roc", unsaved_dep_source)],
+        ExecutionMode::Check,
+    );
+
+    let loaded_module = match loaded {
+        LoadResult::TypeChecked(module) => module,
+        LoadResult::Monomorphized(_) | LoadResult::TypeCheckedForTargets(_) => unreachable!(),
+    };
+
+    let dep_id = loaded_module
+        .interns
+        .module_ids
+        .get_id(&ModuleName::from("Dep"))
+        .unwrap();
+    let (_, dep_source) = &loaded_module.sources[&dep_id];
+
+    assert_eq!(dep_source.as_ref(), unsaved_dep_source);
+}
+
 #[test]
 fn interface_with_deps() {
     let subs_by_module = Default::default();
@@ -920,7 +1109,7 @@ fn opaque_wrapped_unwrapped_outside_defining_module() {
 
                 Note: Opaque types can only be wrapped and unwrapped in the module they are defined in!
 
-                ── UNUSED IMPORT in ...aque_wrapped_unwrapped_outside_defining_module/Main.roc ─
+                ── UNUSED IMPORT [E1002] in ...pped_unwrapped_outside_defining_module/Main.roc ─
 
                 Nothing from Age is used in this module.
 
@@ -975,7 +1164,7 @@ fn issue_2863_module_type_does_not_exist() {
                 report,
                 indoc!(
                     "
-                        ── UNRECOGNIZED NAME in tmp/issue_2863_module_type_does_not_exist/main.roc ─────
+                        ── UNRECOGNIZED NAME [E1059] in ...ue_2863_module_type_does_not_exist/main.roc ─
 
                         Nothing is named `DoesNotExist` in this scope.
 
@@ -1033,6 +1222,989 @@ fn import_builtin_in_platform_and_check_app() {
     assert!(result.is_ok(), "should check");
 }
 
+#[test]
+fn platform_declares_foreign_function() {
+    use roc_can::expr::Expr;
+
+    let modules = vec![(
+        "main.roc",
+        indoc!(
+            r#"
+                platform "testplatform"
+                    requires {} { main : F64 }
+                    exposes []
+                    packages {}
+                    imports []
+                    provides [mainForHost]
+
+                squareRoot : F64 -> F64
+                squareRoot = "sqrt"
+
+                mainForHost : F64
+                mainForHost = squareRoot main
+                "#
+        ),
+    )];
+
+    let loaded_module = multiple_modules("platform_declares_foreign_function", modules).unwrap();
+
+    let foreign_calls: Vec<_> = loaded_module
+        .declarations_by_id
+        .values()
+        .flat_map(|declarations| &declarations.expressions)
+        .filter_map(|expr| match &expr.value {
+            Expr::ForeignCall {
+                foreign_symbol,
+                args,
+                ..
+            } => Some((foreign_symbol.as_str().to_string(), args.len())),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(foreign_calls, [("sqrt".to_string(), 1)]);
+}
+
+#[test]
+fn platform_foreign_function_with_non_c_type() {
+    let modules = vec![(
+        "main.roc",
+        indoc!(
+            r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports []
+                    provides [mainForHost]
+
+                strlen : Str -> U64
+                strlen = "strlen"
+
+                mainForHost : U64
+                mainForHost = strlen main
+                "#
+        ),
+    )];
+
+    let report = multiple_modules("platform_foreign_function_with_non_c_type", modules)
+        .map(|_| ())
+        .unwrap_err();
+
+    assert!(report.contains("FOREIGN TYPE NOT IN C"), "{report}");
+    assert!(report.contains("strlen : Str -> U64"), "{report}");
+}
+
+#[test]
+fn hosted_module_generates_several_effects() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r#"
+                hosted Effect
+                    exposes [Effect, after, map, always, putLine, readLines]
+                    imports []
+                    generates Effect with [after, map, always]
+                    generates Stream with [after, map]
+
+                putLine : Str -> Effect {}
+
+                readLines : Str -> Stream (List Str)
+                "#
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports [Effect, Effect.Stream.{ Stream }]
+                    provides [mainForHost]
+
+                mainForHost : Stream U64
+                mainForHost =
+                    Effect.readLines main
+                    |> Effect.Stream.map List.len
+                    |> Effect.Stream.map Num.toU64
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module =
+        multiple_modules("hosted_module_generates_several_effects", modules).unwrap();
+
+    assert!(loaded_module.can_problems.values().all(Vec::is_empty));
+    assert!(loaded_module.type_problems.values().all(Vec::is_empty));
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "mainForHost" => "Effect.Stream.Stream U64",
+        },
+    );
+}
+
+#[test]
+fn generated_effect_module_with_companion_defs() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r#"
+                hosted Effect
+                    exposes [Effect, readLines]
+                    imports []
+                    generates Effect with []
+                    generates Stream with [after, always]
+
+                readLines : Str -> Stream (List Str)
+                "#
+            ),
+        ),
+        (
+            "Effect/Stream.roc",
+            indoc!(
+                r#"
+                sequence : List (Stream a) -> Stream (List a)
+                sequence = \streams ->
+                    @Stream \{} ->
+                        List.map streams \@Stream thunk -> thunk {}
+                "#
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports [Effect, Effect.Stream.{ Stream }]
+                    provides [mainForHost]
+
+                mainForHost : Stream (List (List Str))
+                mainForHost =
+                    Effect.Stream.sequence [Effect.readLines main, Effect.Stream.always []]
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module =
+        multiple_modules("generated_effect_module_with_companion_defs", modules).unwrap();
+
+    assert!(loaded_module.can_problems.values().all(Vec::is_empty));
+    assert!(loaded_module.type_problems.values().all(Vec::is_empty));
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "mainForHost" => "Effect.Stream.Stream (List (List Str))",
+        },
+    );
+}
+
+#[test]
+fn hosted_record_of_effects() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r#"
+                hosted Effect
+                    exposes [Effect, after, always, api]
+                    imports []
+                    generates Effect with [after, always]
+
+                api : { init : Effect Str, update : Str, U64 -> Effect Str }
+                "#
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : U64 }
+                    exposes []
+                    packages {}
+                    imports [Effect]
+                    provides [mainForHost]
+
+                mainForHost =
+                    Effect.after Effect.api.init \model -> Effect.api.update model main
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("hosted_record_of_effects", modules).unwrap();
+
+    assert!(loaded_module.can_problems.values().all(Vec::is_empty));
+    assert!(loaded_module.type_problems.values().all(Vec::is_empty));
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "mainForHost" => "Effect.Effect Str",
+        },
+    );
+}
+
+#[test]
+fn hosted_module_generates_error_aware_functions() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r#"
+                hosted Effect
+                    exposes [Effect, after, always, attempt, mapOk, mapErr, readFile]
+                    imports []
+                    generates Effect with [after, always, attempt, mapOk, mapErr]
+
+                readFile : Str -> Effect (Result Str [NotFound])
+                "#
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports [Effect]
+                    provides [mainForHost]
+
+                mainForHost : Effect.Effect (Result U64 Str)
+                mainForHost =
+                    Effect.readFile main
+                    |> Effect.mapOk Str.countUtf8Bytes
+                    |> Effect.mapErr \NotFound -> "not found"
+                    |> Effect.attempt \result ->
+                        when result is
+                            Ok n -> Effect.always (Ok (Num.toU64 n))
+                            Err msg -> Effect.always (Err msg)
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module =
+        multiple_modules("hosted_module_generates_error_aware_functions", modules).unwrap();
+
+    assert!(loaded_module.can_problems.values().all(Vec::is_empty));
+    assert!(loaded_module.type_problems.values().all(Vec::is_empty));
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "mainForHost" => "Effect.Effect [Err Str, Ok U64]",
+        },
+    );
+}
+
+#[test]
+fn hosted_module_generates_bracket() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, bracket, open, close, readAll]
+                    imports []
+                    generates Effect with [bracket]
+
+                open : Str -> Effect U64
+
+                close : U64 -> Effect {}
+
+                readAll : U64 -> Effect (Result Str [EndOfFile])
+                "
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports [Effect]
+                    provides [mainForHost]
+
+                mainForHost =
+                    Effect.bracket (Effect.open main) Effect.close Effect.readAll
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("hosted_module_generates_bracket", modules).unwrap();
+
+    assert!(loaded_module.can_problems.values().all(Vec::is_empty));
+    assert!(loaded_module.type_problems.values().all(Vec::is_empty));
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "mainForHost" => "Effect.Effect (Result Str [EndOfFile])",
+        },
+    );
+}
+
+#[test]
+fn hosted_module_generates_once() {
+    use roc_can::expr::Expr;
+    use roc_can::traverse::{walk_decls, walk_expr, Visitor};
+    use roc_region::all::Region;
+    use roc_types::subs::Variable;
+
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, after, map, once, readConfig]
+                    imports []
+                    generates Effect with [after, map, once]
+
+                readConfig : Effect { port : U16 }
+                "
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : U16 }
+                    exposes []
+                    packages {}
+                    imports [Effect]
+                    provides [mainForHost]
+
+                mainForHost =
+                    config = Effect.once Effect.readConfig
+
+                    Effect.after config \first ->
+                        Effect.map config \second -> { port: first.port + second.port + main }
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("hosted_module_generates_once", modules).unwrap();
+
+    assert!(loaded_module.can_problems.values().all(Vec::is_empty));
+    assert!(loaded_module.type_problems.values().all(Vec::is_empty));
+
+    struct ForeignCalls(Vec<(String, usize)>);
+
+    impl Visitor for ForeignCalls {
+        fn visit_expr(&mut self, expr: &Expr, _region: Region, var: Variable) {
+            if let Expr::ForeignCall {
+                foreign_symbol,
+                args,
+                ..
+            } = expr
+            {
+                self.0
+                    .push((foreign_symbol.as_str().to_string(), args.len()));
+            }
+
+            walk_expr(self, expr, var);
+        }
+    }
+
+    let effect_module = loaded_module
+        .interns
+        .module_ids
+        .get_id(&"Effect".into())
+        .unwrap();
+    let mut foreign_calls = ForeignCalls(Vec::new());
+    walk_decls(
+        &mut foreign_calls,
+        &loaded_module.typechecked[&effect_module].decls,
+    );
+    foreign_calls.0.sort();
+    foreign_calls.0.dedup();
+
+    assert_eq!(
+        foreign_calls.0,
+        [
+            ("roc_fx_onceCell".to_string(), 0),
+            ("roc_fx_onceGet".to_string(), 1),
+            ("roc_fx_onceIsSet".to_string(), 1),
+            ("roc_fx_onceSet".to_string(), 2),
+            ("roc_fx_readConfig".to_string(), 0),
+        ]
+    );
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "mainForHost" => "Effect.Effect { port : U16 }",
+        },
+    );
+}
+
+#[test]
+fn hosted_module_generates_conditional_effects() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, after, ifThen, unless, putLine]
+                    imports []
+                    generates Effect with [after, ifThen, unless]
+
+                putLine : Str -> Effect {}
+                "
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports [Effect]
+                    provides [mainForHost]
+
+                mainForHost =
+                    verbose = Str.isEmpty main
+
+                    Effect.after (Effect.ifThen verbose (Effect.putLine "starting")) \{} ->
+                        Effect.unless verbose (Effect.putLine main)
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module =
+        multiple_modules("hosted_module_generates_conditional_effects", modules).unwrap();
+
+    assert!(loaded_module.can_problems.values().all(Vec::is_empty));
+    assert!(loaded_module.type_problems.values().all(Vec::is_empty));
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "mainForHost" => "Effect.Effect {}",
+        },
+    );
+}
+
+#[test]
+fn hosted_module_with_async_effect() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, always, after, run, readLine]
+                    imports []
+                    generates Effect with [async]
+
+                readLine : Str -> Effect Str
+
+                always : a -> Effect a
+                always = \value -> @Effect \continue -> continue value
+
+                after : Effect a, (a -> Effect b) -> Effect b
+                after = \@Effect effect, toEffect ->
+                    @Effect \continue ->
+                        effect \value ->
+                            when toEffect value is
+                                @Effect next -> next continue
+
+                run : Effect {} -> ({} -> {})
+                run = \@Effect effect -> \{} -> effect \{} -> {}
+                "
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports [Effect]
+                    provides [mainForHost]
+
+                mainForHost =
+                    Effect.after (Effect.readLine main) \line -> Effect.always (Str.isEmpty line)
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("hosted_module_with_async_effect", modules).unwrap();
+
+    assert!(loaded_module.can_problems.values().all(Vec::is_empty));
+    assert!(loaded_module.type_problems.values().all(Vec::is_empty));
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "mainForHost" => "Effect.Effect Bool",
+        },
+    );
+}
+
+#[test]
+fn hosted_module_generates_map_n() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, always, map2, map3, map8]
+                    imports []
+                    generates Effect with [always, map2, map3, map8]
+                "
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports [Effect]
+                    provides [mainForHost]
+
+                mainForHost =
+                    one = Effect.always 1u8
+                    pair = Effect.map2 one (Effect.always main) \n, str -> (n, str)
+                    triple = Effect.map3 one one pair \a, b, (c, str) -> (a + b + c, str)
+
+                    Effect.map8 one one one one one one one triple \a, b, c, d, e, f, g, (h, str) ->
+                        { sum: a + b + c + d + e + f + g + h, str }
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("hosted_module_generates_map_n", modules).unwrap();
+
+    assert!(loaded_module.can_problems.values().all(Vec::is_empty));
+    assert!(loaded_module.type_problems.values().all(Vec::is_empty));
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "mainForHost" => "Effect.Effect { str : Str, sum : U8 }",
+        },
+    );
+}
+
+#[test]
+fn hosted_module_documents_generated_functions() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, after, map2, putLine]
+                    imports []
+                    generates Effect with [after, map2]
+
+                ## Writes a line to stdout
+                putLine : Str -> Effect {}
+                "
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports [Effect]
+                    provides [mainForHost]
+
+                mainForHost = Effect.after (Effect.putLine main) \{} -> Effect.putLine main
+                "#
+            ),
+        ),
+    ];
+
+    let loaded_module =
+        multiple_modules("hosted_module_documents_generated_functions", modules).unwrap();
+
+    let effect_id = loaded_module
+        .interns
+        .module_ids
+        .get_id(&ModuleName::from("Effect"))
+        .unwrap();
+
+    let module_docs = loaded_module
+        .docs_by_module
+        .get(&effect_id)
+        .expect("module should have docs");
+
+    let documented: Vec<_> = module_docs
+        .entries
+        .iter()
+        .filter_map(|entry| match entry {
+            roc_load_internal::docs::DocEntry::DocDef(DocDef { name, docs, .. }) => {
+                Some((name.as_str(), docs.clone().unwrap_or_default()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        documented,
+        [
+            ("putLine", "Writes a line to stdout\n".to_string()),
+            (
+                "after",
+                "Runs an effect, then passes its result to a function that gives the effect to run next.".to_string()
+            ),
+            (
+                "map2",
+                "Runs 2 effects one after the other, and combines their results with a function.".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn hosted_function_taking_a_closure_exposes_its_callers() {
+    let files = [
+        (
+            "platform/Effect.roc",
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, after, map, always, putLine, onTick]
+                    imports []
+                    generates Effect with [after, map, always]
+
+                putLine : Str -> Effect {}
+
+                onTick : (U64 -> Effect {}) -> Effect {}
+                "
+            ),
+        ),
+        (
+            "platform/main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Effect {} }
+                    exposes []
+                    packages {}
+                    imports [Effect.{ Effect }]
+                    provides [mainForHost]
+
+                mainForHost : Effect {}
+                mainForHost = main
+                "#
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                app "test"
+                    packages { pf: "platform/main.roc" }
+                    imports [pf.Effect]
+                    provides [main] to pf
+
+                main =
+                    greeting = "tick "
+
+                    Effect.onTick \n -> Effect.putLine (Str.concat greeting (Num.toStr n))
+                "#
+            ),
+        ),
+    ];
+
+    let arena = Bump::new();
+    let module = load_monomorphized(
+        &arena,
+        "hosted_function_taking_a_closure_exposes_its_callers",
+        &files,
+    );
+    let callers = host_callers(&module);
+
+    // The closure takes a `U64` and its captures; the effect it returns takes `{}` and its captures
+    assert_eq!(
+        callers,
+        [
+            ("fx_onTick_0".to_string(), 2),
+            ("fx_onTick_1".to_string(), 2),
+            ("mainForHost_0".to_string(), 2),
+        ]
+    );
+}
+
+#[test]
+fn hosted_functions_record_their_declared_signatures() {
+    let files = [
+        (
+            "platform/Effect.roc",
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, after, always, putLine, getLine]
+                    imports []
+                    generates Effect with [after, always]
+
+                putLine : Str -> Effect {}
+
+                getLine : Effect (Result Str [EndOfFile])
+                "
+            ),
+        ),
+        (
+            "platform/main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Effect {} }
+                    exposes []
+                    packages {}
+                    imports [Effect.{ Effect }]
+                    provides [mainForHost]
+
+                mainForHost : Effect {}
+                mainForHost = main
+                "#
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                app "test"
+                    packages { pf: "platform/main.roc" }
+                    imports [pf.Effect]
+                    provides [main] to pf
+
+                main =
+                    Effect.after Effect.getLine \result ->
+                        when result is
+                            Ok line -> Effect.putLine line
+                            Err EndOfFile -> Effect.always {}
+                "#
+            ),
+        ),
+    ];
+
+    let arena = Bump::new();
+    let module = load_monomorphized(
+        &arena,
+        "hosted_functions_record_their_declared_signatures",
+        &files,
+    );
+
+    let mut functions: Vec<_> = module
+        .hosted_functions
+        .iter()
+        .map(|function| {
+            (
+                function.foreign_symbol.as_str().to_string(),
+                function.arg_layouts.len(),
+                function.returns_result,
+            )
+        })
+        .collect();
+    functions.sort();
+
+    assert_eq!(
+        functions,
+        [
+            ("roc_fx_getLine".to_string(), 0, true),
+            ("roc_fx_putLine".to_string(), 1, false),
+        ]
+    );
+}
+
+/// The callers that the host gets for an app that batches two effects, with the hosted module
+/// generating `Effect with [after, always, batch]` followed by `more_generates`
+fn batch_callers(name: &str, more_generates: &str) -> Vec<(String, usize)> {
+    let effect_module = format!(
+        indoc!(
+            r"
+            hosted Effect
+                exposes [Effect, after, always, batch, readLine]
+                imports []
+                generates Effect with [after, always, batch{}]
+
+            readLine : Str -> Effect Str
+            "
+        ),
+        more_generates
+    );
+
+    let files = [
+        ("platform/Effect.roc", effect_module.as_str()),
+        (
+            "platform/main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Effect U64 }
+                    exposes []
+                    packages {}
+                    imports [Effect.{ Effect }]
+                    provides [mainForHost]
+
+                mainForHost : Effect U64
+                mainForHost = main
+                "#
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                app "test"
+                    packages { pf: "platform/main.roc" }
+                    imports [pf.Effect]
+                    provides [main] to pf
+
+                main =
+                    prompts = ["name", "quest"]
+
+                    Effect.batch (List.map prompts Effect.readLine)
+                    |> Effect.after \answers -> Effect.always (List.len answers)
+                "#
+            ),
+        ),
+    ];
+
+    let arena = Bump::new();
+    let module = load_monomorphized(&arena, name, &files);
+
+    host_callers(&module)
+}
+
+#[test]
+fn hosted_module_generates_batch() {
+    // The effects run one after the other, so the host doesn't need to provide `roc_fx_batch`
+    assert_eq!(
+        batch_callers("hosted_module_generates_batch", ""),
+        [("mainForHost_0".to_string(), 2)]
+    );
+}
+
+#[test]
+fn hosted_module_generates_batch_on_the_host() {
+    // The thunks of the batched effects take `{}` and their captures
+    assert_eq!(
+        batch_callers("hosted_module_generates_batch_on_the_host", ", hostBatch"),
+        [
+            ("fx_batch_0".to_string(), 2),
+            ("mainForHost_0".to_string(), 2),
+        ]
+    );
+}
+
+#[test]
+fn hosted_module_defines_a_generated_function() {
+    let modules = vec![
+        (
+            "Effect.roc",
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, map, always]
+                    imports []
+                    generates Effect with [always, map]
+
+                map = \effect, _ -> effect
+                "
+            ),
+        ),
+        (
+            "main.roc",
+            indoc!(
+                r#"
+                platform "testplatform"
+                    requires {} { main : Str }
+                    exposes []
+                    packages {}
+                    imports [Effect]
+                    provides [mainForHost]
+
+                mainForHost = Effect.map (Effect.always main) Str.trim
+                "#
+            ),
+        ),
+    ];
+
+    let mut loaded_module =
+        multiple_modules("hosted_module_defines_a_generated_function", modules).unwrap();
+
+    let effect_id = loaded_module
+        .interns
+        .module_ids
+        .get_id(&ModuleName::from("Effect"))
+        .unwrap();
+    let (filepath, src) = &loaded_module.sources[&effect_id];
+
+    let err = format_can_problems(
+        loaded_module.can_problems.remove(&effect_id).unwrap(),
+        effect_id,
+        &loaded_module.interns,
+        filepath.clone(),
+        src,
+    );
+    assert_eq!(
+        err,
+        indoc!(
+            r"
+            ── GENERATED NAME TAKEN in ...d_module_defines_a_generated_function/Effect.roc ─
+
+            This module asks for a generated `map` function:
+
+            4│      generates Effect with [always, map]
+                                                   ^^^
+
+            But it already has a definition by that name:
+
+            6│  map = \effect, _ -> effect
+                ^^^
+
+            This name is reserved by the generated Effect module. Either rename
+            this definition, or remove `map` from the `generates` list.
+            "
+        ),
+        "\n{}",
+        err
+    );
+}
+
 #[test]
 fn module_doesnt_match_file_path() {
     let modules = vec![(
@@ -1,6 +1,6 @@
 use crate::ident::{Ident, ModuleName};
 use crate::module_err::{IdentIdNotFoundSnafu, ModuleIdNotFoundSnafu, ModuleResult};
-use roc_collections::{SmallStringInterner, VecMap};
+use roc_collections::{BucketedBitSet, BucketedIndex, SmallStringInterner, VecMap};
 use roc_error_macros::internal_error;
 use roc_ident::IdentStr;
 use roc_region::all::Region;
@@ -238,6 +238,26 @@ impl fmt::Display for Symbol {
     }
 }
 
+impl BucketedIndex for Symbol {
+    fn bucket(self) -> u32 {
+        self.module_id.get()
+    }
+
+    fn index_in_bucket(self) -> u32 {
+        self.ident_id
+    }
+
+    fn from_bucket_and_index(bucket: u32, index: u32) -> Self {
+        Symbol::new(
+            ModuleId(NonZeroU32::new(bucket).expect("module ids are never zero")),
+            IdentId(index),
+        )
+    }
+}
+
+/// A set of symbols, with a bit per ident of each module that it has symbols from.
+pub type SymbolSet = BucketedBitSet<Symbol>;
+
 impl From<Symbol> for u64 {
     fn from(symbol: Symbol) -> Self {
         symbol.as_u64()
@@ -370,7 +390,18 @@ impl ModuleId {
     pub fn register_debug_idents(self, ident_ids: &IdentIds) {
         let mut all = DEBUG_IDENT_IDS_BY_MODULE_ID.lock().expect("Failed to acquire lock for Debug interning into DEBUG_MODULE_ID_NAMES, presumably because a thread panicked.");
 
-        all.insert(self.to_zero_indexed() as u32, ident_ids.clone());
+        // Idents are only ever added to an IdentIds, so it's enough to copy the ones that are new
+        // since the last time. That keeps registering cheap enough to do after every change.
+        match all.get_mut(&(self.to_zero_indexed() as u32)) {
+            Some(registered) if registered.len() <= ident_ids.len() => {
+                for (_, ident) in ident_ids.ident_strs().skip(registered.len()) {
+                    registered.add_str(ident);
+                }
+            }
+            _ => {
+                all.insert(self.to_zero_indexed() as u32, ident_ids.clone());
+            }
+        }
     }
 
     #[cfg(not(any(debug_assertions, feature = "debug-symbols")))]
@@ -453,6 +484,8 @@ impl<'a, T> PackageQualified<'a, T> {
 #[derive(Debug, Clone)]
 pub struct PackageModuleIds<'a> {
     by_id: Vec<PQModuleName<'a>>,
+    /// The package each shorthand refers to, e.g. `pf` to the platform's URL
+    packages: VecMap<&'a str, &'a str>,
 }
 
 impl<'a> PackageModuleIds<'a> {
@@ -520,6 +553,44 @@ impl<'a> PackageModuleIds<'a> {
         self.by_id.iter()
     }
 
+    /// Records the package a shorthand refers to, e.g. the URL or path of `pf`. Modules imported
+    /// through the shorthand get their [StableModuleId] from this package.
+    pub fn register_package(&mut self, shorthand: &'a str, package: &'a str) {
+        self.packages.insert(shorthand, package);
+    }
+
+    /// The stable identity of a module. Unqualified modules belong to the root package (or are
+    /// builtins), whose modules are identified by their name alone.
+    ///
+    /// Returns [None] if the module is unknown, or if it is qualified with a shorthand that
+    /// hasn't been registered yet.
+    pub fn stable_id(&self, id: ModuleId) -> Option<StableModuleId> {
+        match self.get_name(id)? {
+            PQModuleName::Unqualified(name) => Some(StableModuleId::new("", name)),
+            PQModuleName::Qualified(shorthand, name) => {
+                let package = self.packages.get(shorthand)?;
+
+                Some(StableModuleId::new(package, name))
+            }
+        }
+    }
+
+    /// The stable identities of all modules with a known package, for translating between
+    /// them and the `ModuleId`s of this run.
+    pub fn stable_module_ids(&self) -> Result<StableModuleIds, StableModuleIdCollision> {
+        let mut stable_module_ids = StableModuleIds::default();
+
+        for index in 0..self.by_id.len() {
+            let module_id = ModuleId::from_zero_indexed(index);
+
+            if let Some(stable_id) = self.stable_id(module_id) {
+                stable_module_ids.insert(module_id, stable_id)?;
+            }
+        }
+
+        Ok(stable_module_ids)
+    }
+
     /// Returns true iff two modules belong to the same package.
     /// Returns [None] if one module is unknown.
     pub fn package_eq(&self, left: ModuleId, right: ModuleId) -> Option<bool> {
@@ -591,6 +662,250 @@ impl ModuleIds {
     pub fn available_modules(&self) -> impl Iterator<Item = &ModuleName> {
         self.by_id.iter()
     }
+
+    /// The stable identities of these modules, which are all taken to belong to the root
+    /// package (or be builtins), like the unqualified modules of a [PackageModuleIds].
+    pub fn stable_module_ids(&self) -> Result<StableModuleIds, StableModuleIdCollision> {
+        let mut stable_module_ids = StableModuleIds::default();
+
+        for (index, name) in self.by_id.iter().enumerate() {
+            stable_module_ids.insert(
+                ModuleId::from_zero_indexed(index),
+                StableModuleId::new("", name),
+            )?;
+        }
+
+        Ok(stable_module_ids)
+    }
+}
+
+/// An identity for a module that, unlike its [ModuleId], is the same in every run and on every
+/// machine: a hash of the package the module belongs to and its name. It is meant for keying
+/// things that outlive a run, like caches and serialized IR, while everything else keeps using
+/// the dense `ModuleId`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StableModuleId(u64);
+
+impl StableModuleId {
+    pub fn new(package: &str, module_name: &ModuleName) -> Self {
+        use std::hash::Hasher;
+
+        // The hasher is seeded with a constant, so the hash doesn't change between runs.
+        let mut hasher = roc_collections::all::WyHash::default();
+
+        hasher.write(package.as_bytes());
+        // Separate the two, so that e.g. package "a" with module "bC" differs from "ab" with "C".
+        hasher.write_u8(0);
+        hasher.write(module_name.as_str().as_bytes());
+
+        Self(hasher.finish())
+    }
+
+    pub const fn to_u64(self) -> u64 {
+        self.0
+    }
+
+    pub const fn from_u64(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+/// Two modules got the same [StableModuleId]. This takes two different modules whose hashes
+/// collide, so it should never happen in practice, but a stable id must never silently refer to
+/// the wrong module.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StableModuleIdCollision {
+    pub stable_id: StableModuleId,
+    pub existing: ModuleId,
+    pub colliding: ModuleId,
+}
+
+/// Translates between the [StableModuleId]s of modules and their `ModuleId`s in this run.
+///
+/// Anything that stores symbols beyond a run, like the cached types of the builtins, persists
+/// these along with them. A later run can then translate the persisted symbols into its own
+/// `ModuleId`s with a [SymbolRemap].
+#[derive(Debug, Clone, Default)]
+pub struct StableModuleIds {
+    /// Indexed by ModuleId
+    by_id: Vec<Option<StableModuleId>>,
+    by_stable_id: VecMap<StableModuleId, ModuleId>,
+}
+
+impl StableModuleIds {
+    /// Fails if another module already has this stable id.
+    pub fn insert(
+        &mut self,
+        module_id: ModuleId,
+        stable_id: StableModuleId,
+    ) -> Result<(), StableModuleIdCollision> {
+        if let Some(existing) = self.module_id(stable_id) {
+            if existing != module_id {
+                return Err(StableModuleIdCollision {
+                    stable_id,
+                    existing,
+                    colliding: module_id,
+                });
+            }
+        }
+
+        let index = module_id.to_zero_indexed();
+
+        if index >= self.by_id.len() {
+            self.by_id.resize(index + 1, None);
+        }
+
+        if let Some(previous) = self.by_id[index].replace(stable_id) {
+            self.by_stable_id.remove(&previous);
+        }
+
+        self.by_stable_id.insert(stable_id, module_id);
+
+        Ok(())
+    }
+
+    pub fn stable_id(&self, module_id: ModuleId) -> Option<StableModuleId> {
+        self.by_id
+            .get(module_id.to_zero_indexed())
+            .copied()
+            .flatten()
+    }
+
+    pub fn module_id(&self, stable_id: StableModuleId) -> Option<ModuleId> {
+        self.by_stable_id.get(&stable_id).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ModuleId, StableModuleId)> + '_ {
+        self.by_stable_id
+            .iter()
+            .map(|(stable_id, module_id)| (*module_id, *stable_id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_stable_id.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_stable_id.is_empty()
+    }
+
+    /// Writes the number of modules, followed by each module's `ModuleId` as a `u32` and its
+    /// stable id as a `u64`, all little-endian. Returns the number of bytes written.
+    pub fn serialize(&self, writer: &mut impl std::io::Write) -> std::io::Result<usize> {
+        writer.write_all(&(self.by_stable_id.len() as u32).to_le_bytes())?;
+
+        for (stable_id, module_id) in self.by_stable_id.iter() {
+            writer.write_all(&module_id.0.get().to_le_bytes())?;
+            writer.write_all(&stable_id.0.to_le_bytes())?;
+        }
+
+        Ok(4 + self.by_stable_id.len() * (4 + 8))
+    }
+
+    /// Reads ids written by [StableModuleIds::serialize], returning them along with the offset
+    /// just past them. Fails if the bytes end early, hold an invalid `ModuleId`, or give two
+    /// modules the same stable id.
+    pub fn deserialize(bytes: &[u8]) -> std::io::Result<(Self, usize)> {
+        use std::io::{Error, ErrorKind};
+
+        fn read<const N: usize>(bytes: &[u8], offset: &mut usize) -> std::io::Result<[u8; N]> {
+            let chunk = bytes
+                .get(*offset..)
+                .and_then(|rest| rest.get(..N))
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "the stable module ids are truncated",
+                    )
+                })?;
+
+            *offset += N;
+
+            Ok(chunk.try_into().unwrap())
+        }
+
+        let mut offset = 0;
+        let length = u32::from_le_bytes(read(bytes, &mut offset)?);
+        let mut stable_module_ids = Self::default();
+
+        for _ in 0..length {
+            let module_id = NonZeroU32::new(u32::from_le_bytes(read(bytes, &mut offset)?))
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "a ModuleId of 0 was stored"))?;
+            let stable_id = u64::from_le_bytes(read(bytes, &mut offset)?);
+
+            stable_module_ids
+                .insert(ModuleId(module_id), StableModuleId(stable_id))
+                .map_err(|collision| {
+                    Error::new(ErrorKind::InvalidData, format!("{collision:?}"))
+                })?;
+        }
+
+        Ok((stable_module_ids, offset))
+    }
+}
+
+impl PartialEq for StableModuleIds {
+    fn eq(&self, other: &Self) -> bool {
+        // `by_stable_id` is the inverse of `by_id`
+        self.by_id == other.by_id
+    }
+}
+
+impl Eq for StableModuleIds {}
+
+/// Translates symbols that were persisted along with one run's [StableModuleIds] into the
+/// `ModuleId`s of another run.
+///
+/// An `IdentId` only depends on the source of its module, so only the module half of a symbol
+/// has to be translated.
+#[derive(Debug, Clone)]
+pub struct SymbolRemap {
+    /// The current ModuleId for each persisted one, indexed by the persisted ModuleId
+    module_ids: Vec<Option<ModuleId>>,
+}
+
+impl SymbolRemap {
+    /// Fails with the stable id of the first persisted module that the current run doesn't know.
+    pub fn new(
+        persisted: &StableModuleIds,
+        current: &StableModuleIds,
+    ) -> Result<Self, StableModuleId> {
+        let module_ids = persisted
+            .by_id
+            .iter()
+            .map(|opt_stable_id| match opt_stable_id {
+                Some(stable_id) => current.module_id(*stable_id).map(Some).ok_or(*stable_id),
+                None => Ok(None),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { module_ids })
+    }
+
+    /// Returns true iff every persisted module has the same id in the current run.
+    pub fn is_identity(&self) -> bool {
+        self.module_ids
+            .iter()
+            .enumerate()
+            .all(|(index, opt_module_id)| match opt_module_id {
+                Some(module_id) => module_id.to_zero_indexed() == index,
+                None => true,
+            })
+    }
+
+    pub fn module_id(&self, persisted: ModuleId) -> ModuleId {
+        match self.module_ids.get(persisted.to_zero_indexed()) {
+            Some(Some(module_id)) => *module_id,
+            _ => internal_error!(
+                "The persisted module id {} has no stable id to translate it by",
+                persisted.0
+            ),
+        }
+    }
+
+    pub fn symbol(&self, persisted: Symbol) -> Symbol {
+        Symbol::new(self.module_id(persisted.module_id()), persisted.ident_id())
+    }
 }
 
 /// An ID that is assigned to interned string identifiers within a module.
@@ -949,7 +1264,10 @@ macro_rules! define_builtins {
                     insert_both(ModuleId::$module_const, $module_name);
                 )+
 
-                PackageModuleIds { by_id }
+                PackageModuleIds {
+                    by_id,
+                    packages: VecMap::default(),
+                }
             }
         }
 
@@ -1638,3 +1956,167 @@ define_builtins! {
 
     num_modules: 16 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        IdentId, IdentIds, ModuleId, ModuleIds, PQModuleName, PackageModuleIds, StableModuleId,
+        StableModuleIdCollision, StableModuleIds, Symbol, SymbolRemap,
+    };
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "debug-symbols"))]
+    fn registered_idents_are_kept_up_to_date() {
+        let mut module_ids = ModuleIds::default();
+        let home = module_ids.get_or_insert(&"Effect".into());
+
+        let mut ident_ids = IdentIds::default();
+        let first = ident_ids.add_str("after");
+        home.register_debug_idents(&ident_ids);
+
+        let second = ident_ids.add_str("map");
+        let generated = ident_ids.gen_unique();
+        home.register_debug_idents(&ident_ids);
+
+        let debug = |ident_id| format!("{:?}", Symbol::new(home, ident_id));
+
+        assert!(debug(first).ends_with(".after`"));
+        assert!(debug(second).ends_with(".map`"));
+        assert!(debug(generated).ends_with(".2`"));
+    }
+
+    #[test]
+    fn stable_ids_do_not_depend_on_numbering() {
+        let main = PQModuleName::Unqualified("Main".into());
+        let task = PQModuleName::Qualified("pf", "Task".into());
+
+        let mut first = PackageModuleIds::default();
+        let first_main = first.get_or_insert(&main);
+        let first_task = first.get_or_insert(&task);
+
+        let mut second = PackageModuleIds::default();
+        let second_task = second.get_or_insert(&task);
+        let second_main = second.get_or_insert(&main);
+
+        assert_ne!(first_main, second_main);
+        assert_eq!(first.stable_id(first_main), second.stable_id(second_main));
+
+        // The package behind a shorthand is only known once it has been registered
+        assert_eq!(first.stable_id(first_task), None);
+
+        first.register_package("pf", "https://example.com/platform.tar.br");
+        second.register_package("pf", "https://example.com/platform.tar.br");
+
+        let stable_task = first.stable_id(first_task).unwrap();
+        assert_eq!(second.stable_id(second_task), Some(stable_task));
+        assert_eq!(
+            stable_task,
+            StableModuleId::new("https://example.com/platform.tar.br", &"Task".into())
+        );
+        assert_ne!(stable_task, StableModuleId::new("", &"Task".into()));
+
+        let stable_module_ids = second.stable_module_ids().unwrap();
+        assert_eq!(stable_module_ids.module_id(stable_task), Some(second_task));
+        assert_eq!(
+            stable_module_ids.stable_id(second_main),
+            first.stable_id(first_main)
+        );
+    }
+
+    #[test]
+    fn stable_ids_roundtrip_through_bytes() {
+        let mut module_ids = PackageModuleIds::default();
+        module_ids.get_or_insert(&PQModuleName::Unqualified("Main".into()));
+
+        let stable_module_ids = module_ids.stable_module_ids().unwrap();
+        let mut bytes = Vec::new();
+        let written = stable_module_ids.serialize(&mut bytes).unwrap();
+
+        assert_eq!(written, bytes.len());
+
+        let (deserialized, offset) = StableModuleIds::deserialize(&bytes).unwrap();
+        assert_eq!(deserialized, stable_module_ids);
+        assert_eq!(offset, written);
+    }
+
+    #[test]
+    fn truncated_stable_ids_are_an_error() {
+        let stable_module_ids = PackageModuleIds::default().stable_module_ids().unwrap();
+        let mut bytes = Vec::new();
+        stable_module_ids.serialize(&mut bytes).unwrap();
+
+        for length in [0, 3, bytes.len() - 1] {
+            let error = StableModuleIds::deserialize(&bytes[..length]).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[test]
+    fn colliding_stable_ids_are_an_error() {
+        let stable_id = StableModuleId::new("", &"Main".into());
+        let mut stable_module_ids = StableModuleIds::default();
+
+        stable_module_ids.insert(ModuleId::STR, stable_id).unwrap();
+        // Inserting the same module again is fine
+        stable_module_ids.insert(ModuleId::STR, stable_id).unwrap();
+
+        assert_eq!(
+            stable_module_ids.insert(ModuleId::LIST, stable_id),
+            Err(StableModuleIdCollision {
+                stable_id,
+                existing: ModuleId::STR,
+                colliding: ModuleId::LIST,
+            })
+        );
+        assert_eq!(stable_module_ids.module_id(stable_id), Some(ModuleId::STR));
+        assert_eq!(stable_module_ids.stable_id(ModuleId::LIST), None);
+    }
+
+    #[test]
+    fn remap_follows_stable_ids() {
+        let mut first_run = ModuleIds::default();
+        let a = first_run.get_or_insert(&"A".into());
+        let b = first_run.get_or_insert(&"B".into());
+        let persisted = first_run.stable_module_ids().unwrap();
+
+        // The second run loads the modules the other way around, and one more besides.
+        let mut second_run = ModuleIds::default();
+        let c = second_run.get_or_insert(&"C".into());
+        let b2 = second_run.get_or_insert(&"B".into());
+        let a2 = second_run.get_or_insert(&"A".into());
+
+        let remap = SymbolRemap::new(&persisted, &second_run.stable_module_ids().unwrap()).unwrap();
+
+        assert!(!remap.is_identity());
+        assert_ne!(a2, c);
+        assert_eq!(remap.module_id(a), a2);
+        assert_eq!(remap.module_id(b), b2);
+        assert_eq!(
+            remap.symbol(Symbol::new(b, IdentId(7))),
+            Symbol::new(b2, IdentId(7))
+        );
+
+        let same_run = SymbolRemap::new(&persisted, &persisted).unwrap();
+        assert!(same_run.is_identity());
+        assert_eq!(
+            same_run.symbol(Symbol::new(a, IdentId(3))),
+            Symbol::new(a, IdentId(3))
+        );
+    }
+
+    #[test]
+    fn remap_needs_every_persisted_module() {
+        let mut first_run = ModuleIds::default();
+        first_run.get_or_insert(&"A".into());
+        first_run.get_or_insert(&"Gone".into());
+        let persisted = first_run.stable_module_ids().unwrap();
+
+        let mut second_run = ModuleIds::default();
+        second_run.get_or_insert(&"A".into());
+
+        assert_eq!(
+            SymbolRemap::new(&persisted, &second_run.stable_module_ids().unwrap()).unwrap_err(),
+            StableModuleId::new("", &"Gone".into())
+        );
+    }
+}
@@ -1,30 +1,102 @@
 use bumpalo::{collections::Vec, Bump};
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::LowLevelWrapperType;
 use roc_module::symbol::Symbol;
 
 use crate::{
     inc_dec::Ownership,
-    ir::{Call, CallType, Expr, Proc, Stmt},
+    ir::{Call, CallType, Expr, HigherOrderLowLevel, ListLiteralElement, Proc, ProcLayout, Stmt},
     layout::{Builtin, InLayout, LayoutInterner, LayoutRepr},
 };
 
-#[allow(unused)]
-pub(crate) fn infer_borrow_signature<'a>(
+/// The ownership of each procedure's parameters, in order.
+pub(crate) type BorrowSignatures<'a> = MutMap<(Symbol, ProcLayout<'a>), &'a [Ownership]>;
+
+/// Infers which parameters each procedure only reads, so that callers can lend them instead
+/// of handing over a reference. This saves an increment at the call site and a decrement in
+/// the callee for every such argument.
+///
+/// A parameter is owned as soon as the procedure stores it, returns it, jumps with it, or
+/// passes it to something which takes it owned, which includes the parameters other
+/// procedures take owned. Those depend on each other through (mutual) recursion, so the
+/// signatures start out borrowing everything they can and are iterated to a fixed point.
+///
+/// Procedures which are exposed to the host, or called through a pointer, keep taking all of
+/// their parameters owned, as their callers don't know about their signature.
+pub(crate) fn infer_borrow_signatures<'a>(
     arena: &'a Bump,
     interner: &impl LayoutInterner<'a>,
-    proc: &'a Proc<'a>,
-) -> &'a [Ownership] {
-    let mut state = State::new(arena, interner, proc);
-    state.inspect_stmt(&proc.body);
-    state.borrow_signature
-}
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    host_exposed_procs: &[Symbol],
+) -> BorrowSignatures<'a> {
+    let mut escaping: MutSet<Symbol> = host_exposed_procs.iter().copied().collect();
+    for proc in procedures.values() {
+        if proc.is_erased {
+            escaping.insert(proc.name.name());
+        }
 
-struct State<'a> {
-    /// Argument symbols with a layout of `List *` or `Str`, i.e. the layouts
-    /// for which borrow inference might decide to pass as borrowed
-    args: &'a [(InLayout<'a>, Symbol)],
-    borrow_signature: &'a mut [Ownership],
+        collect_escaping_procs(&proc.body, &mut escaping);
+    }
+
+    let mut signatures: BorrowSignatures<'a> = procedures
+        .iter()
+        .filter(|((symbol, _), _)| {
+            // Calls to lowlevel wrappers are replaced by the lowlevel itself.
+            matches!(
+                LowLevelWrapperType::from_symbol(*symbol),
+                LowLevelWrapperType::NotALowLevelWrapper
+            )
+        })
+        .map(|(key, proc)| {
+            let signature = proc.args.iter().map(|(in_layout, _)| {
+                if escaping.contains(&key.0) {
+                    Ownership::Owned
+                } else {
+                    layout_to_ownership(*in_layout, interner)
+                }
+            });
+
+            (*key, Vec::from_iter_in(signature, arena).into_bump_slice())
+        })
+        .collect();
+
+    loop {
+        let mut changed = false;
+
+        for (key, proc) in procedures.iter() {
+            let Some(&signature) = signatures.get(key) else {
+                continue;
+            };
+
+            if signature.iter().all(Ownership::is_owned) {
+                continue;
+            }
+
+            let new_signature = {
+                let mut state = State {
+                    args: proc.args,
+                    borrow_signature: Vec::from_iter_in(signature.iter().copied(), arena),
+                    signatures: &signatures,
+                };
+                state.inspect_stmt(&proc.body);
+
+                state.borrow_signature
+            };
+
+            if new_signature.as_slice() != signature {
+                signatures.insert(*key, new_signature.into_bump_slice());
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return signatures;
+        }
+    }
 }
 
+/// The ownership of a parameter with this layout before anything is known about its uses.
+/// Only `List *` and `Str` parameters are ever borrowed.
 fn layout_to_ownership<'a>(
     in_layout: InLayout<'a>,
     interner: &impl LayoutInterner<'a>,
@@ -38,34 +110,91 @@ fn layout_to_ownership<'a>(
     }
 }
 
-impl<'a> State<'a> {
-    fn new(arena: &'a Bump, interner: &impl LayoutInterner<'a>, proc: &'a Proc<'a>) -> Self {
-        let borrow_signature = Vec::from_iter_in(
-            proc.args
-                .iter()
-                .map(|(in_layout, _)| layout_to_ownership(*in_layout, interner)),
-            arena,
-        )
-        .into_bump_slice_mut();
-
-        Self {
-            args: proc.args,
-            borrow_signature,
+/// Collects the procedures which are called through a pointer, rather than by name.
+fn collect_escaping_procs(stmt: &Stmt<'_>, escaping: &mut MutSet<Symbol>) {
+    let mut stmt = stmt;
+
+    // Let bindings are walked in a loop, to prevent the stack from overflowing when there are
+    // many of them.
+    while let Stmt::Let(_, expr, _, continuation) = stmt {
+        match expr {
+            Expr::FunctionPointer { lambda_name } => {
+                escaping.insert(lambda_name.name());
+            }
+            Expr::Call(Call {
+                call_type:
+                    CallType::HigherOrder(HigherOrderLowLevel {
+                        passed_function, ..
+                    }),
+                ..
+            }) => {
+                escaping.insert(passed_function.name.name());
+            }
+            _ => {}
         }
+
+        stmt = continuation;
     }
 
+    match stmt {
+        Stmt::Let(..) => unreachable!("All let bindings should have been walked"),
+        Stmt::Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            for (_, _, branch) in branches.iter() {
+                collect_escaping_procs(branch, escaping);
+            }
+            collect_escaping_procs(default_branch.1, escaping);
+        }
+        Stmt::Join {
+            body, remainder, ..
+        } => {
+            collect_escaping_procs(body, escaping);
+            collect_escaping_procs(remainder, escaping);
+        }
+        Stmt::Refcounting(_, remainder)
+        | Stmt::Expect { remainder, .. }
+        | Stmt::ExpectFx { remainder, .. }
+        | Stmt::Dbg { remainder, .. } => collect_escaping_procs(remainder, escaping),
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => {}
+    }
+}
+
+struct State<'a, 'r> {
+    /// The parameters of the procedure whose signature is being inferred
+    args: &'a [(InLayout<'a>, Symbol)],
+    borrow_signature: Vec<'a, Ownership>,
+    /// The signatures inferred so far, which calls to other procedures are checked against
+    signatures: &'r BorrowSignatures<'a>,
+}
+
+impl<'a> State<'a, '_> {
     fn mark_owned(&mut self, symbol: Symbol) {
         if let Some(index) = self.args.iter().position(|(_, s)| *s == symbol) {
             self.borrow_signature[index] = Ownership::Owned;
         }
     }
 
-    fn inspect_stmt(&mut self, stmt: &'a Stmt<'a>) {
+    fn mark_all_owned(&mut self, symbols: impl IntoIterator<Item = Symbol>) {
+        for symbol in symbols {
+            self.mark_owned(symbol)
+        }
+    }
+
+    fn inspect_stmt(&mut self, stmt: &Stmt<'a>) {
+        let mut stmt = stmt;
+
+        // Let bindings are walked in a loop, to prevent the stack from overflowing when there
+        // are many of them.
+        while let Stmt::Let(_, expr, _, continuation) = stmt {
+            self.inspect_expr(expr);
+            stmt = continuation;
+        }
+
         match stmt {
-            Stmt::Let(_, expr, _, stmt) => {
-                self.inspect_expr(expr);
-                self.inspect_stmt(stmt);
-            }
+            Stmt::Let(..) => unreachable!("All let bindings should have been walked"),
             Stmt::Switch {
                 branches,
                 default_branch,
@@ -76,15 +205,18 @@ impl<'a> State<'a> {
                 }
                 self.inspect_stmt(default_branch.1);
             }
-            Stmt::Ret(_) => todo!(),
-            Stmt::Refcounting(_, _) => todo!(),
-            Stmt::Expect { .. } | Stmt::ExpectFx { .. } => {
-                // TODO do we rely on values being passed by-value here?
-                // it would be better to pass by-reference in general
+            Stmt::Ret(symbol) => {
+                // The caller gets a reference to the returned value, so it must be owned.
+                self.mark_owned(*symbol)
+            }
+            Stmt::Refcounting(_, _) => unreachable!("refcounting should not be in the AST yet"),
+            Stmt::Expect { remainder, .. } | Stmt::ExpectFx { remainder, .. } => {
+                // The lookups of an expect are only read.
+                self.inspect_stmt(remainder);
             }
-            Stmt::Dbg { .. } => {
-                // TODO do we rely on values being passed by-value here?
-                // it would be better to pass by-reference in general
+            Stmt::Dbg { remainder, .. } => {
+                // The value that is printed is only read.
+                self.inspect_stmt(remainder);
             }
             Stmt::Join {
                 body, remainder, ..
@@ -92,33 +224,65 @@ impl<'a> State<'a> {
                 self.inspect_stmt(body);
                 self.inspect_stmt(remainder);
             }
-
-            Stmt::Jump(_, _) | Stmt::Crash(_, _) => { /* not relevant for ownership */ }
+            Stmt::Jump(_, arguments) => {
+                // Join point parameters are always owned.
+                self.mark_all_owned(arguments.iter().copied())
+            }
+            Stmt::Crash(_, _) => { /* not relevant for ownership */ }
         }
     }
 
-    fn inspect_expr(&mut self, expr: &'a Expr<'a>) {
-        if let Expr::Call(call) = expr {
-            self.inspect_call(call)
+    fn inspect_expr(&mut self, expr: &Expr<'a>) {
+        match expr {
+            Expr::Call(call) => self.inspect_call(call),
+            Expr::Tag { arguments, .. } | Expr::Struct(arguments) => {
+                self.mark_all_owned(arguments.iter().copied())
+            }
+            Expr::Array { elems, .. } => {
+                self.mark_all_owned(elems.iter().filter_map(|element| match element {
+                    ListLiteralElement::Literal(_) => None,
+                    ListLiteralElement::Symbol(symbol) => Some(*symbol),
+                }))
+            }
+            Expr::ErasedMake { value, .. } => self.mark_all_owned(value.iter().copied()),
+            Expr::Alloca { initializer, .. } => self.mark_all_owned(initializer.iter().copied()),
+            _ => { /* only reads its arguments, if it has any */ }
         }
     }
 
-    fn inspect_call(&mut self, call: &'a Call<'a>) {
+    fn inspect_call(&mut self, call: &Call<'a>) {
         let Call {
             call_type,
             arguments,
         } = call;
 
-        match call_type {
-            CallType::ByName { name: _, .. } => {
-                // TODO ownership should depend on the borrow signature of the called function
-                for argument in arguments.iter() {
-                    self.mark_owned(*argument)
+        match call_type.clone().replace_lowlevel_wrapper() {
+            CallType::ByName {
+                name,
+                ret_layout,
+                arg_layouts,
+                ..
+            } => {
+                let proc_layout = ProcLayout {
+                    arguments: arg_layouts,
+                    result: ret_layout,
+                    niche: name.niche(),
+                };
+
+                match self.signatures.get(&(name.name(), proc_layout)) {
+                    Some(borrow_signature) => {
+                        for (argument, ownership) in arguments.iter().zip(borrow_signature.iter()) {
+                            if ownership.is_owned() {
+                                self.mark_owned(*argument);
+                            }
+                        }
+                    }
+                    None => self.mark_all_owned(arguments.iter().copied()),
                 }
             }
             CallType::LowLevel { op, .. } => {
                 // if the lowlevel must own the argument, mark it as owned
-                let borrow_signature = crate::inc_dec::lowlevel_borrow_signature(*op);
+                let borrow_signature = crate::inc_dec::lowlevel_borrow_signature(op);
 
                 for (argument, ownership) in arguments.iter().zip(borrow_signature) {
                     if ownership.is_owned() {
@@ -127,9 +291,7 @@ impl<'a> State<'a> {
                 }
             }
             CallType::ByPointer { .. } | CallType::Foreign { .. } | CallType::HigherOrder(_) => {
-                for argument in arguments.iter() {
-                    self.mark_owned(*argument)
-                }
+                self.mark_all_owned(arguments.iter().copied())
             }
         }
     }
@@ -0,0 +1,96 @@
+//! Branch coverage for `roc test --coverage`.
+//!
+//! Every `when` branch in the modules being tested gets a counter. When lowering a branch, a
+//! call to the [COVERAGE_HIT] builtin with the branch's counter is put in front of its body.
+//! Counters are numbered across all modules, so that the test runner can hand the compiled
+//! tests a single buffer to count in, and map each counter back to the region of its branch.
+use roc_can::expr::{Declarations, Expr};
+use roc_can::traverse::{walk_expr, Visitor};
+use roc_region::all::Region;
+use roc_types::subs::Variable;
+
+/// The builtin that increments a counter, given its index.
+pub const COVERAGE_HIT: &str = "roc_coverage_hit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoveredBranch {
+    /// The `when` expression this is a branch of.
+    pub when_region: Region,
+    /// The branch's body.
+    pub region: Region,
+}
+
+/// The `when` branches of a module, each with its own counter.
+#[derive(Debug, Clone, Default)]
+pub struct BranchCounters {
+    /// The counter of the first branch. The others follow it in order.
+    pub first_counter: u64,
+    /// Sorted by the region of the branch.
+    pub branches: Vec<CoveredBranch>,
+}
+
+impl BranchCounters {
+    pub fn new(decls: &Declarations, first_counter: u64) -> Self {
+        let mut collector = CollectBranches::default();
+        collector.visit_decls(decls);
+
+        let mut branches = collector.branches;
+        branches.sort_by_key(|branch| branch.region);
+        branches.dedup_by_key(|branch| branch.region);
+
+        Self {
+            first_counter,
+            branches,
+        }
+    }
+
+    /// The counter of the branch whose body is at the given region.
+    pub fn counter(&self, region: Region) -> Option<u64> {
+        let index = self
+            .branches
+            .binary_search_by_key(&region, |branch| branch.region)
+            .ok()?;
+
+        Some(self.first_counter + index as u64)
+    }
+
+    /// One past the last counter of this module.
+    pub fn end(&self) -> u64 {
+        self.first_counter + self.branches.len() as u64
+    }
+
+    /// The branches, with the number of times each one ran according to the counters of all
+    /// modules.
+    pub fn hits<'c>(
+        &'c self,
+        counters: &'c [u64],
+    ) -> impl Iterator<Item = (CoveredBranch, u64)> + 'c {
+        self.branches
+            .iter()
+            .enumerate()
+            .map(move |(index, branch)| {
+                let counter = self.first_counter as usize + index;
+
+                (*branch, counters.get(counter).copied().unwrap_or(0))
+            })
+    }
+}
+
+#[derive(Default)]
+struct CollectBranches {
+    branches: Vec<CoveredBranch>,
+}
+
+impl Visitor for CollectBranches {
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        if let Expr::When { branches, .. } = expr {
+            self.branches
+                .extend(branches.iter().map(|branch| CoveredBranch {
+                    when_region: region,
+                    region: branch.value.region,
+                }));
+        }
+
+        walk_expr(self, expr, var);
+    }
+}
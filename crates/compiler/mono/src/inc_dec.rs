@@ -14,6 +14,7 @@ use roc_error_macros::internal_error;
 use roc_module::low_level::LowLevel;
 use roc_module::{low_level::LowLevelWrapperType, symbol::Symbol};
 
+use crate::borrow::{infer_borrow_signatures, BorrowSignatures};
 use crate::ir::ErasedField;
 use crate::{
     ir::{
@@ -26,24 +27,39 @@ use crate::{
 
 /**
 Insert the reference count operations for procedures.
+The procedures that are exposed to the host take all their parameters as owned, the others may borrow some of them.
 */
 pub fn insert_inc_dec_operations<'a>(
     arena: &'a Bump,
     layout_interner: &STLayoutInterner<'a>,
-    procedures: &mut HashMap<(Symbol, ProcLayout), Proc<'a>, BuildHasherDefault<WyHash>>,
+    procedures: &mut HashMap<(Symbol, ProcLayout<'a>), Proc<'a>, BuildHasherDefault<WyHash>>,
+    host_exposed_procs: &[Symbol],
 ) {
+    let borrow_signatures =
+        infer_borrow_signatures(arena, layout_interner, procedures, host_exposed_procs);
+
     // All calls to lowlevels are wrapped in another function to help with type inference and return/parameter layouts.
     // But this lowlevel might get inlined into the caller of the wrapper and thus removing any reference counting operations.
     // Thus, these rc operations are performed on the caller of the wrapper instead, and we skip rc on the lowlevel.
     // It might be possible to inline the lowlevels at this point already,
     // but previous attempt conflicted as the parameters layouts and return layout do not match.
-    for ((symbol, _layout), proc) in procedures.iter_mut() {
+    for ((symbol, layout), proc) in procedures.iter_mut() {
         if matches!(
             LowLevelWrapperType::from_symbol(*symbol),
             LowLevelWrapperType::NotALowLevelWrapper
         ) {
             let symbol_rc_types_env = SymbolRcTypesEnv::from_layout_interner(layout_interner);
-            insert_inc_dec_operations_proc(arena, symbol_rc_types_env, proc);
+            let borrow_signature = borrow_signatures
+                .get(&(*symbol, *layout))
+                .copied()
+                .unwrap_or_default();
+            insert_inc_dec_operations_proc(
+                arena,
+                symbol_rc_types_env,
+                &borrow_signatures,
+                borrow_signature,
+                proc,
+            );
         }
     }
 }
@@ -256,6 +272,10 @@ struct RefcountEnvironment<'v> {
     // The Koka implementation assumes everything that is not owned to be borrowed.
     symbols_ownership: SymbolsOwnership,
     jointpoint_closures: MutMap<JoinPointId, JoinPointConsumption>,
+    // The ownership of the parameters of every procedure, to know which arguments a call consumes.
+    borrow_signatures: &'v BorrowSignatures<'v>,
+    // The parameters of the current procedure that are borrowed, these stay borrowed throughout.
+    borrowed_parameters: &'v MutSet<Symbol>,
 }
 
 impl<'v> RefcountEnvironment<'v> {
@@ -316,6 +336,21 @@ impl<'v> RefcountEnvironment<'v> {
         }
     }
 
+    /**
+    Add a parameter of the procedure to the environment if it is reference counted.
+    A borrowed parameter can be used but not consumed, as the caller keeps its reference.
+    */
+    fn add_parameter(&mut self, symbol: Symbol, ownership: Ownership) {
+        match self.get_symbol_rc_type(&symbol) {
+            VarRcType::ReferenceCounted => {
+                self.symbols_ownership.insert(symbol, ownership);
+            }
+            VarRcType::NotReferenceCounted => {
+                // If this symbol is not reference counted, we don't need to do anything.
+            }
+        }
+    }
+
     /**
     Remove a symbol from the environment.
     Is used when a symbol is no longer in scope (before a let binding).
@@ -403,22 +438,42 @@ impl<'v> RefcountEnvironment<'v> {
 fn insert_inc_dec_operations_proc<'a>(
     arena: &'a Bump,
     mut symbol_rc_types_env: SymbolRcTypesEnv<'a, '_>,
+    borrow_signatures: &BorrowSignatures<'a>,
+    borrow_signature: &[Ownership],
     proc: &mut Proc<'a>,
 ) {
     // Clone the symbol_rc_types_env and insert the symbols in the current procedure.
     // As the symbols should be limited in scope for the current proc.
     symbol_rc_types_env.insert_symbols_rc_type_proc(proc);
 
+    let parameter_ownership = |index: usize| {
+        borrow_signature
+            .get(index)
+            .copied()
+            .unwrap_or(Ownership::Owned)
+    };
+
+    let borrowed_parameters = proc
+        .args
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_layout, symbol))| {
+            parameter_ownership(index).is_borrowed().then_some(*symbol)
+        })
+        .collect::<MutSet<_>>();
+
     let mut environment = RefcountEnvironment {
         symbols_rc_types: &symbol_rc_types_env.symbols_rc_type,
         symbols_ownership: MutMap::default(),
         jointpoint_closures: MutMap::default(),
+        borrow_signatures,
+        borrowed_parameters: &borrowed_parameters,
     };
 
     // Add all arguments to the environment (if they are reference counted)
     let proc_symbols = proc.args.iter().map(|(_layout, symbol)| symbol);
-    for symbol in proc_symbols.clone() {
-        environment.add_symbol(*symbol);
+    for (index, symbol) in proc_symbols.clone().enumerate() {
+        environment.add_parameter(*symbol, parameter_ownership(index));
     }
 
     // Update the body with reference count statements.
@@ -720,10 +775,12 @@ fn insert_refcount_operations_stmt<'v, 'a>(
         } => {
             // Assuming that the values in the closure of the body of this jointpoint are already bound.
             // Assuming that all symbols are still owned. (So that we can determine what symbols got consumed in the join point.)
+            // Except for the borrowed parameters of the procedure, which are never owned.
             debug_assert!(environment
                 .symbols_ownership
                 .iter()
-                .all(|(_, ownership)| ownership.is_owned()));
+                .all(|(symbol, ownership)| ownership.is_owned()
+                    || environment.borrowed_parameters.contains(symbol)));
 
             let mut body_env = environment.clone();
 
@@ -891,6 +948,24 @@ fn insert_refcount_operations_binding<'a>(
         };
     }
 
+    macro_rules! call_with_borrow_signature {
+        ($arguments:expr, $borrow_signature:expr) => {{
+            // The arguments that are owned by the callee are consumed, the borrowed ones might have to be dropped after the call.
+            let arguments_with_borrow_signature = $arguments
+                .iter()
+                .copied()
+                .zip($borrow_signature.iter().copied());
+            let owned_arguments = arguments_with_borrow_signature
+                .clone()
+                .filter_map(|(symbol, ownership)| ownership.is_owned().then_some(symbol));
+            let borrowed_arguments = arguments_with_borrow_signature
+                .filter_map(|(symbol, ownership)| ownership.is_borrowed().then_some(symbol));
+            let new_stmt = dec_borrowed!(borrowed_arguments, stmt);
+            let new_let = new_let!(new_stmt);
+            inc_owned!(owned_arguments, new_let)
+        }};
+    }
+
     match expr {
         Expr::Literal(_)
         | Expr::NullPointer
@@ -979,11 +1054,31 @@ fn insert_refcount_operations_binding<'a>(
         }) => {
             match call_type.clone().replace_lowlevel_wrapper() {
                 // A by name call refers to a normal function call.
-                // Normal functions take all their parameters as owned, so we can mark them all as such.
-                CallType::ByName { .. } => {
-                    let new_let = new_let!(stmt);
+                // Normal functions take their parameters as their inferred borrow signature says.
+                CallType::ByName {
+                    name,
+                    ret_layout,
+                    arg_layouts,
+                    ..
+                } => {
+                    let proc_layout = ProcLayout {
+                        arguments: arg_layouts,
+                        result: ret_layout,
+                        niche: name.niche(),
+                    };
+
+                    let borrow_signatures = environment.borrow_signatures;
+                    match borrow_signatures.get(&(name.name(), proc_layout)) {
+                        Some(borrow_signature) => {
+                            call_with_borrow_signature!(arguments, borrow_signature)
+                        }
+                        // Functions we know nothing about take all their parameters as owned.
+                        None => {
+                            let new_let = new_let!(stmt);
 
-                    inc_owned!(arguments.iter().copied(), new_let)
+                            inc_owned!(arguments.iter().copied(), new_let)
+                        }
+                    }
                 }
                 // A normal Roc function call, but we don't actually know where its target is.
                 // As such, we assume that it takes all parameters as owned, as will the function
@@ -1025,21 +1120,7 @@ fn insert_refcount_operations_binding<'a>(
                     }
                     // Otherwise, perform regular reference counting using the lowlevel borrow signature.
                     _ => {
-                        let borrow_signature = lowlevel_borrow_signature(operator);
-                        let arguments_with_borrow_signature = arguments
-                            .iter()
-                            .copied()
-                            .zip(borrow_signature.iter().copied());
-                        let owned_arguments = arguments_with_borrow_signature.clone().filter_map(
-                            |(symbol, ownership)| ownership.is_owned().then_some(symbol),
-                        );
-                        let borrowed_arguments =
-                            arguments_with_borrow_signature.filter_map(|(symbol, ownership)| {
-                                ownership.is_borrowed().then_some(symbol)
-                            });
-                        let new_stmt = dec_borrowed!(borrowed_arguments, stmt);
-                        let new_let = new_let!(new_stmt);
-                        inc_owned!(owned_arguments, new_let)
+                        call_with_borrow_signature!(arguments, lowlevel_borrow_signature(operator))
                     }
                 },
                 CallType::HigherOrder(HigherOrderLowLevel {
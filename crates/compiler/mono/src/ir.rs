@@ -1,5 +1,6 @@
 #![allow(clippy::manual_map)]
 
+use crate::coverage::{BranchCounters, COVERAGE_HIT};
 use crate::ir::erased::{build_erased_function, ResolvedErasedLambda};
 use crate::ir::literal::{make_num_literal, IntOrFloatValue};
 use crate::layout::{
@@ -319,6 +320,17 @@ pub struct HostExposedLambdaSet<'a> {
     pub raw_function_layout: RawFunctionLayout<'a>,
 }
 
+/// A function that the program calls on its host: a hosted function like `roc_fx_putLine`, or a
+/// C function that the platform declares
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostedFunction<'a> {
+    pub foreign_symbol: ForeignSymbol,
+    pub arg_layouts: &'a [InLayout<'a>],
+    pub ret_layout: InLayout<'a>,
+    /// The declared return type is a `Result`, so the host answers with an `Err` or an `Ok`
+    pub returns_result: bool,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SelfRecursive {
     NotSelfRecursive,
@@ -898,6 +910,8 @@ impl<'a> SpecializationStack<'a> {
 pub type HostExposedLambdaSets<'a> =
     std::vec::Vec<(LambdaName<'a>, Symbol, HostExposedLambdaSet<'a>)>;
 
+pub type HostedFunctions<'a> = std::vec::Vec<HostedFunction<'a>>;
+
 #[derive(Clone, Debug)]
 pub struct Procs<'a> {
     pub partial_procs: PartialProcs<'a>,
@@ -905,6 +919,7 @@ pub struct Procs<'a> {
     pending_specializations: PendingSpecializations<'a>,
     specialized: Specialized<'a>,
     host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
+    hosted_functions: HostedFunctions<'a>,
     pub runtime_errors: BumpMap<Symbol, &'a str>,
     pub externals_we_need: BumpMap<ModuleId, ExternalSpecializations<'a>>,
     symbol_specializations: SymbolSpecializations<'a>,
@@ -925,6 +940,7 @@ impl<'a> Procs<'a> {
             runtime_errors: BumpMap::new_in(arena),
             externals_we_need: BumpMap::new_in(arena),
             host_exposed_lambda_sets: std::vec::Vec::new(),
+            hosted_functions: std::vec::Vec::new(),
             symbol_specializations: Default::default(),
             specialization_stack: SpecializationStack(Vec::with_capacity_in(16, arena)),
 
@@ -993,6 +1009,7 @@ impl<'a> Procs<'a> {
     ) -> (
         MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
         HostExposedLambdaSets<'a>,
+        HostedFunctions<'a>,
         ProcsBase<'a>,
     ) {
         let mut specialized_procs =
@@ -1015,6 +1032,7 @@ impl<'a> Procs<'a> {
         (
             specialized_procs,
             self.host_exposed_lambda_sets,
+            self.hosted_functions,
             restored_procs_base,
         )
     }
@@ -1362,6 +1380,8 @@ pub struct Env<'a, 'i> {
     pub exposed_by_module: &'i ExposedByModule,
     pub derived_module: &'i SharedDerivedModule,
     pub struct_indexing: UsageTrackingMap<(Symbol, u64), Symbol>,
+    /// The counters of this module's `when` branches, if they should count how often they run.
+    pub branch_counters: Option<&'i BranchCounters>,
 }
 
 impl<'a, 'i> Env<'a, 'i> {
@@ -2858,11 +2878,12 @@ fn pattern_to_when(
             // for underscore we generate a dummy Symbol
             (env.unique_symbol(), body)
         }
-        Shadowed(region, loc_ident, new_symbol) => {
+        Shadowed(original, loc_ident, new_symbol) => {
             let error = roc_problem::can::RuntimeError::Shadowing {
-                original_region: *region,
+                original_region: original.region,
                 shadow: loc_ident.clone(),
                 kind: ShadowKind::Variable,
+                original_symbol: original.value,
             };
             (*new_symbol, Loc::at_zero(RuntimeError(error)))
         }
@@ -3154,6 +3175,128 @@ fn specialize_host_specializations<'a>(
     }
 }
 
+/// Writes down the signature of a function that the host provides. Like the callers of its
+/// closures, this is taken from the first specialization of the foreign call.
+fn record_hosted_function<'a>(
+    env: &mut Env<'a, '_>,
+    procs: &mut Procs<'a>,
+    layout_cache: &mut LayoutCache<'a>,
+    foreign_symbol: &ForeignSymbol,
+    argument_variables: impl Iterator<Item = Variable>,
+    ret_layout: InLayout<'a>,
+    returns_result: bool,
+) {
+    if procs
+        .hosted_functions
+        .iter()
+        .any(|function| function.foreign_symbol == *foreign_symbol)
+    {
+        return;
+    }
+
+    let mut arg_layouts = Vec::new_in(env.arena);
+
+    for var in argument_variables {
+        match layout_cache.from_var(env.arena, var, env.subs) {
+            Ok(layout) => arg_layouts.push(layout),
+            // The call itself reports the problem
+            Err(_) => return,
+        }
+    }
+
+    procs.hosted_functions.push(HostedFunction {
+        foreign_symbol: foreign_symbol.clone(),
+        arg_layouts: arg_layouts.into_bump_slice(),
+        ret_layout,
+        returns_result,
+    });
+}
+
+/// Whether this is a `Result`, perhaps under other aliases
+fn is_result(subs: &Subs, mut var: Variable) -> bool {
+    loop {
+        match subs.get_content_without_compacting(var) {
+            Content::Alias(Symbol::RESULT_RESULT, ..) => return true,
+            Content::Alias(_, _, real_var, _) => var = *real_var,
+            _ => return false,
+        }
+    }
+}
+
+/// A host that gets a Roc closure from a foreign call, like the `U64 -> Effect {}` given to
+/// `onTick : (U64 -> Effect {}) -> Effect {}`, can only call it through a caller, just like the
+/// closures in the values a platform provides. The foreign function gets a pointer to the
+/// closure's data, and calls it with e.g. `roc__fx_onTick_0_caller`. Lambda sets are numbered as
+/// for provided values, starting with the lambda set of the first closure argument, so the
+/// `Effect {}` that closure returns is run with `roc__fx_onTick_1_caller`.
+fn expose_foreign_call_closures<'a>(
+    env: &mut Env<'a, '_>,
+    procs: &mut Procs<'a>,
+    layout_cache: &mut LayoutCache<'a>,
+    foreign_symbol: &ForeignSymbol,
+    argument_variables: impl DoubleEndedIterator<Item = Variable>,
+) {
+    // `find_lambda_sets_help` pops from the back
+    let stack = Vec::from_iter_in(argument_variables.rev(), env.arena);
+    let lambda_sets = find_lambda_sets_help(env.subs, stack);
+
+    if lambda_sets.is_empty() {
+        return;
+    }
+
+    let foreign_name = foreign_symbol.as_str();
+    let exposed_name = format!(
+        "fx_{}",
+        foreign_name.strip_prefix("roc_fx_").unwrap_or(foreign_name)
+    );
+    let exposed_symbol = Symbol::new(env.home, env.ident_ids.get_or_insert(&exposed_name));
+
+    // The host links against these callers by name, so every specialization of the foreign call
+    // must share them. We expose the closures of the first one.
+    if procs
+        .host_exposed_lambda_sets
+        .iter()
+        .any(|(exposed, _, _)| exposed.name() == exposed_symbol)
+    {
+        return;
+    }
+
+    for (var, id) in lambda_sets {
+        let symbol = env.unique_symbol();
+        let lambda_name = LambdaName::no_niche(symbol);
+
+        let mut layout_env = layout::Env::from_components(layout_cache, env.subs, env.arena);
+        let lambda_set = env.subs.get_lambda_set(var);
+        let raw_function_layout =
+            RawFunctionLayout::from_var(&mut layout_env, lambda_set.ambient_function)
+                .value()
+                .unwrap();
+
+        let (key, (top_level, proc)) = generate_host_exposed_function(
+            env,
+            procs,
+            layout_cache,
+            lambda_name,
+            raw_function_layout,
+        );
+
+        procs
+            .specialized
+            .insert_specialized(symbol, top_level, proc);
+
+        let hels = HostExposedLambdaSet {
+            id,
+            symbol,
+            proc_layout: top_level,
+            raw_function_layout,
+        };
+
+        procs
+            .host_exposed_lambda_sets
+            .push((LambdaName::no_niche(exposed_symbol), key, hels));
+    }
+}
+
 fn specialize_external_specializations<'a>(
     env: &mut Env<'a, '_>,
     procs: &mut Procs<'a>,
@@ -4114,7 +4257,7 @@ impl<'a> ProcLayout<'a> {
         }
     }
 
-    fn from_raw_named(
+    pub fn from_raw_named(
         arena: &'a Bump,
         lambda_name: LambdaName<'a>,
         raw: RawFunctionLayout<'a>,
@@ -5683,6 +5826,14 @@ pub fn with_hole<'a>(
             }
             let arg_symbols = arg_symbols.into_bump_slice();
 
+            expose_foreign_call_closures(
+                env,
+                procs,
+                layout_cache,
+                &foreign_symbol,
+                args.iter().map(|(var, _)| *var),
+            );
+
             // layout of the return type
             let layout = return_on_layout_error!(
                 env,
@@ -5690,6 +5841,16 @@ pub fn with_hole<'a>(
                 "ForeignCall"
             );
 
+            record_hosted_function(
+                env,
+                procs,
+                layout_cache,
+                &foreign_symbol,
+                args.iter().map(|(var, _)| *var),
+                layout,
+                is_result(env.subs, ret_var),
+            );
+
             let call = self::Call {
                 call_type: CallType::Foreign {
                     foreign_symbol,
@@ -7210,17 +7371,21 @@ fn store_specialized_expectation_lookups(
     }
 }
 
+/// A `when` branch's pattern, guard and body, with the region of the body if it's in the source.
+type OptBranch<'a> = (
+    Pattern<'a>,
+    Option<Loc<roc_can::expr::Expr>>,
+    roc_can::expr::Expr,
+    Option<Region>,
+);
+
 fn to_opt_branches<'a>(
     env: &mut Env<'a, '_>,
     procs: &mut Procs<'a>,
     branches: std::vec::Vec<roc_can::expr::WhenBranch>,
     exhaustive_mark: ExhaustiveMark,
     layout_cache: &mut LayoutCache<'a>,
-) -> std::vec::Vec<(
-    Pattern<'a>,
-    Option<Loc<roc_can::expr::Expr>>,
-    roc_can::expr::Expr,
-)> {
+) -> std::vec::Vec<OptBranch<'a>> {
     debug_assert!(!branches.is_empty());
 
     let mut opt_branches = std::vec::Vec::new();
@@ -7264,7 +7429,12 @@ fn to_opt_branches<'a>(
                     };
 
                     // TODO remove clone?
-                    opt_branches.push((mono_pattern, when_branch.guard.clone(), loc_expr.value));
+                    opt_branches.push((
+                        mono_pattern,
+                        when_branch.guard.clone(),
+                        loc_expr.value,
+                        Some(when_branch.value.region),
+                    ));
                 }
                 Err(runtime_error) => {
                     // TODO remove clone?
@@ -7272,6 +7442,7 @@ fn to_opt_branches<'a>(
                         Pattern::Underscore,
                         when_branch.guard.clone(),
                         roc_can::expr::Expr::RuntimeError(runtime_error),
+                        Some(when_branch.value.region),
                     ));
                 }
             }
@@ -7285,6 +7456,7 @@ fn to_opt_branches<'a>(
             Pattern::Underscore,
             None,
             roc_can::expr::Expr::RuntimeError(roc_problem::can::RuntimeError::NonExhaustivePattern),
+            None,
         ));
     }
 
@@ -7325,7 +7497,7 @@ fn from_can_when<'a>(
     let arena = env.arena;
     let it = opt_branches
         .into_iter()
-        .filter_map(|(pattern, opt_guard, can_expr)| {
+        .filter_map(|(pattern, opt_guard, can_expr, region)| {
             // If the pattern has a void layout we can drop it; however, we must still perform the
             // work of building the body, because that may contain specializations we must
             // discover for use elsewhere. See
@@ -7353,6 +7525,11 @@ fn from_can_when<'a>(
                 }
             };
 
+            let branch_stmt = match region {
+                Some(region) => count_branch(env, region, branch_stmt),
+                None => branch_stmt,
+            };
+
             use decision_tree::Guard;
             let result = if let Some(loc_expr) = opt_guard {
                 let guard_spec = GuardStmtSpec {
@@ -7392,6 +7569,43 @@ fn from_can_when<'a>(
     )
 }
 
+/// Prepends a call that counts how often the branch at the given region runs, if the module's
+/// branches are counted.
+fn count_branch<'a>(env: &mut Env<'a, '_>, region: Region, stmt: Stmt<'a>) -> Stmt<'a> {
+    let counter = match env
+        .branch_counters
+        .and_then(|counters| counters.counter(region))
+    {
+        Some(counter) => counter,
+        None => return stmt,
+    };
+
+    let counter_symbol = env.unique_symbol();
+    let unit_symbol = env.unique_symbol();
+
+    let call = self::Call {
+        call_type: CallType::Foreign {
+            foreign_symbol: ForeignSymbol::from(COVERAGE_HIT),
+            ret_layout: Layout::UNIT,
+        },
+        arguments: env.arena.alloc([counter_symbol]),
+    };
+
+    let stmt = Stmt::Let(
+        unit_symbol,
+        Expr::Call(call),
+        Layout::UNIT,
+        env.arena.alloc(stmt),
+    );
+
+    Stmt::Let(
+        counter_symbol,
+        Expr::Literal(Literal::Int((counter as i128).to_ne_bytes())),
+        Layout::U64,
+        env.arena.alloc(stmt),
+    )
+}
+
 /// A functor to generate IR for a guard under a `when` branch.
 /// Used in the decision tree compiler, after building a decision tree and converting into IR.
 ///
@@ -353,10 +353,11 @@ fn from_can_pattern_help<'a>(
                 o => internal_error!("an integer width was expected, but we found {:?}", o),
             }
         }
-        Shadowed(region, ident, _new_symbol) => Err(RuntimeError::Shadowing {
-            original_region: *region,
+        Shadowed(original, ident, _new_symbol) => Err(RuntimeError::Shadowing {
+            original_region: original.region,
             shadow: ident.clone(),
             kind: ShadowKind::Variable,
+            original_symbol: original.value,
         }),
         UnsupportedPattern(region) => Err(RuntimeError::UnsupportedPattern(*region)),
         MalformedPattern(_problem, region) => {
@@ -11,11 +11,13 @@
 
 pub mod borrow;
 pub mod code_gen_help;
+pub mod coverage;
 pub mod drop_specialization;
 pub mod inc_dec;
 pub mod ir;
 pub mod layout;
 pub mod low_level;
+pub mod property;
 pub mod reset_reuse;
 pub mod tail_recursion;
 
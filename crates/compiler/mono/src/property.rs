@@ -0,0 +1,155 @@
+//! Property-based testing for `roc test`.
+//!
+//! A top-level function with an `expect` in its body is a property: the test runner calls it with
+//! arguments it generates, and reports the smallest arguments it can find that make one of its
+//! expects fail.
+//!
+//! The test runner can only call thunks, so each property gets a thunk that loads the arguments
+//! from a buffer the test runner fills in, calls the function with them, and drops the result.
+//! The arguments are laid out like a struct with one field per argument, in order.
+use crate::ir::{Call, CallType, Env, Expr, Proc, ProcLayout, SelfRecursive, Stmt, UpdateModeId};
+use crate::layout::{LambdaName, Layout, LayoutCache, LayoutInterner, LayoutRepr};
+use bumpalo::collections::Vec;
+use roc_can::traverse::{walk_expr, Visitor};
+use roc_module::ident::ForeignSymbol;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+use roc_region::all::Region;
+use roc_types::subs::Variable;
+
+/// The builtin that returns a pointer to the arguments the test runner generated.
+pub const PROPERTY_ARGS: &str = "roc_property_args";
+
+/// The layout of a property's thunk, which is the same as that of a top-level expect.
+pub const PROPERTY_THUNK_LAYOUT: ProcLayout<'static> = ProcLayout {
+    arguments: &[],
+    result: Layout::UNIT,
+    niche: crate::layout::Niche::NONE,
+};
+
+/// Whether a function body has an `expect` in it, which makes the function a property.
+pub fn has_expect(body: &roc_can::expr::Expr, var: Variable) -> bool {
+    let mut finder = FindExpect { found: false };
+    finder.visit_expr(body, Region::zero(), var);
+
+    finder.found
+}
+
+struct FindExpect {
+    found: bool,
+}
+
+impl Visitor for FindExpect {
+    fn visit_expr(&mut self, expr: &roc_can::expr::Expr, _region: Region, var: Variable) {
+        if self.found {
+            return;
+        }
+
+        match expr {
+            roc_can::expr::Expr::Expect { .. } => self.found = true,
+            _ => walk_expr(self, expr, var),
+        }
+    }
+}
+
+/// Generates the thunk that calls the `function` property with the generated arguments.
+pub fn generate_property_thunk<'a>(
+    env: &mut Env<'a, '_>,
+    layout_cache: &mut LayoutCache<'a>,
+    thunk: Symbol,
+    function: Symbol,
+    function_layout: ProcLayout<'a>,
+) -> Proc<'a> {
+    let arena = env.arena;
+    let argument_layouts = function_layout.arguments;
+
+    let args_layout = layout_cache
+        .interner
+        .insert_direct_no_semantic(LayoutRepr::struct_(argument_layouts));
+    let args_ptr_layout = layout_cache
+        .interner
+        .insert_direct_no_semantic(LayoutRepr::Ptr(args_layout));
+
+    let args_ptr_symbol = env.unique_symbol();
+    let args_symbol = env.unique_symbol();
+    let result_symbol = env.unique_symbol();
+    let unit_symbol = env.unique_symbol();
+
+    let argument_symbols =
+        Vec::from_iter_in(argument_layouts.iter().map(|_| env.unique_symbol()), arena)
+            .into_bump_slice();
+
+    let call = Call {
+        call_type: CallType::ByName {
+            name: LambdaName::no_niche(function),
+            ret_layout: function_layout.result,
+            arg_layouts: argument_layouts,
+            specialization_id: env.next_call_specialization_id(),
+        },
+        arguments: argument_symbols,
+    };
+
+    let mut body = Stmt::Let(
+        unit_symbol,
+        Expr::Struct(&[]),
+        Layout::UNIT,
+        arena.alloc(Stmt::Ret(unit_symbol)),
+    );
+
+    body = Stmt::Let(
+        result_symbol,
+        Expr::Call(call),
+        function_layout.result,
+        arena.alloc(body),
+    );
+
+    for (index, (symbol, layout)) in argument_symbols
+        .iter()
+        .zip(argument_layouts)
+        .enumerate()
+        .rev()
+    {
+        let expr = Expr::StructAtIndex {
+            index: index as _,
+            field_layouts: argument_layouts,
+            structure: args_symbol,
+        };
+
+        body = Stmt::Let(*symbol, expr, *layout, arena.alloc(body));
+    }
+
+    let load = Expr::Call(Call {
+        call_type: CallType::LowLevel {
+            op: LowLevel::PtrLoad,
+            update_mode: UpdateModeId::BACKEND_DUMMY,
+        },
+        arguments: arena.alloc([args_ptr_symbol]),
+    });
+
+    body = Stmt::Let(args_symbol, load, args_layout, arena.alloc(body));
+
+    let args_ptr = Expr::Call(Call {
+        call_type: CallType::Foreign {
+            foreign_symbol: ForeignSymbol::from(PROPERTY_ARGS),
+            ret_layout: args_ptr_layout,
+        },
+        arguments: &[],
+    });
+
+    body = Stmt::Let(
+        args_ptr_symbol,
+        args_ptr,
+        args_ptr_layout,
+        arena.alloc(body),
+    );
+
+    Proc {
+        name: LambdaName::no_niche(thunk),
+        args: &[],
+        body,
+        closure_data_layout: None,
+        ret_layout: Layout::UNIT,
+        is_self_recursive: SelfRecursive::NotSelfRecursive,
+        is_erased: false,
+    }
+}
@@ -1395,6 +1395,12 @@ impl<'a> Expr<'a> {
         ident: "<function>",
     };
 
+    /// Stands in for values the repl left out because of its print limits.
+    pub const REPL_ELIDED: Self = Expr::Var {
+        module_name: "",
+        ident: "…",
+    };
+
     pub const REPL_RUNTIME_CRASH: Self = Expr::Var {
         module_name: "",
         ident: "*",
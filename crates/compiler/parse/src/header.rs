@@ -47,6 +47,9 @@ pub enum HeaderType<'a> {
         exposes: &'a [Loc<ExposedName<'a>>],
         generates: UppercaseIdent<'a>,
         generates_with: &'a [Loc<ExposedName<'a>>],
+        /// Effect types after the first. Each of these gets a module of its own, named after the
+        /// hosted module and the type, e.g. `Effect.Stream`.
+        more_generates: &'a [(UppercaseIdent<'a>, &'a [Loc<ExposedName<'a>>])],
     },
     /// Only created during canonicalization, never actually parsed from source
     Builtin {
@@ -240,6 +243,16 @@ pub struct HostedHeader<'a> {
     pub generates: KeywordItem<'a, GeneratesKeyword, UppercaseIdent<'a>>,
     pub generates_with:
         KeywordItem<'a, WithKeyword, Collection<'a, Loc<Spaced<'a, ExposedName<'a>>>>>,
+
+    /// Any further effect types, each in a `generates Stream with [after, map]` clause of its own.
+    pub more_generates: &'a [GeneratesClause<'a>],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratesClause<'a> {
+    pub generates: KeywordItem<'a, GeneratesKeyword, UppercaseIdent<'a>>,
+    pub generates_with:
+        KeywordItem<'a, WithKeyword, Collection<'a, Loc<Spaced<'a, ExposedName<'a>>>>>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -1,10 +1,11 @@
 use crate::ast::{Collection, Defs, Header, Module, Spaced, Spaces};
 use crate::blankspace::{space0_around_ee, space0_before_e, space0_e};
 use crate::header::{
-    package_entry, package_name, AppHeader, ExposedName, ExposesKeyword, GeneratesKeyword,
-    HostedHeader, ImportsEntry, ImportsKeyword, InterfaceHeader, Keyword, KeywordItem, ModuleName,
-    PackageEntry, PackageHeader, PackagesKeyword, PlatformHeader, PlatformRequires,
-    ProvidesKeyword, ProvidesTo, RequiresKeyword, To, ToKeyword, TypedIdent, WithKeyword,
+    package_entry, package_name, AppHeader, ExposedName, ExposesKeyword, GeneratesClause,
+    GeneratesKeyword, HostedHeader, ImportsEntry, ImportsKeyword, InterfaceHeader, Keyword,
+    KeywordItem, ModuleName, PackageEntry, PackageHeader, PackagesKeyword, PlatformHeader,
+    PlatformRequires, ProvidesKeyword, ProvidesTo, RequiresKeyword, To, ToKeyword, TypedIdent,
+    WithKeyword,
 };
 use crate::ident::{self, lowercase_ident, unqualified_ident, uppercase, UppercaseIdent};
 use crate::parser::Progress::{self, *};
@@ -112,6 +113,11 @@ fn hosted_header<'a>() -> impl Parser<'a, HostedHeader<'a>, EHeader<'a>> {
         imports: specialize(EHeader::Imports, imports()),
         generates: specialize(EHeader::Generates, generates()),
         generates_with: specialize(EHeader::GeneratesWith, generates_with()),
+        more_generates: map_with_arena!(
+            zero_or_more!(generates_clause()),
+            |_arena, clauses: bumpalo::collections::Vec<'a, GeneratesClause<'a>>| clauses
+                .into_bump_slice()
+        ),
     })
     .trace("hosted_header")
 }
@@ -490,6 +496,14 @@ fn packages<'a>() -> impl Parser<
     })
 }
 
+#[inline(always)]
+fn generates_clause<'a>() -> impl Parser<'a, GeneratesClause<'a>, EHeader<'a>> {
+    record!(GeneratesClause {
+        generates: specialize(EHeader::Generates, generates()),
+        generates_with: specialize(EHeader::GeneratesWith, generates_with()),
+    })
+}
+
 #[inline(always)]
 fn generates<'a>(
 ) -> impl Parser<'a, KeywordItem<'a, GeneratesKeyword, UppercaseIdent<'a>>, EGenerates> {
@@ -40,6 +40,11 @@ pub enum Problem {
     UnusedModuleImport(ModuleId, Region),
     ExposedButNotDefined(Symbol),
     UnknownGeneratesWith(Loc<Ident>),
+    /// A hosted module asks for a generated function, like `map`, that it already defines itself
+    GeneratedNameTaken {
+        generated: Loc<Ident>,
+        original_region: Region,
+    },
     /// First symbol is the name of the closure with that argument
     /// Bool is whether the closure is anonymous
     /// Second symbol is the name of the argument that is unused
@@ -54,6 +59,17 @@ pub enum Problem {
         shadow: Loc<Ident>,
         kind: ShadowKind,
     },
+    /// A variable shadows another one in a module that permits it (like the REPL's);
+    /// from the shadow on, the name refers to the new value.
+    PermittedShadowing {
+        original_region: Region,
+        shadow: Loc<Ident>,
+    },
+    /// A platform binds a C function whose signature uses a type that C doesn't have.
+    ForeignTypeNotCAbi {
+        symbol: Symbol,
+        region: Region,
+    },
     CyclicAlias(Symbol, Region, Vec<Symbol>, AliasKind),
     BadRecursion(Vec<CycleEntry>),
     PhantomTypeArgument {
@@ -223,11 +239,14 @@ impl Problem {
             Problem::UnusedModuleImport(_, _) => Warning,
             Problem::ExposedButNotDefined(_) => RuntimeError,
             Problem::UnknownGeneratesWith(_) => RuntimeError,
+            Problem::GeneratedNameTaken { .. } => RuntimeError,
             Problem::UnusedArgument(_, _, _, _) => Warning,
             Problem::UnusedBranchDef(_, _) => Warning,
             Problem::PrecedenceProblem(_) => RuntimeError,
             Problem::UnsupportedPattern(_, _) => RuntimeError,
             Problem::Shadowing { .. } => RuntimeError,
+            Problem::PermittedShadowing { .. } => Warning,
+            Problem::ForeignTypeNotCAbi { .. } => RuntimeError,
             Problem::CyclicAlias(..) => RuntimeError,
             Problem::BadRecursion(_) => RuntimeError,
             Problem::PhantomTypeArgument { .. } => Warning,
@@ -280,6 +299,70 @@ impl Problem {
         }
     }
 
+    /// The code shown in the report's header, and accepted by `roc explain`.
+    /// Codes never change once assigned, so only append new ones. Variants which describe
+    /// the same problem (like unused defs at the top level and in a branch) share a code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Problem::UnusedDef(..) | Problem::UnusedBranchDef(..) => "E1001",
+            Problem::UnusedImport(..) | Problem::UnusedModuleImport(..) => "E1002",
+            Problem::UnusedArgument(..) => "E1003",
+            Problem::ExposedButNotDefined(_) => "E1004",
+            Problem::UnknownGeneratesWith(_) => "E1005",
+            Problem::GeneratedNameTaken { .. } => "E1006",
+            Problem::DefsOnlyUsedInRecursion(..) => "E1007",
+            Problem::PrecedenceProblem(_) => "E1008",
+            Problem::UnsupportedPattern(..) => "E1009",
+            Problem::Shadowing { .. } => "E1010",
+            Problem::PermittedShadowing { .. } => "E1011",
+            Problem::CyclicAlias(..) => "E1013",
+            Problem::BadRecursion(_) => "E1014",
+            Problem::PhantomTypeArgument { .. } => "E1015",
+            Problem::UnboundTypeVariable { .. } => "E1016",
+            Problem::DuplicateRecordFieldValue { .. } => "E1017",
+            Problem::DuplicateRecordFieldType { .. } => "E1018",
+            Problem::InvalidOptionalValue { .. } => "E1019",
+            Problem::DuplicateTag { .. } => "E1020",
+            Problem::RuntimeError(runtime_error) => runtime_error.code(),
+            Problem::SignatureDefMismatch { .. } => "E1021",
+            Problem::InvalidAliasRigid { .. } => "E1022",
+            Problem::InvalidInterpolation(_) => "E1023",
+            Problem::InvalidHexadecimal(_) => "E1024",
+            Problem::InvalidUnicodeCodePt(_) => "E1025",
+            Problem::NestedDatatype { .. } => "E1026",
+            Problem::InvalidExtensionType { .. } => "E1027",
+            Problem::AbilityHasTypeVariables { .. } => "E1028",
+            Problem::ImplementsClauseIsNotAbility { .. } => "E1029",
+            Problem::IllegalImplementsClause { .. } => "E1030",
+            Problem::DuplicateImplementsAbility { .. } => "E1031",
+            Problem::AbilityMemberMissingImplementsClause { .. } => "E1032",
+            Problem::AbilityMemberMultipleBoundVars { .. } => "E1033",
+            Problem::AbilityNotOnToplevel { .. } => "E1034",
+            Problem::AbilityUsedAsType(..) => "E1035",
+            Problem::NestedSpecialization(..) => "E1036",
+            Problem::IllegalDerivedAbility(_) => "E1037",
+            Problem::ImplementationNotFound { .. } => "E1038",
+            Problem::NotAnAbilityMember { .. } => "E1039",
+            Problem::OptionalAbilityImpl { .. } => "E1040",
+            Problem::QualifiedAbilityImpl { .. } => "E1041",
+            Problem::AbilityImplNotIdent { .. } => "E1042",
+            Problem::DuplicateImpl { .. } => "E1043",
+            Problem::NotAnAbility(_) => "E1044",
+            Problem::ImplementsNonRequired { .. } => "E1045",
+            Problem::DoesNotImplementAbility { .. } => "E1046",
+            Problem::NotBoundInAllPatterns { .. } => "E1047",
+            Problem::NoIdentifiersIntroduced(_) => "E1048",
+            Problem::OverloadedSpecialization { .. } => "E1049",
+            Problem::UnnecessaryOutputWildcard { .. } => "E1050",
+            Problem::MultipleListRestPattern { .. } => "E1051",
+            Problem::BadTypeArguments { .. } => "E1052",
+            Problem::UnappliedCrash { .. } => "E1053",
+            Problem::OverAppliedCrash { .. } => "E1054",
+            Problem::FileProblem { .. } => "E1055",
+            Problem::ForeignTypeNotCAbi { .. } => "E1080",
+        }
+    }
+
     /// Returns a Region value from the Problem, if possible.
     /// Some problems have more than one region; in those cases,
     /// this tries to pick the one that's closest to the original
@@ -293,9 +376,18 @@ impl Problem {
                 original_region: region,
                 ..
             }
+            | Problem::PermittedShadowing {
+                shadow: Loc { region, .. },
+                ..
+            }
+            | Problem::ForeignTypeNotCAbi { region, .. }
             | Problem::UnusedImport(_, region)
             | Problem::UnusedModuleImport(_, region)
             | Problem::UnknownGeneratesWith(Loc { region, .. })
+            | Problem::GeneratedNameTaken {
+                original_region: region,
+                ..
+            }
             | Problem::UnusedArgument(_, _, _, region)
             | Problem::UnusedBranchDef(_, region)
             | Problem::PrecedenceProblem(PrecedenceProblem::BothNonAssociative(region, _, _))
@@ -503,6 +595,8 @@ pub enum RuntimeError {
         original_region: Region,
         shadow: Loc<Ident>,
         kind: ShadowKind,
+        /// The symbol that was shadowed. Uses of the name still refer to it.
+        original_symbol: Symbol,
     },
     InvalidOptionalValue {
         field_name: Lowercase,
@@ -558,6 +652,8 @@ pub enum RuntimeError {
     ModuleNotImported {
         /// The name of the module that was referenced
         module_name: ModuleName,
+        /// The name that was looked up in the module, e.g. `map` in `Lst.map`
+        ident: Ident,
         /// A list of modules which *have* been imported
         imported_modules: MutSet<Box<str>>,
         /// Where the problem occurred
@@ -628,6 +724,46 @@ impl RuntimeError {
         }
     }
 
+    /// See [Problem::code]. Runtime errors which can also be reported as a [Problem] on their
+    /// own, like shadowing, use the same code as that problem.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeError::Shadowing { .. } => "E1010",
+            RuntimeError::InvalidOptionalValue { .. } => "E1019",
+            RuntimeError::UnsupportedPattern(_) => "E1009",
+            RuntimeError::MalformedPattern(..) => "E1056",
+            RuntimeError::UnresolvedTypeVar => "E1057",
+            RuntimeError::ErroneousType => "E1058",
+            RuntimeError::LookupNotInScope { .. } => "E1059",
+            RuntimeError::OpaqueNotDefined { .. } => "E1060",
+            RuntimeError::OpaqueOutsideScope { .. } => "E1061",
+            RuntimeError::OpaqueNotApplied(_) => "E1062",
+            RuntimeError::OpaqueAppliedToMultipleArgs(_) => "E1063",
+            RuntimeError::ValueNotExposed { .. } => "E1064",
+            RuntimeError::ModuleNotImported { .. } => "E1065",
+            RuntimeError::InvalidPrecedence(..) => "E1008",
+            RuntimeError::MalformedIdentifier(..) => "E1066",
+            RuntimeError::MalformedTypeName(..) => "E1067",
+            RuntimeError::MalformedClosure(_) => "E1068",
+            RuntimeError::InvalidRecordUpdate { .. } => "E1069",
+            RuntimeError::InvalidFloat(..) => "E1070",
+            RuntimeError::InvalidInt(..) => "E1071",
+            RuntimeError::CircularDef(_) => "E1014",
+            RuntimeError::NonExhaustivePattern => "E1072",
+            RuntimeError::InvalidInterpolation(_) => "E1023",
+            RuntimeError::InvalidHexadecimal(_) => "E1024",
+            RuntimeError::InvalidUnicodeCodePt(_) => "E1025",
+            RuntimeError::NoImplementationNamed { .. } | RuntimeError::NoImplementation => "E1073",
+            RuntimeError::VoidValue => "E1074",
+            RuntimeError::ExposedButNotDefined(_) => "E1004",
+            RuntimeError::EmptySingleQuote(_) => "E1075",
+            RuntimeError::MultipleCharsInSingleQuote(_) => "E1076",
+            RuntimeError::DegenerateBranch(_) => "E1077",
+            RuntimeError::MultipleRecordBuilders(_) => "E1078",
+            RuntimeError::UnappliedRecordBuilder(_) => "E1079",
+        }
+    }
+
     pub fn region(&self) -> Region {
         match self {
             RuntimeError::Shadowing { shadow, .. } => shadow.region,
@@ -177,6 +177,9 @@ pub enum Target {
     LinuxX32,
     #[strum(serialize = "linux-x64")]
     LinuxX64,
+    /// Links the app and its host into a fully static executable, with musl instead of glibc.
+    #[strum(serialize = "linux-x64-musl")]
+    LinuxX64Musl,
     #[strum(serialize = "linux-arm64")]
     LinuxArm64,
     #[strum(serialize = "macos-x64")]
@@ -219,6 +222,13 @@ impl Target {
                 environment: Environment::Unknown,
                 binary_format: BinaryFormat::Elf,
             },
+            Target::LinuxX64Musl => Triple {
+                architecture: Architecture::X86_64,
+                vendor: Vendor::Unknown,
+                operating_system: OperatingSystem::Linux,
+                environment: Environment::Musl,
+                binary_format: BinaryFormat::Elf,
+            },
             Target::LinuxArm64 => Triple {
                 architecture: Architecture::Aarch64(Aarch64Architecture::Aarch64),
                 vendor: Vendor::Unknown,
@@ -290,6 +300,12 @@ pub fn get_target_triple_str(target: &target_lexicon::Triple) -> Option<&'static
             architecture: target_lexicon::Architecture::Wasm32,
             ..
         } => Some(Target::Wasm32.into()),
+        target_lexicon::Triple {
+            operating_system: target_lexicon::OperatingSystem::Linux,
+            architecture: target_lexicon::Architecture::X86_64,
+            environment: target_lexicon::Environment::Musl,
+            ..
+        } => Some(Target::LinuxX64Musl.into()),
         target_lexicon::Triple {
             operating_system: target_lexicon::OperatingSystem::Linux,
             architecture: target_lexicon::Architecture::X86_64,
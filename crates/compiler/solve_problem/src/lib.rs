@@ -6,7 +6,7 @@ use roc_module::{ident::Lowercase, symbol::Symbol};
 use roc_problem::{can::CycleEntry, Severity};
 use roc_region::all::Region;
 
-use roc_types::types::{Category, ErrorType, PatternCategory};
+use roc_types::types::{Category, ErrorType, PatternCategory, Reason};
 
 #[derive(Debug, Clone)]
 pub enum TypeError {
@@ -57,6 +57,29 @@ impl TypeError {
         }
     }
 
+    /// The code shown in the report's header, and accepted by `roc explain`.
+    /// Codes never change once assigned, so only append new ones.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TypeError::BadExpr(..) => "E2001",
+            TypeError::BadPattern(..) => "E2002",
+            TypeError::CircularType(..) => "E2003",
+            // The same problem as `roc_problem::can::Problem::BadRecursion`, found while solving
+            TypeError::CircularDef(_) => "E1014",
+            TypeError::UnexposedLookup(..) => "E2004",
+            TypeError::UnfulfilledAbility(_) => "E2005",
+            TypeError::BadExprMissingAbility(..) => "E2006",
+            TypeError::BadPatternMissingAbility(..) => "E2007",
+            TypeError::Exhaustive(roc_exhaustive::Error::Incomplete(..)) => "E2008",
+            TypeError::Exhaustive(roc_exhaustive::Error::Redundant { .. }) => "E2009",
+            TypeError::Exhaustive(roc_exhaustive::Error::Unmatchable { .. }) => "E2010",
+            TypeError::StructuralSpecialization { .. } => "E2011",
+            TypeError::WrongSpecialization { .. } => "E2012",
+            TypeError::IngestedFileBadUtf8(..) => "E2013",
+            TypeError::IngestedFileUnsupportedType(..) => "E2014",
+        }
+    }
+
     pub fn region(&self) -> Option<Region> {
         match self {
             TypeError::BadExpr(region, ..)
@@ -74,6 +97,46 @@ impl TypeError {
             TypeError::IngestedFileUnsupportedType(_, _) => None,
         }
     }
+
+    /// Symbols whose types could not be determined because of this problem, e.g. a def
+    /// which refers to itself. Problems found through uses of these symbols are follow-on errors.
+    pub fn erroneous_symbols(&self) -> Vec<Symbol> {
+        match self {
+            TypeError::CircularType(_, symbol, _) => vec![*symbol],
+            TypeError::CircularDef(entries) => entries.iter().map(|entry| entry.symbol).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The symbol whose type led to this problem being found, e.g. `f` in a call `f x`
+    /// where `x` has the wrong type.
+    pub fn blamed_symbol(&self) -> Option<Symbol> {
+        let (category, expected) = match self {
+            TypeError::BadExpr(_, category, _, expected) => (category, expected),
+            _ => return None,
+        };
+
+        match category {
+            Category::Lookup(symbol) | Category::CallResult(Some(symbol), _) => {
+                return Some(*symbol);
+            }
+            _ => {}
+        }
+
+        match expected {
+            Expected::ForReason(
+                Reason::FnArg {
+                    name: Some(symbol), ..
+                }
+                | Reason::FnCall {
+                    name: Some(symbol), ..
+                },
+                _,
+                _,
+            ) => Some(*symbol),
+            _ => None,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -83,7 +83,18 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
 
     let exec_mode = match mode {
         "exec" => ExecutionMode::Executable,
-        "test" => ExecutionMode::Test,
+        "test" => ExecutionMode::Test {
+            coverage: false,
+            properties: false,
+        },
+        "coverage" => ExecutionMode::Test {
+            coverage: true,
+            properties: false,
+        },
+        "properties" => ExecutionMode::Test {
+            coverage: false,
+            properties: true,
+        },
         _ => panic!("Invalid test_mono exec mode {mode}"),
     };
 
@@ -3500,3 +3511,52 @@ fn issue_6174() {
         "
     )
 }
+
+#[mono_test(mode = "coverage")]
+fn when_branches_are_counted() {
+    indoc!(
+        r"
+        interface Test exposes [] imports []
+
+        describe = \n ->
+            when n is
+                0 -> Zero
+                1 | 2 -> Few
+                _ -> Many
+
+        expect describe 1 == Few
+        "
+    )
+}
+
+#[mono_test(mode = "properties")]
+fn functions_with_expects_are_called_with_generated_arguments() {
+    indoc!(
+        r"
+        interface Test exposes [] imports []
+
+        clamp : I64, I64 -> I64
+        clamp = \n, limit ->
+            result = if n > limit then limit else n
+
+            expect result <= limit
+
+            result
+        "
+    )
+}
+
+/// The host calls the closure through a generated caller, and hands over its arguments.
+/// The platform is in `platform/main.roc`.
+#[mono_test]
+fn host_exposed_closure_owns_its_arguments() {
+    indoc!(
+        r#"
+        app "test"
+            packages { pf: "platform/main.roc" }
+            provides [main] to pf
+
+        main = \greeting -> \name -> Str.concat greeting name
+        "#
+    )
+}
@@ -1,7 +1,8 @@
 use bumpalo::Bump;
-use roc_fmt::{annotation::Formattable, module::fmt_module};
+use roc_fmt::{annotation::Formattable, def::fmt_top_level_defs, module::fmt_module};
 use roc_parse::{
     ast::{Defs, Expr, Malformed, Module},
+    highlight::{highlight, Token},
     module::module_defs,
     parser::{Parser, SyntaxError},
     state::State,
@@ -64,9 +65,10 @@ pub enum Output<'a> {
 }
 
 impl<'a> Output<'a> {
-    fn format(&self) -> InputOwned {
+    fn format(&self, max_width: Option<usize>) -> InputOwned {
         let arena = Bump::new();
         let mut buf = Buf::new_in(&arena);
+        buf.set_max_width(max_width);
         match self {
             Output::Header(header) => {
                 fmt_module(&mut buf, header);
@@ -74,7 +76,7 @@ impl<'a> Output<'a> {
                 InputOwned::Header(buf.as_str().to_string())
             }
             Output::ModuleDefs(defs) => {
-                defs.format(&mut buf, 0);
+                fmt_top_level_defs(&mut buf, defs);
                 buf.fmt_end_of_file();
                 InputOwned::ModuleDefs(buf.as_str().to_string())
             }
@@ -87,7 +89,7 @@ impl<'a> Output<'a> {
                 module_defs,
             } => {
                 fmt_module(&mut buf, header);
-                module_defs.format(&mut buf, 0);
+                fmt_top_level_defs(&mut buf, module_defs);
                 buf.fmt_end_of_file();
                 InputOwned::Full(buf.as_str().to_string())
             }
@@ -191,6 +193,16 @@ impl<'a> Input<'a> {
         &self,
         handle_formatted_output: impl Fn(Input),
         check_idempotency: bool,
+    ) {
+        self.check_invariants_with_max_width(None, handle_formatted_output, check_idempotency)
+    }
+
+    /// Like `check_invariants`, but formats with the given maximum line width.
+    pub fn check_invariants_with_max_width(
+        &self,
+        max_width: Option<usize>,
+        handle_formatted_output: impl Fn(Input),
+        check_idempotency: bool,
     ) {
         let arena = Bump::new();
 
@@ -198,7 +210,7 @@ impl<'a> Input<'a> {
             panic!("Unexpected parse failure when parsing this for formatting:\n\n{}\n\nParse error was:\n\n{:?}\n\n", self.as_str(), err);
         });
 
-        let output = actual.format();
+        let output = actual.format(max_width);
 
         handle_formatted_output(output.as_ref());
 
@@ -238,9 +250,27 @@ impl<'a> Input<'a> {
             );
         }
 
+        // RemoveSpaces drops comments, so check separately that none were lost or reordered
+        let original_comments = comments(self.as_str());
+        let formatted_comments = comments(output.as_ref().as_str());
+
+        if original_comments != formatted_comments {
+            panic!(
+                "Formatting bug; formatting didn't preserve the comments\n\n\
+                * * * Source code before formatting:\n{}\n\n\
+                * * * Source code after formatting:\n{}\n\n\
+                * * * Comments before formatting:\n{:#?}\n\n\
+                * * * Comments after formatting:\n{:#?}\n\n",
+                self.as_str(),
+                output.as_ref().as_str(),
+                original_comments,
+                formatted_comments
+            );
+        }
+
         // Now verify that the resultant formatting is _idempotent_ - i.e. that it doesn't change again if re-formatted
         if check_idempotency {
-            let reformatted = reparsed_ast.format();
+            let reformatted = reparsed_ast.format(max_width);
 
             if output != reformatted {
                 eprintln!("Formatting bug; formatting is not stable.\nOriginal code:\n{}\n\nFormatted code:\n{}\n\nAST:\n{:#?}\n\nReparsed AST:\n{:#?}\n\n",
@@ -255,3 +285,23 @@ impl<'a> Input<'a> {
         }
     }
 }
+
+/// The text of each comment in the source, in order. Formatting may add a space
+/// after the `#` and trim trailing whitespace, so those are ignored. Doc comments may
+/// also be reflowed, so they're split into words.
+pub fn comments(src: &str) -> Vec<&str> {
+    highlight(src)
+        .into_iter()
+        .flat_map(|token| {
+            let start = token.region.start().offset as usize;
+            let end = token.region.end().offset as usize;
+            let text = src[start..end].trim_start_matches('#').trim();
+
+            match token.value {
+                Token::LineComment => vec![text],
+                Token::DocComment => text.split_whitespace().collect(),
+                _ => vec![],
+            }
+        })
+        .collect()
+}
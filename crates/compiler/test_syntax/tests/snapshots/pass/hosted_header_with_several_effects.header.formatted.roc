@@ -0,0 +1,5 @@
+hosted Effect
+    exposes [Effect, after, map, putLine, readLines]
+    imports []
+    generates Effect with [after, map]
+    generates Stream with [after, map]
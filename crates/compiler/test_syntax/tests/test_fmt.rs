@@ -4,7 +4,7 @@ extern crate indoc;
 #[cfg(test)]
 mod test_fmt {
     use bumpalo::Bump;
-    use roc_fmt::def::fmt_defs;
+    use roc_fmt::def::fmt_top_level_defs;
     use roc_fmt::module::fmt_module;
     use roc_fmt::Buf;
     use roc_parse::ast::Module;
@@ -12,7 +12,7 @@ mod test_fmt {
     use roc_parse::parser::Parser;
     use roc_parse::state::State;
     use roc_test_utils::{assert_multiline_str_eq, workspace_root};
-    use test_syntax::test_helpers::Input;
+    use test_syntax::test_helpers::{comments, Input};
 
     fn check_formatting(expected: &'_ str) -> impl Fn(Input) + '_ {
         let expected = expected.trim();
@@ -29,6 +29,18 @@ mod test_fmt {
         Input::Expr(input.trim()).check_invariants(check_formatting(input.trim()), true)
     }
 
+    fn expr_formats_to_with_max_width(input: &str, expected: &str, max_width: usize) {
+        Input::Expr(input.trim()).check_invariants_with_max_width(
+            Some(max_width),
+            check_formatting(expected.trim()),
+            true,
+        )
+    }
+
+    fn expr_formats_same_with_max_width(input: &str, max_width: usize) {
+        expr_formats_to_with_max_width(input, input, max_width);
+    }
+
     fn fmt_module_and_defs<'a>(
         arena: &Bump,
         src: &str,
@@ -40,7 +52,7 @@ mod test_fmt {
 
         match module_defs().parse(arena, state, 0) {
             Ok((_, loc_defs, _)) => {
-                fmt_defs(buf, &loc_defs, 0);
+                fmt_top_level_defs(buf, &loc_defs);
             }
             Err(error) => panic!(
                 r"Unexpected parse failure when parsing this for defs formatting:\n\n{src:?}\n\nParse error was:\n\n{error:?}\n\n"
@@ -86,6 +98,14 @@ mod test_fmt {
                     );
                 }
 
+                if comments(src) != comments(output) {
+                    panic!(
+                        "Formatting bug; formatting didn't preserve the comments\n\n\
+                        * * * Source code before formatting:\n{src}\n\n\
+                        * * * Source code after formatting:\n{output}\n\n"
+                    );
+                }
+
                 // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
                 let mut reformatted_buf = Buf::new_in(&arena);
 
@@ -124,6 +144,49 @@ mod test_fmt {
         module_formats_to(input, input);
     }
 
+    fn module_formats_to_with_sorted_headers(input: &str, expected: &str) {
+        let format = |src: &str| {
+            let arena = Bump::new();
+            let (module, state) = module::parse_header(&arena, State::new(src.as_bytes()))
+                .unwrap_or_else(|err| panic!("Unexpected parse failure when parsing this for module header formatting:\n\n{src:?}\n\nParse error was:\n\n{err:?}\n\n"));
+
+            let mut buf = Buf::new_in(&arena);
+            buf.set_sort_headers(true);
+            fmt_module_and_defs(&arena, src, &module, state, &mut buf);
+
+            buf.as_str().trim().to_string()
+        };
+
+        let output = format(input.trim());
+
+        assert_multiline_str_eq!(expected.trim(), output.as_str());
+
+        // Sorting an already sorted header doesn't change it
+        assert_multiline_str_eq!(output.as_str(), format(&output).as_str());
+    }
+
+    /// Formats the part of `input` starting at the first occurrence of `selected`,
+    /// and checks the edits which come back turn it into `expected`.
+    fn range_formats_to(input: &str, selected: &str, expected: &str) {
+        let arena = Bump::new();
+        let start = input
+            .find(selected)
+            .expect("the selection isn't in the input");
+        let range = start..start + selected.len();
+
+        let edits = roc_fmt::format_range(&arena, input, range, None).unwrap_or_else(|error| {
+            panic!("Unexpected parse failure when parsing this for range formatting:\n\n{input:?}\n\nParse error was:\n\n{error:?}\n\n")
+        });
+
+        let mut output = input.to_string();
+
+        for edit in edits.iter().rev() {
+            output.replace_range(edit.range.clone(), &edit.replacement);
+        }
+
+        assert_multiline_str_eq!(expected, output.as_str());
+    }
+
     // STRING LITERALS
 
     #[test]
@@ -2122,8 +2185,7 @@ mod test_fmt {
             {
                 y: 41,
                 # comment 1
-                x: 42,
-                # comment 2
+                x: 42, # comment 2
             }"
             ),
         );
@@ -2144,8 +2206,7 @@ mod test_fmt {
                 r"
             {
                 y: 41,
-                x: 42,
-                # comment 1
+                x: 42, # comment 1
                 # comment 2
             }"
             ),
@@ -2173,10 +2234,8 @@ mod test_fmt {
             indoc!(
                 r"
             {
-                z: 44,
-                # comment 0
-                y: 41,
-                # comment 1
+                z: 44, # comment 0
+                y: 41, # comment 1
                 # comment 2
                 x: 42,
                 # comment 3
@@ -2201,14 +2260,40 @@ mod test_fmt {
                 r"
             {
                 y: 41,
-                x: 42,
-                # comment 1
+                x: 42, # comment 1
                 # comment 2
             }"
             ),
         );
     }
 
+    #[test]
+    fn comments_after_items_stay_on_their_line() {
+        expr_formats_to(
+            indoc!(
+                r"
+            {
+                a: [1, # one
+                    2 # two
+                ],
+                b: 2 # comment b
+                , c: 3 # comment c
+            }"
+            ),
+            indoc!(
+                r"
+            {
+                a: [
+                    1, # one
+                    2, # two
+                ],
+                b: 2, # comment b
+                c: 3, # comment c
+            }"
+            ),
+        );
+    }
+
     #[test]
     fn trailing_comma_in_record_annotation() {
         expr_formats_to(
@@ -2678,6 +2763,40 @@ mod test_fmt {
         expr_formats_to(indoc!("[   7  ,   8  ]"), indoc!("[7, 8]"));
     }
 
+    #[test]
+    fn blank_line_before_closing_bracket() {
+        expr_formats_to(
+            indoc!(
+                r"
+                [
+                    1,
+                    2,
+
+                ]
+                "
+            ),
+            indoc!(
+                r"
+                [
+                    1,
+                    2,
+                ]
+                "
+            ),
+        );
+
+        expr_formats_same(indoc!(
+            r"
+            [
+                1,
+                2,
+
+                # the end
+            ]
+            "
+        ));
+    }
+
     #[test]
     fn multi_line_list() {
         expr_formats_same(indoc!(
@@ -4102,6 +4221,39 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn when_guard_with_comment() {
+        expr_formats_same(indoc!(
+            r"
+            when maybeScore is
+                Just score if score > 21 # bust
+                -> lose
+                _ -> nextRound
+            "
+        ));
+    }
+
+    #[test]
+    fn when_condition_with_comment() {
+        expr_formats_to(
+            indoc!(
+                r"
+                when maybeScore # from the last round
+                is
+                    _ -> nextRound
+                "
+            ),
+            indoc!(
+                r"
+                when
+                    maybeScore # from the last round
+                is
+                    _ -> nextRound
+                "
+            ),
+        );
+    }
+
     // ACCESSOR
 
     #[test]
@@ -4840,6 +4992,92 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn sort_headers() {
+        module_formats_to_with_sorted_headers(
+            indoc!(
+                r"
+                interface Foo
+                    exposes [zeta, Beta, alpha, Beta]
+                    imports [pf.Task.{ await, Task }, Json, Dict.{ insert, Dict }]"
+            ),
+            indoc!(
+                r"
+                interface Foo
+                    exposes [Beta, alpha, zeta]
+                    imports [Dict.{ Dict, insert }, Json, pf.Task.{ Task, await }]"
+            ),
+        );
+
+        module_formats_to_with_sorted_headers(
+            indoc!(
+                r#"
+                app "Foo"
+                    packages { pf: "platform/main.roc" }
+                    imports [pf.Task, pf.Stdout, pf.Task]
+                    provides [main] to pf"#
+            ),
+            indoc!(
+                r#"
+                app "Foo"
+                    packages { pf: "platform/main.roc" }
+                    imports [pf.Stdout, pf.Task]
+                    provides [main] to pf"#
+            ),
+        );
+    }
+
+    #[test]
+    fn sort_headers_keeps_generates_with_order() {
+        module_formats_to_with_sorted_headers(
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [map, Effect, after]
+                    imports []
+                    generates Effect with [map, after]"
+            ),
+            indoc!(
+                r"
+                hosted Effect
+                    exposes [Effect, after, map]
+                    imports []
+                    generates Effect with [map, after]"
+            ),
+        );
+    }
+
+    #[test]
+    fn sort_headers_keeps_comments_with_their_items() {
+        module_formats_to_with_sorted_headers(
+            indoc!(
+                r"
+                interface Foo
+                    exposes [
+                        zeta, # last
+                        Beta,
+                        # the alpha
+                        alpha,
+                        zeta,
+                        Alpha, # first
+                    ]
+                    imports []"
+            ),
+            indoc!(
+                r"
+                interface Foo
+                    exposes [
+                        Alpha, # first
+                        Beta,
+                        # the alpha
+                        alpha,
+                        zeta, # last
+                    ]
+                    imports []"
+            ),
+        );
+    }
+
     #[test]
     fn single_line_app() {
         module_formats_same(indoc!(
@@ -4891,6 +5129,58 @@ mod test_fmt {
         );
     }
 
+    #[test]
+    fn top_level_defs_keep_their_grouping() {
+        module_formats_to(
+            indoc!(
+                r"
+                    interface Foo
+                        exposes []
+                        imports []
+
+                    a = 1
+                    b = 2
+
+
+
+
+                    # Section
+
+                    c =
+                        x = 1
+
+
+
+                        x
+
+
+                    d = 4
+                "
+            ),
+            indoc!(
+                r"
+                    interface Foo
+                        exposes []
+                        imports []
+
+                    a = 1
+                    b = 2
+
+
+                    # Section
+
+                    c =
+                        x = 1
+
+                        x
+
+
+                    d = 4
+                "
+            ),
+        );
+    }
+
     #[test]
     fn format_tui_package_config() {
         // At one point this failed to reformat.
@@ -5098,6 +5388,30 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn multiline_tuple_annotation() {
+        expr_formats_to(
+            indoc!(
+                r"
+                pair : (Str,
+                    Str)
+
+                pair
+                "
+            ),
+            indoc!(
+                r"
+                pair : (
+                    Str,
+                    Str,
+                )
+
+                pair
+                "
+            ),
+        );
+    }
+
     #[test]
     fn multiline_tag_union_annotation_with_final_comment() {
         expr_formats_to(
@@ -5349,6 +5663,339 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn backpassing_with_comments() {
+        expr_formats_same(indoc!(
+            r"
+                x <- Task.await getX # first
+                # then
+                y <- Task.await getY
+
+                # finally
+                Task.ok (x + y)
+            "
+        ));
+    }
+
+    #[test]
+    fn closure_record_pattern_with_comment() {
+        expr_formats_to(
+            indoc!(
+                r"
+                \{ x, # horizontal
+                    y } -> x + y
+                "
+            ),
+            indoc!(
+                r"
+                \{ x, # horizontal
+                    y }
+                    -> x + y
+                "
+            ),
+        );
+    }
+
+    #[test]
+    fn comment_after_open_paren() {
+        expr_formats_to(
+            indoc!(
+                r"
+                f ( # the answer
+                    42)
+                "
+            ),
+            indoc!(
+                r"
+                f
+                    ( # the answer
+                        42)
+                "
+            ),
+        );
+    }
+
+    #[test]
+    fn max_width_leaves_short_lines_alone() {
+        expr_formats_same(indoc!(
+            r#"
+            greeting = Str.concat "Hello, " name
+
+            { greeting, names: [first, second, third] }
+            "#
+        ));
+
+        expr_formats_to_with_max_width(
+            indoc!(
+                r#"
+                greeting = Str.concat "Hello, " name
+
+                { greeting, names: [first, second] }
+                "#
+            ),
+            indoc!(
+                r#"
+                greeting = Str.concat "Hello, " name
+
+                { greeting, names: [first, second] }
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_long_application() {
+        expr_formats_to_with_max_width(
+            indoc!(
+                r#"
+                greeting = Str.concat "Hello there, " (Str.joinWith names " and ")
+
+                greeting
+                "#
+            ),
+            indoc!(
+                r#"
+                greeting = Str.concat
+                    "Hello there, "
+                    (Str.joinWith names " and ")
+
+                greeting
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_long_record_and_list() {
+        expr_formats_to_with_max_width(
+            indoc!(
+                r#"
+                config = { name: "roc", version: "0.1.0", tags: [compiler, language, functional] }
+
+                config
+                "#
+            ),
+            indoc!(
+                r#"
+                config = {
+                    name: "roc",
+                    version: "0.1.0",
+                    tags: [
+                        compiler,
+                        language,
+                        functional,
+                    ],
+                }
+
+                config
+                "#
+            ),
+            30,
+        );
+    }
+
+    #[test]
+    fn max_width_reflows_last_collection_argument() {
+        expr_formats_to_with_max_width(
+            indoc!(
+                r#"
+                List.map items \item -> Task.attempt task { name: item.name, size: item.size }
+                "#
+            ),
+            indoc!(
+                r#"
+                List.map items \item ->
+                    Task.attempt task {
+                        name: item.name,
+                        size: item.size,
+                    }
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_long_operators_and_conditions() {
+        expr_formats_to_with_max_width(
+            indoc!(
+                r#"
+                total = if List.isEmpty prices then 0 else List.sum prices + shippingCost + tax
+
+                total
+                "#
+            ),
+            indoc!(
+                r#"
+                total =
+                    if List.isEmpty prices then
+                        0
+                    else
+                        List.sum prices
+                        + shippingCost
+                        + tax
+
+                total
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_reflows_doc_comments() {
+        expr_formats_to_with_max_width(
+            indoc!(
+                r#"
+                ## Adds one to a number, which is something that comes up surprisingly often.
+                ## Really!
+                ##
+                ## - The number can be any size, as long as it doesn't overflow.
+                ## - It can be
+                ##   negative.
+                addOne = \n -> n + 1
+
+                addOne
+                "#
+            ),
+            indoc!(
+                r#"
+                ## Adds one to a number, which is something that comes up
+                ## surprisingly often. Really!
+                ##
+                ## - The number can be any size, as long as it doesn't
+                ##   overflow.
+                ## - It can be negative.
+                addOne = \n -> n + 1
+
+                addOne
+                "#
+            ),
+            60,
+        );
+    }
+
+    #[test]
+    fn max_width_leaves_doc_comment_code_alone() {
+        expr_formats_same_with_max_width(
+            indoc!(
+                r#"
+                ## # A heading which is too long for the width, but stays as it is
+                ##
+                ## ```
+                ## addOne (addOne (addOne (addOne (addOne (addOne 1)))))
+                ## ```
+                ##
+                ##     addOne (addOne (addOne (addOne (addOne (addOne 1)))))
+                addOne = \n -> n + 1
+
+                addOne
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_multiline_if_def_body() {
+        expr_formats_to_with_max_width(
+            indoc!(
+                r"
+                x = if a then
+                        1
+                    else
+                        2
+
+                x
+                "
+            ),
+            indoc!(
+                r"
+                x =
+                    if a then
+                        1
+                    else
+                        2
+
+                x
+                "
+            ),
+            80,
+        );
+    }
+
+    #[test]
+    fn range_formats_only_the_selected_def() {
+        let input = indoc!(
+            r#"
+            interface Foo exposes [a, b] imports []
+
+            a=1+2
+
+            b =   [1,2,  3]
+            "#
+        );
+
+        range_formats_to(
+            input,
+            "[1,2",
+            indoc!(
+                r#"
+                interface Foo exposes [a, b] imports []
+
+                a=1+2
+
+                b = [1, 2, 3]
+                "#
+            ),
+        );
+
+        range_formats_to(
+            input,
+            "1+2\n\nb",
+            indoc!(
+                r#"
+                interface Foo exposes [a, b] imports []
+
+                a = 1 + 2
+
+                b = [1, 2, 3]
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn range_at_cursor_formats_the_enclosing_def() {
+        let input = indoc!(
+            r#"
+            interface Foo exposes [a, b] imports []
+
+            a=1+2
+
+            # not part of a def
+            b =   [1,2,  3]
+            "#
+        );
+
+        // Comments between defs aren't part of either one
+        range_formats_to(input, "not part", input);
+
+        let cursor = input.find("+2").unwrap();
+        let arena = Bump::new();
+        let edits = roc_fmt::format_range(&arena, input, cursor..cursor, None).unwrap();
+
+        assert_eq!(
+            edits,
+            [roc_fmt::Edit {
+                range: 42..45,
+                replacement: " = 1 + ".to_string(),
+            }]
+        );
+    }
+
     #[test]
     fn multiline_higher_order_function() {
         expr_formats_same(indoc!(
@@ -0,0 +1,301 @@
+//! Property tests for the formatter. We generate programs with a mix of single-line and
+//! multiline expressions and comments, and check that formatting each one gives code
+//! which parses to the same AST, keeps every comment, and doesn't change when formatted again.
+
+#[cfg(test)]
+mod test_fmt_properties {
+    use proptest::prelude::*;
+    use test_syntax::test_helpers::Input;
+
+    const VARS: &[&str] = &["x", "foo", "List.map", "answer"];
+    const TAGS: &[&str] = &["Ok", "Err", "Red"];
+    const OPS: &[&str] = &["+", "-", "*", "==", "|>", "&&"];
+    const COMMENT: &str = "# note";
+
+    /// A generated expression. Every multiline expression is rendered starting at the
+    /// given indentation, which is also the column it starts at.
+    #[derive(Debug, Clone)]
+    enum Gen {
+        Var(&'static str),
+        Num(u32),
+        Str(&'static str),
+        Tag(&'static str),
+        Apply(Box<Gen>, Vec<Gen>),
+        BinOp(Box<Gen>, &'static str, Box<Gen>),
+        List(Vec<(Gen, bool)>, bool),
+        Record(Vec<(&'static str, Gen, bool)>, bool),
+        If(Box<Gen>, Box<Gen>, Box<Gen>),
+        When(Box<Gen>, Vec<(bool, Gen)>),
+        Closure(Vec<&'static str>, Box<Gen>),
+        Def(bool, Box<Gen>, Box<Gen>),
+        Backpassing(bool, Box<Gen>, Box<Gen>),
+    }
+
+    impl Gen {
+        fn render(&self, indent: usize) -> String {
+            let pad = |extra: usize| " ".repeat(indent + extra);
+            let comment_line = |has_comment: bool, extra: usize| {
+                if has_comment {
+                    format!("{}{COMMENT}\n", pad(extra))
+                } else {
+                    String::new()
+                }
+            };
+
+            match self {
+                Gen::Var(name) | Gen::Tag(name) => name.to_string(),
+                Gen::Str(text) => format!("\"{text}\""),
+                Gen::Num(num) => num.to_string(),
+                Gen::Apply(function, args) => {
+                    let mut out = function.render_arg(indent);
+
+                    for arg in args {
+                        out.push(' ');
+                        out.push_str(&arg.render_arg(indent));
+                    }
+
+                    out
+                }
+                Gen::BinOp(left, op, right) => format!(
+                    "{} {op} {}",
+                    left.render_arg(indent),
+                    right.render_arg(indent)
+                ),
+                Gen::List(items, false) => {
+                    let items: Vec<String> =
+                        items.iter().map(|(item, _)| item.render(indent)).collect();
+
+                    format!("[{}]", items.join(", "))
+                }
+                Gen::List(items, true) => {
+                    let mut out = String::from("[\n");
+
+                    for (item, has_comment) in items {
+                        out.push_str(&pad(4));
+                        out.push_str(&item.render(indent + 4));
+                        out.push(',');
+
+                        if *has_comment {
+                            out.push(' ');
+                            out.push_str(COMMENT);
+                        }
+
+                        out.push('\n');
+                    }
+
+                    out.push_str(&pad(0));
+                    out.push(']');
+                    out
+                }
+                Gen::Record(fields, false) => {
+                    let fields: Vec<String> = fields
+                        .iter()
+                        .map(|(name, value, _)| format!("{name}: {}", value.render(indent)))
+                        .collect();
+
+                    format!("{{ {} }}", fields.join(", "))
+                }
+                Gen::Record(fields, true) => {
+                    let mut out = String::from("{\n");
+
+                    for (name, value, has_comment) in fields {
+                        out.push_str(&comment_line(*has_comment, 4));
+                        out.push_str(&format!(
+                            "{}{name}: {},\n",
+                            pad(4),
+                            value.render(indent + 4)
+                        ));
+                    }
+
+                    out.push_str(&pad(0));
+                    out.push('}');
+                    out
+                }
+                Gen::If(condition, then_branch, else_branch) => format!(
+                    "if {} then\n{}{}\n{}else\n{}{}",
+                    condition.render(indent),
+                    pad(4),
+                    then_branch.render(indent + 4),
+                    pad(0),
+                    pad(4),
+                    else_branch.render(indent + 4),
+                ),
+                Gen::When(condition, branches) => {
+                    let mut out = format!("when {} is\n", condition.render(indent));
+
+                    for (index, (has_comment, body)) in branches.iter().enumerate() {
+                        let pattern = if index + 1 == branches.len() {
+                            "_".to_string()
+                        } else {
+                            format!("{} n{index}", TAGS[index % TAGS.len()])
+                        };
+
+                        out.push_str(&comment_line(*has_comment, 4));
+                        out.push_str(&format!(
+                            "{}{pattern} ->\n{}{}\n",
+                            pad(4),
+                            pad(8),
+                            body.render(indent + 8)
+                        ));
+                    }
+
+                    out.trim_end().to_string()
+                }
+                Gen::Closure(args, body) => format!(
+                    "\\{} ->\n{}{}",
+                    args.join(", "),
+                    pad(4),
+                    body.render(indent + 4)
+                ),
+                Gen::Def(has_comment, body, ret) => format!(
+                    "value =\n{}{}\n\n{}{}{}",
+                    pad(4),
+                    body.render(indent + 4),
+                    comment_line(*has_comment, 0),
+                    pad(0),
+                    ret.render(indent)
+                ),
+                Gen::Backpassing(has_comment, body, ret) => format!(
+                    "result <- {}\n{}{}{}",
+                    body.render(indent),
+                    comment_line(*has_comment, 0),
+                    pad(0),
+                    ret.render(indent)
+                ),
+            }
+        }
+
+        fn ends_with_when(&self) -> bool {
+            match self {
+                Gen::When(..) => true,
+                Gen::If(_, _, last)
+                | Gen::Closure(_, last)
+                | Gen::Def(_, _, last)
+                | Gen::Backpassing(_, _, last) => last.ends_with_when(),
+                _ => false,
+            }
+        }
+
+        /// Renders this as a function argument or operand, adding parens if needed.
+        fn render_arg(&self, indent: usize) -> String {
+            match self {
+                Gen::Apply(..) | Gen::BinOp(..) => format!("({})", self.render(indent)),
+                _ => self.render(indent),
+            }
+        }
+    }
+
+    fn leaf() -> BoxedStrategy<Gen> {
+        prop_oneof![
+            prop::sample::select(VARS).prop_map(Gen::Var),
+            (0..1000u32).prop_map(Gen::Num),
+            Just(Gen::Str("hello")),
+            prop::sample::select(TAGS).prop_map(Gen::Tag),
+        ]
+        .boxed()
+    }
+
+    /// Expressions which always fit on one line
+    fn single_line() -> BoxedStrategy<Gen> {
+        leaf()
+            .prop_recursive(3, 16, 3, |inner| {
+                prop_oneof![
+                    (inner.clone(), prop::collection::vec(inner.clone(), 1..3))
+                        .prop_map(|(function, args)| Gen::Apply(Box::new(function), args)),
+                    (inner.clone(), prop::sample::select(OPS), inner.clone()).prop_map(
+                        |(left, op, right)| Gen::BinOp(Box::new(left), op, Box::new(right))
+                    ),
+                    prop::collection::vec(inner.clone(), 0..3).prop_map(|items| Gen::List(
+                        items.into_iter().map(|item| (item, false)).collect(),
+                        false
+                    )),
+                    prop::collection::vec(inner, 1..3).prop_map(|values| Gen::Record(
+                        values
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, value)| (["a", "b", "c"][index], value, false))
+                            .collect(),
+                        false
+                    )),
+                ]
+            })
+            .boxed()
+    }
+
+    /// Expressions which can go in any position where a multiline expression can start a line
+    fn expr(depth: u32) -> BoxedStrategy<Gen> {
+        if depth == 0 {
+            return single_line();
+        }
+
+        let block = block(depth - 1);
+        // The parser doesn't support a `when` followed by a comma, e.g. in a list
+        let item = expr(depth - 1).prop_filter("ends with a when", |item| !item.ends_with_when());
+
+        prop_oneof![
+            2 => single_line(),
+            1 => prop::collection::vec((item.clone(), any::<bool>()), 1..4)
+                .prop_map(|items| Gen::List(items, true)),
+            1 => prop::collection::vec((item.clone(), any::<bool>()), 1..4).prop_map(|values| {
+                Gen::Record(
+                    values
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, (value, has_comment))| {
+                            (["a", "b", "c", "d"][index], value, has_comment)
+                        })
+                        .collect(),
+                    true,
+                )
+            }),
+            1 => (single_line(), block.clone(), block.clone()).prop_map(
+                |(condition, then_branch, else_branch)| Gen::If(
+                    Box::new(condition),
+                    Box::new(then_branch),
+                    Box::new(else_branch)
+                )
+            ),
+            1 => (single_line(), prop::collection::vec((any::<bool>(), block.clone()), 1..4))
+                .prop_map(|(condition, branches)| Gen::When(Box::new(condition), branches)),
+            1 => (prop::sample::subsequence(vec!["a", "b", "c"], 1..3), block)
+                .prop_map(|(args, body)| Gen::Closure(args, Box::new(body))),
+        ]
+        .boxed()
+    }
+
+    /// Expressions which can go where defs are allowed, e.g. the body of a closure
+    fn block(depth: u32) -> BoxedStrategy<Gen> {
+        let inner = expr(depth);
+
+        if depth == 0 {
+            return inner;
+        }
+
+        let ret = block(depth - 1);
+
+        prop_oneof![
+            3 => inner.clone(),
+            1 => (any::<bool>(), inner, ret.clone())
+                .prop_map(|(has_comment, body, ret)| Gen::Def(has_comment, Box::new(body), Box::new(ret))),
+            1 => (any::<bool>(), single_line(), ret)
+                .prop_map(|(has_comment, body, ret)| Gen::Backpassing(has_comment, Box::new(body), Box::new(ret))),
+        ]
+        .boxed()
+    }
+
+    proptest! {
+        #[test]
+        fn formatting_is_idempotent(gen in block(3)) {
+            let src = gen.render(0);
+
+            Input::Expr(&src).check_invariants(|_| (), true);
+        }
+
+        #[test]
+        fn formatting_with_max_width_is_idempotent(gen in block(3), max_width in 10..60usize) {
+            let src = gen.render(0);
+
+            Input::Expr(&src).check_invariants_with_max_width(Some(max_width), |_| (), true);
+        }
+    }
+}
@@ -322,6 +322,7 @@ mod test_snapshots {
         pass/function_with_tuple_type.expr,
         pass/highest_float.expr,
         pass/highest_int.expr,
+        pass/hosted_header_with_several_effects.header,
         pass/if_def.expr,
         pass/int_with_underscore.expr,
         pass/interface_with_newline.header,
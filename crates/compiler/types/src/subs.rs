@@ -355,6 +355,19 @@ impl UlsOfVar {
         *self = snapshot.0;
     }
 
+    /// Forgets all variables at index `len` and above, see [Subs::truncate_to].
+    fn truncate(&mut self, len: usize) {
+        let is_truncated = |var: &Variable| var.index() as usize >= len;
+
+        self.0
+            .drain_filter(|var, _| is_truncated(var))
+            .for_each(drop);
+
+        for (_, lambda_sets) in self.0.iter_mut() {
+            lambda_sets.retain(|var| !is_truncated(var));
+        }
+    }
+
     pub fn remove_dependent_unspecialized_lambda_sets<'a>(
         &'a mut self,
         subs: &'a Subs,
@@ -1063,6 +1076,28 @@ impl VarStore {
 
         Variable(answer)
     }
+
+    /// Remembers which variables have been handed out so far, so that speculative work can give
+    /// the variables it creates back with [VarStore::rollback_to].
+    pub fn checkpoint(&self) -> VarStoreCheckpoint {
+        VarStoreCheckpoint { next: self.next }
+    }
+
+    /// Forgets all variables created since the checkpoint; they will be handed out again.
+    /// Any [Subs] that these variables were added to must be truncated with [Subs::truncate_to].
+    pub fn rollback_to(&mut self, checkpoint: VarStoreCheckpoint) {
+        debug_assert!(
+            checkpoint.next <= self.next,
+            "rolling back to a checkpoint from after the current state"
+        );
+
+        self.next = checkpoint.next;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VarStoreCheckpoint {
+    next: u32,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
@@ -2131,6 +2166,34 @@ impl Subs {
         self.utable.is_empty()
     }
 
+    /// Replaces every symbol stored in these subs, e.g. to translate types that were persisted
+    /// by an earlier run of the compiler into the `ModuleId`s of this run.
+    pub fn remap_symbols(&mut self, remap: impl Fn(Symbol) -> Symbol) {
+        for symbol in self.symbol_names.iter_mut() {
+            *symbol = remap(*symbol);
+        }
+
+        for Uls(_, member, _) in self.unspecialized_lambda_sets.iter_mut() {
+            *member = remap(*member);
+        }
+
+        for i in 0..self.len() {
+            let var = Variable(i as u32);
+
+            let new_content = match *self.get_content_unchecked(var) {
+                Content::Alias(symbol, args, real_var, kind) => {
+                    Content::Alias(remap(symbol), args, real_var, kind)
+                }
+                Content::Structure(FlatType::Apply(symbol, args)) => {
+                    Content::Structure(FlatType::Apply(remap(symbol), args))
+                }
+                _ => continue,
+            };
+
+            self.set_content_unchecked(var, new_content);
+        }
+    }
+
     pub fn contains(&self, var: Variable) -> bool {
         (var.index() as usize) < self.len()
     }
@@ -2156,6 +2219,20 @@ impl Subs {
         self.utable.vars_since_snapshot(&snapshot.utable_snapshot)
     }
 
+    /// Removes all variables created since the checkpoint was taken. Unlike [Subs::rollback_to]
+    /// this does not need to keep a copy of the whole table around, but it does not undo
+    /// unifications either: the remaining variables must not refer to any of the removed ones.
+    /// That holds when the new variables were only used for speculative work that is thrown away
+    /// afterwards, e.g. when everything it unified was itself created after the checkpoint.
+    pub fn truncate_to(&mut self, checkpoint: VarStoreCheckpoint) {
+        let len = checkpoint.next as usize;
+
+        debug_assert!(checkpoint.next >= Variable::FIRST_USER_SPACE_VAR.0);
+
+        self.utable.truncate(len);
+        self.uls_of_var.truncate(len);
+    }
+
     pub fn get_lambda_set(&self, mut lambda_set: Variable) -> LambdaSet {
         loop {
             match self.get_content_without_compacting(lambda_set) {
@@ -6127,3 +6204,33 @@ fn is_inhabited(subs: &Subs, var: Variable) -> bool {
 
     true
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Subs, VarStore};
+
+    #[test]
+    fn rollback_var_store_and_truncate_subs() {
+        let mut subs = Subs::new();
+        let mut var_store = VarStore::new_from_subs(&subs);
+
+        let kept = var_store.fresh();
+        subs.extend_by(1);
+
+        let checkpoint = var_store.checkpoint();
+
+        let speculative1 = var_store.fresh();
+        let speculative2 = var_store.fresh();
+        subs.extend_by(2);
+        let desc = subs.get(speculative1);
+        subs.union(speculative1, speculative2, desc);
+
+        var_store.rollback_to(checkpoint);
+        subs.truncate_to(checkpoint);
+
+        assert!(subs.contains(kept));
+        assert!(!subs.contains(speculative1));
+        assert_eq!(var_store.fresh(), speculative1);
+        assert_eq!(VarStore::new_from_subs(&subs).peek(), speculative1.index());
+    }
+}
@@ -3399,6 +3399,8 @@ pub enum Reason {
     WhenBranches,
     WhenBranch {
         index: HumanIndex,
+        /// The first branch, whose type the later branches must match
+        first_branch: Region,
     },
     WhenGuard,
     ExpectCondition,
@@ -3406,6 +3408,8 @@ pub enum Reason {
     IfBranch {
         index: HumanIndex,
         total_branches: usize,
+        /// The first branch, whose type the later branches must match
+        first_branch: Region,
     },
     ElemInList {
         index: HumanIndex,
@@ -307,6 +307,22 @@ impl UnificationTable {
         *self = snapshot.0;
     }
 
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.contents.truncate(len);
+        self.metadata.truncate(len);
+
+        debug_assert!(
+            self.metadata.iter().all(|combine| match combine {
+                Combine::Redirect(var) => (var.index() as usize) < len,
+                Combine::Root(root) => root
+                    .copy
+                    .into_variable()
+                    .map_or(true, |copy| (copy.index() as usize) < len),
+            }),
+            "a remaining variable refers to a truncated one"
+        );
+    }
+
     pub(crate) fn vars_since_snapshot(&self, snapshot: &Snapshot) -> std::ops::Range<Variable> {
         unsafe {
             let start = Variable::from_index(snapshot.0.len() as u32);
@@ -3,8 +3,9 @@
 extern crate pulldown_cmark;
 extern crate roc_load;
 use bumpalo::Bump;
+use roc_can::expr::Declarations;
 use roc_can::scope::Scope;
-use roc_collections::VecSet;
+use roc_collections::{MutMap, VecSet};
 use roc_load::docs::{DocEntry, TypeAnnotation};
 use roc_load::docs::{ModuleDocumentation, RecordField};
 use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
@@ -14,6 +15,8 @@ use roc_parse::ident::{parse_ident, Accessor, Ident};
 use roc_parse::keyword;
 use roc_parse::state::State;
 use roc_region::all::Region;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+use roc_types::subs::Subs;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -77,10 +80,7 @@ pub fn generate_docs_html(root_file: PathBuf, build_dir: &Path) {
 
     // Write CSS, JS, and favicon
     // (The HTML requires more work!)
-    for (file, contents) in [
-        ("aux.js", assets.aux_js),
-        ("styles.css", assets.styles_css),
-    ] {
+    for (file, contents) in [("aux.js", assets.aux_js), ("styles.css", assets.styles_css)] {
         let dir = build_dir.join(file);
         fs::write(&dir, contents).unwrap_or_else(|error| {
             panic!(
@@ -123,6 +123,8 @@ pub fn generate_docs_html(root_file: PathBuf, build_dir: &Path) {
         set
     };
 
+    let solved_signatures = solved_type_signatures(&mut loaded_module, &all_exposed_symbols);
+
     // TODO fix: as is, this overrides an existing index.html
     // Write index.html for package (/index.html)
     {
@@ -164,8 +166,13 @@ pub fn generate_docs_html(root_file: PathBuf, build_dir: &Path) {
             )
             .replace(
                 "<!-- Module Docs -->",
-                render_module_documentation(module_docs, &loaded_module, &all_exposed_symbols)
-                    .as_str(),
+                render_module_documentation(
+                    module_docs,
+                    &loaded_module,
+                    &all_exposed_symbols,
+                    &solved_signatures,
+                )
+                .as_str(),
             );
 
         fs::write(module_dir.join("index.html"), rendered_module)
@@ -193,6 +200,66 @@ fn get_exposed_module_docs(
     exposed_docs
 }
 
+/// Pretty-prints the solved type of every exposed value, so that exposed defs
+/// without a type annotation still get a signature in the generated docs.
+fn solved_type_signatures(
+    loaded_module: &mut LoadedModule,
+    exposed_symbols: &VecSet<Symbol>,
+) -> MutMap<Symbol, String> {
+    let mut signatures = MutMap::default();
+    let LoadedModule {
+        module_id: root_module_id,
+        interns,
+        solved,
+        declarations_by_id,
+        typechecked,
+        ..
+    } = loaded_module;
+
+    for (module_id, checked) in typechecked.iter_mut() {
+        push_solved_signatures(
+            &mut signatures,
+            &checked.decls,
+            checked.solved_subs.inner_mut(),
+            *module_id,
+            interns,
+            exposed_symbols,
+        );
+    }
+
+    if let Some(decls) = declarations_by_id.get(root_module_id) {
+        push_solved_signatures(
+            &mut signatures,
+            decls,
+            solved.inner_mut(),
+            *root_module_id,
+            interns,
+            exposed_symbols,
+        );
+    }
+
+    signatures
+}
+
+fn push_solved_signatures(
+    signatures: &mut MutMap<Symbol, String>,
+    decls: &Declarations,
+    subs: &mut Subs,
+    home: ModuleId,
+    interns: &Interns,
+    exposed_symbols: &VecSet<Symbol>,
+) {
+    for (loc_symbol, var) in decls.symbols.iter().zip(decls.variables.iter()) {
+        let symbol = loc_symbol.value;
+
+        if exposed_symbols.contains(&symbol) {
+            let signature = name_and_print_var(*var, subs, home, interns, DebugPrint::NOTHING);
+
+            signatures.insert(symbol, signature);
+        }
+    }
+}
+
 fn page_title(package_name: &str, module_name: &str) -> String {
     format!("<title>{module_name} - {package_name}</title>")
 }
@@ -238,6 +305,7 @@ fn render_module_documentation(
     module: &ModuleDocumentation,
     root_module: &LoadedModule,
     all_exposed_symbols: &VecSet<Symbol>,
+    solved_signatures: &MutMap<Symbol, String>,
 ) -> String {
     let mut buf = String::new();
     let module_name = module.name.as_str();
@@ -280,6 +348,16 @@ fn render_module_documentation(
                         content.push(' ');
 
                         type_annotation_to_html(0, &mut content, type_ann, false);
+                    } else if let Some(signature) = solved_signatures.get(&doc_def.symbol) {
+                        // There was no annotation, so fall back on the type we inferred.
+                        content.push_str(" : ");
+
+                        match roc_load::docs::parse_type_annotation(signature) {
+                            Some(inferred) => {
+                                type_annotation_to_html(0, &mut content, &inferred, false)
+                            }
+                            None => content.push_str(&escape_html(signature)),
+                        }
                     }
 
                     push_html(
@@ -326,6 +404,22 @@ fn render_module_documentation(
     buf
 }
 
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
 fn push_html(buf: &mut String, tag_name: &str, attrs: Vec<(&str, &str)>, content: impl AsRef<str>) {
     buf.push('<');
     buf.push_str(tag_name);
@@ -1137,3 +1231,51 @@ fn markdown_to_html(
 
     pulldown_cmark::html::push_html(buf, docs_parser.into_iter());
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unannotated_defs_get_inferred_signatures() {
+        let dir = std::env::temp_dir().join(format!("roc_docs_inferred-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let package = "package \"greeting\"\n    exposes [Greeting]\n    packages {}\n";
+        let module = r#"interface Greeting
+    exposes [greet, greeting]
+    imports []
+
+greeting : Str
+greeting = "Hello, "
+
+greet = \name -> Str.concat greeting name
+"#;
+        fs::write(dir.join("main.roc"), package).unwrap();
+        fs::write(dir.join("Greeting.roc"), module).unwrap();
+
+        let mut loaded_module = load_module_for_docs(dir.join("main.roc"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let exposed_module_docs = get_exposed_module_docs(&mut loaded_module);
+        let mut exposed_symbols = VecSet::default();
+        for (_, docs) in exposed_module_docs.iter() {
+            exposed_symbols.insert_all(docs.exposed_symbols.iter().copied());
+        }
+        let solved_signatures = solved_type_signatures(&mut loaded_module, &exposed_symbols);
+
+        let (_, docs) = &exposed_module_docs[0];
+        let html =
+            render_module_documentation(docs, &loaded_module, &exposed_symbols, &solved_signatures);
+
+        // The inferred signature is rendered just like the annotated one is
+        assert!(
+            html.contains("<strong>greeting</strong> : Str</h3>"),
+            "{html}"
+        );
+        assert!(
+            html.contains("<strong>greet</strong> : Str -> Str</h3>"),
+            "{html}"
+        );
+    }
+}
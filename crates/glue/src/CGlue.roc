@@ -0,0 +1,414 @@
+app "c-glue"
+    packages { pf: "../platform/main.roc" }
+    imports [
+        pf.Types.{ Types },
+        pf.Shape.{ Shape, RocStructFields },
+        pf.File.{ File },
+        pf.TypeId.{ TypeId },
+    ]
+    provides [makeGlue] to pf
+
+makeGlue : List Types -> Result (List File) Str
+makeGlue = \typesByArch ->
+    dispatchHeader =
+        List.walk typesByArch mainHeaderStart \content, types ->
+            archStr = archName (Types.target types).architecture
+            macro = archMacro (Types.target types).architecture
+
+            Str.concat
+                content
+                """
+                #if defined($(macro))
+                #include "roc_app_$(archStr).h"
+                #endif
+
+                """
+
+    typesByArch
+    |> List.map convertTypesToFile
+    |> List.append { name: "roc_app.h", content: Str.concat dispatchHeader mainHeaderEnd }
+    |> List.append { name: "roc_std.h", content: rocStdHeader }
+    |> Ok
+
+convertTypesToFile : Types -> File
+convertTypesToFile = \types ->
+    # Types are always recorded after the types they depend on, so walking them in order
+    # means every type is declared before it gets used by value in another declaration.
+    declarations =
+        Types.walkShapes types fileHeader \buf, shape, id ->
+            when shape is
+                Struct { name, fields } ->
+                    generateStruct buf types id name fields
+
+                TagUnionPayload { name, fields } ->
+                    generateStruct buf types id name fields
+
+                TagUnion (Enumeration { name, tags, size }) ->
+                    generateEnumeration buf name tags size
+
+                TagUnion (NonRecursive { name, tags, discriminantSize, discriminantOffset }) ->
+                    if List.isEmpty tags then
+                        buf
+                    else
+                        generateNonRecursiveTagUnion buf types id name tags discriminantSize discriminantOffset
+
+                TagUnion (SingleTagStruct { name }) ->
+                    # TODO: generate accessors for the payload instead of an opaque blob.
+                    generateOpaque buf types id name
+
+                TagUnion (Recursive { name })
+                | TagUnion (NullableWrapped { name })
+                | TagUnion (NullableUnwrapped { name })
+                | TagUnion (NonNullableUnwrapped { name }) ->
+                    # Recursive tag unions are always behind a pointer.
+                    Str.concat buf "typedef void *$(name);\n\n"
+
+                Function _
+                | RecursivePointer _
+                | Unit
+                | Unsized
+                | EmptyTagUnion
+                | Num _
+                | Bool
+                | RocResult _ _
+                | RocStr
+                | RocDict _ _
+                | RocSet _
+                | RocList _
+                | RocBox _ ->
+                    # These are either declared in roc_std.h, or don't need a declaration in C.
+                    buf
+
+    content =
+        Types.entryPoints types
+        |> List.walk declarations \buf, T name id -> generateEntryPoint buf types name id
+
+    archStr = archName (Types.target types).architecture
+
+    { name: "roc_app_$(archStr).h", content }
+
+generateStruct : Str, Types, TypeId, Str, RocStructFields -> Str
+generateStruct = \buf, types, id, name, structFields ->
+    fields =
+        when structFields is
+            HasNoClosure list -> List.map list \{ name: fieldName, id: fieldId } -> { fieldName, fieldId }
+            HasClosure list -> List.map list \{ name: fieldName, id: fieldId } -> { fieldName, fieldId }
+
+    body =
+        List.walk fields "" \state, { fieldName, fieldId } ->
+            Str.concat state (fieldDeclaration types fieldId (escapeName fieldName) "    ")
+
+    """
+    $(buf)typedef struct $(name) {
+    $(body)} $(name);
+
+    $(layoutAssertions types id name)
+    """
+
+generateEnumeration : Str, Str, List Str, U32 -> Str
+generateEnumeration = \buf, name, tags, size ->
+    variants =
+        List.walkWithIndex tags "" \state, tag, index ->
+            Str.concat state "    $(name)_$(tag) = $(Num.toStr index),\n"
+
+    # C enums are int-sized, so store the discriminant in an integer of the right size instead.
+    reprType =
+        when size is
+            1 -> "uint8_t"
+            2 -> "uint16_t"
+            4 -> "uint32_t"
+            _ -> "uint64_t"
+
+    """
+    $(buf)enum $(name)_Tag {
+    $(variants)};
+
+    typedef $(reprType) $(name);
+
+    """
+
+generateNonRecursiveTagUnion : Str, Types, TypeId, Str, List { name : Str, payload : [Some TypeId, None] }, U32, U32 -> Str
+generateNonRecursiveTagUnion = \buf, types, id, name, tags, discriminantSize, discriminantOffset ->
+    tagNames = List.map tags \{ name: tagName } -> tagName
+    withDiscriminant = generateEnumeration buf "$(name)_Discriminant" tagNames discriminantSize
+
+    payloads =
+        List.walk tags "" \state, { name: tagName, payload } ->
+            when payload is
+                Some payloadId ->
+                    Str.concat state (fieldDeclaration types payloadId (escapeName tagName) "        ")
+
+                None ->
+                    state
+
+    # Roc may store the discriminant in the padding at the end of the largest payload, where a C
+    # struct couldn't put it, so overlay it on the payloads at the offset that Roc uses.
+    payloadBytes =
+        if discriminantOffset == 0 then
+            ""
+        else
+            "        uint8_t _payload[$(Num.toStr discriminantOffset)];\n"
+
+    """
+    $(withDiscriminant)typedef union $(name) {
+        union {
+    $(payloads)        uint8_t _empty;
+        } payload;
+        struct {
+    $(payloadBytes)        $(name)_Discriminant discriminant;
+        };
+    } $(name);
+
+    $(layoutAssertions types id name)
+    """
+
+generateOpaque : Str, Types, TypeId, Str -> Str
+generateOpaque = \buf, types, id, name ->
+    size = Num.toStr (Types.size types id)
+    align = Num.toStr (Types.alignment types id)
+
+    """
+    $(buf)typedef struct $(name) {
+        _Alignas($(align)) uint8_t bytes[$(size)];
+    } $(name);
+
+    """
+
+generateEntryPoint : Str, Types, Str, TypeId -> Str
+generateEntryPoint = \buf, types, name, id ->
+    when Types.shape types id is
+        Function rocFn ->
+            args =
+                List.walkWithIndex rocFn.args "" \state, argId, index ->
+                    shape = Types.shape types argId
+
+                    if isUnit shape then
+                        state
+                    else
+                        argType =
+                            if isScalar shape then
+                                typeName types argId
+                            else
+                                "$(typeName types argId) *"
+
+                        Str.concat state ", $(argType) arg$(Num.toStr index)"
+
+            when Types.shape types rocFn.ret is
+                Function _ ->
+                    # The returned closure's size is only known at runtime, so the caller must
+                    # allocate roc__$(name)_1_exposed_size() bytes for it.
+                    """
+                    $(buf)int64_t roc__$(name)_1_exposed_size(void);
+                    void roc__$(name)_1_exposed_generic(uint8_t *ret$(args));
+
+                    """
+
+                _ ->
+                    """
+                    $(buf)void roc__$(name)_1_exposed_generic($(typeName types rocFn.ret) *ret$(args));
+
+                    """
+
+        _ ->
+            """
+            $(buf)void roc__$(name)_1_exposed_generic($(typeName types id) *ret);
+
+            """
+
+layoutAssertions : Types, TypeId, Str -> Str
+layoutAssertions = \types, id, name ->
+    size = Num.toStr (Types.size types id)
+    align = Num.toStr (Types.alignment types id)
+
+    """
+    _Static_assert(sizeof($(name)) == $(size), "$(name) has the wrong size; please regenerate this file with `roc glue`");
+    _Static_assert(_Alignof($(name)) == $(align), "$(name) has the wrong alignment; please regenerate this file with `roc glue`");
+
+
+    """
+
+typeName : Types, TypeId -> Str
+typeName = \types, id ->
+    when Types.shape types id is
+        Unit -> "void"
+        Unsized -> "struct RocList"
+        EmptyTagUnion -> "void"
+        RocStr -> "struct RocStr"
+        Bool -> "bool"
+        Num U8 -> "uint8_t"
+        Num U16 -> "uint16_t"
+        Num U32 -> "uint32_t"
+        Num U64 -> "uint64_t"
+        Num U128 -> "unsigned __int128"
+        Num I8 -> "int8_t"
+        Num I16 -> "int16_t"
+        Num I32 -> "int32_t"
+        Num I64 -> "int64_t"
+        Num I128 -> "__int128"
+        Num F32 -> "float"
+        Num F64 -> "double"
+        Num Dec -> "struct RocDec"
+        RocList _ -> "struct RocList"
+        RocDict _ _ -> "struct RocDict"
+        RocSet _ -> "struct RocSet"
+        RocBox _ -> "void *"
+        RocResult _ _ -> "struct RocResult"
+        RecursivePointer _ -> "void *"
+        Function _ -> "uint8_t"
+        Struct { name } -> name
+        TagUnionPayload { name } -> name
+        TagUnion (NonRecursive { name }) -> name
+        TagUnion (Recursive { name }) -> name
+        TagUnion (Enumeration { name }) -> name
+        TagUnion (NullableWrapped { name }) -> name
+        TagUnion (NullableUnwrapped { name }) -> name
+        TagUnion (NonNullableUnwrapped { name }) -> name
+        TagUnion (SingleTagStruct { name }) -> name
+
+## Declares a field of a struct or union. Types that roc_std.h can't give a complete C type
+## are embedded as bytes with the size and alignment that Roc uses for them.
+fieldDeclaration : Types, TypeId, Str, Str -> Str
+fieldDeclaration = \types, id, name, indent ->
+    shape = Types.shape types id
+
+    if isUnit shape || Types.size types id == 0 then
+        # Zero-sized fields don't take up any space, and C doesn't allow `void` fields.
+        ""
+    else if isIncomplete shape then
+        size = Num.toStr (Types.size types id)
+        align = Num.toStr (Types.alignment types id)
+
+        "$(indent)_Alignas($(align)) uint8_t $(name)[$(size)];\n"
+    else
+        "$(indent)$(typeName types id) $(name);\n"
+
+## Whether C only knows this shape as an incomplete type, so it can't be embedded by value.
+## A function is embedded as the captures of its closure.
+isIncomplete : Shape -> Bool
+isIncomplete = \shape ->
+    when shape is
+        RocDict _ _ | RocSet _ | RocResult _ _ | Function _ -> Bool.true
+        _ -> Bool.false
+
+isUnit : Shape -> Bool
+isUnit = \shape ->
+    when shape is
+        Unit -> Bool.true
+        _ -> Bool.false
+
+## Whether a value of this shape can be passed to Roc by value, rather than by pointer.
+isScalar : Shape -> Bool
+isScalar = \shape ->
+    when shape is
+        Bool | Num _ | TagUnion (Enumeration _) -> Bool.true
+        _ -> Bool.false
+
+## Payload fields of tag union payloads are named `0`, `1`, etc., which aren't valid C identifiers.
+escapeName : Str -> Str
+escapeName = \name ->
+    when Str.toU64 name is
+        Ok _ -> "f$(name)"
+        Err _ -> name
+
+archName = \arch ->
+    when arch is
+        Aarch32 ->
+            "arm"
+
+        Aarch64 ->
+            "aarch64"
+
+        Wasm32 ->
+            "wasm32"
+
+        X86x32 ->
+            "x86"
+
+        X86x64 ->
+            "x86_64"
+
+archMacro = \arch ->
+    when arch is
+        Aarch32 ->
+            "__arm__"
+
+        Aarch64 ->
+            "__aarch64__"
+
+        Wasm32 ->
+            "__wasm32__"
+
+        X86x32 ->
+            "__i386__"
+
+        X86x64 ->
+            "__x86_64__"
+
+mainHeaderStart =
+    """
+    // ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+    #ifndef ROC_APP_H
+    #define ROC_APP_H
+
+
+    """
+
+mainHeaderEnd =
+    """
+    #endif // ROC_APP_H
+
+    """
+
+fileHeader =
+    """
+    // ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+    #pragma once
+
+    #include "roc_std.h"
+
+
+    """
+
+rocStdHeader =
+    """
+    // ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+    #pragma once
+
+    #include <stdbool.h>
+    #include <stddef.h>
+    #include <stdint.h>
+
+    struct RocStr {
+        char *bytes;
+        size_t len;
+        size_t capacity;
+    };
+
+    struct RocList {
+        void *elements;
+        size_t len;
+        size_t capacity;
+    };
+
+    struct RocDec {
+        __int128 value;
+    };
+
+    // These don't have a stable layout yet, so they're passed around by pointer, and other types
+    // embed them as bytes of the right size.
+    struct RocDict;
+    struct RocSet;
+    struct RocResult;
+
+    // The host must provide these for Roc to use.
+    void *roc_alloc(size_t size, uint32_t alignment);
+    void *roc_realloc(void *ptr, size_t new_size, size_t old_size, uint32_t alignment);
+    void roc_dealloc(void *ptr, uint32_t alignment);
+    void roc_panic(struct RocStr *msg, uint32_t tag_id);
+    void roc_dbg(struct RocStr *loc, struct RocStr *msg, struct RocStr *src);
+    void *roc_memset(void *str, int c, size_t n);
+
+    """
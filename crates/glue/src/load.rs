@@ -3,7 +3,7 @@ use crate::types::Types;
 use bumpalo::Bump;
 use libloading::Library;
 use roc_build::{
-    link::{LinkType, LinkingStrategy},
+    link::{LinkOptions, LinkType, LinkingStrategy},
     program::{
         build_file, handle_error_module, handle_loading_problem, standard_load_config,
         BuildFileError, BuildOrdering, BuiltFile, CodeGenBackend, CodeGenOptions,
@@ -57,6 +57,7 @@ pub fn generate(
                 opt_level: OptLevel::Development,
                 emit_debug_info: false,
                 emit_llvm_ir: false,
+                emit_artifact: None,
                 fuzz: false,
             };
 
@@ -85,6 +86,7 @@ pub fn generate(
                     false,
                     link_type,
                     linking_strategy,
+                    &LinkOptions::default(),
                     true,
                     None,
                     RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
@@ -7,6 +7,7 @@ use roc_builtins::bitcode::{
     FloatWidth::*,
     IntWidth::{self, *},
 };
+use roc_collections::all::sorted_entries;
 use roc_collections::{MutMap, VecMap};
 use roc_error_macros::todo_lambda_erasure;
 use roc_module::{
@@ -111,7 +112,13 @@ impl Types {
             target,
         );
 
-        for (_symbol, var) in entry_points.clone() {
+        // Entry points are numbered in the order they're added, so add them in a stable order.
+        let entry_point_vars: Vec<Variable> = sorted_entries(&entry_points)
+            .into_iter()
+            .map(|(_, var)| *var)
+            .collect();
+
+        for var in entry_point_vars {
             env.lambda_set_ids = env.find_lambda_sets(var);
             let id = env.add_toplevel_type(var, &mut types);
 
@@ -0,0 +1,4 @@
+// Compiled against the generated header by test_glue_cli. The header's own
+// _Static_asserts check the size and alignment of every type, so this only
+// needs to check that it compiles at all.
+#include "test_glue/roc_app.h"
@@ -0,0 +1,17 @@
+platform "test-platform"
+    requires {} { main : _ }
+    exposes []
+    packages {}
+    imports []
+    provides [mainForHost]
+
+# None of these have a complete C type in roc_std.h, so the glue has to embed them as bytes.
+Fields : {
+    dict : Dict Str U64,
+    set : Set U8,
+    result : Result Str I32,
+    callback : I64 -> I64,
+}
+
+mainForHost : {} -> Fields
+mainForHost = \{} -> main
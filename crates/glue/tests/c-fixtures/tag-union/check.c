@@ -0,0 +1,8 @@
+// Compiled against the generated header by test_glue_cli. The header's own
+// _Static_asserts check the size and alignment of every type.
+#include "test_glue/roc_app.h"
+
+_Static_assert(sizeof(NonRecursive_Discriminant) == 1, "the discriminant should be a single byte");
+_Static_assert(NonRecursive_Discriminant_Bar == 0, "tags should be sorted by name");
+_Static_assert(NonRecursive_Discriminant_Foo == 3, "tags should be sorted by name");
+_Static_assert(offsetof(NonRecursive, discriminant) == 24, "the discriminant should follow the Str");
@@ -0,0 +1,13 @@
+platform "test-platform"
+    requires {} { main : _ }
+    exposes []
+    packages {}
+    imports []
+    provides [mainForHost]
+
+# The same union as the union-with-padding fixture: the discriminant goes in the
+# padding after the Str, and has to be declared with the size that glue computed.
+NonRecursive : [Foo Str, Bar U128, Blah I32, Baz]
+
+mainForHost : {} -> NonRecursive
+mainForHost = \{} -> main
@@ -11,7 +11,7 @@ mod helpers;
 
 #[cfg(test)]
 mod glue_cli_run {
-    use crate::helpers::fixtures_dir;
+    use crate::helpers::{fixtures_dir, root_dir};
     use cli_utils::helpers::{has_error, run_glue, run_roc, Out};
     use std::fs;
     use std::path::{Path, PathBuf};
@@ -168,6 +168,67 @@ mod glue_cli_run {
         "#),
     }
 
+    #[test]
+    fn c_glue_matches_roc_layouts() {
+        check_c_glue("tag-union");
+    }
+
+    #[test]
+    fn c_glue_embeds_incomplete_types() {
+        check_c_glue("incomplete-fields");
+    }
+
+    /// The C glue has no host to run, so check that the generated header compiles and agrees
+    /// with the layouts Roc uses. The header `_Static_assert`s every type's size and alignment.
+    fn check_c_glue(fixture: &str) {
+        let glue_crate_dir = root_dir().join("crates").join("glue");
+        let platform_dir = glue_crate_dir
+            .join("tests")
+            .join("c-fixtures")
+            .join(fixture);
+        let glue_dir = platform_dir.join("test_glue");
+
+        if glue_dir.exists() {
+            fs::remove_dir_all(&glue_dir)
+                .expect("Unable to remove test_glue dir in order to regenerate it in the test");
+        }
+
+        let glue_out = run_glue([
+            "glue".to_string(),
+            glue_crate_dir
+                .join("src")
+                .join("CGlue.roc")
+                .to_str()
+                .unwrap()
+                .to_string(),
+            glue_dir.to_str().unwrap().to_string(),
+            platform_dir
+                .join("platform.roc")
+                .to_str()
+                .unwrap()
+                .to_string(),
+        ]);
+
+        assert!(
+            !has_error(&glue_out.stderr),
+            "`roc glue` command had unexpected stderr: {}",
+            glue_out.stderr
+        );
+        assert!(glue_out.status.success(), "bad status {glue_out:?}");
+
+        let cc_out = std::process::Command::new("cc")
+            .args(["-std=c11", "-fsyntax-only", "check.c"])
+            .current_dir(&platform_dir)
+            .output()
+            .expect("Unable to run cc");
+
+        assert!(
+            cc_out.status.success(),
+            "The generated C glue did not compile:\n{}",
+            String::from_utf8_lossy(&cc_out.stderr)
+        );
+    }
+
     fn check_for_tests(all_fixtures: &mut roc_collections::VecSet<String>) {
         use roc_collections::VecSet;
 
@@ -7,28 +7,36 @@ use std::{
 use bumpalo::Bump;
 
 use parking_lot::Mutex;
-use roc_can::{abilities::AbilitiesStore, expr::Declarations};
+use roc_can::{abilities::AbilitiesStore, expr::Declarations, traverse::SymbolOccurrences};
 use roc_collections::{MutMap, MutSet, VecMap};
-use roc_load::{docs::ModuleDocumentation, CheckedModule, LoadedModule};
+use roc_load::{
+    docs::ModuleDocumentation, CheckedModule, ExecutionMode, LoadResult, LoadStart, LoadedModule,
+    LoadingProblem, VirtualFiles,
+};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_packaging::cache::{self, RocCacheDir};
 use roc_region::all::LineInfo;
-use roc_reporting::report::RocDocAllocator;
+use roc_reporting::{diagnostic::DiagnosticFix, report::RocDocAllocator};
 use roc_solve_problem::TypeError;
 use roc_types::subs::{Subs, Variable};
 
 use tower_lsp::lsp_types::{Diagnostic, SemanticTokenType, Url};
 
 mod analysed_doc;
+mod canonical_tokens;
 mod completion;
+mod outline;
 mod parse_ast;
 mod semantic_tokens;
+mod signature_help;
+mod symbol_index;
 mod tokens;
 mod utils;
 
-use crate::convert::diag::{IntoLspDiagnostic, ProblemFmt};
+use crate::convert::diag::{IntoLspDiagnostic, IntoRocDiagnostic, ProblemFmt};
 
 pub(crate) use self::analysed_doc::{AnalyzedDocument, DocInfo};
+pub(crate) use self::symbol_index::SymbolIndex;
 use self::{analysed_doc::ModuleIdToUrl, tokens::Token};
 
 pub const HIGHLIGHT_TOKENS_LEGEND: &[SemanticTokenType] = Token::LEGEND;
@@ -91,6 +99,8 @@ pub(super) struct AnalyzedModule {
     subs: Subs,
     abilities: AbilitiesStore,
     declarations: Declarations,
+    /// Where each symbol is defined and used in this module, rebuilt whenever it's reanalyzed.
+    references: MutMap<Symbol, SymbolOccurrences>,
     modules_info: Arc<ModulesInfo>,
     // We need this because ModuleIds are not stable between compilations, so a ModuleId visible to
     // one module may not be true global to the language server.
@@ -100,24 +110,27 @@ pub(super) struct AnalyzedModule {
 pub struct AnalysisResult {
     module: Option<AnalyzedModule>,
     diagnostics: Vec<Diagnostic>,
+    quick_fixes: Vec<QuickFix>,
+}
+
+/// A fix suggested by the report behind one of the diagnostics, which is offered as a code action.
+#[derive(Debug, Clone)]
+struct QuickFix {
+    diagnostic: Diagnostic,
+    fix: DiagnosticFix,
 }
 
-pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
+/// Analyzes the document along with the modules it imports, reading those from `virtual_files`
+/// when they're open with unsaved changes. Nothing is reused from earlier analyses, so every
+/// call checks all of those modules from scratch.
+pub(crate) fn global_analysis(
+    doc_info: DocInfo,
+    virtual_files: VirtualFiles,
+) -> Vec<AnalyzedDocument> {
     let fi = doc_info.url.to_file_path().unwrap();
-    let src_dir = find_src_dir(&fi).to_path_buf();
 
     let arena = Bump::new();
-    let loaded = roc_load::load_and_typecheck_str(
-        &arena,
-        fi,
-        &doc_info.source,
-        src_dir,
-        roc_target::TargetInfo::default_x86_64(),
-        roc_load::FunctionKind::LambdaSet,
-        roc_reporting::report::RenderTarget::Generic,
-        RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
-        roc_reporting::report::DEFAULT_PALETTE,
-    );
+    let loaded = typecheck(&arena, fi, &doc_info.source, virtual_files);
 
     let module = match loaded {
         Ok(module) => module,
@@ -132,6 +145,7 @@ pub(crate) fn global_analysis(doc_info: DocInfo) -> Vec<AnalyzedDocument> {
                 analysis_result: AnalysisResult {
                     module: None,
                     diagnostics: all_problems,
+                    quick_fixes: Vec::new(),
                 },
             };
 
@@ -220,6 +234,38 @@ fn resolve_exposed_imports(
         .collect()
 }
 
+fn typecheck<'a>(
+    arena: &'a Bump,
+    path: PathBuf,
+    source: &'a str,
+    virtual_files: VirtualFiles,
+) -> Result<LoadedModule, LoadingProblem<'a>> {
+    let cache_dir = cache::roc_cache_dir();
+    let roc_cache_dir = RocCacheDir::Persistent(cache_dir.as_path());
+    let src_dir = find_src_dir(&path).to_path_buf();
+
+    let load_start = LoadStart::from_str(arena, path, source, roc_cache_dir, src_dir)?
+        .with_virtual_files(virtual_files);
+
+    let loaded = roc_load::load_single_threaded(
+        arena,
+        load_start,
+        roc_target::TargetInfo::default_x86_64(),
+        roc_load::FunctionKind::LambdaSet,
+        roc_reporting::report::RenderTarget::Generic,
+        roc_reporting::report::DEFAULT_PALETTE,
+        roc_cache_dir,
+        ExecutionMode::Check,
+    )?;
+
+    match loaded {
+        LoadResult::TypeChecked(module) => Ok(module),
+        LoadResult::Monomorphized(_) | LoadResult::TypeCheckedForTargets(_) => {
+            unreachable!("the language server only typechecks")
+        }
+    }
+}
+
 fn find_src_dir(path: &Path) -> &Path {
     path.parent().unwrap_or(path)
 }
@@ -302,12 +348,15 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
             declarations = self.declarations_by_id.remove(&module_id).unwrap();
         }
 
+        let references = roc_can::traverse::index_symbol_occurrences(&declarations);
+
         let analyzed_module = AnalyzedModule {
             exposed_imports,
             imports,
             subs,
             abilities,
             declarations,
+            references,
             module_id,
             modules_info: self.modules_info.clone(),
             interns: self.interns.clone(),
@@ -315,7 +364,8 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
         };
 
         let line_info = LineInfo::new(&source);
-        let diagnostics = self.build_diagnostics(&path, &source, &line_info, module_id);
+        let (diagnostics, quick_fixes) =
+            self.build_diagnostics(&path, &source, &line_info, module_id);
 
         AnalyzedDocument {
             doc_info: DocInfo {
@@ -327,6 +377,7 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
             analysis_result: AnalysisResult {
                 module: Some(analyzed_module),
                 diagnostics,
+                quick_fixes,
             },
         }
     }
@@ -357,12 +408,13 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
         source: &str,
         line_info: &LineInfo,
         module_id: ModuleId,
-    ) -> Vec<Diagnostic> {
+    ) -> (Vec<Diagnostic>, Vec<QuickFix>) {
         let lines: Vec<_> = source.lines().collect();
 
         let alloc = RocDocAllocator::new(&lines, module_id, self.interns);
 
         let mut all_problems = Vec::new();
+        let mut quick_fixes = Vec::new();
         let fmt = ProblemFmt {
             alloc: &alloc,
             line_info,
@@ -373,18 +425,26 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
 
         let type_problems = self.type_problems.remove(&module_id).unwrap_or_default();
 
-        for can_problem in can_problems {
-            if let Some(diag) = can_problem.into_lsp_diagnostic(&fmt) {
-                all_problems.push(diag);
-            }
-        }
+        let roc_diagnostics = (can_problems.into_iter())
+            .filter_map(|can_problem| can_problem.into_roc_diagnostic(&fmt))
+            .chain(
+                (type_problems.into_iter())
+                    .filter_map(|type_problem| type_problem.into_roc_diagnostic(&fmt)),
+            );
+
+        for mut roc_diagnostic in roc_diagnostics {
+            let fixes = std::mem::take(&mut roc_diagnostic.fixes);
+
+            if let Some(diagnostic) = roc_diagnostic.into_lsp_diagnostic(&()) {
+                quick_fixes.extend(fixes.into_iter().map(|fix| QuickFix {
+                    diagnostic: diagnostic.clone(),
+                    fix,
+                }));
 
-        for type_problem in type_problems {
-            if let Some(diag) = type_problem.into_lsp_diagnostic(&fmt) {
-                all_problems.push(diag);
+                all_problems.push(diagnostic);
             }
         }
 
-        all_problems
+        (all_problems, quick_fixes)
     }
 }
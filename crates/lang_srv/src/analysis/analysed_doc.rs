@@ -1,27 +1,36 @@
 use log::{debug, info};
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use bumpalo::Bump;
 
-use roc_module::symbol::{ModuleId, Symbol};
+use roc_module::{
+    ident::ModuleName,
+    symbol::{ModuleId, Symbol},
+};
 
-use roc_region::all::LineInfo;
+use roc_region::all::{LineInfo, Region};
 
 use tower_lsp::lsp_types::{
-    CompletionItem, Diagnostic, GotoDefinitionResponse, Hover, HoverContents, LanguageString,
-    Location, MarkedString, Position, Range, SemanticTokens, SemanticTokensResult, TextEdit, Url,
+    CodeAction, CodeActionKind, CodeActionOrCommand, CompletionItem, Diagnostic,
+    DocumentSymbolResponse, GotoDefinitionResponse, Hover, HoverContents, LanguageString, Location,
+    MarkedString, Position, Range, SemanticTokens, SemanticTokensResult, SignatureHelp, TextEdit,
+    Url, WorkspaceEdit,
 };
 
 use crate::{
-    analysis::completion::{field_completion, get_completion_items, get_module_completion_items},
-    convert::{ToRange, ToRocPosition},
+    analysis::completion::{
+        field_completion, get_completion_items, get_module_completion_items,
+        get_tag_completion_items,
+    },
+    convert::{diag::to_lsp_text_edit, ToRange, ToRocPosition},
 };
 
 use super::{
-    parse_ast::Ast,
-    semantic_tokens::arrange_semantic_tokens,
-    utils::{format_var_type, is_roc_identifier_char},
-    AnalysisResult, AnalyzedModule,
+    canonical_tokens::refine_tokens, outline::Outline, parse_ast::Ast,
+    semantic_tokens::arrange_semantic_tokens, signature_help::signature_help,
+    symbol_index::IndexedSymbol, utils::is_roc_identifier_char, AnalysisResult, AnalyzedModule,
+    QuickFix,
 };
 
 pub(super) type ModuleIdToUrl = HashMap<ModuleId, Url>;
@@ -82,12 +91,12 @@ impl DocInfo {
 
         String::from(symbol)
     }
-    pub fn format(&self) -> Option<Vec<TextEdit>> {
+    pub fn format(&self, max_width: Option<usize>) -> Option<Vec<TextEdit>> {
         let source = &self.source;
         let arena = &Bump::new();
 
         let ast = Ast::parse(arena, source).ok()?;
-        let fmt = ast.fmt();
+        let fmt = ast.fmt(max_width);
 
         if source == fmt.as_str() {
             None
@@ -98,6 +107,28 @@ impl DocInfo {
         }
     }
 
+    pub fn format_range(&self, range: Range, max_width: Option<usize>) -> Option<Vec<TextEdit>> {
+        let arena = &Bump::new();
+        let start = range.start.to_roc_position(&self.line_info).offset as usize;
+        let end = range.end.to_roc_position(&self.line_info).offset as usize;
+
+        let edits = roc_fmt::format_range(arena, &self.source, start..end, max_width).ok()?;
+
+        let text_edits = edits
+            .into_iter()
+            .map(|edit| {
+                let region = Region::new(
+                    roc_region::all::Position::new(edit.range.start as u32),
+                    roc_region::all::Position::new(edit.range.end as u32),
+                );
+
+                TextEdit::new(region.to_range(&self.line_info), edit.replacement)
+            })
+            .collect();
+
+        Some(text_edits)
+    }
+
     pub fn semantic_tokens(&self) -> Option<SemanticTokensResult> {
         let source = &self.source;
         let arena = &Bump::new();
@@ -142,6 +173,31 @@ impl AnalyzedDocument {
         self.analysis_result.diagnostics.clone()
     }
 
+    /// The fixes suggested for the diagnostics which overlap `range`, as quick fixes. Fixes the
+    /// compiler is sure about are marked as preferred, so editors can apply them automatically.
+    pub fn code_actions(&self, range: Range) -> Vec<CodeActionOrCommand> {
+        self.analysis_result
+            .quick_fixes
+            .iter()
+            .filter(|QuickFix { diagnostic, .. }| {
+                diagnostic.range.start <= range.end && range.start <= diagnostic.range.end
+            })
+            .map(|QuickFix { diagnostic, fix }| {
+                let edits = fix.edits.iter().cloned().map(to_lsp_text_edit).collect();
+                let changes = HashMap::from([(self.url().clone(), edits)]);
+
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.description.clone(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit::new(changes)),
+                    is_preferred: Some(fix.machine_applicable),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
     pub fn symbol_at(&self, position: Position) -> Option<Symbol> {
         let line_info = self.line_info();
 
@@ -173,15 +229,14 @@ impl AnalyzedDocument {
             ..
         } = self.module()?;
 
-        let (region, var) = roc_can::traverse::find_closest_type_at(pos, declarations)?;
+        let (region, type_str) =
+            roc_load::type_at(declarations, &mut subs.clone(), *module_id, interns, pos)?;
 
         //TODO:Can this be integrated into find closest type? is it even worth it?
         let docs_opt = self
             .symbol_at(position)
             .and_then(|symb| modules_info.docs.get(module_id)?.get_doc_for_symbol(&symb));
 
-        let type_str = format_var_type(var, &mut subs.clone(), module_id, interns);
-
         let range = region.to_range(self.line_info());
 
         let type_content = MarkedString::LanguageString(LanguageString {
@@ -200,6 +255,99 @@ impl AnalyzedDocument {
         })
     }
 
+    /// The document's semantic tokens, refined with what canonicalization knows about the names
+    /// in it.
+    pub fn semantic_tokens(&self) -> Option<SemanticTokensResult> {
+        let AnalyzedModule {
+            subs, declarations, ..
+        } = self.module()?;
+
+        let source = &self.doc_info.source;
+        let arena = &Bump::new();
+
+        let ast = Ast::parse(arena, source).ok()?;
+        let tokens = refine_tokens(ast.semantic_tokens(), source, declarations, subs);
+
+        let data = arrange_semantic_tokens(tokens, self.line_info());
+
+        Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data,
+        }))
+    }
+
+    pub fn signature_help(&self, position: Position) -> Option<SignatureHelp> {
+        let AnalyzedModule {
+            subs,
+            declarations,
+            module_id,
+            interns,
+            modules_info,
+            ..
+        } = self.module()?;
+
+        let position = position.to_roc_position(self.line_info());
+
+        signature_help(
+            position,
+            declarations,
+            &mut subs.clone(),
+            module_id,
+            interns,
+            &modules_info.docs,
+        )
+    }
+
+    pub fn document_symbols(&self) -> Option<DocumentSymbolResponse> {
+        let AnalyzedModule {
+            subs,
+            declarations,
+            module_id,
+            interns,
+            ..
+        } = self.module()?;
+
+        let source = &self.doc_info.source;
+        let arena = Bump::new();
+        let ast = Ast::parse(&arena, source).ok();
+
+        let mut outline = Outline {
+            source,
+            line_info: self.line_info(),
+            subs: &mut subs.clone(),
+            module_id,
+            interns,
+        };
+
+        let symbols = outline.document_symbols(declarations, ast.as_ref());
+
+        Some(DocumentSymbolResponse::Nested(symbols))
+    }
+
+    /// The module's top-level symbols, for the workspace's [SymbolIndex](super::SymbolIndex).
+    pub fn indexed_symbols(&self) -> Vec<IndexedSymbol> {
+        let Some(DocumentSymbolResponse::Nested(symbols)) = self.document_symbols() else {
+            return Vec::new();
+        };
+        let Some(module_name) = self
+            .module()
+            .and_then(|module| module.interns.module_ids.get_name(module.module_id))
+        else {
+            return Vec::new();
+        };
+
+        symbols
+            .into_iter()
+            .map(|symbol| IndexedSymbol {
+                name: symbol.name,
+                kind: symbol.kind,
+                module_name: module_name.as_str().to_string(),
+                type_str: symbol.detail,
+                location: self.location(symbol.selection_range),
+            })
+            .collect()
+    }
+
     pub fn definition(&self, symbol: Symbol) -> Option<GotoDefinitionResponse> {
         let AnalyzedModule { declarations, .. } = self.module()?;
 
@@ -210,6 +358,216 @@ impl AnalyzedDocument {
         Some(GotoDefinitionResponse::Scalar(self.location(range)))
     }
 
+    /// Builtins have no file in the user's project, so their definitions are found in a copy of
+    /// the builtin module's source, which gets written to a temporary directory. That directory
+    /// belongs to this process, so language servers of other roc versions don't overwrite it.
+    pub fn builtin_definition(&self, symbol: Symbol) -> Option<GotoDefinitionResponse> {
+        let AnalyzedModule { interns, .. } = self.module()?;
+
+        let source = roc_builtins::roc::try_module_source(symbol.module_id())?;
+        let arena = Bump::new();
+        let ast = Ast::parse(&arena, source).ok()?;
+        let region = ast.definition_region(symbol.as_str(interns))?;
+
+        let dir = std::env::temp_dir().join(format!("roc_builtins-{}", std::process::id()));
+        let path = dir.join(format!("{}.roc", symbol.module_string(interns)));
+
+        if std::fs::read_to_string(&path).ok().as_deref() != Some(source) {
+            std::fs::create_dir_all(&dir).ok()?;
+            std::fs::write(&path, source).ok()?;
+        }
+
+        Some(GotoDefinitionResponse::Scalar(Location {
+            uri: Url::from_file_path(path).ok()?,
+            range: region.to_range(&LineInfo::new(source)),
+        }))
+    }
+
+    /// The names of `symbol`'s module and of the symbol itself. Module ids are only stable within
+    /// one compilation, so this is how a symbol is found in other documents.
+    pub fn symbol_name(&self, symbol: Symbol) -> Option<(ModuleName, String)> {
+        let AnalyzedModule { interns, .. } = self.module()?;
+
+        let module_name = interns.module_ids.get_name(symbol.module_id())?.clone();
+        let ident = interns.all_ident_ids.get(&symbol.module_id())?;
+
+        Some((module_name, ident.get_name(symbol.ident_id())?.to_string()))
+    }
+
+    /// The directory which this document's module name is relative to, e.g. `src` for the module
+    /// `Json.Decode` in `src/Json/Decode.roc`.
+    pub fn package_root(&self) -> Option<PathBuf> {
+        let AnalyzedModule {
+            module_id, interns, ..
+        } = self.module()?;
+
+        let path = self.doc_info.url.to_file_path().ok()?;
+        let module_name = interns.module_ids.get_name(*module_id)?.as_str();
+        let module_path = format!("{}.roc", module_name.replace('.', "/"));
+
+        if path.ends_with(module_path) {
+            let depth = module_name.matches('.').count();
+
+            path.ancestors()
+                .nth(depth + 1)
+                .map(|root| root.to_path_buf())
+        } else {
+            path.parent().map(|root| root.to_path_buf())
+        }
+    }
+
+    /// Whether `symbol` is defined within a def of this document's module, so no other module
+    /// can refer to it.
+    pub fn is_local(&self, symbol: Symbol) -> bool {
+        let Some(AnalyzedModule {
+            module_id,
+            declarations,
+            ..
+        }) = self.module()
+        else {
+            return false;
+        };
+
+        symbol.module_id() == *module_id
+            && !declarations
+                .symbols
+                .iter()
+                .any(|loc_symbol| loc_symbol.value == symbol)
+    }
+
+    /// The symbol named by [AnalyzedDocument::symbol_name] in this document's compilation.
+    pub fn resolve_symbol(&self, module_name: &ModuleName, ident: &str) -> Option<Symbol> {
+        let AnalyzedModule { interns, .. } = self.module()?;
+
+        let module_id = interns.module_ids.get_id(module_name)?;
+        let ident_id = interns.all_ident_ids.get(&module_id)?.get_id(ident)?;
+
+        Some(Symbol::new(module_id, ident_id))
+    }
+
+    /// The regions of `symbol`'s name in this document: its uses, and the header's exposes and
+    /// imports lists, along with its definitions and annotations if `include_declaration` is set.
+    fn reference_regions(&self, symbol: Symbol, include_declaration: bool) -> Vec<Region> {
+        let Some(AnalyzedModule {
+            module_id,
+            references,
+            ..
+        }) = self.module()
+        else {
+            return Vec::new();
+        };
+        let Some((symbol_module_name, ident)) = self.symbol_name(symbol) else {
+            return Vec::new();
+        };
+        let source = &self.doc_info.source;
+        let mut regions = Vec::new();
+
+        if let Some(occurrences) = references.get(&symbol) {
+            let lookups = occurrences
+                .lookups
+                .iter()
+                .filter_map(|&region| name_region(source, region, &ident));
+
+            regions.extend(lookups);
+
+            if include_declaration {
+                let definitions = occurrences
+                    .definitions
+                    .iter()
+                    .filter_map(|&region| name_region(source, region, &ident));
+                let annotations = occurrences
+                    .annotations
+                    .iter()
+                    .filter_map(|&region| annotation_name_region(source, region, &ident));
+
+                regions.extend(definitions);
+                regions.extend(annotations);
+            }
+        }
+
+        let arena = Bump::new();
+
+        if let Ok(ast) = Ast::parse(&arena, source) {
+            let defined_here = symbol.module_id() == *module_id;
+            let header_regions =
+                ast.header_name_regions(&ident, defined_here, symbol_module_name.as_str());
+
+            regions.extend(header_regions);
+        }
+
+        regions.sort();
+        regions.dedup();
+
+        regions
+    }
+
+    /// The locations in this document which refer to `symbol`.
+    pub fn references(&self, symbol: Symbol, include_declaration: bool) -> Vec<Location> {
+        self.reference_regions(symbol, include_declaration)
+            .into_iter()
+            .map(|region| self.location(region.to_range(self.line_info())))
+            .collect()
+    }
+
+    /// The edits which rename `symbol` to `new_name` in this document: its definitions, its uses,
+    /// and the header's exposes and imports lists. A record field like `{ x }` which names the
+    /// symbol keeps its name, becoming `{ x: y }`. Fails if `new_name` is already used in the
+    /// module, where the renamed symbol would shadow or collide with it.
+    pub fn rename_edits(&self, symbol: Symbol, new_name: &str) -> Result<Vec<TextEdit>, String> {
+        let Some(AnalyzedModule {
+            module_id,
+            interns,
+            references,
+            ..
+        }) = self.module()
+        else {
+            return Ok(Vec::new());
+        };
+
+        let regions = self.reference_regions(symbol, true);
+        let ident_ids = interns.all_ident_ids.get(module_id);
+        let ident = symbol.as_str(interns);
+
+        if !regions.is_empty() && ident_ids.map_or(false, |ids| ids.get_id(new_name).is_some()) {
+            let module_name = interns.module_ids.get_name(*module_id);
+
+            return Err(format!(
+                "Can't rename `{ident}` to `{new_name}`, because `{new_name}` is already defined in {}.",
+                module_name.map_or("this module", |name| name.as_str()),
+            ));
+        }
+
+        let punned = references
+            .get(&symbol)
+            .map_or(&[][..], |occurrences| &occurrences.punned);
+        let source = &self.doc_info.source;
+
+        regions
+            .into_iter()
+            .map(|region| {
+                let new_text = if punned.contains(&region) {
+                    let after = &source[region.end().offset as usize..];
+
+                    // `{ x ? 0 }` can't be written with a pattern for `x`
+                    if after.trim_start().starts_with('?') {
+                        return Err(format!(
+                            "Can't rename `{ident}`, because it's destructured from a record field with a default value."
+                        ));
+                    }
+
+                    format!("{ident}: {new_name}")
+                } else {
+                    new_name.to_string()
+                };
+
+                Ok(TextEdit {
+                    range: region.to_range(self.line_info()),
+                    new_text,
+                })
+            })
+            .collect()
+    }
+
     pub(crate) fn module_url(&self, module_id: ModuleId) -> Option<Url> {
         self.module()?.module_id_to_url.get(&module_id).cloned()
     }
@@ -281,14 +639,17 @@ impl AnalyzedDocument {
                 .map_or(false, |c| c.is_uppercase());
 
             if is_module_or_type_completion {
-                info!("Getting module completion...");
-                let completions = get_module_completion_items(
+                info!("Getting module and tag completion...");
+                let tag_completions =
+                    get_tag_completion_items(position, &symbol_prefix, declarations, subs);
+                let mut completions = get_module_completion_items(
                     symbol_prefix,
                     interns,
                     imports,
                     modules_info,
                     true,
                 );
+                completions.extend(tag_completions);
                 Some(completions)
             } else {
                 info!("Getting variable completion...");
@@ -307,3 +668,31 @@ impl AnalyzedDocument {
         }
     }
 }
+
+/// The region of `name` at the end of `region`, which may start with a module name or pattern.
+fn name_region(source: &str, region: Region, name: &str) -> Option<Region> {
+    let end = region.end().offset as usize;
+    let text = source.get(region.start().offset as usize..end)?;
+
+    text.ends_with(name).then(|| {
+        Region::new(
+            roc_region::all::Position::new((end - name.len()) as u32),
+            region.end(),
+        )
+    })
+}
+
+/// The region of the `name` in `name : Type`, given the region of the type.
+fn annotation_name_region(source: &str, type_region: Region, name: &str) -> Option<Region> {
+    let before_type = source.get(..type_region.start().offset as usize)?;
+    let before_colon = before_type.trim_end().strip_suffix(':')?.trim_end();
+
+    before_colon.ends_with(name).then(|| {
+        let end = before_colon.len();
+
+        Region::new(
+            roc_region::all::Position::new((end - name.len()) as u32),
+            roc_region::all::Position::new(end as u32),
+        )
+    })
+}
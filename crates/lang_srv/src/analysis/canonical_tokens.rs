@@ -0,0 +1,156 @@
+//! Refines the semantic tokens found from the parse AST with what canonicalization knows about
+//! each name, which the syntax alone can't tell: whether a value is a function or a parameter,
+//! and which part of a qualified name like `Str.concat` is the module.
+use roc_can::{
+    expr::{AnnotatedMark, ClosureData, Declarations, Expr},
+    pattern::Pattern,
+    traverse::{
+        symbols_introduced_from_pattern, walk_decl, walk_expr, walk_pattern, DeclarationInfo,
+        Visitor,
+    },
+};
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::Symbol;
+use roc_region::all::{Loc, Position, Region};
+use roc_types::subs::{Content, FlatType, Subs, Variable};
+
+use super::tokens::Token;
+
+/// Replaces the tokens of the values named in `declarations` with more precise ones. Tokens
+/// canonicalization doesn't know about are kept as they are.
+pub(super) fn refine_tokens(
+    tokens: impl IntoIterator<Item = Loc<Token>>,
+    source: &str,
+    declarations: &Declarations,
+    subs: &Subs,
+) -> Vec<Loc<Token>> {
+    let mut collector = NameCollector {
+        subs,
+        names: Vec::new(),
+        parameters: MutSet::default(),
+    };
+    collector.visit_decls(declarations);
+
+    let NameCollector {
+        names, parameters, ..
+    } = collector;
+
+    let refined: MutMap<Region, Token> = names
+        .into_iter()
+        .map(|name| {
+            let token = if parameters.contains(&name.symbol) {
+                Token::Parameter
+            } else if name.is_function {
+                Token::Function
+            } else {
+                Token::Variable
+            };
+
+            (name.region, token)
+        })
+        .collect();
+
+    let mut result = Vec::new();
+
+    for Loc { region, value } in tokens {
+        let Some(&token) = refined.get(&region) else {
+            result.push(Loc::at(region, value));
+            continue;
+        };
+
+        match qualifier_len(source, region) {
+            Some(len) => {
+                let start = region.start().offset;
+                let module = Region::new(region.start(), Position::new(start + len));
+                let name = Region::new(Position::new(start + len + 1), region.end());
+
+                result.push(Loc::at(module, Token::Module));
+                result.push(Loc::at(name, token));
+            }
+            None => result.push(Loc::at(region, token)),
+        }
+    }
+
+    result
+}
+
+/// The length of the module name before the last `.` in a qualified lookup, if it has one.
+fn qualifier_len(source: &str, region: Region) -> Option<u32> {
+    let text = source.get(region.start().offset as usize..region.end().offset as usize)?;
+
+    text.rfind('.').map(|dot| dot as u32)
+}
+
+struct Name {
+    region: Region,
+    symbol: Symbol,
+    is_function: bool,
+}
+
+/// Collects the definitions and lookups of values, along with the symbols bound by function
+/// arguments, which are only known to be parameters once the whole module has been walked.
+struct NameCollector<'a> {
+    subs: &'a Subs,
+    names: Vec<Name>,
+    parameters: MutSet<Symbol>,
+}
+
+impl NameCollector<'_> {
+    fn push(&mut self, region: Region, symbol: Symbol, var: Option<Variable>) {
+        let is_function = var.map_or(false, |var| is_function(self.subs, var));
+
+        self.names.push(Name {
+            region,
+            symbol,
+            is_function,
+        });
+    }
+
+    fn add_parameters(&mut self, arguments: &[(Variable, AnnotatedMark, Loc<Pattern>)]) {
+        let symbols = arguments
+            .iter()
+            .flat_map(|(_, _, pattern)| symbols_introduced_from_pattern(pattern));
+
+        self.parameters
+            .extend(symbols.map(|loc_symbol| loc_symbol.value));
+    }
+}
+
+impl Visitor for NameCollector<'_> {
+    fn visit_decl(&mut self, decl: DeclarationInfo<'_>) {
+        // Top-level functions' arguments aren't visited as part of a closure expression.
+        if let DeclarationInfo::Function { function, .. } = &decl {
+            self.add_parameters(&function.value.arguments);
+        }
+
+        walk_decl(self, decl);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        match expr {
+            Expr::Var(symbol, var) | Expr::AbilityMember(symbol, _, var) => {
+                self.push(region, *symbol, Some(*var));
+            }
+            Expr::Closure(ClosureData { arguments, .. }) => self.add_parameters(arguments),
+            _ => {}
+        }
+
+        walk_expr(self, expr, var);
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern, region: Region, opt_var: Option<Variable>) {
+        if let Pattern::Identifier(symbol) = pattern {
+            self.push(region, *symbol, opt_var);
+        }
+
+        walk_pattern(self, pattern);
+    }
+}
+
+fn is_function(subs: &Subs, var: Variable) -> bool {
+    match subs.get_content_without_compacting(var) {
+        Content::Structure(FlatType::Func(..)) => true,
+        Content::Alias(_, _, real_var, _) => is_function(subs, *real_var),
+        _ => false,
+    }
+}
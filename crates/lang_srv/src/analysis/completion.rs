@@ -5,11 +5,14 @@ use log::{debug, warn};
 use roc_can::{expr::Declarations, traverse::Visitor};
 use roc_collections::MutMap;
 use roc_load::docs::{DocDef, ModuleDocumentation};
-use roc_module::symbol::{Interns, ModuleId, Symbol};
-use roc_region::all::Position;
+use roc_module::{
+    ident::TagName,
+    symbol::{Interns, ModuleId, Symbol},
+};
+use roc_region::all::{Position, Region};
 use roc_types::{
-    subs::{Subs, Variable},
-    types::Alias,
+    subs::{Content, FlatType, Subs, Variable},
+    types::{Alias, Type, TypeExtension},
 };
 use tower_lsp::lsp_types::{self, CompletionItem, CompletionItemKind};
 
@@ -167,6 +170,112 @@ fn get_completion_docs(
         .collect()
 }
 
+/// Provides completions for the tags in the annotation of the definition around `position`,
+/// e.g. `Green` when completing `color = Gr` where `color : [Red, Green]`, and for the tags
+/// of the expression being completed if it already has a tag union type.
+pub(super) fn get_tag_completion_items(
+    position: Position,
+    prefix: &str,
+    decls: &Declarations,
+    subs: &Subs,
+) -> Vec<CompletionItem> {
+    let enclosing_annotation = (0..decls.len())
+        .find(|&index| {
+            Region::span_across(
+                &decls.symbols[index].region,
+                &decls.expressions[index].region,
+            )
+            .contains_pos(position)
+        })
+        .and_then(|index| decls.annotations[index].as_ref());
+
+    let mut tag_names = Vec::new();
+
+    if let Some(annotation) = enclosing_annotation {
+        collect_tag_names(&annotation.signature, &mut tag_names);
+    }
+
+    if let Some((_, var)) = roc_can::traverse::find_closest_type_at(position, decls) {
+        collect_solved_tag_names(var, subs, &mut tag_names);
+    }
+
+    tag_names
+        .into_iter()
+        .map(|tag_name| tag_name.0.as_str().to_string())
+        .filter(|name| name.starts_with(prefix))
+        .map(|name| CompletionItem {
+            label: name,
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn add_tag_name(tag_name: &TagName, tag_names: &mut Vec<TagName>) {
+    if !tag_names.contains(tag_name) {
+        tag_names.push(tag_name.clone());
+    }
+}
+
+/// Collects the names of all the tags in a type, without duplicates.
+fn collect_tag_names(typ: &Type, tag_names: &mut Vec<TagName>) {
+    match typ {
+        Type::TagUnion(tags, ext) | Type::RecursiveTagUnion(_, tags, ext) => {
+            for (tag_name, args) in tags {
+                add_tag_name(tag_name, tag_names);
+
+                for arg in args {
+                    collect_tag_names(arg, tag_names);
+                }
+            }
+
+            if let TypeExtension::Open(ext, _) = ext {
+                collect_tag_names(ext, tag_names);
+            }
+        }
+        Type::FunctionOrTagUnion(tag_name, _, _) => add_tag_name(tag_name, tag_names),
+        Type::Function(args, _, ret) => {
+            for arg in args {
+                collect_tag_names(arg, tag_names);
+            }
+
+            collect_tag_names(ret, tag_names);
+        }
+        Type::Record(fields, _) => {
+            for field in fields.values() {
+                collect_tag_names(field.as_inner(), tag_names);
+            }
+        }
+        Type::Tuple(elems, _) => {
+            for (_, elem) in elems.iter() {
+                collect_tag_names(elem, tag_names);
+            }
+        }
+        Type::Alias { actual, .. } => collect_tag_names(actual, tag_names),
+        Type::Apply(_, args, _) => {
+            for arg in args {
+                collect_tag_names(&arg.value, tag_names);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the names of the tags of a solved tag union type.
+fn collect_solved_tag_names(var: Variable, subs: &Subs, tag_names: &mut Vec<TagName>) {
+    match subs.get_content_without_compacting(var) {
+        Content::Alias(_, _, real_var, _) => collect_solved_tag_names(*real_var, subs, tag_names),
+        Content::Structure(
+            FlatType::TagUnion(tags, ext) | FlatType::RecursiveTagUnion(_, tags, ext),
+        ) => {
+            for (tag_name, _) in tags.unsorted_iterator(subs, *ext) {
+                add_tag_name(tag_name, tag_names);
+            }
+        }
+        _ => {}
+    }
+}
+
 /// Provides a list of completions for Type aliases within the scope.
 ///TODO: Use this when we know we are within a type definition
 fn _alias_completions(
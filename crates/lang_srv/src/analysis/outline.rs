@@ -0,0 +1,212 @@
+//! The outline of a module, for `textDocument/documentSymbol`.
+use roc_can::{
+    def::Def,
+    expr::{Declarations, Expr},
+    pattern::Pattern,
+    traverse::{walk_decl, walk_def, DeclarationInfo, Visitor},
+};
+use roc_module::symbol::{Interns, ModuleId};
+use roc_parse::ast::TypeDef;
+use roc_region::all::{LineInfo, Region};
+use roc_types::subs::{Subs, Variable};
+use tower_lsp::lsp_types::{DocumentSymbol, SymbolKind};
+
+use crate::convert::ToRange;
+
+use super::{parse_ast::Ast, utils::format_var_type};
+
+pub(super) struct Outline<'a> {
+    pub source: &'a str,
+    pub line_info: &'a LineInfo,
+    pub subs: &'a mut Subs,
+    pub module_id: &'a ModuleId,
+    pub interns: &'a Interns,
+}
+
+impl Outline<'_> {
+    /// The module's top-level values with their solved types, with the defs nested inside them
+    /// as their children, along with its type aliases, opaque types and abilities.
+    pub fn document_symbols(
+        &mut self,
+        declarations: &Declarations,
+        ast: Option<&Ast>,
+    ) -> Vec<DocumentSymbol> {
+        let mut collector = DefCollector {
+            outline: self,
+            symbols: Vec::new(),
+        };
+        collector.visit_decls(declarations);
+
+        let mut symbols = collector.symbols;
+
+        if let Some(ast) = ast {
+            symbols.extend(
+                ast.type_defs()
+                    .map(|(type_def, region)| self.type_def(type_def, region)),
+            );
+        }
+
+        symbols.sort_by_key(|symbol| symbol.range.start);
+
+        symbols
+    }
+
+    fn value(
+        &mut self,
+        name: &str,
+        kind: SymbolKind,
+        var: Variable,
+        region: Region,
+        name_region: Region,
+        children: Vec<DocumentSymbol>,
+    ) -> DocumentSymbol {
+        let type_str = format_var_type(var, self.subs, self.module_id, self.interns);
+
+        self.symbol(name, kind, Some(type_str), region, name_region, children)
+    }
+
+    fn type_def(&self, type_def: &TypeDef, region: Region) -> DocumentSymbol {
+        match type_def {
+            TypeDef::Alias { header, ann } => self.symbol(
+                header.name.value,
+                SymbolKind::STRUCT,
+                Some(self.source_of(ann.region)),
+                region,
+                header.name.region,
+                Vec::new(),
+            ),
+            TypeDef::Opaque { header, typ, .. } => self.symbol(
+                header.name.value,
+                SymbolKind::CLASS,
+                Some(self.source_of(typ.region)),
+                region,
+                header.name.region,
+                Vec::new(),
+            ),
+            TypeDef::Ability {
+                header, members, ..
+            } => {
+                let members = members
+                    .iter()
+                    .map(|member| {
+                        self.symbol(
+                            member.name.value.item(),
+                            SymbolKind::METHOD,
+                            Some(self.source_of(member.typ.region)),
+                            Region::span_across(&member.name.region, &member.typ.region),
+                            member.name.region,
+                            Vec::new(),
+                        )
+                    })
+                    .collect();
+
+                self.symbol(
+                    header.name.value,
+                    SymbolKind::INTERFACE,
+                    None,
+                    region,
+                    header.name.region,
+                    members,
+                )
+            }
+        }
+    }
+
+    fn symbol(
+        &self,
+        name: &str,
+        kind: SymbolKind,
+        detail: Option<String>,
+        region: Region,
+        name_region: Region,
+        children: Vec<DocumentSymbol>,
+    ) -> DocumentSymbol {
+        #[allow(deprecated)]
+        DocumentSymbol {
+            name: name.to_string(),
+            detail,
+            kind,
+            tags: None,
+            deprecated: None,
+            range: region.to_range(self.line_info),
+            selection_range: name_region.to_range(self.line_info),
+            children: (!children.is_empty()).then_some(children),
+        }
+    }
+
+    /// The source of a type annotation, on one line.
+    fn source_of(&self, region: Region) -> String {
+        let source = &self.source[region.start().offset as usize..region.end().offset as usize];
+
+        source.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Collects the defs in the part of the module it visits, with the defs nested inside each of
+/// them as their children.
+struct DefCollector<'a, 'b> {
+    outline: &'b mut Outline<'a>,
+    symbols: Vec<DocumentSymbol>,
+}
+
+impl DefCollector<'_, '_> {
+    fn nested_defs(&mut self, visit: impl FnOnce(&mut DefCollector)) -> Vec<DocumentSymbol> {
+        let mut nested = DefCollector {
+            outline: &mut *self.outline,
+            symbols: Vec::new(),
+        };
+        visit(&mut nested);
+
+        nested.symbols
+    }
+}
+
+impl Visitor for DefCollector<'_, '_> {
+    fn visit_decl(&mut self, decl: DeclarationInfo<'_>) {
+        let (loc_symbol, var, kind) = match &decl {
+            DeclarationInfo::Value {
+                loc_symbol,
+                expr_var,
+                ..
+            } => (*loc_symbol, *expr_var, SymbolKind::VARIABLE),
+            DeclarationInfo::Function {
+                loc_symbol,
+                expr_var,
+                ..
+            } => (*loc_symbol, *expr_var, SymbolKind::FUNCTION),
+            DeclarationInfo::Expectation { .. } | DeclarationInfo::Destructure { .. } => return,
+        };
+
+        let region = decl.region();
+        let name = loc_symbol.value.as_str(self.outline.interns);
+        let children = self.nested_defs(|nested| walk_decl(nested, decl));
+        let symbol = self
+            .outline
+            .value(name, kind, var, region, loc_symbol.region, children);
+
+        self.symbols.push(symbol);
+    }
+
+    fn visit_def(&mut self, def: &Def) {
+        let Pattern::Identifier(symbol) = def.loc_pattern.value else {
+            return walk_def(self, def);
+        };
+
+        let kind = match def.loc_expr.value {
+            Expr::Closure(_) => SymbolKind::FUNCTION,
+            _ => SymbolKind::VARIABLE,
+        };
+        let name = symbol.as_str(self.outline.interns);
+        let children = self.nested_defs(|nested| walk_def(nested, def));
+        let symbol = self.outline.value(
+            name,
+            kind,
+            def.expr_var,
+            def.region(),
+            def.loc_pattern.region,
+            children,
+        );
+
+        self.symbols.push(symbol);
+    }
+}
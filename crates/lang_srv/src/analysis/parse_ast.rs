@@ -1,10 +1,11 @@
 use bumpalo::Bump;
 use roc_fmt::Buf;
 use roc_parse::{
-    ast::{Defs, Module},
+    ast::{Collection, Defs, Header, Module, Pattern, Spaced, TypeDef, ValueDef},
+    header::{ExposedName, ImportsEntry},
     parser::SyntaxError,
 };
-use roc_region::all::Loc;
+use roc_region::all::{Loc, Region};
 
 use self::format::FormattedAst;
 
@@ -38,18 +39,105 @@ impl<'a> Ast<'a> {
         })
     }
 
-    pub fn fmt(&self) -> FormattedAst<'a> {
+    pub fn fmt(&self, max_width: Option<usize>) -> FormattedAst<'a> {
         let mut buf = Buf::new_in(self.arena);
+        buf.set_max_width(max_width);
 
         roc_fmt::module::fmt_module(&mut buf, &self.module);
 
-        roc_fmt::def::fmt_defs(&mut buf, &self.defs, 0);
+        roc_fmt::def::fmt_top_level_defs(&mut buf, &self.defs);
 
         buf.fmt_end_of_file();
 
         FormattedAst::new(buf)
     }
 
+    /// The top-level type aliases, opaque types and abilities, with their regions.
+    pub fn type_defs(&self) -> impl Iterator<Item = (&TypeDef<'a>, Region)> {
+        self.defs
+            .defs()
+            .zip(self.defs.regions.iter())
+            .filter_map(|(def, region)| Some((def.ok()?, *region)))
+    }
+
+    /// The region of the top-level def, or ability member, named `name`.
+    pub fn definition_region(&self, name: &str) -> Option<Region> {
+        self.defs
+            .defs()
+            .zip(self.defs.regions.iter())
+            .find_map(|(def, region)| match def {
+                Ok(TypeDef::Alias { header, .. } | TypeDef::Opaque { header, .. }) => {
+                    (header.name.value == name).then_some(*region)
+                }
+                Ok(TypeDef::Ability {
+                    header, members, ..
+                }) => {
+                    if header.name.value == name {
+                        return Some(*region);
+                    }
+
+                    members
+                        .iter()
+                        .find(|member| *member.name.value.item() == name)
+                        .map(|member| member.name.region)
+                }
+                Err(ValueDef::Annotation(pattern, _)) => {
+                    is_identifier(&pattern.value, name).then_some(*region)
+                }
+                Err(
+                    ValueDef::Body(pattern, _)
+                    | ValueDef::AnnotatedBody {
+                        ann_pattern: pattern,
+                        ..
+                    },
+                ) => is_identifier(&pattern.value, name).then_some(*region),
+                Err(_) => None,
+            })
+    }
+
+    /// The regions of `name` in the header: in the exposes (or provides) list, if `exposed_here`,
+    /// and in the lists of values imported from the module named `imported_from`.
+    pub fn header_name_regions(
+        &self,
+        name: &str,
+        exposed_here: bool,
+        imported_from: &str,
+    ) -> Vec<Region> {
+        let (exposes, imports) = match &self.module.header {
+            Header::Interface(header) => (Some(&header.exposes.item), Some(&header.imports.item)),
+            Header::Hosted(header) => (Some(&header.exposes.item), Some(&header.imports.item)),
+            Header::App(header) => (
+                Some(&header.provides.entries),
+                header.imports.as_ref().map(|imports| &imports.item),
+            ),
+            Header::Platform(header) => (Some(&header.provides.item), Some(&header.imports.item)),
+            Header::Package(_) => (None, None),
+        };
+
+        let mut lists = Vec::new();
+
+        if exposed_here {
+            lists.extend(exposes);
+        }
+
+        for entry in imports.iter().flat_map(|imports| imports.items) {
+            match entry.value.item() {
+                ImportsEntry::Module(module_name, exposed)
+                | ImportsEntry::Package(_, module_name, exposed)
+                    if module_name.as_str() == imported_from =>
+                {
+                    lists.push(exposed);
+                }
+                _ => {}
+            }
+        }
+
+        lists
+            .into_iter()
+            .flat_map(|list| named_regions(list, name))
+            .collect()
+    }
+
     pub fn semantic_tokens(&self) -> impl IntoIterator<Item = Loc<Token>> + '_ {
         let header_tokens = self.module.iter_tokens(self.arena);
         let body_tokens = self.defs.iter_tokens(self.arena);
@@ -57,3 +145,23 @@ impl<'a> Ast<'a> {
         header_tokens.into_iter().chain(body_tokens)
     }
 }
+
+fn is_identifier(pattern: &Pattern, name: &str) -> bool {
+    match pattern {
+        Pattern::Identifier(ident) => *ident == name,
+        Pattern::SpaceBefore(pattern, _) | Pattern::SpaceAfter(pattern, _) => {
+            is_identifier(pattern, name)
+        }
+        _ => false,
+    }
+}
+
+fn named_regions<'a>(
+    list: &'a Collection<'a, Loc<Spaced<'a, ExposedName<'a>>>>,
+    name: &'a str,
+) -> impl Iterator<Item = Region> + 'a {
+    list.items
+        .iter()
+        .filter(move |exposed| exposed.value.item().as_str() == name)
+        .map(|exposed| exposed.region)
+}
@@ -0,0 +1,157 @@
+//! Signature help for `textDocument/signatureHelp`: the type of the function being called where
+//! the cursor is, with the argument it's in highlighted.
+use roc_can::{
+    expr::{Declarations, Expr},
+    traverse::{walk_expr, Visitor},
+};
+use roc_collections::VecMap;
+use roc_load::docs::ModuleDocumentation;
+use roc_module::{
+    called_via::{BinOp, CalledVia},
+    symbol::{Interns, ModuleId, Symbol},
+};
+use roc_region::all::{Position, Region};
+use roc_types::subs::{Subs, Variable};
+use tower_lsp::lsp_types::{
+    Documentation, ParameterInformation, ParameterLabel, SignatureHelp, SignatureInformation,
+};
+
+use super::utils::format_var_type;
+
+pub(super) fn signature_help(
+    position: Position,
+    declarations: &Declarations,
+    subs: &mut Subs,
+    module_id: &ModuleId,
+    interns: &Interns,
+    docs: &VecMap<ModuleId, ModuleDocumentation>,
+) -> Option<SignatureHelp> {
+    let mut visitor = CallAtPosition {
+        position,
+        call: None,
+    };
+    visitor.visit_decls(declarations);
+
+    let FoundCall {
+        fn_var,
+        callee,
+        argument_regions,
+    } = visitor.call?;
+
+    let type_str = format_var_type(fn_var, subs, module_id, interns);
+    let label = match callee {
+        Some(symbol) => format!("{} : {type_str}", symbol.as_str(interns)),
+        None => type_str.clone(),
+    };
+    let type_offset = label.len() - type_str.len();
+
+    let parameters: Vec<_> = argument_ranges(&type_str)
+        .into_iter()
+        .map(|(start, end)| {
+            let utf16_offset = |offset: usize| label[..type_offset + offset].encode_utf16().count();
+
+            ParameterInformation {
+                label: ParameterLabel::LabelOffsets([
+                    utf16_offset(start) as u32,
+                    utf16_offset(end) as u32,
+                ]),
+                documentation: None,
+            }
+        })
+        .collect();
+
+    // The argument the cursor is in is the last one which starts before it. Arguments which
+    // are piped in with `|>` come before the function, so this finds them too.
+    let active_argument = argument_regions
+        .iter()
+        .rposition(|region| region.start() <= position)
+        .unwrap_or(0);
+    let active_parameter = active_argument.min(parameters.len().saturating_sub(1));
+
+    let documentation = callee.and_then(|symbol| {
+        let docs = docs.get(&symbol.module_id())?.get_doc_for_symbol(&symbol)?;
+
+        Some(Documentation::String(docs))
+    });
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation,
+            parameters: Some(parameters),
+            active_parameter: None,
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter as u32),
+    })
+}
+
+/// The byte ranges of the arguments' types in a printed function type, like `Str` and `U64` in
+/// `Str, U64 -> List Str`. Empty when the type isn't printed as a function, e.g. for an alias.
+fn argument_ranges(type_str: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, char) in type_str.char_indices() {
+        match char {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                ranges.push((start, index));
+                start = index + ", ".len();
+            }
+            ' ' if depth == 0 && type_str[index..].starts_with(" -> ") => {
+                ranges.push((start, index));
+                return ranges;
+            }
+            _ => {}
+        }
+    }
+
+    Vec::new()
+}
+
+struct FoundCall {
+    fn_var: Variable,
+    callee: Option<Symbol>,
+    argument_regions: Vec<Region>,
+}
+
+/// Finds the innermost call around the position which is written out as one, so not the calls
+/// that operators and string interpolation turn into.
+struct CallAtPosition {
+    position: Position,
+    call: Option<FoundCall>,
+}
+
+impl Visitor for CallAtPosition {
+    fn should_visit(&mut self, region: Region) -> bool {
+        region.contains_pos(self.position)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        if !region.contains_pos(self.position) {
+            return;
+        }
+
+        if let Expr::Call(function, arguments, CalledVia::Space | CalledVia::BinOp(BinOp::Pizza)) =
+            expr
+        {
+            let (fn_var, loc_fn, _, _) = &**function;
+
+            let callee = match loc_fn.value {
+                Expr::Var(symbol, _) | Expr::AbilityMember(symbol, _, _) => Some(symbol),
+                _ => None,
+            };
+
+            self.call = Some(FoundCall {
+                fn_var: *fn_var,
+                callee,
+                argument_regions: arguments.iter().map(|(_, arg)| arg.region).collect(),
+            });
+        }
+
+        walk_expr(self, expr, var);
+    }
+}
@@ -0,0 +1,129 @@
+//! An index of the top-level symbols of every module which has been analyzed, for
+//! `workspace/symbol`.
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{Location, SymbolInformation, SymbolKind, Url};
+
+/// The most symbols a search returns. Editors filter the results further as more of the query
+/// is typed, so there's no need to send every match for a short one.
+const MAX_RESULTS: usize = 128;
+
+#[derive(Debug, Clone)]
+pub(crate) struct IndexedSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub module_name: String,
+    pub type_str: Option<String>,
+    pub location: Location,
+}
+
+impl IndexedSymbol {
+    fn qualified_name(&self) -> String {
+        format!("{}.{}", self.module_name, self.name)
+    }
+
+    #[allow(deprecated)]
+    fn to_symbol_information(&self) -> SymbolInformation {
+        let container_name = match &self.type_str {
+            Some(type_str) => format!("{} : {type_str}", self.qualified_name()),
+            None => self.module_name.clone(),
+        };
+
+        SymbolInformation {
+            name: self.name.clone(),
+            kind: self.kind,
+            tags: None,
+            deprecated: None,
+            location: self.location.clone(),
+            container_name: Some(container_name),
+        }
+    }
+}
+
+/// The symbols of each module, which are replaced a module at a time as they're rebuilt.
+#[derive(Debug, Default)]
+pub(crate) struct SymbolIndex {
+    modules: HashMap<Url, Vec<IndexedSymbol>>,
+}
+
+impl SymbolIndex {
+    pub fn update(&mut self, url: Url, symbols: Vec<IndexedSymbol>) {
+        self.modules.insert(url, symbols);
+    }
+
+    /// The symbols which fuzzily match `query`, best matches first. A query with a `.` in it is
+    /// matched against the qualified name, like `Str.concat`, so it can narrow down the module.
+    pub fn search(&self, query: &str) -> Vec<SymbolInformation> {
+        let query = query.trim();
+        let qualified = query.contains('.');
+
+        let mut matches: Vec<_> = self
+            .modules
+            .values()
+            .flatten()
+            .filter_map(|symbol| {
+                let score = if qualified {
+                    fuzzy_score(query, &symbol.qualified_name())
+                } else {
+                    fuzzy_score(query, &symbol.name)
+                }?;
+
+                Some((score, symbol))
+            })
+            .collect();
+
+        matches.sort_by(|(score_a, a), (score_b, b)| {
+            score_b
+                .cmp(score_a)
+                .then_with(|| a.name.len().cmp(&b.name.len()))
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.module_name.cmp(&b.module_name))
+        });
+
+        matches
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, symbol)| symbol.to_symbol_information())
+            .collect()
+    }
+}
+
+/// How well `query` matches `candidate`, if all of its characters appear in it in order,
+/// ignoring case. Characters which follow the previous match, or start a word, score higher.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut previous_match: Option<usize> = None;
+    let mut previous_char = None;
+    let mut candidate_chars = candidate.chars().enumerate();
+
+    for query_char in query.chars() {
+        loop {
+            let (index, char) = candidate_chars.next()?;
+            let at_word_start = previous_char.map_or(true, |previous: char| {
+                previous == '.' || (char.is_uppercase() && !previous.is_uppercase())
+            });
+            previous_char = Some(char);
+
+            if char.to_lowercase().eq(query_char.to_lowercase()) {
+                score += 1;
+
+                if at_word_start {
+                    score += 2;
+                }
+
+                if previous_match.map_or(false, |previous| previous + 1 == index) {
+                    score += 3;
+                }
+
+                previous_match = Some(index);
+                break;
+            }
+        }
+    }
+
+    if query.to_lowercase() == candidate.to_lowercase() {
+        score += 10;
+    }
+
+    Some(score)
+}
@@ -22,6 +22,7 @@ use tower_lsp::lsp_types::SemanticTokenType;
 
 macro_rules! tokens {
     ($($(#[$meta:meta])* $token:ident => $lsp_token:literal),* $(,)?) => {
+        #[derive(Clone, Copy)]
         pub enum Token {
             $(
                 $(#[$meta])*
@@ -52,7 +53,6 @@ tokens! {
     Ability => "interface",
     #[allow(unused)]
     TypeVariable => "typeParameter",
-    #[allow(unused)]
     Parameter => "parameter",
     Variable => "variable",
     Field => "property",
@@ -291,12 +291,18 @@ impl IterTokens for HostedHeader<'_> {
             imports,
             generates: _,
             generates_with,
+            more_generates,
         } = self;
 
         (name.iter_tokens(arena).into_iter())
             .chain(exposes.item.iter_tokens(arena))
             .chain(imports.item.iter_tokens(arena))
             .chain(generates_with.item.iter_tokens(arena))
+            .chain(
+                more_generates
+                    .iter()
+                    .flat_map(|clause| clause.generates_with.item.iter_tokens(arena)),
+            )
             .collect_in(arena)
     }
 }
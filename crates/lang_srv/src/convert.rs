@@ -76,10 +76,15 @@ pub(crate) mod diag {
     use roc_solve_problem::TypeError;
 
     use roc_problem::Severity;
+    use roc_reporting::diagnostic::{
+        can_problem_related, Diagnostic as RocDiagnostic,
+        DiagnosticSeverity as DiagnosticSeverityKind, TextEdit as RocTextEdit,
+    };
     use roc_reporting::report::RocDocAllocator;
-    use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
-
-    use super::ToRange;
+    use tower_lsp::lsp_types::{
+        Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+        Position, Range, TextEdit, Url,
+    };
 
     pub trait IntoLspSeverity {
         fn into_lsp_severity(self) -> DiagnosticSeverity;
@@ -173,14 +178,16 @@ pub(crate) mod diag {
         pub path: &'a Path,
     }
 
-    impl<'a> IntoLspDiagnostic<'a> for roc_problem::can::Problem {
-        type Feed = ProblemFmt<'a>;
+    /// Problems whose reports can be turned into diagnostics, which carry the fixes the report
+    /// suggests along with its message.
+    pub trait IntoRocDiagnostic<'a> {
+        fn into_roc_diagnostic(self, fmt: &'a ProblemFmt<'a>) -> Option<RocDiagnostic>;
+    }
 
-        fn into_lsp_diagnostic(self, fmt: &'a ProblemFmt<'a>) -> Option<Diagnostic> {
-            let range = self
-                .region()
-                .unwrap_or_else(Region::zero)
-                .to_range(fmt.line_info);
+    impl<'a> IntoRocDiagnostic<'a> for roc_problem::can::Problem {
+        fn into_roc_diagnostic(self, fmt: &'a ProblemFmt<'a>) -> Option<RocDiagnostic> {
+            let region = Some(self.region().unwrap_or_else(Region::zero));
+            let related = can_problem_related(&self, fmt.line_info, fmt.path);
 
             let report = roc_reporting::report::can_problem(
                 fmt.alloc,
@@ -189,33 +196,19 @@ pub(crate) mod diag {
                 self,
             );
 
-            let severity = report.severity.into_lsp_severity();
-
-            let mut msg = String::new();
-            report.render_ci(&mut msg, fmt.alloc);
-
-            Some(Diagnostic {
-                range,
-                severity: Some(severity),
-                code: None,
-                code_description: None,
-                source: None,
-                message: msg,
-                related_information: None,
-                tags: None,
-                data: None,
-            })
+            Some(RocDiagnostic::from_report(
+                report,
+                fmt.alloc,
+                fmt.line_info,
+                region,
+                related,
+            ))
         }
     }
 
-    impl<'a> IntoLspDiagnostic<'a> for TypeError {
-        type Feed = ProblemFmt<'a>;
-
-        fn into_lsp_diagnostic(self, fmt: &'a ProblemFmt<'a>) -> Option<Diagnostic> {
-            let range = self
-                .region()
-                .unwrap_or_else(Region::zero)
-                .to_range(fmt.line_info);
+    impl<'a> IntoRocDiagnostic<'a> for TypeError {
+        fn into_roc_diagnostic(self, fmt: &'a ProblemFmt<'a>) -> Option<RocDiagnostic> {
+            let region = Some(self.region().unwrap_or_else(Region::zero));
 
             let report = roc_reporting::report::type_problem(
                 fmt.alloc,
@@ -224,22 +217,74 @@ pub(crate) mod diag {
                 self,
             )?;
 
-            let severity = report.severity.into_lsp_severity();
+            Some(RocDiagnostic::from_report(
+                report,
+                fmt.alloc,
+                fmt.line_info,
+                region,
+                Vec::new(),
+            ))
+        }
+    }
+
+    impl IntoLspDiagnostic<'_> for RocDiagnostic {
+        type Feed = ();
 
-            let mut msg = String::new();
-            report.render_ci(&mut msg, fmt.alloc);
+        fn into_lsp_diagnostic(self, _feed: &()) -> Option<Diagnostic> {
+            let related_information = self
+                .related
+                .into_iter()
+                .filter_map(|related| {
+                    Some(DiagnosticRelatedInformation {
+                        location: Location {
+                            uri: Url::from_file_path(&related.file).ok()?,
+                            range: to_lsp_range(related.range),
+                        },
+                        message: related.message,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let severity = match self.severity {
+                DiagnosticSeverityKind::Error => DiagnosticSeverity::ERROR,
+                DiagnosticSeverityKind::Warning => DiagnosticSeverity::WARNING,
+            };
 
             Some(Diagnostic {
-                range,
+                range: self.range.map(to_lsp_range).unwrap_or_default(),
                 severity: Some(severity),
-                code: None,
+                code: self.code.map(NumberOrString::String),
                 code_description: None,
                 source: None,
-                message: msg,
-                related_information: None,
+                message: self.message,
+                related_information: if related_information.is_empty() {
+                    None
+                } else {
+                    Some(related_information)
+                },
                 tags: None,
                 data: None,
             })
         }
     }
+
+    pub fn to_lsp_text_edit(edit: RocTextEdit) -> TextEdit {
+        TextEdit {
+            range: to_lsp_range(edit.range),
+            new_text: edit.replacement,
+        }
+    }
+
+    fn to_lsp_range(range: roc_reporting::diagnostic::Range) -> Range {
+        Range {
+            start: Position {
+                line: range.start.line,
+                character: range.start.column,
+            },
+            end: Position {
+                line: range.end.line,
+                character: range.end.column,
+            },
+        }
+    }
 }
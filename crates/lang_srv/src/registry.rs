@@ -1,19 +1,22 @@
 use log::{debug, info, trace};
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
     sync::{Arc, OnceLock},
     time::Duration,
 };
 
+use roc_load::VirtualFiles;
 use tokio::sync::{Mutex, MutexGuard};
 
 use tower_lsp::lsp_types::{
-    CompletionResponse, Diagnostic, GotoDefinitionResponse, Hover, Position, SemanticTokensResult,
-    TextEdit, Url,
+    CodeActionResponse, CompletionResponse, Diagnostic, DocumentSymbolResponse,
+    GotoDefinitionResponse, Hover, Location, Position, Range, SemanticTokensResult, SignatureHelp,
+    SymbolInformation, TextEdit, Url, WorkspaceEdit,
 };
 
-use crate::analysis::{AnalyzedDocument, DocInfo};
+use crate::analysis::{global_analysis, AnalyzedDocument, DocInfo, SymbolIndex};
 
 #[derive(Debug)]
 pub(crate) struct DocumentPair {
@@ -38,12 +41,14 @@ impl DocumentPair {
 #[derive(Debug)]
 pub(crate) struct RegistryConfig {
     pub(crate) latest_document_timeout: Duration,
+    pub(crate) format_max_width: Option<usize>,
 }
 
 impl Default for RegistryConfig {
     fn default() -> Self {
         Self {
             latest_document_timeout: Duration::from_millis(5000),
+            format_max_width: None,
         }
     }
 }
@@ -51,6 +56,7 @@ impl Default for RegistryConfig {
 #[derive(Debug, Default)]
 pub(crate) struct Registry {
     documents: Mutex<HashMap<Url, DocumentPair>>,
+    symbol_index: Mutex<SymbolIndex>,
     config: RegistryConfig,
 }
 
@@ -58,6 +64,7 @@ impl Registry {
     pub(crate) fn new(config: RegistryConfig) -> Self {
         Self {
             documents: Default::default(),
+            symbol_index: Default::default(),
             config,
         }
     }
@@ -113,7 +120,15 @@ impl Registry {
             updating_url.to_string()
         );
 
+        let mut symbol_index = self.symbol_index.lock().await;
+
         for document in analysed_docs {
+            // Only the modules which were checked are reindexed, so the index keeps the last good
+            // symbols of the others.
+            if document.type_checked() {
+                symbol_index.update(document.url().clone(), document.indexed_symbols());
+            }
+
             let document = Arc::new(document);
             Registry::update_document(&mut documents, document, &updating_url);
         }
@@ -139,7 +154,23 @@ impl Registry {
         }
     }
 
-    async fn document_info_by_url(&self, url: &Url) -> Option<DocInfo> {
+    /// The latest sources of the open documents other than the one at `url`, which analysis reads
+    /// instead of the files on disk, so it sees the changes which haven't been saved yet.
+    pub(crate) async fn virtual_files(&self, url: &Url) -> VirtualFiles {
+        self.documents
+            .lock()
+            .await
+            .iter()
+            .filter(|(other_url, _)| *other_url != url)
+            .filter_map(|(other_url, pair)| {
+                let path = other_url.to_file_path().ok()?;
+
+                Some((path, pair.info.source.as_str().into()))
+            })
+            .collect()
+    }
+
+    pub(crate) async fn document_info_by_url(&self, url: &Url) -> Option<DocInfo> {
         self.documents.lock().await.get(url).map(|a| a.info.clone())
     }
 
@@ -181,19 +212,185 @@ impl Registry {
     ) -> Option<GotoDefinitionResponse> {
         let document = self.latest_document_by_url(url).await?;
         let symbol = document.symbol_at(position)?;
+
+        if symbol.is_builtin() {
+            return document.builtin_definition(symbol);
+        }
+
         let def_document_url = document.module_url(symbol.module_id())?;
         let def_document = self.latest_document_by_url(&def_document_url).await?;
         def_document.definition(symbol)
     }
 
+    pub async fn code_actions(&self, url: &Url, range: Range) -> Option<CodeActionResponse> {
+        let document = self.latest_document_by_url(url).await?;
+
+        Some(document.code_actions(range))
+    }
+
+    pub async fn signature_help(&self, url: &Url, position: Position) -> Option<SignatureHelp> {
+        self.latest_document_by_url(url)
+            .await?
+            .signature_help(position)
+    }
+
+    pub async fn document_symbols(&self, url: &Url) -> Option<DocumentSymbolResponse> {
+        self.latest_document_by_url(url).await?.document_symbols()
+    }
+
+    /// The top-level symbols of every module that has been analyzed which match `query`.
+    pub async fn workspace_symbols(&self, query: &str) -> Vec<SymbolInformation> {
+        self.symbol_index.lock().await.search(query)
+    }
+
+    /// The last good analysis of every document other than the one at `url`.
+    async fn other_documents(&self, url: &Url) -> Vec<Arc<AnalyzedDocument>> {
+        self.documents
+            .lock()
+            .await
+            .iter()
+            .filter(|(other_url, _)| *other_url != url)
+            .map(|(_, pair)| pair.last_good_document.clone())
+            .collect()
+    }
+
+    /// Finds everywhere the value at `position` is referred to, in every open document.
+    pub async fn references(
+        &self,
+        url: &Url,
+        position: Position,
+        include_declaration: bool,
+    ) -> Option<Vec<Location>> {
+        let document = self.latest_document_by_url(url).await?;
+        let symbol = document.symbol_at(position)?;
+        let (module_name, ident) = document.symbol_name(symbol)?;
+
+        let mut locations = document.references(symbol, include_declaration);
+
+        for other in self.other_documents(url).await {
+            if let Some(other_symbol) = other.resolve_symbol(&module_name, &ident) {
+                locations.extend(other.references(other_symbol, include_declaration));
+            }
+        }
+
+        Some(locations)
+    }
+
+    /// Analyzes the `.roc` files under `root` which no analysis has reached yet, like the modules
+    /// which import an open module without being imported by it.
+    async fn analyze_unopened_files(&self, root: &Path) -> Vec<Arc<AnalyzedDocument>> {
+        let known: HashSet<Url> = self.documents.lock().await.keys().cloned().collect();
+        let mut analyzed: HashMap<Url, Arc<AnalyzedDocument>> = HashMap::new();
+
+        for path in roc_files(root) {
+            let Ok(url) = Url::from_file_path(&path) else {
+                continue;
+            };
+
+            if known.contains(&url) || analyzed.contains_key(&url) {
+                continue;
+            }
+
+            let Ok(source) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+
+            let virtual_files = self.virtual_files(&url).await;
+            let doc_info = DocInfo::new(url, source, 0);
+            let analysis = || global_analysis(doc_info, virtual_files);
+
+            let Ok(documents) = tokio::task::spawn_blocking(analysis).await else {
+                continue;
+            };
+
+            for document in documents {
+                if !known.contains(document.url()) {
+                    analyzed
+                        .entry(document.url().clone())
+                        .or_insert_with(|| Arc::new(document));
+                }
+            }
+        }
+
+        analyzed.into_values().collect()
+    }
+
+    /// Renames the value at `position` in every module of the workspace which refers to it: the
+    /// documents which have been analyzed, and the other `.roc` files in the same package.
+    pub async fn rename(
+        &self,
+        url: &Url,
+        position: Position,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>, String> {
+        let Some(document) = self.latest_document_by_url(url).await else {
+            return Ok(None);
+        };
+        let Some(symbol) = document.symbol_at(position) else {
+            return Ok(None);
+        };
+        let Some((module_name, ident)) = document.symbol_name(symbol) else {
+            return Ok(None);
+        };
+
+        if symbol.is_builtin() {
+            return Err(format!("`{ident}` is a builtin, so it can't be renamed."));
+        }
+
+        if !is_lowercase_ident(new_name) {
+            return Err(format!("`{new_name}` isn't a valid name for a value."));
+        }
+
+        if new_name == ident {
+            return Ok(None);
+        }
+
+        let mut changes = HashMap::new();
+        changes.insert(url.clone(), document.rename_edits(symbol, new_name)?);
+
+        let mut others = self.other_documents(url).await;
+
+        if !document.is_local(symbol) {
+            if let Some(root) = document.package_root() {
+                others.extend(self.analyze_unopened_files(&root).await);
+            }
+        }
+
+        for other in others {
+            if let Some(other_symbol) = other.resolve_symbol(&module_name, &ident) {
+                let edits = other.rename_edits(other_symbol, new_name)?;
+
+                if !edits.is_empty() {
+                    changes.insert(other.url().clone(), edits);
+                }
+            }
+        }
+
+        Ok(Some(WorkspaceEdit::new(changes)))
+    }
+
     pub async fn formatting(&self, url: &Url) -> Option<Vec<TextEdit>> {
         let document = self.document_info_by_url(url).await?;
-        document.format()
+        document.format(self.config.format_max_width)
     }
 
-    pub async fn semantic_tokens(&self, url: &Url) -> Option<SemanticTokensResult> {
+    pub async fn range_formatting(&self, url: &Url, range: Range) -> Option<Vec<TextEdit>> {
         let document = self.document_info_by_url(url).await?;
-        document.semantic_tokens()
+        document.format_range(range, self.config.format_max_width)
+    }
+
+    /// Uses the latest analysis when the document's source has been analyzed, and the parse alone
+    /// when it hasn't been yet.
+    pub async fn semantic_tokens(&self, url: &Url) -> Option<SemanticTokensResult> {
+        let info = self.document_info_by_url(url).await?;
+
+        let analyzed = self
+            .latest_document_by_url(url)
+            .await
+            .filter(|document| document.doc_info.source == info.source)
+            .and_then(|document| document.semantic_tokens());
+
+        analyzed.or_else(|| info.semantic_tokens())
     }
     pub async fn completion_items(
         &self,
@@ -217,3 +414,42 @@ impl Registry {
         Some(CompletionResponse::Array(completions))
     }
 }
+
+/// The `.roc` files in `dir` and its subdirectories, except for hidden ones.
+fn roc_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return files;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => files.extend(roc_files(&path)),
+            Ok(file_type) if file_type.is_file() && path.extension() == Some("roc".as_ref()) => {
+                files.push(path)
+            }
+            _ => {}
+        }
+    }
+
+    files.sort();
+
+    files
+}
+
+fn is_lowercase_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .map_or(false, |first| first.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric())
+        && !roc_parse::keyword::KEYWORDS.contains(&name)
+}
@@ -53,6 +53,7 @@ impl RocServer {
             latest_document_timeout: Duration::from_millis(
                 read_env_num("ROCLS_LATEST_DOC_TIMEOUT_MS").unwrap_or(5000),
             ),
+            format_max_width: read_env_num("ROCLS_FORMAT_MAX_WIDTH").map(|width| width as usize),
         };
         let config = RocServerConfig {
             debounce_ms: Duration::from_millis(read_env_num("ROCLS_DEBOUNCE_MS").unwrap_or(100)),
@@ -69,6 +70,7 @@ impl RocServer {
             TextDocumentSyncOptions {
                 open_close: Some(true),
                 change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::Supported(true)),
                 ..TextDocumentSyncOptions::default()
             },
         );
@@ -83,6 +85,11 @@ impl RocServer {
                 work_done_progress: None,
             },
         };
+        let document_range_formatting_provider = DocumentRangeFormattingOptions {
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
         let semantic_tokens_provider =
             SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
                 work_done_progress_options: WorkDoneProgressOptions {
@@ -103,13 +110,29 @@ impl RocServer {
                 work_done_progress: None,
             },
         };
+        let signature_help_provider = SignatureHelpOptions {
+            trigger_characters: Some(vec![" ".to_string()]),
+            retrigger_characters: None,
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
         ServerCapabilities {
             text_document_sync: Some(text_document_sync),
             hover_provider: Some(hover_provider),
             definition_provider: Some(OneOf::Right(definition_provider)),
             document_formatting_provider: Some(OneOf::Right(document_formatting_provider)),
+            document_range_formatting_provider: Some(OneOf::Right(
+                document_range_formatting_provider,
+            )),
             semantic_tokens_provider: Some(semantic_tokens_provider),
             completion_provider: Some(completion_provider),
+            signature_help_provider: Some(signature_help_provider),
+            references_provider: Some(OneOf::Left(true)),
+            workspace_symbol_provider: Some(OneOf::Left(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            rename_provider: Some(OneOf::Left(true)),
+            document_symbol_provider: Some(OneOf::Left(true)),
             ..ServerCapabilities::default()
         }
     }
@@ -173,7 +196,10 @@ impl RocServerState {
                 return Err("Not latest version skipping analysis".to_string());
             }
 
-            let results = match tokio::task::spawn_blocking(|| global_analysis(doc_info)).await {
+            let virtual_files = inner_ref.registry.virtual_files(fi).await;
+            let analysis = || global_analysis(doc_info, virtual_files);
+
+            let results = match tokio::task::spawn_blocking(analysis).await {
                 Err(e) => return Err(format!("Document analysis failed. reason:{:?}", e)),
                 Ok(a) => a,
             };
@@ -236,9 +262,22 @@ impl LanguageServer for RocServer {
         self.change(uri, text, version).await;
     }
 
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        let TextDocumentIdentifier { uri } = params.text_document;
+
+        // The modules this one imports may have been saved since it was last checked,
+        // so check it again to update its diagnostics.
+        if let Some(doc_info) = self.state.registry.document_info_by_url(&uri).await {
+            self.change(uri, doc_info.source, doc_info.version).await;
+        }
+    }
+
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let TextDocumentIdentifier { uri } = params.text_document;
-        self.state.close(uri).await;
+        self.state.close(uri.clone()).await;
+
+        // Diagnostics for a closed document would otherwise stay in the editor
+        self.client.publish_diagnostics(uri, vec![], None).await;
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -280,6 +319,106 @@ impl LanguageServer for RocServer {
         .await
     }
 
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let SignatureHelpParams {
+            context: _,
+            text_document_position_params:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            work_done_progress_params: _,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .signature_help(&text_document.uri, position),
+        )
+        .await
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let DocumentSymbolParams {
+            text_document,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.document_symbols(&text_document.uri)).await
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let CodeActionParams {
+            text_document,
+            range,
+            context: _,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.code_actions(&text_document.uri, range)).await
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let ReferenceParams {
+            text_document_position:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            context: ReferenceContext {
+                include_declaration,
+            },
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.references(
+            &text_document.uri,
+            position,
+            include_declaration,
+        ))
+        .await
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let WorkspaceSymbolParams {
+            query,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(async { Some(self.state.registry.workspace_symbols(&query).await) }).await
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let RenameParams {
+            text_document_position:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            new_name,
+            work_done_progress_params: _,
+        } = params;
+
+        let renamed = unwind_async(self.state.registry.rename(
+            &text_document.uri,
+            position,
+            &new_name,
+        ))
+        .await?;
+
+        renamed.map_err(jsonrpc::Error::invalid_params)
+    }
+
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
         let DocumentFormattingParams {
             text_document,
@@ -290,6 +429,25 @@ impl LanguageServer for RocServer {
         unwind_async(self.state.registry.formatting(&text_document.uri)).await
     }
 
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let DocumentRangeFormattingParams {
+            text_document,
+            range,
+            options: _,
+            work_done_progress_params: _,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .range_formatting(&text_document.uri, range),
+        )
+        .await
+    }
+
     async fn semantic_tokens_full(
         &self,
         params: SemanticTokensParams,
@@ -392,6 +550,12 @@ mod tests {
     static INIT: Once = Once::new();
 
     async fn test_setup(doc: String) -> (RocServerState, Url) {
+        let url = Url::parse("file:/Test.roc").unwrap();
+
+        (test_setup_at(url.clone(), doc).await, url)
+    }
+
+    async fn test_setup_at(url: Url, doc: String) -> RocServerState {
         INIT.call_once(|| {
             env_logger::builder()
                 .is_test(true)
@@ -399,12 +563,11 @@ mod tests {
                 .init();
         });
         info!("Doc is:\n{0}", doc);
-        let url = Url::parse("file:/Test.roc").unwrap();
 
         let inner = RocServerState::new(RocServerConfig::default(), Registry::default());
         // setup the file
         inner.change(&url, doc, 0).await.unwrap();
-        (inner, url)
+        inner
     }
 
     /// Runs a basic completion and returns the response
@@ -594,4 +757,543 @@ mod tests {
         "#]]
         .assert_debug_eq(&actual);
     }
+
+    #[tokio::test]
+    async fn test_completion_tags_from_annotation() {
+        let actual = completion_test_labels(
+            indoc! {r"
+            color : [Red, Green, Blue]
+            color = Gr
+              "},
+            "e",
+            Position::new(4, 10),
+        )
+        .await;
+
+        expect![[r#"
+            Some(
+                [
+                    "Green",
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    fn outline_lines(symbols: &[DocumentSymbol], indent: usize, lines: &mut Vec<String>) {
+        for symbol in symbols {
+            let detail = symbol.detail.as_deref().unwrap_or("");
+            lines.push(format!("{}{} : {detail}", " ".repeat(indent), symbol.name));
+
+            outline_lines(symbol.children.as_deref().unwrap_or(&[]), indent + 4, lines);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_document_symbols() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r"
+            Color : [Red, Green]
+
+            main =
+                double = \n -> n * 2
+
+                double 21
+            "};
+        let (inner, url) = test_setup(doc).await;
+
+        let mut actual = Vec::new();
+        if let Some(DocumentSymbolResponse::Nested(symbols)) =
+            inner.registry.document_symbols(&url).await
+        {
+            outline_lines(&symbols, 0, &mut actual);
+        }
+
+        expect![[r#"
+            [
+                "Color : [Red, Green]",
+                "main : Num *",
+                "    double : Num a -> Num a",
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_code_action_from_report_fix() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r"
+            theLength = 1
+
+            main = theLenght
+            "};
+        let (inner, url) = test_setup(doc).await;
+
+        let range = Range::new(Position::new(5, 7), Position::new(5, 16));
+        let actions = inner.registry.code_actions(&url, range).await;
+
+        let actual = actions.and_then(|actions| match actions.into_iter().next()? {
+            CodeActionOrCommand::CodeAction(action) => {
+                let edits = action.edit?.changes?.remove(&url)?;
+                let edits: Vec<_> = edits
+                    .into_iter()
+                    .map(|edit| {
+                        let Range { start, end } = edit.range;
+                        format!(
+                            "{}:{}-{}:{} {}",
+                            start.line, start.character, end.line, end.character, edit.new_text
+                        )
+                    })
+                    .collect();
+
+                Some((action.title, edits))
+            }
+            CodeActionOrCommand::Command(_) => None,
+        });
+
+        expect![[r#"
+            Some(
+                (
+                    "Change `theLenght` to `theLength`",
+                    [
+                        "5:7-5:16 theLength",
+                    ],
+                ),
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_signature_help() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r"
+            add3 : I64, I64, I64 -> I64
+            add3 = \a, b, c -> a + b + c
+
+            main = add3 1 2 3
+            "};
+        let (inner, url) = test_setup(doc).await;
+
+        let help = inner
+            .registry
+            .signature_help(&url, Position::new(6, 14))
+            .await;
+
+        let actual = help.and_then(|help| {
+            let signature = help.signatures.into_iter().next()?;
+            let parameters: Vec<_> = signature
+                .parameters?
+                .into_iter()
+                .filter_map(|parameter| match parameter.label {
+                    ParameterLabel::LabelOffsets([start, end]) => {
+                        Some(signature.label[start as usize..end as usize].to_string())
+                    }
+                    ParameterLabel::Simple(_) => None,
+                })
+                .collect();
+
+            Some((signature.label, parameters, help.active_parameter?))
+        });
+
+        expect![[r#"
+            Some(
+                (
+                    "add3 : I64, I64, I64 -> I64",
+                    [
+                        "I64",
+                        "I64",
+                        "I64",
+                    ],
+                    1,
+                ),
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_builtin_definition() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r#"
+            greet = \name -> Str.concat "Hello, " name
+            "#};
+        let (inner, url) = test_setup(doc).await;
+
+        let definition = inner
+            .registry
+            .goto_definition(&url, Position::new(3, 23))
+            .await;
+        let Some(GotoDefinitionResponse::Scalar(location)) = definition else {
+            panic!("expected one location, got {definition:?}");
+        };
+
+        let path = location.uri.to_file_path().unwrap();
+        let dir = format!("roc_builtins-{}", std::process::id());
+        assert_eq!(path, std::env::temp_dir().join(dir).join("Str.roc"));
+
+        let source = std::fs::read_to_string(&path).unwrap();
+        let line = source.lines().nth(location.range.start.line as usize);
+        assert!(
+            line.is_some_and(|line| line.starts_with("concat")),
+            "{line:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_workspace_symbols() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r#"
+            greeting : Str
+            greeting = "Hello"
+
+            greet = \name -> Str.concat greeting name
+            "#};
+        let (inner, _url) = test_setup(doc).await;
+
+        let symbols = inner.registry.workspace_symbols("gret").await;
+        let actual: Vec<_> = symbols
+            .into_iter()
+            .map(|symbol| {
+                let start = symbol.location.range.start;
+                format!(
+                    "{} {}:{} {}",
+                    symbol.name,
+                    start.line,
+                    start.character,
+                    symbol.container_name.unwrap_or_default()
+                )
+            })
+            .collect();
+
+        expect![[r#"
+            [
+                "greet 6:0 Test.greet : Str -> Str",
+                "greeting 3:0 Test.greeting : Str",
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_references() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r#"
+            greeting : Str
+            greeting = "Hello"
+
+            main = Str.concat greeting greeting
+            "#};
+        let (inner, url) = test_setup(doc).await;
+
+        let mut actual = Vec::new();
+        for include_declaration in [false, true] {
+            let locations = inner
+                .registry
+                .references(&url, Position::new(6, 20), include_declaration)
+                .await
+                .unwrap_or_default();
+
+            let ranges: Vec<_> = locations
+                .into_iter()
+                .map(|location| {
+                    let Range { start, end } = location.range;
+                    format!(
+                        "{}:{}-{}:{}",
+                        start.line, start.character, end.line, end.character
+                    )
+                })
+                .collect();
+
+            actual.push(ranges.join(" "));
+        }
+
+        expect![[r#"
+            [
+                "6:18-6:26 6:27-6:35",
+                "3:0-3:8 4:0-4:8 6:18-6:26 6:27-6:35",
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_semantic_tokens_from_canonicalization() {
+        let doc = DOC_LIT.to_string()
+            + indoc! {r"
+            double = \n -> n * 2
+
+            main = Num.toStr (double 21)
+            "};
+        let (inner, url) = test_setup(doc.clone()).await;
+
+        let Some(SemanticTokensResult::Tokens(tokens)) = inner.registry.semantic_tokens(&url).await
+        else {
+            panic!("no semantic tokens");
+        };
+
+        let lines: Vec<_> = doc.lines().collect();
+        let (mut line, mut start) = (0, 0);
+        let mut actual = Vec::new();
+
+        for token in tokens.data {
+            if token.delta_line > 0 {
+                start = 0;
+            }
+            line += token.delta_line;
+            start += token.delta_start;
+
+            let kind = HIGHLIGHT_TOKENS_LEGEND[token.token_type as usize].as_str();
+            if ["namespace", "function", "parameter", "variable"].contains(&kind) {
+                let (start, end) = (start as usize, (start + token.length) as usize);
+                actual.push(format!("{} {kind}", &lines[line as usize][start..end]));
+            }
+        }
+
+        expect![[r#"
+            [
+                "Test namespace",
+                "double function",
+                "n parameter",
+                "n parameter",
+                "main variable",
+                "Num namespace",
+                "toStr function",
+                "double function",
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    /// Renames the value at `position` in a `Test.roc` whose source is `doc`, in a directory that
+    /// also has the `unopened` files, and returns the edits to each file by name.
+    async fn rename_in_workspace(
+        doc: String,
+        unopened: &[(&str, &str)],
+        position: Position,
+        new_name: &str,
+    ) -> Result<Vec<(String, Vec<String>)>, String> {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().canonicalize().unwrap();
+
+        for (file_name, source) in unopened {
+            std::fs::write(dir_path.join(file_name), source).unwrap();
+        }
+
+        let url = Url::from_file_path(dir_path.join("Test.roc")).unwrap();
+        let inner = test_setup_at(url.clone(), doc).await;
+
+        let edit = inner.registry.rename(&url, position, new_name).await?;
+
+        let mut changes: Vec<_> = edit
+            .and_then(|edit| edit.changes)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(url, edits)| {
+                let path = url.to_file_path().unwrap();
+                let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+
+                let edits = edits
+                    .into_iter()
+                    .map(|edit| {
+                        let Range { start, end } = edit.range;
+                        format!(
+                            "{}:{}-{}:{} {}",
+                            start.line, start.character, end.line, end.character, edit.new_text
+                        )
+                    })
+                    .collect();
+
+                (file_name, edits)
+            })
+            .collect();
+
+        changes.sort();
+
+        Ok(changes)
+    }
+
+    async fn rename_test(
+        doc: &str,
+        position: Position,
+        new_name: &str,
+    ) -> Result<Vec<String>, String> {
+        let changes =
+            rename_in_workspace(DOC_LIT.to_string() + doc, &[], position, new_name).await?;
+
+        Ok(changes
+            .into_iter()
+            .find(|(file_name, _)| file_name == "Test.roc")
+            .map(|(_, edits)| edits)
+            .unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_rename_definition_and_uses() {
+        let actual = rename_test(
+            indoc! {r#"
+            greeting : Str
+            greeting = "Hello"
+
+            main = Str.concat greeting "!"
+            "#},
+            Position::new(6, 20),
+            "message",
+        )
+        .await;
+
+        expect![[r#"
+            Ok(
+                [
+                    "3:0-3:8 message",
+                    "4:0-4:8 message",
+                    "6:18-6:26 message",
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_rename_refuses_collision() {
+        let actual = rename_test(
+            indoc! {r#"
+            greeting = "Hello"
+
+            main = greeting
+            "#},
+            Position::new(5, 9),
+            "main",
+        )
+        .await;
+
+        expect![[r#"
+            Err(
+                "Can't rename `greeting` to `main`, because `main` is already defined in Test.",
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_rename_record_destructure() {
+        let actual = rename_test(
+            indoc! {r#"
+            main =
+                { x } = { x: 1 }
+                x + 1
+            "#},
+            Position::new(5, 4),
+            "y",
+        )
+        .await;
+
+        expect![[r#"
+            Ok(
+                [
+                    "4:6-4:7 x: y",
+                    "5:4-5:5 y",
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_rename_tag_destructure() {
+        let actual = rename_test(
+            indoc! {r#"
+            main =
+                when Ok 1 is
+                    Ok n -> n
+                    Err _ -> 0
+            "#},
+            Position::new(5, 16),
+            "count",
+        )
+        .await;
+
+        expect![[r#"
+            Ok(
+                [
+                    "5:11-5:12 count",
+                    "5:16-5:17 count",
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_rename_keeps_punned_field_name() {
+        let actual = rename_test(
+            indoc! {r#"
+            main =
+                x = 1
+                { x }
+            "#},
+            Position::new(4, 4),
+            "y",
+        )
+        .await;
+
+        expect![[r#"
+            Ok(
+                [
+                    "4:4-4:5 y",
+                    "5:6-5:7 x: y",
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_rename_in_unopened_files() {
+        let actual = rename_in_workspace(
+            indoc! {r#"
+            interface Test
+              exposes [greeting]
+              imports []
+
+            greeting = "Hello"
+            "#}
+            .to_string(),
+            &[(
+                "Other.roc",
+                indoc! {r#"
+                interface Other
+                  exposes [main]
+                  imports [Test.{ greeting }]
+
+                main = greeting
+                "#},
+            )],
+            Position::new(4, 0),
+            "welcome",
+        )
+        .await;
+
+        expect![[r#"
+            Ok(
+                [
+                    (
+                        "Other.roc",
+                        [
+                            "2:18-2:26 welcome",
+                            "4:7-4:15 welcome",
+                        ],
+                    ),
+                    (
+                        "Test.roc",
+                        [
+                            "1:11-1:19 welcome",
+                            "4:0-4:8 welcome",
+                        ],
+                    ),
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
 }
@@ -0,0 +1,356 @@
+//! Attributes the size of a built binary to the functions in it, using its symbol table (or, for
+//! wasm, its name section), so that users can see what makes their binary big.
+use bumpalo::Bump;
+use object::{Object, ObjectSection, ObjectSymbol, SymbolKind};
+use roc_collections::MutMap;
+use roc_module::symbol::ModuleIds;
+use roc_wasm_module::WasmModule;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// How many of the largest functions the report lists by name
+const LARGEST_FUNCTIONS_SHOWN: usize = 40;
+
+/// The files of the zig builtins. Their functions are named after the file they're in,
+/// e.g. `str.RocStr.concat`.
+const ZIG_BUILTIN_FILES: &[&str] = &[
+    "dbg", "dec", "expect", "glue", "hash", "list", "num", "panic", "str", "utils",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Origin {
+    /// A specialization of a function defined in the app, its packages, or the platform's Roc
+    /// code, including derived ability implementations
+    RocFunction,
+    /// A specialization of a function from a builtin module, or one of the zig builtins
+    Builtin,
+    /// The wrappers Roc generates around the functions it exposes to the host, e.g.
+    /// `roc__mainForHost_1_exposed_generic`
+    EffectWrapper,
+    /// Everything else: the host, and the libraries it was linked with
+    Host,
+}
+
+impl Origin {
+    const ALL: [Origin; 4] = [
+        Origin::RocFunction,
+        Origin::Builtin,
+        Origin::EffectWrapper,
+        Origin::Host,
+    ];
+
+    fn description(self) -> &'static str {
+        match self {
+            Origin::RocFunction => "Roc functions",
+            Origin::Builtin => "Builtin specializations",
+            Origin::EffectWrapper => "Generated effect wrappers",
+            Origin::Host => "Host code",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSize {
+    pub name: String,
+    pub origin: Origin,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloatReport {
+    /// The size of the whole file
+    pub total_bytes: u64,
+    /// Largest first
+    pub functions: Vec<FunctionSize>,
+}
+
+impl BloatReport {
+    pub fn bytes_from(&self, origin: Origin) -> u64 {
+        self.functions
+            .iter()
+            .filter(|function| function.origin == origin)
+            .map(|function| function.bytes)
+            .sum()
+    }
+
+    /// Headers, data, and code that no symbol covers (e.g. because the binary was stripped)
+    pub fn unattributed_bytes(&self) -> u64 {
+        let attributed: u64 = self.functions.iter().map(|function| function.bytes).sum();
+
+        self.total_bytes.saturating_sub(attributed)
+    }
+}
+
+impl fmt::Display for BloatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = |bytes: u64| 100.0 * bytes as f64 / self.total_bytes.max(1) as f64;
+
+        writeln!(f, "Total size: {} bytes", self.total_bytes)?;
+        writeln!(f)?;
+
+        for origin in Origin::ALL {
+            let bytes = self.bytes_from(origin);
+            let description = origin.description();
+
+            writeln!(f, "{description:<30}{bytes:>12}{:>8.1}%", percent(bytes))?;
+        }
+
+        let unattributed = self.unattributed_bytes();
+        writeln!(
+            f,
+            "{:<30}{unattributed:>12}{:>8.1}%",
+            "Headers, data, and unnamed code",
+            percent(unattributed)
+        )?;
+
+        if self.functions.is_empty() {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "This binary has no symbols, so I could not tell which functions are in it. Building without --strip keeps them."
+            )?;
+
+            return Ok(());
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Largest functions:")?;
+        writeln!(f)?;
+
+        for function in self.functions.iter().take(LARGEST_FUNCTIONS_SHOWN) {
+            writeln!(
+                f,
+                "{:>12}  {:<26}{}",
+                function.bytes,
+                function.origin.description(),
+                function.name
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Where `roc build --emit-bloat-report` writes the report for the given binary
+pub fn report_path(binary_path: &Path) -> PathBuf {
+    binary_path.with_extension("bloat.txt")
+}
+
+/// Attributes the size of `binary` to the functions in it.
+///
+/// The surgical linker copies the app's code into the host without adding its symbols to the
+/// output, so in that case pass the app object too, and its functions are taken from there.
+pub fn bloat_report(binary: &[u8], app_object: Option<&[u8]>) -> Result<BloatReport, String> {
+    let builtin_modules: Vec<String> = ModuleIds::default()
+        .available_modules()
+        .map(|name| name.as_str().to_string())
+        .filter(|name| !name.starts_with('#'))
+        .collect();
+
+    let mut sizes = function_sizes(binary)?;
+
+    if let Some(app_object) = app_object {
+        sizes.extend(function_sizes(app_object)?);
+    }
+
+    let mut functions: Vec<FunctionSize> = sizes
+        .into_iter()
+        .map(|(name, bytes)| FunctionSize {
+            origin: classify(&name, &builtin_modules),
+            name,
+            bytes,
+        })
+        .collect();
+
+    functions.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(BloatReport {
+        total_bytes: binary.len() as u64,
+        functions,
+    })
+}
+
+fn function_sizes(bytes: &[u8]) -> Result<MutMap<String, u64>, String> {
+    if bytes.starts_with(b"\0asm") {
+        wasm_function_sizes(bytes)
+    } else {
+        native_function_sizes(bytes)
+    }
+}
+
+fn wasm_function_sizes(bytes: &[u8]) -> Result<MutMap<String, u64>, String> {
+    let arena = Bump::new();
+    let module = WasmModule::preload(&arena, bytes, false).map_err(|e| e.message)?;
+
+    let import_count = module.import.function_count() as u32;
+    let offsets = &module.code.function_offsets;
+    let code_end = module.code.bytes.len() as u32;
+
+    let mut sizes = MutMap::default();
+
+    for (fn_index, name) in module.names.function_names.iter() {
+        // Imported functions have no code in this module
+        let Some(code_index) = fn_index.checked_sub(import_count) else {
+            continue;
+        };
+        let code_index = code_index as usize;
+
+        if let Some(start) = offsets.get(code_index) {
+            let end = offsets.get(code_index + 1).copied().unwrap_or(code_end);
+
+            sizes.insert(name.to_string(), (end - start) as u64);
+        }
+    }
+
+    Ok(sizes)
+}
+
+fn native_function_sizes(bytes: &[u8]) -> Result<MutMap<String, u64>, String> {
+    let object = object::File::parse(bytes).map_err(|e| e.to_string())?;
+    let is_macho = matches!(object.format(), object::BinaryFormat::MachO);
+
+    // Mach-O and PE symbols have no size, so take the distance to the next symbol in the section
+    // instead. Symbols with the same address are aliases; only the first one is counted.
+    let mut by_section: MutMap<_, Vec<(u64, u64, &str)>> = MutMap::default();
+
+    for symbol in object.symbols() {
+        if !symbol.is_definition() || symbol.kind() != SymbolKind::Text {
+            continue;
+        }
+
+        let (Some(section_index), Ok(name)) = (symbol.section_index(), symbol.name()) else {
+            continue;
+        };
+
+        // Mach-O prefixes C symbol names with an underscore
+        let name = match name.strip_prefix('_') {
+            Some(stripped) if is_macho => stripped,
+            _ => name,
+        };
+
+        by_section
+            .entry(section_index)
+            .or_default()
+            .push((symbol.address(), symbol.size(), name));
+    }
+
+    let mut sizes = MutMap::default();
+
+    for (section_index, mut symbols) in by_section {
+        let section_end = object
+            .section_by_index(section_index)
+            .map(|section| section.address() + section.size())
+            .map_err(|e| e.to_string())?;
+
+        symbols.sort_unstable();
+        symbols.dedup_by_key(|(address, _, _)| *address);
+
+        for (i, (address, size, name)) in symbols.iter().enumerate() {
+            let next = symbols.get(i + 1).map_or(section_end, |(next, _, _)| *next);
+            let size = if *size > 0 { *size } else { next - address };
+
+            sizes.insert(name.to_string(), size);
+        }
+    }
+
+    Ok(sizes)
+}
+
+/// Tells where a function came from by its name. Roc names its procedures
+/// `{module}_{ident}_{suffix}` (see `func_spec_name` in the LLVM backend and
+/// `LayoutId::to_symbol_string` for the dev backends).
+fn classify(name: &str, builtin_modules: &[String]) -> Origin {
+    if name.starts_with("roc__") {
+        return Origin::EffectWrapper;
+    }
+
+    if let Some((file, _)) = name.split_once('.') {
+        if ZIG_BUILTIN_FILES.contains(&file) || file == "roc_builtins" {
+            return Origin::Builtin;
+        }
+    }
+
+    let Some((module, rest)) = name.split_once('_') else {
+        return Origin::Host;
+    };
+
+    if !rest.contains('_') {
+        return Origin::Host;
+    }
+
+    if builtin_modules.iter().any(|builtin| builtin == module) {
+        return Origin::Builtin;
+    }
+
+    // Module names are capitalized, except for the hardcoded ones like `#UserApp`, and can be
+    // qualified like `Parser.Core`.
+    let last_segment = module.rsplit('.').next().unwrap_or(module);
+
+    if module.starts_with('#') || last_segment.starts_with(|c: char| c.is_ascii_uppercase()) {
+        Origin::RocFunction
+    } else {
+        Origin::Host
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ELF64_DYNHOST: &[u8] = include_bytes!("../dynhost_benchmarks_elf64") as &[_];
+
+    fn builtin_modules() -> Vec<String> {
+        ["Num", "Str", "List", "Dict"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn classify_names() {
+        let builtins = builtin_modules();
+
+        let cases = [
+            ("#UserApp_main_4f1e2a", Origin::RocFunction),
+            ("#Derived_gen_toEncoder_12", Origin::RocFunction),
+            ("Parser.Core_map_3", Origin::RocFunction),
+            ("List_map_a0b1", Origin::Builtin),
+            ("Num_add_7", Origin::Builtin),
+            ("str.RocStr.concat", Origin::Builtin),
+            ("roc_builtins.list.sort_with", Origin::Builtin),
+            ("roc__mainForHost_1_exposed_generic", Origin::EffectWrapper),
+            ("roc__mainForHost_1__Fx_caller", Origin::EffectWrapper),
+            ("roc_fx_putLine", Origin::Host),
+            ("main", Origin::Host),
+            (
+                "_ZN4core3fmt9Formatter3pad17h5b0d0aa1ff5cef42E",
+                Origin::Host,
+            ),
+        ];
+
+        for (name, origin) in cases {
+            assert_eq!(classify(name, &builtins), origin, "{name}");
+        }
+    }
+
+    #[test]
+    fn report_host() {
+        let report = bloat_report(ELF64_DYNHOST, None).unwrap();
+
+        assert_eq!(report.total_bytes, ELF64_DYNHOST.len() as u64);
+
+        let put_line = report
+            .functions
+            .iter()
+            .find(|function| function.name == "roc_fx_putLine")
+            .unwrap();
+
+        assert_eq!(put_line.origin, Origin::Host);
+        assert!(put_line.bytes > 0);
+
+        assert!(report
+            .functions
+            .windows(2)
+            .all(|pair| pair[0].bytes >= pair[1].bytes));
+    }
+}
@@ -5,6 +5,7 @@
 //! practical to use a regular linker.
 use memmap2::{Mmap, MmapMut};
 use object::Object;
+use roc_builtins::bitcode;
 use roc_error_macros::internal_error;
 use roc_load::{EntryPoint, ExecutionMode, ExposedToHost, LoadConfig, Threading};
 use roc_module::symbol::Interns;
@@ -17,6 +18,8 @@ use std::mem;
 use std::path::{Path, PathBuf};
 use target_lexicon::Triple;
 
+pub mod bloat;
+
 mod elf;
 mod macho;
 mod pe;
@@ -34,6 +37,13 @@ pub enum LinkType {
 pub fn supported(link_type: LinkType, target: &Triple) -> bool {
     if let LinkType::Executable = link_type {
         match target {
+            // The surgical linker patches a dynamically linked host, but musl builds are static
+            Triple {
+                operating_system: target_lexicon::OperatingSystem::Linux,
+                environment: target_lexicon::Environment::Musl,
+                ..
+            } => false,
+
             Triple {
                 architecture: target_lexicon::Architecture::X86_64,
                 operating_system: target_lexicon::OperatingSystem::Linux,
@@ -68,7 +78,7 @@ pub fn preprocessed_host_filename(target: &Triple) -> Option<String> {
     roc_target::get_target_triple_str(target).map(|x| format!("{x}.{PRECOMPILED_HOST_EXT}"))
 }
 
-fn metadata_file_name(target: &Triple) -> String {
+pub fn metadata_file_name(target: &Triple) -> String {
     let target_triple_str = get_target_triple_str(target);
 
     format!("metadata_{}.rm", target_triple_str.unwrap_or("unknown"))
@@ -218,6 +228,10 @@ impl ExposedSymbols {
             custom_names.extend([format!("roc__{sym}_{id}_caller")]);
         }
 
+        // lets the host install its own allocator and hooks (see `RocHostAbi` in roc_std).
+        // A host built against another version of this ABI fails to link.
+        custom_names.push(bitcode::SET_HOST_ABI.to_string());
+
         // on windows (PE) binary search is used on the symbols,
         // so they must be in alphabetical order
         custom_names.sort_unstable();
@@ -244,6 +258,10 @@ impl ExposedSymbols {
             }
         }
 
+        // lets the host install its own allocator and hooks (see `RocHostAbi` in roc_std).
+        // A host built against another version of this ABI fails to link.
+        custom_names.push(bitcode::SET_HOST_ABI.to_string());
+
         // on windows (PE) binary search is used on the symbols,
         // so they must be in alphabetical order
         custom_names.sort_unstable();
@@ -13,6 +13,9 @@ use std::path::{Path, PathBuf};
 pub enum RocCacheDir<'a> {
     /// Normal scenario: reading from the user's cache dir on disk
     Persistent(&'a Path),
+    /// For `--offline` builds and vendored packages: like Persistent, but packages that aren't
+    /// in the dir already are reported as unavailable instead of being downloaded.
+    Offline(&'a Path),
     /// For build.rs and tests where we never want to be downloading anything - yell loudly if we try!
     Disallowed,
     /// For tests only; we don't want to write to the real cache during a test!
@@ -20,6 +23,18 @@ pub enum RocCacheDir<'a> {
     Temp(&'a tempfile::TempDir),
 }
 
+impl RocCacheDir<'_> {
+    /// The dir packages get installed into, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            RocCacheDir::Persistent(path) | RocCacheDir::Offline(path) => Some(path),
+            RocCacheDir::Disallowed => None,
+            #[cfg(test)]
+            RocCacheDir::Temp(temp_dir) => Some(temp_dir.path()),
+        }
+    }
+}
+
 // Errors in case NixOS users try to use a dynamically linked platform
 #[cfg(target_os = "linux")]
 fn nixos_error_if_dynamic(url: &str, dest_dir: &Path) {
@@ -175,6 +190,20 @@ pub fn install_package<'a>(
                 }
             }
         }
+        RocCacheDir::Offline(cache_dir) => {
+            let dest_dir = cache_dir.join(cache_subdir).join(content_hash);
+
+            if dest_dir.exists() {
+                #[cfg(target_os = "linux")]
+                {
+                    nixos_error_if_dynamic(url, &dest_dir);
+                }
+
+                Ok((dest_dir, root_module_filename))
+            } else {
+                Err(Problem::NotAvailableOffline)
+            }
+        }
         RocCacheDir::Disallowed => {
             internal_error!(
                 "Tried to download a package ({:?}) via RocCacheDir::Disallowed - which was explicitly used in order to disallow downloading packages in the current context!",
@@ -186,6 +215,75 @@ pub fn install_package<'a>(
     }
 }
 
+/// A package that was downloaded into the cache dir by [install_package].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedPackage {
+    /// Relative to the cache dir, e.g. `example.com/roc-packages/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE`
+    pub path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// List the packages in the cache dir, sorted by path. If the cache dir doesn't exist yet,
+/// then nothing has been downloaded, so this returns an empty list.
+#[cfg(not(target_family = "wasm"))]
+pub fn cached_packages(cache_dir: &Path) -> std::io::Result<Vec<CachedPackage>> {
+    let mut packages = Vec::new();
+
+    if cache_dir.is_dir() {
+        find_cached_packages(cache_dir, cache_dir, &mut packages)?;
+    }
+
+    packages.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(packages)
+}
+
+/// Packages are extracted into a dir named after their hash, somewhere under a subdir
+/// for their URL, e.g. `example.com/roc-packages/<hash>`. The URL subdirs only ever contain
+/// other dirs, so the first dir we find that contains files is the root of a package.
+#[cfg(not(target_family = "wasm"))]
+fn find_cached_packages(
+    cache_dir: &Path,
+    dir: &Path,
+    packages: &mut Vec<CachedPackage>,
+) -> std::io::Result<()> {
+    let mut subdirs = Vec::new();
+    let mut has_files = false;
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            subdirs.push(path);
+        } else {
+            has_files = true;
+        }
+    }
+
+    if has_files && dir != cache_dir {
+        let mut size_bytes = 0;
+
+        for entry in walkdir::WalkDir::new(dir) {
+            let entry = entry?;
+
+            if entry.file_type().is_file() {
+                size_bytes += entry.metadata()?.len();
+            }
+        }
+
+        packages.push(CachedPackage {
+            path: dir.strip_prefix(cache_dir).unwrap_or(dir).to_path_buf(),
+            size_bytes,
+        });
+    } else {
+        for subdir in subdirs {
+            find_cached_packages(cache_dir, &subdir, packages)?;
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(windows)]
 // e.g. the "Roc" in %APPDATA%\\Roc
 const ROC_CACHE_DIR_NAME: &str = "Roc";
@@ -196,7 +294,18 @@ const ROC_CACHE_DIR_NAME: &str = "roc";
 
 /// This looks up environment variables, so it should ideally be called once and then cached!
 ///
-/// Returns a path of the form cache_dir_path.join(ROC_CACHE_DIR_NAME).join("packages")
+/// Returns a path of the form cache_dir_path.join(ROC_CACHE_DIR_NAME).join("packages"),
+/// where cache_dir_path.join(ROC_CACHE_DIR_NAME) is the [roc_cache_root].
+#[cfg(not(target_family = "wasm"))]
+pub fn roc_cache_dir() -> PathBuf {
+    const PACKAGES_DIR_NAME: &str = "packages";
+
+    roc_cache_root().join(PACKAGES_DIR_NAME)
+}
+
+/// This looks up environment variables, so it should ideally be called once and then cached!
+///
+/// Returns a path of the form cache_dir_path.join(ROC_CACHE_DIR_NAME)
 /// where cache_dir_path is:
 /// - The XDG_CACHE_HOME environment varaible, if it's set.
 /// - Otherwise, ~/.cache on UNIX and %APPDATA% on Windows.
@@ -204,21 +313,18 @@ const ROC_CACHE_DIR_NAME: &str = "roc";
 /// ROC_CACHE_DIR_NAME is "roc" on UNIX and "Roc" on Windows.
 ///
 /// So ~/.cache/roc will be typical on UNIX, and %APPDATA%\\Roc will be typical on Windows.
+/// Besides the packages directory, this holds other things roc keeps around, like REPL history.
 ///
-/// Returns None if XDG_CACHE_HOME is not set, and also we can't determine the home directory
+/// Exits the process if XDG_CACHE_HOME is not set, and also we can't determine the home directory
 /// (or if %APPDATA% is missing on Windows) on this system.
 #[cfg(not(target_family = "wasm"))]
-pub fn roc_cache_dir() -> PathBuf {
+pub fn roc_cache_root() -> PathBuf {
     use std::{env, process};
 
-    const PACKAGES_DIR_NAME: &str = "packages";
-
     // Respect XDG, if the system appears to be using it.
     // https://specifications.freedesktop.org/basedir-spec/basedir-spec-latest.html
     match env::var_os("XDG_CACHE_HOME") {
-        Some(xdg_cache_home) => Path::new(&xdg_cache_home)
-            .join(ROC_CACHE_DIR_NAME)
-            .join(PACKAGES_DIR_NAME),
+        Some(xdg_cache_home) => Path::new(&xdg_cache_home).join(ROC_CACHE_DIR_NAME),
         None => {
             #[cfg(windows)]
             {
@@ -228,9 +334,7 @@ pub fn roc_cache_dir() -> PathBuf {
                     // https://learn.microsoft.com/en-us/windows/deployment/usmt/usmt-recognized-environment-variables
                     env::var_os("APPDATA").or_else(|| env::var_os("CSIDL_APPDATA"))
                 {
-                    Path::new(&appdata)
-                        .join(ROC_CACHE_DIR_NAME)
-                        .join(PACKAGES_DIR_NAME)
+                    Path::new(&appdata).join(ROC_CACHE_DIR_NAME)
                 } else {
                     eprintln!("roc needs either the %APPDATA% or else the %XDG_CACHE_HOME% environment variables set. Please set one of these environment variables and re-run roc!");
                     process::exit(1);
@@ -241,10 +345,7 @@ pub fn roc_cache_dir() -> PathBuf {
             {
                 // e.g. $HOME/.cache/roc
                 if let Some(home) = env::var_os("HOME") {
-                    Path::new(&home)
-                        .join(".cache")
-                        .join(ROC_CACHE_DIR_NAME)
-                        .join(PACKAGES_DIR_NAME)
+                    Path::new(&home).join(".cache").join(ROC_CACHE_DIR_NAME)
                 } else {
                     eprintln!("roc needs either the $HOME or else the $XDG_CACHE_HOME environment variables set. Please set one of these environment variables and re-run roc!");
                     process::exit(1);
@@ -260,3 +361,54 @@ pub fn roc_cache_dir() -> PathBuf {
 pub fn roc_cache_dir() -> PathBuf {
     PathBuf::from(".cache").join(ROC_CACHE_DIR_NAME)
 }
+
+/// On WASI the packages go straight into the cache root, see [roc_cache_dir]
+#[cfg(target_family = "wasm")]
+pub fn roc_cache_root() -> PathBuf {
+    roc_cache_dir()
+}
+
+#[test]
+fn cached_packages_are_found_under_url_subdirs() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let package_dir = cache_dir
+        .path()
+        .join("example.com")
+        .join("pkgs")
+        .join("hash");
+
+    fs::create_dir_all(package_dir.join("Sub")).unwrap();
+    fs::write(package_dir.join("main.roc"), "12345").unwrap();
+    fs::write(package_dir.join("Sub").join("Module.roc"), "678").unwrap();
+
+    assert_eq!(
+        cached_packages(cache_dir.path()).unwrap(),
+        vec![CachedPackage {
+            path: Path::new("example.com").join("pkgs").join("hash"),
+            size_bytes: 8,
+        }]
+    );
+}
+
+#[test]
+fn offline_cache_dir_does_not_download() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let url = "https://example.com/pkgs/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar.br";
+
+    assert!(matches!(
+        install_package(RocCacheDir::Offline(cache_dir.path()), url),
+        Err(Problem::NotAvailableOffline)
+    ));
+
+    let package_dir = cache_dir
+        .path()
+        .join("example.com")
+        .join("pkgs")
+        .join("jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE");
+    fs::create_dir_all(&package_dir).unwrap();
+
+    assert_eq!(
+        install_package(RocCacheDir::Offline(cache_dir.path()), url).unwrap(),
+        (package_dir, None)
+    );
+}
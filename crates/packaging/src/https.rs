@@ -241,6 +241,8 @@ pub enum Problem {
     /// The Content-Length header of the response exceeded max_download_bytes
     DownloadTooBig(u64),
     NotFound,
+    /// The package isn't in the cache, and downloading it isn't allowed.
+    NotAvailableOffline,
 }
 
 pub fn download_and_hash(
@@ -0,0 +1,601 @@
+//! A project can list the packages it depends on in a manifest named `roc.json`, which lives in
+//! the app module's directory or in any directory above it:
+//!
+//! ```json
+//! {
+//!     "packages": {
+//!         "cli": {
+//!             "url": "https://github.com/roc-lang/basic-cli/releases/download/0.7.0/bkGby8jb0tmZYsy2hg1E_B2QrCgcSTxdUlHtETwm5m4.tar.br",
+//!             "version": "0.7.0"
+//!         },
+//!         "json": { "version": "^1.2" }
+//!     },
+//!     "releases": {
+//!         "json": {
+//!             "1.2.0": "https://example.com/json/1.2.0/<hash>.tar.br",
+//!             "1.3.1": "https://example.com/json/1.3.1/<hash>.tar.br"
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The app's header can then refer to a package by its name instead of its URL, as in
+//! `packages { pf: "cli" }`. A package is either pinned to a URL, or given a version requirement
+//! which is resolved against the releases listed in the manifest. A package can have a manifest
+//! of its own in the root of its bundle, which lists the packages it depends on in the same way.
+//!
+//! Using a manifest generates a `roc.lock` beside it, which records the exact bundle each name was
+//! resolved to, including the packages the project depends on indirectly. Committing both files
+//! gives everyone working on the project the same set of dependencies.
+//!
+//! `roc vendor` copies all of those bundles into a `vendor` dir beside the manifest. Once that
+//! exists, packages are loaded from there instead of from the cache, and nothing gets downloaded.
+use crate::cache::{self, RocCacheDir};
+use crate::https::{PackageMetadata, Problem, UrlProblem};
+use crate::resolve::{resolve, PackageIndex, Release, Requirement};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE_NAME: &str = "roc.json";
+pub const LOCKFILE_NAME: &str = "roc.lock";
+pub const VENDOR_DIR_NAME: &str = "vendor";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default)]
+    pub packages: BTreeMap<String, PackageSpec>,
+    /// The URL of each known version of a package, by package name.
+    #[serde(default)]
+    pub releases: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// A package as it's listed in the manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackageSpec {
+    /// Pins the package to this bundle. In that case the version, if any, is the bundle's
+    /// version. Otherwise it's a requirement like `^1.2`, and any version will do if it's missing.
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub packages: BTreeMap<String, LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The BLAKE3 hash of the package's bundle, which downloads are verified against.
+    pub content_hash: String,
+}
+
+#[derive(Debug)]
+pub enum ManifestProblem {
+    IoErr(PathBuf, io::Error),
+    InvalidJson(PathBuf, serde_json::Error),
+    InvalidUrl {
+        name: String,
+        url: String,
+        problem: UrlProblem,
+    },
+    InvalidVersion {
+        name: String,
+        version: String,
+        error: semver::Error,
+    },
+    /// A package had to be downloaded to find out what it depends on, which failed.
+    DownloadFailed {
+        name: String,
+        url: String,
+        problem: Problem,
+    },
+    /// The lockfile's hash for a package doesn't match the one in its URL, so one of them was
+    /// edited by hand.
+    LockedHashMismatch {
+        name: String,
+        url: String,
+        locked_hash: String,
+    },
+    /// No version of the package satisfies all of the requirements on it.
+    VersionConflict {
+        name: String,
+        requirements: Vec<Requirement>,
+        available: Vec<Version>,
+        /// Whether the package was also pinned by URL without a version, which can only be
+        /// picked where any version is allowed.
+        unversioned: bool,
+    },
+}
+
+/// Finds the manifest that applies to the modules in the given directory.
+pub fn find_manifest(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(MANIFEST_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// Finds the dir that `roc vendor` copied the packages of the project the given dir is in to.
+pub fn find_vendor_dir(dir: &Path) -> Option<PathBuf> {
+    let vendor_dir = find_manifest(dir)?.with_file_name(VENDOR_DIR_NAME);
+
+    vendor_dir.is_dir().then_some(vendor_dir)
+}
+
+/// Copies the bundle of every package the project depends on into its vendor dir, which is laid
+/// out like the cache dir so that packages can be loaded from it instead. Bundles that were
+/// vendored before are left alone, since their dir names are their content hashes.
+///
+/// Returns the vendor dir and the names of the packages that were copied, or None if the project
+/// doesn't have a manifest.
+pub fn vendor(
+    dir: &Path,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> Result<Option<(PathBuf, Vec<String>)>, ManifestProblem> {
+    let lockfile = match lock_project(dir, roc_cache_dir)? {
+        Some(lockfile) => lockfile,
+        None => return Ok(None),
+    };
+    // lock_project found the manifest, so this does too
+    let vendor_dir = find_manifest(dir).unwrap().with_file_name(VENDOR_DIR_NAME);
+    let mut copied = Vec::new();
+
+    for (name, locked) in lockfile.packages.iter() {
+        let (package_dir, _) =
+            cache::install_package(roc_cache_dir, &locked.url).map_err(|problem| {
+                ManifestProblem::DownloadFailed {
+                    name: name.clone(),
+                    url: locked.url.clone(),
+                    problem,
+                }
+            })?;
+
+        // install_package validated the URL already
+        let metadata = PackageMetadata::try_from(locked.url.as_str()).unwrap();
+        let dest_dir = vendor_dir
+            .join(metadata.cache_subdir)
+            .join(metadata.content_hash);
+
+        if dest_dir.exists() {
+            continue;
+        }
+
+        // Copy into a temporary dir first, so that a copy that fails halfway isn't mistaken for
+        // a vendored bundle the next time.
+        let temp_dir = dest_dir.with_extension("partial");
+        let io_err = |err| ManifestProblem::IoErr(dest_dir.clone(), err);
+
+        std::fs::create_dir_all(&temp_dir).map_err(io_err)?;
+
+        fs_extra::dir::copy(
+            &package_dir,
+            &temp_dir,
+            &fs_extra::dir::CopyOptions {
+                content_only: true,
+                overwrite: true,
+                ..Default::default()
+            },
+        )
+        .map_err(|err| io_err(io::Error::new(io::ErrorKind::Other, err)))?;
+
+        std::fs::rename(&temp_dir, &dest_dir).map_err(io_err)?;
+
+        copied.push(name.clone());
+    }
+
+    Ok(Some((vendor_dir, copied)))
+}
+
+/// Returns the lockfile for the project the given directory is in, or None if it doesn't have
+/// a manifest. The lockfile is generated first if it's missing, and updated if it no longer
+/// satisfies the manifest.
+pub fn lock_project(
+    dir: &Path,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> Result<Option<Lockfile>, ManifestProblem> {
+    let manifest_path = match find_manifest(dir) {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let lockfile_path = manifest_path.with_file_name(LOCKFILE_NAME);
+
+    let manifest = Manifest::read(&manifest_path)?;
+    let previous = Lockfile::read(&lockfile_path)?;
+    let lockfile = Lockfile::update(previous.as_ref(), &manifest, roc_cache_dir)?;
+
+    if previous.as_ref() != Some(&lockfile) {
+        lockfile.write(&lockfile_path)?;
+    }
+
+    Ok(Some(lockfile))
+}
+
+impl Manifest {
+    pub fn read(path: &Path) -> Result<Self, ManifestProblem> {
+        read_json(path)
+    }
+}
+
+impl Lockfile {
+    /// Returns None if there is no lockfile yet.
+    pub fn read(path: &Path) -> Result<Option<Self>, ManifestProblem> {
+        if path.exists() {
+            read_json(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), ManifestProblem> {
+        let mut json = serde_json::to_string_pretty(self)
+            .map_err(|err| ManifestProblem::InvalidJson(path.to_path_buf(), err))?;
+        json.push('\n');
+
+        std::fs::write(path, json).map_err(|err| ManifestProblem::IoErr(path.to_path_buf(), err))
+    }
+
+    /// Keeps the previous lockfile as long as it still satisfies the manifest. Otherwise, all of
+    /// the project's dependencies are resolved again, downloading packages into the cache to
+    /// read their manifests.
+    pub fn update(
+        previous: Option<&Lockfile>,
+        manifest: &Manifest,
+        roc_cache_dir: RocCacheDir<'_>,
+    ) -> Result<Self, ManifestProblem> {
+        if let Some(previous) = previous {
+            previous.verify_hashes()?;
+
+            if previous.satisfies(manifest)? {
+                return Ok(previous.clone());
+            }
+        }
+
+        let mut index = CacheIndex {
+            roc_cache_dir,
+            pinned: BTreeMap::new(),
+            releases: BTreeMap::new(),
+        };
+
+        // The project's own pins replace any other release of the same package.
+        for (name, spec) in manifest.packages.iter() {
+            if let Some(url) = &spec.url {
+                let release = Release {
+                    version: parse_version(name, spec.version.as_deref())?,
+                    url: url.clone(),
+                };
+                index.pinned.insert(name.clone(), release);
+            }
+        }
+
+        let requirements = index
+            .add_manifest(manifest)?
+            .into_iter()
+            .map(|(name, req)| Requirement {
+                name,
+                req,
+                chain: Vec::new(),
+            })
+            .collect();
+
+        let mut packages = BTreeMap::new();
+
+        for (name, release) in resolve(requirements, &mut index)? {
+            let content_hash = content_hash(&name, &release.url)?;
+            let locked = LockedPackage {
+                url: release.url,
+                version: release.version.map(|version| version.to_string()),
+                content_hash,
+            };
+
+            packages.insert(name, locked);
+        }
+
+        Ok(Self { packages })
+    }
+
+    /// The URL of the package with the given name, if the project depends on one.
+    pub fn url(&self, name: &str) -> Option<&str> {
+        self.packages.get(name).map(|locked| locked.url.as_str())
+    }
+
+    fn verify_hashes(&self) -> Result<(), ManifestProblem> {
+        for (name, locked) in self.packages.iter() {
+            if content_hash(name, &locked.url)? != locked.content_hash {
+                return Err(ManifestProblem::LockedHashMismatch {
+                    name: name.clone(),
+                    url: locked.url.clone(),
+                    locked_hash: locked.content_hash.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether every package in the manifest is locked to a release that it allows.
+    fn satisfies(&self, manifest: &Manifest) -> Result<bool, ManifestProblem> {
+        for (name, spec) in manifest.packages.iter() {
+            let locked = match self.packages.get(name) {
+                Some(locked) => locked,
+                None => return Ok(false),
+            };
+
+            let satisfied = match &spec.url {
+                Some(url) => *url == locked.url && spec.version == locked.version,
+                None => {
+                    let req = parse_version_req(name, spec.version.as_deref())?;
+
+                    match parse_version(name, locked.version.as_deref())? {
+                        Some(version) => req.matches(&version),
+                        None => false,
+                    }
+                }
+            };
+
+            if !satisfied {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// Finds releases in the manifests of the project and of the packages it depends on. Packages
+/// are installed into the cache in order to read their manifests.
+struct CacheIndex<'a> {
+    roc_cache_dir: RocCacheDir<'a>,
+    pinned: BTreeMap<String, Release>,
+    releases: BTreeMap<String, Vec<Release>>,
+}
+
+impl CacheIndex<'_> {
+    /// Learns about the releases the manifest lists, and returns its packages' requirements.
+    fn add_manifest(
+        &mut self,
+        manifest: &Manifest,
+    ) -> Result<Vec<(String, VersionReq)>, ManifestProblem> {
+        for (name, versions) in manifest.releases.iter() {
+            for (version, url) in versions.iter() {
+                let release = Release {
+                    version: parse_version(name, Some(version))?,
+                    url: url.clone(),
+                };
+                self.add_release(name, release);
+            }
+        }
+
+        let mut requirements = Vec::with_capacity(manifest.packages.len());
+
+        for (name, spec) in manifest.packages.iter() {
+            let req = match &spec.url {
+                Some(url) => {
+                    let version = parse_version(name, spec.version.as_deref())?;
+                    let req = match &version {
+                        Some(version) => VersionReq::parse(&format!("={version}")).unwrap(),
+                        None => VersionReq::STAR,
+                    };
+
+                    self.add_release(
+                        name,
+                        Release {
+                            version,
+                            url: url.clone(),
+                        },
+                    );
+
+                    req
+                }
+                None => parse_version_req(name, spec.version.as_deref())?,
+            };
+
+            requirements.push((name.clone(), req));
+        }
+
+        Ok(requirements)
+    }
+
+    fn add_release(&mut self, name: &str, release: Release) {
+        let releases = self.releases.entry(name.to_string()).or_default();
+
+        if !releases.contains(&release) {
+            releases.push(release);
+        }
+    }
+}
+
+impl PackageIndex for CacheIndex<'_> {
+    fn releases(&mut self, name: &str) -> Vec<Release> {
+        match self.pinned.get(name) {
+            Some(release) => vec![release.clone()],
+            None => self.releases.get(name).cloned().unwrap_or_default(),
+        }
+    }
+
+    fn dependencies(
+        &mut self,
+        name: &str,
+        release: &Release,
+    ) -> Result<Vec<(String, VersionReq)>, ManifestProblem> {
+        let (package_dir, _) =
+            cache::install_package(self.roc_cache_dir, &release.url).map_err(|problem| {
+                ManifestProblem::DownloadFailed {
+                    name: name.to_string(),
+                    url: release.url.clone(),
+                    problem,
+                }
+            })?;
+
+        let manifest_path = package_dir.join(MANIFEST_FILE_NAME);
+
+        if manifest_path.is_file() {
+            self.add_manifest(&Manifest::read(&manifest_path)?)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+}
+
+fn parse_version(name: &str, version: Option<&str>) -> Result<Option<Version>, ManifestProblem> {
+    version
+        .map(|version| {
+            Version::parse(version).map_err(|error| ManifestProblem::InvalidVersion {
+                name: name.to_string(),
+                version: version.to_string(),
+                error,
+            })
+        })
+        .transpose()
+}
+
+fn parse_version_req(name: &str, req: Option<&str>) -> Result<VersionReq, ManifestProblem> {
+    match req {
+        Some(req) => VersionReq::parse(req).map_err(|error| ManifestProblem::InvalidVersion {
+            name: name.to_string(),
+            version: req.to_string(),
+            error,
+        }),
+        None => Ok(VersionReq::STAR),
+    }
+}
+
+fn content_hash(name: &str, url: &str) -> Result<String, ManifestProblem> {
+    match PackageMetadata::try_from(url) {
+        Ok(metadata) => Ok(metadata.content_hash.to_string()),
+        Err(problem) => Err(ManifestProblem::InvalidUrl {
+            name: name.to_string(),
+            url: url.to_string(),
+            problem,
+        }),
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, ManifestProblem> {
+    let bytes =
+        std::fs::read(path).map_err(|err| ManifestProblem::IoErr(path.to_path_buf(), err))?;
+
+    serde_json::from_slice(&bytes)
+        .map_err(|err| ManifestProblem::InvalidJson(path.to_path_buf(), err))
+}
+
+#[cfg(test)]
+const TEST_URL: &str =
+    "https://example.com/pkgs/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar.br#main.roc";
+
+#[test]
+fn lock_project_generates_lockfile() {
+    let project_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let app_dir = project_dir.path().join("app");
+
+    std::fs::create_dir(&app_dir).unwrap();
+    std::fs::write(
+        project_dir.path().join(MANIFEST_FILE_NAME),
+        format!(r#"{{ "packages": {{ "cli": {{ "url": "{TEST_URL}", "version": "0.7.0" }} }} }}"#),
+    )
+    .unwrap();
+
+    let lockfile = lock_project(&app_dir, RocCacheDir::Temp(&cache_dir))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(lockfile.url("cli"), Some(TEST_URL));
+    assert_eq!(
+        lockfile.packages["cli"].content_hash,
+        "jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE"
+    );
+    assert_eq!(
+        Lockfile::read(&project_dir.path().join(LOCKFILE_NAME)).unwrap(),
+        Some(lockfile)
+    );
+}
+
+#[test]
+fn lockfile_with_edited_hash_is_rejected() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let manifest = Manifest {
+        packages: BTreeMap::from([(
+            "cli".to_string(),
+            PackageSpec {
+                url: Some(TEST_URL.to_string()),
+                version: None,
+            },
+        )]),
+        ..Default::default()
+    };
+
+    let mut lockfile = Lockfile::update(None, &manifest, RocCacheDir::Temp(&cache_dir)).unwrap();
+
+    lockfile.packages.get_mut("cli").unwrap().content_hash = "tampered".to_string();
+
+    assert!(matches!(
+        Lockfile::update(Some(&lockfile), &manifest, RocCacheDir::Temp(&cache_dir)),
+        Err(ManifestProblem::LockedHashMismatch { .. })
+    ));
+}
+
+#[test]
+fn lockfile_is_kept_while_it_satisfies_the_manifest() {
+    let manifest: Manifest =
+        serde_json::from_str(r#"{ "packages": { "json": { "version": "^1.2" } } }"#).unwrap();
+    let mut lockfile = Lockfile {
+        packages: BTreeMap::from([(
+            "json".to_string(),
+            LockedPackage {
+                url: TEST_URL.to_string(),
+                version: Some("1.4.0".to_string()),
+                content_hash: "jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE".to_string(),
+            },
+        )]),
+    };
+
+    assert!(lockfile.satisfies(&manifest).unwrap());
+
+    lockfile.packages.get_mut("json").unwrap().version = Some("2.0.0".to_string());
+
+    assert!(!lockfile.satisfies(&manifest).unwrap());
+}
+
+#[test]
+fn project_without_manifest_has_no_lockfile() {
+    let project_dir = tempfile::tempdir().unwrap();
+
+    assert!(lock_project(project_dir.path(), RocCacheDir::Disallowed)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn vendor_copies_bundles_like_the_cache() {
+    let project_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    std::fs::write(
+        project_dir.path().join(MANIFEST_FILE_NAME),
+        format!(r#"{{ "packages": {{ "cli": {{ "url": "{TEST_URL}" }} }} }}"#),
+    )
+    .unwrap();
+    std::fs::write(cache_dir.path().join("main.roc"), "package").unwrap();
+
+    let (vendor_dir, copied) = vendor(project_dir.path(), RocCacheDir::Temp(&cache_dir))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(copied, vec!["cli".to_string()]);
+    assert_eq!(
+        find_vendor_dir(project_dir.path()),
+        Some(vendor_dir.clone())
+    );
+    assert!(vendor_dir
+        .join("example.com/pkgs/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE/main.roc")
+        .is_file());
+}
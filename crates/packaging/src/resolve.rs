@@ -0,0 +1,403 @@
+//! Picks a version of every package a project depends on, directly or through other packages,
+//! such that every package's version requirements are satisfied at once.
+//!
+//! Versions are picked one package at a time, in order of name, trying the newest release that
+//! satisfies the requirements found so far. When a package turns out to have no such release,
+//! the resolver backtracks and tries older releases of the packages picked before it. If nothing
+//! works, the conflict it ran into first is reported, with the chain of packages that led to
+//! each of the requirements involved.
+use crate::manifest::ManifestProblem;
+use semver::{Version, VersionReq};
+use std::collections::BTreeMap;
+
+/// A bundle of a package that can be depended on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    /// None for bundles that a manifest pinned by URL without saying which version they are.
+    /// There's no version to check, so these only satisfy requirements that allow any version.
+    pub version: Option<Version>,
+    pub url: String,
+}
+
+impl Release {
+    fn satisfies(&self, req: &VersionReq) -> bool {
+        match &self.version {
+            Some(version) => req.matches(version),
+            None => *req == VersionReq::STAR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub name: String,
+    pub req: VersionReq,
+    /// The packages through which the project depends on this one, outermost first. Empty if
+    /// the requirement is the project's own.
+    pub chain: Vec<(String, Option<Version>)>,
+}
+
+/// Where the resolver finds out which releases exist, and what they depend on.
+pub trait PackageIndex {
+    /// Every known release of the package, in any order.
+    fn releases(&mut self, name: &str) -> Vec<Release>;
+
+    /// The names and version requirements of the packages the given release depends on.
+    fn dependencies(
+        &mut self,
+        name: &str,
+        release: &Release,
+    ) -> Result<Vec<(String, VersionReq)>, ManifestProblem>;
+}
+
+/// Returns the release that was picked for every package the requirements lead to.
+pub fn resolve<I: PackageIndex>(
+    requirements: Vec<Requirement>,
+    index: &mut I,
+) -> Result<BTreeMap<String, Release>, ManifestProblem> {
+    let mut resolver = Resolver {
+        index,
+        first_conflict: None,
+    };
+    let mut selected = BTreeMap::new();
+
+    match resolver.resolve(&mut selected, requirements)? {
+        true => Ok(selected),
+        false => Err(resolver
+            .first_conflict
+            .expect("resolution can only fail because of a conflict")),
+    }
+}
+
+struct Resolver<'i, I> {
+    index: &'i mut I,
+    first_conflict: Option<ManifestProblem>,
+}
+
+impl<I: PackageIndex> Resolver<'_, I> {
+    /// Returns false if no combination of releases satisfies the requirements, in which case
+    /// `selected` is left as it was.
+    fn resolve(
+        &mut self,
+        selected: &mut BTreeMap<String, Release>,
+        requirements: Vec<Requirement>,
+    ) -> Result<bool, ManifestProblem> {
+        let name = match requirements
+            .iter()
+            .map(|requirement| &requirement.name)
+            .filter(|name| !selected.contains_key(*name))
+            .min()
+        {
+            Some(name) => name.clone(),
+            None => return Ok(true),
+        };
+        let reqs_on_name: Vec<&Requirement> = requirements
+            .iter()
+            .filter(|requirement| requirement.name == name)
+            .collect();
+
+        let mut releases = self.index.releases(&name);
+
+        releases.sort_by(|a, b| b.version.cmp(&a.version));
+
+        let candidates: Vec<Release> = releases
+            .iter()
+            .filter(|release| {
+                reqs_on_name
+                    .iter()
+                    .all(|requirement| release.satisfies(&requirement.req))
+            })
+            .cloned()
+            .collect();
+
+        if candidates.is_empty() {
+            let available = releases.into_iter().map(|release| release.version);
+            self.conflict(
+                &name,
+                reqs_on_name.into_iter().cloned().collect(),
+                available,
+            );
+
+            return Ok(false);
+        }
+
+        // Later requirements on this package's dependencies are attributed to the shortest of
+        // the chains that led to it.
+        let chain = &reqs_on_name
+            .iter()
+            .min_by_key(|requirement| requirement.chain.len())
+            .unwrap()
+            .chain;
+
+        'candidates: for candidate in candidates {
+            let mut dep_chain = chain.clone();
+            dep_chain.push((name.clone(), candidate.version.clone()));
+
+            let mut next_requirements = requirements.clone();
+
+            for (dep_name, req) in self.index.dependencies(&name, &candidate)? {
+                let dep_requirement = Requirement {
+                    name: dep_name,
+                    req,
+                    chain: dep_chain.clone(),
+                };
+
+                // A dependency that was picked already has to satisfy the new requirement too.
+                if let Some(release) = selected.get(&dep_requirement.name) {
+                    if !release.satisfies(&dep_requirement.req) {
+                        let conflicting = next_requirements
+                            .iter()
+                            .filter(|requirement| requirement.name == dep_requirement.name)
+                            .cloned()
+                            .chain([dep_requirement.clone()])
+                            .collect();
+                        let available = self.index.releases(&dep_requirement.name);
+                        let available = available.into_iter().map(|release| release.version);
+                        self.conflict(&dep_requirement.name, conflicting, available);
+
+                        continue 'candidates;
+                    }
+                }
+
+                next_requirements.push(dep_requirement);
+            }
+
+            selected.insert(name.clone(), candidate);
+
+            if self.resolve(selected, next_requirements)? {
+                return Ok(true);
+            }
+
+            selected.remove(&name);
+        }
+
+        Ok(false)
+    }
+
+    fn conflict(
+        &mut self,
+        name: &str,
+        requirements: Vec<Requirement>,
+        available: impl IntoIterator<Item = Option<Version>>,
+    ) {
+        if self.first_conflict.is_none() {
+            let (versioned, unversioned): (Vec<_>, Vec<_>) =
+                available.into_iter().partition(Option::is_some);
+
+            self.first_conflict = Some(ManifestProblem::VersionConflict {
+                name: name.to_string(),
+                requirements,
+                available: versioned.into_iter().flatten().collect(),
+                unversioned: !unversioned.is_empty(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct TestIndex(BTreeMap<String, Vec<(Option<Version>, Vec<(String, VersionReq)>)>>);
+
+#[cfg(test)]
+impl TestIndex {
+    fn release(mut self, name: &str, version: &str, dependencies: &[(&str, &str)]) -> Self {
+        let dependencies = dependencies
+            .iter()
+            .map(|(name, req)| (name.to_string(), VersionReq::parse(req).unwrap()))
+            .collect();
+
+        self.0
+            .entry(name.to_string())
+            .or_default()
+            .push((Some(Version::parse(version).unwrap()), dependencies));
+
+        self
+    }
+
+    fn unversioned_release(mut self, name: &str) -> Self {
+        self.0
+            .entry(name.to_string())
+            .or_default()
+            .push((None, Vec::new()));
+
+        self
+    }
+}
+
+#[cfg(test)]
+impl PackageIndex for TestIndex {
+    fn releases(&mut self, name: &str) -> Vec<Release> {
+        let releases = self.0.get(name).map(Vec::as_slice).unwrap_or_default();
+
+        releases
+            .iter()
+            .map(|(version, _)| Release {
+                version: version.clone(),
+                url: match version {
+                    Some(version) => format!("https://example.com/{name}/{version}.tar"),
+                    None => format!("https://example.com/{name}/pinned.tar"),
+                },
+            })
+            .collect()
+    }
+
+    fn dependencies(
+        &mut self,
+        name: &str,
+        release: &Release,
+    ) -> Result<Vec<(String, VersionReq)>, ManifestProblem> {
+        let (_, dependencies) = self.0[name]
+            .iter()
+            .find(|(version, _)| *version == release.version)
+            .unwrap();
+
+        Ok(dependencies.clone())
+    }
+}
+
+#[cfg(test)]
+fn root_requirement(name: &str, req: &str) -> Requirement {
+    Requirement {
+        name: name.to_string(),
+        req: VersionReq::parse(req).unwrap(),
+        chain: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+fn resolved_versions(resolved: BTreeMap<String, Release>) -> Vec<(String, String)> {
+    resolved
+        .into_iter()
+        .map(|(name, release)| (name, release.version.unwrap().to_string()))
+        .collect()
+}
+
+#[test]
+fn resolve_picks_newest_compatible_versions() {
+    let mut index = TestIndex::default()
+        .release("cli", "0.7.0", &[("json", "^1.0")])
+        .release("cli", "0.7.2", &[("json", "^1.1")])
+        .release("cli", "0.8.0", &[("json", "^2.0")])
+        .release("json", "1.0.0", &[])
+        .release("json", "1.2.0", &[])
+        .release("json", "2.0.0", &[]);
+
+    let resolved = resolve(vec![root_requirement("cli", "^0.7")], &mut index).unwrap();
+
+    assert_eq!(
+        resolved_versions(resolved),
+        vec![
+            ("cli".to_string(), "0.7.2".to_string()),
+            ("json".to_string(), "1.2.0".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn resolve_backtracks_to_older_releases() {
+    // The newest cli needs a json that the project doesn't allow
+    let mut index = TestIndex::default()
+        .release("cli", "0.7.0", &[("json", "^1.0")])
+        .release("cli", "0.8.0", &[("json", "^2.0")])
+        .release("json", "1.4.0", &[])
+        .release("json", "2.0.0", &[]);
+
+    let resolved = resolve(
+        vec![root_requirement("cli", "*"), root_requirement("json", "^1")],
+        &mut index,
+    )
+    .unwrap();
+
+    assert_eq!(
+        resolved_versions(resolved),
+        vec![
+            ("cli".to_string(), "0.7.0".to_string()),
+            ("json".to_string(), "1.4.0".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn resolve_reports_conflicts_with_requirement_chains() {
+    let mut index = TestIndex::default()
+        .release("cli", "0.7.0", &[("parser", "^0.3")])
+        .release("parser", "0.3.1", &[("json", "^1.0")])
+        .release("json", "1.0.0", &[])
+        .release("json", "2.0.0", &[]);
+
+    let problem = resolve(
+        vec![
+            root_requirement("cli", "^0.7"),
+            root_requirement("json", "^2"),
+        ],
+        &mut index,
+    )
+    .unwrap_err();
+
+    match problem {
+        ManifestProblem::VersionConflict {
+            name,
+            requirements,
+            available,
+            ..
+        } => {
+            let chains: Vec<Vec<String>> = requirements
+                .iter()
+                .map(|requirement| {
+                    requirement
+                        .chain
+                        .iter()
+                        .map(|(name, version)| format!("{name} {}", version.as_ref().unwrap()))
+                        .collect()
+                })
+                .collect();
+
+            assert_eq!(name, "json");
+            assert_eq!(
+                chains,
+                vec![
+                    vec![],
+                    vec!["cli 0.7.0".to_string(), "parser 0.3.1".to_string()]
+                ]
+            );
+            assert_eq!(available.len(), 2);
+        }
+        other => panic!("expected a version conflict, got {other:?}"),
+    }
+}
+
+#[test]
+fn resolve_accepts_unversioned_release_when_any_version_will_do() {
+    let mut index = TestIndex::default()
+        .release("cli", "0.7.0", &[("json", "*")])
+        .unversioned_release("json");
+
+    let resolved = resolve(vec![root_requirement("cli", "^0.7")], &mut index).unwrap();
+
+    assert_eq!(resolved["json"].version, None);
+    assert_eq!(resolved["json"].url, "https://example.com/json/pinned.tar");
+}
+
+#[test]
+fn resolve_rejects_unversioned_release_for_version_requirement() {
+    // Nothing says which version the pinned json is, so it can't be checked against ^1.0
+    let mut index = TestIndex::default()
+        .release("cli", "0.7.0", &[("json", "^1.0")])
+        .unversioned_release("json");
+
+    let problem = resolve(vec![root_requirement("cli", "^0.7")], &mut index).unwrap_err();
+
+    match problem {
+        ManifestProblem::VersionConflict {
+            name,
+            available,
+            unversioned,
+            ..
+        } => {
+            assert_eq!(name, "json");
+            assert!(available.is_empty());
+            assert!(unversioned);
+        }
+        other => panic!("expected a version conflict, got {other:?}"),
+    }
+}
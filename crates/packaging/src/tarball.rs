@@ -1,14 +1,15 @@
 use brotli::enc::BrotliEncoderParams;
 use bumpalo::Bump;
 use flate2::write::GzEncoder;
-use roc_parse::ast::{Header, Module};
-use roc_parse::header::PlatformHeader;
+use roc_parse::ast::{Collection, Header, Module, Spaced, StrLiteral};
+use roc_parse::header::{ImportsEntry, PackageEntry, PlatformHeader};
 use roc_parse::module::parse_header;
 use roc_parse::state::State;
+use roc_region::all::Loc;
 use std::ffi::OsStr;
 use std::fs::File;
 use std::io::{self, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tar;
 use walkdir::WalkDir;
 
@@ -251,6 +252,89 @@ fn add_dot_roc_files<W: Write>(
     Ok(())
 }
 
+/// A package or ingested file that a module header refers to by an absolute path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbsolutePathImport {
+    /// The .roc file whose header contains the path
+    pub module_path: PathBuf,
+    pub imported_path: String,
+}
+
+/// Find the absolute paths in the headers of all the .roc files that would be bundled
+/// along with the given main module. These resolve on the machine that wrote them,
+/// but not for anyone who downloads the bundle, so they have to be relative instead.
+pub fn absolute_path_imports(path_to_main: &Path) -> io::Result<Vec<AbsolutePathImport>> {
+    let root_dir = path_to_main.parent().unwrap_or_else(|| Path::new("."));
+    let arena = Bump::new();
+    let mut buf = Vec::new();
+    let mut found = Vec::new();
+
+    for entry in WalkDir::new(root_dir) {
+        let entry = entry?;
+        let path = entry.path();
+
+        if !path.is_file() || path.extension().and_then(OsStr::to_str) != Some("roc") {
+            continue;
+        }
+
+        {
+            let mut file = File::open(path)?;
+            buf.clear();
+            file.read_to_end(&mut buf)?;
+        }
+
+        let src = arena.alloc_slice_copy(&buf);
+        let module = match parse_header(&arena, State::new(src)) {
+            Ok((module, _)) => module,
+            // Syntax errors get reported when the package is checked, so skip this file here.
+            Err(_) => continue,
+        };
+
+        for imported_path in header_paths(&module.header) {
+            if Path::new(imported_path).is_absolute() {
+                found.push(AbsolutePathImport {
+                    module_path: path.to_path_buf(),
+                    imported_path: imported_path.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+/// The package paths and ingested file paths in a module header.
+fn header_paths<'a>(header: &Header<'a>) -> Vec<&'a str> {
+    type Packages<'a> = Collection<'a, Loc<Spaced<'a, PackageEntry<'a>>>>;
+    type Imports<'a> = Collection<'a, Loc<Spaced<'a, ImportsEntry<'a>>>>;
+
+    let (packages, imports): (Option<&Packages<'a>>, Option<&Imports<'a>>) = match header {
+        Header::Interface(header) => (None, Some(&header.imports.item)),
+        Header::Hosted(header) => (None, Some(&header.imports.item)),
+        Header::App(header) => (
+            header.packages.as_ref().map(|packages| &packages.item),
+            header.imports.as_ref().map(|imports| &imports.item),
+        ),
+        Header::Package(header) => (Some(&header.packages.item), None),
+        Header::Platform(header) => (Some(&header.packages.item), Some(&header.imports.item)),
+    };
+
+    let package_paths = packages
+        .into_iter()
+        .flat_map(|packages| packages.items.iter())
+        .map(|entry| entry.value.item().package_name.value.as_str());
+
+    let ingested_paths = imports
+        .into_iter()
+        .flat_map(|imports| imports.items.iter())
+        .filter_map(|entry| match entry.value.item() {
+            ImportsEntry::IngestedFile(StrLiteral::PlainLine(path), _) => Some(*path),
+            _ => None,
+        });
+
+    package_paths.chain(ingested_paths).collect()
+}
+
 fn read_header<'a>(
     arena: &'a Bump,
     buf: &'a mut Vec<u8>,
@@ -11,7 +11,7 @@ use roc_load::{EntryPoint, MonomorphizedModule};
 use roc_mono::ir::OptLevel;
 use roc_mono::layout::STLayoutInterner;
 use roc_parse::ast::Expr;
-use roc_repl_eval::eval::jit_to_ast;
+use roc_repl_eval::eval::{jit_to_ast, PrintLimits};
 use roc_repl_eval::gen::{format_answer, ReplOutput};
 use roc_repl_eval::{ReplApp, ReplAppMemory};
 use roc_std::RocStr;
@@ -20,10 +20,11 @@ use roc_types::pretty_print::{name_and_print_var, DebugPrint};
 use roc_types::subs::Subs;
 use target_lexicon::Triple;
 
-pub fn eval_llvm(
+pub fn eval_native(
     mut loaded: MonomorphizedModule<'_>,
     target: &Triple,
     opt_level: OptLevel,
+    print_limits: PrintLimits,
 ) -> Option<ReplOutput> {
     let arena = Bump::new();
     let target_info = TargetInfo::from(target);
@@ -54,21 +55,26 @@ pub fn eval_llvm(
 
     let interns = loaded.interns.clone();
 
-    #[cfg(not(all(
+    // The dev backend produces code much faster than LLVM, which matters a lot for repl
+    // latency, so use it wherever it's available. It doesn't support everything LLVM does
+    // yet, though, so if it gives up partway through, fall back to LLVM.
+    #[cfg(all(
         any(target_os = "linux", target_os = "macos"),
         any(target_arch = "x86_64", target_arch = "aarch64")
-    )))]
-    let (lib, main_fn_name, subs, layout_interner) =
-        mono_module_to_dylib_llvm(&arena, target, loaded, opt_level)
-            .expect("we produce a valid Dylib");
+    ))]
+    let opt_dev_lib = mono_module_to_dylib_asm(&arena, target, &mut loaded);
 
-    #[cfg(all(
+    #[cfg(not(all(
         any(target_os = "linux", target_os = "macos"),
         any(target_arch = "x86_64", target_arch = "aarch64")
-    ))]
-    let (lib, main_fn_name, subs, layout_interner) =
-        mono_module_to_dylib_asm(&arena, target, loaded, opt_level)
-            .expect("we produce a valid Dylib");
+    )))]
+    let opt_dev_lib = None;
+
+    let (lib, main_fn_name, subs, layout_interner) = match opt_dev_lib {
+        Some(lib) => (lib, "test_main", loaded.subs, loaded.layout_interner),
+        None => mono_module_to_dylib_llvm(&arena, target, loaded, opt_level)
+            .expect("we produce a valid Dylib"),
+    };
 
     let mut app = CliApp { lib };
 
@@ -82,6 +88,7 @@ pub fn eval_llvm(
         &interns,
         layout_interner.into_global().fork(),
         target_info,
+        print_limits,
     );
 
     let expr_str = format_answer(&arena, expr).to_string();
@@ -171,13 +178,6 @@ impl ReplAppMemory for CliMemory {
     }
 }
 
-#[cfg_attr(
-    all(
-        any(target_os = "linux", target_os = "macos"),
-        any(target_arch = "x86_64", target_arch = "aarch64")
-    ),
-    allow(unused)
-)]
 fn mono_module_to_dylib_llvm<'a>(
     arena: &'a Bump,
     target: &Triple,
@@ -287,9 +287,8 @@ fn mono_module_to_dylib_llvm<'a>(
 fn mono_module_to_dylib_asm<'a>(
     arena: &'a Bump,
     target: &Triple,
-    loaded: MonomorphizedModule<'a>,
-    _opt_level: OptLevel,
-) -> Result<(libloading::Library, &'a str, Subs, STLayoutInterner<'a>), libloading::Error> {
+    loaded: &mut MonomorphizedModule<'a>,
+) -> Option<libloading::Library> {
     // let dir = std::env::temp_dir().join("roc_repl");
     let dir = tempfile::tempdir().unwrap();
 
@@ -297,34 +296,43 @@ fn mono_module_to_dylib_asm<'a>(
 
     let _target_info = TargetInfo::from(target);
 
-    let MonomorphizedModule {
-        module_id,
-        procedures,
-        host_exposed_lambda_sets: _,
-        exposed_to_host,
-        mut interns,
-        subs,
-        mut layout_interner,
-        ..
-    } = loaded;
-
     let lazy_literals = true;
     let env = roc_gen_dev::Env {
         arena,
-        module_id,
-        exposed_to_host: exposed_to_host.top_level_values.keys().copied().collect(),
+        module_id: loaded.module_id,
+        exposed_to_host: loaded
+            .exposed_to_host
+            .top_level_values
+            .keys()
+            .copied()
+            .collect(),
         lazy_literals,
         mode: roc_gen_dev::AssemblyBackendMode::Repl,
     };
 
     let target = target_lexicon::Triple::host();
-    let module_object = roc_gen_dev::build_module(
-        &env,
-        &mut interns,
-        &mut layout_interner,
-        &target,
-        procedures,
-    );
+
+    // The procedures are still needed if we end up falling back to LLVM.
+    let procedures = loaded.procedures.clone();
+
+    // The dev backend panics when it runs into something it doesn't support yet.
+    // That's not a problem for the user, since we can still use LLVM, so don't print it.
+    let panic_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        roc_gen_dev::build_module(
+            &env,
+            &mut loaded.interns,
+            &mut loaded.layout_interner,
+            &target,
+            procedures,
+        )
+    }));
+
+    std::panic::set_hook(panic_hook);
+
+    let module_object = result.ok()?;
 
     let module_out = module_object
         .write()
@@ -359,7 +367,5 @@ fn mono_module_to_dylib_asm<'a>(
     // Load the dylib
     let path = dylib_path.as_path().to_str().unwrap();
 
-    let lib = unsafe { Library::new(path) }?;
-
-    Ok((lib, "test_main", subs, layout_interner))
+    unsafe { Library::new(path) }.ok()
 }
@@ -5,19 +5,22 @@ use bumpalo::Bump;
 use const_format::concatcp;
 use roc_load::MonomorphizedModule;
 use roc_mono::ir::OptLevel;
+use roc_repl_eval::eval::PrintLimits;
 use roc_repl_eval::gen::Problems;
 use roc_repl_ui::colors::{CYAN, END_COL};
 use roc_repl_ui::repl_state::{ReplAction, ReplState};
 use roc_repl_ui::{format_output, is_incomplete, CONT_PROMPT, PROMPT, SHORT_INSTRUCTIONS, TIPS};
 use roc_reporting::report::{ANSI_STYLE_CODES, DEFAULT_PALETTE};
 use roc_target::TargetInfo;
+use rustyline::completion::Completer;
 use rustyline::highlight::{Highlighter, PromptInfo};
 use rustyline::validate::{self, ValidationContext, ValidationResult, Validator};
-use rustyline_derive::{Completer, Helper, Hinter};
+use rustyline_derive::{Helper, Hinter};
 use std::borrow::Cow;
+use std::path::PathBuf;
 use target_lexicon::Triple;
 
-use crate::cli_gen::eval_llvm;
+use crate::cli_gen::eval_native;
 
 pub const WELCOME_MESSAGE: &str = concatcp!(
     "\n  The rockin' ",
@@ -28,7 +31,9 @@ pub const WELCOME_MESSAGE: &str = concatcp!(
     "\n\n"
 );
 
-#[derive(Completer, Helper, Hinter, Default)]
+const HISTORY_FILE_NAME: &str = "repl_history.txt";
+
+#[derive(Helper, Hinter, Default)]
 pub struct ReplHelper {
     validator: InputValidator,
     state: ReplState,
@@ -46,26 +51,47 @@ pub fn main() -> i32 {
     let mut editor = Editor::<ReplHelper>::new();
     let repl_helper = ReplHelper::default();
     editor.set_helper(Some(repl_helper));
+
+    // Up-arrow and Ctrl-R search both work across sessions, because the history is persisted.
+    let history_path = history_path();
+
+    // It's fine if there's no history yet (e.g. this is the first time the repl was run).
+    let _ = editor.load_history(&history_path);
+
+    if let Some(parent) = history_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
     let target = Triple::host();
     let target_info = TargetInfo::from(&target);
     let mut arena = Bump::new();
 
-    loop {
+    let exit_code = loop {
         match editor.readline(PROMPT) {
             Ok(line) => {
                 let line = line.trim();
 
                 editor.add_history_entry(line);
 
+                // Append right away, so the session's history survives a crash or a kill
+                if let Err(err) = editor.append_history(&history_path) {
+                    eprintln!(
+                        "Unable to save the REPL history to {}: {err}",
+                        history_path.display()
+                    );
+                }
+
                 let repl_state = &mut editor
                     .helper_mut()
                     .expect("Editor helper was not set")
                     .state;
 
                 arena.reset();
+                let print_limits = repl_state.print_limits();
+
                 match repl_state.step(&arena, line, target_info, DEFAULT_PALETTE) {
                     ReplAction::Eval { opt_mono, problems } => {
-                        let output = evaluate(opt_mono, problems, &target);
+                        let output = evaluate(opt_mono, problems, &target, print_limits);
                         // If there was no output, don't print a blank line!
                         // (This happens for something like a type annotation.)
                         if !output.is_empty() {
@@ -73,7 +99,7 @@ pub fn main() -> i32 {
                         }
                     }
                     ReplAction::Exit => {
-                        return 0;
+                        break 0;
                     }
                     ReplAction::Help => {
                         println!("{TIPS}");
@@ -87,26 +113,35 @@ pub fn main() -> i32 {
             }
             Err(ReadlineError::Eof) => {
                 // End of input; we're done!
-                return 0;
+                break 0;
             }
             Err(ReadlineError::Interrupted) => {
                 eprintln!("CTRL-C");
-                return 1;
+                break 1;
             }
             Err(err) => {
                 eprintln!("REPL error: {err:?}");
-                return 1;
+                break 1;
             }
         }
-    }
+    };
+
+    exit_code
+}
+
+/// The history lives next to the package cache, e.g. ~/.cache/roc/repl_history.txt
+fn history_path() -> PathBuf {
+    roc_packaging::cache::roc_cache_root().join(HISTORY_FILE_NAME)
 }
 
 pub fn evaluate(
     opt_mono: Option<MonomorphizedModule<'_>>,
     problems: Problems,
     target: &Triple,
+    print_limits: PrintLimits,
 ) -> String {
-    let opt_output = opt_mono.and_then(|mono| eval_llvm(mono, target, OptLevel::Normal));
+    let opt_output =
+        opt_mono.and_then(|mono| eval_native(mono, target, OptLevel::Normal, print_limits));
     format_output(ANSI_STYLE_CODES, opt_output, problems)
 }
 
@@ -123,6 +158,19 @@ impl Validator for InputValidator {
     }
 }
 
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.state.completions(line, pos))
+    }
+}
+
 impl Highlighter for ReplHelper {
     fn has_continuation_prompt(&self) -> bool {
         true
@@ -29,6 +29,34 @@ struct Env<'a, 'env> {
     target_info: TargetInfo,
     interns: &'a Interns,
     layout_cache: LayoutCache<'a>,
+    print_limits: PrintLimits,
+    depth: usize,
+}
+
+/// How much of a value to print before eliding the rest with `…`,
+/// so that e.g. evaluating a 100k-element list doesn't flood the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrintLimits {
+    /// The most elements of any one list to print
+    pub max_list_len: Option<usize>,
+    /// How deeply nested a value can be (e.g. a list in a record in a tag) and still get printed
+    pub max_depth: Option<usize>,
+}
+
+impl PrintLimits {
+    pub const UNLIMITED: Self = PrintLimits {
+        max_list_len: None,
+        max_depth: None,
+    };
+}
+
+impl Default for PrintLimits {
+    fn default() -> Self {
+        PrintLimits {
+            max_list_len: Some(100),
+            max_depth: Some(20),
+        }
+    }
 }
 
 /// JIT execute the given main function, and then wrap its results in an Expr
@@ -50,6 +78,7 @@ pub fn jit_to_ast<'a, A: ReplApp<'a>>(
     interns: &'a Interns,
     layout_interner: TLLayoutInterner<'a>,
     target_info: TargetInfo,
+    print_limits: PrintLimits,
 ) -> Expr<'a> {
     let mut env = Env {
         arena,
@@ -57,6 +86,8 @@ pub fn jit_to_ast<'a, A: ReplApp<'a>>(
         target_info,
         interns,
         layout_cache: LayoutCache::new(layout_interner, target_info),
+        print_limits,
+        depth: 0,
     };
 
     match layout {
@@ -568,6 +599,27 @@ fn addr_to_ast<'a, M: ReplAppMemory>(
     layout: LayoutRepr<'a>,
     when_recursive: WhenRecursive<'a>,
     var: Variable,
+) -> Expr<'a> {
+    if matches!(env.print_limits.max_depth, Some(max_depth) if env.depth >= max_depth) {
+        return Expr::REPL_ELIDED;
+    }
+
+    env.depth += 1;
+
+    let expr = addr_to_ast_help(env, mem, addr, layout, when_recursive, var);
+
+    env.depth -= 1;
+
+    expr
+}
+
+fn addr_to_ast_help<'a, M: ReplAppMemory>(
+    env: &mut Env<'a, '_>,
+    mem: &'a M,
+    addr: usize,
+    layout: LayoutRepr<'a>,
+    when_recursive: WhenRecursive<'a>,
+    var: Variable,
 ) -> Expr<'a> {
     macro_rules! helper {
         ($method: ident, $ty: ty) => {{
@@ -942,10 +994,14 @@ fn list_to_ast<'a, M: ReplAppMemory>(
     };
 
     let arena = env.arena;
-    let mut output = Vec::with_capacity_in(len, arena);
+    let shown_len = match env.print_limits.max_list_len {
+        Some(max_list_len) => len.min(max_list_len),
+        None => len,
+    };
+    let mut output = Vec::with_capacity_in(shown_len + 1, arena);
     let elem_size = env.layout_cache.interner.stack_size(elem_layout) as usize;
 
-    for index in 0..len {
+    for index in 0..shown_len {
         let offset_bytes = index * elem_size;
         let elem_addr = addr + offset_bytes;
         let (newtype_containers, _alias_content, elem_content) =
@@ -967,6 +1023,10 @@ fn list_to_ast<'a, M: ReplAppMemory>(
         output.push(&*arena.alloc(expr));
     }
 
+    if shown_len < len {
+        output.push(&*arena.alloc(Loc::at_zero(Expr::REPL_ELIDED)));
+    }
+
     let output = output.into_bump_slice();
 
     Expr::List(Collection::with_items(output))
@@ -10,7 +10,9 @@ use roc_fmt::annotation::{Newlines, Parens};
 use roc_load::{LoadingProblem, MonomorphizedModule};
 use roc_parse::ast::Expr;
 use roc_region::all::LineInfo;
-use roc_reporting::report::{can_problem, type_problem, RocDocAllocator};
+use roc_reporting::report::{
+    can_problem, to_file_problem_report_string, type_problem, RocDocAllocator,
+};
 use roc_solve::FunctionKind;
 use roc_target::TargetInfo;
 
@@ -45,16 +47,52 @@ impl Problems {
     }
 }
 
+/// The packages and modules which have been imported into a repl session.
+/// These go in the header of the module we generate for each input.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ReplImports {
+    /// Package shorthands and where to find them, e.g. `("json", "https://...")`
+    pub packages: Vec<(String, String)>,
+    /// Entries for the header's `imports` list, e.g. `Foo.Bar` or `json.Decode`
+    pub modules: Vec<String>,
+}
+
+impl ReplImports {
+    pub fn module_header(&self) -> String {
+        let mut buf = String::from("app \"app\"");
+
+        if !self.packages.is_empty() {
+            let packages: Vec<String> = self
+                .packages
+                .iter()
+                .map(|(shorthand, src)| format!("{shorthand}: \"{src}\""))
+                .collect();
+
+            buf.push_str(&format!(" packages {{ {} }}", packages.join(", ")));
+        }
+
+        if !self.modules.is_empty() {
+            buf.push_str(&format!(" imports [{}]", self.modules.join(", ")));
+        }
+
+        buf.push_str(" provides [replOutput] to \"./platform\"\n\n");
+
+        buf
+    }
+}
+
 pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
     arena: &'a Bump,
+    imports: &ReplImports,
     defs: I,
     expr: &str,
     target_info: TargetInfo,
     palette: Palette,
 ) -> (Option<MonomorphizedModule<'a>>, Problems) {
     let filename = PathBuf::from("replfile.roc");
-    let src_dir = PathBuf::from("fake/test/path");
-    let (bytes_before_expr, module_src) = promote_expr_to_module(arena, defs, expr);
+    // Imported modules are looked up relative to wherever the repl was started.
+    let src_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("fake/test/path"));
+    let (bytes_before_expr, module_src) = promote_expr_to_module(arena, imports, defs, expr);
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
         filename,
@@ -67,7 +105,7 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
             render: roc_reporting::report::RenderTarget::ColorTerminal,
             palette,
             threading: Threading::Single,
-            exec_mode: ExecutionMode::Executable,
+            exec_mode: ExecutionMode::Repl,
         },
     );
 
@@ -88,6 +126,18 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
                 },
             );
         }
+        Err(LoadMonomorphizedError::LoadingProblem(LoadingProblem::FileProblem {
+            filename,
+            error,
+        })) => {
+            return (
+                None,
+                Problems {
+                    errors: vec![to_file_problem_report_string(filename, error)],
+                    warnings: Vec::new(),
+                },
+            );
+        }
         Err(e) => {
             todo!("error while loading module: {:?}", e)
         }
@@ -96,6 +146,7 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
     let MonomorphizedModule {
         interns,
         sources,
+        imported_symbols,
         can_problems,
         type_problems,
         ..
@@ -120,7 +171,8 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
         let src_lines: Vec<&str> = src.split('\n').collect();
 
         // Report parsing and canonicalization problems
-        let alloc = RocDocAllocator::new(&src_lines, *home, interns);
+        let alloc = RocDocAllocator::new(&src_lines, *home, interns)
+            .with_imported_symbols(imported_symbols.get(home).map_or(&[], Vec::as_slice));
 
         for problem in can_probs.into_iter() {
             // Filter out all warnings and errors whose regions end before this,
@@ -169,14 +221,15 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
 
 fn promote_expr_to_module<'a, 'i, I: Iterator<Item = &'i str>>(
     arena: &'a Bump,
+    imports: &ReplImports,
     defs: I,
     expr: &str,
 ) -> (usize, &'a str) {
-    const REPL_MODULE_HEADER: &str = "app \"app\" provides [replOutput] to \"./platform\"\n\n";
     const REPL_MODULE_MAIN_DEF: &str = "replOutput =\n";
     const INDENT: &str = "    ";
 
-    let mut buffer = bumpalo::collections::string::String::from_str_in(REPL_MODULE_HEADER, arena);
+    let mut buffer =
+        bumpalo::collections::string::String::from_str_in(&imports.module_header(), arena);
 
     for line in defs {
         // don't indent the defs
@@ -7,7 +7,10 @@ use {
         layout::{GlobalLayoutInterner, LayoutCache, LayoutInterner, Niche},
     },
     roc_parse::ast::Expr,
-    roc_repl_eval::{eval::jit_to_ast, ReplAppMemory},
+    roc_repl_eval::{
+        eval::{jit_to_ast, PrintLimits},
+        ReplAppMemory,
+    },
     roc_target::TargetInfo,
     roc_types::subs::{Subs, Variable},
 };
@@ -15,6 +18,8 @@ use {
 #[cfg(not(windows))]
 mod app;
 #[cfg(not(windows))]
+mod property;
+#[cfg(not(windows))]
 pub mod run;
 
 #[cfg(not(windows))]
@@ -77,6 +82,7 @@ pub fn get_values<'a>(
             interns,
             layout_interner.fork(),
             target_info,
+            PrintLimits::UNLIMITED,
         );
 
         app.offset += layout_cache.interner.stack_size_and_alignment(layout).0 as usize;
@@ -127,7 +133,10 @@ mod test {
             render: RenderTarget::ColorTerminal,
             palette: DEFAULT_PALETTE,
             threading: Threading::Single,
-            exec_mode: ExecutionMode::Test,
+            exec_mode: ExecutionMode::Test {
+                coverage: false,
+                properties: false,
+            },
         };
         let loaded = match roc_load::load_and_monomorphize_from_str(
             arena,
@@ -0,0 +1,454 @@
+//! Generates the arguments that `roc test --properties` calls properties with, and shrinks the
+//! ones that make a property fail into the smallest ones that still do.
+//!
+//! Values are generated from the layouts of the arguments, and written into a buffer in the
+//! memory representation the compiled code expects. Lists and strings that don't fit in their
+//! struct are put after the arguments, with a refcount of zero, so that Roc code never tries to
+//! free or mutate them.
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_module::symbol::Symbol;
+use roc_mono::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr};
+use roc_types::subs::{Content, FlatType, Subs, Variable};
+use roc_types::types::AliasKind;
+
+/// How many times a property is called with generated arguments.
+pub(crate) const RUNS: usize = 100;
+
+/// How many times a property is called while shrinking the arguments it failed for.
+pub(crate) const MAX_SHRINK_RUNS: usize = 1000;
+
+const USIZE: usize = std::mem::size_of::<usize>();
+
+const DEC_ONE: i128 = 1_000_000_000_000_000_000;
+
+/// Characters that strings are made of. Mostly ASCII, with some that take more bytes in UTF-8.
+const CHARS: &[char] = &[
+    'a', 'b', 'c', 'x', 'y', 'z', 'A', 'B', 'Z', '0', '1', '9', ' ', '-', '_', '.', ',', '!', '\n',
+    'é', 'ß', 'Ω', '中', '🙂',
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Int(i128),
+    Float(f64),
+    /// The number of billionths of billionths, like the `Dec` itself.
+    Dec(i128),
+    Bool(bool),
+    Str(String),
+    List(Vec<Value>),
+    Struct(Vec<Value>),
+}
+
+/// Whether arguments of this type can be generated: numbers, `Bool`, `Str`, and lists, records
+/// and tuples of those. Other types can have invariants that arbitrary values would break, like
+/// tag unions whose layout is just an integer.
+pub(crate) fn can_generate(subs: &Subs, var: Variable) -> bool {
+    match subs.get_content_without_compacting(var) {
+        Content::Alias(symbol, _, real_var, kind) => match kind {
+            AliasKind::Structural => can_generate(subs, *real_var),
+            AliasKind::Opaque => *symbol == Symbol::NUM_NUM || *symbol == Symbol::BOOL_BOOL,
+        },
+        Content::RangedNumber(_) => true,
+        Content::Structure(flat_type) => match flat_type {
+            FlatType::Apply(Symbol::STR_STR, _) => true,
+            FlatType::Apply(Symbol::LIST_LIST, args) => {
+                let elem_var = subs.variables[args.start as usize];
+                can_generate(subs, elem_var)
+            }
+            FlatType::Record(fields, ext) => match fields.unsorted_iterator(subs, *ext) {
+                Ok(mut it) => it.all(|(_, field)| can_generate(subs, field.into_inner())),
+                Err(_) => false,
+            },
+            FlatType::Tuple(elems, ext) => match elems.unsorted_iterator(subs, *ext) {
+                Ok(mut it) => it.all(|(_, elem_var)| can_generate(subs, elem_var)),
+                Err(_) => false,
+            },
+            FlatType::EmptyRecord | FlatType::EmptyTuple => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// A small, fast random number generator (splitmix64), so that runs are reproducible.
+pub(crate) struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, or 0 if `n` is 0.
+    fn below(&mut self, n: u64) -> u64 {
+        match n {
+            0 => 0,
+            n => self.next_u64() % n,
+        }
+    }
+
+    fn one_in(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+
+    /// A value of the given layout. The bigger `size` is, the bigger the numbers, strings and
+    /// lists get. Returns `None` if values of the layout can't be generated.
+    pub(crate) fn value<'a>(
+        &mut self,
+        interner: &impl LayoutInterner<'a>,
+        layout: InLayout<'a>,
+        size: u32,
+    ) -> Option<Value> {
+        let value = match interner.get_repr(layout) {
+            LayoutRepr::Builtin(builtin) => match builtin {
+                Builtin::Int(width) => Value::Int(self.int(width, size)),
+                Builtin::Float(width) => Value::Float(self.float(width, size)),
+                Builtin::Decimal => {
+                    let whole = self.int(IntWidth::I64, size);
+                    let fraction = match self.one_in(2) {
+                        true => 0,
+                        false => self.below(1000) as i128 * (DEC_ONE / 1000),
+                    };
+
+                    Value::Dec(whole * DEC_ONE + whole.signum() * fraction)
+                }
+                Builtin::Bool => Value::Bool(self.one_in(2)),
+                Builtin::Str => {
+                    let len = self.below(size as u64 + 1);
+                    let string = (0..len)
+                        .map(|_| CHARS[self.below(CHARS.len() as u64) as usize])
+                        .collect();
+
+                    Value::Str(string)
+                }
+                Builtin::List(elem_layout) => {
+                    let len = self.below(size.min(32) as u64 + 1);
+                    let elems = (0..len)
+                        .map(|_| self.value(interner, elem_layout, size))
+                        .collect::<Option<_>>()?;
+
+                    Value::List(elems)
+                }
+            },
+            LayoutRepr::Struct(field_layouts) => {
+                let fields = field_layouts
+                    .iter()
+                    .map(|field_layout| self.value(interner, *field_layout, size))
+                    .collect::<Option<_>>()?;
+
+                Value::Struct(fields)
+            }
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn int(&mut self, width: IntWidth, size: u32) -> i128 {
+        let (min, max) = int_range(width);
+
+        // Edge cases are where bugs like to hide.
+        if self.one_in(8) {
+            let edges = [0, 1, max, min, min.max(-1)];
+            return edges[self.below(edges.len() as u64) as usize];
+        }
+
+        let bound = (size as i128 + 1).pow(2);
+        let magnitude = self.below(bound as u64 + 1) as i128;
+
+        match self.one_in(2) {
+            true => (-magnitude).max(min),
+            false => magnitude.min(max),
+        }
+    }
+
+    fn float(&mut self, width: FloatWidth, size: u32) -> f64 {
+        if self.one_in(8) {
+            let edges = [0.0, 1.0, -1.0, 0.5, f64::EPSILON];
+            return edges[self.below(edges.len() as u64) as usize];
+        }
+
+        let whole = self.int(IntWidth::I32, size) as f64;
+        let fraction = self.below(1 << 20) as f64 / (1 << 20) as f64;
+        let float = whole + whole.signum() * fraction;
+
+        match width {
+            FloatWidth::F32 => float as f32 as f64,
+            FloatWidth::F64 => float,
+        }
+    }
+}
+
+fn int_range(width: IntWidth) -> (i128, i128) {
+    let bits = width.stack_size() * 8;
+
+    match width {
+        IntWidth::U128 => (0, i128::MAX),
+        IntWidth::I128 => (i128::MIN, i128::MAX),
+        _ if width.is_signed() => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+        _ => (0, (1 << bits) - 1),
+    }
+}
+
+/// Simpler versions of the value, simplest first.
+pub(crate) fn shrink(value: &Value) -> Vec<Value> {
+    let mut candidates = Vec::new();
+
+    match value {
+        Value::Int(0) | Value::Bool(false) => {}
+        Value::Int(n) => {
+            candidates.extend([0, n / 2, n - n.signum()].map(Value::Int));
+        }
+        Value::Float(x) => {
+            if *x != 0.0 {
+                candidates.push(Value::Float(0.0));
+                candidates.push(Value::Float(x.trunc()));
+
+                if x.abs() >= 2.0 {
+                    candidates.push(Value::Float((x / 2.0).trunc()));
+                }
+            }
+        }
+        Value::Dec(0) => {}
+        Value::Dec(d) => {
+            let whole = d / DEC_ONE;
+
+            candidates.extend([0, whole * DEC_ONE, whole / 2 * DEC_ONE].map(Value::Dec));
+
+            if whole != 0 {
+                candidates.push(Value::Dec((whole - whole.signum()) * DEC_ONE));
+            }
+        }
+        Value::Bool(true) => candidates.push(Value::Bool(false)),
+        Value::Str(string) => {
+            let chars: Vec<char> = string.chars().collect();
+
+            candidates.extend(shrink_sequence(&chars, |chars| {
+                Value::Str(chars.iter().collect())
+            }));
+
+            for (index, c) in chars.iter().enumerate() {
+                if *c != 'a' {
+                    let mut simpler = chars.clone();
+                    simpler[index] = 'a';
+                    candidates.push(Value::Str(simpler.into_iter().collect()));
+                }
+            }
+        }
+        Value::List(elems) => {
+            candidates.extend(shrink_sequence(elems, |elems| Value::List(elems.to_vec())));
+            candidates.extend(shrink_each(elems).map(Value::List));
+        }
+        Value::Struct(fields) => {
+            candidates.extend(shrink_each(fields).map(Value::Struct));
+        }
+    }
+
+    candidates.dedup();
+    candidates.retain(|candidate| candidate != value);
+
+    candidates
+}
+
+/// Shorter versions of the sequence: empty, either half, and without each one of its items.
+fn shrink_sequence<T: Clone>(items: &[T], to_value: impl Fn(&[T]) -> Value) -> Vec<Value> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+
+    let half = items.len() / 2;
+    let mut candidates = vec![
+        to_value(&[]),
+        to_value(&items[..half]),
+        to_value(&items[half..]),
+    ];
+
+    if items.len() <= 32 {
+        for index in 0..items.len() {
+            let mut shorter = items.to_vec();
+            shorter.remove(index);
+            candidates.push(to_value(&shorter));
+        }
+    }
+
+    candidates
+}
+
+/// The values with one of them replaced by a simpler version.
+fn shrink_each(values: &[Value]) -> impl Iterator<Item = Vec<Value>> + '_ {
+    values.iter().enumerate().flat_map(move |(index, value)| {
+        shrink(value).into_iter().map(move |simpler| {
+            let mut values = values.to_vec();
+            values[index] = simpler;
+            values
+        })
+    })
+}
+
+/// Values written out in memory, with pointers as offsets from the start of the buffer. That is
+/// how the test runner reads values that the compiled code wrote, so they can be rendered the
+/// same way; [Self::relocate] turns the offsets into real pointers to call the compiled code with.
+pub(crate) struct Encoded {
+    pub(crate) bytes: Vec<u8>,
+    relocations: Vec<usize>,
+}
+
+impl Encoded {
+    /// Writes the values like a struct with one field per value, at the start of the buffer.
+    pub(crate) fn new<'a>(
+        interner: &impl LayoutInterner<'a>,
+        layouts: &[InLayout<'a>],
+        values: &[Value],
+    ) -> Self {
+        let (offsets, size) = field_offsets(interner, layouts);
+
+        let mut encoded = Self {
+            bytes: vec![0; size],
+            relocations: Vec::new(),
+        };
+
+        for ((layout, value), offset) in layouts.iter().zip(values).zip(offsets) {
+            encoded.write(interner, *layout, value, offset);
+        }
+
+        encoded
+    }
+
+    /// A copy of the values that the compiled code can read. The copy must outlive the call.
+    pub(crate) fn relocate(&self) -> Vec<u128> {
+        let mut words = vec![0u128; (self.bytes.len() + 15) / 16];
+
+        let base = words.as_mut_ptr() as *mut u8;
+        let bytes = unsafe { std::slice::from_raw_parts_mut(base, self.bytes.len()) };
+        bytes.copy_from_slice(&self.bytes);
+
+        for at in self.relocations.iter() {
+            let offset = usize::from_ne_bytes(bytes[*at..*at + USIZE].try_into().unwrap());
+            let pointer = base as usize + offset;
+            bytes[*at..*at + USIZE].copy_from_slice(&pointer.to_ne_bytes());
+        }
+
+        words
+    }
+
+    fn write<'a>(
+        &mut self,
+        interner: &impl LayoutInterner<'a>,
+        layout: InLayout<'a>,
+        value: &Value,
+        at: usize,
+    ) {
+        let size = interner.stack_size(layout) as usize;
+
+        match (interner.get_repr(layout), value) {
+            (LayoutRepr::Builtin(Builtin::Int(_)), Value::Int(n)) => {
+                self.bytes[at..at + size].copy_from_slice(&n.to_le_bytes()[..size]);
+            }
+            (LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32)), Value::Float(x)) => {
+                self.bytes[at..at + 4].copy_from_slice(&(*x as f32).to_le_bytes());
+            }
+            (LayoutRepr::Builtin(Builtin::Float(FloatWidth::F64)), Value::Float(x)) => {
+                self.bytes[at..at + 8].copy_from_slice(&x.to_le_bytes());
+            }
+            (LayoutRepr::Builtin(Builtin::Decimal), Value::Dec(d)) => {
+                self.bytes[at..at + 16].copy_from_slice(&d.to_le_bytes());
+            }
+            (LayoutRepr::Builtin(Builtin::Bool), Value::Bool(b)) => {
+                self.bytes[at] = *b as u8;
+            }
+            (LayoutRepr::Builtin(Builtin::Str), Value::Str(string)) => {
+                let bytes = string.as_bytes();
+
+                if bytes.len() < 3 * USIZE {
+                    // a small string: the bytes are stored inline, and the length in the last byte
+                    self.bytes[at..at + bytes.len()].copy_from_slice(bytes);
+                    self.bytes[at + 3 * USIZE - 1] = bytes.len() as u8 | 0b1000_0000;
+                } else {
+                    let data = self.allocate(bytes.len());
+                    self.bytes[data..data + bytes.len()].copy_from_slice(bytes);
+
+                    self.write_triple(at, data, bytes.len());
+                }
+            }
+            (LayoutRepr::Builtin(Builtin::List(elem_layout)), Value::List(elems)) => {
+                // an empty list is all zeroes
+                if !elems.is_empty() {
+                    let stride = interner.stack_size(elem_layout) as usize;
+                    let data = self.allocate(elems.len() * stride);
+
+                    for (index, elem) in elems.iter().enumerate() {
+                        self.write(interner, elem_layout, elem, data + index * stride);
+                    }
+
+                    self.write_triple(at, data, elems.len());
+                }
+            }
+            (LayoutRepr::Struct(field_layouts), Value::Struct(fields)) => {
+                let (offsets, _) = field_offsets(interner, field_layouts);
+
+                for ((field_layout, field), offset) in field_layouts.iter().zip(fields).zip(offsets)
+                {
+                    self.write(interner, *field_layout, field, at + offset);
+                }
+            }
+            (repr, value) => {
+                roc_error_macros::internal_error!("cannot write {value:?} as a {repr:?}")
+            }
+        }
+    }
+
+    /// Writes the pointer, length and capacity of a string or list.
+    fn write_triple(&mut self, at: usize, data: usize, len: usize) {
+        self.relocations.push(at);
+
+        for (index, word) in [data, len, len].into_iter().enumerate() {
+            let at = at + index * USIZE;
+            self.bytes[at..at + USIZE].copy_from_slice(&word.to_ne_bytes());
+        }
+    }
+
+    /// Appends the data of a string or list, after a refcount of zero. Returns its offset.
+    fn allocate(&mut self, size: usize) -> usize {
+        // align the data for any element, with the refcount right before it
+        let start = (self.bytes.len() + USIZE + 15) / 16 * 16;
+
+        self.bytes.resize(start + size, 0);
+
+        start
+    }
+}
+
+/// The offset of each field of a struct, and the size of the struct.
+pub(crate) fn field_offsets<'a>(
+    interner: &impl LayoutInterner<'a>,
+    field_layouts: &[InLayout<'a>],
+) -> (Vec<usize>, usize) {
+    let mut offsets = Vec::with_capacity(field_layouts.len());
+    let mut offset = 0;
+    let mut struct_alignment = 1;
+
+    for field_layout in field_layouts {
+        let (size, alignment) = interner.stack_size_and_alignment(*field_layout);
+        let alignment = (alignment as usize).max(1);
+
+        offset = (offset + alignment - 1) / alignment * alignment;
+        offsets.push(offset);
+        offset += size as usize;
+
+        struct_alignment = struct_alignment.max(alignment);
+    }
+
+    let size = (offset + struct_alignment - 1) / struct_alignment * struct_alignment;
+
+    (offsets, size)
+}
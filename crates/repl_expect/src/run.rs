@@ -21,15 +21,18 @@ use roc_gen_llvm::{
 use roc_load::{Expectations, MonomorphizedModule};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::{
-    ir::OptLevel,
-    layout::{GlobalLayoutInterner, STLayoutInterner},
+    ir::{OptLevel, ProcLayout},
+    layout::{GlobalLayoutInterner, LayoutCache, Niche, STLayoutInterner},
 };
 use roc_region::all::Region;
+use roc_repl_eval::eval::{jit_to_ast, PrintLimits};
 use roc_reporting::{error::expect::Renderer, report::RenderTarget};
 use roc_target::TargetInfo;
 use roc_types::subs::Subs;
 use target_lexicon::Triple;
 
+use crate::property::{self, Encoded, Generator, Value};
+
 pub struct ExpectMemory<'a> {
     ptr: *mut u8,
     length: usize,
@@ -125,6 +128,14 @@ impl<'a> ExpectMemory<'a> {
     }
 }
 
+/// Hands the tests the counters that `roc test --coverage` counts `when` branch executions in.
+/// They are only counted while the buffer is alive, and only in this process.
+pub fn set_coverage_buffer(lib: &libloading::Library, counters: &mut [u64]) {
+    let set_coverage_buffer = run_roc_dylib!(lib, "set_coverage_buffer", (*mut u64, usize), ());
+    let mut result = RocCallResult::default();
+    unsafe { set_coverage_buffer((counters.as_mut_ptr(), counters.len()), &mut result) };
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn run_inline_expects<'a, W: std::io::Write>(
     writer: &mut W,
@@ -234,6 +245,26 @@ pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
         }
     }
 
+    for expect in expects.properties {
+        let result = run_property(
+            writer,
+            render_target,
+            arena,
+            interns,
+            layout_interner,
+            lib,
+            expectations,
+            memory,
+            expect,
+        )?;
+
+        match result {
+            Some(true) => passed += 1,
+            Some(false) => failed += 1,
+            None => {}
+        }
+    }
+
     Ok((failed, passed))
 }
 
@@ -295,6 +326,207 @@ fn run_expect_pure<'a, W: std::io::Write>(
     }
 }
 
+/// Calls a property with generated arguments until its expects fail, and then with simpler
+/// versions of the arguments it failed for. Returns `None` if arguments of its types can't be
+/// generated.
+#[allow(clippy::too_many_arguments)]
+fn run_property<'a, W: std::io::Write>(
+    writer: &mut W,
+    render_target: RenderTarget,
+    arena: &'a Bump,
+    interns: &'a Interns,
+    layout_interner: &GlobalLayoutInterner<'a>,
+    lib: &libloading::Library,
+    expectations: &mut VecMap<ModuleId, Expectations>,
+    shared_memory: &mut ExpectMemory,
+    expect: ToplevelExpect<'_>,
+) -> std::io::Result<Option<bool>> {
+    use roc_gen_llvm::try_run_jit_function;
+
+    // we always run programs as the host
+    let target_info = (&target_lexicon::Triple::host()).into();
+
+    let module_id = expect.symbol.module_id();
+    let data = expectations.get_mut(&module_id).unwrap();
+    let property = data.properties.get(&expect.symbol).unwrap().clone();
+
+    let mut layout_cache = LayoutCache::new(layout_interner.fork(), target_info);
+
+    let layouts: Option<Vec<_>> = property
+        .arguments
+        .iter()
+        .map(|(var, _)| match property::can_generate(&data.subs, *var) {
+            true => layout_cache.from_var(arena, *var, &data.subs).ok(),
+            false => None,
+        })
+        .collect();
+
+    let skip = |writer: &mut W| {
+        writeln!(
+            writer,
+            "Skipping the expects in `{}`, because I can't generate arguments of its types.\n",
+            property.function.as_str(interns)
+        )
+    };
+
+    let Some(layouts) = layouts else {
+        skip(writer)?;
+        return Ok(None);
+    };
+
+    let set_property_args = run_roc_dylib!(lib, "set_property_args", *mut u8, ());
+
+    let run = |args: &Value| {
+        let Value::Struct(args) = args else {
+            internal_error!("the arguments of a property are not a struct");
+        };
+
+        let encoded = Encoded::new(&layout_cache.interner, &layouts, args);
+        let mut relocated = encoded.relocate();
+
+        let mut result = RocCallResult::default();
+        unsafe { set_property_args(relocated.as_mut_ptr().cast(), &mut result) };
+
+        let sequence = ExpectSequence::new(shared_memory.ptr.cast());
+        let result: Result<(), (String, _)> =
+            try_run_jit_function!(lib, expect.name, (), |v: ()| v);
+
+        // the arguments must stay alive until the property returns
+        drop(relocated);
+
+        (result, sequence.count_failures(), encoded)
+    };
+
+    let fails =
+        |(result, failures, _): &(Result<(), _>, usize, Encoded)| result.is_err() || *failures > 0;
+
+    // the same arguments on every run, so that failures can be reproduced
+    let seed = expect
+        .name
+        .bytes()
+        .fold(0xCBF2_9CE4_8422_2325, |hash: u64, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100_0000_01B3)
+        });
+    let mut generator = Generator::new(seed);
+
+    let mut failing = None;
+
+    for size in 0..property::RUNS {
+        let args: Option<Vec<_>> = layouts
+            .iter()
+            .map(|layout| generator.value(&layout_cache.interner, *layout, size as u32))
+            .collect();
+
+        let Some(args) = args else {
+            skip(writer)?;
+            return Ok(None);
+        };
+
+        let args = Value::Struct(args);
+
+        if fails(&run(&args)) {
+            failing = Some((size + 1, args));
+            break;
+        }
+    }
+
+    let Some((runs, mut args)) = failing else {
+        return Ok(Some(true));
+    };
+
+    let mut shrink_runs = 0;
+
+    'shrinking: while shrink_runs < property::MAX_SHRINK_RUNS {
+        for simpler in property::shrink(&args) {
+            shrink_runs += 1;
+
+            if fails(&run(&simpler)) {
+                args = simpler;
+                continue 'shrinking;
+            }
+
+            if shrink_runs == property::MAX_SHRINK_RUNS {
+                break;
+            }
+        }
+
+        break;
+    }
+
+    // run it once more, so the shared memory has the failures of these arguments
+    let (result, failures, encoded) = run(&args);
+
+    let data = expectations.get_mut(&module_id).unwrap();
+    let filename = data.path.to_owned();
+    let source = std::fs::read_to_string(&data.path).unwrap();
+
+    let renderer = Renderer::new(arena, interns, render_target, module_id, filename, &source);
+
+    // the arguments are read the way values of failed expects are, with pointers as offsets
+    let memory: &_ = arena.alloc(crate::app::ExpectMemory {
+        start: arena.alloc_slice_copy(&encoded.bytes).as_ptr(),
+    });
+
+    let (offsets, _) = property::field_offsets(&layout_cache.interner, &layouts);
+
+    let arguments: Vec<_> = property
+        .arguments
+        .iter()
+        .zip(layouts.iter())
+        .zip(offsets)
+        .map(|(((var, name), layout), offset)| {
+            let mut app = crate::app::ExpectReplApp { memory, offset };
+
+            let proc_layout = ProcLayout {
+                arguments: &[],
+                result: *layout,
+                niche: Niche::NONE,
+            };
+
+            let expr = jit_to_ast(
+                arena,
+                &mut app,
+                "expect_repl_main_fn",
+                proc_layout,
+                *var,
+                &data.subs,
+                interns,
+                layout_interner.fork(),
+                target_info,
+                PrintLimits::default(),
+            );
+
+            (*name, *var, expr)
+        })
+        .collect();
+
+    renderer.render_counterexample(writer, &mut data.subs, property.function, &arguments, runs)?;
+
+    if let Err((roc_panic_message, _roc_panic_tag)) = result {
+        renderer.render_panic(writer, &roc_panic_message, expect.region)?;
+    } else {
+        let mut offset = ExpectSequence::START_OFFSET;
+
+        for _ in 0..failures {
+            offset = render_expect_failure(
+                writer,
+                &renderer,
+                arena,
+                Some(expect),
+                expectations,
+                interns,
+                layout_interner,
+                shared_memory.ptr.cast(),
+                offset,
+            )?;
+        }
+    }
+
+    writeln!(writer)?;
+
+    Ok(Some(false))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn run_expect_fx<'a, W: std::io::Write>(
     writer: &mut W,
@@ -609,6 +841,8 @@ pub struct ToplevelExpect<'a> {
 pub struct ExpectFunctions<'a> {
     pub pure: BumpVec<'a, ToplevelExpect<'a>>,
     pub fx: BumpVec<'a, ToplevelExpect<'a>>,
+    /// The thunks that call properties with generated arguments.
+    pub properties: BumpVec<'a, ToplevelExpect<'a>>,
 }
 
 pub fn expect_mono_module_to_dylib<'a>(
@@ -676,7 +910,8 @@ pub fn expect_mono_module_to_dylib<'a>(
                         .pure
                         .keys()
                         .copied()
-                        .chain(expects.fx.keys().copied()),
+                        .chain(expects.fx.keys().copied())
+                        .chain(expects.properties.keys().copied()),
                     env.arena,
                 ),
             )
@@ -696,6 +931,23 @@ pub fn expect_mono_module_to_dylib<'a>(
     for (module_id, expects) in toplevel_expects.into_iter() {
         let expect_names = expect_names.get(&module_id).unwrap();
 
+        let expects_properties = bumpalo::collections::Vec::from_iter_in(
+            expects
+                .properties
+                .into_iter()
+                .zip(
+                    expect_names
+                        .iter()
+                        .skip(expects.pure.len() + expects.fx.len()),
+                )
+                .map(|((symbol, region), name)| ToplevelExpect {
+                    symbol,
+                    region,
+                    name,
+                }),
+            env.arena,
+        );
+
         let expects_fx = bumpalo::collections::Vec::from_iter_in(
             expects
                 .fx
@@ -724,6 +976,7 @@ pub fn expect_mono_module_to_dylib<'a>(
         let expect_funs = ExpectFunctions {
             pure: expects_pure,
             fx: expects_fx,
+            properties: expects_properties,
         };
 
         modules_expects.insert(module_id, expect_funs);
@@ -2,7 +2,7 @@ use bumpalo::Bump;
 use indoc::indoc;
 use roc_repl_cli::{evaluate, ReplHelper};
 use roc_repl_ui::is_incomplete;
-use roc_repl_ui::repl_state::{ReplAction, ReplState};
+use roc_repl_ui::repl_state::{parse_src, ParseOutcome, ReplAction, ReplState};
 use roc_reporting::report::DEFAULT_PALETTE;
 use roc_target::TargetInfo;
 use rustyline::Editor;
@@ -26,6 +26,29 @@ fn persisted_defs() {
     complete("y = 6", &mut state, "6 : Num *");
 }
 
+#[test]
+fn persisted_functions() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, "5 : Num *");
+    complete(
+        "double = \\n -> n * 2",
+        &mut state,
+        "<function> : Num a -> Num a",
+    );
+    complete("double x", &mut state, "10 : Num *");
+}
+
+#[test]
+fn redefined_defs() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, "5 : Num *");
+    complete("y = x + 1", &mut state, "6 : Num *");
+    complete("x = 10", &mut state, "10 : Num *");
+    complete("y", &mut state, "11 : Num *");
+}
+
 #[test]
 fn annotated_body() {
     let mut input = "t : [A, B, C]".to_string();
@@ -62,7 +85,7 @@ fn exhaustiveness_problem() {
 
         let expected_error: &str = indoc!(
             r#"
-            ── UNSAFE PATTERN ──────────────────────────────────────────────────────────────
+            ── UNSAFE PATTERN [E2008] ──────────────────────────────────────────────────────
 
             This when does not cover all the possibilities:
 
@@ -124,6 +147,204 @@ fn partial_record_definition() {
     }
 }
 
+#[test]
+fn unbalanced_parens() {
+    let mut input = "(1 +".to_string();
+    incomplete(&mut input);
+
+    input.push_str("2) * 3");
+    complete(&input, &mut ReplState::new(), "9 : Num *");
+}
+
+#[test]
+fn unclosed_list() {
+    let mut input = "[1,".to_string();
+    incomplete(&mut input);
+
+    input.push_str("2]");
+    complete(&input, &mut ReplState::new(), "[1, 2] : List (Num *)");
+}
+
+#[test]
+fn trailing_equals() {
+    let mut state = ReplState::new();
+    let mut input = "x =".to_string();
+    incomplete(&mut input);
+
+    input.push_str("    5");
+    complete(&input, &mut state, "5 : Num *");
+    complete("x + 1", &mut state, "6 : Num *");
+}
+
+#[test]
+fn incomplete_aborted_by_blank_line() {
+    let mut input = "(1 +".to_string();
+    incomplete(&mut input);
+
+    // An empty line means the user is done, so we stop waiting for more input.
+    assert!(!is_incomplete(&input));
+}
+
+#[test]
+fn import_parsing() {
+    let arena = Bump::new();
+
+    assert_eq!(
+        parse_src(&arena, "import Foo.Bar"),
+        ParseOutcome::Import {
+            module: "Foo.Bar",
+            package: None
+        }
+    );
+    assert_eq!(
+        parse_src(
+            &arena,
+            "import json.Decode from \"https://example.com/json.tar.br\""
+        ),
+        ParseOutcome::Import {
+            module: "json.Decode",
+            package: Some(("json", "https://example.com/json.tar.br"))
+        }
+    );
+    assert!(!is_incomplete("import Foo.Bar"));
+    assert_eq!(parse_src(&arena, "import "), ParseOutcome::SyntaxErr);
+    assert_eq!(parse_src(&arena, "  import  "), ParseOutcome::SyntaxErr);
+}
+
+#[test]
+fn import_without_module() {
+    let arena = Bump::new();
+    let target_info = TargetInfo::from(&Triple::host());
+    let mut state = ReplState::new();
+    let action = state.step(&arena, "import ", target_info, DEFAULT_PALETTE);
+
+    assert!(matches!(
+        action,
+        ReplAction::Eval { opt_mono: None, problems } if !problems.errors.is_empty()
+    ));
+}
+
+#[test]
+fn import_missing_module() {
+    let arena = Bump::new();
+    let target_info = TargetInfo::from(&Triple::host());
+    let mut state = ReplState::new();
+    let action = state.step(&arena, "import NoSuchModule", target_info, DEFAULT_PALETTE);
+
+    assert!(matches!(
+        action,
+        ReplAction::Eval { opt_mono: None, problems } if !problems.errors.is_empty()
+    ));
+}
+
+#[test]
+fn completion() {
+    let mut state = ReplState::new();
+
+    complete(
+        "user = { name: \"Sam\", nickname: \"S\" }",
+        &mut state,
+        "{ name: \"Sam\", nickname: \"S\" } : { name : Str, nickname : Str }",
+    );
+    complete("usage = 1", &mut state, "1 : Num *");
+
+    assert_eq!(
+        state.completions("us", 2),
+        (0, vec!["usage".to_string(), "user".to_string()])
+    );
+    assert_eq!(
+        state.completions("1 + user.n", 10),
+        (
+            4,
+            vec!["user.name".to_string(), "user.nickname".to_string()]
+        )
+    );
+    assert!(state
+        .completions("List.ma", 7)
+        .1
+        .contains(&"List.map".to_string()));
+    assert!(state.completions("Li", 2).1.contains(&"List".to_string()));
+}
+
+#[test]
+fn print_length() {
+    let mut state = ReplState::new();
+
+    let arena = Bump::new();
+    let target_info = TargetInfo::from(&Triple::host());
+    let action = state.step(&arena, ":set print-length 3", target_info, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    complete("[1, 2, 3, 4, 5]", &mut state, "[1, 2, 3, …] : List (Num *)");
+    complete("[1, 2, 3]", &mut state, "[1, 2, 3] : List (Num *)");
+}
+
+#[test]
+fn print_depth() {
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target_info = TargetInfo::from(&Triple::host());
+    let action = state.step(&arena, ":set print-depth 1", target_info, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    complete(
+        "[[1], [2, 3]]",
+        &mut state,
+        "[[…], […, …]] : List (List (Num *))",
+    );
+}
+
+#[test]
+fn unknown_setting() {
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target_info = TargetInfo::from(&Triple::host());
+    let action = state.step(&arena, ":set colour red", target_info, DEFAULT_PALETTE);
+
+    assert!(matches!(
+        action,
+        ReplAction::Eval { opt_mono: None, problems } if !problems.errors.is_empty()
+    ));
+}
+
+#[test]
+fn load_and_reload() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Helpers.roc");
+    let load = format!(":load {}", path.display());
+    let arena = Bump::new();
+    let target_info = TargetInfo::from(&Triple::host());
+    let mut state = ReplState::new();
+
+    std::fs::write(
+        &path,
+        "interface Helpers exposes [double] imports []\n\ndouble = \\n -> n * 2\n",
+    )
+    .unwrap();
+
+    let action = state.step(&arena, &load, target_info, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    complete(
+        "quadruple = \\n -> double (double n)",
+        &mut state,
+        "<function> : Num a -> Num a",
+    );
+    complete("double 5", &mut state, "10 : Num *");
+
+    std::fs::write(
+        &path,
+        "interface Helpers exposes [double] imports []\n\ndouble = \\n -> n * 3\n",
+    )
+    .unwrap();
+
+    let action = state.step(&arena, ":reload", target_info, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    complete("double 5", &mut state, "15 : Num *");
+    complete("quadruple 1", &mut state, "9 : Num *");
+}
+
 #[test]
 fn tips() {
     assert!(!is_incomplete(""));
@@ -162,7 +383,7 @@ fn complete(input: &str, state: &mut ReplState, expected_start: &str) {
 
     match action {
         ReplAction::Eval { opt_mono, problems } => {
-            let string = evaluate(opt_mono, problems, &target);
+            let string = evaluate(opt_mono, problems, &target, state.print_limits());
             let escaped =
                 std::string::String::from_utf8(strip_ansi_escapes::strip(string.trim()).unwrap())
                     .unwrap();
@@ -199,7 +420,7 @@ fn error(input: &str, state: &mut ReplState, expected_step_result: String) {
 
     match action {
         ReplAction::Eval { opt_mono, problems } => {
-            let string = evaluate(opt_mono, problems, &target);
+            let string = evaluate(opt_mono, problems, &target, state.print_limits());
             let escaped =
                 std::string::String::from_utf8(strip_ansi_escapes::strip(string.trim()).unwrap())
                     .unwrap();
@@ -0,0 +1,139 @@
+//! Tab completion for the repl, based on what's in scope: the session's defs,
+//! the builtin modules, and whatever modules have been imported.
+use bumpalo::Bump;
+use roc_module::symbol::{IdentIds, ModuleIds};
+use roc_parse::ast::{AssignedField, Expr, ValueDef};
+
+use crate::repl_state::{parse_src, ParseOutcome};
+
+/// Returns the position in `line` where the completed word starts,
+/// along with the candidates to replace it with.
+pub fn complete<'s>(
+    line: &str,
+    pos: usize,
+    defs: impl Iterator<Item = (&'s str, &'s str)> + Clone,
+    imported_modules: impl Iterator<Item = &'s str>,
+) -> (usize, Vec<String>) {
+    let line = &line[..pos];
+    let start = line
+        .char_indices()
+        .rev()
+        .take_while(|(_, ch)| ch.is_alphanumeric() || *ch == '_' || *ch == '.')
+        .last()
+        .map(|(index, _)| index)
+        .unwrap_or(pos);
+    let word = &line[start..];
+
+    let mut candidates: Vec<String> = match word.rsplit_once('.') {
+        Some((qualifier, partial)) if starts_with_uppercase(qualifier) => builtin_idents(qualifier)
+            .into_iter()
+            .filter(|ident| ident.starts_with(partial))
+            .map(|ident| format!("{qualifier}.{ident}"))
+            .collect(),
+        Some((qualifier, partial)) => {
+            // Record field access. We only know the fields of records which were
+            // defined as literals earlier in the session, e.g. `user = { name: "Sam" }`
+            match defs.clone().find(|(ident, _)| *ident == qualifier) {
+                Some((_, src)) => record_fields(src)
+                    .into_iter()
+                    .filter(|field| field.starts_with(partial))
+                    .map(|field| format!("{qualifier}.{field}"))
+                    .collect(),
+                None => Vec::new(),
+            }
+        }
+        None if word.is_empty() => Vec::new(),
+        None => {
+            let module_names = ModuleIds::default();
+            let builtin_modules = module_names
+                .available_modules()
+                .map(|name| name.as_str().to_string())
+                .filter(|name| starts_with_uppercase(name));
+            let imported_modules = imported_modules
+                .map(|module| {
+                    // e.g. `json.Decode` is referred to as `Decode`
+                    let name = module.split_once('.').map_or(module, |(_, name)| name);
+
+                    // e.g. `Foo.{ bar }` is referred to as `Foo`
+                    name.split(|ch: char| !ch.is_alphanumeric())
+                        .next()
+                        .unwrap_or(name)
+                })
+                .map(|name| name.to_string());
+
+            defs.map(|(ident, _)| ident.to_string())
+                .chain(builtin_modules)
+                .chain(imported_modules)
+                .filter(|name| name.starts_with(word))
+                .collect()
+        }
+    };
+
+    candidates.sort();
+    candidates.dedup();
+
+    (start, candidates)
+}
+
+fn starts_with_uppercase(name: &str) -> bool {
+    name.starts_with(|ch: char| ch.is_ascii_uppercase())
+}
+
+/// The exposed values of the builtin module with the given name, e.g. `List.map`
+fn builtin_idents(module_name: &str) -> Vec<String> {
+    let module_ids = ModuleIds::default();
+    let module_id = match module_ids.get_id(&module_name.into()) {
+        Some(module_id) => module_id,
+        None => return Vec::new(),
+    };
+
+    match IdentIds::exposed_builtins(0).get(&module_id) {
+        Some(ident_ids) => ident_ids
+            .ident_strs()
+            // Skip internal names like `#inc`, as well as tags and types.
+            .filter(|(_, ident)| ident.starts_with(|ch: char| ch.is_ascii_lowercase()))
+            .map(|(_, ident)| ident.to_string())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn record_fields(def_src: &str) -> Vec<String> {
+    let arena = Bump::new();
+
+    let body = match parse_src(&arena, def_src) {
+        ParseOutcome::ValueDef(ValueDef::Body(_, body))
+        | ParseOutcome::ValueDef(ValueDef::AnnotatedBody {
+            body_expr: body, ..
+        }) => body.value,
+        _ => return Vec::new(),
+    };
+
+    let mut expr = &body;
+
+    while let Expr::SpaceBefore(inner, _) | Expr::SpaceAfter(inner, _) = expr {
+        expr = *inner;
+    }
+
+    match expr {
+        Expr::Record(fields) => fields
+            .items
+            .iter()
+            .filter_map(|field| field_name(&field.value))
+            .map(|name| name.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn field_name<'a, Val>(field: &AssignedField<'a, Val>) -> Option<&'a str> {
+    match field {
+        AssignedField::RequiredValue(label, _, _)
+        | AssignedField::OptionalValue(label, _, _)
+        | AssignedField::LabelOnly(label) => Some(label.value),
+        AssignedField::SpaceBefore(inner, _) | AssignedField::SpaceAfter(inner, _) => {
+            field_name(inner)
+        }
+        AssignedField::Malformed(_) => None,
+    }
+}
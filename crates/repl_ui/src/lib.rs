@@ -1,6 +1,7 @@
 //! UI functionality, shared between CLI and web, for the Read-Evaluate-Print-Loop (REPL).
 // We don't do anything here related to the terminal (doesn't exist on the web) or LLVM (too big for the web).
 pub mod colors;
+mod completion;
 pub mod repl_state;
 
 use bumpalo::Bump;
@@ -77,6 +78,10 @@ pub fn is_incomplete(input: &str) -> bool {
         ParseOutcome::Empty
         | ParseOutcome::Help
         | ParseOutcome::Exit
+        | ParseOutcome::Import { .. }
+        | ParseOutcome::Set(_)
+        | ParseOutcome::Load(_)
+        | ParseOutcome::Reload
         | ParseOutcome::ValueDef(_)
         | ParseOutcome::TypeDef(_)
         | ParseOutcome::SyntaxErr
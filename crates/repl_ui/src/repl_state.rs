@@ -1,17 +1,19 @@
 use bumpalo::Bump;
 use roc_collections::MutSet;
 use roc_load::MonomorphizedModule;
-use roc_parse::ast::{Expr, Pattern, TypeDef, TypeHeader, ValueDef};
+use roc_parse::ast::{Expr, Header, Pattern, TypeDef, TypeHeader, ValueDef};
 use roc_parse::expr::{parse_single_def, ExprParseOptions, SingleDef};
 use roc_parse::parser::Parser;
-use roc_parse::parser::{EClosure, EExpr, EPattern};
+use roc_parse::parser::{EClosure, EExpr, EIf, EInParens, EList, EPattern, EString};
 use roc_parse::parser::{EWhen, Either};
 use roc_parse::state::State;
 use roc_parse::{join_alias_to_body, join_ann_to_body};
-use roc_region::all::Loc;
-use roc_repl_eval::gen::{compile_to_mono, Problems};
+use roc_region::all::{Loc, Region};
+use roc_repl_eval::eval::PrintLimits;
+use roc_repl_eval::gen::{compile_to_mono, Problems, ReplImports};
 use roc_reporting::report::Palette;
 use roc_target::TargetInfo;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, PartialEq)]
 struct PastDef {
@@ -19,9 +21,21 @@ struct PastDef {
     src: String,
 }
 
+/// A file which was brought into the session with `:load`
+#[derive(Debug, Clone, PartialEq)]
+struct LoadedFile {
+    path: PathBuf,
+    /// The names of the defs the file contributed, so they can be replaced on `:reload`
+    idents: Vec<String>,
+}
+
+#[derive(Clone)]
 pub struct ReplState {
     past_defs: Vec<PastDef>,
     past_def_idents: MutSet<String>,
+    imports: ReplImports,
+    print_limits: PrintLimits,
+    loaded_files: Vec<LoadedFile>,
 }
 
 impl Default for ReplState {
@@ -47,9 +61,16 @@ impl ReplState {
         Self {
             past_defs: Default::default(),
             past_def_idents: Default::default(),
+            imports: Default::default(),
+            print_limits: Default::default(),
+            loaded_files: Default::default(),
         }
     }
 
+    pub fn print_limits(&self) -> PrintLimits {
+        self.print_limits
+    }
+
     pub fn step<'a>(
         &mut self,
         arena: &'a Bump,
@@ -61,6 +82,51 @@ impl ReplState {
         let src: &str = match parse_src(arena, line) {
             ParseOutcome::Empty | ParseOutcome::Help => return ReplAction::Help,
             ParseOutcome::Exit => return ReplAction::Exit,
+            ParseOutcome::Set(Some(setting)) => {
+                match setting {
+                    ReplSetting::PrintLength(max_list_len) => {
+                        self.print_limits.max_list_len = max_list_len;
+                    }
+                    ReplSetting::PrintDepth(max_depth) => {
+                        self.print_limits.max_depth = max_depth;
+                    }
+                }
+
+                return ReplAction::Nothing;
+            }
+            ParseOutcome::Set(None) => {
+                return error_action(
+                    "Settings look like `:set print-length 50` or `:set print-depth 10`. Use `none` instead of a number to turn the limit off.",
+                );
+            }
+            ParseOutcome::Load(path) => {
+                return self.load_file(arena, Path::new(path), target_info, palette);
+            }
+            ParseOutcome::Reload => {
+                if self.loaded_files.is_empty() {
+                    return error_action(
+                        "There is nothing to reload yet. Use `:load path/to/File.roc` to load a file first.",
+                    );
+                }
+
+                for path in self.loaded_files.clone().into_iter().map(|file| file.path) {
+                    match self.load_file(arena, &path, target_info, palette) {
+                        ReplAction::Nothing => {}
+                        action => return action,
+                    }
+                }
+
+                return ReplAction::Nothing;
+            }
+            ParseOutcome::Import { module, package } => {
+                return self.import(arena, module, package, target_info, palette);
+            }
+            ParseOutcome::SyntaxErr if line.trim_start().starts_with("import ") => {
+                return error_action(&format!(
+                    "I could not make sense of this import:\n\n    {}\n\n{IMPORT_USAGE}",
+                    line.trim()
+                ));
+            }
             ParseOutcome::Expr(_) | ParseOutcome::Incomplete | ParseOutcome::SyntaxErr => {
                 pending_past_def = None;
 
@@ -166,9 +232,16 @@ impl ReplState {
             }
         };
 
+        // If this input redefines something from earlier in the session, leave out the old
+        // definition; otherwise the new one would be reported as shadowing it.
+        let redefined_ident = pending_past_def.as_ref().map(|(ident, _)| ident.as_str());
         let (opt_mono, problems) = compile_to_mono(
             arena,
-            self.past_defs.iter().map(|def| def.src.as_str()),
+            &self.imports,
+            self.past_defs
+                .iter()
+                .filter(|def| Some(def.ident.as_str()) != redefined_ident)
+                .map(|def| def.src.as_str()),
             src,
             target_info,
             palette,
@@ -181,10 +254,204 @@ impl ReplState {
         ReplAction::Eval { opt_mono, problems }
     }
 
+    /// Tab completion candidates for the word ending at `pos` in `line`,
+    /// along with where that word starts.
+    pub fn completions(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        crate::completion::complete(
+            line,
+            pos,
+            self.past_defs
+                .iter()
+                .map(|def| (def.ident.as_str(), def.src.as_str())),
+            self.imports.modules.iter().map(|module| module.as_str()),
+        )
+    }
+
+    /// Add a module (and optionally the package it comes from) to the session's imports.
+    /// We load it right away, so that problems are reported for the `import` line itself
+    /// rather than for whatever the user happens to enter next.
+    fn import<'a>(
+        &mut self,
+        arena: &'a Bump,
+        module: &str,
+        package: Option<(&str, &str)>,
+        target_info: TargetInfo,
+        palette: Palette,
+    ) -> ReplAction<'a> {
+        let mut imports = self.imports.clone();
+
+        if let Some((shorthand, src)) = package {
+            if cfg!(target_family = "wasm") && src.starts_with("https://") {
+                return error_action(
+                    "Importing packages from URLs is not supported in the web repl yet.",
+                );
+            }
+
+            imports
+                .packages
+                .retain(|(existing, _)| existing != shorthand);
+            imports
+                .packages
+                .push((shorthand.to_string(), src.to_string()));
+        }
+
+        if !imports.modules.iter().any(|existing| existing == module) {
+            imports.modules.push(module.to_string());
+        }
+
+        // Make sure the import is something we can put in a module header before loading it.
+        let header = arena.alloc_str(&imports.module_header());
+
+        if roc_parse::module::parse_header(arena, State::new(header.as_bytes())).is_err() {
+            return error_action(&format!(
+                "I could not make sense of this import:\n\n    import {module}\n\n{IMPORT_USAGE}"
+            ));
+        }
+
+        let (opt_mono, problems) = compile_to_mono(
+            arena,
+            &imports,
+            self.past_defs.iter().map(|def| def.src.as_str()),
+            "{}",
+            target_info,
+            palette,
+        );
+
+        if opt_mono.is_some() && problems.errors.is_empty() {
+            self.imports = imports;
+
+            ReplAction::Nothing
+        } else {
+            ReplAction::Eval {
+                opt_mono: None,
+                problems,
+            }
+        }
+    }
+
+    /// Bring the top-level defs of an interface module into the session, along with its imports.
+    /// If the file was loaded before, its old defs are replaced, and the rest of the session
+    /// (including defs which refer to the file's defs) is left as it was.
+    fn load_file<'a>(
+        &mut self,
+        arena: &'a Bump,
+        path: &Path,
+        target_info: TargetInfo,
+        palette: Palette,
+    ) -> ReplAction<'a> {
+        let src = match std::fs::read_to_string(path) {
+            Ok(src) => &*arena.alloc_str(&src),
+            Err(err) => {
+                return error_action(&format!("I could not read {}: {err}", path.display()));
+            }
+        };
+
+        let (header, state) = match roc_parse::module::parse_header(
+            arena,
+            State::new(src.as_bytes()),
+        ) {
+            Ok((module, state)) => (module.header, state),
+            Err(_) => {
+                return error_action(&format!(
+                        "I could not parse the header of {}. Try running `roc check` on it to see what the problem is.",
+                        path.display()
+                    ));
+            }
+        };
+
+        let header_imports = match header {
+            Header::Interface(header) => header.imports.item.items,
+            _ => {
+                return error_action(&format!(
+                    "{} is not an interface module. Only interface modules (which start with something like `interface Foo exposes [bar] imports []`) can be loaded into the repl.",
+                    path.display()
+                ));
+            }
+        };
+
+        let defs = match roc_parse::module::module_defs().parse(arena, state, 0) {
+            Ok((_, defs, _)) => defs,
+            Err(_) => {
+                return error_action(&format!(
+                    "I could not parse {}. Try running `roc check` on it to see what the problem is.",
+                    path.display()
+                ));
+            }
+        };
+
+        let slice =
+            |region: Region| &src[region.start().offset as usize..region.end().offset as usize];
+        let mut next = self.clone();
+
+        if let Some(index) = next.loaded_files.iter().position(|file| file.path == path) {
+            let previous = next.loaded_files.remove(index);
+
+            next.past_defs
+                .retain(|def| !previous.idents.contains(&def.ident));
+
+            for ident in previous.idents.iter() {
+                next.past_def_idents.remove(ident);
+            }
+        }
+
+        for entry in header_imports {
+            let entry = slice(entry.region);
+
+            if !next
+                .imports
+                .modules
+                .iter()
+                .any(|existing| existing == entry)
+            {
+                next.imports.modules.push(entry.to_string());
+            }
+        }
+
+        let mut idents = Vec::new();
+
+        for (def, region) in defs.defs().zip(defs.regions.iter()) {
+            // Defs without a name (like top-level expects) can't be referred to from the repl,
+            // so there's no need to bring them in.
+            if let Some(ident) = def_ident(def) {
+                next.add_past_def(ident.to_string(), slice(*region).to_string());
+                idents.push(ident.to_string());
+            }
+        }
+
+        next.loaded_files.push(LoadedFile {
+            path: path.to_path_buf(),
+            idents,
+        });
+
+        let (opt_mono, problems) = compile_to_mono(
+            arena,
+            &next.imports,
+            next.past_defs.iter().map(|def| def.src.as_str()),
+            "{}",
+            target_info,
+            palette,
+        );
+
+        if opt_mono.is_some() && problems.errors.is_empty() {
+            *self = next;
+
+            ReplAction::Nothing
+        } else {
+            ReplAction::Eval {
+                opt_mono: None,
+                problems,
+            }
+        }
+    }
+
     fn add_past_def(&mut self, ident: String, src: String) {
         let existing_idents = &mut self.past_def_idents;
 
-        existing_idents.insert(ident.clone());
+        // Later inputs see only the most recent definition of a name. Defs which refer to
+        // it will use the new definition, since the whole session gets recompiled each time.
+        if !existing_idents.insert(ident.clone()) {
+            self.past_defs.retain(|def| def.ident != ident);
+        }
 
         self.past_defs.push(PastDef { ident, src });
     }
@@ -195,6 +462,16 @@ pub enum ParseOutcome<'a> {
     ValueDef(ValueDef<'a>),
     TypeDef(TypeDef<'a>),
     Expr(Expr<'a>),
+    /// e.g. `:load path/to/File.roc`
+    Load(&'a str),
+    Reload,
+    /// e.g. `:set print-length 50`, or None if the setting wasn't recognized
+    Set(Option<ReplSetting>),
+    /// e.g. `import Foo.Bar`, or `import json.Decode from "https://..."`
+    Import {
+        module: &'a str,
+        package: Option<(&'a str, &'a str)>,
+    },
     Incomplete,
     SyntaxErr,
     Empty,
@@ -202,6 +479,14 @@ pub enum ParseOutcome<'a> {
     Exit,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplSetting {
+    /// `:set print-length`
+    PrintLength(Option<usize>),
+    /// `:set print-depth`
+    PrintDepth(Option<usize>),
+}
+
 pub fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
     match line.trim().to_lowercase().as_str() {
         "" => ParseOutcome::Empty,
@@ -211,17 +496,19 @@ pub fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
         // If you really need to evaluate `exit` for some reason,
         // you can do `foo = exit` and then evaluate `foo` instead.
         ":exit" | ":quit" | ":q" | "exit" | "quit" | "exit()" | "quit()" => ParseOutcome::Exit,
+        ":reload" => ParseOutcome::Reload,
+        command if command.starts_with(":load ") => {
+            ParseOutcome::Load(line.trim()[":load ".len()..].trim())
+        }
+        setting if setting.starts_with(":set ") => ParseOutcome::Set(parse_setting(setting)),
+        _ if line.trim_start().starts_with("import ") => parse_import(line),
         _ => {
             let src_bytes = line.as_bytes();
 
             match roc_parse::expr::loc_expr(true).parse(arena, State::new(src_bytes), 0) {
                 Ok((_, loc_expr, _)) => ParseOutcome::Expr(loc_expr.value),
                 // Special case some syntax errors to allow for multi-line inputs
-                Err((_, EExpr::Closure(EClosure::Body(_, _), _)))
-                | Err((_, EExpr::When(EWhen::Pattern(EPattern::Start(_), _), _)))
-                | Err((_, EExpr::Record(_, _)))
-                | Err((_, EExpr::Start(_)))
-                | Err((_, EExpr::IndentStart(_))) => ParseOutcome::Incomplete,
+                Err((_, err)) if is_incomplete_expr(&err) => ParseOutcome::Incomplete,
                 Err((_, EExpr::DefMissingFinalExpr(_)))
                 | Err((_, EExpr::DefMissingFinalExpr2(_, _))) => {
                     // This indicates that we had an attempted def; re-parse it as a single-line def.
@@ -360,6 +647,9 @@ pub fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
                         Ok((_, None, _)) => {
                             todo!("TODO determine appropriate ParseOutcome for Ok(None)")
                         }
+                        // A def with nothing after its `=` yet, e.g. `x =`
+                        Err((_, EExpr::IndentDefBody(_))) => ParseOutcome::Incomplete,
+                        Err((_, err)) if is_incomplete_expr(&err) => ParseOutcome::Incomplete,
                         Err(_) => ParseOutcome::SyntaxErr,
                     }
                 }
@@ -368,3 +658,127 @@ pub fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
         }
     }
 }
+
+fn def_ident<'a>(def: Result<&TypeDef<'a>, &ValueDef<'a>>) -> Option<&'a str> {
+    match def {
+        Ok(TypeDef::Alias { header, .. })
+        | Ok(TypeDef::Opaque { header, .. })
+        | Ok(TypeDef::Ability { header, .. }) => Some(header.name.value),
+        Err(ValueDef::Annotation(pattern, _))
+        | Err(ValueDef::Body(pattern, _))
+        | Err(ValueDef::AnnotatedBody {
+            body_pattern: pattern,
+            ..
+        }) => match pattern.value {
+            Pattern::Identifier(ident) => Some(ident),
+            _ => None,
+        },
+        Err(_) => None,
+    }
+}
+
+fn parse_setting(line: &str) -> Option<ReplSetting> {
+    let mut words = line.split_whitespace().skip(1);
+    let (name, value) = (words.next()?, words.next()?);
+
+    if words.next().is_some() {
+        return None;
+    }
+
+    let limit = match value {
+        "none" => None,
+        number => Some(number.parse().ok()?),
+    };
+
+    match name {
+        "print-length" => Some(ReplSetting::PrintLength(limit)),
+        "print-depth" => Some(ReplSetting::PrintDepth(limit)),
+        _ => None,
+    }
+}
+
+const IMPORT_USAGE: &str = "Imports look like `import Foo.Bar`, or `import json.Decode from \"https://...\"` to import a module from a package.";
+
+fn parse_import(line: &str) -> ParseOutcome<'_> {
+    let rest = match line.trim().strip_prefix("import") {
+        Some(rest) => rest.trim(),
+        None => return ParseOutcome::SyntaxErr,
+    };
+
+    match rest.split_once(" from ") {
+        None if !rest.is_empty() => ParseOutcome::Import {
+            module: rest,
+            package: None,
+        },
+        None => ParseOutcome::SyntaxErr,
+        Some((module, src)) => {
+            let module = module.trim();
+            let src = src.trim();
+
+            // A module from a package has to be qualified by the package's shorthand,
+            // e.g. `json.Decode`, and the package's location has to be a string.
+            match (module.split_once('.'), src.strip_prefix('"')) {
+                (Some((shorthand, _)), Some(src)) if src.ends_with('"') => ParseOutcome::Import {
+                    module,
+                    package: Some((shorthand, &src[..src.len() - 1])),
+                },
+                _ => ParseOutcome::SyntaxErr,
+            }
+        }
+    }
+}
+
+fn error_action<'a>(message: &str) -> ReplAction<'a> {
+    ReplAction::Eval {
+        opt_mono: None,
+        problems: Problems {
+            errors: vec![message.to_string()],
+            warnings: Vec::new(),
+        },
+    }
+}
+
+/// Whether this parse error means the expression was cut off partway through,
+/// e.g. an open `when`, unbalanced parens, or a trailing operator, as opposed to
+/// being malformed. In those cases we keep reading lines instead of reporting it.
+fn is_incomplete_expr(err: &EExpr<'_>) -> bool {
+    match err {
+        EExpr::Start(_) | EExpr::IndentStart(_) | EExpr::TrailingOperator(_) => true,
+        EExpr::Record(_, _) => true,
+        EExpr::Closure(EClosure::Body(_, _) | EClosure::IndentBody(_), _) => true,
+        EExpr::When(EWhen::PatternAlignment(_, _), _) => false,
+        EExpr::When(EWhen::Condition(inner, _) | EWhen::Branch(inner, _), _) => {
+            is_incomplete_expr(inner)
+        }
+        EExpr::When(EWhen::Pattern(EPattern::Start(_), _), _) => true,
+        EExpr::When(
+            EWhen::Is(_)
+            | EWhen::Arrow(_)
+            | EWhen::IndentCondition(_)
+            | EWhen::IndentPattern(_)
+            | EWhen::IndentArrow(_)
+            | EWhen::IndentBranch(_),
+            _,
+        ) => true,
+        EExpr::If(
+            EIf::Condition(inner, _) | EIf::ThenBranch(inner, _) | EIf::ElseBranch(inner, _),
+            _,
+        ) => is_incomplete_expr(inner),
+        EExpr::If(
+            EIf::Then(_)
+            | EIf::Else(_)
+            | EIf::IndentCondition(_)
+            | EIf::IndentThenToken(_)
+            | EIf::IndentElseToken(_)
+            | EIf::IndentThenBranch(_)
+            | EIf::IndentElseBranch(_),
+            _,
+        ) => true,
+        EExpr::InParens(EInParens::End(_), _) | EExpr::List(EList::End(_), _) => true,
+        EExpr::InParens(EInParens::Expr(inner, _), _) | EExpr::List(EList::Expr(inner, _), _) => {
+            is_incomplete_expr(inner)
+        }
+        EExpr::Str(EString::EndlessMultiLine(_), _) => true,
+        _ => false,
+    }
+}
@@ -33,3 +33,22 @@ macro_rules! console_log {
 pub async fn entrypoint_from_js(src: String) -> String {
     crate::repl::entrypoint_from_js(src).await
 }
+
+/// Start a new repl session, independent of the website's repl and of any other sessions.
+/// Returns the session's id, for use with `eval_in_session`.
+#[wasm_bindgen]
+pub fn create_session() -> u32 {
+    crate::repl::create_session()
+}
+
+#[wasm_bindgen]
+pub fn drop_session(session: u32) {
+    crate::repl::drop_session(session)
+}
+
+/// Async entrypoint for embedders, which returns a JSON description of the result
+/// instead of HTML, so it can be rendered however the embedder likes.
+#[wasm_bindgen]
+pub async fn eval_in_session(session: u32, src: String) -> String {
+    crate::repl::eval_in_session(session, src).await
+}
@@ -7,8 +7,8 @@ use roc_gen_wasm::wasm32_result;
 use roc_load::MonomorphizedModule;
 use roc_parse::ast::Expr;
 use roc_repl_eval::{
-    eval::jit_to_ast,
-    gen::{format_answer, ReplOutput},
+    eval::{jit_to_ast, PrintLimits},
+    gen::{format_answer, Problems, ReplOutput},
     ReplApp, ReplAppMemory,
 };
 use roc_repl_ui::{
@@ -25,10 +25,16 @@ const WRAPPER_NAME: &str = "wrapper";
 
 // On the web, we keep the REPL state in a global variable, because `main` is not in our Rust code!
 // We return back to JS after every line of input. `main` is in the browser engine, running the JS event loop.
+//
+// Each session has its own state, so that several independent REPLs (e.g. one per code sample on a page)
+// can be embedded at once. The website's REPL uses DEFAULT_SESSION. Dropped sessions leave a `None` behind,
+// so that the other sessions' ids stay valid.
 std::thread_local! {
-    static REPL_STATE: RefCell<ReplState> = RefCell::new(ReplState::new());
+    static SESSIONS: RefCell<Vec<Option<ReplState>>> = RefCell::new(vec![Some(ReplState::new())]);
 }
 
+const DEFAULT_SESSION: u32 = 0;
+
 pub struct WasmReplApp<'a> {
     arena: &'a Bump,
 }
@@ -179,7 +185,106 @@ impl<'a> ReplApp<'a> for WasmReplApp<'a> {
 const PRE_LINKED_BINARY: &[u8] =
     include_bytes!(concat!(env!("OUT_DIR"), "/pre_linked_binary.wasm")) as &[_];
 
+/// What came of evaluating one input in a session
+enum StepResult {
+    Help,
+    Exit,
+    Nothing,
+    Eval {
+        output: Option<ReplOutput>,
+        problems: Problems,
+    },
+}
+
 pub async fn entrypoint_from_js(src: String) -> String {
+    match step_session(DEFAULT_SESSION, &src).await {
+        Some(StepResult::Help) => TIPS.to_string(),
+        Some(StepResult::Exit) => {
+            "To exit the web version of the REPL, just close the browser tab!".to_string()
+        }
+        Some(StepResult::Nothing) | None => String::new(),
+        Some(StepResult::Eval { output, problems }) => {
+            format_output(HTML_STYLE_CODES, output, problems)
+        }
+    }
+}
+
+// Only the browser build exposes sessions to the outside world.
+#[cfg_attr(feature = "wasi_test", allow(dead_code))]
+pub fn create_session() -> u32 {
+    SESSIONS.with(|sessions| {
+        let mut sessions = sessions.borrow_mut();
+
+        sessions.push(Some(ReplState::new()));
+
+        (sessions.len() - 1) as u32
+    })
+}
+
+#[cfg_attr(feature = "wasi_test", allow(dead_code))]
+pub fn drop_session(session: u32) {
+    SESSIONS.with(|sessions| {
+        if let Some(state) = sessions.borrow_mut().get_mut(session as usize) {
+            *state = None;
+        }
+    })
+}
+
+/// Evaluate one input in the given session, and describe the outcome as a JSON object, e.g.
+///
+/// ```json
+/// { "kind": "value", "expr": "2", "type": "Num *", "errors": [], "warnings": [] }
+/// ```
+///
+/// `kind` is one of "value", "problems", "help", "exit", or "nothing".
+/// Errors and warnings are rendered as HTML.
+#[cfg_attr(feature = "wasi_test", allow(dead_code))]
+pub async fn eval_in_session(session: u32, src: String) -> String {
+    step_result_json(session, step_session(session, &src).await).to_string()
+}
+
+/// "problems" is only for inputs with errors. An input that only has warnings, like a def that
+/// shadows another one, is still a "value" or "nothing", with the warnings alongside.
+fn step_result_json(session: u32, result: Option<StepResult>) -> serde_json::Value {
+    match result {
+        None => serde_json::json!({
+            "kind": "problems",
+            "errors": [format!("There is no repl session with id {session}.")],
+            "warnings": [],
+        }),
+        Some(StepResult::Help) => serde_json::json!({ "kind": "help", "text": TIPS }),
+        Some(StepResult::Exit) => serde_json::json!({ "kind": "exit" }),
+        Some(StepResult::Nothing) => serde_json::json!({ "kind": "nothing" }),
+        Some(StepResult::Eval { problems, .. }) if !problems.errors.is_empty() => {
+            serde_json::json!({
+                "kind": "problems",
+                "errors": problems.errors,
+                "warnings": problems.warnings,
+            })
+        }
+        Some(StepResult::Eval {
+            output: Some(ReplOutput { expr, expr_type }),
+            problems,
+        }) => serde_json::json!({
+            "kind": "value",
+            "expr": expr,
+            "type": expr_type,
+            "errors": problems.errors,
+            "warnings": problems.warnings,
+        }),
+        Some(StepResult::Eval {
+            output: None,
+            problems,
+        }) => serde_json::json!({
+            "kind": "nothing",
+            "errors": problems.errors,
+            "warnings": problems.warnings,
+        }),
+    }
+}
+
+/// Returns None if there's no session with the given id
+async fn step_session(session: u32, src: &str) -> Option<StepResult> {
     // If our Rust code panics, redirect the error message to JS console.error
     // Also, our JS code overrides console.error to display the error message text (including stack trace) in the REPL output.
     #[cfg(feature = "console_error_panic_hook")]
@@ -192,33 +297,40 @@ pub async fn entrypoint_from_js(src: String) -> String {
     let target_info = TargetInfo::default_wasm32();
 
     // Advance the REPL state machine
-    let action = REPL_STATE.with(|repl_state_cell| {
-        let mut repl_state = repl_state_cell.borrow_mut();
-        repl_state.step(arena, &src, target_info, DEFAULT_PALETTE_HTML)
-    });
-
-    // Perform the action the state machine asked for, and return the appropriate output string
-    match action {
-        ReplAction::Help => TIPS.to_string(),
-        ReplAction::Exit => {
-            "To exit the web version of the REPL, just close the browser tab!".to_string()
-        }
-        ReplAction::Nothing => String::new(),
+    let (action, print_limits) = SESSIONS.with(|sessions| {
+        let mut sessions = sessions.borrow_mut();
+        let repl_state = sessions.get_mut(session as usize)?.as_mut()?;
+        let print_limits = repl_state.print_limits();
+
+        Some((
+            repl_state.step(arena, src, target_info, DEFAULT_PALETTE_HTML),
+            print_limits,
+        ))
+    })?;
+
+    // Perform the action the state machine asked for
+    let result = match action {
+        ReplAction::Help => StepResult::Help,
+        ReplAction::Exit => StepResult::Exit,
+        ReplAction::Nothing => StepResult::Nothing,
         ReplAction::Eval { opt_mono, problems } => {
-            let opt_output = match opt_mono {
-                Some(mono) => eval_wasm(arena, target_info, mono).await,
+            let output = match opt_mono {
+                Some(mono) => eval_wasm(arena, target_info, mono, print_limits).await,
                 None => None,
             };
 
-            format_output(HTML_STYLE_CODES, opt_output, problems)
+            StepResult::Eval { output, problems }
         }
-    }
+    };
+
+    Some(result)
 }
 
 async fn eval_wasm<'a>(
     arena: &'a Bump,
     target_info: TargetInfo,
     mono: MonomorphizedModule<'a>,
+    print_limits: PrintLimits,
 ) -> Option<ReplOutput> {
     let MonomorphizedModule {
         module_id,
@@ -312,6 +424,7 @@ async fn eval_wasm<'a>(
         &interns,
         layout_interner.into_global().fork(),
         target_info,
+        print_limits,
     );
 
     // Transform the Expr to a string
@@ -319,3 +432,67 @@ async fn eval_wasm<'a>(
 
     Some(ReplOutput { expr, expr_type })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(output: Option<(&str, &str)>, errors: &[&str], warnings: &[&str]) -> StepResult {
+        StepResult::Eval {
+            output: output.map(|(expr, expr_type)| ReplOutput {
+                expr: expr.to_string(),
+                expr_type: expr_type.to_string(),
+            }),
+            problems: Problems {
+                errors: errors.iter().map(|error| error.to_string()).collect(),
+                warnings: warnings.iter().map(|warning| warning.to_string()).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn value_json() {
+        assert_eq!(
+            step_result_json(0, Some(eval(Some(("2", "Num *")), &[], &["unused"]))),
+            serde_json::json!({
+                "kind": "value",
+                "expr": "2",
+                "type": "Num *",
+                "errors": [],
+                "warnings": ["unused"],
+            })
+        );
+    }
+
+    #[test]
+    fn only_warnings_json() {
+        assert_eq!(
+            step_result_json(0, Some(eval(None, &[], &["shadowed"]))),
+            serde_json::json!({
+                "kind": "nothing",
+                "errors": [],
+                "warnings": ["shadowed"],
+            })
+        );
+    }
+
+    #[test]
+    fn problems_json() {
+        assert_eq!(
+            step_result_json(0, Some(eval(None, &["mismatch"], &["shadowed"]))),
+            serde_json::json!({
+                "kind": "problems",
+                "errors": ["mismatch"],
+                "warnings": ["shadowed"],
+            })
+        );
+        assert_eq!(
+            step_result_json(7, None),
+            serde_json::json!({
+                "kind": "problems",
+                "errors": ["There is no repl session with id 7."],
+                "warnings": [],
+            })
+        );
+    }
+}
@@ -1,24 +1,156 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-use roc_collections::MutMap;
-use roc_module::symbol::{Interns, ModuleId};
-use roc_problem::can::Problem;
-use roc_region::all::LineInfo;
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_problem::can::{Problem, RuntimeError};
+use roc_problem::Severity;
+use roc_region::all::{LineInfo, Region};
 use roc_solve_problem::TypeError;
 
-use crate::report::ANSI_STYLE_CODES;
+use crate::diagnostic::{can_problem_related, Diagnostic, RelatedInformation};
+use crate::report::{
+    can_problem, type_problem, Palette, Report, RocDocAllocator, SnippetConfig, DEFAULT_PALETTE,
+    DEFAULT_SNIPPET_CONFIG, HIGH_CONTRAST_PALETTE, LIGHT_BACKGROUND_PALETTE, PLAIN_PALETTE,
+};
+
+/// Whether to use colors in terminal output, as chosen by `--color`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Use colors when printing to a terminal, unless `NO_COLOR` is set or `TERM` is `dumb`
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// The colors to use in terminal output, as chosen by `--theme` or `ROC_THEME`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl Theme {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Theme::Dark),
+            "light" => Some(Theme::Light),
+            "high-contrast" => Some(Theme::HighContrast),
+            _ => None,
+        }
+    }
+
+    /// The theme named by the `ROC_THEME` environment variable, if any.
+    pub fn from_env() -> Option<Self> {
+        std::env::var("ROC_THEME")
+            .ok()
+            .and_then(|name| Theme::from_name(&name))
+    }
+}
+
+/// The palette for terminal output, given the user's choices and the environment.
+pub fn choose_palette(color: ColorChoice, theme: Theme) -> Palette {
+    use std::io::IsTerminal;
+
+    let use_color = match color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = std::env::var_os("NO_COLOR").map_or(false, |val| !val.is_empty());
+            let dumb_terminal = std::env::var_os("TERM").map_or(false, |term| term == "dumb");
+
+            !no_color && !dumb_terminal && std::io::stdout().is_terminal()
+        }
+    };
+
+    match (use_color, theme) {
+        (false, _) => PLAIN_PALETTE,
+        (true, Theme::Dark) => DEFAULT_PALETTE,
+        (true, Theme::Light) => LIGHT_BACKGROUND_PALETTE,
+        (true, Theme::HighContrast) => HIGH_CONTRAST_PALETTE,
+    }
+}
+
+static TERMINAL_PALETTE: std::sync::OnceLock<Palette> = std::sync::OnceLock::new();
+
+/// Set the palette that reports and summaries printed to the terminal will use.
+/// This can only be done once, before anything is printed.
+pub fn set_terminal_palette(palette: Palette) {
+    let _ = TERMINAL_PALETTE.set(palette);
+}
+
+/// The palette set by [set_terminal_palette], or the default palette if none was set.
+pub fn terminal_palette() -> &'static Palette {
+    TERMINAL_PALETTE.get().unwrap_or(&DEFAULT_PALETTE)
+}
+
+static SNIPPET_CONFIG: std::sync::OnceLock<SnippetConfig> = std::sync::OnceLock::new();
+
+/// Set how much source code the snippets in reports printed by the CLI will show.
+/// This can only be done once, before anything is printed.
+pub fn set_snippet_config(config: SnippetConfig) {
+    let _ = SNIPPET_CONFIG.set(config);
+}
+
+/// The config set by [set_snippet_config], or the default config if none was set.
+pub fn snippet_config() -> SnippetConfig {
+    SNIPPET_CONFIG
+        .get()
+        .copied()
+        .unwrap_or(DEFAULT_SNIPPET_CONFIG)
+}
+
+static MAX_ERRORS: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+
+/// Stop printing errors after this many, so a badly broken program doesn't bury the first
+/// problems under all the others. This can only be done once, before anything is printed.
+pub fn set_max_errors(max_errors: usize) {
+    let _ = MAX_ERRORS.set(max_errors);
+}
+
+/// The limit set by [set_max_errors], if any.
+pub fn max_errors() -> Option<usize> {
+    MAX_ERRORS.get().copied()
+}
+
+/// How problems found during compilation should be printed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ProblemOutput {
+    /// Human-readable reports, rendered for a color terminal
+    #[default]
+    Text,
+    /// A single JSON document on stdout, for CI annotations and editor plugins
+    Json,
+}
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Problems {
     pub fatally_errored: bool,
     pub errors: usize,
     pub warnings: usize,
+    /// Set by `--deny-warnings`, so that warnings fail the build the same way errors do.
+    pub warnings_denied: bool,
 }
 
 impl Problems {
+    pub fn deny_warnings(self, deny: bool) -> Self {
+        Problems {
+            warnings_denied: deny,
+            ..self
+        }
+    }
+
+    /// Whether the program should be treated as broken, e.g. not run or not bundled.
+    pub fn failed(&self) -> bool {
+        self.errors > 0 || (self.warnings_denied && self.warnings > 0)
+    }
+
     pub fn exit_code(&self) -> i32 {
-        // 0 means no problems, 1 means errors, 2 means warnings
-        if self.errors > 0 {
+        // 0 means no problems, 1 means errors (or denied warnings), 2 means warnings
+        if self.failed() {
             1
         } else if self.warnings > 0 {
             2
@@ -29,32 +161,44 @@ impl Problems {
 
     // prints e.g. `1 error and 0 warnings found in 63 ms.`
     pub fn print_error_warning_count(&self, total_time: std::time::Duration) {
-        const GREEN: &str = ANSI_STYLE_CODES.green;
-        const YELLOW: &str = ANSI_STYLE_CODES.yellow;
-        const RESET: &str = ANSI_STYLE_CODES.reset;
+        let palette = terminal_palette();
+        let (green, yellow, red, reset) = (
+            palette.success,
+            palette.warning,
+            palette.error,
+            palette.reset,
+        );
+
+        let denied = self.warnings_denied && self.warnings > 0;
 
         println!(
-            "{}{}{} {} and {}{}{} {} found in {} ms",
+            "{}{}{} {} and {}{}{} {}{} found in {} ms",
             match self.errors {
-                0 => GREEN,
-                _ => YELLOW,
+                0 => green,
+                _ => yellow,
             },
             self.errors,
-            RESET,
+            reset,
             match self.errors {
                 1 => "error",
                 _ => "errors",
             },
             match self.warnings {
-                0 => GREEN,
-                _ => YELLOW,
+                0 => green,
+                _ if denied => red,
+                _ => yellow,
             },
             self.warnings,
-            RESET,
+            reset,
             match self.warnings {
                 1 => "warning",
                 _ => "warnings",
             },
+            if denied {
+                " (denied by --deny-warnings)"
+            } else {
+                ""
+            },
             total_time.as_millis()
         );
     }
@@ -63,13 +207,13 @@ impl Problems {
 pub fn report_problems(
     sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
     interns: &Interns,
+    imported_symbols: &MutMap<ModuleId, Vec<Symbol>>,
     can_problems: &mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
 ) -> Problems {
-    use crate::report::{can_problem, type_problem, Report, RocDocAllocator, DEFAULT_PALETTE};
     use roc_problem::Severity::*;
 
-    let palette = DEFAULT_PALETTE;
+    let palette = terminal_palette();
     let mut total_problems = 0;
 
     for problems in can_problems.values() {
@@ -82,84 +226,41 @@ pub fn report_problems(
 
     // This will often over-allocate total memory, but it means we definitely
     // never need to re-allocate either the warnings or the errors vec!
-    let mut warnings = Vec::with_capacity(total_problems);
-    let mut errors = Vec::with_capacity(total_problems);
+    let mut warnings: Vec<(ReportOrder, String)> = Vec::with_capacity(total_problems);
+    let mut errors: Vec<(ReportOrder, String)> = Vec::with_capacity(total_problems);
     let mut fatally_errored = false;
 
-    for (home, (module_path, src)) in sources.iter() {
-        let mut src_lines: Vec<&str> = Vec::new();
-
-        src_lines.extend(src.split('\n'));
-
-        let lines = LineInfo::new(&src_lines.join("\n"));
-
-        // Report parsing and canonicalization problems
-        let alloc = RocDocAllocator::new(&src_lines, *home, interns);
-
-        let problems = type_problems.remove(home).unwrap_or_default();
-
-        for problem in problems {
-            if let Some(report) = type_problem(&alloc, &lines, module_path.clone(), problem) {
-                let severity = report.severity;
-                let mut buf = String::new();
-
-                report.render_color_terminal(&mut buf, &alloc, &palette);
-
-                match severity {
-                    Warning => {
-                        warnings.push(buf);
-                    }
-                    RuntimeError => {
-                        errors.push(buf);
-                    }
-                    Fatal => {
-                        fatally_errored = true;
-                        errors.push(buf);
-                    }
-                }
-            }
-        }
-
-        // Shadowing errors often cause cryptic type errors. To make it easy to spot the root cause,
-        // we print the shadowing errors last.
-        let problems = can_problems.remove(home).unwrap_or_default();
-        let (shadowing_errs, mut ordered): (Vec<Problem>, Vec<Problem>) =
-            problems.into_iter().partition(|p| {
-                matches!(
-                    p,
-                    Problem::Shadowing {
-                        original_region: _,
-                        shadow: _,
-                        kind: _,
-                    }
-                )
-            });
-        ordered.extend(shadowing_errs);
-
-        for problem in ordered.into_iter() {
-            let report = can_problem(&alloc, &lines, module_path.clone(), problem);
-            let severity = report.severity;
+    let left_out = for_each_report(
+        sources,
+        interns,
+        imported_symbols,
+        can_problems,
+        type_problems,
+        |alloc, _lines, shown| {
+            let severity = shown.report.severity;
             let mut buf = String::new();
 
-            report.render_color_terminal(&mut buf, &alloc, &palette);
+            shown.report.render_color_terminal(&mut buf, alloc, palette);
 
             match severity {
                 Warning => {
-                    warnings.push(buf);
+                    warnings.push((shown.order, buf));
                 }
                 RuntimeError => {
-                    errors.push(buf);
+                    errors.push((shown.order, buf));
                 }
                 Fatal => {
                     fatally_errored = true;
-                    errors.push(buf);
+                    errors.push((shown.order, buf));
                 }
             }
-        }
-    }
+        },
+    );
 
-    debug_assert!(can_problems.is_empty() && type_problems.is_empty(), "After reporting problems, there were {:?} can_problems and {:?} type_problems that could not be reported because they did not have corresponding entries in `sources`.", can_problems.len(), type_problems.len());
-    debug_assert_eq!(errors.len() + warnings.len(), total_problems);
+    debug_assert_eq!(errors.len() + warnings.len() + left_out, total_problems);
+
+    warnings.sort();
+    errors.sort();
 
     let problems_reported;
 
@@ -167,15 +268,33 @@ pub fn report_problems(
     if errors.is_empty() {
         problems_reported = warnings.len();
 
-        for warning in warnings.iter() {
+        for (_, warning) in warnings.iter() {
             println!("\n{warning}\n");
         }
     } else {
-        problems_reported = errors.len();
+        problems_reported = max_errors().map_or(errors.len(), |max| max.min(errors.len()));
 
-        for error in errors.iter() {
+        for (_, error) in errors.iter().take(problems_reported) {
             println!("\n{error}\n");
         }
+
+        let not_shown = errors.len() - problems_reported;
+
+        if not_shown > 0 {
+            println!(
+                "\nThere {} {not_shown} more {}, which I left out because of --max-errors.\n",
+                if not_shown == 1 { "is" } else { "are" },
+                if not_shown == 1 { "error" } else { "errors" },
+            );
+        }
+
+        if left_out > 0 {
+            println!(
+                "\nI left out {left_out} more {}, because {} probably caused by the problems above.\n",
+                if left_out == 1 { "problem" } else { "problems" },
+                if left_out == 1 { "it is" } else { "they are" },
+            );
+        }
     }
 
     // If we printed any problems, print a horizontal rule at the end,
@@ -185,12 +304,373 @@ pub fn report_problems(
     // compiling it, as it lets you clearly see where the compiler
     // errors/warnings end and the program output begins.
     if problems_reported > 0 {
-        println!("{}\u{001B}[0m\n", Report::horizontal_rule(&palette));
+        print_file_summary(&errors, &warnings);
+
+        println!("{}{}\n", Report::horizontal_rule(palette), palette.reset);
     }
 
     Problems {
         fatally_errored,
         errors: errors.len(),
         warnings: warnings.len(),
+        warnings_denied: false,
+    }
+}
+
+/// A report worth showing, as found by [for_each_report].
+struct ShownReport<'b> {
+    report: Report<'b>,
+    order: ReportOrder,
+    region: Option<Region>,
+    related: Vec<RelatedInformation>,
+}
+
+/// Calls `f` with the report for each problem in `sources` that is worth showing, module by
+/// module. Problems which were probably caused by other problems, and type problems which were
+/// found more than once, are left out; returns how many were.
+///
+/// This is shared by every output format, so that they all show the same problems.
+fn for_each_report<F>(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    imported_symbols: &MutMap<ModuleId, Vec<Symbol>>,
+    can_problems: &mut MutMap<ModuleId, Vec<Problem>>,
+    type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+    mut f: F,
+) -> usize
+where
+    F: for<'b> FnMut(&'b RocDocAllocator<'b>, &LineInfo, ShownReport<'b>),
+{
+    let mut left_out = 0;
+    let mut rendered_type_problems = MutSet::default();
+
+    for (home, (module_path, src)) in sources.iter() {
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(&src_lines.join("\n"));
+        let alloc = RocDocAllocator::new(&src_lines, *home, interns)
+            .with_snippet_config(snippet_config())
+            .with_imported_symbols(imported_symbols.get(home).map_or(&[], Vec::as_slice));
+
+        let module_type_problems = type_problems.remove(home).unwrap_or_default();
+        let module_can_problems = can_problems.remove(home).unwrap_or_default();
+        let cascades = Cascades::new(&module_can_problems, &module_type_problems);
+
+        for problem in module_type_problems {
+            if cascades.is_follow_on(&problem) {
+                left_out += 1;
+                continue;
+            }
+
+            let region = problem.region();
+
+            if let Some(report) = type_problem(&alloc, &lines, module_path.clone(), problem) {
+                // The same problem can be found more than once, e.g. when a def is checked
+                // both on its own and as part of a specialization.
+                let mut rendered = String::new();
+                report.clone().render_plain(&mut rendered, &alloc);
+
+                if !rendered_type_problems.insert(rendered) {
+                    left_out += 1;
+                    continue;
+                }
+
+                f(
+                    &alloc,
+                    &lines,
+                    ShownReport {
+                        order: ReportOrder::new(&report, region, false),
+                        report,
+                        region,
+                        related: Vec::new(),
+                    },
+                );
+            }
+        }
+
+        for problem in module_can_problems {
+            let region = problem.region();
+            let related = can_problem_related(&problem, &lines, module_path);
+            let shadowing = matches!(problem, Problem::Shadowing { .. });
+            let report = can_problem(&alloc, &lines, module_path.clone(), problem);
+
+            f(
+                &alloc,
+                &lines,
+                ShownReport {
+                    order: ReportOrder::new(&report, region, shadowing),
+                    report,
+                    region,
+                    related,
+                },
+            );
+        }
+    }
+
+    debug_assert!(can_problems.is_empty() && type_problems.is_empty(), "After reporting problems, there were {:?} can_problems and {:?} type_problems that could not be reported because they did not have corresponding entries in `sources`.", can_problems.len(), type_problems.len());
+
+    left_out
+}
+
+/// When problems were found in more than one file, prints how many each file has,
+/// including the ones that were left out, e.g. warnings when there are errors.
+fn print_file_summary(errors: &[(ReportOrder, String)], warnings: &[(ReportOrder, String)]) {
+    let mut counts: BTreeMap<&Path, (usize, usize)> = BTreeMap::new();
+
+    for (order, _) in errors {
+        counts.entry(order.path.as_path()).or_default().0 += 1;
+    }
+
+    for (order, _) in warnings {
+        counts.entry(order.path.as_path()).or_default().1 += 1;
+    }
+
+    if counts.len() < 2 {
+        return;
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_default();
+
+    println!("Problems by file:\n");
+
+    for (path, (errors, warnings)) in counts {
+        println!(
+            "    {}: {errors} {} and {warnings} {}",
+            path.strip_prefix(&cwd).unwrap_or(path).display(),
+            if errors == 1 { "error" } else { "errors" },
+            if warnings == 1 { "warning" } else { "warnings" },
+        );
+    }
+
+    println!();
+}
+
+/// Tracks which problems in a module are most likely caused by other problems in it,
+/// so that one mistake doesn't get buried under a pile of follow-on errors.
+struct Cascades {
+    /// Symbols whose types could not be determined, e.g. because they are defined circularly
+    erroneous_symbols: MutSet<Symbol>,
+    /// Symbols which were shadowed. Uses of their name still refer to them,
+    /// which may not be the definition the author meant.
+    shadowed_symbols: MutSet<Symbol>,
+}
+
+impl Cascades {
+    fn new(can_problems: &[Problem], type_problems: &[TypeError]) -> Self {
+        let erroneous_symbols = type_problems
+            .iter()
+            .flat_map(TypeError::erroneous_symbols)
+            .collect();
+
+        let shadowed_symbols = can_problems
+            .iter()
+            .filter_map(|problem| match problem {
+                Problem::RuntimeError(RuntimeError::Shadowing {
+                    original_symbol, ..
+                }) => Some(*original_symbol),
+                _ => None,
+            })
+            .collect();
+
+        Cascades {
+            erroneous_symbols,
+            shadowed_symbols,
+        }
+    }
+
+    /// Whether this problem was found through a use of a symbol that is already known to be
+    /// broken, so reporting it would only repeat an earlier problem.
+    fn is_follow_on(&self, problem: &TypeError) -> bool {
+        match problem.blamed_symbol() {
+            Some(symbol) => {
+                self.erroneous_symbols.contains(&symbol) || self.shadowed_symbols.contains(&symbol)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Where a report goes in the output. Modules are checked in parallel, so reports are sorted
+/// by this before printing to make the output the same on every run.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct ReportOrder {
+    path: PathBuf,
+    /// Shadowing errors often cause cryptic type errors. To make it easy to spot the root cause,
+    /// we print the shadowing errors after the module's other problems.
+    shadowing: bool,
+    /// Problems without a location come first.
+    offset: Option<u32>,
+    title: String,
+}
+
+impl ReportOrder {
+    fn new(report: &Report, region: Option<Region>, shadowing: bool) -> Self {
+        ReportOrder {
+            path: report.filename.clone(),
+            shadowing,
+            offset: region.map(|region| region.start().offset),
+            title: report.title.clone(),
+        }
     }
 }
+
+/// Like [report_problems], but prints every problem (including warnings) as one JSON document:
+///
+/// ```json
+/// {
+///   "diagnostics": [
+///     {
+///       "file": "main.roc",
+///       "severity": "error",
+///       "code": "E2001",
+///       "title": "TYPE MISMATCH",
+///       "range": { "start": { "line": 4, "column": 8 }, "end": { "line": 4, "column": 12 } },
+///       "message": "...",
+///       "markdown": "...",
+///       "related": [],
+///       "fixes": []
+///     }
+///   ],
+///   "errors": 1,
+///   "warnings": 0,
+///   "left_out": 0
+/// }
+/// ```
+///
+/// Like in [report_problems], problems which were probably caused by other problems are left out
+/// of `diagnostics`; `left_out` says how many. See [Diagnostic] for what each field means.
+pub fn report_problems_json(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    imported_symbols: &MutMap<ModuleId, Vec<Symbol>>,
+    can_problems: &mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
+    type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+) -> Problems {
+    let mut diagnostics = Vec::new();
+    let mut problems = Problems::default();
+
+    let left_out = for_each_report(
+        sources,
+        interns,
+        imported_symbols,
+        can_problems,
+        type_problems,
+        |alloc, lines, shown| {
+            match shown.report.severity {
+                Severity::Warning => {
+                    problems.warnings += 1;
+                }
+                Severity::RuntimeError => {
+                    problems.errors += 1;
+                }
+                Severity::Fatal => {
+                    problems.fatally_errored = true;
+                    problems.errors += 1;
+                }
+            }
+
+            diagnostics.push((
+                shown.order,
+                Diagnostic::from_report(shown.report, alloc, lines, shown.region, shown.related),
+            ));
+        },
+    );
+
+    diagnostics.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let diagnostics: Vec<Diagnostic> = diagnostics
+        .into_iter()
+        .map(|(_, diagnostic)| diagnostic)
+        .collect();
+
+    let output = serde_json::json!({
+        "diagnostics": diagnostics,
+        "errors": problems.errors,
+        "warnings": problems.warnings,
+        "left_out": left_out,
+    });
+
+    println!("{output}");
+
+    problems
+}
+
+/// A module's source after applying the machine-applicable fixes for its problems.
+pub struct FixedModule {
+    pub path: PathBuf,
+    pub src: String,
+    pub fixes: usize,
+}
+
+/// Apply every machine-applicable fix to the sources of the modules it's for. This is what
+/// `roc check --fix` does; the problems are left in place so they can still be reported.
+pub fn apply_machine_applicable_fixes(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    can_problems: &MutMap<ModuleId, Vec<Problem>>,
+    type_problems: &MutMap<ModuleId, Vec<TypeError>>,
+) -> Vec<FixedModule> {
+    use crate::report::apply_edits;
+
+    let mut fixed_modules = Vec::new();
+
+    for (home, (module_path, src)) in sources.iter() {
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(src);
+        let alloc = RocDocAllocator::new(&src_lines, *home, interns);
+
+        let can_reports = can_problems
+            .get(home)
+            .into_iter()
+            .flatten()
+            .map(|problem| can_problem(&alloc, &lines, module_path.clone(), problem.clone()));
+        let type_reports = type_problems
+            .get(home)
+            .into_iter()
+            .flatten()
+            .filter_map(|problem| {
+                type_problem(&alloc, &lines, module_path.clone(), problem.clone())
+            });
+
+        let fixes: Vec<_> = can_reports
+            .chain(type_reports)
+            .flat_map(|report| report.fixes)
+            .filter(|fix| fix.machine_applicable)
+            .collect();
+
+        if !fixes.is_empty() {
+            fixed_modules.push(FixedModule {
+                path: module_path.clone(),
+                src: apply_edits(
+                    src,
+                    fixes.iter().flat_map(|fix| fix.edits.clone()).collect(),
+                ),
+                fixes: fixes.len(),
+            });
+        }
+    }
+
+    fixed_modules
+}
+
+/// Print a problem that stopped loading before any modules could be checked (for example,
+/// a file that could not be read or parsed), in the same format as [report_problems_json].
+pub fn report_loading_problem_json(message: &str) {
+    let output = serde_json::json!({
+        "diagnostics": [{
+            "file": null,
+            "severity": "error",
+            "code": null,
+            "title": null,
+            "range": null,
+            "message": message,
+            "markdown": message,
+            "related": [],
+            "fixes": [],
+        }],
+        "errors": 1,
+        "warnings": 0,
+        "left_out": 0,
+    });
+
+    println!("{output}");
+}
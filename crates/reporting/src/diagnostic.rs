@@ -0,0 +1,233 @@
+//! A serializable form of [Report], shaped after the Language Server Protocol's diagnostics.
+//!
+//! This is what `roc check --output=json` prints, and what the language server sends to editors,
+//! so that both agree on ranges, severities, and messages.
+use std::path::Path;
+
+use roc_problem::can::{Problem, RuntimeError};
+use roc_problem::Severity;
+use roc_region::all::{LineColumnRegion, LineInfo, Region};
+use serde::Serialize;
+
+use crate::report::{Edit, Fix, Report, RocDocAllocator, RocDocBuilder};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    pub severity: DiagnosticSeverity,
+    /// A stable identifier for the kind of problem, e.g. `E2001`. See `roc explain`, which has an
+    /// explanation for every code that is given here.
+    pub code: Option<String>,
+    pub title: String,
+    /// `None` when the problem has no source location.
+    pub range: Option<Range>,
+    /// The report body without its header, with no formatting at all.
+    pub message: String,
+    /// The report body without its header, with inline code in backticks
+    /// and source snippets as indented code blocks.
+    pub markdown: String,
+    /// Other places in the source that help explain the problem.
+    pub related: Vec<RelatedInformation>,
+    /// Changes to the source which would resolve the problem.
+    pub fixes: Vec<DiagnosticFix>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+impl From<Severity> for DiagnosticSeverity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::RuntimeError | Severity::Fatal => DiagnosticSeverity::Error,
+            Severity::Warning => DiagnosticSeverity::Warning,
+        }
+    }
+}
+
+/// Lines and columns are 0-based, and the end is exclusive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Range {
+    pub fn from_region(lines: &LineInfo, region: Region) -> Self {
+        let LineColumnRegion { start, end } = lines.convert_region(region);
+
+        Range {
+            start: Position {
+                line: start.line,
+                column: start.column,
+            },
+            end: Position {
+                line: end.line,
+                column: end.column,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RelatedInformation {
+    pub file: String,
+    pub range: Range,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DiagnosticFix {
+    pub description: String,
+    /// Whether `roc check --fix` applies this fix. When `false`, the fix is only a suggestion.
+    pub machine_applicable: bool,
+    pub edits: Vec<TextEdit>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TextEdit {
+    pub range: Range,
+    pub replacement: String,
+}
+
+impl DiagnosticFix {
+    pub fn from_fix(fix: Fix, lines: &LineInfo) -> Self {
+        let Fix {
+            description,
+            edits,
+            machine_applicable,
+        } = fix;
+
+        DiagnosticFix {
+            description,
+            machine_applicable,
+            edits: edits
+                .into_iter()
+                .map(|edit: Edit| TextEdit {
+                    range: Range::from_region(lines, edit.region),
+                    replacement: edit.replacement,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Diagnostic {
+    pub fn from_report<'b>(
+        report: Report<'b>,
+        alloc: &'b RocDocAllocator<'b>,
+        lines: &LineInfo,
+        region: Option<Region>,
+        related: Vec<RelatedInformation>,
+    ) -> Self {
+        let code = report.shown_code();
+        let Report {
+            title,
+            filename,
+            doc,
+            severity,
+            fixes,
+            code: _,
+        } = report;
+
+        // Render the body without the header, since the title and path are separate fields.
+        let body = |doc: RocDocBuilder<'b>| Report {
+            title: String::new(),
+            filename: filename.clone(),
+            doc,
+            severity,
+            fixes: Vec::new(),
+            code: None,
+        };
+
+        let mut message = String::new();
+        body(doc.clone()).render_plain(&mut message, alloc);
+
+        let mut markdown = String::new();
+        body(doc).render_ci(&mut markdown, alloc);
+
+        Diagnostic {
+            file: filename.to_string_lossy().into_owned(),
+            severity: severity.into(),
+            code: code.map(String::from),
+            title,
+            range: region.map(|region| Range::from_region(lines, region)),
+            message,
+            markdown,
+            related,
+            fixes: fixes
+                .into_iter()
+                .map(|fix| DiagnosticFix::from_fix(fix, lines))
+                .collect(),
+        }
+    }
+}
+
+/// The secondary locations of a canonicalization problem, e.g. the definition that
+/// a shadowing definition shadows.
+pub fn can_problem_related(
+    problem: &Problem,
+    lines: &LineInfo,
+    filename: &Path,
+) -> Vec<RelatedInformation> {
+    let related = |region: Region, message: &str| RelatedInformation {
+        file: filename.to_string_lossy().into_owned(),
+        range: Range::from_region(lines, region),
+        message: message.to_string(),
+    };
+
+    match problem {
+        Problem::Shadowing { shadow, .. }
+        | Problem::RuntimeError(RuntimeError::Shadowing { shadow, .. }) => {
+            vec![related(shadow.region, "The shadowing definition")]
+        }
+        Problem::PermittedShadowing {
+            original_region, ..
+        } => vec![related(*original_region, "The definition it shadows")],
+        Problem::DuplicateRecordFieldValue {
+            field_region,
+            replaced_region,
+            ..
+        }
+        | Problem::DuplicateRecordFieldType {
+            field_region,
+            replaced_region,
+            ..
+        } => vec![
+            related(*replaced_region, "The first definition of this field"),
+            related(*field_region, "The definition that replaces it"),
+        ],
+        Problem::DuplicateTag {
+            tag_region,
+            replaced_region,
+            ..
+        } => vec![
+            related(*replaced_region, "The first occurrence of this tag"),
+            related(*tag_region, "The occurrence that replaces it"),
+        ],
+        Problem::SignatureDefMismatch {
+            annotation_pattern,
+            def_pattern,
+        } => vec![
+            related(*annotation_pattern, "The annotation"),
+            related(*def_pattern, "The definition"),
+        ],
+        Problem::NestedDatatype {
+            differing_recursion_region,
+            ..
+        } => vec![related(
+            *differing_recursion_region,
+            "The recursive use with different arguments",
+        )],
+        _ => Vec::new(),
+    }
+}
@@ -7,12 +7,15 @@ use roc_problem::can::{
     ShadowKind,
 };
 use roc_problem::Severity;
-use roc_region::all::{LineColumn, LineColumnRegion, LineInfo, Loc, Region};
+use roc_region::all::{LineColumn, LineColumnRegion, LineInfo, Loc, Position, Region};
 use roc_types::types::AliasKind;
 use std::path::PathBuf;
 
 use crate::error::r#type::suggest;
-use crate::report::{to_file_problem_report, Annotation, Report, RocDocAllocator, RocDocBuilder};
+use crate::report::{
+    to_file_problem_report, Annotation, Edit, Explanation, Fix, Report, RocDocAllocator,
+    RocDocBuilder,
+};
 use ven_pretty::{text, DocAllocator};
 
 const SYNTAX_PROBLEM: &str = "SYNTAX PROBLEM";
@@ -25,11 +28,14 @@ const UNBOUND_TYPE_VARIABLE: &str = "UNBOUND TYPE VARIABLE";
 const UNUSED_ARG: &str = "UNUSED ARGUMENT";
 const MISSING_DEFINITION: &str = "MISSING DEFINITION";
 const UNKNOWN_GENERATES_WITH: &str = "UNKNOWN GENERATES FUNCTION";
+const GENERATED_NAME_TAKEN: &str = "GENERATED NAME TAKEN";
 const DUPLICATE_FIELD_NAME: &str = "DUPLICATE FIELD NAME";
 const DUPLICATE_TAG_NAME: &str = "DUPLICATE TAG NAME";
 const INVALID_UNICODE: &str = "INVALID UNICODE";
 pub const CIRCULAR_DEF: &str = "CIRCULAR DEFINITION";
 const DUPLICATE_NAME: &str = "DUPLICATE NAME";
+const SHADOWED_NAME: &str = "SHADOWED NAME";
+const FOREIGN_TYPE_NOT_C: &str = "FOREIGN TYPE NOT IN C";
 const VALUE_NOT_EXPOSED: &str = "NOT EXPOSED";
 const MODULE_NOT_IMPORTED: &str = "MODULE NOT IMPORTED";
 const NESTED_DATATYPE: &str = "NESTED DATATYPE";
@@ -60,6 +66,119 @@ const DUPLICATE_IMPLEMENTATION: &str = "DUPLICATE IMPLEMENTATION";
 const UNNECESSARY_IMPLEMENTATIONS: &str = "UNNECESSARY IMPLEMENTATIONS";
 const INCOMPLETE_ABILITY_IMPLEMENTATION: &str = "INCOMPLETE ABILITY IMPLEMENTATION";
 
+pub(crate) const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "E1059",
+        title: UNRECOGNIZED_NAME,
+        explanation: "A name was used which is not defined anywhere in scope. This is often a typo, or a value from another module which was not imported, or qualified with its module name.",
+        example: r#"main = Str.concat greting "!"
+
+greeting = "Hello""#,
+        fix: "Check the spelling of the name. Values from other modules must be qualified, like `List.map`, or exposed in the `imports` list of the module header.",
+    },
+    Explanation {
+        code: "E1001",
+        title: UNUSED_DEF,
+        explanation: "A definition is never used. This is a warning rather than an error, so the program can still be run.",
+        example: r#"main =
+    unused = 1
+    "Hello""#,
+        fix: "Remove the definition, or use it. If it is only unused for now, you can ignore the warning until it is needed.",
+    },
+    Explanation {
+        code: "E1002",
+        title: UNUSED_IMPORT,
+        explanation: "A module or value is imported but never used. This is a warning rather than an error.",
+        example: r#"interface Foo
+    exposes [main]
+    imports [Dict]
+
+main = "Hello""#,
+        fix: "Remove it from the `imports` list in the module header.",
+    },
+    Explanation {
+        code: "E1003",
+        title: UNUSED_ARG,
+        explanation: "A function never uses one of its arguments. This is a warning rather than an error, since it is often intentional, for example to match the type a higher-order function expects.",
+        example: r#"always = \value, other -> value"#,
+        fix: "If the argument is unused on purpose, prefix it with an underscore, like `_other`. Otherwise, remove it.",
+    },
+    Explanation {
+        code: "E1010",
+        title: DUPLICATE_NAME,
+        explanation: "Roc does not allow shadowing: a name can only be defined once in a given scope, including in nested scopes like function arguments and `when` branches. Two things with the same name make it easy to use the wrong one by accident.",
+        example: r#"x = 1
+
+addOne = \x -> x + 1"#,
+        fix: "Give one of the definitions a different name.",
+    },
+    Explanation {
+        code: "E1011",
+        title: SHADOWED_NAME,
+        explanation: "In the REPL, a name can be defined again inside an expression, and from then on it refers to the new value. Since that is easy to do by accident, it is a warning.",
+        example: r#"x = 1
+
+addOne = \x -> x + 1"#,
+        fix: "Give one of the definitions a different name, unless replacing the value was the point.",
+    },
+    Explanation {
+        code: "E1080",
+        title: FOREIGN_TYPE_NOT_C,
+        explanation: "A platform binds a C function, but its signature uses a type that C doesn't have, so the host couldn't be called with it. Only integers of up to 64 bits, `F32`, `F64` and `Bool` can be passed to C functions and returned from them, and `{}` can be returned from one that doesn't return anything.",
+        example: r#"platform "example"
+    requires {} { main : Str }
+    exposes []
+    packages {}
+    imports []
+    provides [mainForHost]
+
+strlen : Str -> U64
+strlen = "strlen"
+
+mainForHost : U64
+mainForHost = strlen main"#,
+        fix: "Only use C types in the signature. Anything else, like a `Str`, has to go through a hosted function instead.",
+    },
+    Explanation {
+        code: "E1014",
+        title: CIRCULAR_DEF,
+        explanation: "A value is defined in terms of itself, without a function in between to stop the recursion. Evaluating it would loop forever.",
+        example: r#"x = y + 1
+
+y = x + 1"#,
+        fix: "Break the cycle. Recursion is only allowed in functions, like `countdown = \\n -> if n == 0 then 0 else countdown (n - 1)`.",
+    },
+    Explanation {
+        code: "E1064",
+        title: VALUE_NOT_EXPOSED,
+        explanation: "A qualified name refers to a value which the module does not expose. Modules only make the values in their `exposes` list available to other modules.",
+        example: r#"main = List.isempty []"#,
+        fix: "Check the spelling of the value. If it is your own module, add the value to the `exposes` list in its header.",
+    },
+    Explanation {
+        code: "E1065",
+        title: MODULE_NOT_IMPORTED,
+        explanation: "A qualified name refers to a module which is not imported.",
+        example: r#"interface Foo
+    exposes [main]
+    imports []
+
+main = Json.toUtf8"#,
+        fix: "Add the module to the `imports` list in the module header, or check the spelling of the module name.",
+    },
+    Explanation {
+        code: "E1004",
+        title: MISSING_DEFINITION,
+        explanation: "A module exposes a name which it does not define.",
+        example: r#"interface Foo
+    exposes [foo, bar]
+    imports []
+
+foo = 1"#,
+        fix: "Define the missing value, or remove it from the `exposes` list.",
+    },
+];
+
 pub fn can_problem<'b>(
     alloc: &'b RocDocAllocator<'b>,
     lines: &LineInfo,
@@ -69,6 +188,8 @@ pub fn can_problem<'b>(
     let doc;
     let title;
     let severity = problem.severity();
+    let code = problem.code();
+    let fixes = can_problem_fixes(alloc, &problem);
 
     match problem {
         Problem::UnusedDef(symbol, region) => {
@@ -176,6 +297,29 @@ pub fn can_problem<'b>(
 
             title = UNKNOWN_GENERATES_WITH.to_string();
         }
+        Problem::GeneratedNameTaken {
+            generated,
+            original_region,
+        } => {
+            doc = alloc.stack([
+                alloc
+                    .reflow("This module asks for a generated ")
+                    .append(alloc.ident(generated.value.clone()))
+                    .append(alloc.reflow(" function:")),
+                alloc.region(lines.convert_region(generated.region)),
+                alloc.reflow("But it already has a definition by that name:"),
+                alloc.region(lines.convert_region(original_region)),
+                alloc.concat([
+                    alloc.reflow("This name is reserved by the generated Effect module. Either rename this definition, or remove "),
+                    alloc.ident(generated.value),
+                    alloc.reflow(" from the "),
+                    alloc.keyword("generates"),
+                    alloc.reflow(" list."),
+                ]),
+            ]);
+
+            title = GENERATED_NAME_TAKEN.to_string();
+        }
         Problem::UnusedArgument(closure_symbol, is_anonymous, argument_symbol, region) => {
             let line = "\". Adding an underscore at the start of a variable name is a way of saying that the variable is not used.";
 
@@ -299,6 +443,52 @@ pub fn can_problem<'b>(
             doc = res_doc;
             title = res_title.to_string();
         }
+        Problem::PermittedShadowing {
+            original_region,
+            shadow,
+        } => {
+            doc = alloc.stack([
+                alloc
+                    .text("The ")
+                    .append(alloc.ident(shadow.value.clone()))
+                    .append(alloc.reflow(" name is first defined here:")),
+                alloc.region(lines.convert_region(original_region)),
+                alloc.reflow("But then it's defined a second time here:"),
+                alloc.region(lines.convert_region(shadow.region)),
+                alloc.concat([
+                    alloc.reflow("From here on, "),
+                    alloc.ident(shadow.value),
+                    alloc.reflow(" refers to the second definition. If that's not what you meant, give one of them a different name."),
+                ]),
+            ]);
+
+            title = SHADOWED_NAME.to_string();
+        }
+        Problem::ForeignTypeNotCAbi { symbol, region } => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("The C function "),
+                    alloc.symbol_unqualified(symbol),
+                    alloc.reflow(" is declared with a type that C doesn't have:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.concat([
+                    alloc.reflow(
+                        "A C function can only take and return integers of up to 64 bits, ",
+                    ),
+                    alloc.type_str("F32"),
+                    alloc.reflow(", "),
+                    alloc.type_str("F64"),
+                    alloc.reflow(" and "),
+                    alloc.type_str("Bool"),
+                    alloc.reflow(". It returns "),
+                    alloc.type_str("{}"),
+                    alloc.reflow(" when it doesn't return anything."),
+                ]),
+            ]);
+
+            title = FOREIGN_TYPE_NOT_C.to_string();
+        }
         Problem::CyclicAlias(symbol, region, others, alias_kind) => {
             let answer = crate::error::r#type::cyclic_alias(
                 alloc, lines, symbol, region, others, alias_kind,
@@ -413,14 +603,17 @@ pub fn can_problem<'b>(
             field_region,
             record_region,
         } => {
-            return to_invalid_optional_value_report(
-                alloc,
-                lines,
-                filename,
-                field_name,
-                field_region,
-                record_region,
-            );
+            return Report {
+                code: Some(code),
+                ..to_invalid_optional_value_report(
+                    alloc,
+                    lines,
+                    filename,
+                    field_name,
+                    field_region,
+                    record_region,
+                )
+            };
         }
         Problem::DuplicateRecordFieldType {
             field_name,
@@ -1101,9 +1294,100 @@ pub fn can_problem<'b>(
         filename,
         doc,
         severity,
+        fixes,
+        code: Some(code),
     }
 }
 
+fn can_problem_fixes(alloc: &RocDocAllocator, problem: &Problem) -> Vec<Fix> {
+    match problem {
+        Problem::UnusedImport(symbol, region) => vec![Fix {
+            description: format!(
+                "Remove the unused import of `{}`",
+                symbol.as_str(alloc.interns)
+            ),
+            edits: vec![remove_list_item(&alloc.src_lines.join("\n"), *region)],
+            machine_applicable: true,
+        }],
+        Problem::UnusedModuleImport(module_id, region) if !region.is_empty() => vec![Fix {
+            description: format!(
+                "Remove the unused import of `{}`",
+                alloc.interns.module_name(*module_id)
+            ),
+            edits: vec![remove_list_item(&alloc.src_lines.join("\n"), *region)],
+            machine_applicable: true,
+        }],
+        Problem::RuntimeError(RuntimeError::LookupNotInScope {
+            loc_name,
+            suggestion_options,
+            underscored_suggestion_region,
+        }) => {
+            let mut fixes = Vec::new();
+
+            if let Some(underscored_region) = underscored_suggestion_region {
+                fixes.push(Fix {
+                    description: format!("Rename `_{0}` to `{0}`", loc_name.value),
+                    edits: vec![Edit {
+                        region: *underscored_region,
+                        replacement: loc_name.value.to_string(),
+                    }],
+                    machine_applicable: false,
+                });
+            }
+
+            let suggestions =
+                unrecognized_name_suggestions(alloc, loc_name.value.as_str(), suggestion_options);
+
+            fixes.extend(suggestions.into_iter().map(|suggestion| Fix {
+                description: format!("Change `{}` to `{suggestion}`", loc_name.value),
+                edits: vec![Edit {
+                    region: loc_name.region,
+                    replacement: suggestion,
+                }],
+                machine_applicable: false,
+            }));
+
+            fixes
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The edit that removes an element from a comma-separated list like `imports [a, b, c]`,
+/// along with the comma which separates it from its neighbors. When it's the only value
+/// exposed from an import, e.g. `pf.Task.{ Task }`, this removes the braces too.
+fn remove_list_item(src: &str, region: Region) -> Edit {
+    let start = region.start().offset as usize;
+    let end = region.end().offset as usize;
+    let before = src[..start].trim_end();
+    let after = src[end..].trim_start();
+
+    let (start, end) = if let Some(rest) = after.strip_prefix(',') {
+        let next = rest.trim_start();
+
+        if next.starts_with([']', '}']) {
+            // The last element, followed by a trailing comma
+            (before.len(), src.len() - rest.len())
+        } else {
+            (start, src.len() - next.len())
+        }
+    } else if before.ends_with(',') {
+        (before.len() - 1, end)
+    } else if let (Some(module), true) = (before.strip_suffix('{'), after.starts_with('}')) {
+        match module.trim_end().strip_suffix('.') {
+            Some(module) => (module.len(), src.len() - after.len() + 1),
+            None => (start, end),
+        }
+    } else {
+        (start, end)
+    };
+
+    Edit::delete(Region::new(
+        Position::new(start as u32),
+        Position::new(end as u32),
+    ))
+}
+
 fn list_builtin_abilities<'a>(alloc: &'a RocDocAllocator<'a>) -> RocDocBuilder<'a> {
     alloc.intersperse(
         DERIVABLE_ABILITIES
@@ -1134,6 +1418,8 @@ fn to_invalid_optional_value_report<'b>(
         filename,
         doc,
         severity: Severity::RuntimeError,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
@@ -1608,6 +1894,7 @@ fn pretty_runtime_error<'b>(
             original_region,
             shadow,
             kind,
+            original_symbol: _,
         } => {
             (title, doc) = report_shadowing(alloc, lines, original_region, shadow, kind);
         }
@@ -1726,6 +2013,7 @@ fn pretty_runtime_error<'b>(
 
         RuntimeError::ModuleNotImported {
             module_name,
+            ident,
             imported_modules,
             region,
             module_exists,
@@ -1735,6 +2023,7 @@ fn pretty_runtime_error<'b>(
                 lines,
                 region,
                 &module_name,
+                &ident,
                 imported_modules,
                 module_exists,
             );
@@ -2264,11 +2553,7 @@ fn not_found<'b>(
     options: MutSet<Box<str>>,
     underscored_suggestion_region: Option<Region>,
 ) -> RocDocBuilder<'b> {
-    let mut suggestions = suggest::sort(
-        name.as_inline_str().as_str(),
-        options.iter().map(|v| v.as_ref()).collect(),
-    );
-    suggestions.truncate(4);
+    let suggestions = unrecognized_name_suggestions(alloc, name.as_inline_str().as_str(), &options);
 
     let default_no = alloc.concat([
         alloc.reflow("Is there an "),
@@ -2295,7 +2580,7 @@ fn not_found<'b>(
             alloc.stack([
                 yes_suggestion_details,
                 alloc
-                    .vcat(suggestions.into_iter().map(|v| alloc.string(v.to_string())))
+                    .vcat(suggestions.into_iter().map(|v| alloc.string(v)))
                     .indent(4),
             ])
         }
@@ -2312,6 +2597,65 @@ fn not_found<'b>(
     ])
 }
 
+/// Suggestions for an unrecognized name: values from imported modules with (nearly) the
+/// same name, e.g. `Bool.true` for `true`, followed by similar names that are in scope.
+fn unrecognized_name_suggestions(
+    alloc: &RocDocAllocator,
+    name: &str,
+    options: &MutSet<Box<str>>,
+) -> Vec<String> {
+    // Short names have to match exactly, or almost anything would be suggested.
+    let max_distance = name.len() / 4;
+
+    let mut suggestions = imported_suggestions(alloc, |module_name, ident| {
+        let distance = distance::damerau_levenshtein(name, ident);
+
+        (distance <= max_distance).then(|| (distance, format!("{module_name}.{ident}")))
+    });
+
+    let in_scope = suggest::sort(name, options.iter().map(|v| v.as_ref()).collect());
+
+    suggestions.extend(in_scope.into_iter().map(|v| v.to_string()));
+    suggestions.truncate(4);
+
+    suggestions
+}
+
+/// Values from imported modules which have exactly the given name, in a module whose name
+/// is similar to the given one, e.g. `List.map` for `Lst.map`.
+fn qualified_name_suggestions(alloc: &RocDocAllocator, module: &str, name: &str) -> Vec<String> {
+    let max_distance = (module.len() / 4).max(1);
+
+    imported_suggestions(alloc, |module_name, ident| {
+        let distance = distance::damerau_levenshtein(module, module_name);
+
+        (ident == name && distance <= max_distance)
+            .then(|| (distance, format!("{module_name}.{ident}")))
+    })
+}
+
+/// The qualified names of the symbols exposed by imported modules which `rank` picks,
+/// best first.
+fn imported_suggestions(
+    alloc: &RocDocAllocator,
+    rank: impl Fn(&str, &str) -> Option<(usize, String)>,
+) -> Vec<String> {
+    let mut ranked: Vec<(usize, String)> = alloc
+        .imported_symbols
+        .iter()
+        .filter_map(|symbol| {
+            rank(
+                symbol.module_string(alloc.interns).as_str(),
+                symbol.as_str(alloc.interns),
+            )
+        })
+        .collect();
+
+    ranked.sort();
+
+    ranked.into_iter().map(|(_, qualified)| qualified).collect()
+}
+
 /// Generate a message informing the user that a module was referenced, but not found
 ///
 /// See [`roc_problem::can::ModuleNotImported`]
@@ -2320,6 +2664,7 @@ fn module_not_found<'b>(
     lines: &LineInfo,
     region: roc_region::all::Region,
     name: &ModuleName,
+    ident: &Ident,
     options: MutSet<Box<str>>,
     module_exists: bool,
 ) -> RocDocBuilder<'b> {
@@ -2328,9 +2673,18 @@ fn module_not_found<'b>(
         // TODO:  Maybe give an example of how to do that
         alloc.reflow("Did you mean to import it?")
     } else {
-        // If the module might not exist, sugguest that it's a typo
-        let mut suggestions =
-            suggest::sort(name.as_str(), options.iter().map(|v| v.as_ref()).collect());
+        // If the module might not exist, sugguest that it's a typo. If an imported module
+        // with a similar name has this value, e.g. `List.map` for `Lst.map`, that's likely it.
+        let mut suggestions = qualified_name_suggestions(alloc, name.as_str(), ident.as_str());
+
+        if suggestions.is_empty() {
+            suggestions =
+                suggest::sort(name.as_str(), options.iter().map(|v| v.as_ref()).collect())
+                    .into_iter()
+                    .map(|v| v.to_string())
+                    .collect();
+        }
+
         suggestions.truncate(4);
 
         if suggestions.is_empty() {
@@ -2346,7 +2700,7 @@ fn module_not_found<'b>(
             alloc.stack([
                 alloc.reflow("Is there an import missing? Perhaps there is a typo. Did you mean one of these?"),
                 alloc
-                    .vcat(suggestions.into_iter().map(|v| alloc.string(v.to_string())))
+                    .vcat(suggestions.into_iter().map(|v| alloc.string(v)))
                     .indent(4),
             ])
         }
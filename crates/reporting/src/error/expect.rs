@@ -72,6 +72,30 @@ impl<'a> Renderer<'a> {
         ])
     }
 
+    fn render_argument(
+        &'a self,
+        index: usize,
+        name: Option<Symbol>,
+        expr: &Expr<'_>,
+        error_type: ErrorType,
+    ) -> RocDocBuilder<'a> {
+        use roc_fmt::annotation::Formattable;
+        use ven_pretty::DocAllocator;
+
+        let mut buf = roc_fmt::Buf::new_in(self.arena);
+        expr.format(&mut buf, 0);
+
+        let label = || match name {
+            Some(symbol) => self.alloc.symbol_unqualified(symbol),
+            None => self.alloc.text(format!("argument {}", index + 1)),
+        };
+
+        self.alloc.vcat([
+            label().append(" : ").append(self.render_expr(error_type)),
+            label().append(" = ").append(buf.into_bump_str()),
+        ])
+    }
+
     fn render_lookups(
         &'a self,
         subs: &mut Subs,
@@ -156,6 +180,8 @@ impl<'a> Renderer<'a> {
             doc,
             filename: self.filename.clone(),
             severity: Severity::RuntimeError,
+            fixes: Vec::new(),
+            code: None,
         };
 
         let mut buf = String::new();
@@ -227,6 +253,70 @@ impl<'a> Renderer<'a> {
             doc,
             filename: self.filename.clone(),
             severity: Severity::RuntimeError,
+            fixes: Vec::new(),
+            code: None,
+        };
+
+        let mut buf = String::new();
+
+        report.render(
+            self.render_target,
+            &mut buf,
+            &self.alloc,
+            &crate::report::DEFAULT_PALETTE,
+        );
+
+        write!(writer, "{buf}")
+    }
+
+    /// Reports the arguments that made the expects in a function fail, when it was called with
+    /// generated arguments. The failures themselves are reported separately.
+    pub fn render_counterexample<W>(
+        &self,
+        writer: &mut W,
+        subs: &mut Subs,
+        function: Symbol,
+        arguments: &[(Option<Symbol>, Variable, Expr<'_>)],
+        runs: usize,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        use crate::report::Report;
+        use ven_pretty::DocAllocator;
+
+        let it = arguments
+            .iter()
+            .enumerate()
+            .map(|(index, (name, variable, expr))| {
+                let error_type = subs.var_to_error_type(*variable, Polarity::OF_VALUE);
+                self.render_argument(index, *name, expr, error_type)
+            });
+
+        let runs = match runs {
+            1 => "the first run".to_string(),
+            n => format!("{n} runs"),
+        };
+
+        let doc = self.alloc.stack([
+            self.alloc.concat([
+                self.alloc.text("The expectations in "),
+                self.alloc.symbol_unqualified(function),
+                self.alloc.text(format!(
+                    " failed after {runs}. The smallest arguments I found that make them fail are:"
+                )),
+            ]),
+            self.alloc.stack(it).indent(4),
+            self.alloc.text(""), // Blank line at the end
+        ]);
+
+        let report = Report {
+            title: "PROPERTY FAILED".into(),
+            doc,
+            filename: self.filename.clone(),
+            severity: Severity::RuntimeError,
+            fixes: Vec::new(),
+            code: None,
         };
 
         let mut buf = String::new();
@@ -2,3 +2,20 @@ pub mod canonicalize;
 pub mod expect;
 pub mod parse;
 pub mod r#type;
+
+use crate::report::Explanation;
+
+/// Every report kind which has an extended explanation, for `roc explain`.
+pub fn explanations() -> impl Iterator<Item = &'static Explanation> {
+    canonicalize::EXPLANATIONS
+        .iter()
+        .chain(r#type::EXPLANATIONS)
+        .chain(parse::EXPLANATIONS)
+}
+
+/// Look up the explanation for a code like `E2001`, as shown in the report's header.
+pub fn explain(code: &str) -> Option<&'static Explanation> {
+    let code = code.trim();
+
+    explanations().find(|explanation| explanation.code.eq_ignore_ascii_case(code))
+}
@@ -3,9 +3,42 @@ use roc_problem::Severity;
 use roc_region::all::{LineColumn, LineColumnRegion, LineInfo, Position, Region};
 use std::path::PathBuf;
 
-use crate::report::{Report, RocDocAllocator, RocDocBuilder};
+use crate::report::{Explanation, Report, RocDocAllocator, RocDocBuilder};
 use ven_pretty::DocAllocator;
 
+pub(crate) const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "E3002",
+        title: "ARGUMENTS BEFORE EQUALS",
+        explanation: "A definition has arguments before its `=`, like a function definition in Elm or Haskell. In Roc, a function is a value like any other, so it is defined with a lambda.",
+        example: r#"add a b = a + b"#,
+        fix: "Move the arguments into a lambda, like `add = \\a, b -> a + b`.",
+    },
+    Explanation {
+        code: "E3029",
+        title: "MISSING FINAL EXPRESSION",
+        explanation: "A block of nested definitions has to end with an expression, which is the value of the whole block. The definitions are only visible to the lines after them, so a block with nothing after its last definition has no value.",
+        example: r#"main =
+    greeting = "Hello"
+"#,
+        fix: "Add the expression the block evaluates to after the last definition, like `greeting` here.",
+    },
+    Explanation {
+        code: "E3041",
+        title: "TAB CHARACTER",
+        explanation: "Roc source files use spaces for indentation, and tab characters are not allowed anywhere outside of string literals.",
+        example: "main =\n\t\"Hello\"",
+        fix: "Replace the tabs with spaces. `roc format` indents with four spaces.",
+    },
+    Explanation {
+        code: "E3053",
+        title: "UNFINISHED PARENTHESES",
+        explanation: "An opening parenthesis has no matching closing parenthesis.",
+        example: r#"main = Num.toStr (1 + 2"#,
+        fix: "Add the missing `)`.",
+    },
+];
+
 pub fn parse_problem<'a>(
     alloc: &'a RocDocAllocator<'a>,
     lines: &LineInfo,
@@ -13,7 +46,100 @@ pub fn parse_problem<'a>(
     _starting_line: u32,
     parse_problem: FileError<SyntaxError<'a>>,
 ) -> Report<'a> {
-    to_syntax_report(alloc, lines, filename, &parse_problem.problem.problem)
+    let report = to_syntax_report(alloc, lines, filename, &parse_problem.problem.problem);
+
+    Report {
+        code: syntax_report_code(&report.title),
+        ..report
+    }
+}
+
+/// The code shown in the report's header, and accepted by `roc explain`.
+/// Codes never change once assigned, so only append new ones.
+///
+/// The same syntax problem is found in many places, like an unfinished parenthesis in an
+/// expression, a pattern or a type, which all give a report with the same title. So the title is
+/// what tells the problems apart, rather than where the parser got stuck.
+fn syntax_report_code(title: &str) -> Option<&'static str> {
+    let code = match title {
+        "PARSE PROBLEM" => "E3001",
+        "ARGUMENTS BEFORE EQUALS" => "E3002",
+        "ASCII CONTROL CHARACTER" => "E3003",
+        "BAD BACKPASSING ARROW" => "E3004",
+        "BAD RECORD BUILDER" => "E3005",
+        "BAD RECORD UPDATE" => "E3006",
+        "BAD REQUIRES RIGIDS" => "E3007",
+        "BAD REQUIRES" => "E3008",
+        "BAD TYPE VARIABLE" => "E3009",
+        "DOUBLE COMMA" => "E3010",
+        "DOUBLE DOT" => "E3011",
+        "EMPTY PARENTHESES" => "E3012",
+        "END OF FILE" => "E3013",
+        "ENDLESS FORMAT" => "E3014",
+        "ENDLESS SCALAR" => "E3015",
+        "ENDLESS STRING" => "E3016",
+        "EXPECTED STRING" => "E3017",
+        "IF GUARD NO CONDITION" => "E3018",
+        "INCOMPLETE HEADER" => "E3019",
+        "INCORRECT REST PATTERN" => "E3020",
+        "INDENT ENDS AFTER EXPRESSION" => "E3021",
+        "INVALID NUMBER LITERAL" => "E3022",
+        "INVALID PACKAGE NAME" => "E3023",
+        "INVALID PLATFORM NAME" => "E3024",
+        "INVALID SCALAR" => "E3025",
+        "MISPLACED CARRIAGE RETURN" => "E3026",
+        "MISSING ARROW" => "E3027",
+        "MISSING EXPRESSION" => "E3028",
+        "MISSING FINAL EXPRESSION" => "E3029",
+        "MISSING HEADER" => "E3030",
+        "MISSING PACKAGES" => "E3031",
+        "MISSING REQUIRES" => "E3032",
+        "NEED MORE INDENTATION" => "E3033",
+        "NOT AN INLINE ALIAS" => "E3034",
+        "NOT END OF FILE" => "E3035",
+        "PROBLEM IN RECORD PATTERN" => "E3036",
+        "PROBLEM IN RECORD TYPE" => "E3037",
+        "QUALIFIED ALIAS NAME" => "E3038",
+        "RECORD PARSE PROBLEM" => "E3039",
+        "SYNTAX PROBLEM" => "E3040",
+        "TAB CHARACTER" => "E3041",
+        "TRAILING DOT" => "E3042",
+        "TRAILING OPERATOR" => "E3043",
+        "TYPE ARGUMENT NOT LOWERCASE" => "E3044",
+        "UNEXPECTED ARROW" => "E3045",
+        "UNFINISHED ABILITY" => "E3046",
+        "UNFINISHED ARGUMENT LIST" => "E3047",
+        "UNFINISHED FUNCTION" => "E3048",
+        "UNFINISHED IF" => "E3049",
+        "UNFINISHED INLINE ALIAS" => "E3050",
+        "UNFINISHED LIST PATTERN" => "E3051",
+        "UNFINISHED LIST" => "E3052",
+        "UNFINISHED PARENTHESES" => "E3053",
+        "UNFINISHED PATTERN" => "E3054",
+        "UNFINISHED RECORD PATTERN" => "E3055",
+        "UNFINISHED RECORD TYPE" => "E3056",
+        "UNFINISHED TAG UNION TYPE" => "E3057",
+        "UNFINISHED TYPE" => "E3058",
+        "UNFINISHED WHEN" => "E3059",
+        "UNKNOWN OPERATOR" => "E3060",
+        "WEIRD APP NAME" => "E3061",
+        "WEIRD ARROW" => "E3062",
+        "WEIRD CODE POINT" => "E3063",
+        "WEIRD ESCAPE" => "E3064",
+        "WEIRD EXPOSES" => "E3065",
+        "WEIRD GENERATED TYPE NAME" => "E3066",
+        "WEIRD GENERATES" => "E3067",
+        "WEIRD IDENTIFIER" => "E3068",
+        "WEIRD IMPORTS" => "E3069",
+        "WEIRD MODULE NAME" => "E3070",
+        "WEIRD PACKAGES LIST" => "E3071",
+        "WEIRD PROVIDES" => "E3072",
+        "WEIRD QUALIFIED NAME" => "E3073",
+        "WEIRD TAG NAME" => "E3074",
+        _ => return None,
+    };
+
+    Some(code)
 }
 
 fn note_for_record_type_indent<'a>(alloc: &'a RocDocAllocator<'a>) -> RocDocBuilder<'a> {
@@ -65,6 +191,8 @@ fn to_syntax_report<'a>(
         doc,
         title: "PARSE PROBLEM".to_string(),
         severity: Severity::RuntimeError,
+        fixes: Vec::new(),
+        code: None,
     };
 
     match parse_problem {
@@ -79,6 +207,8 @@ fn to_syntax_report<'a>(
                 doc,
                 title: "PARSE PROBLEM".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Unexpected(region) => {
@@ -111,6 +241,8 @@ fn to_syntax_report<'a>(
                 doc,
                 title: "NOT END OF FILE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         SyntaxError::Eof(region) => {
@@ -124,6 +256,8 @@ fn to_syntax_report<'a>(
                 doc,
                 title: "PARSE PROBLEM".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         SyntaxError::OutdentedTooFar => {
@@ -134,6 +268,8 @@ fn to_syntax_report<'a>(
                 doc,
                 title: "PARSE PROBLEM".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Type(typ) => to_type_report(alloc, lines, filename, typ, Position::default()),
@@ -215,6 +351,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "ARGUMENTS BEFORE EQUALS".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -329,6 +467,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "UNKNOWN OPERATOR".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -355,6 +495,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "WEIRD IDENTIFIER".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -437,6 +579,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: title.to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -465,6 +609,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "MISSING FINAL EXPRESSION".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -496,6 +642,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "SYNTAX PROBLEM".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -519,6 +667,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "ARGUMENTS BEFORE EQUALS".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -537,6 +687,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "BAD BACKPASSING ARROW".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -555,6 +707,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "RECORD PARSE PROBLEM".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -575,6 +729,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "BAD RECORD BUILDER".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -595,6 +751,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "BAD RECORD UPDATE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -661,6 +819,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "INDENT ENDS AFTER EXPRESSION".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EExpr::Expect(e_expect, _position) => {
@@ -685,6 +845,8 @@ fn to_expr_report<'a>(
                 doc,
                 title: "TRAILING OPERATOR".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         _ => todo!("unhandled parse error: {:?}", parse_problem),
@@ -723,6 +885,8 @@ fn to_lambda_report<'a>(
                     doc,
                     title: "WEIRD ARROW".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             _ => {
@@ -745,6 +909,8 @@ fn to_lambda_report<'a>(
                     doc,
                     title: "MISSING ARROW".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
         },
@@ -770,6 +936,8 @@ fn to_lambda_report<'a>(
                     doc,
                     title: "WEIRD ARROW".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             _ => {
@@ -792,6 +960,8 @@ fn to_lambda_report<'a>(
                     doc,
                     title: "MISSING ARROW".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
         },
@@ -816,6 +986,8 @@ fn to_lambda_report<'a>(
                     doc,
                     title: "UNFINISHED ARGUMENT LIST".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             _ => {
@@ -837,6 +1009,8 @@ fn to_lambda_report<'a>(
                     doc,
                     title: "MISSING ARROW".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
         },
@@ -918,6 +1092,8 @@ fn to_unfinished_lambda_report<'a>(
         doc,
         title: "UNFINISHED FUNCTION".to_string(),
         severity: Severity::RuntimeError,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
@@ -984,6 +1160,8 @@ fn to_str_report<'a>(
                 doc,
                 title: "WEIRD ESCAPE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EString::CodePtOpen(pos) | EString::CodePtEnd(pos) => {
@@ -1010,6 +1188,8 @@ fn to_str_report<'a>(
                 doc,
                 title: "WEIRD CODE POINT".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EString::FormatEnd(pos) => {
@@ -1031,6 +1211,8 @@ fn to_str_report<'a>(
                 doc,
                 title: "ENDLESS FORMAT".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EString::EndlessSingleQuote(pos) => {
@@ -1054,6 +1236,8 @@ fn to_str_report<'a>(
                 doc,
                 title: "ENDLESS SCALAR".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EString::InvalidSingleQuote(e, pos) => {
@@ -1120,6 +1304,8 @@ fn to_str_report<'a>(
                 doc,
                 title: "INVALID SCALAR".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EString::EndlessSingleLine(pos) => {
@@ -1143,6 +1329,8 @@ fn to_str_report<'a>(
                 doc,
                 title: "ENDLESS STRING".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EString::ExpectedDoubleQuoteGotSingleQuote(pos) => {
@@ -1167,6 +1355,8 @@ fn to_str_report<'a>(
                 doc,
                 title: "EXPECTED STRING".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EString::EndlessMultiLine(pos) => {
@@ -1190,6 +1380,8 @@ fn to_str_report<'a>(
                 doc,
                 title: "ENDLESS STRING".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EString::MultilineInsufficientIndent(pos) => {
@@ -1211,6 +1403,8 @@ fn to_str_report<'a>(
                 doc,
                 title: "INSUFFICIENT INDENT IN MULTI-LINE STRING".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
     }
@@ -1253,6 +1447,8 @@ fn to_expr_in_parens_report<'a>(
                 doc,
                 title: "EMPTY PARENTHESES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EInParens::End(pos) => {
@@ -1277,6 +1473,8 @@ fn to_expr_in_parens_report<'a>(
                 doc,
                 title: "UNFINISHED PARENTHESES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EInParens::Open(pos) => {
@@ -1302,6 +1500,8 @@ fn to_expr_in_parens_report<'a>(
                 doc,
                 title: "UNFINISHED PARENTHESES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
     }
@@ -1352,6 +1552,8 @@ fn to_list_report<'a>(
                         doc,
                         title: "UNFINISHED LIST".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 _ => {
@@ -1385,6 +1587,8 @@ fn to_list_report<'a>(
                         doc,
                         title: "UNFINISHED LIST".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
             }
@@ -1532,6 +1736,8 @@ fn to_unfinished_if_report<'a>(
         doc,
         title: "UNFINISHED IF".to_string(),
         severity: Severity::RuntimeError,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
@@ -1565,6 +1771,8 @@ fn to_when_report<'a>(
                         doc,
                         title: "IF GUARD NO CONDITION".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 _ => to_expr_report(
@@ -1597,6 +1805,8 @@ fn to_when_report<'a>(
                 doc,
                 title: "MISSING ARROW".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -1752,6 +1962,8 @@ fn to_unfinished_when_report<'a>(
                 doc,
                 title: "UNFINISHED WHEN".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
     }
@@ -1792,6 +2004,8 @@ fn to_unexpected_arrow_report<'a>(
         doc,
         title: "UNEXPECTED ARROW".to_string(),
         severity: Severity::RuntimeError,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
@@ -1868,6 +2082,8 @@ fn to_pattern_report<'a>(
                 doc,
                 title: "UNFINISHED PATTERN".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EPattern::Record(record, pos) => to_precord_report(alloc, lines, filename, record, *pos),
@@ -1912,6 +2128,8 @@ fn to_precord_report<'a>(
                     doc,
                     title: "UNFINISHED RECORD PATTERN".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             _ => {
@@ -1929,6 +2147,8 @@ fn to_precord_report<'a>(
                     doc,
                     title: "UNFINISHED RECORD PATTERN".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
         },
@@ -1954,6 +2174,8 @@ fn to_precord_report<'a>(
                         doc,
                         title: "UNFINISHED RECORD PATTERN".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 _ => {
@@ -1974,6 +2196,8 @@ fn to_precord_report<'a>(
                         doc,
                         title: "UNFINISHED RECORD PATTERN".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
             }
@@ -1999,6 +2223,8 @@ fn to_precord_report<'a>(
                     doc,
                     title: "UNFINISHED RECORD PATTERN".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             Next::Other(Some(',')) => todo!(),
@@ -2024,6 +2250,8 @@ fn to_precord_report<'a>(
                     doc,
                     title: "PROBLEM IN RECORD PATTERN".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
         },
@@ -2077,6 +2305,8 @@ fn to_plist_report<'a>(
                 doc,
                 title: "UNFINISHED LIST PATTERN".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2099,6 +2329,8 @@ fn to_plist_report<'a>(
                 doc,
                 title: "UNFINISHED LIST PATTERN".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2121,6 +2353,8 @@ fn to_plist_report<'a>(
                 doc,
                 title: "INCORRECT REST PATTERN".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2164,6 +2398,8 @@ fn to_pattern_in_parens_report<'a>(
                 doc,
                 title: "UNFINISHED PARENTHESES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2185,6 +2421,8 @@ fn to_pattern_in_parens_report<'a>(
                 doc,
                 title: "EMPTY PARENTHESES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2209,6 +2447,8 @@ fn to_pattern_in_parens_report<'a>(
                 doc,
                 title: "UNFINISHED PARENTHESES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2237,6 +2477,8 @@ fn to_malformed_number_literal_report<'a>(
         doc,
         title: "INVALID NUMBER LITERAL".to_string(),
         severity: Severity::RuntimeError,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
@@ -2277,6 +2519,8 @@ fn to_type_report<'a>(
                         doc,
                         title: "DOUBLE COMMA".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 _ => todo!(),
@@ -2304,6 +2548,8 @@ fn to_type_report<'a>(
                 doc,
                 title: "UNFINISHED TYPE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2322,6 +2568,8 @@ fn to_type_report<'a>(
                 doc,
                 title: "UNFINISHED TYPE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2340,6 +2588,8 @@ fn to_type_report<'a>(
                 doc,
                 title: "UNFINISHED TYPE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2358,6 +2608,8 @@ fn to_type_report<'a>(
                 doc,
                 title: "UNFINISHED INLINE ALIAS".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2375,6 +2627,8 @@ fn to_type_report<'a>(
                 doc,
                 title: "BAD TYPE VARIABLE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2412,6 +2666,8 @@ fn to_trecord_report<'a>(
                     doc,
                     title: "UNFINISHED RECORD TYPE".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             _ => {
@@ -2433,6 +2689,8 @@ fn to_trecord_report<'a>(
                     doc,
                     title: "UNFINISHED RECORD TYPE".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
         },
@@ -2458,6 +2716,8 @@ fn to_trecord_report<'a>(
                         doc,
                         title: "UNFINISHED RECORD TYPE".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 _ => {
@@ -2478,6 +2738,8 @@ fn to_trecord_report<'a>(
                         doc,
                         title: "UNFINISHED RECORD TYPE".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
             }
@@ -2503,6 +2765,8 @@ fn to_trecord_report<'a>(
                     doc,
                     title: "UNFINISHED RECORD TYPE".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             Next::Other(Some(',')) => todo!(),
@@ -2528,6 +2792,8 @@ fn to_trecord_report<'a>(
                     doc,
                     title: "PROBLEM IN RECORD TYPE".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
         },
@@ -2561,6 +2827,8 @@ fn to_trecord_report<'a>(
                 doc,
                 title: "UNFINISHED RECORD TYPE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2585,6 +2853,8 @@ fn to_trecord_report<'a>(
                         doc,
                         title: "NEED MORE INDENTATION".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 None => {
@@ -2610,6 +2880,8 @@ fn to_trecord_report<'a>(
                         doc,
                         title: "UNFINISHED RECORD TYPE".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
             }
@@ -2657,6 +2929,8 @@ fn to_ttag_union_report<'a>(
                     doc,
                     title: "UNFINISHED TAG UNION TYPE".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             Next::Other(Some(c)) if c.is_alphabetic() => {
@@ -2679,6 +2953,8 @@ fn to_ttag_union_report<'a>(
                     doc,
                     title: "WEIRD TAG NAME".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             _ => {
@@ -2700,6 +2976,8 @@ fn to_ttag_union_report<'a>(
                     doc,
                     title: "UNFINISHED TAG UNION TYPE".to_string(),
                     severity: Severity::RuntimeError,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
         },
@@ -2726,6 +3004,8 @@ fn to_ttag_union_report<'a>(
                         doc,
                         title: "WEIRD TAG NAME".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 _ => {
@@ -2746,6 +3026,8 @@ fn to_ttag_union_report<'a>(
                         doc,
                         title: "UNFINISHED TAG UNION TYPE".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
             }
@@ -2789,6 +3071,8 @@ fn to_tinparens_report<'a>(
                         doc,
                         title: "UNFINISHED PARENTHESES".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 Next::Other(Some(c)) if c.is_alphabetic() => {
@@ -2811,6 +3095,8 @@ fn to_tinparens_report<'a>(
                         doc,
                         title: "WEIRD TAG NAME".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 _ => {
@@ -2834,6 +3120,8 @@ fn to_tinparens_report<'a>(
                         doc,
                         title: "UNFINISHED PARENTHESES".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
             }
@@ -2857,6 +3145,8 @@ fn to_tinparens_report<'a>(
                 doc,
                 title: "EMPTY PARENTHESES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2883,6 +3173,8 @@ fn to_tinparens_report<'a>(
                         doc,
                         title: "WEIRD TAG NAME".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 _ => {
@@ -2903,6 +3195,8 @@ fn to_tinparens_report<'a>(
                         doc,
                         title: "UNFINISHED PARENTHESES".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
             }
@@ -2931,6 +3225,8 @@ fn to_tinparens_report<'a>(
                 doc,
                 title: "UNFINISHED PARENTHESES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -2955,6 +3251,8 @@ fn to_tinparens_report<'a>(
                         doc,
                         title: "NEED MORE INDENTATION".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 None => {
@@ -2980,6 +3278,8 @@ fn to_tinparens_report<'a>(
                         doc,
                         title: "UNFINISHED PARENTHESES".to_string(),
                         severity: Severity::RuntimeError,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
             }
@@ -3013,6 +3313,8 @@ fn to_tapply_report<'a>(
                 doc,
                 title: "DOUBLE DOT".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         ETypeApply::TrailingDot(pos) => {
@@ -3035,6 +3337,8 @@ fn to_tapply_report<'a>(
                 doc,
                 title: "TRAILING DOT".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         ETypeApply::StartIsNumber(pos) => {
@@ -3057,6 +3361,8 @@ fn to_tapply_report<'a>(
                 doc,
                 title: "WEIRD QUALIFIED NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         ETypeApply::StartNotUppercase(pos) => {
@@ -3079,6 +3385,8 @@ fn to_tapply_report<'a>(
                 doc,
                 title: "WEIRD QUALIFIED NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3097,6 +3405,8 @@ fn to_tapply_report<'a>(
                 doc,
                 title: "END OF FILE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3137,6 +3447,8 @@ fn to_talias_report<'a>(
                 doc,
                 title: "NOT AN INLINE ALIAS".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         ETypeInlineAlias::Qualified(pos) => {
@@ -3153,6 +3465,8 @@ fn to_talias_report<'a>(
                 doc,
                 title: "QUALIFIED ALIAS NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         ETypeInlineAlias::ArgumentNotLowercase(pos) => {
@@ -3169,6 +3483,8 @@ fn to_talias_report<'a>(
                 doc,
                 title: "TYPE ARGUMENT NOT LOWERCASE".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
     }
@@ -3215,6 +3531,8 @@ fn to_header_report<'a>(
                 doc,
                 title: "INCOMPLETE HEADER".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3253,6 +3571,8 @@ fn to_header_report<'a>(
                 doc,
                 title: "MISSING HEADER".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3277,6 +3597,8 @@ fn to_header_report<'a>(
                 doc,
                 title: "WEIRD MODULE NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3304,6 +3626,8 @@ fn to_header_report<'a>(
                 doc,
                 title: "WEIRD MODULE NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3328,6 +3652,8 @@ fn to_header_report<'a>(
                 doc,
                 title: "WEIRD APP NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3350,6 +3676,8 @@ fn to_header_report<'a>(
                 doc,
                 title: "INVALID PACKAGE NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3373,6 +3701,8 @@ fn to_header_report<'a>(
                 doc,
                 title: "INVALID PLATFORM NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3396,6 +3726,8 @@ fn to_header_report<'a>(
                 doc,
                 title: "WEIRD GENERATED TYPE NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EHeader::GeneratesWith(generates_with, pos) => {
@@ -3436,6 +3768,8 @@ fn to_generates_with_report<'a>(
                 doc,
                 title: "WEIRD GENERATES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3461,6 +3795,8 @@ fn to_generates_with_report<'a>(
                 doc,
                 title: "WEIRD GENERATES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3502,6 +3838,8 @@ fn to_provides_report<'a>(
                 doc,
                 title: "WEIRD PROVIDES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3527,6 +3865,8 @@ fn to_provides_report<'a>(
                 doc,
                 title: "WEIRD PROVIDES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3554,6 +3894,8 @@ fn to_provides_report<'a>(
                 doc,
                 title: "WEIRD PROVIDES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3575,6 +3917,8 @@ fn to_provides_report<'a>(
                 doc,
                 title: "WEIRD PROVIDES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3613,6 +3957,8 @@ fn to_exposes_report<'a>(
                 doc,
                 title: "WEIRD EXPOSES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3638,6 +3984,8 @@ fn to_exposes_report<'a>(
                 doc,
                 title: "WEIRD EXPOSES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3677,6 +4025,8 @@ fn to_imports_report<'a>(
                 doc,
                 title: "WEIRD IMPORTS".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3702,6 +4052,8 @@ fn to_imports_report<'a>(
                 doc,
                 title: "WEIRD IMPORTS".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3728,6 +4080,8 @@ fn to_imports_report<'a>(
                 doc,
                 title: "WEIRD MODULE NAME".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3747,6 +4101,8 @@ fn to_imports_report<'a>(
                 doc,
                 title: "WEIRD IMPORTS".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3786,6 +4142,8 @@ fn to_requires_report<'a>(
                 doc,
                 title: "MISSING REQUIRES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3813,6 +4171,8 @@ fn to_requires_report<'a>(
                 doc,
                 title: "MISSING REQUIRES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3842,6 +4202,8 @@ fn to_requires_report<'a>(
                 doc,
                 title: "BAD REQUIRES RIGIDS".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3871,6 +4233,8 @@ fn to_requires_report<'a>(
                 doc,
                 title: "BAD REQUIRES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3908,6 +4272,8 @@ fn to_packages_report<'a>(
                 doc,
                 title: "MISSING PACKAGES".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         EPackages::ListEnd(pos) => {
@@ -3930,6 +4296,8 @@ fn to_packages_report<'a>(
                 doc,
                 title: "WEIRD PACKAGES LIST".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3965,6 +4333,8 @@ fn to_space_report<'a>(
                 doc,
                 title: "TAB CHARACTER".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3982,6 +4352,8 @@ fn to_space_report<'a>(
                 doc,
                 title: "ASCII CONTROL CHARACTER".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -3999,6 +4371,8 @@ fn to_space_report<'a>(
                 doc,
                 title: "MISPLACED CARRIAGE RETURN".to_string(),
                 severity: Severity::RuntimeError,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -4080,6 +4454,8 @@ fn to_unfinished_ability_report<'a>(
         doc,
         title: "UNFINISHED ABILITY".to_string(),
         severity: Severity::RuntimeError,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
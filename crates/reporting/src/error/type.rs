@@ -1,7 +1,9 @@
 #![allow(clippy::too_many_arguments)]
 
 use crate::error::canonicalize::{to_circular_def_doc, CIRCULAR_DEF};
-use crate::report::{Annotation, Report, RocDocAllocator, RocDocBuilder};
+use crate::report::{
+    Annotation, Edit, Explanation, Fix, PlainWrite, Report, RocDocAllocator, RocDocBuilder,
+};
 use itertools::EitherOrBoth;
 use itertools::Itertools;
 use roc_can::expected::{Expected, PExpected};
@@ -28,6 +30,39 @@ use ven_pretty::{text, DocAllocator};
 
 const ADD_ANNOTATIONS: &str = r#"Can more type annotations be added? Type annotations always help me give more specific messages, and I think they could help a lot in this case"#;
 
+pub(crate) const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "E2001",
+        title: "TYPE MISMATCH",
+        explanation: "Two parts of the program disagree about the type of a value. For example, a function is called with an argument of a different type than it expects, or the branches of an `if` produce different types. The report shows both types, with the parts that differ highlighted. Calling a function with the wrong number of arguments is reported with this code too, as TOO MANY ARGS or TOO FEW ARGS; Roc does not allow functions to be partially applied.",
+        example: r#"main = Str.concat "Hello, " 42"#,
+        fix: "Change the value, or convert it to the expected type, like `Num.toStr 42`. If the types are surprising, adding type annotations helps narrow down where they come from. For a missing argument, pass it, or make partial application explicit with a closure, like `\\list -> List.map list Num.toStr`.",
+    },
+    Explanation {
+        code: "E2008",
+        title: "UNSAFE PATTERN",
+        explanation: "A `when` expression or destructuring pattern does not cover every possible value, so the program could crash on values the patterns don't match.",
+        example: r#"describe : [Red, Green, Blue] -> Str
+describe = \color ->
+    when color is
+        Red -> "red"
+        Green -> "green"
+
+main = describe Blue"#,
+        fix: "Add the missing branches, or add a `_` branch which matches everything else.",
+    },
+    Explanation {
+        code: "E2009",
+        title: "REDUNDANT PATTERN",
+        explanation: "A branch of a `when` expression can never match, because the branches before it already cover every value it would match.",
+        example: r#"isZero = \n ->
+    when n is
+        _ -> Bool.false
+        0 -> Bool.true"#,
+        fix: "Remove the branch, or move it before the branch which covers it.",
+    },
+];
+
 const OPAQUE_NUM_SYMBOLS: &[Symbol] = &[
     Symbol::NUM_NUM,
     Symbol::NUM_INTEGER,
@@ -39,6 +74,20 @@ pub fn type_problem<'b>(
     lines: &LineInfo,
     filename: PathBuf,
     problem: TypeError,
+) -> Option<Report<'b>> {
+    let code = problem.code();
+
+    type_problem_help(alloc, lines, filename, problem).map(|report| Report {
+        code: Some(code),
+        ..report
+    })
+}
+
+fn type_problem_help<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    problem: TypeError,
 ) -> Option<Report<'b>> {
     use TypeError::*;
 
@@ -51,6 +100,8 @@ pub fn type_problem<'b>(
                 filename,
                 doc,
                 severity,
+                fixes: Vec::new(),
+                code: None,
             })
         };
 
@@ -112,6 +163,8 @@ pub fn type_problem<'b>(
                 filename,
                 doc: alloc.stack(stack),
                 severity,
+                fixes: Vec::new(),
+                code: None,
             };
             Some(report)
         }
@@ -134,6 +187,8 @@ pub fn type_problem<'b>(
                 filename,
                 doc: alloc.stack(stack),
                 severity,
+                fixes: Vec::new(),
+                code: None,
             };
             Some(report)
         }
@@ -147,6 +202,8 @@ pub fn type_problem<'b>(
                 filename,
                 doc,
                 severity,
+                fixes: Vec::new(),
+                code: None,
             })
         }
         StructuralSpecialization {
@@ -177,6 +234,8 @@ pub fn type_problem<'b>(
                 filename,
                 doc: alloc.stack(stack),
                 severity,
+                fixes: Vec::new(),
+                code: None,
             })
         }
         WrongSpecialization {
@@ -206,6 +265,8 @@ pub fn type_problem<'b>(
                 filename,
                 doc: alloc.stack(stack),
                 severity,
+                fixes: Vec::new(),
+                code: None,
             })
         }
         IngestedFileBadUtf8(file_path, utf8_err) => {
@@ -222,6 +283,8 @@ pub fn type_problem<'b>(
                 filename,
                 doc: alloc.stack(stack),
                 severity,
+                fixes: Vec::new(),
+                code: None,
             })
         }
         IngestedFileUnsupportedType(file_path, typ) => {
@@ -245,6 +308,8 @@ pub fn type_problem<'b>(
                 filename,
                 doc: alloc.stack(stack),
                 severity,
+                fixes: Vec::new(),
+                code: None,
             })
         }
     }
@@ -519,6 +584,74 @@ fn report_mismatch<'b>(
     } else {
         alloc.region(lines.convert_region(region))
     };
+
+    mismatch_report(
+        alloc,
+        filename,
+        severity,
+        category,
+        found,
+        expected_type,
+        snippet,
+        problem,
+        this_is,
+        instead_of,
+        further_details,
+    )
+}
+
+/// Like [report_mismatch], for a branch whose type doesn't match the first branch of its
+/// `if` or `when`. The first branch is underlined too, when it's close enough to show.
+fn report_branch_mismatch<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    severity: Severity,
+    category: &Category,
+    found: ErrorType,
+    expected_type: ErrorType,
+    region: roc_region::all::Region,
+    expr_region: roc_region::all::Region,
+    first_branch: roc_region::all::Region,
+    problem: RocDocBuilder<'b>,
+    this_is: RocDocBuilder<'b>,
+    instead_of: RocDocBuilder<'b>,
+    further_details: Option<RocDocBuilder<'b>>,
+) -> Report<'b> {
+    let snippet = alloc.region_with_secondary(
+        lines.convert_region(expr_region),
+        lines.convert_region(region),
+        lines.convert_region(first_branch),
+    );
+
+    mismatch_report(
+        alloc,
+        filename,
+        severity,
+        category,
+        found,
+        expected_type,
+        snippet,
+        problem,
+        this_is,
+        instead_of,
+        further_details,
+    )
+}
+
+fn mismatch_report<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    filename: PathBuf,
+    severity: Severity,
+    category: &Category,
+    found: ErrorType,
+    expected_type: ErrorType,
+    snippet: RocDocBuilder<'b>,
+    problem: RocDocBuilder<'b>,
+    this_is: RocDocBuilder<'b>,
+    instead_of: RocDocBuilder<'b>,
+    further_details: Option<RocDocBuilder<'b>>,
+) -> Report<'b> {
     let lines = vec![
         problem,
         snippet,
@@ -538,6 +671,8 @@ fn report_mismatch<'b>(
         filename,
         doc: alloc.stack(lines),
         severity,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
@@ -581,6 +716,8 @@ fn report_bad_type<'b>(
         filename,
         doc: alloc.stack(lines),
         severity,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
@@ -668,6 +805,8 @@ fn to_expr_report<'b>(
                     comparison,
                 ]),
                 severity,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Expected::FromAnnotation(name, _arity, annotation_source, expected_type) => {
@@ -783,14 +922,17 @@ fn to_expr_report<'b>(
                         // for typed bodies, include the line(s) with the signature
                         let joined =
                             roc_region::all::Region::span_across(&ann_region, &expr_region);
-                        alloc.region_with_subregion(
+                        alloc.region_with_secondary(
                             lines.convert_region(joined),
                             lines.convert_region(expr_region),
+                            lines.convert_region(ann_region),
                         )
                     },
                     comparison,
                 ]),
                 severity,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Expected::ForReason(reason, expected_type, region) => match reason {
@@ -910,8 +1052,9 @@ fn to_expr_report<'b>(
             Reason::IfBranch {
                 index,
                 total_branches,
+                first_branch,
             } => match total_branches {
-                2 => report_mismatch(
+                2 => report_branch_mismatch(
                     alloc,
                     lines,
                     filename,
@@ -920,7 +1063,8 @@ fn to_expr_report<'b>(
                     found,
                     expected_type,
                     region,
-                    Some(expr_region),
+                    expr_region,
+                    first_branch,
                     alloc.concat([
                         alloc.text("This "),
                         alloc.keyword("if"),
@@ -946,7 +1090,7 @@ fn to_expr_report<'b>(
                         alloc.text(" must have the same type!"),
                     ])),
                 ),
-                _ => report_mismatch(
+                _ => report_branch_mismatch(
                     alloc,
                     lines,
                     filename,
@@ -955,7 +1099,8 @@ fn to_expr_report<'b>(
                     found,
                     expected_type,
                     region,
-                    Some(expr_region),
+                    expr_region,
+                    first_branch,
                     alloc.concat([
                         alloc.reflow("The "),
                         alloc.string(index.ordinal()),
@@ -972,7 +1117,10 @@ fn to_expr_report<'b>(
                     ])),
                 ),
             },
-            Reason::WhenBranch { index } => report_mismatch(
+            Reason::WhenBranch {
+                index,
+                first_branch,
+            } => report_branch_mismatch(
                 alloc,
                 lines,
                 filename,
@@ -981,7 +1129,8 @@ fn to_expr_report<'b>(
                 found,
                 expected_type,
                 region,
-                Some(expr_region),
+                expr_region,
+                first_branch,
                 alloc.concat([
                     alloc.reflow("The "),
                     alloc.string(index.ordinal()),
@@ -1182,6 +1331,8 @@ fn to_expr_report<'b>(
                         title: "TOO MANY ARGS".to_string(),
                         doc,
                         severity,
+                        fixes: Vec::new(),
+                        code: None,
                     }
                 }
                 DescribedFunction::Arguments(n) => {
@@ -1217,6 +1368,8 @@ fn to_expr_report<'b>(
                             title: "TOO MANY ARGS".to_string(),
                             doc: alloc.stack(lines),
                             severity,
+                            fixes: Vec::new(),
+                            code: None,
                         }
                     } else {
                         let lines = vec![
@@ -1244,6 +1397,8 @@ fn to_expr_report<'b>(
                             title: "TOO FEW ARGS".to_string(),
                             doc: alloc.stack(lines),
                             severity,
+                            fixes: Vec::new(),
+                            code: None,
                         }
                     }
                 }
@@ -1445,6 +1600,8 @@ fn to_expr_report<'b>(
                     filename,
                     doc: alloc.stack(lines),
                     severity,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
 
@@ -1482,6 +1639,8 @@ fn to_expr_report<'b>(
                     title: "TYPE MISMATCH".to_string(),
                     doc,
                     severity,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
 
@@ -1518,6 +1677,8 @@ fn to_expr_report<'b>(
                     title: "TYPE MISMATCH".to_string(),
                     doc: alloc.stack(lines),
                     severity,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
 
@@ -1931,6 +2092,8 @@ fn to_pattern_report<'b>(
                 title: "TYPE MISMATCH".to_string(),
                 doc,
                 severity,
+                fixes: Vec::new(),
+                code: None,
             }
         }
 
@@ -1974,6 +2137,8 @@ fn to_pattern_report<'b>(
                     title: "TYPE MISMATCH".to_string(),
                     doc,
                     severity,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             PReason::WhenMatch { index, sub_pattern } => {
@@ -2054,6 +2219,8 @@ fn to_pattern_report<'b>(
                     title: "TYPE MISMATCH".to_string(),
                     doc,
                     severity,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             PReason::ListElem => {
@@ -2081,6 +2248,8 @@ fn to_pattern_report<'b>(
                     title: "TYPE MISMATCH".to_string(),
                     doc,
                     severity,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             PReason::TagArg { .. } | PReason::PatternGuard => {
@@ -2182,6 +2351,8 @@ fn to_circular_report<'b>(
             ])
         },
         severity,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
@@ -2989,9 +3160,12 @@ fn to_diff<'b>(
         }
 
         pair => {
-            // We hit none of the specific cases where we give more detailed information
+            // We hit none of the specific cases where we give more detailed information,
+            // so this is where the two types actually differ.
             let (left, left_able) = to_doc(alloc, parens, type1);
             let (right, right_able) = to_doc(alloc, parens, type2);
+            let left = left.annotate(Annotation::Difference);
+            let right = right.annotate(Annotation::Difference);
 
             let is_int = |t: &ErrorType| match t {
                 ErrorType::Type(Symbol::NUM_INT, _) => true,
@@ -3153,10 +3327,13 @@ fn diff_record<'b>(
             }
         };
 
+    // These fields are only in one of the records, so they are part of the difference.
     let to_unknown_docs = |(field, tipe): (&Lowercase, &RecordField<ErrorType>)| {
         (
             field.clone(),
-            alloc.string(field.as_str().to_string()),
+            alloc
+                .string(field.as_str().to_string())
+                .annotate(Annotation::Difference),
             tipe.map(|t| to_doc(alloc, Parens::Unnecessary, t.clone()).0),
         )
     };
@@ -3532,17 +3709,18 @@ fn should_show_field_diff(
         // Demanded and RigidRequired don't need a diff
         | (Demanded(t1), RigidRequired(t2))
         | (RigidRequired(t1), Demanded(t2))
+        // Required and RigidRequired (e.g. a record literal and its annotation) don't need a diff
+        | (Required(t1), RigidRequired(t2))
+        | (RigidRequired(t1), Required(t2))
         => should_show_diff(t1, t2),
         // Everything else needs a diff
         (Demanded(_), Optional(_))
         | (Demanded(_), RigidOptional(_))
-        | (Required(_), RigidRequired(_))
         | (Required(_), Optional(_))
         | (Optional(_), Demanded(_))
         | (Optional(_), RigidRequired(_))
         | (Optional(_), RigidOptional(_))
         | (Optional(_), Required(_))
-        | (RigidRequired(_), Required(_))
         | (RigidRequired(_), Optional(_))
         | (RigidRequired(_), RigidOptional(_))
         | (Required(_), RigidOptional(_))
@@ -3656,7 +3834,10 @@ fn diff_tag_union<'b>(
                 .unzip();
         (
             tag_name.clone(),
-            alloc.tag_name(tag_name.clone()),
+            // This tag is only in one of the unions, so it's part of the difference.
+            alloc
+                .tag_name(tag_name.clone())
+                .annotate(Annotation::Difference),
             args,
             able.into_iter().flatten().collect(),
         )
@@ -4856,6 +5037,8 @@ fn report_record_field_typo<'b>(
         title: "TYPE MISMATCH".to_string(),
         doc,
         severity,
+        fixes: Vec::new(),
+        code: None,
     }
 }
 
@@ -4893,6 +5076,8 @@ fn exhaustive_problem<'a>(
                     title: "UNSAFE PATTERN".to_string(),
                     doc,
                     severity,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             BadDestruct => {
@@ -4917,9 +5102,14 @@ fn exhaustive_problem<'a>(
                     title: "UNSAFE PATTERN".to_string(),
                     doc,
                     severity,
+                    fixes: Vec::new(),
+                    code: None,
                 }
             }
             BadCase => {
+                let fixes = add_missing_branches_fix(alloc, lines, region, &missing)
+                    .into_iter()
+                    .collect();
                 let doc = alloc.stack([
                     alloc.concat([
                         alloc.reflow("This "),
@@ -4941,6 +5131,8 @@ fn exhaustive_problem<'a>(
                     title: "UNSAFE PATTERN".to_string(),
                     doc,
                     severity,
+                    fixes,
+                    code: None,
                 }
             }
         },
@@ -4970,6 +5162,8 @@ fn exhaustive_problem<'a>(
                 title: "REDUNDANT PATTERN".to_string(),
                 doc,
                 severity,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Unmatchable {
@@ -4998,11 +5192,54 @@ fn exhaustive_problem<'a>(
                 title: "UNMATCHABLE PATTERN".to_string(),
                 doc,
                 severity,
+                fixes: Vec::new(),
+                code: None,
             }
         }
     }
 }
 
+/// Adds a branch for each missing pattern to the end of a `when`. The new branches crash,
+/// because only the author knows what they should really do.
+fn add_missing_branches_fix<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    when_region: Region,
+    missing: &[roc_exhaustive::Pattern],
+) -> Option<Fix> {
+    // Line up the new branches with the first existing one.
+    let first_branch_line = lines.convert_region(when_region).start.line as usize + 1;
+    let first_branch = alloc.src_lines.get(first_branch_line)?;
+    let indent = &first_branch[..first_branch.len() - first_branch.trim_start().len()];
+
+    let mut replacement = String::new();
+
+    for pattern in missing {
+        let mut pattern_src = String::new();
+
+        exhaustive_pattern_to_doc(alloc, pattern.clone())
+            .1
+            .render_raw(1000, &mut PlainWrite::new(&mut pattern_src))
+            .ok()?;
+
+        // Missing guards are described in prose rather than as a pattern.
+        if pattern_src.contains("(note the lack of") {
+            return None;
+        }
+
+        replacement.push_str(&format!("\n{indent}{pattern_src} -> crash \"TODO\""));
+    }
+
+    Some(Fix {
+        description: "Add the missing branches".to_string(),
+        edits: vec![Edit {
+            region: Region::new(when_region.end(), when_region.end()),
+            replacement,
+        }],
+        machine_applicable: false,
+    })
+}
+
 pub fn unhandled_patterns_to_doc_block<'b>(
     alloc: &'b RocDocAllocator<'b>,
     patterns: Vec<roc_exhaustive::Pattern>,
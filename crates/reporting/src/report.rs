@@ -2,7 +2,7 @@ use roc_module::ident::Ident;
 use roc_module::ident::{Lowercase, ModuleName, TagName, Uppercase};
 use roc_module::symbol::{Interns, ModuleId, ModuleIds, PQModuleName, PackageQualified, Symbol};
 use roc_problem::Severity;
-use roc_region::all::LineColumnRegion;
+use roc_region::all::{LineColumnRegion, Region};
 use std::path::{Path, PathBuf};
 use std::{fmt, io};
 use ven_pretty::{text, BoxAllocator, DocAllocator, DocBuilder, Render, RenderAnnotated};
@@ -11,6 +11,8 @@ use ven_pretty::{text, BoxAllocator, DocAllocator, DocBuilder, Render, RenderAnn
 use byte_unit::Byte;
 #[cfg(not(target_family = "wasm"))]
 use roc_packaging::https::Problem;
+#[cfg(not(target_family = "wasm"))]
+use roc_packaging::manifest::{ManifestProblem, LOCKFILE_NAME};
 
 pub use crate::error::canonicalize::can_problem;
 pub use crate::error::parse::parse_problem;
@@ -29,11 +31,42 @@ const CYCLE_END: &str = CYCLE_ELEMENTS[3];
 
 const GUTTER_BAR: &str = "│";
 const ERROR_UNDERLINE: &str = "^";
+const SECONDARY_UNDERLINE: &str = "-";
+
+/// How many lines apart two regions can be, and still be shown together in one snippet.
+const MAX_SNIPPET_GAP_LINES: u32 = 10;
 
 /// The number of monospace spaces the gutter bar takes up.
 /// (This is not necessarily the same as GUTTER_BAR.len()!)
 const GUTTER_BAR_WIDTH: usize = 1;
 
+/// Marks where a source line was cut off because it was too long to show.
+const TRUNCATED_LINE: &str = "…";
+
+/// How much source code the snippets in reports show.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SnippetConfig {
+    /// How many lines to show before and after the highlighted region.
+    pub context_lines: u32,
+    /// If a region spans more lines than this, only the part with the problem is shown.
+    pub max_lines: u32,
+    /// Source lines longer than this many characters (e.g. in generated code) are cut off.
+    pub max_line_width: usize,
+}
+
+pub const DEFAULT_SNIPPET_CONFIG: SnippetConfig = SnippetConfig {
+    context_lines: 0,
+    // About one full screen
+    max_lines: 60,
+    max_line_width: 500,
+};
+
+impl Default for SnippetConfig {
+    fn default() -> Self {
+        DEFAULT_SNIPPET_CONFIG
+    }
+}
+
 pub fn cycle<'b>(
     alloc: &'b RocDocAllocator<'b>,
     indent: usize,
@@ -114,15 +147,100 @@ pub enum RenderTarget {
     Generic,
 }
 
+/// The longer-form documentation for one kind of report, printed by `roc explain`.
+/// These live next to the functions that build the corresponding reports.
+pub struct Explanation {
+    /// The code of the reports this explains, e.g. `E2001`. See [Report::code].
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+    /// A small program which produces this report.
+    pub example: &'static str,
+    pub fix: &'static str,
+}
+
+/// A change to the source that would resolve the problem a [Report] is about,
+/// e.g. removing an unused import. Editors offer these as quick fixes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fix {
+    /// What the fix does, e.g. "Remove the unused import"
+    pub description: String,
+    pub edits: Vec<Edit>,
+    /// Whether the fix is certain to be what the author wants, so that `roc check --fix`
+    /// can apply it without asking. Guesses, like spelling corrections, are not.
+    pub machine_applicable: bool,
+}
+
+/// Replace the source in `region` with `replacement`.
+/// An empty region inserts the replacement at that position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub region: Region,
+    pub replacement: String,
+}
+
+impl Edit {
+    pub fn delete(region: Region) -> Self {
+        Edit {
+            region,
+            replacement: String::new(),
+        }
+    }
+}
+
+/// Apply edits to the source they were made for. If two edits overlap,
+/// only the one which starts first is applied.
+pub fn apply_edits(src: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|edit| (edit.region.start(), std::cmp::Reverse(edit.region.end())));
+
+    let mut output = String::with_capacity(src.len());
+    let mut copied_up_to = 0;
+
+    for Edit {
+        region,
+        replacement,
+    } in edits
+    {
+        let start = region.start().offset as usize;
+        let end = region.end().offset as usize;
+
+        if start < copied_up_to {
+            continue;
+        }
+
+        output.push_str(&src[copied_up_to..start]);
+        output.push_str(&replacement);
+        copied_up_to = end;
+    }
+
+    output.push_str(&src[copied_up_to..]);
+
+    output
+}
+
 /// A textual report.
+#[derive(Clone)]
 pub struct Report<'b> {
     pub title: String,
     pub filename: PathBuf,
     pub doc: RocDocBuilder<'b>,
     pub severity: Severity,
+    /// Ways to resolve the problem, in order of how likely they are to be right
+    pub fixes: Vec<Fix>,
+    /// The stable code for the kind of problem, e.g. `E2001` for a type mismatch, which is
+    /// shown in the header. `roc explain` accepts it, and editors show it next to a diagnostic.
+    /// Reports about the build rather than the program, like a missing file, have none.
+    pub code: Option<&'static str>,
 }
 
 impl<'b> Report<'b> {
+    /// The code to show for this report. Codes which `roc explain` has no explanation for are
+    /// left out, since there would be nothing to look them up in.
+    pub fn shown_code(&self) -> Option<&'static str> {
+        self.code
+            .filter(|code| crate::error::explain(code).is_some())
+    }
+
     pub fn render(
         self,
         target: RenderTarget,
@@ -146,6 +264,16 @@ impl<'b> Report<'b> {
             .expect(err_msg);
     }
 
+    /// Render as plain text, without even the backticks that CI output uses for inline code.
+    pub fn render_plain(self, buf: &mut String, alloc: &'b RocDocAllocator<'b>) {
+        let err_msg = "<buffer is not a utf-8 encoded string>";
+
+        self.pretty(alloc)
+            .1
+            .render_raw(70, &mut PlainWrite::new(buf))
+            .expect(err_msg);
+    }
+
     /// Render to a color terminal using ANSI escape sequences,
     /// or to the web REPL, using HTML tags.
     pub fn render_color_terminal(
@@ -166,10 +294,15 @@ impl<'b> Report<'b> {
         if self.title.is_empty() {
             self.doc
         } else {
+            let title = match self.shown_code() {
+                Some(code) => format!("{} [{}]", self.title, code),
+                None => self.title,
+            };
+
             let header = if self.filename == PathBuf::from("replfile.roc") {
-                crate::report::pretty_header(&self.title)
+                crate::report::pretty_header(&title)
             } else {
-                crate::report::pretty_header_with_path(&self.title, &self.filename)
+                crate::report::pretty_header_with_path(&title, &self.filename)
             };
 
             alloc.stack([alloc.text(header).annotate(Annotation::Header), self.doc])
@@ -206,6 +339,14 @@ pub struct Palette {
     pub typo: &'static str,
     pub typo_suggestion: &'static str,
     pub parser_suggestion: &'static str,
+    /// Underlines a region related to the problem, e.g. the annotation a body doesn't match
+    pub secondary_highlight: &'static str,
+    /// Marks the parts of two types which differ, in type mismatch reports
+    pub difference: &'static str,
+    /// Used in summaries, e.g. for a count of zero errors
+    pub success: &'static str,
+    /// Used in summaries, e.g. for a nonzero count of warnings
+    pub warning: &'static str,
     pub bold: &'static str,
     pub underline: &'static str,
     pub reset: &'static str,
@@ -233,6 +374,10 @@ const fn default_palette_from_style_codes(codes: StyleCodes) -> Palette {
         typo: codes.yellow,
         typo_suggestion: codes.yellow,
         parser_suggestion: codes.yellow,
+        secondary_highlight: codes.cyan,
+        difference: codes.underline,
+        success: codes.green,
+        warning: codes.yellow,
         bold: codes.bold,
         underline: codes.underline,
         reset: codes.reset,
@@ -243,6 +388,68 @@ pub const DEFAULT_PALETTE: Palette = default_palette_from_style_codes(ANSI_STYLE
 
 pub const DEFAULT_PALETTE_HTML: Palette = default_palette_from_style_codes(HTML_STYLE_CODES);
 
+/// For terminals without color support, logs, and `NO_COLOR`.
+pub const PLAIN_PALETTE: Palette = default_palette_from_style_codes(NO_STYLE_CODES);
+
+/// For terminals with a light background, where the default palette's white and
+/// yellow text is hard to read.
+pub const LIGHT_BACKGROUND_PALETTE: Palette = Palette {
+    primary: "\u{001b}[39m",
+    code_block: "\u{001b}[39m",
+    keyword: "\u{001b}[32m",
+    ellipsis: "\u{001b}[32m",
+    variable: "\u{001b}[34m",
+    type_variable: "\u{001b}[35m",
+    structure: "\u{001b}[32m",
+    alias: "\u{001b}[35m",
+    opaque: "\u{001b}[35m",
+    error: "\u{001b}[1;31m",
+    line_number: "\u{001b}[34m",
+    header: "\u{001b}[34m",
+    gutter_bar: "\u{001b}[34m",
+    module_name: "\u{001b}[32m",
+    binop: "\u{001b}[32m",
+    typo: "\u{001b}[35m",
+    typo_suggestion: "\u{001b}[35m",
+    parser_suggestion: "\u{001b}[35m",
+    secondary_highlight: "\u{001b}[34m",
+    difference: ANSI_STYLE_CODES.underline,
+    success: "\u{001b}[32m",
+    warning: "\u{001b}[35m",
+    bold: ANSI_STYLE_CODES.bold,
+    underline: ANSI_STYLE_CODES.underline,
+    reset: ANSI_STYLE_CODES.reset,
+};
+
+/// Bright, bold colors which stand out from each other as much as possible.
+pub const HIGH_CONTRAST_PALETTE: Palette = Palette {
+    primary: "\u{001b}[1;97m",
+    code_block: "\u{001b}[1;97m",
+    keyword: "\u{001b}[1;92m",
+    ellipsis: "\u{001b}[1;92m",
+    variable: "\u{001b}[1;96m",
+    type_variable: "\u{001b}[1;93m",
+    structure: "\u{001b}[1;92m",
+    alias: "\u{001b}[1;93m",
+    opaque: "\u{001b}[1;93m",
+    error: "\u{001b}[1;4;91m",
+    line_number: "\u{001b}[1;96m",
+    header: "\u{001b}[1;96m",
+    gutter_bar: "\u{001b}[1;96m",
+    module_name: "\u{001b}[1;92m",
+    binop: "\u{001b}[1;92m",
+    typo: "\u{001b}[1;93m",
+    typo_suggestion: "\u{001b}[1;93m",
+    parser_suggestion: "\u{001b}[1;93m",
+    secondary_highlight: "\u{001b}[1;96m",
+    difference: "\u{001b}[1;4m",
+    success: "\u{001b}[1;92m",
+    warning: "\u{001b}[1;93m",
+    bold: ANSI_STYLE_CODES.bold,
+    underline: ANSI_STYLE_CODES.underline,
+    reset: ANSI_STYLE_CODES.reset,
+};
+
 /// A machine-readable format for text styles (colors and other styles)
 #[derive(Debug, PartialEq)]
 pub struct StyleCodes {
@@ -267,6 +474,17 @@ pub const ANSI_STYLE_CODES: StyleCodes = StyleCodes {
     reset: "\u{001b}[0m",
 };
 
+pub const NO_STYLE_CODES: StyleCodes = StyleCodes {
+    red: "",
+    green: "",
+    yellow: "",
+    cyan: "",
+    white: "",
+    bold: "",
+    underline: "",
+    reset: "",
+};
+
 macro_rules! html_color {
     ($name: expr) => {
         concat!("<span class='color-", $name, "'>")
@@ -302,6 +520,9 @@ pub struct RocDocAllocator<'a> {
     pub src_lines: &'a [&'a str],
     pub home: ModuleId,
     pub interns: &'a Interns,
+    pub snippet_config: SnippetConfig,
+    /// The symbols exposed by the modules that `home` imports, for suggesting names.
+    pub imported_symbols: &'a [Symbol],
 }
 
 pub type RocDocBuilder<'b> = DocBuilder<'b, RocDocAllocator<'b>, Annotation>;
@@ -338,6 +559,22 @@ impl<'a> RocDocAllocator<'a> {
             home,
             src_lines,
             interns,
+            snippet_config: DEFAULT_SNIPPET_CONFIG,
+            imported_symbols: &[],
+        }
+    }
+
+    pub fn with_snippet_config(self, snippet_config: SnippetConfig) -> Self {
+        RocDocAllocator {
+            snippet_config,
+            ..self
+        }
+    }
+
+    pub fn with_imported_symbols(self, imported_symbols: &'a [Symbol]) -> Self {
+        RocDocAllocator {
+            imported_symbols,
+            ..self
         }
     }
 
@@ -661,11 +898,45 @@ impl<'a> RocDocAllocator<'a> {
         &'a self,
         region: LineColumnRegion,
         sub_region: LineColumnRegion,
+    ) -> DocBuilder<'a, Self, Annotation> {
+        self.snippet(region, sub_region, None)
+    }
+
+    /// Like [Self::region_with_subregion], but also underlines a related region above the
+    /// sub-region in a different style, e.g. the annotation that a definition's body doesn't
+    /// match. If the related region doesn't fit in the same snippet (because it spans several
+    /// lines, or is too far away), only the sub-region is highlighted.
+    pub fn region_with_secondary(
+        &'a self,
+        region: LineColumnRegion,
+        sub_region: LineColumnRegion,
+        secondary: LineColumnRegion,
+    ) -> DocBuilder<'a, Self, Annotation> {
+        let fits = region.contains(&secondary)
+            && !secondary.is_empty()
+            && secondary.start().line == secondary.end().line
+            && secondary.end().line < sub_region.start().line
+            && sub_region.start().line - secondary.end().line <= MAX_SNIPPET_GAP_LINES;
+
+        self.snippet(region, sub_region, fits.then_some(secondary))
+    }
+
+    fn snippet(
+        &'a self,
+        region: LineColumnRegion,
+        sub_region: LineColumnRegion,
+        opt_secondary: Option<LineColumnRegion>,
     ) -> DocBuilder<'a, Self, Annotation> {
         // debug_assert!(region.contains(&sub_region));
 
-        // If the outer region takes more than 1 full screen (~60 lines), only show the inner region
-        if region.end().line.saturating_sub(region.start().line) > 60 {
+        let SnippetConfig {
+            context_lines,
+            max_lines,
+            max_line_width,
+        } = self.snippet_config;
+
+        // If the outer region is too big to be useful, only show the inner region
+        if region.end().line.saturating_sub(region.start().line) > max_lines {
             // If the inner region contains the outer region (or if they are the same),
             // attempting this will recurse forever, so don't do that! Instead, give up and
             // accept that this report will take up more than 1 full screen.
@@ -674,22 +945,40 @@ impl<'a> RocDocAllocator<'a> {
             }
         }
 
-        // if true, the final line of the snippet will be some ^^^ that point to the region where
-        // the problem is. Otherwise, the snippet will have a > on the lines that are in the region
-        // where the problem is.
+        // if true, the final line of the region will be followed by some ^^^ that point to the
+        // region where the problem is. Otherwise, the snippet will have a > on the lines that are
+        // in the region where the problem is.
         let error_highlight_line = sub_region.start().line == region.end().line;
 
-        let max_line_number_length = (region.end().line + 1).to_string().len();
+        // Underlines stop where a line that is too long gets cut off.
+        let visible_column = |column: u32| (column as usize).min(max_line_width);
+
+        // Show the requested context around the region, but not the blank lines at its edges.
+        let is_blank = |line: u32| {
+            self.src_lines
+                .get(line as usize)
+                .map_or(true, |line| line.trim().is_empty())
+        };
+        let mut first_line = region.start().line.saturating_sub(context_lines);
+        while first_line < region.start().line && is_blank(first_line) {
+            first_line += 1;
+        }
+        let mut last_line = region.end().line.saturating_add(context_lines);
+        while last_line > region.end().line && is_blank(last_line) {
+            last_line -= 1;
+        }
+
+        let max_line_number_length = (last_line + 1).to_string().len();
         let indent = 2;
 
         let mut result = self.nil();
-        for i in region.start().line..=region.end().line {
+        for i in first_line..=last_line {
             let line_number_string = (i + 1).to_string();
             let line_number = line_number_string;
             let this_line_number_length = line_number.len();
 
             // filter out any escape characters for the current line that could mess up the output.
-            let line: String = self
+            let mut line: String = self
                 .src_lines
                 .get(i as usize)
                 .unwrap_or(&"")
@@ -697,6 +986,11 @@ impl<'a> RocDocAllocator<'a> {
                 .filter(|&c| !c.is_ascii_control() || c == '\t')
                 .collect::<String>();
 
+            if let Some((cut_off, _)) = line.char_indices().nth(max_line_width) {
+                line.truncate(cut_off);
+                line.push_str(TRUNCATED_LINE);
+            }
+
             let is_line_empty = line.trim().is_empty();
             let rest_of_line = if !is_line_empty {
                 self.text(line)
@@ -736,29 +1030,60 @@ impl<'a> RocDocAllocator<'a> {
 
             result = result.append(source_line);
 
-            if i != region.end().line {
-                result = result.append(self.line())
+            if let Some(secondary) = opt_secondary.filter(|secondary| secondary.start().line == i) {
+                // Lines marked with `>` (and the ones around them) are shifted by one column.
+                let gutter_width =
+                    max_line_number_length + GUTTER_BAR_WIDTH + usize::from(!error_highlight_line);
+                let underline = SECONDARY_UNDERLINE.repeat(
+                    visible_column(secondary.end().column)
+                        - visible_column(secondary.start().column),
+                );
+
+                result = result
+                    .append(self.line())
+                    .append(self.text(" ".repeat(gutter_width)))
+                    .append(
+                        self.text(" ".repeat(visible_column(secondary.start().column)))
+                            .indent(indent)
+                            .append(
+                                self.text(underline)
+                                    .annotate(Annotation::SecondaryHighlight),
+                            ),
+                    );
             }
-        }
 
-        if error_highlight_line {
-            let highlight_text = ERROR_UNDERLINE
-                .repeat((sub_region.end().column - sub_region.start().column) as usize);
+            if error_highlight_line && i == region.end().line {
+                let highlight_text = ERROR_UNDERLINE.repeat(
+                    visible_column(sub_region.end().column)
+                        - visible_column(sub_region.start().column),
+                );
 
-            let highlight_line = self
-                .line()
                 // Omit the gutter bar when we know there are no further
                 // line numbers to be printed after this!
-                .append(self.text(" ".repeat(max_line_number_length + GUTTER_BAR_WIDTH)))
-                .append(if highlight_text.is_empty() {
-                    self.nil()
+                let gutter = if i == last_line {
+                    self.text(" ".repeat(max_line_number_length + GUTTER_BAR_WIDTH))
                 } else {
-                    self.text(" ".repeat(sub_region.start().column as usize))
-                        .indent(indent)
-                        .append(self.text(highlight_text).annotate(Annotation::Error))
-                });
+                    self.text(" ".repeat(max_line_number_length))
+                        .append(self.text(GUTTER_BAR).annotate(Annotation::GutterBar))
+                };
 
-            result = result.append(highlight_line);
+                let highlight_line =
+                    self.line()
+                        .append(gutter)
+                        .append(if highlight_text.is_empty() {
+                            self.nil()
+                        } else {
+                            self.text(" ".repeat(visible_column(sub_region.start().column)))
+                                .indent(indent)
+                                .append(self.text(highlight_text).annotate(Annotation::Error))
+                        });
+
+                result = result.append(highlight_line);
+            }
+
+            if i != last_line {
+                result = result.append(self.line())
+            }
         }
 
         result
@@ -858,6 +1183,8 @@ pub enum Annotation {
     BinOp,
     UnaryOp,
     Error,
+    SecondaryHighlight,
+    Difference,
     GutterBar,
     LineNumber,
     PlainText,
@@ -891,6 +1218,17 @@ impl<W> CiWrite<W> {
     }
 }
 
+/// Render with no formatting at all
+pub struct PlainWrite<W> {
+    upstream: W,
+}
+
+impl<W> PlainWrite<W> {
+    pub fn new(upstream: W) -> PlainWrite<W> {
+        PlainWrite { upstream }
+    }
+}
+
 /// Render with fancy formatting
 pub struct ColorWrite<'a, W> {
     style_stack: Vec<Annotation>,
@@ -995,6 +1333,34 @@ where
     }
 }
 
+impl<W> Render for PlainWrite<W>
+where
+    W: fmt::Write,
+{
+    type Error = fmt::Error;
+
+    fn write_str(&mut self, s: &str) -> Result<usize, fmt::Error> {
+        self.write_str_all(s).map(|_| s.len())
+    }
+
+    fn write_str_all(&mut self, s: &str) -> fmt::Result {
+        self.upstream.write_str(s)
+    }
+}
+
+impl<W> RenderAnnotated<Annotation> for PlainWrite<W>
+where
+    W: fmt::Write,
+{
+    fn push_annotation(&mut self, _annotation: &Annotation) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn pop_annotation(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
 impl<'a, W> Render for ColorWrite<'a, W>
 where
     W: fmt::Write,
@@ -1059,6 +1425,12 @@ where
             Error => {
                 self.write_str(self.palette.error)?;
             }
+            SecondaryHighlight => {
+                self.write_str(self.palette.secondary_highlight)?;
+            }
+            Difference => {
+                self.write_str(self.palette.difference)?;
+            }
             Header => {
                 self.write_str(self.palette.header)?;
             }
@@ -1093,8 +1465,9 @@ where
             None => {}
             Some(annotation) => match annotation {
                 Emphasized | Url | TypeVariable | Alias | Symbol | BinOp | UnaryOp | Error
-                | GutterBar | Ellipsis | Typo | TypoSuggestion | ParserSuggestion | Structure
-                | CodeBlock | PlainText | LineNumber | Tip | Module | Header | Keyword => {
+                | SecondaryHighlight | Difference | GutterBar | Ellipsis | Typo
+                | TypoSuggestion | ParserSuggestion | Structure | CodeBlock | PlainText
+                | LineNumber | Tip | Module | Header | Keyword => {
                     self.write_str(self.palette.reset)?;
                 }
 
@@ -1134,7 +1507,7 @@ pub fn to_https_problem_report_string(
 #[cfg(not(target_family = "wasm"))]
 pub fn to_https_problem_report<'b>(
     alloc: &'b RocDocAllocator<'b>,
-    url: &'b str,
+    url: &str,
     https_problem: Problem,
     filename: PathBuf,
 ) -> Report<'b> {
@@ -1169,6 +1542,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "UNSUPPORTED ENCODING".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::MultipleEncodings(multiple_encodings) => {
@@ -1204,6 +1579,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "MULTIPLE ENCODINGS".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::InvalidContentHash { expected, actual } => {
@@ -1239,6 +1616,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "INVALID CONTENT HASH".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::NotFound => {
@@ -1256,6 +1635,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "NOTFOUND".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         // TODO: The reporting text for IoErr and FsExtraErr could probably be unified
@@ -1283,6 +1664,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "IO ERROR".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         // TODO: The reporting text for IoErr and FsExtraErr could probably be unified
@@ -1310,6 +1693,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "IO ERROR".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::HttpErr(reqwest_error) => {
@@ -1339,6 +1724,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "HTTP ERROR".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::InvalidUrl(roc_packaging::https::UrlProblem::InvalidExtensionSuffix(
@@ -1380,6 +1767,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "INVALID EXTENSION SUFFIX".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::InvalidUrl(roc_packaging::https::UrlProblem::MissingTarExt) => {
@@ -1413,6 +1802,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "INVALID EXTENSION".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::InvalidUrl(roc_packaging::https::UrlProblem::InvalidFragment(
@@ -1451,6 +1842,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "INVALID FRAGMENT".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::InvalidUrl(roc_packaging::https::UrlProblem::MissingHash) => {
@@ -1489,6 +1882,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "MISSING PACKAGE HASH".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::InvalidUrl(roc_packaging::https::UrlProblem::MissingHttps) => {
@@ -1515,6 +1910,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "HTTPS MANDATORY".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::InvalidUrl(roc_packaging::https::UrlProblem::MisleadingCharacter) => {
@@ -1558,6 +1955,8 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "MISLEADING CHARACTERS".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         Problem::DownloadTooBig(content_len) => {
@@ -1588,6 +1987,276 @@ pub fn to_https_problem_report<'b>(
                 doc,
                 title: "FILE TOO LARGE".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
+            }
+        }
+        Problem::NotAvailableOffline => {
+            let doc = alloc.stack([
+                alloc.reflow(r"I need the package at this URL:"),
+                alloc
+                    .string((&url).to_string())
+                    .annotate(Annotation::Url)
+                    .indent(4),
+                alloc.concat([
+                    alloc.reflow(r"But it hasn't been downloaded, and I am not allowed to "),
+                    alloc.reflow(r"download it. That's because either this is an "),
+                    alloc.keyword("--offline"),
+                    alloc.reflow(r" build, or the project's packages are vendored."),
+                ]),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"If the project's packages are vendored, run "),
+                    alloc.keyword("roc vendor"),
+                    alloc.reflow(r" again to add the ones that are missing. Otherwise, build "),
+                    alloc.reflow(r"once without "),
+                    alloc.keyword("--offline"),
+                    alloc.reflow(r" to download them."),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "PACKAGE NOT AVAILABLE OFFLINE".to_string(),
+                severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
+            }
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn to_manifest_problem_report_string(problem: ManifestProblem, filename: PathBuf) -> String {
+    let src_lines: Vec<&str> = Vec::new();
+    let mut module_ids = ModuleIds::default();
+    let module_id = module_ids.get_or_insert(&"find module name somehow?".into());
+    let interns = Interns::default();
+
+    let alloc = RocDocAllocator::new(&src_lines, module_id, &interns);
+
+    let mut buf = String::new();
+    let palette = DEFAULT_PALETTE;
+    let report = to_manifest_problem_report(&alloc, problem, filename);
+    report.render_color_terminal(&mut buf, &alloc, &palette);
+
+    buf
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn to_manifest_problem_report<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    problem: ManifestProblem,
+    filename: PathBuf,
+) -> Report<'b> {
+    match problem {
+        ManifestProblem::IoErr(path, io_err) => {
+            let doc = alloc.stack([
+                alloc.reflow(r"I was working with this package file:"),
+                alloc
+                    .string(path.display().to_string())
+                    .annotate(Annotation::ParserSuggestion)
+                    .indent(4),
+                alloc.concat([
+                    alloc.reflow(r"But I ran into this error: "),
+                    alloc.string(io_err.to_string()),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "PACKAGE FILE PROBLEM".to_string(),
+                severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
+            }
+        }
+        ManifestProblem::InvalidJson(path, json_err) => {
+            let doc = alloc.stack([
+                alloc.reflow(r"I could not make sense of this package file:"),
+                alloc
+                    .string(path.display().to_string())
+                    .annotate(Annotation::ParserSuggestion)
+                    .indent(4),
+                alloc.string(json_err.to_string()),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"A manifest lists each package's URL and version under its name, like "),
+                    alloc.parser_suggestion(
+                        r#"{ "packages": { "cli": { "url": "https://...", "version": "0.7.0" } } }"#,
+                    ),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "INVALID PACKAGE FILE".to_string(),
+                severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
+            }
+        }
+        ManifestProblem::InvalidUrl { url, problem, .. } => {
+            to_https_problem_report(alloc, &url, Problem::InvalidUrl(problem), filename)
+        }
+        ManifestProblem::DownloadFailed { url, problem, .. } => {
+            to_https_problem_report(alloc, &url, problem, filename)
+        }
+        ManifestProblem::InvalidVersion {
+            name,
+            version,
+            error,
+        } => {
+            let doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow(r"The version of the "),
+                    alloc.string(name).annotate(Annotation::Emphasized),
+                    alloc.reflow(r" package is not one I understand:"),
+                ]),
+                alloc.string(version).indent(4),
+                alloc.string(error.to_string()),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"Versions look like "),
+                    alloc.parser_suggestion("1.2.3"),
+                    alloc.reflow(r", and version requirements like "),
+                    alloc.parser_suggestion("^1.2"),
+                    alloc.reflow(r". A package that's pinned to a URL needs an exact version."),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "INVALID PACKAGE VERSION".to_string(),
+                severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
+            }
+        }
+        ManifestProblem::VersionConflict {
+            name,
+            requirements,
+            available,
+            unversioned,
+        } => {
+            let requirement_lines = requirements.into_iter().map(|requirement| {
+                let mut line = String::from("this project");
+
+                for (dependent, version) in requirement.chain {
+                    line.push_str(" → ");
+                    line.push_str(&dependent);
+
+                    if let Some(version) = version {
+                        line.push_str(&format!(" {version}"));
+                    }
+                }
+
+                line.push_str(&format!(
+                    " requires {} {}",
+                    requirement.name, requirement.req
+                ));
+
+                alloc.string(line).indent(4)
+            });
+
+            let available_doc = if available.is_empty() {
+                alloc.concat([
+                    alloc.reflow(r"I don't know of any versions of "),
+                    alloc.string(name.clone()).annotate(Annotation::Emphasized),
+                    alloc.reflow(r". Its releases can be listed in the manifest."),
+                ])
+            } else {
+                let versions: Vec<String> = available.iter().map(|v| v.to_string()).collect();
+
+                alloc.concat([
+                    alloc.reflow(r"These versions of "),
+                    alloc.string(name.clone()).annotate(Annotation::Emphasized),
+                    alloc.reflow(r" are available: "),
+                    alloc.string(versions.join(", ")),
+                ])
+            };
+
+            let available_doc = if unversioned {
+                alloc.stack([
+                    available_doc,
+                    alloc.concat([
+                        alloc.reflow(r"The manifest also pins "),
+                        alloc.string(name.clone()).annotate(Annotation::Emphasized),
+                        alloc.reflow(r" to a URL without saying which version it is, so it "),
+                        alloc.reflow(r"can only be used where any version is allowed. Adding "),
+                        alloc.reflow(r"its version to the manifest lets it satisfy the others."),
+                    ]),
+                ])
+            } else {
+                available_doc
+            };
+
+            let doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow(r"I could not find a version of the "),
+                    alloc.string(name).annotate(Annotation::Emphasized),
+                    alloc.reflow(r" package that satisfies all of these requirements:"),
+                ]),
+                alloc.stack(requirement_lines),
+                available_doc,
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"Changing the project's own requirements, or upgrading the "),
+                    alloc.reflow(r"packages that lead to the others, may resolve the conflict."),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "PACKAGE VERSION CONFLICT".to_string(),
+                severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
+            }
+        }
+        ManifestProblem::LockedHashMismatch {
+            name,
+            url,
+            locked_hash,
+        } => {
+            let doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow(r"The lockfile says that the "),
+                    alloc.string(name).annotate(Annotation::Emphasized),
+                    alloc.reflow(r" package has this content hash:"),
+                ]),
+                alloc.string(locked_hash).indent(4),
+                alloc.reflow(
+                    r"But the manifest locates it at this URL, which has a different hash:",
+                ),
+                alloc.string(url).annotate(Annotation::Url).indent(4),
+                alloc.concat([
+                    alloc.reflow(r"I use the content hash to detect if a package might have "),
+                    alloc.reflow(r"been tampered with, so one of these files must have been "),
+                    alloc.reflow(r"edited by hand."),
+                ]),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(
+                        r"If the URL is the one you want, remove the package's entry from ",
+                    ),
+                    alloc.keyword(LOCKFILE_NAME),
+                    alloc.reflow(r" and I will lock it again."),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "LOCKFILE HASH MISMATCH".to_string(),
+                severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
     }
@@ -1635,6 +2304,8 @@ pub fn to_file_problem_report<'b>(
                 doc,
                 title: "FILE NOT FOUND".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         io::ErrorKind::PermissionDenied => {
@@ -1653,6 +2324,8 @@ pub fn to_file_problem_report<'b>(
                 doc,
                 title: "FILE PERMISSION DENIED".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         io::ErrorKind::Unsupported => {
@@ -1689,6 +2362,8 @@ pub fn to_file_problem_report<'b>(
                 doc,
                 title: "NOT A ROC FILE".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
         _ => {
@@ -1709,6 +2384,8 @@ pub fn to_file_problem_report<'b>(
                 doc,
                 title: "FILE PROBLEM".to_string(),
                 severity: Severity::Fatal,
+                fixes: Vec::new(),
+                code: None,
             }
         }
     }
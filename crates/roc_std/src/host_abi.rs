@@ -0,0 +1,116 @@
+//! The hooks a host can hand to Roc code for a run, in place of the `roc_alloc`, `roc_realloc`,
+//! `roc_dealloc`, `roc_panic`, and `roc_dbg` symbols it links in.
+//!
+//! This lets a host give each run its own allocator, for example an arena that is dropped
+//! afterwards, or an allocator that counts allocations to find leaks under valgrind.
+use core::ffi::c_void;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::RocStr;
+
+/// The version of [`RocHostAbi`] this crate was written against. Roc's builtins check it when
+/// the hooks are installed, and this must be bumped whenever the layout of [`RocHostAbi`] changes,
+/// together with the version at the end of the `roc_set_host_abi_v1` symbol below.
+pub const ROC_HOST_ABI_VERSION: u32 = 1;
+
+/// Every hook gets `context` as its first argument, e.g. a pointer to the arena to allocate in.
+#[repr(C)]
+pub struct RocHostAbi {
+    pub version: u32,
+    pub context: *mut c_void,
+    pub alloc:
+        unsafe extern "C" fn(context: *mut c_void, size: usize, alignment: u32) -> *mut c_void,
+    pub realloc: unsafe extern "C" fn(
+        context: *mut c_void,
+        ptr: *mut c_void,
+        new_size: usize,
+        old_size: usize,
+        alignment: u32,
+    ) -> *mut c_void,
+    pub dealloc: unsafe extern "C" fn(context: *mut c_void, ptr: *mut c_void, alignment: u32),
+    pub panic: unsafe extern "C" fn(context: *mut c_void, message: &RocStr, tag_id: u32) -> !,
+    pub dbg:
+        unsafe extern "C" fn(context: *mut c_void, loc: &RocStr, message: &RocStr, src: &RocStr),
+}
+
+extern "C" {
+    /// Provided by the compiled Roc code. Returns the version of [`RocHostAbi`] it was compiled
+    /// against, and only installs `abi` if that matches its `version`.
+    ///
+    /// The version is part of the symbol name, so a host built against another version of the
+    /// hooks fails to link, rather than finding out at startup.
+    #[link_name = "roc_set_host_abi_v1"]
+    fn roc_set_host_abi(abi: *const RocHostAbi) -> u32;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HostAbiMismatch {
+    /// The version the compiled Roc code expects
+    pub expected: u32,
+    pub found: u32,
+}
+
+static INSTALLED: AtomicPtr<RocHostAbi> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Makes the compiled Roc code, and the Roc values this crate allocates, use `abi` until
+/// [`uninstall`] is called.
+///
+/// # Safety
+///
+/// Values allocated with one set of hooks must not be freed after switching to another, so this
+/// must not be called while Roc values from an earlier run are still alive.
+pub unsafe fn install(abi: &'static RocHostAbi) -> Result<(), HostAbiMismatch> {
+    let expected = roc_set_host_abi(abi);
+
+    if expected != abi.version {
+        return Err(HostAbiMismatch {
+            expected,
+            found: abi.version,
+        });
+    }
+
+    INSTALLED.store(abi as *const _ as *mut _, Ordering::Release);
+
+    Ok(())
+}
+
+/// Goes back to the `roc_alloc`, `roc_realloc`, `roc_dealloc`, `roc_panic`, and `roc_dbg` symbols.
+///
+/// # Safety
+///
+/// The same as for [`install`].
+pub unsafe fn uninstall() {
+    roc_set_host_abi(core::ptr::null());
+
+    INSTALLED.store(core::ptr::null_mut(), Ordering::Release);
+}
+
+fn installed() -> Option<&'static RocHostAbi> {
+    unsafe { INSTALLED.load(Ordering::Acquire).as_ref() }
+}
+
+pub(crate) unsafe fn alloc(size: usize, alignment: u32) -> *mut c_void {
+    match installed() {
+        Some(abi) => (abi.alloc)(abi.context, size, alignment),
+        None => crate::roc_alloc(size, alignment),
+    }
+}
+
+pub(crate) unsafe fn realloc(
+    ptr: *mut c_void,
+    new_size: usize,
+    old_size: usize,
+    alignment: u32,
+) -> *mut c_void {
+    match installed() {
+        Some(abi) => (abi.realloc)(abi.context, ptr, new_size, old_size, alignment),
+        None => crate::roc_realloc(ptr, new_size, old_size, alignment),
+    }
+}
+
+pub(crate) unsafe fn dealloc(ptr: *mut c_void, alignment: u32) {
+    match installed() {
+        Some(abi) => (abi.dealloc)(abi.context, ptr, alignment),
+        None => crate::roc_dealloc(ptr, alignment),
+    }
+}
@@ -11,6 +11,7 @@ use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ops::Drop;
 use core::str;
 
+pub mod host_abi;
 mod roc_box;
 mod roc_dict;
 mod roc_list;
@@ -53,7 +54,7 @@ fn roc_alloc_refcounted_help(mut size: usize, mut align: usize) -> *mut u8 {
     align = align.max(core::mem::size_of::<crate::Storage>());
 
     unsafe {
-        let allocation_ptr = roc_alloc(size, align as _) as *mut u8;
+        let allocation_ptr = host_abi::alloc(size, align as _) as *mut u8;
         let data_ptr = allocation_ptr.add(prefix);
         let storage_ptr = (data_ptr as *mut crate::Storage).sub(1);
 
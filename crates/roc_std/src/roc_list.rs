@@ -14,7 +14,7 @@ use core::{
 };
 use std::ops::Range;
 
-use crate::{roc_alloc, roc_dealloc, roc_realloc, storage::Storage};
+use crate::{host_abi, storage::Storage};
 
 #[cfg(feature = "serde")]
 use core::marker::PhantomData;
@@ -71,7 +71,8 @@ impl<T> RocList<T> {
     }
 
     fn elems_with_capacity(num_elems: usize) -> NonNull<ManuallyDrop<T>> {
-        let alloc_ptr = unsafe { roc_alloc(Self::alloc_bytes(num_elems), Self::alloc_alignment()) };
+        let alloc_ptr =
+            unsafe { host_abi::alloc(Self::alloc_bytes(num_elems), Self::alloc_alignment()) };
 
         Self::elems_from_allocation(NonNull::new(alloc_ptr).unwrap_or_else(|| {
             todo!("Call roc_panic with the info that an allocation failed.");
@@ -310,7 +311,7 @@ where
                     // There wasn't enough capacity, so we need a new allocation.
                     // Since this is a unique RocList, we can use realloc here.
                     let new_ptr = unsafe {
-                        roc_realloc(
+                        host_abi::realloc(
                             storage.as_ptr().cast(),
                             Self::alloc_bytes(new_len),
                             Self::alloc_bytes(self.capacity()),
@@ -418,7 +419,7 @@ impl<T> RocList<T> {
                         let old_alloc = self.ptr_to_allocation();
 
                         // Try to reallocate in-place.
-                        let new_alloc = roc_realloc(
+                        let new_alloc = host_abi::realloc(
                             old_alloc,
                             Self::alloc_bytes(new_len),
                             Self::alloc_bytes(self.capacity()),
@@ -464,7 +465,10 @@ impl<T> RocList<T> {
                             // The new allocation is referencing them, so instead of incrementing them all
                             // all just to decrement them again here, we neither increment nor decrement them.
                             unsafe {
-                                roc_dealloc(self.ptr_to_allocation(), Self::alloc_alignment());
+                                host_abi::dealloc(
+                                    self.ptr_to_allocation(),
+                                    Self::alloc_alignment(),
+                                );
                             }
                         } else {
                             // Write the storage back.
@@ -637,7 +641,7 @@ impl<T> Drop for RocList<T> {
                         }
 
                         // Release the memory.
-                        roc_dealloc(self.ptr_to_allocation(), Self::alloc_alignment());
+                        host_abi::dealloc(self.ptr_to_allocation(), Self::alloc_alignment());
                     }
                 } else {
                     // Write the storage back.
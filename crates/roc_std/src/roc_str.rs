@@ -21,7 +21,7 @@ use core::{
 use std::ffi::{CStr, CString};
 use std::{ops::Range, ptr::NonNull};
 
-use crate::{roc_realloc, RocList};
+use crate::{host_abi, RocList};
 
 #[repr(transparent)]
 pub struct RocStr(RocStrInner);
@@ -30,7 +30,6 @@ fn with_stack_bytes<F, E, T>(length: usize, closure: F) -> T
 where
     F: FnOnce(*mut E) -> T,
 {
-    use crate::{roc_alloc, roc_dealloc};
     use core::mem::MaybeUninit;
 
     if length < RocStr::TEMP_STR_MAX_STACK_BYTES {
@@ -44,11 +43,11 @@ where
         let align = core::mem::align_of::<E>() as u32;
         // The string is too long to stack-allocate, so
         // do a heap allocation and then free it afterwards.
-        let ptr = unsafe { roc_alloc(length, align) } as *mut E;
+        let ptr = unsafe { host_abi::alloc(length, align) } as *mut E;
         let answer = closure(ptr);
 
         // Free the heap allocation.
-        unsafe { roc_dealloc(ptr.cast(), align) };
+        unsafe { host_abi::dealloc(ptr.cast(), align) };
 
         answer
     }
@@ -587,7 +586,7 @@ impl RocStr {
                             // it tries to use the refcount - which we just overwrote
                             // with string bytes.
                             mem::forget(self);
-                            crate::roc_dealloc(ptr.cast(), mem::align_of::<E>() as u32);
+                            host_abi::dealloc(ptr.cast(), mem::align_of::<E>() as u32);
 
                             answer
                         } else {
@@ -895,7 +894,7 @@ impl BigString {
                 }
                 isize::MIN => {
                     // refcount becomes zero; free allocation
-                    crate::roc_dealloc(self.ptr_to_allocation().cast(), 1);
+                    host_abi::dealloc(self.ptr_to_allocation().cast(), 1);
                 }
                 _ => {
                     std::ptr::write(ptr, (value - 1) as usize);
@@ -930,7 +929,7 @@ impl BigString {
             }
 
             let new_alloc = unsafe {
-                roc_realloc(
+                host_abi::realloc(
                     self.ptr_to_allocation().cast(),
                     desired_alloc as _,
                     align + self.capacity(),
@@ -949,7 +948,7 @@ impl BigString {
             std::mem::swap(&mut this, self);
             std::mem::forget(this);
         } else {
-            let ptr = unsafe { crate::roc_alloc(desired_alloc, align as _) } as *mut u8;
+            let ptr = unsafe { host_abi::alloc(desired_alloc, align as _) } as *mut u8;
             let elements = unsafe { NonNull::new_unchecked(ptr.cast::<u8>().add(align)) };
 
             unsafe {
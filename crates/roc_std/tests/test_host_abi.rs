@@ -0,0 +1,125 @@
+#![allow(clippy::missing_safety_doc)]
+
+use core::ffi::c_void;
+use core::sync::atomic::{AtomicIsize, Ordering};
+use roc_std::host_abi::{self, HostAbiMismatch, RocHostAbi, ROC_HOST_ABI_VERSION};
+use roc_std::{RocList, RocStr};
+
+#[no_mangle]
+pub unsafe extern "C" fn roc_alloc(size: usize, _alignment: u32) -> *mut c_void {
+    libc::malloc(size)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn roc_realloc(
+    c_ptr: *mut c_void,
+    new_size: usize,
+    _old_size: usize,
+    _alignment: u32,
+) -> *mut c_void {
+    libc::realloc(c_ptr, new_size)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn roc_dealloc(c_ptr: *mut c_void, _alignment: u32) {
+    libc::free(c_ptr)
+}
+
+/// Stands in for the one in Roc's builtins.
+#[export_name = "roc_set_host_abi_v1"]
+pub unsafe extern "C" fn roc_set_host_abi(_abi: *const RocHostAbi) -> u32 {
+    ROC_HOST_ABI_VERSION
+}
+
+/// The number of live allocations, if `context` points to one of these.
+struct Tracker {
+    live: AtomicIsize,
+}
+
+unsafe extern "C" fn tracking_alloc(
+    context: *mut c_void,
+    size: usize,
+    _alignment: u32,
+) -> *mut c_void {
+    (*context.cast::<Tracker>())
+        .live
+        .fetch_add(1, Ordering::SeqCst);
+
+    libc::malloc(size)
+}
+
+unsafe extern "C" fn tracking_realloc(
+    _context: *mut c_void,
+    ptr: *mut c_void,
+    new_size: usize,
+    _old_size: usize,
+    _alignment: u32,
+) -> *mut c_void {
+    libc::realloc(ptr, new_size)
+}
+
+unsafe extern "C" fn tracking_dealloc(context: *mut c_void, ptr: *mut c_void, _alignment: u32) {
+    (*context.cast::<Tracker>())
+        .live
+        .fetch_sub(1, Ordering::SeqCst);
+
+    libc::free(ptr)
+}
+
+unsafe extern "C" fn test_panic(_context: *mut c_void, message: &RocStr, _tag_id: u32) -> ! {
+    panic!("roc_panic during test: {message}")
+}
+
+unsafe extern "C" fn test_dbg(_context: *mut c_void, loc: &RocStr, message: &RocStr, src: &RocStr) {
+    eprintln!("[{loc}] {src} = {message}");
+}
+
+fn leak(abi: RocHostAbi) -> &'static RocHostAbi {
+    Box::leak(Box::new(abi))
+}
+
+fn tracking_abi(version: u32, tracker: &'static Tracker) -> &'static RocHostAbi {
+    leak(RocHostAbi {
+        version,
+        context: tracker as *const Tracker as *mut c_void,
+        alloc: tracking_alloc,
+        realloc: tracking_realloc,
+        dealloc: tracking_dealloc,
+        panic: test_panic,
+        dbg: test_dbg,
+    })
+}
+
+// One test, since the installed hooks are global to the process.
+#[test]
+fn installed_allocator_sees_every_allocation() {
+    let tracker: &'static Tracker = Box::leak(Box::new(Tracker {
+        live: AtomicIsize::new(0),
+    }));
+
+    let stale = tracking_abi(ROC_HOST_ABI_VERSION + 1, tracker);
+    assert_eq!(
+        unsafe { host_abi::install(stale) },
+        Err(HostAbiMismatch {
+            expected: ROC_HOST_ABI_VERSION,
+            found: ROC_HOST_ABI_VERSION + 1,
+        })
+    );
+
+    unsafe { host_abi::install(tracking_abi(ROC_HOST_ABI_VERSION, tracker)) }.unwrap();
+
+    {
+        let string = RocStr::from("a string too long to be stored inline");
+        let list = RocList::from_slice(&[string.clone(), string]);
+        let _copy = list.clone();
+
+        assert!(tracker.live.load(Ordering::SeqCst) > 0);
+    }
+
+    assert_eq!(tracker.live.load(Ordering::SeqCst), 0);
+
+    unsafe { host_abi::uninstall() };
+
+    drop(RocStr::from("allocated with roc_alloc after uninstalling"));
+    assert_eq!(tracker.live.load(Ordering::SeqCst), 0);
+}
@@ -40,7 +40,6 @@ GuiFormatter := { nodes : List Elem }
             f32: f32,
             f64: f64,
             dec: dec,
-
         },
     ]
 